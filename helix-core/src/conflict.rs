@@ -0,0 +1,151 @@
+//! Detection of the conflict markers a merge leaves in a file.
+
+use std::ops::Range;
+
+use crate::RopeSlice;
+
+/// A conflict between two versions of a part of a file, delimited by conflict markers:
+///
+/// ```text
+/// <<<<<<< ours
+/// our version
+/// ||||||| base
+/// the version both are based on (only with the diff3 conflict style)
+/// =======
+/// their version
+/// >>>>>>> theirs
+/// ```
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Conflict {
+    /// Line of the `<<<<<<<` marker.
+    pub start: usize,
+    /// Line of the `|||||||` marker.
+    pub base: Option<usize>,
+    /// Line of the `=======` marker.
+    pub separator: usize,
+    /// Line of the `>>>>>>>` marker.
+    pub end: usize,
+}
+
+impl Conflict {
+    /// Lines of our version.
+    pub fn ours(&self) -> Range<usize> {
+        self.start + 1..self.base.unwrap_or(self.separator)
+    }
+
+    /// Lines of the common ancestor, if the file contains it.
+    pub fn base(&self) -> Option<Range<usize>> {
+        self.base.map(|base| base + 1..self.separator)
+    }
+
+    /// Lines of their version.
+    pub fn theirs(&self) -> Range<usize> {
+        self.separator + 1..self.end
+    }
+
+    /// All lines of the conflict, including the markers.
+    pub fn lines(&self) -> Range<usize> {
+        self.start..self.end + 1
+    }
+
+    /// Whether `line` contains one of the conflict markers.
+    pub fn is_marker(&self, line: usize) -> bool {
+        line == self.start || line == self.separator || line == self.end || Some(line) == self.base
+    }
+}
+
+/// Whether `line` starts with a conflict marker made of `marker`.
+fn is_marker(line: RopeSlice, marker: char) -> bool {
+    let mut chars = line.chars();
+    (&mut chars).take(7).filter(|&ch| ch == marker).count() == 7
+        && chars.next().map_or(true, char::is_whitespace)
+}
+
+/// Finds the conflicts in `text`, ordered by their position. Incomplete conflicts are ignored.
+pub fn find_conflicts(text: RopeSlice) -> Vec<Conflict> {
+    let mut conflicts = Vec::new();
+    // the markers of the conflict that is currently parsed
+    let mut start = None;
+    let mut base = None;
+    let mut separator = None;
+
+    for (line_idx, line) in text.lines().enumerate() {
+        match line.get_char(0) {
+            Some('<') if is_marker(line, '<') => {
+                start = Some(line_idx);
+                base = None;
+                separator = None;
+            }
+            Some('|') if start.is_some() && base.is_none() && separator.is_none() => {
+                if is_marker(line, '|') {
+                    base = Some(line_idx);
+                }
+            }
+            Some('=') if start.is_some() && separator.is_none() => {
+                if is_marker(line, '=') {
+                    separator = Some(line_idx);
+                }
+            }
+            Some('>') if separator.is_some() && is_marker(line, '>') => {
+                conflicts.push(Conflict {
+                    start: start.take().unwrap(),
+                    base: base.take(),
+                    separator: separator.take().unwrap(),
+                    end: line_idx,
+                });
+            }
+            _ => (),
+        }
+    }
+    conflicts
+}
+
+/// Returns the conflict containing `line`, if any.
+pub fn conflict_at(conflicts: &[Conflict], line: usize) -> Option<&Conflict> {
+    conflicts
+        .iter()
+        .find(|conflict| conflict.lines().contains(&line))
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Rope;
+
+    #[test]
+    fn test_find_conflicts() {
+        let text = Rope::from(
+            "a\n<<<<<<< HEAD\nb\n=======\nc\n>>>>>>> branch\nd\n\
+             <<<<<<< HEAD\ne\n||||||| base\nf\n=======\n>>>>>>> branch\n",
+        );
+        let conflicts = find_conflicts(text.slice(..));
+        assert_eq!(
+            conflicts,
+            vec![
+                Conflict {
+                    start: 1,
+                    base: None,
+                    separator: 3,
+                    end: 5,
+                },
+                Conflict {
+                    start: 7,
+                    base: Some(9),
+                    separator: 11,
+                    end: 12,
+                },
+            ]
+        );
+        assert_eq!(conflicts[0].ours(), 2..3);
+        assert_eq!(conflicts[0].theirs(), 4..5);
+        assert_eq!(conflicts[1].base(), Some(10..11));
+        assert!(conflicts[1].theirs().is_empty());
+    }
+
+    #[test]
+    fn test_find_conflicts_ignores_other_lines() {
+        // too few marker characters, a heading underline and an incomplete conflict
+        let text = Rope::from("<<<<<< a\n=======\n>>>>>>> b\nTitle\n=========\n<<<<<<< c\nd\n");
+        assert_eq!(find_conflicts(text.slice(..)), Vec::new());
+    }
+}
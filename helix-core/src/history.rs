@@ -119,6 +119,26 @@ pub const fn at_root(&self) -> bool {
         self.current == 0
     }
 
+    /// The number of revisions, including the root one.
+    pub fn revision_count(&self) -> usize {
+        self.revisions.len()
+    }
+
+    /// The revision `revision` was committed on, `None` for the root one.
+    pub fn parent(&self, revision: usize) -> Option<usize> {
+        (revision != 0).then(|| self.revisions[revision].parent)
+    }
+
+    /// When `revision` was committed.
+    pub fn timestamp(&self, revision: usize) -> Instant {
+        self.revisions[revision].timestamp
+    }
+
+    /// The changes of `revision` to the text of its parent.
+    pub fn transaction(&self, revision: usize) -> &Transaction {
+        &self.revisions[revision].transaction
+    }
+
     /// Returns the changes since the given revision composed into a transaction.
     /// Returns None if there are no changes between the current and given revisions.
     pub fn changes_since(&self, revision: usize) -> Option<Transaction> {
@@ -209,12 +229,12 @@ fn path_up(&self, mut n: usize, a: usize) -> Vec<usize> {
         path
     }
 
-    /// Create a [`Transaction`] that will jump to a specific revision in the history.
-    fn jump_to(&mut self, to: usize) -> Vec<Transaction> {
+    /// The transactions turning the text of the current revision into the one of `to`,
+    /// without moving to it.
+    pub fn path_to(&self, to: usize) -> Vec<Transaction> {
         let lca = self.lowest_common_ancestor(self.current, to);
         let up = self.path_up(self.current, lca);
         let down = self.path_up(to, lca);
-        self.current = to;
         let up_txns = up.iter().map(|&n| self.revisions[n].inversion.clone());
         let down_txns = down
             .iter()
@@ -223,6 +243,13 @@ fn jump_to(&mut self, to: usize) -> Vec<Transaction> {
         up_txns.chain(down_txns).collect()
     }
 
+    /// Create a [`Transaction`] that will jump to a specific revision in the history.
+    pub fn jump_to(&mut self, to: usize) -> Vec<Transaction> {
+        let txns = self.path_to(to);
+        self.current = to;
+        txns
+    }
+
     /// Creates a [`Transaction`] that will undo `delta` revisions.
     fn jump_backward(&mut self, delta: usize) -> Vec<Transaction> {
         self.jump_to(self.current.saturating_sub(delta))
@@ -630,4 +657,39 @@ fn test_parse_undo_kind() {
             Err("duration too large".to_string())
         );
     }
+
+    #[test]
+    fn test_jump_to_branch() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("a"),
+            selection: Selection::point(0),
+        };
+        let commit = |history: &mut History, state: &mut State, text: &str| {
+            let len = state.doc.len_chars();
+            let transaction =
+                Transaction::change(&state.doc, vec![(len, len, Some(text.into()))].into_iter());
+            history.commit_revision(&transaction, state);
+            transaction.apply(&mut state.doc);
+        };
+
+        commit(&mut history, &mut state, "b");
+        commit(&mut history, &mut state, "c");
+        for transaction in history.jump_to(1) {
+            transaction.apply(&mut state.doc);
+        }
+        // a branch off the first edit
+        commit(&mut history, &mut state, "d");
+        assert_eq!("abd", state.doc);
+        assert_eq!(history.revision_count(), 4);
+        assert_eq!(history.parent(3), Some(1));
+        assert_eq!(history.parent(0), None);
+
+        let mut text = state.doc.clone();
+        for transaction in history.path_to(2) {
+            transaction.apply(&mut text);
+        }
+        assert_eq!("abc", text);
+        assert_eq!(history.current_revision(), 3);
+    }
 }
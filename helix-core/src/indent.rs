@@ -56,6 +56,15 @@ pub fn as_str(&self) -> &'static str {
             }
         }
     }
+
+    /// Returns the number of columns a single level of indentation occupies.
+    #[inline]
+    pub fn indent_width(&self, tab_width: usize) -> usize {
+        match *self {
+            IndentStyle::Tabs => tab_width,
+            IndentStyle::Spaces(_) => self.as_str().len(),
+        }
+    }
 }
 
 /// Attempts to detect the indentation style used in a document.
@@ -4,6 +4,7 @@
 pub mod chars;
 pub mod comment;
 pub mod config;
+pub mod conflict;
 pub mod diagnostic;
 pub mod diff;
 pub mod graphemes;
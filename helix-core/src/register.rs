@@ -67,6 +67,10 @@ pub fn push(&mut self, name: char, value: String) {
         }
     }
 
+    pub fn remove(&mut self, name: char) -> Option<Register> {
+        self.inner.remove(&name)
+    }
+
     pub fn first(&self, name: char) -> Option<&String> {
         self.read(name).and_then(|entries| entries.first())
     }
@@ -126,6 +126,8 @@ pub struct LanguageConfiguration {
     pub auto_pairs: Option<AutoPairs>,
 
     pub rulers: Option<Vec<u16>>, // if set, override editor's rulers
+    pub inlay_hints: Option<bool>, // if set, override editor's lsp.display-inlay-hints
+    pub auto_save: Option<bool>,   // if false, override editor's auto-save
 }
 
 #[derive(Debug, PartialEq, Eq, Hash)]
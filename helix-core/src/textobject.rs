@@ -276,6 +276,42 @@ pub fn textobject_treesitter(
     get_range().unwrap_or(range)
 }
 
+/// Returns the first lines of the tree-sitter text objects named `object_names`
+/// (like "function" or "class") that enclose `line` and start above it, ordered
+/// from the outermost to the innermost object.
+pub fn textobject_context_lines(
+    slice: RopeSlice,
+    line: usize,
+    object_names: &[&str],
+    slice_tree: Node,
+    lang_config: &LanguageConfiguration,
+) -> Vec<usize> {
+    let query = match lang_config.textobject_query() {
+        Some(query) => query,
+        None => return Vec::new(),
+    };
+    let byte_pos = slice.line_to_byte(line);
+
+    let mut lines = Vec::new();
+    for object_name in object_names {
+        let capture_name = format!("{}.around", object_name);
+        let mut cursor = QueryCursor::new();
+        // only objects overlapping the line are of interest
+        cursor.set_byte_range(byte_pos..byte_pos + 1);
+        if let Some(nodes) = query.capture_nodes(&capture_name, slice_tree, slice, &mut cursor) {
+            lines.extend(
+                nodes
+                    .filter(|node| node.byte_range().contains(&byte_pos))
+                    .map(|node| slice.byte_to_line(node.start_byte()))
+                    .filter(|&start| start < line),
+            );
+        }
+    }
+    lines.sort_unstable();
+    lines.dedup();
+    lines
+}
+
 #[cfg(test)]
 mod test {
     use super::TextObject::*;
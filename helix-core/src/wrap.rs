@@ -1,7 +1,211 @@
+use std::borrow::Cow;
+
 use smartstring::{LazyCompact, SmartString};
 
+use crate::{
+    graphemes::{ensure_grapheme_boundary_prev, grapheme_width, RopeGraphemes},
+    line_ending::get_line_ending,
+    Position, RopeSlice,
+};
+
 /// Given a slice of text, return the text re-wrapped to fit it
 /// within the given width.
 pub fn reflow_hard_wrap(text: &str, max_line_len: usize) -> SmartString<LazyCompact> {
     textwrap::refill(text, max_line_len).into()
 }
+
+/// Lays out a single line soft wrapped at `width` columns, calling `f` with the
+/// char offset (relative to the start of the line) and the visual position of
+/// every grapheme. The end of the line is laid out as a final single column cell
+/// so that a cursor placed on it is always visible. Continuation rows start at
+/// column `indent`, which leaves room for a wrap indicator. Stops early if `f`
+/// returns `false`.
+fn soft_wrap_layout<F>(line: RopeSlice, tab_width: usize, width: usize, indent: usize, mut f: F)
+where
+    F: FnMut(usize, Position) -> bool,
+{
+    let width = width.max(1);
+    // An indicator that doesn't leave any room for text is not drawn.
+    let indent = if indent < width { indent } else { 0 };
+    let end = line.len_chars() - get_line_ending(&line).map_or(0, |ending| ending.len_chars());
+
+    let grapheme_width_at = |grapheme: &Cow<str>, col: usize| {
+        if grapheme == "\t" {
+            tab_width - (col % tab_width)
+        } else {
+            grapheme_width(grapheme)
+        }
+    };
+
+    let mut row = 0;
+    let mut col = 0;
+    let mut row_start = 0;
+    let mut char_offset = 0;
+
+    for grapheme in RopeGraphemes::new(line.slice(..end)) {
+        let chars = grapheme.len_chars();
+        let grapheme = Cow::from(grapheme);
+        let mut grapheme_width = grapheme_width_at(&grapheme, col);
+
+        // Wrap unless the grapheme is the first on its row: a grapheme wider than
+        // the whole view would never fit otherwise.
+        if col + grapheme_width > width && col > row_start {
+            row += 1;
+            col = indent;
+            row_start = indent;
+            grapheme_width = grapheme_width_at(&grapheme, col);
+        }
+
+        if !f(char_offset, Position::new(row, col)) {
+            return;
+        }
+
+        col += grapheme_width;
+        char_offset += chars;
+    }
+
+    if col >= width && col > row_start {
+        row += 1;
+        col = indent;
+    }
+    f(char_offset, Position::new(row, col));
+}
+
+/// Returns the number of visual rows `line` takes up when soft wrapped at `width`
+/// columns with continuation rows starting at column `indent`.
+pub fn soft_wrap_line_height(
+    line: RopeSlice,
+    tab_width: usize,
+    width: usize,
+    indent: usize,
+) -> usize {
+    let mut rows = 0;
+    soft_wrap_layout(line, tab_width, width, indent, |_, pos| {
+        rows = pos.row;
+        true
+    });
+    rows + 1
+}
+
+/// Convert a character index to visual coordinates within its soft wrapped line.
+///
+/// The returned row is relative to the first row of the line the position is on.
+/// See [`crate::visual_coords_at_pos`] for the unwrapped equivalent.
+pub fn soft_wrap_coords_at_pos(
+    text: RopeSlice,
+    pos: usize,
+    tab_width: usize,
+    width: usize,
+    indent: usize,
+) -> Position {
+    let line = text.char_to_line(pos);
+    let line_start = text.line_to_char(line);
+    let offset = ensure_grapheme_boundary_prev(text, pos) - line_start;
+
+    let mut coords = Position::default();
+    soft_wrap_layout(
+        text.line(line),
+        tab_width,
+        width,
+        indent,
+        |char_offset, pos| {
+            if char_offset > offset {
+                return false;
+            }
+            coords = pos;
+            true
+        },
+    );
+    coords
+}
+
+/// Convert visual coordinates within the soft wrapped `line` to a character index.
+///
+/// If `coords` are before the start of a row (on the wrap indicator), the first
+/// grapheme on that row is returned. If they are past the end of a row, the last
+/// grapheme on that row is returned. If they are below the last row, the line-end position (just before
+/// the line ending character) is returned.
+pub fn soft_wrap_pos_at_coords(
+    text: RopeSlice,
+    line: usize,
+    coords: Position,
+    tab_width: usize,
+    width: usize,
+    indent: usize,
+) -> usize {
+    let line = line.min(text.len_lines() - 1);
+    let line_start = text.line_to_char(line);
+
+    let mut offset = 0;
+    let mut on_row = false;
+    soft_wrap_layout(
+        text.line(line),
+        tab_width,
+        width,
+        indent,
+        |char_offset, pos| {
+            if pos.row > coords.row || (on_row && pos.col > coords.col) {
+                return false;
+            }
+            offset = char_offset;
+            on_row = pos.row == coords.row;
+            true
+        },
+    );
+    line_start + offset
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::Rope;
+
+    #[test]
+    fn test_soft_wrap_line_height() {
+        let text = Rope::from("hello world\n");
+        let line = text.slice(..).line(0);
+        assert_eq!(soft_wrap_line_height(line, 4, 20, 2), 1);
+        // 11 columns of text and the line end cell
+        assert_eq!(soft_wrap_line_height(line, 4, 12, 2), 1);
+        assert_eq!(soft_wrap_line_height(line, 4, 11, 2), 2);
+        // "hello " | "  worl" | "  d"
+        assert_eq!(soft_wrap_line_height(line, 4, 6, 2), 3);
+    }
+
+    #[test]
+    fn test_soft_wrap_coords_at_pos() {
+        let text = Rope::from("hello world\nfoo");
+        let slice = text.slice(..);
+        assert_eq!(soft_wrap_coords_at_pos(slice, 0, 4, 6, 2), (0, 0).into());
+        assert_eq!(soft_wrap_coords_at_pos(slice, 5, 4, 6, 2), (0, 5).into()); // space
+        assert_eq!(soft_wrap_coords_at_pos(slice, 6, 4, 6, 2), (1, 2).into()); // w
+        assert_eq!(soft_wrap_coords_at_pos(slice, 9, 4, 6, 2), (1, 5).into()); // l
+        assert_eq!(soft_wrap_coords_at_pos(slice, 10, 4, 6, 2), (2, 2).into()); // d
+        assert_eq!(soft_wrap_coords_at_pos(slice, 11, 4, 6, 2), (2, 3).into()); // \n
+        assert_eq!(soft_wrap_coords_at_pos(slice, 13, 4, 6, 2), (0, 1).into()); // o
+
+        // Wide characters are moved to the next row as a whole.
+        let text = Rope::from("今日はいい\n");
+        let slice = text.slice(..);
+        assert_eq!(soft_wrap_coords_at_pos(slice, 2, 4, 5, 0), (1, 0).into());
+    }
+
+    #[test]
+    fn test_soft_wrap_pos_at_coords() {
+        let text = Rope::from("hello world\nfoo");
+        let slice = text.slice(..);
+        assert_eq!(soft_wrap_pos_at_coords(slice, 0, (0, 0).into(), 4, 6, 2), 0);
+        assert_eq!(soft_wrap_pos_at_coords(slice, 0, (0, 9).into(), 4, 6, 2), 5);
+        // inside the wrap indicator
+        assert_eq!(soft_wrap_pos_at_coords(slice, 0, (1, 0).into(), 4, 6, 2), 6);
+        assert_eq!(soft_wrap_pos_at_coords(slice, 0, (1, 2).into(), 4, 6, 2), 6);
+        assert_eq!(
+            soft_wrap_pos_at_coords(slice, 0, (5, 0).into(), 4, 6, 2),
+            11
+        );
+        assert_eq!(
+            soft_wrap_pos_at_coords(slice, 1, (0, 2).into(), 4, 6, 2),
+            14
+        );
+    }
+}
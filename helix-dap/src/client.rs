@@ -420,6 +420,22 @@ pub async fn variables(&self, variables_reference: usize) -> Result<Vec<Variable
         Ok(response.variables)
     }
 
+    pub async fn set_variable(
+        &self,
+        variables_reference: usize,
+        name: String,
+        value: String,
+    ) -> Result<requests::SetVariableResponse> {
+        let args = requests::SetVariableArguments {
+            variables_reference,
+            name,
+            value,
+            format: None,
+        };
+
+        self.request::<requests::SetVariable>(args).await
+    }
+
     pub fn step_in(&self, thread_id: ThreadId) -> impl Future<Output = Result<Value>> {
         let args = requests::StepInArguments {
             thread_id,
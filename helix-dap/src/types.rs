@@ -552,6 +552,39 @@ impl Request for Variables {
         const COMMAND: &'static str = "variables";
     }
 
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetVariableArguments {
+        pub variables_reference: usize,
+        pub name: String,
+        pub value: String,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub format: Option<ValueFormat>,
+    }
+
+    #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
+    #[serde(rename_all = "camelCase")]
+    pub struct SetVariableResponse {
+        pub value: String,
+        #[serde(rename = "type", skip_serializing_if = "Option::is_none")]
+        pub ty: Option<String>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub variables_reference: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub named_variables: Option<usize>,
+        #[serde(skip_serializing_if = "Option::is_none")]
+        pub indexed_variables: Option<usize>,
+    }
+
+    #[derive(Debug)]
+    pub enum SetVariable {}
+
+    impl Request for SetVariable {
+        type Arguments = SetVariableArguments;
+        type Result = SetVariableResponse;
+        const COMMAND: &'static str = "setVariable";
+    }
+
     #[derive(Debug, PartialEq, Eq, Clone, Deserialize, Serialize)]
     #[serde(rename_all = "camelCase")]
     pub struct StepInArguments {
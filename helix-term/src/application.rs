@@ -11,20 +11,24 @@
     document::DocumentSavedEventResult,
     editor::{ConfigEvent, EditorEvent},
     graphics::Rect,
-    theme,
+    hooks::HookEvent,
+    recent::Recent,
+    session::{self, ExplorerSession, Session},
+    swap, theme,
     tree::Layout,
-    Align, Editor,
+    Align, DocumentId, Editor,
 };
 use serde_json::json;
 use tui::backend::Backend;
 
 use crate::{
     args::Args,
-    commands::apply_workspace_edit,
+    commands::{self, apply_workspace_edit, MappableCommand},
     compositor::{Compositor, Event},
     config::Config,
-    job::Jobs,
+    job::{Callback, Jobs},
     keymap::Keymaps,
+    remote,
     ui::{self, overlay::overlayed},
 };
 
@@ -83,6 +87,10 @@ pub struct Application {
     jobs: Jobs,
     lsp_progress: LspProgressMap,
     last_render: Instant,
+    /// The document of the focused view, to save it once another buffer is switched to.
+    focused_doc: Option<DocumentId>,
+    /// The socket listened on for the commands of `hx --remote`, when enabled.
+    remote: Option<remote::Server>,
 }
 
 #[cfg(feature = "integration")]
@@ -107,6 +115,35 @@ fn setup_integration_logging() {
         .apply();
 }
 
+/// The next command of a remote client, never ready without remote control.
+async fn next_remote_request(remote: &mut Option<remote::Server>) -> Option<remote::Request> {
+    match remote {
+        Some(server) => server.recv().await,
+        None => std::future::pending().await,
+    }
+}
+
+/// Restores the session of the working directory, if one was saved. Returns the explorer
+/// to open once the editor view exists.
+fn restore_session(editor: &mut Editor) -> Option<ExplorerSession> {
+    let path = session::session_file(&std::env::current_dir().ok()?);
+    if !path.exists() {
+        return None;
+    }
+    let restored = Session::load(&path).and_then(|session| {
+        session.restore(editor)?;
+        Ok(session)
+    });
+    match restored {
+        Ok(session) => session.explorer,
+        Err(err) => {
+            error!("{:#}", err);
+            editor.set_error(format!("{:#}", err));
+            None
+        }
+    }
+}
+
 fn restore_term() -> Result<(), Error> {
     let mut stdout = stdout();
     // reset cursor shape
@@ -176,13 +213,25 @@ pub fn new(
                 &config.editor
             })),
         );
+        editor.recent = Recent::load();
 
         let keys = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
             &config.keys
         }));
-        let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys)));
+        let mousemap = Box::new(Map::new(Arc::clone(&config), |config: &Config| {
+            &config.mouse
+        }));
+        let editor_view = Box::new(ui::EditorView::new(Keymaps::new(keys), mousemap));
         compositor.push(editor_view);
 
+        // the snapshots of the buffers of editors which did not exit are offered first
+        let snapshots = if config.load().editor.swap_files.enable {
+            swap::leftover_snapshots()
+        } else {
+            Vec::new()
+        };
+
+        let mut restored_explorer = None;
         if args.load_tutor {
             let path = helix_loader::runtime_dir().join("tutor");
             editor.open(&path, Action::VerticalSplit)?;
@@ -193,7 +242,7 @@ pub fn new(
             if first.is_dir() {
                 std::env::set_current_dir(first).context("set current dir")?;
                 editor.new_file(Action::VerticalSplit);
-                let picker = ui::file_picker(".".into(), &config.load().editor);
+                let picker = ui::file_picker(".".into(), &editor);
                 compositor.push(Box::new(overlayed(picker)));
             } else {
                 let nr_of_files = args.files.len();
@@ -238,7 +287,16 @@ pub fn new(
                 align_view(doc, view, Align::Center);
             }
         } else if stdin().is_tty() || cfg!(feature = "integration") {
-            editor.new_file(Action::VerticalSplit);
+            if config.load().editor.auto_session {
+                restored_explorer = restore_session(&mut editor);
+            }
+            if editor.tree.is_empty() {
+                editor.new_file(Action::VerticalSplit);
+                if config.load().editor.dashboard.enable && snapshots.is_empty() {
+                    let dashboard = ui::Dashboard::new(&mut editor);
+                    compositor.push(Box::new(dashboard));
+                }
+            }
         } else if cfg!(target_os = "macos") {
             // On Linux and Windows, we allow the output of a command to be piped into the new buffer.
             // This doesn't currently work on macOS because of the following issue:
@@ -252,12 +310,37 @@ pub fn new(
 
         editor.set_theme(theme);
 
+        // the last prompt pushed is the first one asked
+        for (swap_file, snapshot) in snapshots.into_iter().rev() {
+            let prompt = ui::swap_recovery_prompt(&editor, swap_file, snapshot);
+            compositor.push(Box::new(prompt));
+        }
+
         #[cfg(windows)]
         let signals = futures_util::stream::empty();
         #[cfg(not(windows))]
         let signals = Signals::new([signal::SIGTSTP, signal::SIGCONT, signal::SIGUSR1])
             .context("build signal handler")?;
 
+        if let Some(explorer) = restored_explorer {
+            if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                editor_view.restore_explorer(explorer);
+            }
+        }
+
+        let remote = if args.socket.is_some() || config.load().editor.remote_control {
+            let socket = args.socket.unwrap_or_else(remote::default_socket);
+            match remote::Server::bind(socket) {
+                Ok(server) => Some(server),
+                Err(err) => {
+                    editor.set_error(format!("Remote control is disabled: {}", err));
+                    None
+                }
+            }
+        } else {
+            None
+        };
+
         let app = Self {
             compositor,
             terminal,
@@ -272,6 +355,8 @@ pub fn new(
             jobs: Jobs::new(),
             lsp_progress: LspProgressMap::new(),
             last_render: Instant::now(),
+            focused_doc: None,
+            remote,
         };
 
         Ok(app)
@@ -337,6 +422,7 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
             if self.editor.should_close() {
                 return false;
             }
+            self.handle_buffer_switch();
 
             use futures_util::StreamExt;
 
@@ -357,6 +443,10 @@ pub async fn event_loop_until_idle<S>(&mut self, input_stream: &mut S) -> bool
                     self.jobs.handle_callback(&mut self.editor, &mut self.compositor, callback);
                     self.render().await;
                 }
+                Some(request) = next_remote_request(&mut self.remote) => {
+                    self.handle_remote_request(request);
+                    self.render().await;
+                }
                 event = self.editor.wait_event() => {
                     let _idle_handled = self.handle_editor_event(event).await;
 
@@ -510,6 +600,20 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
             }
         };
 
+        // the work tree may no longer be clean
+        self.editor
+            .vcs_status
+            .changed(vec![doc_save_event.path.clone()]);
+        self.editor
+            .emit(HookEvent::DocumentSaved(doc_save_event.path.clone()));
+        if let Some(editor_view) = self.compositor.find::<ui::EditorView>() {
+            let path = doc_save_event.path.clone();
+            if doc_save_event.created {
+                editor_view.explorer_command(ui::ExplorerCommand::Created(path.clone()));
+            }
+            editor_view.explorer_command(ui::ExplorerCommand::Changed(vec![path]));
+        }
+
         let doc = match self.editor.document_mut(doc_save_event.doc_id) {
             None => {
                 warn!(
@@ -529,6 +633,7 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
         );
 
         doc.set_last_saved_revision(doc_save_event.revision);
+        doc.set_disk_mtime(doc_save_event.mtime);
 
         let lines = doc_save_event.text.len_lines();
         let bytes = doc_save_event.text.len_bytes();
@@ -563,6 +668,175 @@ pub fn handle_document_write(&mut self, doc_save_event: DocumentSavedEventResult
         ));
     }
 
+    /// Reloads the buffers whose files were changed outside of the editor, or asks what
+    /// to do with the ones with unsaved changes. Returns whether any file changed.
+    fn handle_file_changes(&mut self) -> bool {
+        let changed = self.editor.documents_changed_on_disk();
+        // another program, like a formatter or a checkout, may have changed more files
+        if !changed.is_empty() {
+            self.editor.vcs_status.refresh();
+            let paths = changed
+                .iter()
+                .filter_map(|doc_id| doc!(self.editor, doc_id).path().cloned())
+                .collect();
+            if let Some(editor_view) = self.compositor.find::<ui::EditorView>() {
+                editor_view.explorer_command(ui::ExplorerCommand::Changed(paths));
+            }
+        }
+        let auto_reload = self.editor.config().file_watch.auto_reload;
+        for &doc_id in &changed {
+            let doc = doc!(self.editor, &doc_id);
+            if !auto_reload || doc.is_modified() {
+                let prompt = ui::file_changed_prompt(&self.editor, doc_id);
+                self.compositor.push(Box::new(prompt));
+                continue;
+            }
+            let name = doc
+                .path()
+                .map(|path| get_relative_path(path).to_string_lossy().into_owned())
+                .unwrap_or_default();
+            match self.editor.reload_document(doc_id) {
+                Ok(()) => self
+                    .editor
+                    .set_status(format!("'{}' reloaded, it changed on disk", name)),
+                Err(err) => self
+                    .editor
+                    .set_error(format!("Failed to reload '{}': {}", name, err)),
+            }
+        }
+        !changed.is_empty()
+    }
+
+    /// Saves the modified buffers automatically: `doc_id` or all of them.
+    fn handle_auto_save(&mut self, doc_id: Option<DocumentId>) {
+        let mut cx = crate::compositor::Context {
+            editor: &mut self.editor,
+            jobs: &mut self.jobs,
+            scroll: None,
+        };
+        if let Err(err) = commands::typed::auto_save_impl(&mut cx, doc_id) {
+            self.editor.set_error(err.to_string());
+        }
+    }
+
+    /// Saves the buffer left, if buffers are saved when switching to another one.
+    fn handle_buffer_switch(&mut self) {
+        let focused_doc = self
+            .editor
+            .tree
+            .try_get(self.editor.tree.focus)
+            .map(|view| view.doc);
+        let left = std::mem::replace(&mut self.focused_doc, focused_doc);
+        let doc_id = match left {
+            Some(doc_id) if left != focused_doc => doc_id,
+            _ => return,
+        };
+        if self.editor.config().auto_save.buffer_switch
+            && self.editor.documents.contains_key(&doc_id)
+        {
+            self.handle_auto_save(Some(doc_id));
+        }
+    }
+
+    /// Runs the command of a remote client in its working directory and answers it with
+    /// the result. The working directory of the editor is restored after, unless the
+    /// command changed it.
+    fn handle_remote_request(&mut self, request: remote::Request) {
+        let cwd = std::env::current_dir().ok();
+        if let Some(client_cwd) = &request.cwd {
+            if let Err(err) = std::env::set_current_dir(client_cwd) {
+                let err = format!("could not enter '{}': {}", client_cwd.display(), err);
+                let _ = request.reply.send(Err(err));
+                return;
+            }
+        }
+        let mut cx = crate::compositor::Context {
+            editor: &mut self.editor,
+            jobs: &mut self.jobs,
+            scroll: None,
+        };
+        let result = commands::typed::execute_command_line(&mut cx, &request.command)
+            .map_err(|err| err.to_string());
+        if let (Some(cwd), Some(client_cwd)) = (cwd, &request.cwd) {
+            if std::env::current_dir().ok().as_ref() == Some(client_cwd) {
+                let _ = std::env::set_current_dir(cwd);
+            }
+        }
+        let _ = request.reply.send(result);
+    }
+
+    /// Runs the hooks of the config matching `event`: their shell commands in the
+    /// background, whose failures are reported, and then their commands.
+    fn handle_hook_event(&mut self, event: HookEvent) {
+        let config = self.editor.config();
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let hooks: Vec<_> = config
+            .hooks
+            .iter()
+            .filter(|hook| hook.matches(&event, &cwd))
+            .cloned()
+            .collect();
+        let shell = config.shell.clone();
+        drop(config);
+
+        for hook in hooks {
+            if let Some(run) = hook.run {
+                let (program, args) = match shell.split_first() {
+                    Some(shell) => shell,
+                    None => {
+                        self.editor.set_error("No shell set");
+                        continue;
+                    }
+                };
+                let mut process = tokio::process::Command::new(program);
+                process
+                    .args(args)
+                    .arg(&run)
+                    .envs(event.env())
+                    .stdin(std::process::Stdio::null())
+                    .stdout(std::process::Stdio::null())
+                    .stderr(std::process::Stdio::piped());
+                self.jobs.callback(async move {
+                    let output = process.output().await?;
+                    let call = move |editor: &mut Editor| {
+                        if !output.status.success() {
+                            let stderr = String::from_utf8_lossy(&output.stderr);
+                            editor.set_error(format!("Hook `{}` failed: {}", run, stderr.trim()));
+                        }
+                    };
+                    Ok(Callback::Editor(Box::new(call)))
+                });
+            }
+            if let Some(command) = hook.command {
+                let command: MappableCommand = match command.parse() {
+                    Ok(command) => command,
+                    Err(err) => {
+                        self.editor
+                            .set_error(format!("Invalid hook command `{}`: {}", command, err));
+                        continue;
+                    }
+                };
+                let mut cx = commands::Context {
+                    register: None,
+                    count: None,
+                    editor: &mut self.editor,
+                    callback: None,
+                    on_next_key_callback: None,
+                    jobs: &mut self.jobs,
+                };
+                command.execute(&mut cx);
+                if let Some(callback) = cx.callback {
+                    let mut cx = crate::compositor::Context {
+                        editor: &mut self.editor,
+                        jobs: &mut self.jobs,
+                        scroll: None,
+                    };
+                    callback(&mut self.compositor, &mut cx);
+                }
+            }
+        }
+    }
+
     #[inline(always)]
     pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
         log::debug!("received editor event: {:?}", event);
@@ -576,6 +850,10 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
                 self.handle_config_events(event);
                 self.render().await;
             }
+            EditorEvent::Hook(event) => {
+                self.handle_hook_event(event);
+                self.render().await;
+            }
             EditorEvent::LanguageServerMessage((id, call)) => {
                 self.handle_language_server_message(call, id).await;
                 // limit render calls for fast language server messages
@@ -587,11 +865,47 @@ pub async fn handle_editor_event(&mut self, event: EditorEvent) -> bool {
                 }
             }
             EditorEvent::DebuggerEvent(payload) => {
+                let stopped = matches!(&payload, helix_dap::Payload::Event(event)
+                    if matches!(**event, helix_dap::Event::Stopped(_)));
                 let needs_render = self.editor.handle_debugger_message(payload).await;
+                // the variables listed are the ones of the previous stop
+                let id = ui::DebugVariables::ID;
+                let panel = self.compositor.find_id::<ui::DebugVariables>(id);
+                if let (true, Some(panel)) = (stopped, panel) {
+                    if let Err(err) = panel.refresh(&self.editor, &mut self.jobs) {
+                        self.editor.set_error(err.to_string());
+                    }
+                }
                 if needs_render {
                     self.render().await;
                 }
             }
+            EditorEvent::FileWatchTimer => {
+                if self.handle_file_changes() {
+                    self.render().await;
+                }
+            }
+            EditorEvent::DocumentLoaded => {
+                self.render().await;
+            }
+            EditorEvent::SwapTimer => self.editor.update_swaps(),
+            EditorEvent::AutoSaveTimer => {
+                self.handle_auto_save(None);
+                self.render().await;
+            }
+            EditorEvent::MouseHover(event) => {
+                let mut cx = crate::compositor::Context {
+                    editor: &mut self.editor,
+                    jobs: &mut self.jobs,
+                    scroll: None,
+                };
+                if self
+                    .compositor
+                    .handle_event(&Event::MouseHover(event), &mut cx)
+                {
+                    self.render().await;
+                }
+            }
             EditorEvent::IdleTimer => {
                 self.editor.clear_idle_timer();
                 self.handle_idle_timeout().await;
@@ -615,8 +929,28 @@ pub async fn handle_terminal_events(
             jobs: &mut self.jobs,
             scroll: None,
         };
+        let event = event.unwrap();
+        if matches!(event, CrosstermEvent::Key(_) | CrosstermEvent::Paste(_)) {
+            cx.editor.reset_auto_save_timer();
+        }
+        // the files may have been changed in another program in the meantime
+        if matches!(event, CrosstermEvent::FocusGained) {
+            cx.editor.vcs_status.refresh();
+        }
+        // moving the pointer hides the hover popup until it rests again
+        let mouse_moved = match event {
+            CrosstermEvent::Mouse(event)
+                if event.kind == crossterm::event::MouseEventKind::Moved =>
+            {
+                Some(event.into())
+            }
+            _ => None,
+        };
+        cx.editor.reset_mouse_hover(mouse_moved);
+        let hover_closed =
+            mouse_moved.is_some() && self.compositor.remove(ui::MOUSE_HOVER_ID).is_some();
         // Handle key events
-        let should_redraw = match event.unwrap() {
+        let should_redraw = match event {
             CrosstermEvent::Resize(width, height) => {
                 self.terminal
                     .resize(Rect::new(0, 0, width, height))
@@ -632,7 +966,7 @@ pub async fn handle_terminal_events(
             event => self.compositor.handle_event(&event.into(), &mut cx),
         };
 
-        if should_redraw && !self.editor.should_close() {
+        if (should_redraw || hover_closed) && !self.editor.should_close() {
             self.render().await;
         }
     }
@@ -1048,7 +1382,7 @@ async fn claim_term(&mut self) -> Result<(), Error> {
             EnableFocusChange
         )?;
         execute!(stdout, terminal::Clear(terminal::ClearType::All))?;
-        if self.config.load().editor.mouse {
+        if self.config.load().editor.mouse.is_captured() {
             execute!(stdout, EnableMouseCapture)?;
         }
         Ok(())
@@ -1105,10 +1439,27 @@ pub async fn close(&mut self) -> Vec<anyhow::Error> {
             errs.push(err);
         };
 
+        let config = self.editor.config();
+        let (dashboard, frecency) = (config.dashboard.enable, config.file_picker.frecency);
+        drop(config);
+        if dashboard || frecency {
+            // on top of the ones saved by the other editors meanwhile
+            let mut recent = Recent::load();
+            if dashboard {
+                recent.record(&self.editor);
+            }
+            recent.add_visits(&self.editor.recent);
+            if let Err(err) = recent.save() {
+                log::error!("Error saving recent files: {}", err);
+                errs.push(err);
+            }
+        }
+
         if let Err(err) = self.editor.flush_writes().await {
             log::error!("Error writing: {}", err);
             errs.push(err);
         }
+        self.editor.swaps.clear().await;
 
         if self.editor.close_language_servers(None).await.is_err() {
             log::error!("Timed out waiting for language servers to shutdown");
@@ -17,6 +17,10 @@ pub struct Args {
     pub log_file: Option<PathBuf>,
     pub config_file: Option<PathBuf>,
     pub files: Vec<(PathBuf, Position)>,
+    /// The socket of `--socket`, listened on or sent `remote` to.
+    pub socket: Option<PathBuf>,
+    /// The words of the command sent with `--remote` to a running editor.
+    pub remote: Option<Vec<String>>,
 }
 
 impl Args {
@@ -59,6 +63,17 @@ pub fn parse_args() -> Result<Args> {
                     Some(path) => args.log_file = Some(path.into()),
                     None => anyhow::bail!("--log must specify a path to write"),
                 },
+                "--socket" => match argv.next().as_deref() {
+                    Some(path) => args.socket = Some(path.into()),
+                    None => anyhow::bail!("--socket must specify the path of a socket"),
+                },
+                "--remote" => {
+                    let command: Vec<_> = argv.by_ref().collect();
+                    if command.is_empty() {
+                        anyhow::bail!("--remote must be followed by a command");
+                    }
+                    args.remote = Some(command);
+                }
                 arg if arg.starts_with("--") => {
                     anyhow::bail!("unexpected double dash argument: {}", arg)
                 }
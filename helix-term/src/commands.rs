@@ -3,13 +3,15 @@
 pub(crate) mod typed;
 
 pub use dap::*;
-use helix_vcs::Hunk;
+use helix_vcs::{DiffProviderRegistry, Hunk};
 pub use lsp::*;
-use tui::text::Spans;
+use tui::text::{Span, Spans};
 pub use typed::*;
 
 use helix_core::{
-    comment, coords_at_pos, encoding, find_first_non_whitespace_char, find_root, graphemes,
+    comment,
+    conflict::{self, Conflict},
+    coords_at_pos, encoding, find_first_non_whitespace_char, find_root, graphemes,
     history::UndoKind,
     increment::date_time::DateTimeIncrementor,
     increment::{number::NumberIncrementor, Increment},
@@ -31,7 +33,7 @@
     apply_transaction,
     clipboard::ClipboardType,
     document::{FormatterError, Mode, SCRATCH_BUFFER_NAME},
-    editor::{Action, Motion},
+    editor::{Action, CloseError, Motion},
     info::Info,
     input::KeyEvent,
     keyboard::KeyCode,
@@ -50,7 +52,7 @@
     compositor::{self, Component, Compositor},
     job::Callback,
     keymap::ReverseKeymap,
-    ui::{self, overlay::overlayed, FilePicker, Picker, Popup, Prompt, PromptEvent},
+    ui::{self, overlay::overlayed, FilePicker, Picker, Popup, Prompt, PromptEvent, TreePanel},
 };
 
 use crate::job::{self, Jobs};
@@ -272,11 +274,19 @@ pub fn doc(&self) -> &str {
         file_picker, "Open file picker",
         file_picker_in_current_directory, "Open file picker at current working directory",
         code_action, "Perform code action",
+        toggle_inlay_hints, "Toggle LSP inlay hints",
         buffer_picker, "Open buffer picker",
+        buffer_tree, "Open buffers panel grouped by directory",
+        buffer_menu, "Open menu of the current buffer",
         jumplist_picker, "Open jumplist picker",
+        jumplist_panel, "Open jumplist panel grouped by file",
         symbol_picker, "Open symbol picker",
+        breadcrumbs_picker, "Open picker of the scopes enclosing the cursor",
         select_references_to_symbol_under_cursor, "Select symbol references",
         workspace_symbol_picker, "Open workspace symbol picker",
+        workspace_symbol_tree, "Open workspace symbol tree panel",
+        document_outline, "Open document symbol outline panel",
+        call_hierarchy, "Open call hierarchy panel",
         diagnostics_picker, "Open diagnostic picker",
         workspace_diagnostics_picker, "Open workspace diagnostic picker",
         last_picker, "Open last picker",
@@ -314,10 +324,16 @@ pub fn doc(&self) -> &str {
         goto_prev_change, "Goto previous change",
         goto_first_change, "Goto first change",
         goto_last_change, "Goto last change",
+        goto_next_conflict, "Goto next conflict",
+        goto_prev_conflict, "Goto previous conflict",
+        conflict_pick_ours, "Resolve conflict with our version",
+        conflict_pick_theirs, "Resolve conflict with their version",
+        conflict_pick_both, "Resolve conflict with both versions",
         goto_line_start, "Goto line start",
         goto_line_end, "Goto line end",
         goto_next_buffer, "Goto next buffer",
         goto_previous_buffer, "Goto previous buffer",
+        close_buffer, "Close current buffer",
         goto_line_end_newline, "Goto newline at line end",
         goto_first_nonwhitespace, "Goto first non-blank in line",
         trim_selections, "Trim whitespace from selections",
@@ -337,6 +353,7 @@ pub fn doc(&self) -> &str {
         redo, "Redo change",
         earlier, "Move backward in history",
         later, "Move forward in history",
+        undo_tree, "Open undo history tree panel",
         commit_undo_checkpoint, "Commit changes to new checkpoint",
         yank, "Yank selection",
         yank_joined_to_clipboard, "Join and yank selections to clipboard",
@@ -385,15 +402,27 @@ pub fn doc(&self) -> &str {
         swap_view_up, "Swap with split above",
         swap_view_down, "Swap with split below",
         transpose_view, "Transpose splits",
+        rotate_splits_forward, "Rotate splits forward",
+        rotate_splits_backward, "Rotate splits backward",
+        grow_view_width, "Widen split",
+        shrink_view_width, "Narrow split",
+        grow_view_height, "Increase split height",
+        shrink_view_height, "Decrease split height",
+        equalize_views, "Give all splits the same size",
         rotate_view, "Goto next window",
         hsplit, "Horizontal bottom split",
         hsplit_new, "Horizontal bottom split scratch buffer",
         vsplit, "Vertical right split",
         vsplit_new, "Vertical right split scratch buffer",
         wclose, "Close window",
+        tab_new, "Open a new tab page",
+        tab_next, "Goto next tab page",
+        tab_prev, "Goto previous tab page",
+        tab_close, "Close tab page",
         wonly, "Close windows except current",
         select_register, "Select register",
         insert_register, "Insert register",
+        register_panel, "Open panel of register contents",
         align_view_middle, "Align view middle",
         align_view_top, "Align view top",
         align_view_center, "Align view center",
@@ -425,7 +454,7 @@ pub fn doc(&self) -> &str {
         dap_step_in, "Step in",
         dap_step_out, "Step out",
         dap_next, "Step to next",
-        dap_variables, "List variables",
+        dap_variables, "Browse and edit variables",
         dap_terminate, "End debug session",
         dap_edit_condition, "Edit breakpoint condition on current line",
         dap_edit_log, "Edit breakpoint log message on current line",
@@ -448,6 +477,33 @@ pub fn doc(&self) -> &str {
         toggle_or_focus_explorer, "toggle or focus explorer",
         open_explorer_recursion, "open explorer recursion",
         close_explorer, "close explorer",
+        explorer_reveal_current_file, "Reveal current file in explorer",
+        explorer_toggle_hidden, "Toggle hidden files in explorer",
+        explorer_toggle_lock, "Lock the explorer against creating and removing files, or unlock it",
+        explorer_parent_root, "Show parent directory in explorer",
+        explorer_refresh, "Refresh explorer",
+        explorer_new_file, "Create file in the selected directory of the explorer, or next to the selected file",
+        explorer_new_directory, "Create directory in the selected directory of the explorer, or next to the selected file",
+        explorer_filter, "Filter explorer",
+        explorer_cycle_filter_preset, "Cycle the filter presets of the explorer",
+        explorer_cycle_sort, "Cycle the order of the directory of the selected item in the explorer",
+        explorer_pin_search_results, "Pin the files of the last workspace search in the explorer",
+        explorer_clear_pinned, "Clear the pinned search results of the explorer",
+        explorer_open_marked, "Open the files marked in the explorer",
+        explorer_open_marked_grid, "Open the files marked in the explorer into a grid of splits",
+        explorer_diff_marked, "Show the differences between the two files marked in the explorer",
+        explorer_inspect, "Show the checksums and metadata of the file selected in the explorer",
+        explorer_metadata, "Show the path, size, timestamps, permissions, owner and git status of the item selected in the explorer",
+        explorer_expand_to_depth, "Expand the directory selected in the explorer as many levels deep as the count",
+        explorer_locate_manifest, "Select the closest project manifest above the selection of the explorer",
+        explorer_toggle_broken_links, "List only the broken symbolic links in the explorer, or all the files again",
+        explorer_toggle_untracked, "List only the untracked files in the explorer, or all the files again",
+        explorer_toggle_modified, "List only the files with uncommitted changes in the explorer, or all the files again",
+        explorer_retarget_link, "Point the symbolic link selected in the explorer to another target",
+        explorer_search, "Search in explorer",
+        explorer_actions, "Show the actions of the config for the explorer item",
+        toggle_zen_mode, "Toggle zen mode",
+        toggle_or_focus_terminal, "Open or focus the terminal",
     );
 }
 
@@ -679,19 +735,25 @@ fn goto_previous_buffer(cx: &mut Context) {
 
 fn goto_buffer(editor: &mut Editor, direction: Direction) {
     let current = view!(editor).doc;
+    // in the order of the bufferline
+    let ids: Vec<_> = editor
+        .buffers_in_order()
+        .iter()
+        .map(|doc| doc.id())
+        .collect();
 
     let id = match direction {
         Direction::Forward => {
-            let iter = editor.documents.keys();
+            let iter = ids.iter();
             let mut iter = iter.skip_while(|id| *id != &current);
             iter.next(); // skip current item
-            iter.next().or_else(|| editor.documents.keys().next())
+            iter.next().or_else(|| ids.first())
         }
         Direction::Backward => {
-            let iter = editor.documents.keys();
+            let iter = ids.iter();
             let mut iter = iter.rev().skip_while(|id| *id != &current);
             iter.next(); // skip current item
-            iter.next().or_else(|| editor.documents.keys().rev().next())
+            iter.next().or_else(|| ids.last())
         }
     }
     .unwrap();
@@ -701,6 +763,21 @@ fn goto_buffer(editor: &mut Editor, direction: Direction) {
     editor.switch(id, Action::Replace);
 }
 
+fn close_buffer(cx: &mut Context) {
+    let doc_id = view!(cx.editor).doc;
+    close_buffer_impl(cx.editor, doc_id);
+}
+
+/// Closes the buffer `doc_id` unless it has unsaved changes.
+pub(crate) fn close_buffer_impl(editor: &mut Editor, doc_id: DocumentId) {
+    if let Err(CloseError::BufferModified(name)) = editor.close_document(doc_id, false) {
+        editor.set_error(format!(
+            "'{}' has unsaved changes, use :buffer-close! to discard them",
+            name
+        ));
+    }
+}
+
 fn extend_to_line_start(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     goto_line_start_impl(view, doc, Movement::Extend)
@@ -1988,6 +2065,17 @@ fn label(&self, current_path: &Self::Data) -> Spans {
                     editor.set_status("No matches found");
                     return;
                 }
+                let mut files: Vec<_> = all_matches
+                    .iter()
+                    .map(|result| result.path.clone())
+                    .collect();
+                files.sort();
+                files.dedup();
+                editor.set_status(format!(
+                    "Matches in {} files, `p` in the explorer pins them",
+                    files.len()
+                ));
+                editor.search_results = files;
 
                 let picker = FilePicker::new(
                     all_matches,
@@ -2293,13 +2381,13 @@ fn file_picker(cx: &mut Context) {
     // We don't specify language markers, root will be the root of the current
     // git repo or the current dir if we're not in a repo
     let root = find_root(None, &[]);
-    let picker = ui::file_picker(root, &cx.editor.config());
+    let picker = ui::file_picker(root, cx.editor);
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
 fn file_picker_in_current_directory(cx: &mut Context) {
     let cwd = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("./"));
-    let picker = ui::file_picker(cwd, &cx.editor.config());
+    let picker = ui::file_picker(cwd, cx.editor);
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
@@ -2307,6 +2395,9 @@ fn toggle_or_focus_explorer(cx: &mut Context) {
     cx.callback = Some(Box::new(
         |compositor: &mut Compositor, cx: &mut compositor::Context| {
             if let Some(editor) = compositor.find::<ui::EditorView>() {
+                if let Some(terminal) = editor.terminal.as_mut() {
+                    terminal.unfocus();
+                }
                 match editor.explorer.as_mut() {
                     Some(explore) => explore.content.focus(),
                     None => match ui::Explorer::new(cx) {
@@ -2323,7 +2414,7 @@ fn open_explorer_recursion(cx: &mut Context) {
     cx.callback = Some(Box::new(
         |compositor: &mut Compositor, cx: &mut compositor::Context| {
             if let Some(editor) = compositor.find::<ui::EditorView>() {
-                match ui::Explorer::new_explorer_recursion() {
+                match ui::Explorer::new_explorer_recursion(cx.editor) {
                     Ok(explore) => editor.explorer = Some(overlayed(explore)),
                     Err(err) => cx.editor.set_error(format!("{}", err)),
                 }
@@ -2340,6 +2431,230 @@ fn close_explorer(cx: &mut Context) {
     }));
 }
 
+/// Runs `f` on the explorer, opening it first if it is closed. With `focus`, the explorer
+/// takes the focus, for the prompts `f` opens in it.
+fn with_explorer<F>(cx: &mut Context, focus: bool, f: F)
+where
+    F: FnOnce(&mut ui::Explorer, &mut compositor::Context) + 'static,
+{
+    cx.callback = Some(Box::new(
+        move |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            let editor = match compositor.find::<ui::EditorView>() {
+                Some(editor) => editor,
+                None => return,
+            };
+            if editor.explorer.is_none() {
+                match ui::Explorer::new(cx) {
+                    Ok(explore) => editor.explorer = Some(overlayed(explore)),
+                    Err(err) => {
+                        cx.editor.set_error(format!("{}", err));
+                        return;
+                    }
+                }
+            }
+            if focus {
+                if let Some(terminal) = editor.terminal.as_mut() {
+                    terminal.unfocus();
+                }
+            }
+            if let Some(explore) = editor.explorer.as_mut() {
+                if focus {
+                    explore.content.focus();
+                }
+                f(&mut explore.content, cx);
+            }
+        },
+    ));
+}
+
+fn explorer_reveal_current_file(cx: &mut Context) {
+    let path = match doc!(cx.editor).path() {
+        Some(path) => path.clone(),
+        None => {
+            cx.editor.set_error("Buffer has no file");
+            return;
+        }
+    };
+    with_explorer(cx, true, move |explorer, cx| {
+        explorer.reveal_file(&path, cx)
+    });
+}
+
+fn explorer_toggle_hidden(cx: &mut Context) {
+    ui::Explorer::toggle_hidden(cx.editor);
+}
+
+fn explorer_toggle_lock(cx: &mut Context) {
+    ui::Explorer::toggle_lock(cx.editor);
+}
+
+fn explorer_parent_root(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.go_to_parent(cx));
+}
+
+fn explorer_refresh(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.refresh(cx));
+}
+
+fn explorer_new_file(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| {
+        explorer.new_create_file_prompt(cx, false)
+    });
+}
+
+fn explorer_new_directory(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| {
+        explorer.new_mkdir_prompt(cx, false)
+    });
+}
+
+fn explorer_cycle_filter_preset(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.cycle_filter_preset(cx));
+}
+
+fn explorer_cycle_sort(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.cycle_sort(cx));
+}
+
+fn explorer_pin_search_results(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.pin_search_results(cx));
+}
+
+fn explorer_clear_pinned(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, _| explorer.clear_pinned());
+}
+
+fn explorer_open_marked(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.open_marked(cx, false));
+}
+
+fn explorer_open_marked_grid(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.open_marked(cx, true));
+}
+
+fn explorer_diff_marked(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.diff_marked(cx));
+}
+
+fn explorer_inspect(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.inspect_current(cx));
+}
+
+fn explorer_metadata(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| explorer.show_metadata(cx));
+}
+
+fn explorer_expand_to_depth(cx: &mut Context) {
+    let depth = cx.count();
+    with_explorer(cx, true, move |explorer, cx| {
+        explorer.expand_to_depth(depth, cx)
+    });
+}
+
+fn explorer_locate_manifest(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| explorer.locate_manifest(cx));
+}
+
+fn explorer_toggle_broken_links(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.toggle_broken_links(cx));
+}
+
+fn explorer_toggle_untracked(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.toggle_untracked(cx));
+}
+
+fn explorer_toggle_modified(cx: &mut Context) {
+    with_explorer(cx, false, |explorer, cx| explorer.toggle_modified(cx));
+}
+
+fn explorer_retarget_link(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| explorer.new_retarget_prompt(cx));
+}
+
+fn explorer_filter(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, _| explorer.new_filter_prompt());
+}
+
+fn explorer_search(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, _| explorer.new_search_prompt(true));
+}
+
+fn explorer_actions(cx: &mut Context) {
+    with_explorer(cx, true, |explorer, cx| explorer.open_actions_menu(cx));
+}
+
+fn toggle_zen_mode(cx: &mut Context) {
+    cx.callback = Some(Box::new(
+        |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            if let Some(editor) = compositor.find::<ui::EditorView>() {
+                cx.editor.toggle_zen_mode();
+                // hide the explorer as it is, including its focus, to restore it afterwards
+                // unless another one was opened in zen mode
+                if cx.editor.zen_mode {
+                    editor.zen_explorer = editor.explorer.take();
+                } else if let Some(explorer) = editor.zen_explorer.take() {
+                    if editor.explorer.is_none() {
+                        editor.explorer = Some(explorer);
+                    }
+                }
+            }
+        },
+    ));
+}
+
+fn toggle_or_focus_terminal(cx: &mut Context) {
+    cx.callback = Some(Box::new(
+        |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            if let Some(editor) = compositor.find::<ui::EditorView>() {
+                if let Some(explore) = editor.explorer.as_mut() {
+                    explore.content.unfocus();
+                }
+                match editor.terminal.as_mut() {
+                    Some(terminal) => terminal.focus(),
+                    None => match std::env::current_dir() {
+                        Ok(cwd) => editor.open_terminal(&cwd, cx.editor),
+                        Err(err) => cx.editor.set_error(format!("{}", err)),
+                    },
+                }
+            }
+        },
+    ));
+}
+
+/// The actions of `buffer_menu`: their key, description and typable command.
+const BUFFER_MENU: &[(char, &str, &str)] = &[
+    ('c', "Close", "buffer-close"),
+    ('o', "Close others", "buffer-close-others"),
+    ('r', "Close to the right", "buffer-close-right"),
+    ('u', "Close unmodified", "buffer-close-unmodified"),
+    ('p', "Pin or unpin", "buffer-pin"),
+];
+
+/// The actions on the current buffer and the ones around it in the bufferline, also
+/// opened by right clicking the buffers of the bufferline.
+fn buffer_menu(cx: &mut Context) {
+    let title = doc!(cx.editor).display_name().into_owned();
+    let body: Vec<_> = BUFFER_MENU
+        .iter()
+        .map(|(key, desc, _)| (key.to_string(), *desc))
+        .collect();
+    cx.editor.autoinfo = Some(Info::new(&title, &body));
+    cx.on_next_key(|cx, event| {
+        cx.editor.autoinfo = None;
+        let action = BUFFER_MENU
+            .iter()
+            .find(|(key, ..)| event.char() == Some(*key));
+        if let Some((_, _, name)) = action {
+            let command = MappableCommand::Typable {
+                name: name.to_string(),
+                args: Vec::new(),
+                doc: String::new(),
+            };
+            command.execute(cx);
+        }
+    });
+}
+
 fn buffer_picker(cx: &mut Context) {
     let current = view!(cx.editor).doc;
 
@@ -2351,9 +2666,26 @@ struct BufferMeta {
     }
 
     impl ui::menu::Item for BufferMeta {
-        type Data = ();
+        /// Whether to show the file type icons.
+        type Data = bool;
 
-        fn label(&self, _data: &Self::Data) -> Spans {
+        fn label(&self, show_icons: &Self::Data) -> Spans {
+            let text = self.filter_text(show_icons).into_owned();
+            if !show_icons {
+                return text.into();
+            }
+            let icon = match &self.path {
+                Some(path) => ui::icons::path_span(path),
+                None => ui::icons::path_span(Path::new(SCRATCH_BUFFER_NAME)),
+            };
+            Spans::from(vec![icon, Span::raw(text)])
+        }
+
+        fn sort_text(&self, data: &Self::Data) -> Cow<str> {
+            self.filter_text(data)
+        }
+
+        fn filter_text(&self, _data: &Self::Data) -> Cow<str> {
             let path = self
                 .path
                 .as_deref()
@@ -2393,7 +2725,7 @@ fn label(&self, _data: &Self::Data) -> Spans {
             .values()
             .map(|doc| new_meta(doc))
             .collect(),
-        (),
+        cx.editor.config().icons,
         |cx, meta, action| {
             cx.editor.switch(meta.id, action);
         },
@@ -2410,6 +2742,39 @@ fn label(&self, _data: &Self::Data) -> Spans {
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
+/// Picks one of the breadcrumbs of the current view to jump to that scope.
+fn breadcrumbs_picker(cx: &mut Context) {
+    let (view, doc) = current_ref!(cx.editor);
+    let view_id = view.id;
+    let doc_id = doc.id();
+    let crumbs = ui::breadcrumbs::crumbs(doc, view);
+
+    let picker = FilePicker::new(
+        crumbs,
+        (),
+        move |cx, crumb, _action| {
+            ui::breadcrumbs::jump_to(cx.editor, view_id, crumb);
+        },
+        move |editor, crumb| {
+            let doc = editor.documents.get(&doc_id)?;
+            let text = doc.text();
+            let line = text.char_to_line(crumb.pos.min(text.len_chars()));
+            Some((doc_id.into(), Some((line, line))))
+        },
+    );
+    cx.push_layer(Box::new(overlayed(picker)));
+}
+
+fn buffer_tree(cx: &mut Context) {
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        let id = ui::BufferTree::ID;
+        match compositor.find_id::<ui::BufferTree>(id) {
+            Some(panel) => panel.focus(),
+            None => compositor.push(Box::new(ui::BufferTree::new())),
+        }
+    }));
+}
+
 fn jumplist_picker(cx: &mut Context) {
     struct JumpMeta {
         id: DocumentId,
@@ -2492,6 +2857,13 @@ fn label(&self, _data: &Self::Data) -> Spans {
     cx.push_layer(Box::new(overlayed(picker)));
 }
 
+fn jumplist_panel(cx: &mut Context) {
+    let panel = ui::JumpListPanel::new(cx.editor);
+    cx.callback = Some(Box::new(move |compositor: &mut Compositor, _| {
+        compositor.replace_or_push(ui::JumpListPanel::ID, panel);
+    }));
+}
+
 impl ui::menu::Item for MappableCommand {
     type Data = ReverseKeymap;
 
@@ -3002,6 +3374,130 @@ fn goto_next_change_impl(cx: &mut Context, direction: Direction) {
     cx.editor.last_motion = Some(Motion(Box::new(motion)));
 }
 
+fn goto_next_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Forward)
+}
+
+fn goto_prev_conflict(cx: &mut Context) {
+    goto_next_conflict_impl(cx, Direction::Backward)
+}
+
+fn goto_next_conflict_impl(cx: &mut Context, direction: Direction) {
+    let count = cx.count();
+    let motion = move |editor: &mut Editor| {
+        let (view, doc) = current!(editor);
+        let doc_text = doc.text().slice(..);
+        let conflicts = doc.conflicts();
+        if conflicts.is_empty() {
+            editor.set_status("No conflicts in current buffer");
+            return;
+        }
+
+        let selection = doc.selection(view.id).clone().transform(|range| {
+            let cursor_line = range.cursor_line(doc_text);
+            // go as far as possible if there are less than `count` conflicts left
+            let conflict = match direction {
+                Direction::Forward => conflicts
+                    .iter()
+                    .filter(|conflict| conflict.start > cursor_line)
+                    .take(count)
+                    .last(),
+                Direction::Backward => conflicts
+                    .iter()
+                    .rev()
+                    .filter(|conflict| conflict.end < cursor_line)
+                    .take(count)
+                    .last(),
+            };
+            let conflict = match conflict {
+                Some(conflict) => conflict,
+                None => return range,
+            };
+
+            let new_range = Range::new(
+                doc_text.line_to_char(conflict.start),
+                doc_text.line_to_char(conflict.end + 1),
+            );
+            if editor.mode == Mode::Select {
+                let head = if new_range.head < range.anchor {
+                    new_range.anchor
+                } else {
+                    new_range.head
+                };
+
+                Range::new(range.anchor, head)
+            } else {
+                new_range.with_direction(direction)
+            }
+        });
+
+        doc.set_selection(view.id, selection)
+    };
+    motion(cx.editor);
+    cx.editor.last_motion = Some(Motion(Box::new(motion)));
+}
+
+#[derive(Clone, Copy)]
+enum ConflictResolution {
+    Ours,
+    Theirs,
+    Both,
+}
+
+fn conflict_pick_ours(cx: &mut Context) {
+    resolve_conflicts(cx, ConflictResolution::Ours)
+}
+
+fn conflict_pick_theirs(cx: &mut Context) {
+    resolve_conflicts(cx, ConflictResolution::Theirs)
+}
+
+fn conflict_pick_both(cx: &mut Context) {
+    resolve_conflicts(cx, ConflictResolution::Both)
+}
+
+/// Replaces the conflicts under the cursors with the chosen versions, removing the markers.
+fn resolve_conflicts(cx: &mut Context, resolution: ConflictResolution) {
+    let (view, doc) = current!(cx.editor);
+    let text = doc.text().slice(..);
+
+    // the ranges of a selection are sorted, so duplicates are next to each other
+    let mut conflicts: Vec<Conflict> = doc
+        .selection(view.id)
+        .iter()
+        .filter_map(|range| conflict::conflict_at(doc.conflicts(), range.cursor_line(text)))
+        .copied()
+        .collect();
+    conflicts.dedup();
+    if conflicts.is_empty() {
+        cx.editor.set_error("No conflict under the cursor");
+        return;
+    }
+
+    let changes = conflicts.iter().map(|conflict| {
+        let versions = match resolution {
+            ConflictResolution::Ours => vec![conflict.ours()],
+            ConflictResolution::Theirs => vec![conflict.theirs()],
+            ConflictResolution::Both => vec![conflict.ours(), conflict.theirs()],
+        };
+        let contents: String = versions
+            .into_iter()
+            .map(|lines| {
+                text.slice(text.line_to_char(lines.start)..text.line_to_char(lines.end))
+                    .to_string()
+            })
+            .collect();
+        (
+            text.line_to_char(conflict.start),
+            text.line_to_char(conflict.end + 1),
+            Some(contents.into()),
+        )
+    });
+    let transaction = Transaction::change(doc.text(), changes);
+    apply_transaction(&transaction, doc, view);
+    doc.append_changes_to_history(view);
+}
+
 pub mod insert {
     use super::*;
     pub type Hook = fn(&Rope, &Selection, char) -> Option<Transaction>;
@@ -3454,6 +3950,16 @@ fn later(cx: &mut Context) {
     }
 }
 
+fn undo_tree(cx: &mut Context) {
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        let id = ui::UndoTree::ID;
+        match compositor.find_id::<ui::UndoTree>(id) {
+            Some(panel) => panel.focus(),
+            None => compositor.push(Box::new(ui::UndoTree::new())),
+        }
+    }));
+}
+
 fn commit_undo_checkpoint(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     doc.append_changes_to_history(view);
@@ -4380,6 +4886,83 @@ fn transpose_view(cx: &mut Context) {
     cx.editor.transpose_view()
 }
 
+fn rotate_splits_forward(cx: &mut Context) {
+    for _ in 0..cx.count() {
+        cx.editor.rotate_splits(true);
+    }
+}
+
+fn rotate_splits_backward(cx: &mut Context) {
+    for _ in 0..cx.count() {
+        cx.editor.rotate_splits(false);
+    }
+}
+
+fn resize_view(cx: &mut Context, layout: tree::Layout, direction: i32) {
+    let amount = cx.count() as i32 * direction;
+    cx.editor.resize_split(layout, amount);
+}
+
+fn grow_view_width(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Vertical, 1)
+}
+
+fn shrink_view_width(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Vertical, -1)
+}
+
+fn grow_view_height(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Horizontal, 1)
+}
+
+fn shrink_view_height(cx: &mut Context) {
+    resize_view(cx, tree::Layout::Horizontal, -1)
+}
+
+fn equalize_views(cx: &mut Context) {
+    cx.editor.equalize_splits()
+}
+
+fn tab_new(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let id = doc.id();
+    let selection = doc.selection(view.id).clone();
+
+    cx.editor.new_tab(id);
+
+    // match the selection in the previous tab page
+    let (view, doc) = current!(cx.editor);
+    doc.set_selection(view.id, selection);
+    align_view(doc, view, Align::Center);
+}
+
+fn goto_tab(cx: &mut Context, direction: Direction) {
+    let tabs = cx.editor.tree.tabs().len();
+    let count = cx.count() % tabs;
+    let active = cx.editor.tree.active_tab();
+    let index = match direction {
+        Direction::Forward => (active + count) % tabs,
+        Direction::Backward => (active + tabs - count) % tabs,
+    };
+    cx.editor.switch_tab(index);
+}
+
+fn tab_next(cx: &mut Context) {
+    goto_tab(cx, Direction::Forward)
+}
+
+fn tab_prev(cx: &mut Context) {
+    goto_tab(cx, Direction::Backward)
+}
+
+fn tab_close(cx: &mut Context) {
+    if cx.editor.tree.tabs().len() == 1 {
+        cx.editor.set_error("Cannot close the last tab page");
+        return;
+    }
+    cx.editor.close_tab(cx.editor.tree.active_tab());
+}
+
 // split helper, clear it later
 fn split(cx: &mut Context, action: Action) {
     let (view, doc) = current!(cx.editor);
@@ -4414,7 +4997,7 @@ fn vsplit_new(cx: &mut Context) {
 }
 
 fn wclose(cx: &mut Context) {
-    if cx.editor.tree.views().count() == 1 {
+    if cx.editor.tree.all_views().count() == 1 {
         if let Err(err) = typed::buffers_remaining_impl(cx.editor) {
             cx.editor.set_error(err.to_string());
             return;
@@ -4449,6 +5032,13 @@ fn select_register(cx: &mut Context) {
     })
 }
 
+fn register_panel(cx: &mut Context) {
+    let panel = ui::RegisterPanel::new(cx.editor);
+    cx.callback = Some(Box::new(move |compositor: &mut Compositor, _| {
+        compositor.replace_or_push(ui::RegisterPanel::ID, panel);
+    }));
+}
+
 fn insert_register(cx: &mut Context) {
     cx.editor.autoinfo = Some(Info::from_registers(&cx.editor.registers));
     cx.on_next_key(move |cx, event| {
@@ -4624,9 +5214,12 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
                         return range;
                     };
                     let hunk = hunks.nth_hunk(hunk_idx).after;
+                    // a count extends the selection over the following hunks
+                    let last_hunk_idx = (hunk_idx + count as u32 - 1).min(hunks.len() - 1);
+                    let last_hunk = hunks.nth_hunk(last_hunk_idx).after;
 
                     let start = text.line_to_char(hunk.start as usize);
-                    let end = text.line_to_char(hunk.end as usize);
+                    let end = text.line_to_char(last_hunk.end as usize);
                     Range::new(start, end).with_direction(range.direction())
                 };
 
@@ -4673,6 +5266,7 @@ fn select_textobject(cx: &mut Context, objtype: textobject::TextObject) {
         ("c", "Comment (tree-sitter)"),
         ("T", "Test (tree-sitter)"),
         ("m", "Closest surrounding pair to cursor"),
+        ("g", "Change (diff hunk)"),
         (" ", "... or any character acting as a pair"),
     ];
 
@@ -4868,7 +5462,7 @@ fn shell_impl(shell: &[String], cmd: &str, input: Option<Rope>) -> anyhow::Resul
     tokio::task::block_in_place(|| helix_lsp::block_on(shell_impl_async(shell, cmd, input)))
 }
 
-async fn shell_impl_async(
+pub async fn shell_impl_async(
     shell: &[String],
     cmd: &str,
     input: Option<Rope>,
@@ -2,7 +2,7 @@
 use crate::{
     compositor::{self, Compositor},
     job::{Callback, Jobs},
-    ui::{self, overlay::overlayed, FilePicker, Picker, Popup, Prompt, PromptEvent, Text},
+    ui::{self, overlay::overlayed, FilePicker, Picker, Prompt, PromptEvent},
 };
 use dap::{StackFrame, Thread, ThreadStates};
 use helix_core::syntax::{DebugArgumentValue, DebugConfigCompletion, DebugTemplate};
@@ -107,7 +107,7 @@ fn get_breakpoint_at_current_line(editor: &mut Editor) -> Option<(usize, Breakpo
 
 // -- DAP
 
-fn dap_callback<T, F>(
+pub(crate) fn dap_callback<T, F>(
     jobs: &mut Jobs,
     call: impl Future<Output = helix_dap::Result<serde_json::Value>> + 'static + Send,
     callback: F,
@@ -471,69 +471,28 @@ pub fn dap_next(cx: &mut Context) {
 }
 
 pub fn dap_variables(cx: &mut Context) {
-    let debugger = debugger!(cx.editor);
-
-    if debugger.thread_id.is_none() {
-        cx.editor
-            .set_status("Cannot access variables while target is running");
-        return;
-    }
-    let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
-        (Some(frame), Some(thread_id)) => (frame, thread_id),
-        _ => {
-            cx.editor
-                .set_status("Cannot find current stack frame to access variables");
-            return;
-        }
-    };
-
-    let frame_id = debugger.stack_frames[&thread_id][frame].id;
-    let scopes = match block_on(debugger.scopes(frame_id)) {
-        Ok(s) => s,
-        Err(e) => {
-            cx.editor.set_error(format!("Failed to get scopes: {}", e));
-            return;
-        }
-    };
-
-    // TODO: allow expanding variables into sub-fields
-    let mut variables = Vec::new();
-
-    let theme = &cx.editor.theme;
-    let scope_style = theme.get("ui.linenr.selected");
-    let type_style = theme.get("ui.text");
-    let text_style = theme.get("ui.text.focus");
-
-    for scope in scopes.iter() {
-        // use helix_view::graphics::Style;
-        use tui::text::Span;
-        let response = block_on(debugger.variables(scope.variables_reference));
-
-        variables.push(Spans::from(Span::styled(
-            format!("▸ {}", scope.name),
-            scope_style,
-        )));
-
-        if let Ok(vars) = response {
-            variables.reserve(vars.len());
-            for var in vars {
-                let mut spans = Vec::with_capacity(5);
-
-                spans.push(Span::styled(var.name.to_owned(), text_style));
-                if let Some(ty) = var.ty {
-                    spans.push(Span::raw(": "));
-                    spans.push(Span::styled(ty.to_owned(), type_style));
+    cx.callback = Some(Box::new(
+        |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            let id = ui::DebugVariables::ID;
+            let result = match compositor.find_id::<ui::DebugVariables>(id) {
+                Some(panel) => {
+                    panel.focus();
+                    panel.refresh(cx.editor, cx.jobs)
+                }
+                None => {
+                    let mut panel = ui::DebugVariables::new();
+                    let result = panel.refresh(cx.editor, cx.jobs);
+                    if result.is_ok() {
+                        compositor.push(Box::new(panel));
+                    }
+                    result
                 }
-                spans.push(Span::raw(" = "));
-                spans.push(Span::styled(var.value.to_owned(), text_style));
-                variables.push(Spans::from(spans));
+            };
+            if let Err(err) = result {
+                cx.editor.set_error(err.to_string());
             }
-        }
-    }
-
-    let contents = Text::from(tui::text::Text::from(variables));
-    let popup = Popup::new("dap-variables", contents);
-    cx.push_layer(Box::new(popup));
+        },
+    ));
 }
 
 pub fn dap_terminate(cx: &mut Context) {
@@ -9,14 +9,22 @@
 
 use super::{align_view, push_jump, Align, Context, Editor, Open};
 
-use helix_core::{path, Selection};
-use helix_view::{apply_transaction, document::Mode, editor::Action, theme::Style};
+use helix_core::{path, Position, Selection};
+use helix_view::{
+    apply_transaction,
+    document::{CodeActionHint, DocumentSymbol, InlayHint, Mode},
+    editor::{Action, ConfigEvent},
+    hooks::HookEvent,
+    theme::Style,
+    DocumentId,
+};
 
 use crate::{
     compositor::{self, Compositor},
+    job,
     ui::{
         self, lsp::SignatureHelp, overlay::overlayed, DynamicPicker, FileLocation, FilePicker,
-        Popup, PromptEvent,
+        Popup, PromptEvent, TreePanel,
     },
 };
 
@@ -162,7 +170,7 @@ fn location_to_file_location(location: &lsp::Location) -> FileLocation {
 }
 
 // TODO: share with symbol picker(symbol.location)
-fn jump_to_location(
+pub fn jump_to_location(
     editor: &mut Editor,
     location: &lsp::Location,
     offset_encoding: OffsetEncoding,
@@ -427,6 +435,35 @@ pub fn workspace_symbol_picker(cx: &mut Context) {
     )
 }
 
+/// Opens the panel of the workspace symbols, or focuses it if it is open already.
+pub fn workspace_symbol_tree(cx: &mut Context) {
+    cx.callback = Some(Box::new(
+        |compositor: &mut Compositor, cx: &mut compositor::Context| {
+            if let Some(panel) =
+                compositor.find_id::<ui::WorkspaceSymbols>(ui::WorkspaceSymbols::ID)
+            {
+                panel.focus();
+                return;
+            }
+            if ui::WorkspaceSymbols::request(cx.editor, cx.jobs, String::new()) {
+                compositor.push(Box::new(ui::WorkspaceSymbols::new()));
+            }
+        },
+    ));
+}
+
+/// Opens the outline of the focused document, or focuses it if it is open already.
+pub fn document_outline(cx: &mut Context) {
+    refresh_document_symbols(cx);
+    cx.callback = Some(Box::new(|compositor: &mut Compositor, _| {
+        let id = ui::DocumentOutline::ID;
+        match compositor.find_id::<ui::DocumentOutline>(id) {
+            Some(outline) => outline.focus(),
+            None => compositor.push(Box::new(ui::DocumentOutline::new())),
+        }
+    }));
+}
+
 pub fn diagnostics_picker(cx: &mut Context) {
     let doc = doc!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
@@ -624,38 +661,313 @@ pub fn code_action(cx: &mut Context) {
                     .reverse()
             });
 
-            let mut picker = ui::Menu::new(actions, (), move |editor, code_action, event| {
-                if event != PromptEvent::Validate {
-                    return;
-                }
+            // the edit may be previewed, which needs the compositor
+            let mut picker =
+                ui::Menu::new(actions, (), |_, _, _| {}).with_validate_fn(move |code_action| {
+                    let code_action = code_action.clone();
+                    Box::new(move |compositor, cx| {
+                        run_code_action(cx.editor, compositor, offset_encoding, code_action)
+                    })
+                });
+            picker.move_down(); // pre-select the first item
 
-                // always present here
-                let code_action = code_action.unwrap();
+            let popup = Popup::new("code-action", picker).with_scrollbar(false);
+            compositor.replace_or_push("code-action", popup);
+        },
+    )
+}
 
-                match code_action {
-                    lsp::CodeActionOrCommand::Command(command) => {
-                        log::debug!("code action command: {:?}", command);
-                        execute_lsp_command(editor, command.clone());
+/// Asks the language server whether code actions are available on the cursor line,
+/// for the code action gutter. Lines are only requested once per document version,
+/// so calling this repeatedly (e.g. on every idle timeout) doesn't spam the server.
+fn run_code_action(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    offset_encoding: OffsetEncoding,
+    code_action: CodeActionOrCommand,
+) {
+    match code_action {
+        lsp::CodeActionOrCommand::Command(command) => {
+            log::debug!("code action command: {:?}", command);
+            execute_lsp_command(editor, command);
+        }
+        lsp::CodeActionOrCommand::CodeAction(code_action) => {
+            log::debug!("code action: {:?}", code_action);
+            // if code action provides both edit and command first the edit
+            // should be applied and then the command
+            match code_action.edit {
+                Some(workspace_edit) => {
+                    log::debug!("edit: {:?}", workspace_edit);
+                    apply_or_preview_workspace_edit(
+                        editor,
+                        compositor,
+                        offset_encoding,
+                        &workspace_edit,
+                        code_action.command,
+                    );
+                }
+                None => {
+                    if let Some(command) = code_action.command {
+                        execute_lsp_command(editor, command);
                     }
-                    lsp::CodeActionOrCommand::CodeAction(code_action) => {
-                        log::debug!("code action: {:?}", code_action);
-                        if let Some(ref workspace_edit) = code_action.edit {
-                            log::debug!("edit: {:?}", workspace_edit);
-                            apply_workspace_edit(editor, offset_encoding, workspace_edit);
-                        }
+                }
+            }
+        }
+    }
+}
+
+pub fn refresh_code_action_hint(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return,
+    };
+
+    let text = doc.text();
+    let line = doc.selection(view.id).primary().cursor_line(text.slice(..));
+    let version = doc.version();
+    if doc
+        .code_action_hint()
+        .map_or(false, |hint| hint.line == line && hint.version == version)
+    {
+        return;
+    }
+
+    let line_range = helix_core::Range::new(
+        text.line_to_char(line),
+        helix_core::line_ending::line_end_char_index(&text.slice(..), line),
+    );
+    let offset_encoding = language_server.offset_encoding();
+    let future = match language_server.code_actions(
+        doc.identifier(),
+        range_to_lsp_range(text, line_range, offset_encoding),
+        lsp::CodeActionContext {
+            diagnostics: doc
+                .diagnostics()
+                .iter()
+                .filter(|&diag| diag.line == line)
+                .map(|diag| diagnostic_to_lsp_diagnostic(text, diag, offset_encoding))
+                .collect(),
+            only: None,
+        },
+    ) {
+        Some(future) => future,
+        None => return,
+    };
+
+    // mark the line as requested so it isn't requested again while pending
+    let mut hint = CodeActionHint {
+        line,
+        version,
+        available: false,
+    };
+    doc.set_code_action_hint(Some(hint));
+
+    let doc_id = doc.id();
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<lsp::CodeActionResponse>| {
+            let doc = match editor.document_mut(doc_id) {
+                Some(doc) => doc,
+                None => return,
+            };
+            // a newer request superseded this one
+            if doc.code_action_hint() != Some(hint) {
+                return;
+            }
+
+            hint.available = response.map_or(false, |actions| {
+                actions.iter().any(|action| {
+                    matches!(
+                        action,
+                        CodeActionOrCommand::Command(_)
+                            | CodeActionOrCommand::CodeAction(CodeAction { disabled: None, .. })
+                    )
+                })
+            });
+            doc.set_code_action_hint(Some(hint));
+        },
+    )
+}
+
+/// Requests the inlay hints of the current document if it changed since they were
+/// last requested, or clears them if they shouldn't be displayed.
+pub fn refresh_inlay_hints(cx: &mut Context) {
+    let config = cx.editor.config();
+    let doc = doc_mut!(cx.editor);
+    if !doc.display_inlay_hints(&config.lsp) {
+        if doc.inlay_hints_version().is_some() {
+            doc.set_inlay_hints(None, Vec::new());
+        }
+        return;
+    }
+
+    let version = doc.version();
+    if doc.inlay_hints_version() == Some(version) {
+        return;
+    }
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return,
+    };
+
+    let text = doc.text();
+    let offset_encoding = language_server.offset_encoding();
+    let range = range_to_lsp_range(
+        text,
+        helix_core::Range::new(0, text.len_chars()),
+        offset_encoding,
+    );
+    let future =
+        match language_server.text_document_range_inlay_hints(doc.identifier(), range, None) {
+            Some(future) => future,
+            None => return,
+        };
+
+    // keep showing the previous hints (mapped over the changes) while the request is
+    // pending, but don't request them again
+    let hints = doc.inlay_hints().to_vec();
+    doc.set_inlay_hints(Some(version), hints);
+
+    let doc_id = doc.id();
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<Vec<lsp::InlayHint>>| {
+            let doc = match editor.document_mut(doc_id) {
+                Some(doc) => doc,
+                None => return,
+            };
+            // the document changed while the request was pending
+            if doc.version() != version {
+                return;
+            }
 
-                        // if code action provides both edit and command first the edit
-                        // should be applied and then the command
-                        if let Some(command) = &code_action.command {
-                            execute_lsp_command(editor, command.clone());
+            let hints = response
+                .unwrap_or_default()
+                .into_iter()
+                .filter_map(|hint| {
+                    let char_idx = lsp_pos_to_pos(doc.text(), hint.position, offset_encoding)?;
+                    let mut label = match hint.label {
+                        lsp::InlayHintLabel::String(label) => label,
+                        lsp::InlayHintLabel::LabelParts(parts) => {
+                            parts.into_iter().map(|part| part.value).collect()
                         }
+                    };
+                    if hint.padding_left == Some(true) {
+                        label.insert(0, ' ');
                     }
-                }
+                    if hint.padding_right == Some(true) {
+                        label.push(' ');
+                    }
+                    Some(InlayHint {
+                        char_idx,
+                        label,
+                        kind: hint.kind,
+                    })
+                })
+                .collect();
+            doc.set_inlay_hints(Some(version), hints);
+        },
+    )
+}
+
+pub fn toggle_inlay_hints(cx: &mut Context) {
+    let mut config = (*cx.editor.config()).clone();
+    config.lsp.display_inlay_hints = !config.lsp.display_inlay_hints;
+    let status = if config.lsp.display_inlay_hints {
+        "Inlay hints enabled"
+    } else {
+        "Inlay hints disabled"
+    };
+    if let Err(err) = cx
+        .editor
+        .config_events
+        .0
+        .send(ConfigEvent::Update(Box::new(config)))
+    {
+        cx.editor.set_error(err.to_string());
+        return;
+    }
+    cx.editor.set_status(status);
+}
+
+/// Requests the document symbols of the current document for the breadcrumbs bar
+/// unless they are up to date.
+pub fn refresh_document_symbols(cx: &mut Context) {
+    let doc = doc_mut!(cx.editor);
+    let version = doc.version();
+    if doc.symbols_version() == Some(version) {
+        return;
+    }
+    let language_server = match doc.language_server() {
+        Some(language_server) => language_server,
+        None => return,
+    };
+    let offset_encoding = language_server.offset_encoding();
+    let future = match language_server.document_symbols(doc.identifier()) {
+        Some(future) => future,
+        None => return,
+    };
+
+    // don't request the symbols again while the request is pending
+    let symbols = doc.symbols().to_vec();
+    doc.set_symbols(Some(version), symbols);
+
+    fn push_nested(
+        symbols: &mut Vec<DocumentSymbol>,
+        text: &helix_core::Rope,
+        symbol: lsp::DocumentSymbol,
+        offset_encoding: OffsetEncoding,
+    ) {
+        if let Some(range) = lsp_range_to_range(text, symbol.range, offset_encoding) {
+            symbols.push(DocumentSymbol {
+                name: symbol.name,
+                kind: symbol.kind,
+                start: range.from(),
+                end: range.to(),
             });
-            picker.move_down(); // pre-select the first item
+        }
+        for child in symbol.children.into_iter().flatten() {
+            push_nested(symbols, text, child, offset_encoding);
+        }
+    }
 
-            let popup = Popup::new("code-action", picker).with_scrollbar(false);
-            compositor.replace_or_push("code-action", popup);
+    let doc_id = doc.id();
+    cx.callback(
+        future,
+        move |editor, _compositor, response: Option<lsp::DocumentSymbolResponse>| {
+            let doc = match editor.document_mut(doc_id) {
+                Some(doc) => doc,
+                None => return,
+            };
+            // the document changed while the request was pending
+            if doc.version() != version {
+                return;
+            }
+
+            let text = doc.text();
+            let mut symbols = Vec::new();
+            match response {
+                Some(lsp::DocumentSymbolResponse::Flat(flat)) => {
+                    symbols.extend(flat.into_iter().filter_map(|symbol| {
+                        let range =
+                            lsp_range_to_range(text, symbol.location.range, offset_encoding)?;
+                        Some(DocumentSymbol {
+                            name: symbol.name,
+                            kind: symbol.kind,
+                            start: range.from(),
+                            end: range.to(),
+                        })
+                    }))
+                }
+                Some(lsp::DocumentSymbolResponse::Nested(nested)) => {
+                    for symbol in nested {
+                        push_nested(&mut symbols, text, symbol, offset_encoding);
+                    }
+                }
+                None => (),
+            }
+            doc.set_symbols(Some(version), symbols);
         },
     )
 }
@@ -747,6 +1059,20 @@ pub fn apply_document_resource_op(op: &lsp::ResourceOp) -> std::io::Result<()> {
     }
 }
 
+/// The event of the hooks for a file created, renamed or deleted by a workspace edit.
+fn resource_op_event(op: &lsp::ResourceOp) -> Option<HookEvent> {
+    use lsp::ResourceOp;
+    let event = match op {
+        ResourceOp::Create(op) => HookEvent::FileCreated(op.uri.to_file_path().ok()?),
+        ResourceOp::Rename(op) => HookEvent::FileRenamed {
+            from: op.old_uri.to_file_path().ok()?,
+            to: op.new_uri.to_file_path().ok()?,
+        },
+        ResourceOp::Delete(op) => HookEvent::FileDeleted(op.uri.to_file_path().ok()?),
+    };
+    Some(event)
+}
+
 pub fn apply_workspace_edit(
     editor: &mut Editor,
     offset_encoding: OffsetEncoding,
@@ -839,10 +1165,12 @@ pub fn apply_workspace_edit(
             }
             lsp::DocumentChanges::Operations(operations) => {
                 log::debug!("document changes - operations: {:?}", operations);
+                let mut events = Vec::new();
                 for operation in operations {
                     match operation {
                         lsp::DocumentChangeOperation::Op(op) => {
                             apply_document_resource_op(op).unwrap();
+                            events.extend(resource_op_event(op));
                         }
 
                         lsp::DocumentChangeOperation::Edit(document_edit) => {
@@ -861,11 +1189,32 @@ pub fn apply_workspace_edit(
                         }
                     }
                 }
+                for event in events {
+                    editor.emit(event);
+                }
             }
         }
     }
 }
 
+/// Applies `workspace_edit` and then executes `command`, if any. Edits changing more than one
+/// file are shown to accept or reject their changes first, unless disabled in the config.
+pub fn apply_or_preview_workspace_edit(
+    editor: &mut Editor,
+    compositor: &mut Compositor,
+    offset_encoding: OffsetEncoding,
+    workspace_edit: &lsp::WorkspaceEdit,
+    command: Option<lsp::Command>,
+) {
+    let mut preview =
+        ui::WorkspaceEditPreview::new(editor, offset_encoding, workspace_edit, command);
+    if editor.config().lsp.preview_workspace_edits && preview.len() > 1 {
+        compositor.push(Box::new(preview));
+    } else {
+        preview.apply(editor);
+    }
+}
+
 fn goto_impl(
     editor: &mut Editor,
     compositor: &mut Compositor,
@@ -935,6 +1284,38 @@ pub fn goto_definition(cx: &mut Context) {
     );
 }
 
+/// Opens the calls to the function at the cursor in a panel, replacing the previous ones.
+pub fn call_hierarchy(cx: &mut Context) {
+    let (view, doc) = current!(cx.editor);
+    let language_server = language_server!(cx.editor, doc);
+    let language_server_id = language_server.id();
+    let offset_encoding = language_server.offset_encoding();
+
+    let pos = doc.position(view.id, offset_encoding);
+
+    let future = match language_server.prepare_call_hierarchy(doc.identifier(), pos) {
+        Some(future) => future,
+        None => {
+            cx.editor
+                .set_error("Language server does not support call hierarchies");
+            return;
+        }
+    };
+
+    cx.callback(
+        future,
+        move |editor, compositor, response: Option<Vec<lsp::CallHierarchyItem>>| {
+            let roots = response.unwrap_or_default();
+            if roots.is_empty() {
+                editor.set_status("No function at the cursor");
+                return;
+            }
+            let panel = ui::CallHierarchy::new(roots, language_server_id, offset_encoding);
+            compositor.replace_or_push(ui::CallHierarchy::ID, panel);
+        },
+    );
+}
+
 pub fn goto_type_definition(cx: &mut Context) {
     let (view, doc) = current!(cx.editor);
     let language_server = language_server!(cx.editor, doc);
@@ -1141,13 +1522,31 @@ pub fn signature_help_impl(cx: &mut Context, invoked: SignatureHelpInvoked) {
 }
 
 pub fn hover(cx: &mut Context) {
-    let (view, doc) = current!(cx.editor);
+    let (view, doc) = current_ref!(cx.editor);
+    let doc_id = doc.id();
+    let pos = doc
+        .selection(view.id)
+        .primary()
+        .cursor(doc.text().slice(..));
+    hover_at(cx, doc_id, pos, "hover", None);
+}
+
+/// Shows the hover information of the language server for the char at `pos` of `doc_id`
+/// in the popup `id`, next to the screen position `anchor` if given.
+pub fn hover_at(
+    cx: &mut Context,
+    doc_id: DocumentId,
+    pos: usize,
+    id: &'static str,
+    anchor: Option<Position>,
+) {
+    let doc = doc!(cx.editor, &doc_id);
     let language_server = language_server!(cx.editor, doc);
     let offset_encoding = language_server.offset_encoding();
 
     // TODO: factor out a doc.position_identifier() that returns lsp::TextDocumentPositionIdentifier
 
-    let pos = doc.position(view.id, offset_encoding);
+    let pos = helix_lsp::util::pos_to_lsp_pos(doc.text(), pos, offset_encoding);
 
     let future = match language_server.text_document_hover(doc.identifier(), pos, None) {
         Some(future) => future,
@@ -1190,8 +1589,8 @@ fn marked_string_to_markdown(contents: lsp::MarkedString) -> String {
                 // skip if contents empty
 
                 let contents = ui::Markdown::new(contents, editor.syn_loader.clone());
-                let popup = Popup::new("hover", contents).auto_close(true);
-                compositor.replace_or_push("hover", popup);
+                let popup = Popup::new(id, contents).auto_close(true).position(anchor);
+                compositor.replace_or_push(id, popup);
             }
         },
     );
@@ -1235,10 +1634,25 @@ pub fn rename_symbol(cx: &mut Context) {
                         return;
                     }
                 };
-            match block_on(future) {
-                Ok(edits) => apply_workspace_edit(cx.editor, offset_encoding, &edits),
-                Err(err) => cx.editor.set_error(err.to_string()),
-            }
+            let edits = match block_on(future) {
+                Ok(edits) => edits,
+                Err(err) => return cx.editor.set_error(err.to_string()),
+            };
+            let callback = async move {
+                let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+                    move |editor: &mut Editor, compositor: &mut Compositor| {
+                        apply_or_preview_workspace_edit(
+                            editor,
+                            compositor,
+                            offset_encoding,
+                            &edits,
+                            None,
+                        );
+                    },
+                ));
+                Ok(call)
+            };
+            cx.jobs.callback(callback);
         },
     );
 }
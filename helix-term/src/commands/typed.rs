@@ -7,8 +7,9 @@
 use helix_view::{
     apply_transaction,
     editor::{Action, CloseError, ConfigEvent},
+    session::{self, Session},
 };
-use ui::completers::{self, Completer};
+use ui::completers::{self, Completer, CompletionKind};
 
 #[derive(Clone)]
 pub struct TypableCommand {
@@ -18,6 +19,7 @@ pub struct TypableCommand {
     // params, flags, helper, completer
     pub fun: fn(&mut compositor::Context, &[Cow<str>], PromptEvent) -> anyhow::Result<()>,
     pub completer: Option<Completer>,
+    pub completion: CompletionKind,
 }
 
 fn quit(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
@@ -30,8 +32,9 @@ fn quit(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
     ensure!(args.is_empty(), ":quit takes no arguments");
 
     // last view and we have unsaved changes
-    if cx.editor.tree.views().count() == 1 {
-        buffers_remaining_impl(cx.editor)?
+    if cx.editor.tree.all_views().count() == 1 {
+        buffers_remaining_impl(cx.editor)?;
+        save_session_on_quit(cx)?;
     }
 
     cx.block_try_flush_writes()?;
@@ -51,6 +54,9 @@ fn force_quit(
 
     ensure!(args.is_empty(), ":quit! takes no arguments");
 
+    if cx.editor.tree.all_views().count() == 1 {
+        save_session_on_quit(cx)?;
+    }
     cx.block_try_flush_writes()?;
     cx.editor.close(view!(cx.editor).id);
 
@@ -72,7 +78,7 @@ fn open(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
             let callback = async move {
                 let call: job::Callback = job::Callback::EditorCompositor(Box::new(
                     move |editor: &mut Editor, compositor: &mut Compositor| {
-                        let picker = ui::file_picker(path, &editor.config());
+                        let picker = ui::file_picker(path, editor);
                         compositor.push(Box::new(overlayed(picker)));
                     },
                 ));
@@ -92,7 +98,7 @@ fn open(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) ->
     Ok(())
 }
 
-fn buffer_close_by_ids_impl(
+pub fn buffer_close_by_ids_impl(
     cx: &mut compositor::Context,
     doc_ids: &[DocumentId],
     force: bool,
@@ -194,6 +200,7 @@ fn buffer_gather_others_impl(editor: &mut Editor) -> Vec<DocumentId> {
     let current_document = &doc!(editor).id();
     editor
         .documents()
+        .filter(|doc| !doc.is_pinned())
         .map(|doc| doc.id())
         .filter(|doc_id| doc_id != current_document)
         .collect()
@@ -225,6 +232,63 @@ fn force_buffer_close_others(
     buffer_close_by_ids_impl(cx, &document_ids, true)
 }
 
+/// The buffers after the current one in the bufferline, but the pinned ones.
+fn buffer_gather_right_impl(editor: &mut Editor) -> Vec<DocumentId> {
+    let current_document = doc!(editor).id();
+    editor
+        .buffers_in_order()
+        .into_iter()
+        .skip_while(|doc| doc.id() != current_document)
+        .skip(1)
+        .filter(|doc| !doc.is_pinned())
+        .map(|doc| doc.id())
+        .collect()
+}
+
+fn buffer_close_right(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let document_ids = buffer_gather_right_impl(cx.editor);
+    buffer_close_by_ids_impl(cx, &document_ids, false)
+}
+
+fn force_buffer_close_right(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let document_ids = buffer_gather_right_impl(cx.editor);
+    buffer_close_by_ids_impl(cx, &document_ids, true)
+}
+
+fn buffer_close_unmodified(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let document_ids: Vec<_> = cx
+        .editor
+        .documents()
+        .filter(|doc| !doc.is_modified() && !doc.is_pinned())
+        .map(|doc| doc.id())
+        .collect();
+    buffer_close_by_ids_impl(cx, &document_ids, false)
+}
+
 fn buffer_gather_all_impl(editor: &mut Editor) -> Vec<DocumentId> {
     editor.documents().map(|doc| doc.id()).collect()
 }
@@ -255,6 +319,27 @@ fn force_buffer_close_all(
     buffer_close_by_ids_impl(cx, &document_ids, true)
 }
 
+fn buffer_pin(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let doc = doc_mut!(cx.editor);
+    let pinned = !doc.is_pinned();
+    doc.set_pinned(pinned);
+    let name = doc.display_name().into_owned();
+    if pinned {
+        cx.editor.set_status(format!("Pinned '{}'", name));
+    } else {
+        cx.editor.set_status(format!("Unpinned '{}'", name));
+    }
+    Ok(())
+}
+
 fn buffer_next(
     cx: &mut compositor::Context,
     _args: &[Cow<str>],
@@ -582,6 +667,31 @@ pub fn write_all_impl(
     cx: &mut compositor::Context,
     force: bool,
     write_scratch: bool,
+) -> anyhow::Result<()> {
+    write_documents_impl(cx, force, write_scratch, |_| true)
+}
+
+/// Writes the modified buffers saved automatically: `doc_id` or all of them, except the
+/// ones of languages with `auto-save = false`.
+pub fn auto_save_impl(
+    cx: &mut compositor::Context,
+    doc_id: Option<DocumentId>,
+) -> anyhow::Result<()> {
+    write_documents_impl(cx, false, false, |doc| {
+        doc_id.map_or(true, |id| doc.id() == id)
+            && doc
+                .language_config()
+                .and_then(|config| config.auto_save)
+                .unwrap_or(true)
+    })
+}
+
+/// Writes the modified buffers for which `filter` returns true.
+fn write_documents_impl(
+    cx: &mut compositor::Context,
+    force: bool,
+    write_scratch: bool,
+    filter: impl Fn(&Document) -> bool,
 ) -> anyhow::Result<()> {
     let mut errors: Vec<&'static str> = Vec::new();
     let auto_format = cx.editor.config().auto_format;
@@ -594,7 +704,7 @@ pub fn write_all_impl(
         .documents
         .values_mut()
         .filter_map(|doc| {
-            if !doc.is_modified() {
+            if !doc.is_modified() || !filter(doc) {
                 return None;
             }
             if doc.path().is_none() {
@@ -695,9 +805,15 @@ fn quit_all_impl(cx: &mut compositor::Context, force: bool) -> anyhow::Result<()
     if !force {
         buffers_remaining_impl(cx.editor)?;
     }
+    save_session_on_quit(cx)?;
 
-    // close all views
-    let views: Vec<_> = cx.editor.tree.views().map(|(view, _)| view.id).collect();
+    // close all views, including the ones of the other tab pages
+    let views: Vec<_> = cx
+        .editor
+        .tree
+        .all_views()
+        .map(|(view, _)| view.id)
+        .collect();
     for view_id in views {
         cx.editor.close(view_id);
     }
@@ -1034,6 +1150,36 @@ fn set_encoding(
     }
 }
 
+/// Reads the file of the [`Document`] again, decoding it with another encoding which is
+/// also used to write it.
+fn reopen_with_encoding(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+    ensure!(args.len() == 1, "Encoding required");
+
+    let scrolloff = cx.editor.config().scrolloff;
+    let redraw_handle = cx.editor.redraw_handle.clone();
+    let (view, doc) = current!(cx.editor);
+    if doc.is_modified() {
+        bail!("The buffer has unsaved changes, write or reload it first");
+    }
+    let previous = doc.encoding();
+    doc.set_encoding(&args[0])?;
+    if let Err(err) = doc.reload(view, &cx.editor.diff_providers, redraw_handle) {
+        doc.set_encoding(previous.name())?;
+        return Err(err);
+    }
+    view.ensure_cursor_in_view(doc, scrolloff);
+    let status = format!("Reopened with encoding {}", doc.encoding().name());
+    cx.editor.set_status(status);
+    Ok(())
+}
+
 /// Reload the [`Document`] from its source file.
 fn reload(
     cx: &mut compositor::Context,
@@ -1062,42 +1208,9 @@ fn reload_all(
         return Ok(());
     }
 
-    let scrolloff = cx.editor.config().scrolloff;
-    let view_id = view!(cx.editor).id;
-
-    let docs_view_ids: Vec<(DocumentId, Vec<ViewId>)> = cx
-        .editor
-        .documents_mut()
-        .map(|doc| {
-            let mut view_ids: Vec<_> = doc.selections().keys().cloned().collect();
-
-            if view_ids.is_empty() {
-                doc.ensure_view_init(view_id);
-                view_ids.push(view_id);
-            };
-
-            (doc.id(), view_ids)
-        })
-        .collect();
-
-    for (doc_id, view_ids) in docs_view_ids {
-        let doc = doc_mut!(cx.editor, &doc_id);
-
-        // Every doc is guaranteed to have at least 1 view at this point.
-        let view = view_mut!(cx.editor, view_ids[0]);
-
-        // Ensure that the view is synced with the document's history.
-        view.sync_changes(doc);
-
-        let redraw_handle = cx.editor.redraw_handle.clone();
-        doc.reload(view, &cx.editor.diff_providers, redraw_handle)?;
-
-        for view_id in view_ids {
-            let view = view_mut!(cx.editor, view_id);
-            if view.doc.eq(&doc_id) {
-                view.ensure_cursor_in_view(doc, scrolloff);
-            }
-        }
+    let doc_ids: Vec<DocumentId> = cx.editor.documents().map(|doc| doc.id()).collect();
+    for doc_id in doc_ids {
+        cx.editor.reload_document(doc_id)?;
     }
 
     Ok(())
@@ -1333,6 +1446,44 @@ fn hsplit_new(
     Ok(())
 }
 
+fn resize_split_impl(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    layout: tree::Layout,
+) -> anyhow::Result<()> {
+    ensure!(args.len() == 1, "Size required");
+    let size = args[0].parse::<u16>()?;
+    ensure!(
+        cx.editor.set_split_size(layout, size),
+        "The current window isn't split in that direction"
+    );
+    Ok(())
+}
+
+fn resize_width(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    resize_split_impl(cx, args, tree::Layout::Vertical)
+}
+
+fn resize_height(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    resize_split_impl(cx, args, tree::Layout::Horizontal)
+}
+
 fn debug_eval(
     cx: &mut compositor::Context,
     args: &[Cow<str>],
@@ -1520,6 +1671,26 @@ fn set_option(
     Ok(())
 }
 
+/// Override a config option in the current view only, or follow the config again
+/// without a value, for example `:set-view gutters none`.
+fn set_view_option(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (key, value) = match args {
+        [key] => (key.to_lowercase(), None),
+        [key, value] => (key.to_lowercase(), Some(value.as_ref())),
+        _ => anyhow::bail!("Bad arguments. Usage: `:set-view key [value]`"),
+    };
+    let view_id = view!(cx.editor).id;
+    cx.editor.set_view_option(view_id, &key, value)
+}
+
 /// Change the language of the current buffer at runtime.
 fn language(
     cx: &mut compositor::Context,
@@ -1618,12 +1789,14 @@ fn reflow(
 
     // Find the max line length by checking the following sources in order:
     //   - The passed argument in `args`
+    //   - The max_line_length of the .editorconfig files of the document
     //   - The configured max_line_len for this language in languages.toml
     //   - The const default we set above
     let max_line_len: usize = args
         .get(0)
         .map(|num| num.parse::<usize>())
         .transpose()?
+        .or(doc.editor_config().max_line_length)
         .or_else(|| {
             doc.language_config()
                 .and_then(|config| config.max_line_length)
@@ -1825,6 +1998,523 @@ fn run_shell_command(
     Ok(())
 }
 
+/// Returns the diff hunk under the primary cursor of the current document, with the diff
+/// base it is a change of.
+fn hunk_under_cursor(doc: &Document, view: &View) -> anyhow::Result<(Hunk, Rope)> {
+    let diff_handle = doc
+        .diff_handle()
+        .ok_or_else(|| anyhow!("Diff is not available in current buffer"))?;
+    let hunks = diff_handle.hunks();
+    ensure!(!hunks.is_inverted(), "Diff is inverted in current buffer");
+
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..));
+    let hunk = hunks
+        .hunk_at(line as u32, true)
+        .map(|idx| hunks.nth_hunk(idx))
+        .ok_or_else(|| anyhow!("No change under the cursor"))?;
+    Ok((hunk, diff_handle.diff_base()))
+}
+
+fn hunk_stage(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let path = doc
+        .path()
+        .cloned()
+        .ok_or_else(|| anyhow!("Cannot stage a buffer without a file"))?;
+    // the diff base of git is the index the hunk is applied to
+    let (hunk, diff_base) = hunk_under_cursor(doc, view)?;
+    let patch = hunk.to_unified_diff(diff_base.slice(..), doc.text().slice(..));
+    let (patch, _, _) = doc.encoding().encode(&patch);
+
+    cx.editor
+        .diff_providers
+        .stage_hunk(&path, &patch)
+        .map_err(|err| anyhow!("Failed to stage hunk: {}", err))?;
+    // the staged hunk leaves the gutter
+    if let Some(diff_base) = cx.editor.diff_providers.get_diff_base(&path) {
+        let redraw_handle = cx.editor.redraw_handle.clone();
+        doc_mut!(cx.editor).set_diff_base(diff_base, redraw_handle);
+    }
+    cx.editor.set_status("Staged hunk");
+    Ok(())
+}
+
+fn hunk_revert(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current!(cx.editor);
+    // the diff base of git is the index, the staged changes are kept
+    let (hunk, diff_base) = hunk_under_cursor(doc, view)?;
+    let text = doc.text();
+
+    let base_start = diff_base.line_to_char(hunk.before.start as usize);
+    let base_end = diff_base.line_to_char(hunk.before.end as usize);
+    let start = text.line_to_char(hunk.after.start as usize);
+    let end = text.line_to_char(hunk.after.end as usize);
+    let original: Tendril = diff_base.slice(base_start..base_end).to_string().into();
+
+    let transaction = Transaction::change(text, std::iter::once((start, end, Some(original))));
+    apply_transaction(&transaction, doc, view);
+    doc.append_changes_to_history(view);
+    Ok(())
+}
+
+fn blame(
+    cx: &mut compositor::Context,
+    _args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (view, doc) = current_ref!(cx.editor);
+    let path = doc
+        .path()
+        .cloned()
+        .ok_or_else(|| anyhow!("Cannot blame a buffer without a file"))?;
+    let line = doc
+        .selection(view.id)
+        .primary()
+        .cursor_line(doc.text().slice(..));
+    // the lines of the buffer may have moved since it was saved
+    let contents = doc.text().to_string().into_bytes();
+    let providers = cx.editor.diff_providers.clone();
+
+    let callback = async move {
+        let info = tokio::task::spawn_blocking(move || {
+            let info = providers.blame_line(&path, &contents, line);
+            (path, info)
+        })
+        .await?;
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| match info {
+                (path, Ok(Some(info))) => {
+                    let contents = ui::BlameInfo::new(editor, path, info);
+                    let popup = Popup::new(ui::BlameInfo::ID, contents).auto_close(true);
+                    compositor.replace_or_push(ui::BlameInfo::ID, popup);
+                }
+                (_, Ok(None)) => editor.set_error("Line is not committed yet"),
+                (_, Err(err)) => editor.set_error(format!("Failed to blame line: {}", err)),
+            },
+        ));
+        Ok(call)
+    };
+
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+/// A side of a diff: the buffer of its file if it is open, as it may have unsaved changes,
+/// or else the file itself, read by [`DiffSource::read`] off the UI thread.
+pub(crate) struct DiffSource {
+    path: PathBuf,
+    buffer: Option<ui::DiffSide>,
+}
+
+impl DiffSource {
+    pub(crate) fn new(editor: &Editor, path: &Path) -> anyhow::Result<Self> {
+        let path = helix_core::path::get_canonicalized_path(&helix_core::path::expand_tilde(path))?;
+        let buffer = editor.document_by_path(&path).map(|doc| ui::DiffSide {
+            title: diff_title(&path),
+            text: doc.text().clone(),
+            tab_width: doc.tab_width(),
+        });
+        Ok(Self { path, buffer })
+    }
+
+    /// The side of the diff, reading the file if its buffer is not open.
+    pub(crate) fn read(self) -> anyhow::Result<ui::DiffSide> {
+        if let Some(side) = self.buffer {
+            return Ok(side);
+        }
+        let mut file = std::fs::File::open(&self.path)
+            .map_err(|err| anyhow!("Failed to open {}: {}", self.path.display(), err))?;
+        let (text, _) = helix_view::document::from_reader(&mut file, None)?;
+        Ok(ui::DiffSide {
+            title: diff_title(&self.path),
+            text,
+            tab_width: 4,
+        })
+    }
+}
+
+fn diff_title(path: &Path) -> String {
+    helix_core::path::get_relative_path(path)
+        .to_string_lossy()
+        .into_owned()
+}
+
+/// The version of `side` in the diff base, the index in git repositories.
+fn diff_base_side(
+    providers: &DiffProviderRegistry,
+    path: &Path,
+    side: &ui::DiffSide,
+) -> anyhow::Result<ui::DiffSide> {
+    let diff_base = providers
+        .get_diff_base(path)
+        .ok_or_else(|| anyhow!("{} is not tracked by version control", side.title))?;
+    let (text, _) = helix_view::document::from_reader(&mut diff_base.as_slice(), None)?;
+    Ok(ui::DiffSide {
+        title: format!("{} (base)", side.title),
+        text,
+        tab_width: side.tab_width,
+    })
+}
+
+/// Reads the sides of a diff in the background then shows them side by side, `before`
+/// being the diff base of `after` when it is `None`.
+pub(crate) fn open_diff(
+    cx: &mut compositor::Context,
+    before: Option<DiffSource>,
+    after: DiffSource,
+) {
+    let providers = cx.editor.diff_providers.clone();
+    cx.jobs.callback(async move {
+        let diff = tokio::task::spawn_blocking(move || {
+            let path = after.path.clone();
+            let after = after.read()?;
+            let before = match before {
+                Some(before) => before.read()?,
+                None => diff_base_side(&providers, &path, &after)?,
+            };
+            anyhow::Ok(ui::DiffView::new(before, after))
+        })
+        .await?;
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| match diff {
+                Ok(diff) if !diff.has_changes() => editor.set_status("No differences"),
+                Ok(diff) => compositor.replace_or_push(ui::DiffView::ID, diff),
+                Err(err) => editor.set_error(format!("{}", err)),
+            },
+        ));
+        Ok(call)
+    });
+}
+
+fn diff(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let (before, after) = match args {
+        [] => {
+            let path = doc!(cx.editor)
+                .path()
+                .ok_or_else(|| anyhow!("Cannot diff a buffer without a file"))?;
+            (None, DiffSource::new(cx.editor, path)?)
+        }
+        [path] => (None, DiffSource::new(cx.editor, Path::new(path.as_ref()))?),
+        [before, after] => (
+            Some(DiffSource::new(cx.editor, Path::new(before.as_ref()))?),
+            DiffSource::new(cx.editor, Path::new(after.as_ref()))?,
+        ),
+        _ => bail!("Bad arguments. Usage: `:diff [file] [other-file]`"),
+    };
+    open_diff(cx, before, after);
+
+    Ok(())
+}
+
+/// Computes the gutter signs of the current buffer against a file instead of version
+/// control, or against version control again without a file.
+fn diff_against(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let redraw_handle = cx.editor.redraw_handle.clone();
+    let (diff_base, status) = match args {
+        [] => {
+            let path = doc!(cx.editor)
+                .path()
+                .ok_or_else(|| anyhow!("Cannot diff a buffer without a file"))?;
+            let diff_base = cx
+                .editor
+                .diff_providers
+                .get_diff_base(path)
+                .ok_or_else(|| anyhow!("{} is not tracked by version control", path.display()))?;
+            (diff_base, "Diffing against version control".to_string())
+        }
+        [path] => {
+            let path = helix_core::path::expand_tilde(Path::new(path.as_ref()));
+            let diff_base = std::fs::read(&path)
+                .map_err(|err| anyhow!("Failed to read {}: {}", path.display(), err))?;
+            (diff_base, format!("Diffing against {}", path.display()))
+        }
+        _ => bail!("Bad arguments. Usage: `:diff-against [file]`"),
+    };
+    doc_mut!(cx.editor).set_diff_base(diff_base, redraw_handle);
+    cx.editor.set_status(status);
+    Ok(())
+}
+
+fn search_replace(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let query = args.join(" ");
+    if !query.is_empty() {
+        ui::SearchReplace::search(&query, cx.editor, cx.jobs)?;
+    }
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                let panel = if query.is_empty() {
+                    let mut panel = ui::SearchReplace::new();
+                    panel.edit_search(editor);
+                    panel
+                } else {
+                    ui::SearchReplace::with_query(query)
+                };
+                compositor.replace_or_push(ui::SearchReplace::ID, panel);
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn preview_image(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = match args {
+        [] => doc!(cx.editor)
+            .path()
+            .cloned()
+            .ok_or_else(|| anyhow!("Cannot preview a buffer without a file"))?,
+        [path] => helix_core::path::expand_tilde(Path::new(path.as_ref())),
+        _ => bail!("Bad arguments. Usage: `:preview-image [file]`"),
+    };
+    let protocol = cx.editor.image_protocol;
+    let callback = async move {
+        let preview =
+            tokio::task::spawn_blocking(move || ui::ImagePreview::new(path, protocol)).await?;
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| match preview {
+                Ok(preview) => compositor.replace_or_push(ui::ImagePreview::ID, overlayed(preview)),
+                Err(err) => editor.set_error(format!("Failed to preview the image: {}", err)),
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+
+    Ok(())
+}
+
+fn explorer(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let command = ui::ExplorerCommand::parse(args)?;
+    cx.jobs.callback(async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                    editor_view.explorer_command(command);
+                }
+            },
+        ));
+        Ok(call)
+    });
+    Ok(())
+}
+
+fn tab_new(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    match args {
+        [] => {
+            let doc_id = doc!(cx.editor).id();
+            cx.editor.new_tab(doc_id);
+        }
+        [path] => {
+            let path = helix_core::path::expand_tilde(Path::new(path.as_ref()));
+            // a directory becomes the root of the file explorer of the tab page
+            if path.is_dir() {
+                let root = helix_core::path::get_canonicalized_path(&path)?;
+                let doc_id = doc!(cx.editor).id();
+                cx.editor.new_tab(doc_id);
+                cx.editor.tree.tab_mut().explorer_root = Some(root);
+            } else {
+                let doc_id = cx.editor.open(&path, Action::Load)?;
+                cx.editor.new_tab(doc_id);
+            }
+        }
+        _ => bail!("Bad arguments. Usage: `:tab-new [file-or-directory]`"),
+    }
+    Ok(())
+}
+
+/// Parses the 1-based index of a tab page, the active tab page by default.
+fn tab_index(editor: &Editor, args: &[Cow<str>]) -> anyhow::Result<usize> {
+    match args {
+        [] => Ok(editor.tree.active_tab()),
+        [index] => match index.parse::<usize>() {
+            Ok(index) if (1..=editor.tree.tabs().len()).contains(&index) => Ok(index - 1),
+            _ => bail!("No tab page {}", index),
+        },
+        _ => bail!("Bad arguments. Expected at most one tab page number"),
+    }
+}
+
+fn tab_close(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let index = tab_index(cx.editor, args)?;
+    ensure!(
+        cx.editor.tree.tabs().len() > 1,
+        "Cannot close the last tab page"
+    );
+    cx.editor.close_tab(index);
+    Ok(())
+}
+
+fn tab(cx: &mut compositor::Context, args: &[Cow<str>], event: PromptEvent) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    ensure!(!args.is_empty(), "Bad arguments. Usage: `:tab <number>`");
+    let index = tab_index(cx.editor, args)?;
+    cx.editor.switch_tab(index);
+    Ok(())
+}
+
+/// The session file given as argument, the one of the working directory by default.
+fn session_path(args: &[Cow<str>]) -> anyhow::Result<PathBuf> {
+    match args {
+        [] => Ok(session::session_file(&std::env::current_dir()?)),
+        [path] => Ok(helix_core::path::expand_tilde(Path::new(path.as_ref()))),
+        _ => bail!("Bad arguments. Expected at most one session file"),
+    }
+}
+
+/// Saves the session of the editor to `path`, along with the explorer of the editor
+/// view. The editor is captured right away, so that it can be saved while quitting.
+fn save_session(cx: &mut compositor::Context, path: PathBuf) {
+    let mut session = Session::capture(cx.editor);
+    let job = Job::with_callback(async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |editor: &mut Editor, compositor: &mut Compositor| {
+                if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                    session.explorer = editor_view.explorer_session();
+                }
+                match session.save(&path) {
+                    Ok(()) => editor.set_status(format!("Saved session '{}'", path.display())),
+                    Err(err) => editor.set_error(format!("{:#}", err)),
+                }
+            },
+        ));
+        Ok(call)
+    });
+    cx.jobs.add(job.wait_before_exiting());
+}
+
+/// Saves the session of the working directory before the last view is closed.
+fn save_session_on_quit(cx: &mut compositor::Context) -> anyhow::Result<()> {
+    if cx.editor.config().auto_session {
+        save_session(cx, session::session_file(&std::env::current_dir()?));
+    }
+    Ok(())
+}
+
+fn session_save(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = session_path(args)?;
+    save_session(cx, path);
+    Ok(())
+}
+
+fn session_load(
+    cx: &mut compositor::Context,
+    args: &[Cow<str>],
+    event: PromptEvent,
+) -> anyhow::Result<()> {
+    if event != PromptEvent::Validate {
+        return Ok(());
+    }
+
+    let path = session_path(args)?;
+    let session = Session::load(&path)?;
+    session.restore(cx.editor)?;
+    if let Some(explorer) = session.explorer {
+        cx.jobs.callback(async move {
+            let call: job::Callback = Callback::EditorCompositor(Box::new(
+                move |_editor: &mut Editor, compositor: &mut Compositor| {
+                    if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                        editor_view.restore_explorer(explorer);
+                    }
+                },
+            ));
+            Ok(call)
+        });
+    }
+    cx.editor
+        .set_status(format!("Loaded session '{}'", path.display()));
+    Ok(())
+}
+
 pub const TYPABLE_COMMAND_LIST: &[TypableCommand] = &[
         TypableCommand {
             name: "quit",
@@ -1832,6 +2522,7 @@ fn run_shell_command(
             doc: "Close the current view.",
             fun: quit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "quit!",
@@ -1839,6 +2530,7 @@ fn run_shell_command(
             doc: "Force close the current view, ignoring unsaved changes.",
             fun: force_quit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "open",
@@ -1846,6 +2538,7 @@ fn run_shell_command(
             doc: "Open a file from disk into the current view.",
             fun: open,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "buffer-close",
@@ -1853,6 +2546,7 @@ fn run_shell_command(
             doc: "Close the current buffer.",
             fun: buffer_close,
             completer: Some(completers::buffer),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-close!",
@@ -1860,20 +2554,47 @@ fn run_shell_command(
             doc: "Close the current buffer forcefully, ignoring unsaved changes.",
             fun: force_buffer_close,
             completer: Some(completers::buffer),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-close-others",
             aliases: &["bco", "bcloseother"],
-            doc: "Close all buffers but the currently focused one.",
+            doc: "Close all buffers but the currently focused one and the pinned ones.",
             fun: buffer_close_others,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-close-others!",
             aliases: &["bco!", "bcloseother!"],
-            doc: "Force close all buffers but the currently focused one.",
+            doc: "Force close all buffers but the currently focused one and the pinned ones.",
             fun: force_buffer_close_others,
             completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "buffer-close-right",
+            aliases: &["bcr", "bcloseright"],
+            doc: "Close all buffers right of the currently focused one in the bufferline, but the pinned ones.",
+            fun: buffer_close_right,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "buffer-close-right!",
+            aliases: &["bcr!", "bcloseright!"],
+            doc: "Force close all buffers right of the currently focused one in the bufferline, but the pinned ones.",
+            fun: force_buffer_close_right,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "buffer-close-unmodified",
+            aliases: &["bcu", "bcloseunmodified"],
+            doc: "Close all buffers without unsaved changes, but the pinned ones.",
+            fun: buffer_close_unmodified,
+            completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-close-all",
@@ -1881,6 +2602,7 @@ fn run_shell_command(
             doc: "Close all buffers without quitting.",
             fun: buffer_close_all,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-close-all!",
@@ -1888,6 +2610,15 @@ fn run_shell_command(
             doc: "Force close all buffers ignoring unsaved changes without quitting.",
             fun: force_buffer_close_all,
             completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "buffer-pin",
+            aliases: &["bpin"],
+            doc: "Pin the current buffer to the start of the bufferline, keeping it open with :buffer-close-others, or unpin it.",
+            fun: buffer_pin,
+            completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-next",
@@ -1895,6 +2626,7 @@ fn run_shell_command(
             doc: "Goto next buffer.",
             fun: buffer_next,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "buffer-previous",
@@ -1902,6 +2634,7 @@ fn run_shell_command(
             doc: "Goto previous buffer.",
             fun: buffer_previous,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "write",
@@ -1909,6 +2642,7 @@ fn run_shell_command(
             doc: "Write changes to disk. Accepts an optional path (:write some/path.txt)",
             fun: write,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "write!",
@@ -1916,6 +2650,7 @@ fn run_shell_command(
             doc: "Force write changes to disk creating necessary subdirectories. Accepts an optional path (:write some/path.txt)",
             fun: force_write,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "new",
@@ -1923,6 +2658,7 @@ fn run_shell_command(
             doc: "Create a new scratch buffer.",
             fun: new_file,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "format",
@@ -1930,6 +2666,7 @@ fn run_shell_command(
             doc: "Format the file using the LSP formatter.",
             fun: format,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "indent-style",
@@ -1937,6 +2674,7 @@ fn run_shell_command(
             doc: "Set the indentation style for editing. ('t' for tabs or 1-8 for number of spaces.)",
             fun: set_indent_style,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "line-ending",
@@ -1947,6 +2685,7 @@ fn run_shell_command(
             doc: "Set the document's default line ending. Options: crlf, lf, cr, ff, nel.",
             fun: set_line_ending,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "earlier",
@@ -1954,6 +2693,7 @@ fn run_shell_command(
             doc: "Jump back to an earlier point in edit history. Accepts a number of steps or a time span.",
             fun: earlier,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "later",
@@ -1961,6 +2701,7 @@ fn run_shell_command(
             doc: "Jump to a later point in edit history. Accepts a number of steps or a time span.",
             fun: later,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "write-quit",
@@ -1968,6 +2709,7 @@ fn run_shell_command(
             doc: "Write changes to disk and close the current view. Accepts an optional path (:wq some/path.txt)",
             fun: write_quit,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "write-quit!",
@@ -1975,6 +2717,7 @@ fn run_shell_command(
             doc: "Write changes to disk and close the current view forcefully. Accepts an optional path (:wq! some/path.txt)",
             fun: force_write_quit,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "write-all",
@@ -1982,6 +2725,7 @@ fn run_shell_command(
             doc: "Write changes from all buffers to disk.",
             fun: write_all,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "write-quit-all",
@@ -1989,6 +2733,7 @@ fn run_shell_command(
             doc: "Write changes from all buffers to disk and close all views.",
             fun: write_all_quit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "write-quit-all!",
@@ -1996,6 +2741,7 @@ fn run_shell_command(
             doc: "Write changes from all buffers to disk and close all views forcefully (ignoring unsaved changes).",
             fun: force_write_all_quit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "quit-all",
@@ -2003,6 +2749,7 @@ fn run_shell_command(
             doc: "Close all views.",
             fun: quit_all,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "quit-all!",
@@ -2010,6 +2757,7 @@ fn run_shell_command(
             doc: "Force close all views ignoring unsaved changes.",
             fun: force_quit_all,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "cquit",
@@ -2017,6 +2765,7 @@ fn run_shell_command(
             doc: "Quit with exit code (default 1). Accepts an optional integer exit code (:cq 2).",
             fun: cquit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "cquit!",
@@ -2024,6 +2773,7 @@ fn run_shell_command(
             doc: "Force quit with exit code (default 1) ignoring unsaved changes. Accepts an optional integer exit code (:cq! 2).",
             fun: force_cquit,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "theme",
@@ -2031,6 +2781,7 @@ fn run_shell_command(
             doc: "Change the editor theme (show current theme if no name specified).",
             fun: theme,
             completer: Some(completers::theme),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "clipboard-yank",
@@ -2038,6 +2789,7 @@ fn run_shell_command(
             doc: "Yank main selection into system clipboard.",
             fun: yank_main_selection_to_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "clipboard-yank-join",
@@ -2045,6 +2797,7 @@ fn run_shell_command(
             doc: "Yank joined selections into system clipboard. A separator can be provided as first argument. Default value is newline.", // FIXME: current UI can't display long doc.
             fun: yank_joined_to_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "primary-clipboard-yank",
@@ -2052,6 +2805,7 @@ fn run_shell_command(
             doc: "Yank main selection into system primary clipboard.",
             fun: yank_main_selection_to_primary_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "primary-clipboard-yank-join",
@@ -2059,6 +2813,7 @@ fn run_shell_command(
             doc: "Yank joined selections into system primary clipboard. A separator can be provided as first argument. Default value is newline.", // FIXME: current UI can't display long doc.
             fun: yank_joined_to_primary_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "clipboard-paste-after",
@@ -2066,6 +2821,7 @@ fn run_shell_command(
             doc: "Paste system clipboard after selections.",
             fun: paste_clipboard_after,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "clipboard-paste-before",
@@ -2073,6 +2829,7 @@ fn run_shell_command(
             doc: "Paste system clipboard before selections.",
             fun: paste_clipboard_before,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "clipboard-paste-replace",
@@ -2080,6 +2837,7 @@ fn run_shell_command(
             doc: "Replace selections with content of system clipboard.",
             fun: replace_selections_with_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "primary-clipboard-paste-after",
@@ -2087,6 +2845,7 @@ fn run_shell_command(
             doc: "Paste primary clipboard after selections.",
             fun: paste_primary_clipboard_after,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "primary-clipboard-paste-before",
@@ -2094,6 +2853,7 @@ fn run_shell_command(
             doc: "Paste primary clipboard before selections.",
             fun: paste_primary_clipboard_before,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "primary-clipboard-paste-replace",
@@ -2101,6 +2861,7 @@ fn run_shell_command(
             doc: "Replace selections with content of system primary clipboard.",
             fun: replace_selections_with_primary_clipboard,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "show-clipboard-provider",
@@ -2108,6 +2869,7 @@ fn run_shell_command(
             doc: "Show clipboard provider name in status bar.",
             fun: show_clipboard_provider,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "change-current-directory",
@@ -2115,6 +2877,7 @@ fn run_shell_command(
             doc: "Change the current working directory.",
             fun: change_current_directory,
             completer: Some(completers::directory),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "show-directory",
@@ -2122,13 +2885,23 @@ fn run_shell_command(
             doc: "Show the current working directory.",
             fun: show_current_directory,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "encoding",
-            aliases: &[],
-            doc: "Set encoding. Based on `https://encoding.spec.whatwg.org`.",
+            aliases: &["set-encoding"],
+            doc: "Set encoding, which the buffer is written with. Based on `https://encoding.spec.whatwg.org`.",
             fun: set_encoding,
-            completer: None,
+            completer: Some(completers::encoding),
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "reopen-with-encoding",
+            aliases: &[],
+            doc: "Read the file of the buffer again with another encoding, which it is also written with.",
+            fun: reopen_with_encoding,
+            completer: Some(completers::encoding),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "reload",
@@ -2136,6 +2909,7 @@ fn run_shell_command(
             doc: "Discard changes and reload from the source file.",
             fun: reload,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "reload-all",
@@ -2143,6 +2917,7 @@ fn run_shell_command(
             doc: "Discard changes and reload all documents from the source files.",
             fun: reload_all,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "update",
@@ -2150,6 +2925,7 @@ fn run_shell_command(
             doc: "Write changes only if the file has been modified.",
             fun: update,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "lsp-workspace-command",
@@ -2157,6 +2933,7 @@ fn run_shell_command(
             doc: "Open workspace command picker",
             fun: lsp_workspace_command,
             completer: Some(completers::lsp_workspace_command),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "lsp-restart",
@@ -2164,6 +2941,7 @@ fn run_shell_command(
             doc: "Restarts the Language Server that is in use by the current doc",
             fun: lsp_restart,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "tree-sitter-scopes",
@@ -2171,6 +2949,7 @@ fn run_shell_command(
             doc: "Display tree sitter scopes, primarily for theming and development.",
             fun: tree_sitter_scopes,
             completer: None,
+            completion: CompletionKind::Other,
        },
         TypableCommand {
             name: "debug-start",
@@ -2178,6 +2957,7 @@ fn run_shell_command(
             doc: "Start a debug session from a given template with given parameters.",
             fun: debug_start,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "debug-remote",
@@ -2185,6 +2965,7 @@ fn run_shell_command(
             doc: "Connect to a debug adapter by TCP address and start a debugging session from a given template with given parameters.",
             fun: debug_remote,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "debug-eval",
@@ -2192,6 +2973,7 @@ fn run_shell_command(
             doc: "Evaluate expression in current debug context.",
             fun: debug_eval,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "vsplit",
@@ -2199,6 +2981,7 @@ fn run_shell_command(
             doc: "Open the file in a vertical split.",
             fun: vsplit,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "vsplit-new",
@@ -2206,6 +2989,47 @@ fn run_shell_command(
             doc: "Open a scratch buffer in a vertical split.",
             fun: vsplit_new,
             completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "tab-new",
+            aliases: &["tabnew"],
+            doc: "Open a tab page with the current buffer, or with a file. With a directory, the file explorer of the tab page shows that directory.",
+            fun: tab_new,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "tab-close",
+            aliases: &["tabclose"],
+            doc: "Close the current tab page, or the tab page with the given number, and its windows.",
+            fun: tab_close,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "tab",
+            aliases: &[],
+            doc: "Go to the tab page with the given number.",
+            fun: tab,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "session-save",
+            aliases: &[],
+            doc: "Save the open buffers, windows, cursors, jumplists and explorer to a session file, the one of the working directory by default.",
+            fun: session_save,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "session-load",
+            aliases: &[],
+            doc: "Replace the open windows with the ones of a session file, the one of the working directory by default.",
+            fun: session_load,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "hsplit",
@@ -2213,6 +3037,7 @@ fn run_shell_command(
             doc: "Open the file in a horizontal split.",
             fun: hsplit,
             completer: Some(completers::filename),
+            completion: CompletionKind::Path,
         },
         TypableCommand {
             name: "hsplit-new",
@@ -2220,6 +3045,23 @@ fn run_shell_command(
             doc: "Open a scratch buffer in a horizontal split.",
             fun: hsplit_new,
             completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "resize-width",
+            aliases: &[],
+            doc: "Set the width of the current window in columns.",
+            fun: resize_width,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "resize-height",
+            aliases: &[],
+            doc: "Set the height of the current window in rows.",
+            fun: resize_height,
+            completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "tutor",
@@ -2227,6 +3069,7 @@ fn run_shell_command(
             doc: "Open the tutorial.",
             fun: tutor,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "goto",
@@ -2234,6 +3077,7 @@ fn run_shell_command(
             doc: "Goto line number.",
             fun: goto_line_number,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "set-language",
@@ -2241,6 +3085,7 @@ fn run_shell_command(
             doc: "Set the language of current buffer.",
             fun: language,
             completer: Some(completers::language),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "set-option",
@@ -2248,6 +3093,7 @@ fn run_shell_command(
             doc: "Set a config option at runtime.\nFor example to disable smart case search, use `:set search.smart-case false`.",
             fun: set_option,
             completer: Some(completers::setting),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "get-option",
@@ -2255,6 +3101,15 @@ fn run_shell_command(
             doc: "Get the current value of a config option.",
             fun: get_option,
             completer: Some(completers::setting),
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "set-view",
+            aliases: &[],
+            doc: "Override `soft-wrap`, `line-number`, `gutters` or `whitespace` in the current view only, or follow the config again without a value.\nFor example to hide the gutters, use `:set-view gutters none`.",
+            fun: set_view_option,
+            completer: Some(completers::view_option),
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "sort",
@@ -2262,6 +3117,7 @@ fn run_shell_command(
             doc: "Sort ranges in selection.",
             fun: sort,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "rsort",
@@ -2269,6 +3125,7 @@ fn run_shell_command(
             doc: "Sort ranges in selection in reverse order.",
             fun: sort_reverse,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "reflow",
@@ -2276,6 +3133,7 @@ fn run_shell_command(
             doc: "Hard-wrap the current selection of lines to a given width.",
             fun: reflow,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "tree-sitter-subtree",
@@ -2283,6 +3141,7 @@ fn run_shell_command(
             doc: "Display tree sitter subtree under cursor, primarily for debugging queries.",
             fun: tree_sitter_subtree,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "config-reload",
@@ -2290,6 +3149,7 @@ fn run_shell_command(
             doc: "Refresh user config.",
             fun: refresh_config,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "config-open",
@@ -2297,6 +3157,7 @@ fn run_shell_command(
             doc: "Open the user config.toml file.",
             fun: open_config,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "log-open",
@@ -2304,6 +3165,7 @@ fn run_shell_command(
             doc: "Open the helix log file.",
             fun: open_log,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "insert-output",
@@ -2311,6 +3173,7 @@ fn run_shell_command(
             doc: "Run shell command, inserting output before each selection.",
             fun: insert_output,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "append-output",
@@ -2318,6 +3181,7 @@ fn run_shell_command(
             doc: "Run shell command, appending output after each selection.",
             fun: append_output,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "pipe",
@@ -2325,6 +3189,7 @@ fn run_shell_command(
             doc: "Pipe each selection to the shell command.",
             fun: pipe,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "pipe-to",
@@ -2332,6 +3197,7 @@ fn run_shell_command(
             doc: "Pipe each selection to the shell command, ignoring output.",
             fun: pipe_to,
             completer: None,
+            completion: CompletionKind::Other,
         },
         TypableCommand {
             name: "run-shell-command",
@@ -2339,6 +3205,71 @@ fn run_shell_command(
             doc: "Run a shell command",
             fun: run_shell_command,
             completer: Some(completers::directory),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "hunk-stage",
+            aliases: &[],
+            doc: "Stage the diff hunk under the cursor.",
+            fun: hunk_stage,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "hunk-revert",
+            aliases: &[],
+            doc: "Revert the diff hunk under the cursor to the diff base, the staged contents in git repositories.",
+            fun: hunk_revert,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "blame",
+            aliases: &[],
+            doc: "Show the commit that last changed the line under the cursor. Press enter in the popup to open the full commit.",
+            fun: blame,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "diff",
+            aliases: &[],
+            doc: "Show two files side by side with their differences highlighted. With a single file, or none for the current buffer, it is compared to its version in HEAD.",
+            fun: diff,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "diff-against",
+            aliases: &[],
+            doc: "Show the changes of the current buffer against a file in the gutter, instead of against version control. Without a file, go back to version control.",
+            fun: diff_against,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "search-replace",
+            aliases: &[],
+            doc: "Search the workspace for a regex, or open the search prompt, and review the matches to replace them.",
+            fun: search_replace,
+            completer: None,
+            completion: CompletionKind::Other,
+        },
+        TypableCommand {
+            name: "preview-image",
+            aliases: &[],
+            doc: "Show an image file, or the current buffer's file, in a popup. Terminals without the kitty or iTerm2 image protocols show its metadata instead.",
+            fun: preview_image,
+            completer: Some(completers::filename),
+            completion: CompletionKind::Path,
+        },
+        TypableCommand {
+            name: "explorer",
+            aliases: &[],
+            doc: "Control the file explorer: `open`, `close`, `toggle`, `reveal [path]` selects a file, the current buffer's by default, `root <path>` shows a directory, `refresh` and `focus`.",
+            fun: explorer,
+            completer: Some(completers::explorer),
+            completion: CompletionKind::Other,
         },
     ];
 
@@ -2353,6 +3284,22 @@ fn run_shell_command(
             .collect()
     });
 
+/// Runs a command line like the ones of the command prompt, without the `:`.
+pub fn execute_command_line(cx: &mut compositor::Context, input: &str) -> anyhow::Result<()> {
+    use shellwords::Shellwords;
+
+    let shellwords = Shellwords::from(input);
+    let words = shellwords.words();
+    let name = match words.first() {
+        Some(name) => name,
+        None => bail!("empty command"),
+    };
+    match TYPABLE_COMMAND_MAP.get(name.as_ref()) {
+        Some(command) => (command.fun)(cx, &words[1..], PromptEvent::Validate),
+        None => bail!("no such command: '{}'", name),
+    }
+}
+
 #[allow(clippy::unnecessary_unwrap)]
 pub(super) fn command_mode(cx: &mut Context) {
     use shellwords::Shellwords;
@@ -2459,6 +3406,18 @@ pub(super) fn command_mode(cx: &mut Context) {
         None
     });
 
+    // the arguments of the commands taking paths, not the names of the commands
+    prompt.icons_fn = Box::new(|input: &str| match input.split_once(' ') {
+        Some((command, _)) => matches!(
+            typed::TYPABLE_COMMAND_MAP.get(command),
+            Some(typed::TypableCommand {
+                completion: CompletionKind::Path,
+                ..
+            })
+        ),
+        None => false,
+    });
+
     // Calculate initial completion
     prompt.recalculate_completion(cx.editor);
     cx.push_layer(Box::new(prompt));
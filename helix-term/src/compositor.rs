@@ -71,6 +71,12 @@ fn type_name(&self) -> &'static str {
     fn id(&self) -> Option<&'static str> {
         None
     }
+
+    /// Layers with a higher z-index are kept in front of the ones with a lower z-index,
+    /// see [helix_view::floating::FloatingWindow].
+    fn z_index(&self) -> u16 {
+        0
+    }
 }
 
 pub struct Compositor {
@@ -97,12 +103,19 @@ pub fn resize(&mut self, area: Rect) {
         self.area = area;
     }
 
-    /// Add a layer to be rendered in front of all existing layers.
+    /// Add a layer to be rendered in front of all existing layers with the same or a lower
+    /// z-index.
     pub fn push(&mut self, mut layer: Box<dyn Component>) {
         let size = self.size();
         // trigger required_size on init
         layer.required_size((size.width, size.height));
-        self.layers.push(layer);
+        let z_index = layer.z_index();
+        let pos = self
+            .layers
+            .iter()
+            .rposition(|layer| layer.z_index() <= z_index)
+            .map_or(0, |pos| pos + 1);
+        self.layers.insert(pos, layer);
     }
 
     /// Replace a component that has the given `id` with the new layer and if
@@ -1,10 +1,10 @@
-use crate::keymap::{default::default, merge_keys, Keymap};
+use crate::keymap::{default::default, merge_keys, mouse::MouseMap, Keymap};
 use helix_view::document::Mode;
 use serde::Deserialize;
 use std::collections::HashMap;
 use std::fmt::Display;
-use std::io::Error as IOError;
-use std::path::PathBuf;
+use std::io::{Error as IOError, ErrorKind};
+use std::path::{Path, PathBuf};
 use toml::de::Error as TomlError;
 
 #[derive(Debug, Clone, PartialEq, Deserialize)]
@@ -14,6 +14,8 @@ pub struct Config {
     #[serde(default = "default")]
     pub keys: HashMap<Mode, Keymap>,
     #[serde(default)]
+    pub mouse: MouseMap,
+    #[serde(default)]
     pub editor: helix_view::editor::Config,
 }
 
@@ -22,6 +24,7 @@ fn default() -> Config {
         Config {
             theme: None,
             keys: default(),
+            mouse: MouseMap::default(),
             editor: helix_view::editor::Config::default(),
         }
     }
@@ -43,13 +46,27 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 }
 
 impl Config {
+    /// Loads the config at `config_path`, a missing file leaving the defaults, with the
+    /// explorer options of the config files of the workspace on top.
     pub fn load(config_path: PathBuf) -> Result<Config, ConfigLoadError> {
-        match std::fs::read_to_string(config_path) {
-            Ok(config) => toml::from_str(&config)
-                .map(merge_keys)
-                .map_err(ConfigLoadError::BadConfig),
-            Err(err) => Err(ConfigLoadError::Error(err)),
+        let config = match std::fs::read_to_string(config_path) {
+            Ok(config) => toml::from_str(&config).map_err(ConfigLoadError::BadConfig)?,
+            Err(err) if err.kind() == ErrorKind::NotFound => toml::Value::Table(Default::default()),
+            Err(err) => return Err(ConfigLoadError::Error(err)),
+        };
+        let mut workspace = Vec::new();
+        for dir in helix_loader::local_config_dirs() {
+            if let Ok(config) = std::fs::read_to_string(dir.join("config.toml")) {
+                workspace.push((
+                    dir,
+                    toml::from_str(&config).map_err(ConfigLoadError::BadConfig)?,
+                ));
+            }
         }
+        merge_workspace_config(config, workspace)
+            .try_into()
+            .map(merge_keys)
+            .map_err(ConfigLoadError::BadConfig)
     }
 
     pub fn load_default() -> Result<Config, ConfigLoadError> {
@@ -57,6 +74,95 @@ pub fn load_default() -> Result<Config, ConfigLoadError> {
     }
 }
 
+/// The options a config file of the workspace can set, by their dotted path. The other
+/// ones could run commands, which a repository cloned from anywhere should not set: the
+/// explorer `actions`, `open` and `templates` are left out too.
+const WORKSPACE_OPTIONS: &[&str] = &[
+    "editor.explorer.style",
+    "editor.explorer.position",
+    "editor.explorer.column-width",
+    "editor.explorer.git-ignore",
+    "editor.explorer.ignore-files",
+    "editor.explorer.show-hidden",
+    "editor.explorer.follow",
+    "editor.explorer.icons",
+    "editor.explorer.last-commit",
+    "editor.explorer.screen-reader",
+    "editor.explorer.classify",
+    "editor.explorer.manifests",
+    "editor.explorer.recent",
+    "editor.explorer.rainbow-guides",
+    "editor.explorer.sort",
+    "editor.explorer.root",
+    "editor.explorer.ignore",
+    "editor.explorer.filter-presets",
+];
+
+/// Merges the options of the config files of the workspace allowed by `WORKSPACE_OPTIONS`
+/// onto `config`, the files being given with their `.helix` directory, the closest to the
+/// working directory first. The closest files win, and their `ignore` globs are added to
+/// the ones of the others.
+fn merge_workspace_config(
+    mut config: toml::Value,
+    workspace: Vec<(PathBuf, toml::Value)>,
+) -> toml::Value {
+    for (dir, workspace_config) in workspace.into_iter().rev() {
+        let file = dir.join("config.toml");
+        let mut overlay = match workspace_config {
+            toml::Value::Table(table) => filter_workspace_options(table, "", &file),
+            _ => continue,
+        };
+        // the root is relative to the workspace, the directory holding `.helix`
+        let root = overlay
+            .get_mut("editor")
+            .and_then(|editor| editor.get_mut("explorer"))
+            .and_then(|explorer| explorer.get_mut("root"));
+        if let Some(toml::Value::String(root)) = root {
+            let workspace_dir = dir.parent().unwrap_or(&dir);
+            *root = workspace_dir.join(&*root).to_string_lossy().into_owned();
+        }
+        // deep enough for the arrays of the explorer to be appended to
+        config = helix_loader::merge_toml_values(config, toml::Value::Table(overlay), 4);
+    }
+    config
+}
+
+/// The options of `table` allowed by `WORKSPACE_OPTIONS`, `prefix` being its dotted path,
+/// going through the tables holding some of them key by key.
+fn filter_workspace_options(
+    table: toml::value::Table,
+    prefix: &str,
+    file: &Path,
+) -> toml::value::Table {
+    table
+        .into_iter()
+        .filter_map(|(key, value)| {
+            let path = match prefix {
+                "" => key.clone(),
+                prefix => format!("{prefix}.{key}"),
+            };
+            if WORKSPACE_OPTIONS.contains(&path.as_str()) {
+                return Some((key, value));
+            }
+            let nested = format!("{path}.");
+            match value {
+                toml::Value::Table(table)
+                    if WORKSPACE_OPTIONS
+                        .iter()
+                        .any(|option| option.starts_with(&nested)) =>
+                {
+                    let table = filter_workspace_options(table, &path, file);
+                    Some((key, toml::Value::Table(table)))
+                }
+                _ => {
+                    log::warn!("the option `{path}` of '{}' is not used", file.display());
+                    None
+                }
+            }
+        })
+        .collect()
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
@@ -94,6 +200,133 @@ fn parsing_keymaps_config_file() {
         );
     }
 
+    #[test]
+    fn parsing_mouse_config() {
+        use crate::keymap::mouse::{MouseAction, MouseBinding};
+        use helix_view::{input::MouseButton, keyboard::KeyModifiers};
+
+        let sample = r#"
+            [mouse.editor]
+            right = "extend_to_pointer"
+            C-left = "goto_definition"
+
+            [mouse.bufferline]
+            middle = ":buffer-close"
+        "#;
+        let mouse = toml::from_str::<Config>(sample).unwrap().mouse;
+        let binding = |button, modifiers| MouseBinding { button, modifiers };
+        assert_eq!(
+            mouse.editor[&binding(MouseButton::Right, KeyModifiers::NONE)],
+            MouseAction::Extend
+        );
+        assert_eq!(
+            mouse.editor[&binding(MouseButton::Left, KeyModifiers::CONTROL)],
+            MouseAction::Command(crate::commands::MappableCommand::goto_definition)
+        );
+        assert_eq!(mouse.bufferline.len(), 1);
+        assert!(mouse.explorer.is_empty());
+        let invalid = "[mouse.editor]\nwheel = \"no_op\"";
+        assert!(toml::from_str::<Config>(invalid).is_err());
+        assert!(toml::from_str::<Config>("[mouse.statusline]").is_err());
+    }
+
+    #[test]
+    fn parsing_mouse_toggles() {
+        use helix_view::editor::Mouse;
+
+        let mouse = |toml: &str| toml::from_str::<Config>(toml).unwrap().editor.mouse;
+        assert_eq!(mouse(""), Mouse::default());
+        assert!(!mouse("[editor]\nmouse = false").is_captured());
+        let mouse = mouse("[editor.mouse]\ntext = false");
+        assert!(!mouse.text && mouse.explorer && mouse.is_captured());
+    }
+
+    #[test]
+    fn parsing_auto_save() {
+        use helix_view::editor::AutoSave;
+        use std::time::Duration;
+
+        let auto_save = |toml: &str| toml::from_str::<Config>(toml).unwrap().editor.auto_save;
+        assert_eq!(auto_save(""), AutoSave::default());
+        assert!(auto_save("[editor]\nauto-save = true").focus_lost);
+        assert_eq!(
+            auto_save("[editor.auto-save]\nafter-delay = true\ndelay = 500"),
+            AutoSave {
+                after_delay: true,
+                delay: Duration::from_millis(500),
+                ..AutoSave::default()
+            }
+        );
+    }
+
+    #[test]
+    fn merging_workspace_config() {
+        use helix_view::editor::ExplorerSort;
+
+        let toml = |toml: &str| toml::from_str::<toml::Value>(toml).unwrap();
+        let global = toml(
+            r#"
+            [editor]
+            scrolloff = 3
+            [editor.explorer]
+            icons = true
+            ignore = ["*.log"]
+        "#,
+        );
+        let top = toml(
+            r#"
+            theme = "base16"
+            [editor]
+            shell = ["rm"]
+            [editor.explorer]
+            sort = "dirs-first"
+            icons = false
+            ignore = ["target"]
+        "#,
+        );
+        let sub = toml("[editor.explorer]\nsort = \"name\"\nroot = \"src\"");
+        let workspace = vec![
+            (PathBuf::from("/project/sub/.helix"), sub),
+            (PathBuf::from("/project/.helix"), top),
+        ];
+        let config: Config = merge_workspace_config(global, workspace)
+            .try_into()
+            .unwrap();
+        assert_eq!(config.theme, None);
+        assert_eq!(config.editor.scrolloff, 3);
+        assert_eq!(config.editor.shell, Config::default().editor.shell);
+        let explorer = config.editor.explorer;
+        assert_eq!(explorer.sort, ExplorerSort::Name);
+        assert!(!explorer.icons);
+        assert_eq!(explorer.ignore, ["*.log", "target"]);
+        assert_eq!(explorer.root, Some(PathBuf::from("/project/sub/src")));
+    }
+
+    #[test]
+    fn workspace_config_cannot_run_commands() {
+        let toml = |toml: &str| toml::from_str::<toml::Value>(toml).unwrap();
+        let global = toml("[editor.explorer.actions]\nr = \"cargo run\"");
+        let workspace = toml(
+            r#"
+            [editor.explorer]
+            templates = "templates"
+            [editor.explorer.actions]
+            x = "rm -rf ~"
+            [editor.explorer.open]
+            "*.rs" = "external"
+        "#,
+        );
+        let workspace = vec![(PathBuf::from("/project/.helix"), workspace)];
+        let config: Config = merge_workspace_config(global, workspace)
+            .try_into()
+            .unwrap();
+        let explorer = config.editor.explorer;
+        assert_eq!(explorer.actions.len(), 1);
+        assert_eq!(explorer.actions["r"], "cargo run");
+        assert_eq!(explorer.open, Config::default().editor.explorer.open);
+        assert_eq!(explorer.templates, None);
+    }
+
     #[test]
     fn keys_resolve_to_correct_defaults() {
         // From serde default
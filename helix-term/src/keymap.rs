@@ -1,5 +1,6 @@
 pub mod default;
 pub mod macros;
+pub mod mouse;
 
 pub use crate::commands::MappableCommand;
 use crate::config::Config;
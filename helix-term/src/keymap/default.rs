@@ -54,6 +54,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "m" => goto_last_modified_file,
             "n" => goto_next_buffer,
             "p" => goto_previous_buffer,
+            "x" => close_buffer,
             "." => goto_last_modification,
         },
         ":" => command_mode,
@@ -103,6 +104,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_first_diag,
             "g" => goto_prev_change,
             "G" => goto_first_change,
+            "x" => goto_prev_conflict,
             "f" => goto_prev_function,
             "t" => goto_prev_class,
             "a" => goto_prev_parameter,
@@ -116,6 +118,7 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "D" => goto_last_diag,
             "g" => goto_next_change,
             "G" => goto_last_change,
+            "x" => goto_next_conflict,
             "f" => goto_next_function,
             "t" => goto_next_class,
             "a" => goto_next_parameter,
@@ -180,6 +183,8 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "C-s" | "s" => hsplit,
             "C-v" | "v" => vsplit,
             "C-t" | "t" => transpose_view,
+            "C-r" | "r" => rotate_splits_forward,
+            "R" => rotate_splits_backward,
             "f" => goto_file_hsplit,
             "F" => goto_file_vsplit,
             "C-q" | "q" => wclose,
@@ -192,6 +197,15 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "K" => swap_view_up,
             "H" => swap_view_left,
             "J" => swap_view_down,
+            ">" => grow_view_width,
+            "<" => shrink_view_width,
+            "+" => grow_view_height,
+            "-" => shrink_view_height,
+            "=" => equalize_views,
+            "T" => tab_new,
+            "]" => tab_next,
+            "[" => tab_prev,
+            "Q" => tab_close,
             "n" => { "New split scratch buffer"
                 "C-s" | "s" => hsplit_new,
                 "C-v" | "v" => vsplit_new,
@@ -243,6 +257,8 @@ pub fn default() -> HashMap<Mode, Keymap> {
                 "C-s" | "s" => hsplit,
                 "C-v" | "v" => vsplit,
                 "C-t" | "t" => transpose_view,
+                "C-r" | "r" => rotate_splits_forward,
+                "R" => rotate_splits_backward,
                 "f" => goto_file_hsplit,
                 "F" => goto_file_vsplit,
                 "C-q" | "q" => wclose,
@@ -255,6 +271,15 @@ pub fn default() -> HashMap<Mode, Keymap> {
                 "J" => swap_view_down,
                 "K" => swap_view_up,
                 "L" => swap_view_right,
+                ">" => grow_view_width,
+                "<" => shrink_view_width,
+                "+" => grow_view_height,
+                "-" => shrink_view_height,
+                "=" => equalize_views,
+                "T" => tab_new,
+                "]" => tab_next,
+                "[" => tab_prev,
+                "Q" => tab_close,
                 "n" => { "New split scratch buffer"
                     "C-s" | "s" => hsplit_new,
                     "C-v" | "v" => vsplit_new,
@@ -272,6 +297,12 @@ pub fn default() -> HashMap<Mode, Keymap> {
             "?" => command_palette,
             "e" => toggle_or_focus_explorer,
             "E" => open_explorer_recursion,
+            "t" => toggle_or_focus_terminal,
+            "x" => { "Conflict"
+                "o" => conflict_pick_ours,
+                "t" => conflict_pick_theirs,
+                "b" => conflict_pick_both,
+            },
         },
         "z" => { "View"
             "z" | "c" => align_view_center,
@@ -0,0 +1,136 @@
+//! Mouse bindings: the commands run by pressing a mouse button with modifiers in the
+//! views, on the rows of the explorer or on the buffers of the bufferline. Clicks
+//! without a binding keep their built-in behavior.
+
+use crate::commands::{self, MappableCommand};
+use anyhow::{anyhow, Error};
+use helix_core::{Range, Selection};
+use helix_view::{input::MouseButton, keyboard::KeyModifiers};
+use serde::{de, Deserialize, Deserializer};
+use std::{collections::HashMap, str::FromStr};
+
+/// A mouse button pressed with modifiers, written like keys: `right`, `C-left`, `S-A-middle`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct MouseBinding {
+    pub button: MouseButton,
+    pub modifiers: KeyModifiers,
+}
+
+impl FromStr for MouseBinding {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        let mut tokens: Vec<_> = s.split('-').collect();
+        let button = match tokens
+            .pop()
+            .ok_or_else(|| anyhow!("Missing mouse button"))?
+        {
+            "left" => MouseButton::Left,
+            "right" => MouseButton::Right,
+            "middle" => MouseButton::Middle,
+            invalid => return Err(anyhow!("Invalid mouse button '{}'", invalid)),
+        };
+
+        let mut modifiers = KeyModifiers::empty();
+        for token in tokens {
+            let flag = match token {
+                "S" => KeyModifiers::SHIFT,
+                "A" => KeyModifiers::ALT,
+                "C" => KeyModifiers::CONTROL,
+                _ => return Err(anyhow!("Invalid key modifier '{}-'", token)),
+            };
+
+            if modifiers.contains(flag) {
+                return Err(anyhow!("Repeated key modifier '{}-'", token));
+            }
+            modifiers.insert(flag);
+        }
+
+        Ok(Self { button, modifiers })
+    }
+}
+
+impl<'de> Deserialize<'de> for MouseBinding {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+/// What a mouse binding does: a command, or one of the actions on the text under the
+/// pointer, which only apply in the views.
+#[derive(Debug, Clone, PartialEq)]
+pub enum MouseAction {
+    /// Moves the cursor to the pointer, `select_at_pointer`.
+    Select,
+    /// Extends the primary selection to the pointer, `extend_to_pointer`.
+    Extend,
+    /// Adds a cursor at the pointer, `add_cursor_at_pointer`.
+    AddCursor,
+    Command(MappableCommand),
+}
+
+impl FromStr for MouseAction {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        Ok(match s {
+            "select_at_pointer" => Self::Select,
+            "extend_to_pointer" => Self::Extend,
+            "add_cursor_at_pointer" => Self::AddCursor,
+            command => Self::Command(command.parse()?),
+        })
+    }
+}
+
+impl<'de> Deserialize<'de> for MouseAction {
+    fn deserialize<D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        D: Deserializer<'de>,
+    {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl MouseAction {
+    /// Runs the action in the focused view, `pos` being the character under the pointer
+    /// when it is over the text of the view.
+    pub fn execute(&self, pos: Option<usize>, cx: &mut commands::Context) {
+        let pos = match (self, pos) {
+            (Self::Command(command), _) => return command.execute(cx),
+            (_, Some(pos)) => pos,
+            (_, None) => return,
+        };
+        let (view, doc) = current!(cx.editor);
+        let selection = doc.selection(view.id).clone();
+        let selection = match self {
+            Self::Extend => {
+                let index = selection.primary_index();
+                let text = doc.text().slice(..);
+                let range = selection.primary().put_cursor(text, pos, true);
+                selection.replace(index, range)
+            }
+            Self::AddCursor => selection.push(Range::point(pos)),
+            _ => Selection::point(pos),
+        };
+        let view_id = view.id;
+        doc.set_selection(view_id, selection);
+        cx.editor.ensure_cursor_in_view(view_id);
+    }
+}
+
+/// The mouse bindings of each context, from the `[mouse]` section of the config.
+#[derive(Debug, Default, Clone, PartialEq, Deserialize)]
+#[serde(default, deny_unknown_fields)]
+pub struct MouseMap {
+    /// Clicks on the text of the views.
+    pub editor: HashMap<MouseBinding, MouseAction>,
+    /// Clicks on the rows of the explorer, which select the row first.
+    pub explorer: HashMap<MouseBinding, MouseAction>,
+    /// Clicks on the buffers of the bufferline, which switch to the buffer first.
+    pub bufferline: HashMap<MouseBinding, MouseAction>,
+}
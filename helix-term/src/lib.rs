@@ -9,6 +9,7 @@
 pub mod health;
 pub mod job;
 pub mod keymap;
+pub mod remote;
 pub mod ui;
 pub use keymap::macros::*;
 
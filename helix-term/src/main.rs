@@ -3,7 +3,7 @@
 use helix_loader::VERSION_AND_GIT_HASH;
 use helix_term::application::Application;
 use helix_term::args::Args;
-use helix_term::config::Config;
+use helix_term::config::{Config, ConfigLoadError};
 use std::path::PathBuf;
 
 fn setup_logging(logpath: PathBuf, verbosity: u64) -> Result<()> {
@@ -73,12 +73,17 @@ async fn main_impl() -> Result<i32> {
     -V, --version                  Prints version information
     --vsplit                       Splits all given files vertically into different windows
     --hsplit                       Splits all given files horizontally into different windows
+    --socket <path>                Specifies the socket to listen on for remote commands, or to
+                                   send them to (default file: {})
+    --remote <command>...          Runs a typable command in the editor listening on the socket,
+                                   like `explorer reveal src/main.rs`
 ",
         env!("CARGO_PKG_NAME"),
         VERSION_AND_GIT_HASH,
         env!("CARGO_PKG_AUTHORS"),
         env!("CARGO_PKG_DESCRIPTION"),
         logpath.display(),
+        helix_term::remote::default_socket().display(),
     );
 
     let args = Args::parse_args().context("could not parse arguments")?;
@@ -106,6 +111,23 @@ async fn main_impl() -> Result<i32> {
         std::process::exit(0);
     }
 
+    if let Some(command) = &args.remote {
+        let socket = args
+            .socket
+            .clone()
+            .unwrap_or_else(helix_term::remote::default_socket);
+        let command: Vec<_> = command
+            .iter()
+            .map(|word| helix_core::shellwords::escape(word.as_str().into()))
+            .collect();
+        let cwd = std::env::current_dir().context("could not read the working directory")?;
+        if let Err(err) = helix_term::remote::send(&socket, &cwd, &command.join(" ")) {
+            eprintln!("{}", err);
+            return Ok(1);
+        }
+        return Ok(0);
+    }
+
     if args.fetch_grammars {
         helix_loader::grammar::fetch_grammars()?;
         return Ok(0);
@@ -126,18 +148,16 @@ async fn main_impl() -> Result<i32> {
 
     helix_loader::initialize_config_file(args.config_file.clone());
 
-    let config = match std::fs::read_to_string(helix_loader::config_file()) {
-        Ok(config) => toml::from_str(&config)
-            .map(helix_term::keymap::merge_keys)
-            .unwrap_or_else(|err| {
-                eprintln!("Bad config: {}", err);
-                eprintln!("Press <ENTER> to continue with default config");
-                use std::io::Read;
-                let _ = std::io::stdin().read(&mut []);
-                Config::default()
-            }),
-        Err(err) if err.kind() == std::io::ErrorKind::NotFound => Config::default(),
-        Err(err) => return Err(Error::new(err)),
+    let config = match Config::load_default() {
+        Ok(config) => config,
+        Err(ConfigLoadError::BadConfig(err)) => {
+            eprintln!("Bad config: {}", err);
+            eprintln!("Press <ENTER> to continue with default config");
+            use std::io::Read;
+            let _ = std::io::stdin().read(&mut []);
+            Config::default()
+        }
+        Err(ConfigLoadError::Error(err)) => return Err(Error::new(err)),
     };
 
     let syn_loader_conf = helix_core::config::user_syntax_loader().unwrap_or_else(|err| {
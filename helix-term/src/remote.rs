@@ -0,0 +1,150 @@
+//! Remote control of a running editor. It listens on a local socket for lines holding
+//! typable commands, like `explorer reveal src/main.rs` or `open src/main.rs:10`, which
+//! are run as if entered in the command prompt. A line may start with the working
+//! directory of the client and a NUL byte, the relative paths of the command resolve
+//! against it then. Each line is answered with `ok` or `error: ` and the message of the
+//! failure. `hx --remote <command>` sends a command.
+
+use std::path::{Path, PathBuf};
+
+use anyhow::Result;
+use tokio::sync::{mpsc, oneshot};
+
+/// A command received, with the channel its result is sent back to.
+pub struct Request {
+    /// The working directory of the client, when it sent one.
+    pub cwd: Option<PathBuf>,
+    pub command: String,
+    pub reply: oneshot::Sender<Result<(), String>>,
+}
+
+/// The socket used without `--socket`.
+pub fn default_socket() -> PathBuf {
+    helix_loader::cache_dir().join("helix.sock")
+}
+
+/// Listens on a socket until dropped, which removes it.
+pub struct Server {
+    requests: mpsc::UnboundedReceiver<Request>,
+    path: PathBuf,
+}
+
+impl Server {
+    /// Receives the next command of a client.
+    pub async fn recv(&mut self) -> Option<Request> {
+        self.requests.recv().await
+    }
+}
+
+impl Drop for Server {
+    fn drop(&mut self) {
+        let _ = std::fs::remove_file(&self.path);
+    }
+}
+
+#[cfg(unix)]
+impl Server {
+    /// Listens on the socket at `path`, unless another editor already does. It has to
+    /// be called within the tokio runtime.
+    pub fn bind(path: PathBuf) -> Result<Self> {
+        use tokio::net::UnixListener;
+
+        if path.exists() {
+            anyhow::ensure!(
+                std::os::unix::net::UnixStream::connect(&path).is_err(),
+                "another editor listens on '{}'",
+                path.display()
+            );
+            // left by an editor which did not exit
+            std::fs::remove_file(&path)?;
+        }
+        if let Some(dir) = path.parent() {
+            std::fs::create_dir_all(dir)?;
+        }
+        // the commands can run shell commands, so only the user may connect, from the
+        // moment the socket exists
+        // SAFETY: umask only swaps the mask of the process
+        let umask = unsafe { libc::umask(0o177) };
+        let listener = UnixListener::bind(&path);
+        unsafe { libc::umask(umask) };
+        let listener = listener?;
+
+        let (sender, requests) = mpsc::unbounded_channel();
+        tokio::spawn(async move {
+            loop {
+                match listener.accept().await {
+                    Ok((stream, _)) => {
+                        tokio::spawn(serve(stream, sender.clone()));
+                    }
+                    Err(err) => {
+                        log::error!("failed to accept a remote connection: {}", err);
+                        break;
+                    }
+                }
+            }
+        });
+        Ok(Self { requests, path })
+    }
+}
+
+#[cfg(not(unix))]
+impl Server {
+    pub fn bind(_path: PathBuf) -> Result<Self> {
+        anyhow::bail!("remote control is only supported on Unix")
+    }
+}
+
+/// Answers the commands of a client until it disconnects or the editor exits.
+#[cfg(unix)]
+async fn serve(stream: tokio::net::UnixStream, requests: mpsc::UnboundedSender<Request>) {
+    use tokio::io::{AsyncBufReadExt, AsyncWriteExt, BufReader};
+
+    let (reader, mut writer) = stream.into_split();
+    let mut lines = BufReader::new(reader).lines();
+    while let Ok(Some(line)) = lines.next_line().await {
+        let (cwd, command) = match line.split_once('\0') {
+            Some((cwd, command)) => (Some(PathBuf::from(cwd)), command.to_string()),
+            None => (None, line),
+        };
+        let (reply, result) = oneshot::channel();
+        let request = Request {
+            cwd,
+            command,
+            reply,
+        };
+        if requests.send(request).is_err() {
+            break;
+        }
+        let answer = match result.await {
+            Ok(Ok(())) => "ok\n".to_string(),
+            Ok(Err(err)) => format!("error: {}\n", err.replace('\n', " ")),
+            Err(_) => break,
+        };
+        if writer.write_all(answer.as_bytes()).await.is_err() {
+            break;
+        }
+    }
+}
+
+/// Sends `command` to the editor listening on `path`, to run it in `cwd`, failing with
+/// its error.
+#[cfg(unix)]
+pub fn send(path: &Path, cwd: &Path, command: &str) -> Result<()> {
+    use anyhow::Context;
+    use std::io::{BufRead, BufReader, Write};
+
+    let mut stream = std::os::unix::net::UnixStream::connect(path)
+        .with_context(|| format!("no editor listens on '{}'", path.display()))?;
+    writeln!(stream, "{}\0{}", cwd.display(), command)?;
+    let mut answer = String::new();
+    BufReader::new(stream).read_line(&mut answer)?;
+    match answer.trim_end().strip_prefix("error: ") {
+        Some(err) => anyhow::bail!("{}", err),
+        None => Ok(()),
+    }
+}
+
+#[cfg(not(unix))]
+pub fn send(_path: &Path, _cwd: &Path, _command: &str) -> Result<()> {
+    anyhow::bail!("remote control is only supported on Unix")
+}
@@ -0,0 +1,160 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::{
+    key,
+    ui::{menu, Markdown, Picker},
+};
+use tui::{buffer::Buffer as Surface, text::Spans};
+
+use std::path::{Path, PathBuf};
+
+use helix_core::{path::get_relative_path, Rope};
+use helix_vcs::CommitInfo;
+use helix_view::{document::from_reader, editor::Action, graphics::Rect, Document, Editor};
+
+/// Contents of the blame popup. Shows the commit that last changed a line,
+/// pressing enter opens the full diff of that commit in a scratch buffer.
+pub struct BlameInfo {
+    contents: Markdown,
+    path: PathBuf,
+    hash: String,
+}
+
+impl BlameInfo {
+    pub const ID: &'static str = "blame";
+
+    pub fn new(editor: &Editor, path: PathBuf, info: CommitInfo) -> Self {
+        let contents = format!(
+            "**{}**\n\n{} — {}\n\n```\n{}\n```\n\n*Press enter to open the full commit*",
+            info.hash, info.author, info.date, info.message
+        );
+        Self {
+            contents: Markdown::new(contents, editor.syn_loader.clone()),
+            path,
+            hash: info.hash,
+        }
+    }
+}
+
+/// Opens the diff of `hash` in a new scratch buffer.
+fn open_commit_diff(editor: &mut Editor, path: &Path, hash: &str, action: Action) {
+    let diff = match editor.diff_providers.commit_diff(path, hash) {
+        Ok(diff) => diff,
+        Err(err) => {
+            editor.set_error(format!("Failed to show commit {hash}: {err}"));
+            return;
+        }
+    };
+
+    let mut doc = Document::from(Rope::from(diff), None);
+    // the diff grammar is optional, plain text is fine as well
+    let _ = doc.set_language_by_language_id("diff", editor.syn_loader.clone());
+    editor.new_file_from_document(action, doc);
+}
+
+/// Opens the version of the file at `path` as of `hash` in a new read-only buffer.
+fn open_file_at_commit(editor: &mut Editor, path: &Path, hash: &str, action: Action) {
+    let contents = match editor.diff_providers.file_at_commit(path, hash) {
+        Ok(contents) => contents,
+        Err(err) => {
+            editor.set_error(format!("Failed to show {}: {err}", path.display()));
+            return;
+        }
+    };
+    let (text, encoding) = match from_reader(&mut contents.as_slice(), None) {
+        Ok(text) => text,
+        Err(err) => {
+            editor.set_error(format!("Failed to decode {}: {err}", path.display()));
+            return;
+        }
+    };
+
+    let mut doc = Document::from(text, Some(encoding));
+    let language = editor.syn_loader.language_config_for_file_name(path);
+    doc.set_language(language, Some(editor.syn_loader.clone()));
+    doc.set_readonly(true);
+    editor.new_file_from_document(action, doc);
+    editor.set_status(format!(
+        "{} as of {}",
+        get_relative_path(path).display(),
+        short_hash(hash)
+    ));
+}
+
+fn short_hash(hash: &str) -> &str {
+    hash.get(..8).unwrap_or(hash)
+}
+
+/// What picking a commit in the history of a file opens.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LogTarget {
+    /// The file as of the commit, in a read-only buffer.
+    File,
+    /// The full diff of the commit.
+    Diff,
+}
+
+impl menu::Item for CommitInfo {
+    type Data = ();
+
+    fn label(&self, _data: &Self::Data) -> Spans {
+        // the name is enough, without the email
+        let author = self.author.split(" <").next().unwrap_or_default();
+        format!(
+            "{} {} {} {}",
+            short_hash(&self.hash),
+            self.date,
+            author,
+            self.message
+        )
+        .into()
+    }
+}
+
+/// Lists the commits that changed the file at `path`. Picking one opens `target`.
+pub fn file_log_picker(
+    editor: &mut Editor,
+    path: PathBuf,
+    target: LogTarget,
+) -> Option<Picker<CommitInfo>> {
+    let log = match editor.diff_providers.file_log(&path) {
+        Ok(log) if log.is_empty() => {
+            editor.set_error(format!("{} has no commits", path.display()));
+            return None;
+        }
+        Ok(log) => log,
+        Err(err) => {
+            editor.set_error(format!("Failed to list commits: {err}"));
+            return None;
+        }
+    };
+    let picker = Picker::new(log, (), move |cx, commit, action| match target {
+        LogTarget::File => open_file_at_commit(cx.editor, &path, &commit.hash, action),
+        LogTarget::Diff => open_commit_diff(cx.editor, &path, &commit.hash, action),
+    });
+    Some(picker)
+}
+
+impl Component for BlameInfo {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        match event {
+            Event::Key(key!(Enter)) => {
+                let path = self.path.clone();
+                let hash = self.hash.clone();
+                let callback: Callback = Box::new(move |compositor, cx| {
+                    compositor.remove(Self::ID);
+                    open_commit_diff(cx.editor, &path, &hash, Action::Replace);
+                });
+                EventResult::Consumed(Some(callback))
+            }
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.contents.render(area, surface, cx);
+    }
+
+    fn required_size(&mut self, viewport: (u16, u16)) -> Option<(u16, u16)> {
+        self.contents.required_size(viewport)
+    }
+}
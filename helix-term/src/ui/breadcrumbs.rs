@@ -0,0 +1,187 @@
+use helix_core::{textobject, unicode::width::UnicodeWidthStr, Selection};
+use helix_lsp::lsp::SymbolKind;
+use helix_view::{
+    align_view, document::SCRATCH_BUFFER_NAME, Align, Document, Editor, Theme, View, ViewId,
+};
+use tui::{buffer::Buffer as Surface, text::Spans};
+
+/// Longest label taken from the first line of a tree-sitter scope.
+const MAX_LINE_LABEL_WIDTH: usize = 40;
+
+/// A segment of the breadcrumbs bar, selecting it moves the cursor to `pos`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Crumb {
+    pub label: String,
+    pub pos: usize,
+}
+
+impl crate::ui::menu::Item for Crumb {
+    type Data = ();
+
+    fn label(&self, _data: &Self::Data) -> Spans {
+        self.label.as_str().into()
+    }
+}
+
+/// The segments of the breadcrumbs bar of `view`: the path of the document followed by
+/// the scopes enclosing the primary cursor, from the outermost to the innermost one.
+///
+/// The scopes are the document symbols of the language server, or the functions and
+/// classes of the tree-sitter syntax tree if the language server doesn't provide them.
+pub fn crumbs(doc: &Document, view: &View) -> Vec<Crumb> {
+    let path = doc
+        .relative_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_else(|| SCRATCH_BUFFER_NAME.into());
+    let mut crumbs = vec![Crumb {
+        label: path,
+        pos: 0,
+    }];
+
+    let text = doc.text().slice(..);
+    let cursor = doc.selection(view.id).primary().cursor(text);
+    if doc.symbols_version().is_some() {
+        crumbs.extend(doc.symbols_at(cursor).map(|symbol| {
+            let label = match symbol_kind_label(symbol.kind) {
+                Some(kind) => format!("{} {}", kind, symbol.name),
+                None => symbol.name.clone(),
+            };
+            Crumb {
+                label,
+                pos: symbol.start,
+            }
+        }));
+        return crumbs;
+    }
+
+    let (syntax, lang_config) = match (doc.syntax(), doc.language_config()) {
+        (Some(syntax), Some(lang_config)) => (syntax, lang_config),
+        _ => return crumbs,
+    };
+    let lines = textobject::textobject_context_lines(
+        text,
+        text.char_to_line(cursor),
+        &["class", "function"],
+        syntax.tree().root_node(),
+        lang_config,
+    );
+    crumbs.extend(lines.into_iter().map(|line| {
+        let line_text = text.line(line).to_string();
+        let indent = line_text.len() - line_text.trim_start().len();
+        let mut label = line_text
+            .trim()
+            .trim_end_matches(|ch| ch == '{' || ch == ':')
+            .trim_end()
+            .to_string();
+        if label.width() > MAX_LINE_LABEL_WIDTH {
+            label = label.chars().take(MAX_LINE_LABEL_WIDTH - 1).collect();
+            label.push('…');
+        }
+        Crumb {
+            label,
+            pos: text.line_to_char(line) + line_text[..indent].chars().count(),
+        }
+    }));
+    crumbs
+}
+
+pub fn symbol_kind_label(kind: SymbolKind) -> Option<&'static str> {
+    match kind {
+        SymbolKind::MODULE | SymbolKind::NAMESPACE | SymbolKind::PACKAGE => Some("mod"),
+        SymbolKind::FUNCTION | SymbolKind::METHOD | SymbolKind::CONSTRUCTOR => Some("fn"),
+        SymbolKind::CLASS => Some("class"),
+        SymbolKind::STRUCT => Some("struct"),
+        SymbolKind::ENUM => Some("enum"),
+        SymbolKind::INTERFACE => Some("interface"),
+        _ => None,
+    }
+}
+
+/// Yields the column each crumb starts at, relative to the start of the bar.
+fn columns<'a>(
+    crumbs: &'a [Crumb],
+    separator: &'a str,
+) -> impl Iterator<Item = (usize, &'a Crumb)> {
+    let mut column = 1; // leave a space before the first crumb
+    crumbs.iter().map(move |crumb| {
+        let start = column;
+        column += crumb.label.width() + separator.width();
+        (start, crumb)
+    })
+}
+
+pub fn render(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme, separator: &str) {
+    let area = match view.winbar_area() {
+        Some(area) if area.width > 0 => area,
+        _ => return,
+    };
+    let style = theme
+        .try_get("ui.breadcrumbs")
+        .unwrap_or_else(|| theme.get("ui.statusline.inactive"));
+    let separator_style = style.patch(theme.get("ui.breadcrumbs.separator"));
+    surface.clear_with(area, style);
+
+    let crumbs = crumbs(doc, view);
+    for (i, (column, crumb)) in columns(&crumbs, separator).enumerate() {
+        let x = area.x as usize + column;
+        if x >= area.right() as usize {
+            break;
+        }
+        let (x, _) = surface.set_stringn(
+            x as u16,
+            area.y,
+            &crumb.label,
+            area.right() as usize - x,
+            style,
+        );
+        if i + 1 < crumbs.len() && x < area.right() {
+            surface.set_stringn(
+                x,
+                area.y,
+                separator,
+                (area.right() - x) as usize,
+                separator_style,
+            );
+        }
+    }
+}
+
+/// Returns the crumb shown at `column` of the breadcrumbs bar of `view`.
+pub fn crumb_at_column(doc: &Document, view: &View, separator: &str, column: u16) -> Option<Crumb> {
+    let area = view.winbar_area()?;
+    let column = (column.checked_sub(area.x)?) as usize;
+    let crumbs = crumbs(doc, view);
+    let (_, crumb) = columns(&crumbs, separator)
+        .take_while(|(start, _)| *start <= column)
+        .last()?;
+    Some(crumb.clone())
+}
+
+/// Moves the primary cursor of `view_id` to the start of `crumb`, saving the jump.
+pub fn jump_to(editor: &mut Editor, view_id: ViewId, crumb: &Crumb) {
+    editor.focus(view_id);
+    let (view, doc) = current!(editor);
+    let jump = (doc.id(), doc.selection(view.id).clone());
+    view.jumps.push(jump);
+
+    let text = doc.text().slice(..);
+    let pos = crumb.pos.min(text.len_chars());
+    doc.set_selection(view.id, Selection::point(pos));
+    // crumbs start at or above the cursor, so only scroll if the scope starts above the view
+    if text.char_to_line(pos) < view.offset.row {
+        align_view(doc, view, Align::Top);
+    }
+}
+
+/// Returns the view whose breadcrumbs bar is at the screen coordinates.
+pub fn view_at_screen_coords(editor: &Editor, row: u16, column: u16) -> Option<ViewId> {
+    editor.tree.views().find_map(|(view, _focus)| {
+        let area = view.winbar_area()?;
+        let hit = row == area.y && column >= area.left() && column < area.right();
+        if hit {
+            Some(view.id)
+        } else {
+            None
+        }
+    })
+}
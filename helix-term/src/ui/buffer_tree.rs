@@ -0,0 +1,272 @@
+use crate::commands::buffer_close_by_ids_impl;
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{
+    cmp::Ordering,
+    collections::HashSet,
+    path::{Path, PathBuf},
+};
+
+use helix_core::path::get_relative_path;
+use helix_view::{
+    document::SCRATCH_BUFFER_NAME,
+    editor::Action,
+    graphics::{Modifier, Rect},
+    DocumentId, Editor,
+};
+
+use super::{
+    sidebar::{self, TreePanel},
+    Tree, TreeItem,
+};
+
+/// A row of the panel: a directory of open buffers relative to the working directory, or
+/// a buffer with its name and directory, which scratch buffers have none of.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum BufferItem {
+    Dir(PathBuf),
+    Buffer(Option<PathBuf>, String, DocumentId),
+}
+
+impl BufferItem {
+    fn key(&self) -> (Option<&Path>, Option<(&str, DocumentId)>) {
+        match self {
+            Self::Dir(dir) => (Some(dir), None),
+            Self::Buffer(dir, name, id) => (dir.as_deref(), Some((name, *id))),
+        }
+    }
+}
+
+struct Buffers {
+    modified: HashSet<DocumentId>,
+    /// The buffer of the focused view.
+    current: Option<DocumentId>,
+}
+
+impl TreeItem for BufferItem {
+    type Params = Buffers;
+
+    fn text(&self, cx: &mut Context, selected: bool, buffers: &mut Buffers) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        let (name, id) = match self {
+            Self::Dir(dir) => {
+                let dir = format!("{}/", dir.display());
+                return Spans::from(Span::styled(dir, style("ui.text.focus")));
+            }
+            Self::Buffer(_, name, id) => (name, *id),
+        };
+        let mut name_style = style("ui.text");
+        if buffers.current == Some(id) {
+            name_style = name_style.add_modifier(Modifier::BOLD);
+        }
+        let mut spans = vec![Span::styled(name.clone(), name_style)];
+        if buffers.modified.contains(&id) {
+            spans.push(Span::styled(" [+]", style("warning")));
+        }
+        Spans::from(spans)
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Buffer(Some(dir), ..), Self::Dir(parent)) => dir == parent,
+            _ => false,
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The items of the buffers at `paths`, under the directories they are in.
+fn items(paths: &[(DocumentId, Option<PathBuf>)]) -> Vec<BufferItem> {
+    let mut dirs = HashSet::new();
+    let mut items = Vec::new();
+    for (id, path) in paths {
+        let path = match path {
+            Some(path) => get_relative_path(path),
+            None => {
+                let name = SCRATCH_BUFFER_NAME.to_string();
+                items.push(BufferItem::Buffer(None, name, *id));
+                continue;
+            }
+        };
+        let dir = match path.parent() {
+            Some(dir) if dir != Path::new("") => dir.to_path_buf(),
+            _ => PathBuf::from("."),
+        };
+        if dirs.insert(dir.clone()) {
+            items.push(BufferItem::Dir(dir.clone()));
+        }
+        let name = path
+            .file_name()
+            .map_or_else(|| path.to_string_lossy(), |name| name.to_string_lossy());
+        items.push(BufferItem::Buffer(Some(dir), name.into_owned(), *id));
+    }
+    items
+}
+
+/// The open buffers as a tree of the directories they are in, next to the views. The keys
+/// go to the views while the panel isn't focused, running `buffer_tree` focuses it again.
+pub struct BufferTree {
+    tree: Tree<BufferItem>,
+    buffers: Buffers,
+    /// The buffers shown and their paths.
+    shown: Vec<(DocumentId, Option<PathBuf>)>,
+    focused: bool,
+    area: Rect,
+}
+
+impl Default for BufferTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl BufferTree {
+    pub const ID: &'static str = "buffer-tree";
+
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::build_tree(Vec::new()),
+            buffers: Buffers {
+                modified: HashSet::new(),
+                current: None,
+            },
+            shown: Vec::new(),
+            focused: true,
+            area: Rect::default(),
+        }
+    }
+
+    /// Shows the buffers again if they were opened, closed or saved elsewhere, and
+    /// selects the focused one while the panel isn't focused.
+    fn update(&mut self, editor: &Editor) {
+        let shown: Vec<_> = editor
+            .documents()
+            .map(|doc| (doc.id(), doc.path().cloned()))
+            .collect();
+        let current = view!(editor).doc;
+        let select_current = |tree: &mut Tree<BufferItem>| {
+            tree.select(|item| matches!(item, BufferItem::Buffer(_, _, id) if *id == current))
+        };
+        if self.shown != shown {
+            let selected = (!self.shown.is_empty()).then(|| self.tree.current_item().clone());
+            self.tree.replace_with_new_items(items(&shown));
+            self.shown = shown;
+            let kept = self.focused
+                && selected.map_or(false, |selected| self.tree.select(|item| *item == selected));
+            if !kept {
+                select_current(&mut self.tree);
+            }
+        } else if self.buffers.current != Some(current) && !self.focused {
+            select_current(&mut self.tree);
+        }
+        self.buffers.current = Some(current);
+        self.buffers.modified = editor
+            .documents()
+            .filter(|doc| doc.is_modified())
+            .map(|doc| doc.id())
+            .collect();
+    }
+
+    /// The buffers of the current row, all the ones in it for a directory.
+    fn current_buffers(&self) -> Vec<DocumentId> {
+        match self.tree.current_item() {
+            BufferItem::Buffer(_, _, id) => vec![*id],
+            BufferItem::Dir(dir) => items(&self.shown)
+                .into_iter()
+                .filter_map(|item| match item {
+                    BufferItem::Buffer(Some(parent), _, id) if parent == *dir => Some(id),
+                    _ => None,
+                })
+                .collect(),
+        }
+    }
+
+    fn close_current(&mut self, cx: &mut Context, force: bool) {
+        let ids = self.current_buffers();
+        if let Err(err) = buffer_close_by_ids_impl(cx, &ids, force) {
+            cx.editor.set_error(err.to_string());
+        }
+    }
+}
+
+impl TreePanel for BufferTree {
+    type Item = BufferItem;
+
+    fn parts(&mut self) -> (&mut Tree<BufferItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for BufferTree {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Mouse(event) => return self.handle_mouse_event(event),
+            Event::Key(key) if self.focused => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            _ if self.shown.is_empty() => {}
+            key!(Enter) => match *self.tree.current_item() {
+                BufferItem::Buffer(_, _, id) => {
+                    cx.editor.switch(id, Action::Replace);
+                    self.focused = false;
+                }
+                BufferItem::Dir(_) => {
+                    return self
+                        .tree
+                        .handle_event(Event::Key(key), cx, &mut self.buffers)
+                }
+            },
+            key!('d') => self.close_current(cx, false),
+            key!('D') => self.close_current(cx, true),
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.buffers)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.update(cx.editor);
+        self.area = sidebar::area(area);
+        let help = "d: close buffer  D: force  q: close";
+        let tree_area = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            "Buffers",
+            help,
+            self.focused,
+        );
+        self.tree.render(tree_area, surface, cx, &mut self.buffers);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
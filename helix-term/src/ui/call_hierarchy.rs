@@ -0,0 +1,288 @@
+use crate::commands::jump_to_location;
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::job;
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{cmp::Ordering, collections::HashSet};
+
+use helix_core::path::get_relative_path;
+use helix_lsp::{lsp, OffsetEncoding};
+use helix_view::{editor::Action, graphics::Rect, Editor};
+
+use super::{
+    breadcrumbs::symbol_kind_label,
+    sidebar::{self, TreePanel},
+    Tree, TreeItem, TreeOp,
+};
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum CallDirection {
+    /// The functions calling the item.
+    Incoming,
+    /// The functions the item calls.
+    Outgoing,
+}
+
+/// A row of the panel: a function of the hierarchy.
+#[derive(Debug, Clone)]
+struct CallItem {
+    /// The positions of the item and of its parents among their siblings, in the order
+    /// of the language server.
+    key: Vec<usize>,
+    item: lsp::CallHierarchyItem,
+}
+
+struct Calls {
+    direction: CallDirection,
+    language_server_id: usize,
+    offset_encoding: OffsetEncoding,
+    /// The items whose calls were asked for, by key.
+    requested: HashSet<Vec<usize>>,
+    /// Counts the changes of direction, the calls received for an older one are dropped.
+    generation: usize,
+}
+
+impl TreeItem for CallItem {
+    type Params = Calls;
+
+    fn text(&self, cx: &mut Context, selected: bool, _calls: &mut Calls) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        let mut spans = Vec::new();
+        if let Some(kind) = symbol_kind_label(self.item.kind) {
+            spans.push(Span::styled(format!("{} ", kind), style("keyword")));
+        }
+        spans.push(Span::styled(self.item.name.clone(), style("ui.text")));
+        if let Ok(path) = self.item.uri.to_file_path() {
+            let path = get_relative_path(&path);
+            let line = self.item.selection_range.start.line + 1;
+            spans.push(Span::styled(
+                format!("  {}:{}", path.display(), line),
+                style("ui.text.inactive"),
+            ));
+        }
+        Spans::from(spans)
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        self.key.len() == other.key.len() + 1 && self.key.starts_with(&other.key)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Asks for the calls of `parent`, which are added to its children once received.
+fn request_calls(editor: &mut Editor, jobs: &mut job::Jobs, calls: &mut Calls, parent: &CallItem) {
+    if !calls.requested.insert(parent.key.clone()) {
+        return;
+    }
+    let language_server = match editor.language_servers.get_by_id(calls.language_server_id) {
+        Some(language_server) => language_server,
+        None => return editor.set_error("Language server is no longer running"),
+    };
+    let (key, generation) = (parent.key.clone(), calls.generation);
+    let item = parent.item.clone();
+    match calls.direction {
+        CallDirection::Incoming => {
+            let future = language_server.incoming_calls(item);
+            jobs.callback(async move {
+                let json = future.await?;
+                let response: Option<Vec<lsp::CallHierarchyIncomingCall>> =
+                    serde_json::from_value(json)?;
+                let items = response.into_iter().flatten().map(|call| call.from);
+                Ok(insert_calls(key, generation, items.collect()))
+            });
+        }
+        CallDirection::Outgoing => {
+            let future = language_server.outgoing_calls(item);
+            jobs.callback(async move {
+                let json = future.await?;
+                let response: Option<Vec<lsp::CallHierarchyOutgoingCall>> =
+                    serde_json::from_value(json)?;
+                let items = response.into_iter().flatten().map(|call| call.to);
+                Ok(insert_calls(key, generation, items.collect()))
+            });
+        }
+    }
+}
+
+fn insert_calls(
+    key: Vec<usize>,
+    generation: usize,
+    items: Vec<lsp::CallHierarchyItem>,
+) -> job::Callback {
+    job::Callback::EditorCompositor(Box::new(
+        move |editor: &mut Editor, compositor: &mut Compositor| {
+            let panel = match compositor.find_id::<CallHierarchy>(CallHierarchy::ID) {
+                Some(panel) if panel.calls.generation == generation => panel,
+                _ => return,
+            };
+            if items.is_empty() && key.len() == 1 {
+                editor.set_status("No calls");
+            }
+            for (index, item) in items.into_iter().enumerate() {
+                let mut key = key.clone();
+                key.push(index);
+                panel.tree.insert(CallItem { key, item });
+            }
+        },
+    ))
+}
+
+/// The calls to or from the functions at the cursor as a tree next to the views, the calls
+/// of each function requested from the language server as it is expanded. The keys go to
+/// the views while the panel isn't focused, running `call_hierarchy` again replaces it.
+pub struct CallHierarchy {
+    tree: Tree<CallItem>,
+    roots: Vec<lsp::CallHierarchyItem>,
+    calls: Calls,
+    /// Whether the calls of the roots are yet to be asked for, which needs the jobs.
+    request_roots: bool,
+    focused: bool,
+    area: Rect,
+}
+
+impl CallHierarchy {
+    pub const ID: &'static str = "call-hierarchy";
+
+    pub fn new(
+        roots: Vec<lsp::CallHierarchyItem>,
+        language_server_id: usize,
+        offset_encoding: OffsetEncoding,
+    ) -> Self {
+        let tree = Tree::build_tree(Vec::new()).with_enter_fn(
+            |item: &mut CallItem, cx: &mut Context, calls: &mut Calls| {
+                if calls.requested.contains(&item.key) {
+                    return TreeOp::Restore;
+                }
+                request_calls(cx.editor, cx.jobs, calls, item);
+                TreeOp::Noop
+            },
+        );
+        let mut panel = Self {
+            tree,
+            roots,
+            calls: Calls {
+                direction: CallDirection::Incoming,
+                language_server_id,
+                offset_encoding,
+                requested: HashSet::new(),
+                generation: 0,
+            },
+            request_roots: true,
+            focused: true,
+            area: Rect::default(),
+        };
+        panel.show(CallDirection::Incoming);
+        panel
+    }
+
+    /// Shows the roots again, to be expanded into the calls in `direction`.
+    fn show(&mut self, direction: CallDirection) {
+        let items = self
+            .roots
+            .iter()
+            .enumerate()
+            .map(|(index, item)| CallItem {
+                key: vec![index],
+                item: item.clone(),
+            })
+            .collect();
+        self.tree.replace_with_new_items(items);
+        self.calls.direction = direction;
+        self.calls.requested.clear();
+        self.calls.generation += 1;
+        self.request_roots = true;
+    }
+
+    /// Jumps to the current function and gives the keys back to the views.
+    fn jump(&mut self, editor: &mut Editor) {
+        let item = &self.tree.current_item().item;
+        let location = lsp::Location {
+            uri: item.uri.clone(),
+            range: item.selection_range,
+        };
+        jump_to_location(
+            editor,
+            &location,
+            self.calls.offset_encoding,
+            Action::Replace,
+        );
+        self.focused = false;
+    }
+}
+
+impl TreePanel for CallHierarchy {
+    type Item = CallItem;
+
+    fn parts(&mut self) -> (&mut Tree<CallItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for CallHierarchy {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Mouse(event) => return self.handle_mouse_event(event),
+            Event::Key(key) if self.focused => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            key!('t') => self.show(match self.calls.direction {
+                CallDirection::Incoming => CallDirection::Outgoing,
+                CallDirection::Outgoing => CallDirection::Incoming,
+            }),
+            key!(Enter) => self.jump(cx.editor),
+            _ => return self.tree.handle_event(Event::Key(key), cx, &mut self.calls),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        if std::mem::take(&mut self.request_roots) {
+            let roots: Vec<_> = self.tree.items().cloned().collect();
+            for root in &roots {
+                request_calls(cx.editor, cx.jobs, &mut self.calls, root);
+            }
+        }
+        self.area = sidebar::area(area);
+        let title = match self.calls.direction {
+            CallDirection::Incoming => "Incoming calls",
+            CallDirection::Outgoing => "Outgoing calls",
+        };
+        let help = "t: toggle  l: expand  q: close";
+        let tree_area = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            title,
+            help,
+            self.focused,
+        );
+        self.tree.render(tree_area, surface, cx, &mut self.calls);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
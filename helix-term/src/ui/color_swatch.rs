@@ -0,0 +1,92 @@
+use helix_core::{
+    line_ending::line_end_char_index,
+    regex::{Captures, Regex},
+    visual_coords_at_pos,
+};
+use helix_view::{
+    graphics::{Color, Style},
+    Document, View,
+};
+use once_cell::sync::Lazy;
+use tui::buffer::Buffer as Surface;
+
+/// Hex (`#rgb`, `#rrggbb`, `#rrggbbaa`) and `rgb()`/`rgba()` color literals.
+static COLOR_LITERAL: Lazy<Regex> = Lazy::new(|| {
+    Regex::new(
+        r"#(?:[0-9a-fA-F]{8}|[0-9a-fA-F]{6}|[0-9a-fA-F]{3})\b|rgba?\(\s*(\d{1,3})\s*,\s*(\d{1,3})\s*,\s*(\d{1,3})",
+    )
+    .unwrap()
+});
+
+const SWATCH: &str = "■";
+/// Columns taken by a single swatch, including the space in front of it.
+const SWATCH_WIDTH: usize = 2;
+
+/// Renders a swatch for every color literal of a visible line after the end of that line.
+pub fn render(doc: &Document, view: &View, surface: &mut Surface) {
+    let text = doc.text().slice(..);
+    let inner = view.inner_area(doc);
+
+    for line in view.offset.row..=view.last_line(doc) {
+        let line_end = line_end_char_index(&text, line);
+        let pos = match view.screen_coords_at_pos(doc, text, line_end) {
+            Some(pos) => pos,
+            None => continue,
+        };
+        // the end of the line is scrolled out of view
+        if visual_coords_at_pos(text, line_end, doc.tab_width()).col < view.offset.col {
+            continue;
+        }
+        let line_text = text.line(line).to_string();
+
+        // leave a space between the line and the first swatch
+        let mut x = inner.x as usize + pos.col + 1;
+        let y = inner.y + pos.row as u16;
+        let colors = COLOR_LITERAL
+            .captures_iter(&line_text)
+            .filter_map(parse_color);
+        for color in colors {
+            if x >= inner.right() as usize {
+                break;
+            }
+            surface.set_string(x as u16, y, SWATCH, Style::default().fg(color));
+            x += SWATCH_WIDTH;
+        }
+    }
+}
+
+/// Number of columns the swatches of `line_text` take after the end of the line.
+pub fn width(line_text: &str) -> usize {
+    COLOR_LITERAL
+        .captures_iter(line_text)
+        .filter_map(parse_color)
+        .count()
+        * SWATCH_WIDTH
+}
+
+fn parse_color(captures: Captures) -> Option<Color> {
+    match (captures.get(1), captures.get(2), captures.get(3)) {
+        (Some(r), Some(g), Some(b)) => Some(Color::Rgb(
+            r.as_str().parse().ok()?,
+            g.as_str().parse().ok()?,
+            b.as_str().parse().ok()?,
+        )),
+        _ => parse_hex_color(captures.get(0)?.as_str()),
+    }
+}
+
+/// Parses a `#rgb`, `#rrggbb` or `#rrggbbaa` literal, the alpha channel is ignored.
+fn parse_hex_color(literal: &str) -> Option<Color> {
+    let hex = literal.strip_prefix('#')?;
+    let channel =
+        |i: usize, len: usize| u8::from_str_radix(hex.get(i * len..(i + 1) * len)?, 16).ok();
+    match hex.len() {
+        3 => {
+            // #rgb is shorthand for #rrggbb
+            let expand = |i| channel(i, 1).map(|v| v * 0x11);
+            Some(Color::Rgb(expand(0)?, expand(1)?, expand(2)?))
+        }
+        6 | 8 => Some(Color::Rgb(channel(0, 2)?, channel(1, 2)?, channel(2, 2)?)),
+        _ => None,
+    }
+}
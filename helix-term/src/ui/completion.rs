@@ -396,8 +396,9 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             let language = doc.language_name().unwrap_or("");
             let text = doc.text().slice(..);
             let cursor_pos = doc.selection(view.id).primary().cursor(text);
-            let coords = helix_core::visual_coords_at_pos(text, cursor_pos, doc.tab_width());
-            let cursor_pos = (coords.row - view.offset.row) as u16;
+            let cursor_pos = view
+                .screen_coords_at_pos(doc, text, cursor_pos)
+                .map_or(0, |coords| coords.row as u16);
 
             let mut markdown_doc = match &option.documentation {
                 Some(lsp::Documentation::String(contents))
@@ -0,0 +1,63 @@
+use helix_core::conflict::Conflict;
+use helix_view::{
+    graphics::{Rect, Style},
+    Document, Theme, View,
+};
+use tui::buffer::Buffer as Surface;
+
+/// Styles of the parts of a conflict.
+struct ConflictStyles {
+    marker: Style,
+    ours: Style,
+    base: Style,
+    theirs: Style,
+}
+
+impl ConflictStyles {
+    fn new(theme: &Theme) -> Self {
+        let get = |scope: &str, fallback: &str| {
+            theme.try_get(scope).unwrap_or_else(|| theme.get(fallback))
+        };
+        Self {
+            marker: get("ui.conflict.marker", "diff.minus"),
+            ours: get("ui.conflict.ours", "diff.plus"),
+            base: get("ui.conflict.base", "ui.text.inactive"),
+            theirs: get("ui.conflict.theirs", "diff.delta"),
+        }
+    }
+
+    fn line_style(&self, conflict: &Conflict, line: usize) -> Style {
+        if conflict.is_marker(line) {
+            self.marker
+        } else if conflict.ours().contains(&line) {
+            self.ours
+        } else if conflict.theirs().contains(&line) {
+            self.theirs
+        } else {
+            self.base
+        }
+    }
+}
+
+/// Highlights the visible lines of the conflicts left by a merge.
+pub fn render(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme) {
+    let conflicts = doc.conflicts();
+    if conflicts.is_empty() {
+        return;
+    }
+    let styles = ConflictStyles::new(theme);
+    let inner = view.inner_area(doc);
+
+    // conflicts are sorted by their position
+    let mut conflicts = conflicts.iter().peekable();
+    for (line, row, rows) in view.visible_lines(doc) {
+        while conflicts.next_if(|conflict| conflict.end < line).is_some() {}
+        let conflict = match conflicts.peek() {
+            Some(conflict) if conflict.start <= line => conflict,
+            Some(_) => continue,
+            None => break,
+        };
+        let area = Rect::new(inner.x, inner.y + row as u16, inner.width, rows as u16);
+        surface.set_style(area, styles.line_style(conflict, line));
+    }
+}
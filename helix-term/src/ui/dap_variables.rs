@@ -0,0 +1,396 @@
+use crate::commands::dap::dap_callback;
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::job::Jobs;
+use crate::{ctrl, key};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{cmp::Ordering, future::Future};
+
+use anyhow::{anyhow, bail, Result};
+use helix_core::Position;
+use helix_dap::{self as dap, requests, Scope, Variable};
+use helix_view::{
+    graphics::{CursorKind, Rect},
+    Editor,
+};
+
+use serde_json::Value;
+
+use super::{
+    completers,
+    sidebar::{self, TreePanel},
+    Prompt, Tree, TreeItem, TreeOp,
+};
+
+/// A row of the sidebar: a scope of the current stack frame, or a variable.
+#[derive(Debug, Clone)]
+struct VariableItem {
+    /// The positions of the item and of its parents among their siblings, in the order
+    /// of the debug adapter.
+    key: Vec<usize>,
+    name: String,
+    /// `None` for scopes.
+    value: Option<String>,
+    ty: Option<String>,
+    /// The reference to request the children with, 0 without children.
+    variables_reference: usize,
+    /// The reference of the scope or variable holding the variable, to set its value.
+    container: usize,
+    /// Whether the children were requested, to be shown again once folded.
+    requested: bool,
+}
+
+impl VariableItem {
+    fn scope(index: usize, scope: Scope) -> Self {
+        Self {
+            key: vec![index],
+            name: scope.name,
+            value: None,
+            ty: None,
+            variables_reference: scope.variables_reference,
+            container: 0,
+            requested: false,
+        }
+    }
+
+    fn children(&self, variables: Vec<Variable>) -> Vec<Self> {
+        variables
+            .into_iter()
+            .enumerate()
+            .map(|(index, variable)| {
+                let mut key = self.key.clone();
+                key.push(index);
+                Self {
+                    key,
+                    name: variable.name,
+                    value: Some(variable.value),
+                    ty: variable.ty,
+                    variables_reference: variable.variables_reference,
+                    container: self.variables_reference,
+                    requested: false,
+                }
+            })
+            .collect()
+    }
+}
+
+impl TreeItem for VariableItem {
+    /// The generation of the scopes listed.
+    type Params = usize;
+
+    fn text(&self, cx: &mut Context, selected: bool, _generation: &mut usize) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        let value = match &self.value {
+            Some(value) => value,
+            None => return Spans::from(Span::styled(&self.name, style("ui.linenr.selected"))),
+        };
+        let mut spans = vec![Span::styled(&self.name, style("ui.text.focus"))];
+        if let Some(ty) = &self.ty {
+            spans.push(Span::styled(": ", style("ui.text")));
+            spans.push(Span::styled(ty, style("ui.text")));
+        }
+        spans.push(Span::styled(" = ", style("ui.text")));
+        spans.push(Span::styled(value, style("ui.text.focus")));
+        Spans::from(spans)
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        self.key.len() == other.key.len() + 1 && self.key.starts_with(&other.key)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+/// Requests the scopes of the stack frame the debugger stopped in.
+fn request_scopes(editor: &Editor) -> Result<impl Future<Output = dap::Result<Value>>> {
+    let debugger = editor
+        .debugger
+        .as_ref()
+        .ok_or_else(|| anyhow!("No debug session"))?;
+    let (frame, thread_id) = match (debugger.active_frame, debugger.thread_id) {
+        (Some(frame), Some(thread_id)) => (frame, thread_id),
+        (_, None) => bail!("Cannot access variables while target is running"),
+        _ => bail!("Cannot find current stack frame to access variables"),
+    };
+    let frame_id = debugger.stack_frames[&thread_id][frame].id;
+    Ok(debugger.call::<requests::Scopes>(requests::ScopesArguments { frame_id }))
+}
+
+/// The panel once a response arrives, unless it was closed or the scopes were requested
+/// again since the request of `generation`.
+fn find_panel(compositor: &mut Compositor, generation: usize) -> Option<&mut DebugVariables> {
+    compositor
+        .find_id::<DebugVariables>(DebugVariables::ID)
+        .filter(|panel| panel.generation == generation)
+}
+
+/// The scopes of the current stack frame as a sidebar, with their variables and the
+/// fields of these requested from the debug adapter as they are expanded. The requests
+/// are sent as jobs, their responses fill the panel once they arrive.
+pub struct DebugVariables {
+    tree: Tree<VariableItem>,
+    /// Counts the requests of the scopes, the responses to the earlier ones are dropped.
+    generation: usize,
+    /// Editing the value of the current variable.
+    prompt: Option<Prompt>,
+    prompt_area: Rect,
+    focused: bool,
+    area: Rect,
+}
+
+impl Default for DebugVariables {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DebugVariables {
+    pub const ID: &'static str = "dap-variables";
+
+    pub fn new() -> Self {
+        let tree = Tree::build_tree(Vec::new()).with_enter_fn(
+            |item: &mut VariableItem, cx: &mut Context, generation: &mut usize| {
+                if item.variables_reference == 0 {
+                    return TreeOp::Noop;
+                }
+                // the children were folded
+                if item.requested {
+                    return TreeOp::Restore;
+                }
+                let debugger = match &cx.editor.debugger {
+                    Some(debugger) => debugger,
+                    None => return TreeOp::Noop,
+                };
+                item.requested = true;
+                let request = debugger.call::<requests::Variables>(requests::VariablesArguments {
+                    variables_reference: item.variables_reference,
+                    filter: None,
+                    start: None,
+                    count: None,
+                    format: None,
+                });
+                let (parent, generation) = (item.clone(), *generation);
+                dap_callback(
+                    cx.jobs,
+                    request,
+                    move |_editor, compositor, response: requests::VariablesResponse| {
+                        if let Some(panel) = find_panel(compositor, generation) {
+                            panel.tree.extend(parent.children(response.variables));
+                        }
+                    },
+                );
+                TreeOp::Noop
+            },
+        );
+        Self {
+            tree,
+            generation: 0,
+            prompt: None,
+            prompt_area: Rect::default(),
+            focused: true,
+            area: Rect::default(),
+        }
+    }
+
+    /// Lists the scopes again, after the debugger stopped elsewhere.
+    pub fn refresh(&mut self, editor: &Editor, jobs: &mut Jobs) -> Result<()> {
+        let request = request_scopes(editor)?;
+        self.generation += 1;
+        let generation = self.generation;
+        dap_callback(
+            jobs,
+            request,
+            move |editor, compositor, response: requests::ScopesResponse| {
+                if response.scopes.is_empty() {
+                    editor.set_error("No variables in the current stack frame");
+                }
+                if let Some(panel) = find_panel(compositor, generation) {
+                    let items = response.scopes.into_iter().enumerate();
+                    let items = items.map(|(index, scope)| VariableItem::scope(index, scope));
+                    panel.tree.replace_with_new_items(items.collect());
+                }
+            },
+        );
+        Ok(())
+    }
+
+    fn edit_current(&mut self, editor: &mut Editor) {
+        let item = self.tree.current_item();
+        let value = match &item.value {
+            Some(value) => value.clone(),
+            None => return editor.set_error("Only variables can be edited"),
+        };
+        let supported = editor.debugger.as_ref().map_or(false, |debugger| {
+            debugger
+                .capabilities()
+                .supports_set_variable
+                .unwrap_or(false)
+        });
+        if !supported {
+            return editor.set_error("The debug adapter cannot set variables");
+        }
+        let prompt = Prompt::new(
+            format!("{} = ", item.name).into(),
+            None,
+            completers::none,
+            |_, _, _| {},
+        )
+        .with_line(value, editor);
+        self.prompt = Some(prompt);
+    }
+
+    fn set_current(&mut self, value: String, cx: &mut Context) {
+        let debugger = match &cx.editor.debugger {
+            Some(debugger) => debugger,
+            None => return,
+        };
+        let item = self.tree.current_item().clone();
+        let request = debugger.call::<requests::SetVariable>(requests::SetVariableArguments {
+            variables_reference: item.container,
+            name: item.name.clone(),
+            value,
+            format: None,
+        });
+        let generation = self.generation;
+        dap_callback(
+            cx.jobs,
+            request,
+            move |_editor, compositor, response: requests::SetVariableResponse| {
+                let panel = match find_panel(compositor, generation) {
+                    Some(panel) => panel,
+                    None => return,
+                };
+                if !panel.tree.select(|other| other.key == item.key) {
+                    return;
+                }
+                // the fields listed are the ones of the previous value
+                let key = &item.key;
+                panel.tree.remove_where(|other| {
+                    other.key.len() > key.len() && other.key.starts_with(key)
+                });
+                let mut item = item;
+                item.value = Some(response.value);
+                item.ty = response.ty.or(item.ty);
+                if let Some(reference) = response.variables_reference {
+                    item.variables_reference = reference;
+                }
+                item.requested = false;
+                panel.tree.replace_current(item);
+            },
+        );
+    }
+
+    fn handle_prompt_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let mut prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return EventResult::Ignored(None),
+        };
+        match event {
+            Event::Key(key!(Enter)) => self.set_current(prompt.line().clone(), cx),
+            Event::Key(key!(Esc) | ctrl!('c')) => {}
+            _ => {
+                prompt.handle_event(event, cx);
+                self.prompt = Some(prompt);
+            }
+        }
+        EventResult::Consumed(None)
+    }
+}
+
+impl TreePanel for DebugVariables {
+    type Item = VariableItem;
+
+    fn parts(&mut self) -> (&mut Tree<VariableItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for DebugVariables {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        if self.prompt.is_some() {
+            return self.handle_prompt_event(event, cx);
+        }
+        let key = match event {
+            Event::Mouse(event) => return self.handle_mouse_event(event),
+            Event::Key(key) if self.focused => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            key!('r') => {
+                if let Err(err) = self.refresh(cx.editor, cx.jobs) {
+                    cx.editor.set_error(err.to_string());
+                }
+            }
+            _ if self.tree.items().next().is_none() => {}
+            key!('e') => self.edit_current(cx.editor),
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.generation)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.area = sidebar::area(area);
+        let help = "e: edit  r: refresh  q: close";
+        let mut tree_area = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            "Variables",
+            help,
+            self.focused,
+        );
+        if let Some(prompt) = &self.prompt {
+            self.prompt_area = tree_area.clip_top(tree_area.height.saturating_sub(1));
+            tree_area = tree_area.clip_bottom(1);
+            prompt.render_prompt(self.prompt_area, surface, cx);
+        }
+        if self.tree.items().next().is_none() {
+            let style = cx.editor.theme.get("ui.text.inactive");
+            surface.set_stringn(
+                tree_area.x,
+                tree_area.y,
+                "No variables",
+                tree_area.width as usize,
+                style,
+            );
+            return;
+        }
+        self.tree
+            .render(tree_area, surface, cx, &mut self.generation);
+    }
+
+    fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        match &self.prompt {
+            Some(prompt) => prompt.cursor(self.prompt_area, editor),
+            None => (None, CursorKind::Hidden),
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
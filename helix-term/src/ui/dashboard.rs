@@ -0,0 +1,287 @@
+use crate::commands::{self, MappableCommand};
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::{ctrl, key, shift};
+use tui::buffer::Buffer as Surface;
+
+use std::path::PathBuf;
+
+use helix_core::{path::fold_home_dir, unicode::width::UnicodeWidthStr};
+use helix_view::{
+    editor::Action,
+    graphics::Rect,
+    input::{KeyEvent, MouseButton, MouseEventKind},
+    keyboard::{KeyCode, KeyModifiers},
+    Editor,
+};
+
+/// What an entry of the dashboard does.
+#[derive(Clone)]
+enum Target {
+    Command(MappableCommand),
+    /// Changes the working directory and opens the explorer in it.
+    Workspace(PathBuf),
+    File(PathBuf),
+}
+
+struct Entry {
+    key: Option<char>,
+    label: String,
+    target: Target,
+}
+
+/// The screen shown instead of an empty scratch buffer when helix is started without
+/// files: opening the explorer, the configured shortcuts and the recent workspaces
+/// and files.
+pub struct Dashboard {
+    entries: Vec<Entry>,
+    /// Titles of the sections, with the index of their first entry.
+    sections: Vec<(usize, &'static str)>,
+    cursor: usize,
+    /// Screen rows of the entries as of the last render, used for mouse clicks.
+    rows: Vec<(u16, usize)>,
+}
+
+impl Dashboard {
+    pub const ID: &'static str = "dashboard";
+
+    pub fn new(editor: &mut Editor) -> Self {
+        let config = editor.config();
+        let mut entries = vec![Entry {
+            key: Some('e'),
+            label: "Open explorer here".to_string(),
+            target: Target::Command(MappableCommand::toggle_or_focus_explorer),
+        }];
+        for shortcut in &config.dashboard.shortcuts {
+            match shortcut.command.parse() {
+                Ok(command) => entries.push(Entry {
+                    key: Some(shortcut.key),
+                    label: shortcut.label.clone(),
+                    target: Target::Command(command),
+                }),
+                Err(err) => editor.set_error(format!("dashboard shortcut: {}", err)),
+            }
+        }
+
+        let recent = &editor.recent;
+        let mut sections = Vec::new();
+        // the recent paths are numbered across both sections
+        let mut digits = ('1'..='9').chain(Some('0'));
+        let mut push_recent = |entries: &mut Vec<Entry>, path: &PathBuf, target: Target| {
+            entries.push(Entry {
+                key: digits.next(),
+                label: fold_home_dir(path).to_string_lossy().into_owned(),
+                target,
+            })
+        };
+
+        let workspaces: Vec<_> = recent
+            .existing_workspaces(config.dashboard.recent)
+            .collect();
+        if !workspaces.is_empty() {
+            sections.push((entries.len(), "Recent workspaces"));
+            for path in workspaces {
+                push_recent(&mut entries, path, Target::Workspace(path.clone()));
+            }
+        }
+        let files: Vec<_> = recent.existing_files(config.dashboard.recent).collect();
+        if !files.is_empty() {
+            sections.push((entries.len(), "Recent files"));
+            for path in files {
+                push_recent(&mut entries, path, Target::File(path.clone()));
+            }
+        }
+
+        Self {
+            entries,
+            sections,
+            cursor: 0,
+            rows: Vec::new(),
+        }
+    }
+
+    fn move_cursor(&mut self, forward: bool) {
+        let len = self.entries.len();
+        self.cursor = if forward {
+            (self.cursor + 1) % len
+        } else {
+            (self.cursor + len - 1) % len
+        };
+    }
+
+    /// Closes the dashboard and runs the entry at `index`.
+    fn activate(&self, index: usize) -> EventResult {
+        let target = self.entries[index].target.clone();
+        let callback: Callback = Box::new(move |compositor, cx| {
+            compositor.remove(Self::ID);
+            match target {
+                Target::Command(command) => run_command(&command, compositor, cx),
+                Target::Workspace(path) => {
+                    if let Err(err) = std::env::set_current_dir(&path) {
+                        cx.editor
+                            .set_error(format!("Failed to change directory: {}", err));
+                        return;
+                    }
+                    cx.editor.set_status(format!(
+                        "Current working directory is now {}",
+                        path.display()
+                    ));
+                    let command = MappableCommand::toggle_or_focus_explorer;
+                    run_command(&command, compositor, cx);
+                }
+                Target::File(path) => {
+                    if let Err(err) = cx.editor.open(&path, Action::Replace) {
+                        cx.editor.set_error(format!("Failed to open file: {}", err));
+                    }
+                }
+            }
+        });
+        EventResult::Consumed(Some(callback))
+    }
+}
+
+/// Runs `command` as if it was bound to a key of the editor.
+fn run_command(command: &MappableCommand, compositor: &mut Compositor, cx: &mut Context) {
+    let callback = {
+        let mut cx = commands::Context {
+            register: None,
+            count: None,
+            editor: cx.editor,
+            callback: None,
+            on_next_key_callback: None,
+            jobs: cx.jobs,
+        };
+        command.execute(&mut cx);
+        cx.callback
+    };
+    if let Some(callback) = callback {
+        callback(compositor, cx);
+    }
+}
+
+fn close() -> Callback {
+    Box::new(|compositor, _| {
+        compositor.remove(Dashboard::ID);
+    })
+}
+
+impl Component for Dashboard {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            Event::Mouse(event) => {
+                match event.kind {
+                    MouseEventKind::Down(MouseButton::Left) => {
+                        let row = self.rows.iter().find(|(row, _)| *row == event.row);
+                        if let Some(&(_, index)) = row {
+                            return self.activate(index);
+                        }
+                    }
+                    MouseEventKind::ScrollDown => self.move_cursor(true),
+                    MouseEventKind::ScrollUp => self.move_cursor(false),
+                    _ => (),
+                }
+                return EventResult::Consumed(None);
+            }
+            // the dashboard makes way for the pasted text
+            Event::Paste(_) => return EventResult::Ignored(Some(close())),
+            _ => return EventResult::Ignored(None),
+        };
+
+        match key {
+            key!(Esc) => EventResult::Consumed(Some(close())),
+            key!('j') | key!(Down) | ctrl!('n') | key!(Tab) => {
+                self.move_cursor(true);
+                EventResult::Consumed(None)
+            }
+            key!('k') | key!(Up) | ctrl!('p') | shift!(Tab) => {
+                self.move_cursor(false);
+                EventResult::Consumed(None)
+            }
+            key!(Enter) => self.activate(self.cursor),
+            KeyEvent {
+                code: KeyCode::Char(ch),
+                modifiers,
+            } if !modifiers.intersects(KeyModifiers::CONTROL | KeyModifiers::ALT) => {
+                match self.entries.iter().position(|entry| entry.key == Some(ch)) {
+                    Some(index) => self.activate(index),
+                    None => EventResult::Ignored(Some(close())),
+                }
+            }
+            // any other key goes to the scratch buffer behind the dashboard
+            _ => EventResult::Ignored(Some(close())),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        // keep the last row free for the status messages
+        let area = area.clip_bottom(1);
+        surface.clear_with(area, theme.get("ui.background"));
+        let text_style = theme.get("ui.text");
+        let title_style = theme.get("ui.text.focus");
+        let section_style = theme.get("ui.text.inactive");
+        let key_style = theme.get("special");
+        let selected_style = theme.get("ui.menu.selected");
+
+        let label_width = self
+            .entries
+            .iter()
+            .map(|entry| entry.label.width())
+            .max()
+            .unwrap_or(0);
+        // the key, two spaces and the label
+        let width = (label_width + 3).min(area.width as usize) as u16;
+        let height = 2 + self.entries.len() + 2 * self.sections.len();
+        let x = area.x + (area.width - width) / 2;
+        let mut y = area.y + area.height.saturating_sub(height as u16) / 2;
+
+        let title = format!("helix {}", helix_loader::VERSION_AND_GIT_HASH);
+        let title_x = area.x + area.width.saturating_sub(title.width() as u16) / 2;
+        surface.set_stringn(title_x, y, &title, area.width as usize, title_style);
+        y += 2;
+
+        self.rows.clear();
+        let mut sections = self.sections.iter().peekable();
+        for (index, entry) in self.entries.iter().enumerate() {
+            if let Some((_, title)) = sections.next_if(|(start, _)| *start == index) {
+                y += 1;
+                if y >= area.bottom() {
+                    break;
+                }
+                surface.set_stringn(x, y, title, width as usize, section_style);
+                y += 1;
+            }
+            if y >= area.bottom() {
+                break;
+            }
+
+            let key = entry.key.map(String::from).unwrap_or_default();
+            let (key_style, label_style) = if index == self.cursor {
+                (key_style.patch(selected_style), selected_style)
+            } else {
+                (key_style, text_style)
+            };
+            surface.set_stringn(
+                x,
+                y,
+                " ".repeat(width as usize),
+                width as usize,
+                label_style,
+            );
+            surface.set_stringn(x, y, &key, 1, key_style);
+            surface.set_stringn(
+                x + 3,
+                y,
+                &entry.label,
+                width.saturating_sub(3) as usize,
+                label_style,
+            );
+            self.rows.push((y, index));
+            y += 1;
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
@@ -0,0 +1,302 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::{ctrl, key, shift};
+use tui::buffer::Buffer as Surface;
+
+use std::ops::Range;
+
+use helix_core::{unicode::width::UnicodeWidthChar, Rope, RopeSlice};
+use helix_vcs::DiffRow;
+use helix_view::{
+    graphics::{Modifier, Rect, Style},
+    input::MouseEventKind,
+    Theme,
+};
+
+/// Rows of unchanged lines kept above a change when jumping to it.
+const CONTEXT_ROWS: usize = 3;
+
+/// One of the two texts of a diff.
+pub struct DiffSide {
+    pub title: String,
+    pub text: Rope,
+    pub tab_width: usize,
+}
+
+/// Two texts shown side by side, scrolled together. Lines missing on one side are
+/// aligned with filler rows, changed words of modified lines are highlighted.
+pub struct DiffView {
+    before: DiffSide,
+    after: DiffSide,
+    rows: Vec<DiffRow>,
+    /// The char ranges of the words changed in each row, before and after, only set for
+    /// the modified lines.
+    words: Vec<(Vec<Range<usize>>, Vec<Range<usize>>)>,
+    /// The first row on screen.
+    offset: usize,
+    /// Columns scrolled to the right.
+    col: usize,
+    /// Text rows on screen as of the last render, used to scroll by pages.
+    height: usize,
+    /// The row of the change last jumped to, cleared when scrolling.
+    change: Option<usize>,
+}
+
+impl DiffView {
+    pub const ID: &'static str = "diff";
+
+    pub fn new(before: DiffSide, after: DiffSide) -> Self {
+        let hunks = helix_vcs::compare_ropes(&before.text, &after.text);
+        let rows = helix_vcs::side_by_side(&hunks, before.text.len_lines(), after.text.len_lines());
+        let words = rows
+            .iter()
+            .map(|row| match (row.before, row.after) {
+                (Some(before_line), Some(after_line)) if row.changed => {
+                    let before_line = before.text.line(before_line).to_string();
+                    let after_line = after.text.line(after_line).to_string();
+                    helix_vcs::changed_words(&before_line, &after_line)
+                }
+                _ => Default::default(),
+            })
+            .collect();
+        let mut view = Self {
+            before,
+            after,
+            rows,
+            words,
+            offset: 0,
+            col: 0,
+            height: 0,
+            change: None,
+        };
+        view.goto_change(true);
+        view
+    }
+
+    /// Whether any lines differ.
+    pub fn has_changes(&self) -> bool {
+        self.rows.iter().any(|row| row.changed)
+    }
+
+    fn is_change_start(&self, row: usize) -> bool {
+        self.rows[row].changed && (row == 0 || !self.rows[row - 1].changed)
+    }
+
+    fn max_offset(&self) -> usize {
+        self.rows.len().saturating_sub(self.height.max(1))
+    }
+
+    fn scroll(&mut self, rows: isize) {
+        let max = self.max_offset() as isize;
+        self.offset = (self.offset as isize + rows).clamp(0, max) as usize;
+        self.change = None;
+    }
+
+    /// Scrolls to the next or previous change, leaving a few rows of context above it.
+    /// Changes are searched from the top of the screen after scrolling.
+    fn goto_change(&mut self, forward: bool) {
+        let is_change_start = |row: &usize| self.is_change_start(*row);
+        let change = match (forward, self.change) {
+            (true, Some(change)) => (change + 1..self.rows.len()).find(is_change_start),
+            (true, None) => (self.offset..self.rows.len()).find(is_change_start),
+            (false, Some(change)) => (0..change).rev().find(is_change_start),
+            (false, None) => (0..self.offset).rev().find(is_change_start),
+        };
+        if let Some(row) = change {
+            self.offset = row.saturating_sub(CONTEXT_ROWS).min(self.max_offset());
+            self.change = Some(row);
+        }
+    }
+
+    fn render_side(
+        &self,
+        side: &DiffSide,
+        is_before: bool,
+        area: Rect,
+        surface: &mut Surface,
+        theme: &Theme,
+    ) {
+        let title_style = theme.get("ui.statusline");
+        surface.clear_with(area.with_height(1), title_style);
+        surface.set_stringn(
+            area.x + 1,
+            area.y,
+            &side.title,
+            area.width.saturating_sub(1) as usize,
+            title_style,
+        );
+
+        let area = area.clip_top(1);
+        let gutter_width = side.text.len_lines().to_string().len() as u16 + 1;
+        let text_area = area.clip_left(gutter_width);
+        let linenr = theme.get("ui.linenr");
+        let filler = theme
+            .try_get("ui.diff.filler")
+            .unwrap_or_else(|| theme.get("ui.virtual.whitespace"));
+        let scope = if is_before { "diff.minus" } else { "diff.plus" };
+        let changed = theme.get(scope);
+        let modified = theme.get("diff.delta");
+
+        let rows = self
+            .rows
+            .iter()
+            .zip(&self.words)
+            .skip(self.offset)
+            .take(area.height as usize);
+        for (y, (row, (removed, added))) in (area.y..).zip(rows) {
+            let (line, other_line) = if is_before {
+                (row.before, row.after)
+            } else {
+                (row.after, row.before)
+            };
+            let line = match line {
+                Some(line) => line,
+                None => {
+                    let width = area.width as usize;
+                    surface.set_stringn(area.x, y, "╱".repeat(width), width, filler);
+                    continue;
+                }
+            };
+
+            surface.set_stringn(
+                area.x,
+                y,
+                format!("{:>1$}", line + 1, gutter_width as usize - 1),
+                gutter_width as usize,
+                linenr,
+            );
+
+            let text = side.text.line(line);
+            let (style, words) = match other_line {
+                _ if !row.changed => (Style::default(), Vec::new()),
+                // a modified line, highlight the words that changed
+                Some(_) => {
+                    let words = if is_before { removed } else { added };
+                    let words_style = changed.add_modifier(Modifier::REVERSED);
+                    (
+                        modified,
+                        words.iter().map(|w| (w.clone(), words_style)).collect(),
+                    )
+                }
+                None => (changed, Vec::new()),
+            };
+            self.render_line(
+                text,
+                side.tab_width,
+                Rect::new(text_area.x, y, text_area.width, 1),
+                surface,
+                style,
+                &words,
+            );
+        }
+    }
+
+    fn render_line(
+        &self,
+        text: RopeSlice,
+        tab_width: usize,
+        area: Rect,
+        surface: &mut Surface,
+        style: Style,
+        words: &[(Range<usize>, Style)],
+    ) {
+        let mut col = 0;
+        for (char_idx, ch) in text.chars().enumerate() {
+            if ch == '\n' || ch == '\r' {
+                break;
+            }
+            let (ch, width) = match ch {
+                '\t' => (' ', tab_width - col % tab_width),
+                ch => (ch, ch.width().unwrap_or(0)),
+            };
+            let style = words
+                .iter()
+                .find(|(range, _)| range.contains(&char_idx))
+                .map_or(style, |(_, word_style)| style.patch(*word_style));
+            for i in 0..width {
+                // wide characters that are scrolled partly out of view are skipped
+                let x = match (col + i).checked_sub(self.col) {
+                    Some(x) if col >= self.col => x,
+                    _ => continue,
+                };
+                if x >= area.width as usize {
+                    return;
+                }
+                let cell = &mut surface[(area.x + x as u16, area.y)];
+                // tabs are drawn as spaces, wide chars take their first cell
+                if i == 0 || ch == ' ' {
+                    cell.set_char(ch);
+                }
+                cell.set_style(style);
+            }
+            col += width;
+        }
+    }
+}
+
+impl Component for DiffView {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            Event::Mouse(event) => {
+                match event.kind {
+                    MouseEventKind::ScrollDown => self.scroll(3),
+                    MouseEventKind::ScrollUp => self.scroll(-3),
+                    _ => return EventResult::Ignored(None),
+                }
+                return EventResult::Consumed(None);
+            }
+            // keep the paste from reaching the buffers behind the diff
+            Event::Paste(_) => return EventResult::Consumed(None),
+            _ => return EventResult::Ignored(None),
+        };
+
+        let page = self.height.max(1) as isize;
+        match key {
+            key!('q') | key!(Esc) => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!('j') | key!(Down) => self.scroll(1),
+            key!('k') | key!(Up) => self.scroll(-1),
+            ctrl!('d') => self.scroll(page / 2),
+            ctrl!('u') => self.scroll(-page / 2),
+            ctrl!('f') | key!(PageDown) => self.scroll(page),
+            ctrl!('b') | key!(PageUp) => self.scroll(-page),
+            key!('g') | key!(Home) => self.scroll(-(self.rows.len() as isize)),
+            shift!('G') | key!(End) => self.scroll(self.rows.len() as isize),
+            key!('l') | key!(Right) => self.col += 4,
+            key!('h') | key!(Left) => self.col = self.col.saturating_sub(4),
+            key!('n') | key!(']') => self.goto_change(true),
+            shift!('N') | key!('[') => self.goto_change(false),
+            _ => (),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        // keep the last row free for the status messages
+        let area = area.clip_bottom(1);
+        surface.clear_with(area, theme.get("ui.background"));
+        self.height = area.height.saturating_sub(1) as usize;
+
+        let width = area.width.saturating_sub(1) / 2;
+        let before_area = area.with_width(width);
+        let after_area = area.clip_left(width + 1);
+        self.render_side(&self.before, true, before_area, surface, theme);
+        self.render_side(&self.after, false, after_area, surface, theme);
+
+        let border_style = theme.get("ui.window");
+        for y in area.top()..area.bottom() {
+            surface[(area.x + width, y)]
+                .set_symbol(tui::symbols::line::VERTICAL)
+                .set_style(border_style);
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
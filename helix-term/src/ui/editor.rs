@@ -3,45 +3,95 @@
     compositor::{Component, Context, Event, EventResult},
     job::{self, Callback},
     key,
-    keymap::{KeymapResult, Keymaps},
-    ui::{overlay::Overlay,Completion, Explorer, ProgressSpinners},
+    keymap::{
+        mouse::{MouseBinding, MouseMap},
+        KeymapResult, Keymaps,
+    },
+    ui::{
+        icons,
+        overlay::{overlayed, Overlay},
+        Completion, Explorer, ExplorerCommand, Markdown, Popup, ProgressSpinners, Terminal,
+    },
 };
 
 use helix_core::{
+    chars::char_is_whitespace,
     graphemes::{
         ensure_grapheme_boundary_next_byte, next_grapheme_boundary, prev_grapheme_boundary,
     },
+    line_ending::line_end_char_index,
     movement::Direction,
     syntax::{self, HighlightEvent},
-    unicode::width::UnicodeWidthStr,
-    visual_coords_at_pos, LineEnding, Position, Range, Selection, Transaction,
+    textobject,
+    unicode::width::{UnicodeWidthChar, UnicodeWidthStr},
+    visual_coords_at_pos, LineEnding, Position, Range, RopeSlice, Selection, Transaction,
 };
 use helix_view::{
-    apply_transaction,
-    document::{Mode, SCRATCH_BUFFER_NAME},
-    editor::{CompleteAction, CursorShapeConfig},
+    align_view, apply_transaction,
+    document::{InlayHint, Mode, SCRATCH_BUFFER_NAME},
+    editor::{Action, CompleteAction, CursorShapeConfig, GutterType, StatusLineElement},
     graphics::{Color, CursorKind, Modifier, Rect, Style},
     input::{KeyEvent, MouseButton, MouseEvent, MouseEventKind},
     keyboard::{KeyCode, KeyModifiers},
-    Document, Editor, Theme, View,
+    session::ExplorerSession,
+    Align, Document, DocumentId, Editor, Theme, View, ViewId,
+};
+use std::{
+    borrow::Cow,
+    cmp::min,
+    num::NonZeroUsize,
+    path::{Path, PathBuf},
+    time::{Duration, Instant},
 };
-use std::{borrow::Cow, cmp::min, num::NonZeroUsize, path::PathBuf};
 
+use arc_swap::{access::DynAccess, ArcSwap};
+use helix_lsp::lsp::InlayHintKind;
 use tui::buffer::Buffer as Surface;
 
 use super::lsp::SignatureHelp;
-use super::statusline;
+use super::{breadcrumbs, color_swatch, conflict, eol_diagnostics, minimap, scrollbar, statusline};
 
 pub struct EditorView {
     pub keymaps: Keymaps,
+    mousemap: Box<dyn DynAccess<MouseMap>>,
+    /// The button of the mouse binding run by the last press, whose release and drags
+    /// are left out.
+    mouse_binding_button: Option<MouseButton>,
     on_next_key: Option<Box<dyn FnOnce(&mut commands::Context, KeyEvent)>>,
     pseudo_pending: Vec<KeyEvent>,
     last_insert: (commands::MappableCommand, Vec<InsertEvent>),
     pub(crate) completion: Option<Completion>,
     spinners: ProgressSpinners,
     pub(crate) explorer: Option<Overlay<Explorer>>,
+    /// The explorer hidden while zen mode is on, restored when it is toggled off.
+    pub(crate) zen_explorer: Option<Overlay<Explorer>>,
+    /// The terminal panel below the views, closed once its shell exits.
+    pub(crate) terminal: Option<Terminal>,
+    /// The explorer of a restored session, opened by the next render.
+    restored_explorer: Option<ExplorerSession>,
+    /// The `:explorer` commands run by the next render.
+    explorer_commands: Vec<ExplorerCommand>,
+    /// Areas of the buffers in the bufferline as of the last render, used for mouse clicks.
+    bufferline_tabs: Vec<(Rect, DocumentId)>,
+    /// Areas of the tab pages in the tabline as of the last render, used for mouse clicks.
+    tabline_tabs: Vec<(Rect, usize)>,
+    /// Areas of the elements of the statuslines as of the last render, with their view,
+    /// used for mouse clicks.
+    statusline_elements: Vec<(Rect, ViewId, StatusLineElement)>,
+    /// Time the next frame of a smooth scroll is rendered at, `None` if nothing is animated.
+    scroll_frame: Option<Instant>,
+    /// The lines matching the last search, marked on the scrollbars.
+    scrollbar_marks: scrollbar::SearchMarks,
+    /// The view whose scrollbar is dragged with the mouse.
+    scrollbar_drag: Option<ViewId>,
 }
 
+/// Time between two frames of a smooth scroll.
+const SMOOTH_SCROLL_FRAME: Duration = Duration::from_millis(16);
+
+/// The id of the popup shown for the resting mouse pointer, closed when it moves.
+pub const MOUSE_HOVER_ID: &str = "mouse-hover";
+
 #[derive(Debug, Clone)]
 pub enum InsertEvent {
     Key(KeyEvent),
@@ -51,20 +101,35 @@ pub enum InsertEvent {
 
 impl Default for EditorView {
     fn default() -> Self {
-        Self::new(Keymaps::default())
+        Self::new(
+            Keymaps::default(),
+            Box::new(ArcSwap::from_pointee(MouseMap::default())),
+        )
     }
 }
 
 impl EditorView {
-    pub fn new(keymaps: Keymaps) -> Self {
+    pub fn new(keymaps: Keymaps, mousemap: Box<dyn DynAccess<MouseMap>>) -> Self {
         Self {
             keymaps,
+            mousemap,
+            mouse_binding_button: None,
             on_next_key: None,
             pseudo_pending: Vec::new(),
             last_insert: (commands::MappableCommand::normal_mode, Vec::new()),
             completion: None,
             spinners: ProgressSpinners::default(),
             explorer: None,
+            zen_explorer: None,
+            terminal: None,
+            restored_explorer: None,
+            explorer_commands: Vec::new(),
+            bufferline_tabs: Vec::new(),
+            tabline_tabs: Vec::new(),
+            statusline_elements: Vec::new(),
+            scroll_frame: None,
+            scrollbar_marks: scrollbar::SearchMarks::default(),
+            scrollbar_drag: None,
         }
     }
 
@@ -72,6 +137,19 @@ pub fn spinners_mut(&mut self) -> &mut ProgressSpinners {
         &mut self.spinners
     }
 
+    /// Opens a terminal panel running the user's shell in `cwd`, replacing any open one.
+    pub fn open_terminal(&mut self, cwd: &Path, editor: &mut Editor) {
+        let shell = std::env::var("SHELL")
+            .ok()
+            .or_else(|| editor.config().shell.first().cloned())
+            .unwrap_or_else(|| "sh".to_string());
+        match Terminal::new(&shell, cwd, editor.redraw_handle.0.clone()) {
+            Ok(terminal) => self.terminal = Some(terminal),
+            Err(err) => editor.set_error(format!("Failed to start the terminal: {}", err)),
+        }
+    }
+
+    /// Renders `view`, returns the areas of the elements of its statusline.
     pub fn render_view(
         &self,
         editor: &Editor,
@@ -80,7 +158,7 @@ pub fn render_view(
         viewport: Rect,
         surface: &mut Surface,
         is_focused: bool,
-    ) {
+    ) -> Vec<(Rect, StatusLineElement)> {
         let inner = view.inner_area(doc);
         let area = view.area;
         let theme = &editor.theme;
@@ -106,14 +184,12 @@ pub fn render_view(
                     == doc.path()
             {
                 let line = frame.line - 1; // convert to 0-indexing
-                if line >= view.offset.row && line < view.offset.row + area.height as usize {
+                if let Some((_, row, rows)) = view
+                    .visible_lines(doc)
+                    .find(|(visible, _, _)| *visible == line)
+                {
                     surface.set_style(
-                        Rect::new(
-                            area.x,
-                            area.y + (line - view.offset.row) as u16,
-                            area.width,
-                            1,
-                        ),
+                        Rect::new(area.x, area.y + row as u16, area.width, rows as u16),
                         theme.get("ui.highlight"),
                     );
                 }
@@ -151,9 +227,51 @@ pub fn render_view(
             Box::new(highlights)
         };
 
-        Self::render_text_highlights(doc, view.offset, inner, surface, theme, highlights, &config);
-        Self::render_gutter(editor, doc, view, view.area, surface, theme, is_focused);
+        let wrap_indicator = view
+            .soft_wrap
+            .map(|_| config.soft_wrap.wrap_indicator.as_str());
+        let inlay_hints = if view.soft_wrap.is_none() {
+            doc.inlay_hints()
+        } else {
+            &[]
+        };
+        let text_config = match view.options.whitespace {
+            Some(render) => {
+                let mut config = (*config).clone();
+                config.whitespace.render = render;
+                Cow::Owned(config)
+            }
+            None => Cow::Borrowed(&*config),
+        };
+        Self::render_text_highlights(
+            doc,
+            view.offset,
+            inner,
+            surface,
+            theme,
+            highlights,
+            &text_config,
+            wrap_indicator,
+            Some(doc.selection(view.id)),
+            inlay_hints,
+        );
+        conflict::render(doc, view, surface, theme);
+        let text_rows = view.text_rows_area();
+        Self::render_gutter(editor, doc, view, text_rows, surface, theme, is_focused);
+        if config.sticky_context.enable {
+            Self::render_sticky_context(doc, view, surface, theme, &config, is_focused);
+        }
+        // rulers are drawn across the sticky context to keep the columns continuous
         Self::render_rulers(editor, doc, view, inner, surface, theme);
+        if config.color_swatches {
+            color_swatch::render(doc, view, surface);
+        }
+        if config.end_of_line_diagnostics.enable {
+            eol_diagnostics::render(doc, view, surface, theme, &config);
+        }
+        minimap::render(doc, view, surface, theme);
+        let search_lines = self.scrollbar_marks.lines(view.doc);
+        scrollbar::render(doc, view, surface, theme, &search_lines);
 
         if is_focused {
             Self::render_focused_view_elements(view, doc, inner, theme, surface);
@@ -173,15 +291,28 @@ pub fn render_view(
 
         Self::render_diagnostics(doc, view, inner, surface, theme);
 
+        if config.breadcrumbs.enable {
+            breadcrumbs::render(doc, view, surface, theme, &config.breadcrumbs.separator);
+        }
+
+        // zen mode hides the statusline
+        if view.zen_width.is_some() {
+            return Vec::new();
+        }
+
         let statusline_area = view
             .area
             .clip_top(view.area.height.saturating_sub(1))
             .clip_bottom(1); // -1 from bottom to remove commandline
 
+        let explorer = self
+            .explorer
+            .as_ref()
+            .map(|explorer| explorer.content.is_focus());
         let mut context =
-            statusline::RenderContext::new(editor, doc, view, is_focused, &self.spinners);
+            statusline::RenderContext::new(editor, doc, view, is_focused, &self.spinners, explorer);
 
-        statusline::render(&mut context, statusline_area, surface);
+        statusline::render(&mut context, statusline_area, surface)
     }
 
     pub fn render_rulers(
@@ -206,12 +337,91 @@ pub fn render_rulers(
             .iter()
             // View might be horizontally scrolled, convert from absolute distance
             // from the 1st column to relative distance from left of viewport
-            .filter_map(|ruler| ruler.checked_sub(1 + view.offset.col as u16))
-            .filter(|ruler| ruler < &viewport.width)
-            .map(|ruler| viewport.clip_left(ruler).with_width(1))
+            .filter_map(|&ruler| (ruler as usize).checked_sub(1 + view.offset.col))
+            .filter(|&ruler| ruler < viewport.width as usize)
+            .map(|ruler| viewport.clip_left(ruler as u16).with_width(1))
             .for_each(|area| surface.set_style(area, ruler_theme))
     }
 
+    /// Pin the first lines of the classes and functions enclosing the top of the view
+    /// to the first rows of the view, so the current scope stays visible.
+    pub fn render_sticky_context(
+        doc: &Document,
+        view: &View,
+        surface: &mut Surface,
+        theme: &Theme,
+        config: &helix_view::editor::Config,
+        is_focused: bool,
+    ) {
+        let (syntax, lang_config) = match (doc.syntax(), doc.language_config()) {
+            (Some(syntax), Some(lang_config)) => (syntax, lang_config),
+            _ => return,
+        };
+        let text = doc.text().slice(..);
+        let tree = syntax.tree().root_node();
+        let inner = view.inner_area(doc);
+        let max_rows = config
+            .sticky_context
+            .max_depth
+            .min(inner.height as usize / 2);
+        let context_lines = |line: usize| {
+            let line = line.min(text.len_lines().saturating_sub(1));
+            textobject::textobject_context_lines(
+                text,
+                line,
+                &["class", "function"],
+                tree,
+                lang_config,
+            )
+        };
+
+        // the context covers the first rows of the view, show the scopes
+        // of the first line that is still visible below it
+        let rows = context_lines(view.offset.row).len().min(max_rows);
+        let mut lines = context_lines(view.offset.row + rows);
+        lines.truncate(max_rows);
+        if lines.is_empty() {
+            return;
+        }
+
+        // never hide the primary cursor
+        if is_focused {
+            let cursor = doc.selection(view.id).primary().cursor(text);
+            if let Some(pos) = view.screen_coords_at_pos(doc, text, cursor) {
+                if pos.row < lines.len() {
+                    return;
+                }
+            }
+        }
+
+        let style = theme
+            .try_get("ui.sticky-context")
+            .unwrap_or_else(|| theme.get("ui.cursorline.primary"));
+        let background = theme.get("ui.background");
+        for (row, &line) in lines.iter().enumerate() {
+            let y = inner.y + row as u16;
+            // cover the gutter as well, its line numbers don't match the pinned lines
+            let row_area = Rect::new(view.area.x, y, inner.right() - view.area.x, 1);
+            surface.clear_with(row_area, background);
+
+            let offset = Position::new(line, view.offset.col);
+            let highlights = Self::doc_syntax_highlights(doc, offset, 1, theme);
+            Self::render_text_highlights(
+                doc,
+                offset,
+                Rect::new(inner.x, y, inner.width, 1),
+                surface,
+                theme,
+                highlights,
+                config,
+                None,
+                None,
+                &[],
+            );
+            surface.set_style(row_area, style);
+        }
+    }
+
     /// Get syntax highlights for a document in a view represented by the first line
     /// and column (`offset`) and the last line. This is done instead of using a view
     /// directly to enable rendering syntax highlighted docs anywhere (eg. picker preview)
@@ -405,6 +615,13 @@ pub fn doc_selection_highlights(
         spans
     }
 
+    /// Render the text of `doc`, starting at `offset`. Lines wider than the viewport
+    /// are soft wrapped if a `wrap_indicator` is given, which is then drawn at the
+    /// start of every continuation row. `offset.col` must be 0 in that case.
+    /// Whitespace configured to be rendered only in selections uses `selection`.
+    /// `inlay_hints` are drawn as virtual text in front of the chars they belong to,
+    /// they must be sorted and are not supported while soft wrapping.
+    #[allow(clippy::too_many_arguments)]
     pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         doc: &Document,
         offset: Position,
@@ -413,6 +630,9 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         theme: &Theme,
         highlights: H,
         config: &helix_view::editor::Config,
+        wrap_indicator: Option<&str>,
+        selection: Option<&Selection>,
+        inlay_hints: &[InlayHint],
     ) {
         let whitespace = &config.whitespace;
         use helix_view::editor::WhitespaceRenderValue;
@@ -426,26 +646,69 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
         let mut spans = Vec::new();
         let mut visual_x = 0usize;
         let mut line = 0u16;
+        // first row of the current document line, differs from `line` when soft wrapping
+        let mut line_first_row = 0u16;
+        // column a row of text starts at, after the wrap indicator on continuation rows
+        let mut row_start = 0usize;
         let tab_width = doc.tab_width();
-        let tab = if whitespace.render.tab() == WhitespaceRenderValue::All {
-            std::iter::once(characters.tab)
-                .chain(std::iter::repeat(characters.tabpad).take(tab_width - 1))
-                .collect()
-        } else {
-            " ".repeat(tab_width)
-        };
+        let tab: String = std::iter::once(characters.tab)
+            .chain(std::iter::repeat(characters.tabpad).take(tab_width - 1))
+            .collect();
+        let blank_tab = " ".repeat(tab_width);
         let space = characters.space.to_string();
         let nbsp = characters.nbsp.to_string();
-        let newline = if whitespace.render.newline() == WhitespaceRenderValue::All {
-            characters.newline.to_string()
-        } else {
-            " ".to_string()
+        let newline = characters.newline.to_string();
+
+        // start of the trailing whitespace of the last line it was computed for
+        let mut trailing_whitespace: Option<(usize, usize)> = None;
+        let mut is_visible = |render: WhitespaceRenderValue, char_idx: usize| match render {
+            WhitespaceRenderValue::None => false,
+            WhitespaceRenderValue::All => true,
+            WhitespaceRenderValue::Selection => selection.map_or(false, |selection| {
+                selection
+                    .iter()
+                    .any(|range| range.from() <= char_idx && char_idx < range.to())
+            }),
+            WhitespaceRenderValue::Trailing => {
+                let line = text.char_to_line(char_idx);
+                let start = match trailing_whitespace {
+                    Some((cached_line, start)) if cached_line == line => start,
+                    _ => {
+                        let start = trailing_whitespace_start(text, line);
+                        trailing_whitespace = Some((line, start));
+                        start
+                    }
+                };
+                char_idx >= start
+            }
         };
         let indent_guide_char = config.indent_guides.character.to_string();
+        // guides are drawn at every level of the document's indentation, not every tab stop
+        let indent_width = doc.indent_style.indent_width(tab_width);
 
         let text_style = theme.get("ui.text");
         let whitespace_style = theme.get("ui.virtual.whitespace");
 
+        // an indicator that doesn't leave any room for text is not drawn
+        let wrap_indicator = wrap_indicator.map(|indicator| {
+            if indicator.width() < viewport.width as usize {
+                indicator
+            } else {
+                ""
+            }
+        });
+        let wrap_indicator_width = wrap_indicator.map_or(0, |indicator| indicator.width());
+        let wrap_style =
+            text_style.patch(theme.try_get("ui.virtual.wrap").unwrap_or(whitespace_style));
+
+        let hint_style = |scope| text_style.patch(theme.try_get(scope).unwrap_or(whitespace_style));
+        let type_hint_style = hint_style("ui.virtual.inlay-hint.type");
+        let parameter_hint_style = hint_style("ui.virtual.inlay-hint.parameter");
+        let other_hint_style = hint_style("ui.virtual.inlay-hint");
+        // hints in front of the first visible line are skipped
+        let first_char = text.line_to_char(offset.row.min(text.len_lines().saturating_sub(1)));
+        let mut next_hint = inlay_hints.partition_point(|hint| hint.char_idx < first_char);
+
         let mut is_in_indent_area = true;
         let mut last_line_indent_level = 0;
 
@@ -461,19 +724,20 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
                 return;
             }
 
-            let starting_indent =
-                (offset.col / tab_width) + config.indent_guides.skip_levels as usize;
+            // Round up, the first visible guide might be a bit after offset.col
+            let starting_indent = ((offset.col + indent_width - 1) / indent_width)
+                .max(config.indent_guides.skip_levels as usize);
 
             // Don't draw indent guides outside of view
             let end_indent = min(
                 indent_level,
-                // Add tab_width - 1 to round up, since the first visible
-                // indent might be a bit after offset.col
-                offset.col + viewport.width as usize + (tab_width - 1),
-            ) / tab_width;
+                // Add indent_width - 1 to round up, since the last visible
+                // indent might be a bit before the end of the view
+                offset.col + viewport.width as usize + (indent_width - 1),
+            ) / indent_width;
 
             for i in starting_indent..end_indent {
-                let x = (viewport.x as usize + (i * tab_width) - offset.col) as u16;
+                let x = (viewport.x as usize + (i * indent_width) - offset.col) as u16;
                 let y = viewport.y + line;
                 debug_assert!(surface.in_bounds(x, y));
                 surface.set_string(x, y, &indent_guide_char, indent_guide_style);
@@ -499,43 +763,91 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
                         .iter()
                         .fold(text_style, |acc, span| acc.patch(theme.highlight(span.0)));
 
-                    let space = if whitespace.render.space() == WhitespaceRenderValue::All
-                        && !is_trailing_cursor
-                    {
-                        &space
-                    } else {
-                        " "
-                    };
-
-                    let nbsp = if whitespace.render.nbsp() == WhitespaceRenderValue::All
-                        && text.len_chars() < end
-                    {
-                        &nbsp
-                    } else {
-                        " "
-                    };
-
                     use helix_core::graphemes::{grapheme_width, RopeGraphemes};
 
+                    let mut char_idx = start;
                     for grapheme in RopeGraphemes::new(text) {
+                        let grapheme_idx = char_idx;
+                        char_idx += grapheme.len_chars();
+
+                        while let Some(hint) = inlay_hints
+                            .get(next_hint)
+                            .filter(|hint| hint.char_idx <= grapheme_idx)
+                        {
+                            next_hint += 1;
+                            let style = match hint.kind {
+                                Some(InlayHintKind::TYPE) => type_hint_style,
+                                Some(InlayHintKind::PARAMETER) => parameter_hint_style,
+                                _ => other_hint_style,
+                            };
+                            for ch in hint.label.chars() {
+                                let width = ch.width().unwrap_or(0);
+                                if visual_x >= offset.col
+                                    && visual_x + width <= offset.col + viewport.width as usize
+                                {
+                                    let x = (viewport.x as usize + visual_x - offset.col) as u16;
+                                    surface[(x, viewport.y + line)]
+                                        .set_char(ch)
+                                        .set_style(style);
+                                }
+                                visual_x += width;
+                            }
+                        }
+
+                        if let Some(indicator) = wrap_indicator {
+                            let width = if LineEnding::from_rope_slice(&grapheme).is_some() {
+                                1
+                            } else if grapheme == "\t" {
+                                tab_width - (visual_x % tab_width)
+                            } else {
+                                grapheme_width(&Cow::from(grapheme))
+                            };
+
+                            // wrap unless this is the first grapheme on the row, otherwise a
+                            // grapheme wider than the view would never be drawn
+                            if visual_x + width > viewport.width as usize && visual_x > row_start {
+                                line += 1;
+                                if line >= viewport.height {
+                                    break 'outer;
+                                }
+
+                                surface.set_string(
+                                    viewport.x,
+                                    viewport.y + line,
+                                    indicator,
+                                    wrap_style,
+                                );
+                                visual_x = wrap_indicator_width;
+                                row_start = wrap_indicator_width;
+                            }
+                        }
+
                         let out_of_bounds = offset.col > visual_x
                             || visual_x >= viewport.width as usize + offset.col;
 
                         if LineEnding::from_rope_slice(&grapheme).is_some() {
                             if !out_of_bounds {
+                                let newline =
+                                    if is_visible(whitespace.render.newline(), grapheme_idx) {
+                                        newline.as_str()
+                                    } else {
+                                        " "
+                                    };
                                 // we still want to render an empty cell with the style
                                 surface.set_string(
                                     (viewport.x as usize + visual_x - offset.col) as u16,
                                     viewport.y + line,
-                                    &newline,
+                                    newline,
                                     style.patch(whitespace_style),
                                 );
                             }
 
-                            draw_indent_guides(last_line_indent_level, line, surface);
+                            draw_indent_guides(last_line_indent_level, line_first_row, surface);
 
                             visual_x = 0;
+                            row_start = 0;
                             line += 1;
+                            line_first_row = line;
                             is_in_indent_area = true;
 
                             // TODO: with proper iter this shouldn't be necessary
@@ -548,18 +860,27 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
 
                             let (display_grapheme, width) = if grapheme == "\t" {
                                 is_whitespace = true;
+                                let tab = if is_visible(whitespace.render.tab(), grapheme_idx) {
+                                    &tab
+                                } else {
+                                    &blank_tab
+                                };
                                 // make sure we display tab as appropriate amount of spaces
                                 let visual_tab_width = tab_width - (visual_x % tab_width);
                                 let grapheme_tab_width =
-                                    helix_core::str_utils::char_to_byte_idx(&tab, visual_tab_width);
+                                    helix_core::str_utils::char_to_byte_idx(tab, visual_tab_width);
 
                                 (&tab[..grapheme_tab_width], visual_tab_width)
                             } else if grapheme == " " {
                                 is_whitespace = true;
-                                (space, 1)
+                                let visible = !is_trailing_cursor
+                                    && is_visible(whitespace.render.space(), grapheme_idx);
+                                (if visible { space.as_str() } else { " " }, 1)
                             } else if grapheme == "\u{00A0}" {
                                 is_whitespace = true;
-                                (nbsp, 1)
+                                let visible = !is_trailing_cursor
+                                    && is_visible(whitespace.render.nbsp(), grapheme_idx);
+                                (if visible { nbsp.as_str() } else { " " }, 1)
                             } else {
                                 is_whitespace = false;
                                 // Cow will prevent allocations if span contained in a single slice
@@ -601,7 +922,7 @@ pub fn render_text_highlights<H: Iterator<Item = HighlightEvent>>(
                             }
 
                             if is_in_indent_area && !(grapheme == " " || grapheme == "\t") {
-                                draw_indent_guides(visual_x, line, surface);
+                                draw_indent_guides(visual_x, line_first_row, surface);
                                 is_in_indent_area = false;
                                 last_line_indent_level = visual_x;
                             }
@@ -649,8 +970,121 @@ pub fn render_focused_view_elements(
         }
     }
 
-    /// Render bufferline at the top
-    pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface) {
+    /// Schedules a render for the next frame of the running smooth scroll animations.
+    fn request_scroll_frame(&mut self, cx: &mut Context, now: Instant) {
+        let animating = cx
+            .editor
+            .tree
+            .views()
+            .any(|(view, _)| view.is_scroll_animating());
+        if !animating {
+            self.scroll_frame = None;
+            return;
+        }
+        // a frame is already pending
+        if self.scroll_frame.map_or(false, |frame| frame > now) {
+            return;
+        }
+        self.scroll_frame = Some(now + SMOOTH_SCROLL_FRAME);
+        cx.jobs.callback(async move {
+            tokio::time::sleep(SMOOTH_SCROLL_FRAME).await;
+            // rendering after the job completes draws the next frame
+            Ok(Callback::Editor(Box::new(|_editor| {})))
+        });
+    }
+
+    /// The state of the explorer to save in a session.
+    pub fn explorer_session(&self) -> Option<ExplorerSession> {
+        self.explorer
+            .as_ref()
+            .or(self.zen_explorer.as_ref())
+            .map(|explorer| explorer.content.session())
+    }
+
+    /// Opens the explorer of a restored session once it can be built.
+    pub fn restore_explorer(&mut self, session: ExplorerSession) {
+        self.restored_explorer = Some(session);
+    }
+
+    fn open_restored_explorer(&mut self, session: ExplorerSession, cx: &mut Context) {
+        cx.editor.tree.tab_mut().explorer_root = Some(session.root);
+        match Explorer::new(cx) {
+            Ok(mut explorer) => {
+                if !session.sorts.is_empty() {
+                    explorer.set_sorts(session.sorts, cx);
+                }
+                explorer.expand(&session.expanded, cx);
+                explorer.unfocus();
+                self.explorer = Some(overlayed(explorer));
+            }
+            Err(err) => cx.editor.set_error(format!("{}", err)),
+        }
+    }
+
+    /// Runs an `:explorer` command once the explorer can be built.
+    pub fn explorer_command(&mut self, command: ExplorerCommand) {
+        self.explorer_commands.push(command);
+    }
+
+    fn run_explorer_command(&mut self, command: ExplorerCommand, cx: &mut Context) {
+        if matches!(command, ExplorerCommand::Close | ExplorerCommand::Toggle)
+            && self.explorer.is_some()
+        {
+            self.explorer = None;
+            return;
+        }
+        // refreshing a closed explorer does nothing
+        let open = !matches!(
+            command,
+            ExplorerCommand::Close
+                | ExplorerCommand::Refresh
+                | ExplorerCommand::Created(_)
+                | ExplorerCommand::Changed(_)
+        );
+        if open && self.explorer.is_none() {
+            match Explorer::new(cx) {
+                Ok(explorer) => self.explorer = Some(overlayed(explorer)),
+                Err(err) => {
+                    cx.editor.set_error(format!("{}", err));
+                    return;
+                }
+            }
+        }
+        let explorer = match self.explorer.as_mut() {
+            Some(explorer) => &mut explorer.content,
+            None => return,
+        };
+        match command {
+            ExplorerCommand::Reveal(path) => {
+                let path = match path.or_else(|| doc!(cx.editor).path().cloned()) {
+                    Some(path) => path,
+                    None => {
+                        cx.editor.set_error("Buffer has no file");
+                        return;
+                    }
+                };
+                explorer.reveal_file(&path, cx);
+            }
+            ExplorerCommand::Root(dir) => explorer.set_root(dir, cx),
+            ExplorerCommand::Refresh => explorer.refresh(cx),
+            ExplorerCommand::Created(path) => explorer.add_created(&path, cx),
+            ExplorerCommand::Changed(paths) => explorer.files_changed(&paths, cx),
+            ExplorerCommand::Focus => {
+                if let Some(terminal) = self.terminal.as_mut() {
+                    terminal.unfocus();
+                }
+                explorer.focus();
+            }
+            ExplorerCommand::Open | ExplorerCommand::Close | ExplorerCommand::Toggle => (),
+        }
+    }
+
+    /// Render bufferline at the top, returns the area each buffer was rendered at.
+    pub fn render_bufferline(
+        editor: &Editor,
+        viewport: Rect,
+        surface: &mut Surface,
+    ) -> Vec<(Rect, DocumentId)> {
         let scratch = PathBuf::from(SCRATCH_BUFFER_NAME); // default filename to use for scratch buffer
         surface.clear_with(
             viewport,
@@ -670,10 +1104,13 @@ pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface)
             .try_get("ui.bufferline")
             .unwrap_or_else(|| editor.theme.get("ui.statusline.inactive"));
 
+        let show_icons = editor.config().icons;
         let mut x = viewport.x;
         let current_doc = view!(editor).doc;
+        let mut tabs = Vec::new();
+        let buffers = editor.buffers_in_order();
 
-        for doc in editor.documents() {
+        for (index, doc) in buffers.iter().enumerate() {
             let fname = doc
                 .path()
                 .unwrap_or(&scratch)
@@ -688,18 +1125,114 @@ pub fn render_bufferline(editor: &Editor, viewport: Rect, surface: &mut Surface)
                 bufferline_inactive
             };
 
+            let start = x;
+            if show_icons {
+                let icon = icons::file_icon(doc.path().map(|path| path.as_path()));
+                let rem_width = viewport.right().saturating_sub(x);
+                x = surface
+                    .set_stringn(
+                        x,
+                        viewport.y,
+                        format!(" {}", icon.glyph),
+                        rem_width as usize,
+                        style.fg(icon.color),
+                    )
+                    .0;
+            }
+
             let text = format!(" {}{} ", fname, if doc.is_modified() { "[+]" } else { "" });
-            let used_width = viewport.x.saturating_sub(x);
-            let rem_width = surface.area.width.saturating_sub(used_width);
+            let rem_width = viewport.right().saturating_sub(x);
 
             x = surface
                 .set_stringn(x, viewport.y, text, rem_width as usize, style)
                 .0;
+            tabs.push((Rect::new(start, viewport.y, x - start, 1), doc.id()));
+
+            // the pinned buffers are set apart from the others
+            let last_pinned = doc.is_pinned()
+                && buffers
+                    .get(index + 1)
+                    .map_or(false, |next| !next.is_pinned());
+            if last_pinned {
+                let rem_width = viewport.right().saturating_sub(x);
+                x = surface
+                    .set_stringn(x, viewport.y, "|", rem_width as usize, bufferline_inactive)
+                    .0;
+            }
 
-            if x >= surface.area.right() {
+            if x >= viewport.right() {
                 break;
             }
         }
+
+        tabs
+    }
+
+    /// Render the tab pages at the top, returns the area each tab page was rendered at.
+    pub fn render_tabline(
+        editor: &Editor,
+        viewport: Rect,
+        surface: &mut Surface,
+    ) -> Vec<(Rect, usize)> {
+        let theme = &editor.theme;
+        let get = |scopes: &[&str], fallback: &str| {
+            scopes
+                .iter()
+                .find_map(|scope| theme.try_get(scope))
+                .unwrap_or_else(|| theme.get(fallback))
+        };
+        surface.clear_with(
+            viewport,
+            get(
+                &["ui.tabline.background", "ui.bufferline.background"],
+                "ui.statusline",
+            ),
+        );
+        let tabline_active = get(
+            &["ui.tabline.active", "ui.bufferline.active"],
+            "ui.statusline.active",
+        );
+        let tabline_inactive = get(&["ui.tabline", "ui.bufferline"], "ui.statusline.inactive");
+
+        let mut x = viewport.x;
+        let mut tabs = Vec::new();
+        for index in 0..editor.tree.tabs().len() {
+            let focus = editor.tree.tab_focus(index);
+            let doc = editor
+                .tree
+                .try_get(focus)
+                .and_then(|view| editor.documents.get(&view.doc));
+            let fname = doc
+                .and_then(|doc| doc.path())
+                .and_then(|path| path.file_name())
+                .map(|name| name.to_string_lossy().into_owned())
+                .unwrap_or_else(|| SCRATCH_BUFFER_NAME.to_string());
+            let modified = doc.map_or(false, |doc| doc.is_modified());
+
+            let style = if index == editor.tree.active_tab() {
+                tabline_active
+            } else {
+                tabline_inactive
+            };
+            let text = format!(
+                " {} {}{} ",
+                index + 1,
+                fname,
+                if modified { "[+]" } else { "" }
+            );
+            let rem_width = viewport.right().saturating_sub(x);
+            let start = x;
+            x = surface
+                .set_stringn(x, viewport.y, text, rem_width as usize, style)
+                .0;
+            tabs.push((Rect::new(start, viewport.y, x - start, 1), index));
+
+            if x >= viewport.right() {
+                break;
+            }
+        }
+
+        tabs
     }
 
     pub fn render_gutter(
@@ -712,7 +1245,6 @@ pub fn render_gutter(
         is_focused: bool,
     ) {
         let text = doc.text().slice(..);
-        let last_line = view.last_line(doc);
 
         // it's used inside an iterator so the collect isn't needless:
         // https://github.com/rust-lang/rust-clippy/issues/6164
@@ -731,14 +1263,19 @@ pub fn render_gutter(
         // avoid lots of small allocations by reusing a text buffer for each line
         let mut text = String::with_capacity(8);
 
+        // it's iterated once per gutter, so compute the row layout only once
+        let visible_lines: Vec<_> = view.visible_lines(doc).collect();
+        let marker = &editor.config().soft_wrap.continuation_marker;
+        let marker_style = theme.get("ui.linenr");
+
         for gutter_type in view.gutters() {
             let mut gutter = gutter_type.style(editor, doc, view, theme, is_focused);
             let width = gutter_type.width(view, doc);
             text.reserve(width); // ensure there's enough space for the gutter
-            for (i, line) in (view.offset.row..(last_line + 1)).enumerate() {
+            for &(line, row, rows) in &visible_lines {
                 let selected = cursors.contains(&line);
                 let x = viewport.x + offset;
-                let y = viewport.y + i as u16;
+                let y = viewport.y + row as u16;
 
                 let gutter_style = if selected {
                     gutter_selected_style
@@ -746,6 +1283,26 @@ pub fn render_gutter(
                     gutter_style
                 };
 
+                // soft wrapped continuation rows get a blank gutter
+                if rows > 1 {
+                    surface.set_style(
+                        Rect {
+                            x,
+                            y: y + 1,
+                            width: width as u16,
+                            height: rows as u16 - 1,
+                        },
+                        gutter_style,
+                    );
+                    if gutter_type == GutterType::LineNumbers && !marker.is_empty() {
+                        let marker = format!("{:>1$}", marker, width);
+                        let style = gutter_style.patch(marker_style);
+                        for y in y + 1..y + rows as u16 {
+                            surface.set_stringn(x, y, &marker, width, style);
+                        }
+                    }
+                }
+
                 if let Some(style) = gutter(line, selected, &mut text) {
                     surface.set_stringn(x, y, &text, width, gutter_style.patch(style));
                 } else {
@@ -823,7 +1380,6 @@ pub fn render_diagnostics(
     /// Apply the highlighting on the lines where a cursor is active
     pub fn highlight_cursorline(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme) {
         let text = doc.text().slice(..);
-        let last_line = view.last_line(doc);
 
         let primary_line = doc.selection(view.id).primary().cursor_line(text);
 
@@ -842,12 +1398,13 @@ pub fn highlight_cursorline(doc: &Document, view: &View, surface: &mut Surface,
         let primary_style = theme.get("ui.cursorline.primary");
         let secondary_style = theme.get("ui.cursorline.secondary");
 
-        for line in view.offset.row..(last_line + 1) {
+        let text_rows = view.text_rows_area();
+        for (line, row, rows) in view.visible_lines(doc) {
             let area = Rect::new(
-                view.area.x,
-                view.area.y + (line - view.offset.row) as u16,
-                view.area.width,
-                1,
+                text_rows.x,
+                text_rows.y + row as u16,
+                text_rows.width,
+                rows as u16,
             );
             if primary_line == line {
                 surface.set_style(area, primary_style);
@@ -891,9 +1448,9 @@ pub fn highlight_cursorcolumn(
             if col >= offset && inner_area.width > (col - offset) as u16 {
                 let area = Rect::new(
                     inner_area.x + (col - offset) as u16,
-                    view.area.y,
+                    inner_area.y,
                     1,
-                    view.area.height,
+                    inner_area.height,
                 );
                 if is_primary {
                     surface.set_style(area, primary_style)
@@ -1103,6 +1660,17 @@ pub fn handle_idle_timeout(&mut self, cx: &mut commands::Context) -> EventResult
             };
         }
 
+        if view!(cx.editor)
+            .gutters()
+            .contains(&GutterType::CodeActions)
+        {
+            commands::refresh_code_action_hint(cx);
+        }
+        commands::refresh_inlay_hints(cx);
+        if cx.editor.config().breadcrumbs.enable {
+            commands::refresh_document_symbols(cx);
+        }
+
         if cx.editor.mode != Mode::Insert || !cx.editor.config().auto_completion {
             return EventResult::Ignored(None);
         }
@@ -1111,9 +1679,117 @@ pub fn handle_idle_timeout(&mut self, cx: &mut commands::Context) -> EventResult
 
         EventResult::Consumed(None)
     }
+
+    /// Shows the diagnostics under the resting mouse pointer, or the hover information of
+    /// the language server while holding ctrl.
+    fn handle_mouse_hover(
+        &mut self,
+        event: &MouseEvent,
+        cx: &mut commands::Context,
+    ) -> EventResult {
+        let editor = &cx.editor;
+        if !editor.config().mouse.text {
+            return EventResult::Ignored(None);
+        }
+        let hovered = editor.tree.views().find_map(|(view, _focus)| {
+            view.pos_at_screen_coords(&editor.documents[&view.doc], event.row, event.column)
+                .map(|pos| (pos, view.doc))
+        });
+        let (pos, doc_id) = match hovered {
+            Some(hovered) => hovered,
+            None => return EventResult::Ignored(None),
+        };
+        let doc = doc!(editor, &doc_id);
+        let anchor = Some(Position::new(event.row as usize, event.column as usize));
+
+        let messages: Vec<_> = doc
+            .diagnostics()
+            .iter()
+            .filter(|diagnostic| {
+                // empty ranges cover the char they start at
+                let range = &diagnostic.range;
+                range.start <= pos && pos < range.end.max(range.start + 1)
+            })
+            .map(|diagnostic| diagnostic.message.as_str())
+            .collect();
+        if !messages.is_empty() {
+            let contents = Markdown::new(messages.join("\n\n"), editor.syn_loader.clone());
+            let popup = Popup::new(MOUSE_HOVER_ID, contents)
+                .auto_close(true)
+                .position(anchor);
+            return EventResult::Consumed(Some(Box::new(
+                move |compositor: &mut crate::compositor::Compositor, _: &mut Context| {
+                    compositor.replace_or_push(MOUSE_HOVER_ID, popup);
+                },
+            )));
+        }
+
+        if event.modifiers.contains(KeyModifiers::CONTROL) && doc.language_server().is_some() {
+            commands::hover_at(cx, doc_id, pos, MOUSE_HOVER_ID, anchor);
+            return EventResult::Consumed(None);
+        }
+        EventResult::Ignored(None)
+    }
+}
+
+/// Scrolls the focused view to center the line at `row` of its scrollbar.
+fn scroll_to_scrollbar_row(cx: &mut commands::Context, row: u16) {
+    let (view, doc) = current_ref!(cx.editor);
+    let line = view.scrollbar_line_at_row(doc, row);
+    let visible = view.inner_height();
+    let max_offset = doc.text().len_lines().saturating_sub(visible);
+    let target = line.saturating_sub(visible / 2).min(max_offset);
+    let offset = view.offset.row;
+    if target > offset {
+        commands::scroll(cx, target - offset, Direction::Forward);
+    } else if target < offset {
+        commands::scroll(cx, offset - target, Direction::Backward);
+    }
 }
 
 impl EditorView {
+    /// Runs the mouse binding of the explorer for a press on one of its rows, and leaves
+    /// out the release and drags of the button of the last binding run.
+    fn handle_explorer_mouse_binding(
+        &mut self,
+        event: &MouseEvent,
+        cx: &mut crate::compositor::Context,
+    ) -> Option<EventResult> {
+        let button = match event.kind {
+            MouseEventKind::Down(button) => button,
+            MouseEventKind::Drag(button) if self.mouse_binding_button == Some(button) => {
+                return Some(EventResult::Consumed(None));
+            }
+            MouseEventKind::Up(button) if self.mouse_binding_button == Some(button) => {
+                self.mouse_binding_button = None;
+                return Some(EventResult::Consumed(None));
+            }
+            _ => return None,
+        };
+        self.mouse_binding_button = None;
+        let binding = MouseBinding {
+            button,
+            modifiers: event.modifiers,
+        };
+        let mousemap = self.mousemap.load();
+        let action = mousemap.explorer.get(&binding)?;
+        let explorer = self.explorer.as_mut()?;
+        if !explorer.content.select_at(event, cx.editor) {
+            return None;
+        }
+        self.mouse_binding_button = Some(button);
+        let mut cx = commands::Context {
+            editor: cx.editor,
+            count: None,
+            register: None,
+            callback: None,
+            on_next_key_callback: None,
+            jobs: cx.jobs,
+        };
+        action.execute(None, &mut cx);
+        Some(EventResult::Consumed(cx.callback))
+    }
+
     fn handle_mouse_event(
         &mut self,
         event: &MouseEvent,
@@ -1135,6 +1811,48 @@ fn handle_mouse_event(
             })
         };
 
+        let minimap_line_and_view = |editor: &Editor, row, column| {
+            editor.tree.views().find_map(|(view, _focus)| {
+                view.minimap_line_at_screen_coords(&editor.documents[&view.doc], row, column)
+                    .map(|line| (line, view.id))
+            })
+        };
+
+        let scrollbar_view = |editor: &Editor, row, column| {
+            editor
+                .tree
+                .views()
+                .find(|(view, _focus)| view.is_on_scrollbar(row, column))
+                .map(|(view, _focus)| view.id)
+        };
+
+        let bufferline_tab = self
+            .bufferline_tabs
+            .iter()
+            .find(|(area, _)| area.top() == row && (area.left()..area.right()).contains(&column))
+            .map(|(_, doc_id)| *doc_id);
+
+        let tabline_tab = self
+            .tabline_tabs
+            .iter()
+            .find(|(area, _)| area.top() == row && (area.left()..area.right()).contains(&column))
+            .map(|(_, index)| *index);
+
+        let enabled = if bufferline_tab.is_some() || tabline_tab.is_some() {
+            config.mouse.bufferline
+        } else {
+            config.mouse.text
+        };
+        if !enabled {
+            return EventResult::Ignored(None);
+        }
+
+        let statusline_element = self
+            .statusline_elements
+            .iter()
+            .find(|(area, _, _)| area.top() == row && (area.left()..area.right()).contains(&column))
+            .map(|(_, view_id, element)| (*view_id, *element));
+
         let gutter_coords_and_view = |editor: &Editor, row, column| {
             editor.tree.views().find_map(|(view, _focus)| {
                 view.gutter_coords_at_screen_coords(row, column)
@@ -1142,10 +1860,59 @@ fn handle_mouse_event(
             })
         };
 
+        if let MouseEventKind::Down(button) = kind {
+            let binding = MouseBinding { button, modifiers };
+            let mousemap = self.mousemap.load();
+            if let Some(doc_id) = bufferline_tab {
+                if let Some(action) = mousemap.bufferline.get(&binding) {
+                    cxt.editor.switch(doc_id, Action::Replace);
+                    self.mouse_binding_button = Some(button);
+                    action.execute(None, cxt);
+                    return EventResult::Consumed(cxt.callback.take());
+                }
+            } else if let Some((pos, view_id)) = pos_and_view(cxt.editor, row, column) {
+                if let Some(action) = mousemap.editor.get(&binding) {
+                    cxt.editor.focus(view_id);
+                    self.mouse_binding_button = Some(button);
+                    action.execute(Some(pos), cxt);
+                    return EventResult::Consumed(cxt.callback.take());
+                }
+            }
+        }
+
         match kind {
             MouseEventKind::Down(MouseButton::Left) => {
+                if let Some((view_id, element)) = statusline_element {
+                    cxt.editor.focus(view_id);
+                    if let Some(command) = statusline::click_command(element) {
+                        command.execute(cxt);
+                    }
+                    return EventResult::Consumed(cxt.callback.take());
+                }
+
                 let editor = &mut cxt.editor;
 
+                if let Some(index) = tabline_tab {
+                    editor.switch_tab(index);
+                    return EventResult::Consumed(None);
+                }
+
+                if let Some(doc_id) = bufferline_tab {
+                    editor.switch(doc_id, Action::Replace);
+                    return EventResult::Consumed(None);
+                }
+
+                if let Some(view_id) = breadcrumbs::view_at_screen_coords(editor, row, column) {
+                    let view = view!(editor, view_id);
+                    let doc = &editor.documents[&view.doc];
+                    let separator = &config.breadcrumbs.separator;
+                    if let Some(crumb) = breadcrumbs::crumb_at_column(doc, view, separator, column)
+                    {
+                        breadcrumbs::jump_to(editor, view_id, &crumb);
+                    }
+                    return EventResult::Consumed(None);
+                }
+
                 if let Some((pos, view_id)) = pos_and_view(editor, row, column) {
                     let doc = doc_mut!(editor, &view!(editor, view_id).doc);
 
@@ -1162,6 +1929,23 @@ fn handle_mouse_event(
                     return EventResult::Consumed(None);
                 }
 
+                if let Some(view_id) = scrollbar_view(editor, row, column) {
+                    editor.focus(view_id);
+                    self.scrollbar_drag = Some(view_id);
+                    scroll_to_scrollbar_row(cxt, row);
+                    return EventResult::Consumed(None);
+                }
+
+                if let Some((line, view_id)) = minimap_line_and_view(editor, row, column) {
+                    editor.focus(view_id);
+
+                    let (view, doc) = current!(editor);
+                    let pos = doc.text().line_to_char(line);
+                    doc.set_selection(view.id, Selection::point(pos));
+                    align_view(doc, view, Align::Center);
+                    return EventResult::Consumed(None);
+                }
+
                 if let Some((coords, view_id)) = gutter_coords_and_view(editor, row, column) {
                     editor.focus(view_id);
 
@@ -1172,8 +1956,7 @@ fn handle_mouse_event(
                         None => return EventResult::Ignored(None),
                     };
 
-                    let line = coords.row + view.offset.row;
-                    if line < doc.text().len_lines() {
+                    if let Some(line) = view.line_at_row(doc, coords.row) {
                         commands::dap_toggle_breakpoint_impl(cxt, path, line);
                         return EventResult::Consumed(None);
                     }
@@ -1183,6 +1966,11 @@ fn handle_mouse_event(
             }
 
             MouseEventKind::Drag(MouseButton::Left) => {
+                if self.scrollbar_drag == Some(cxt.editor.tree.focus) {
+                    scroll_to_scrollbar_row(cxt, row);
+                    return EventResult::Consumed(None);
+                }
+
                 let (view, doc) = current!(cxt.editor);
 
                 let pos = match view.pos_at_screen_coords(doc, row, column) {
@@ -1223,6 +2011,10 @@ fn handle_mouse_event(
             }
 
             MouseEventKind::Up(MouseButton::Left) => {
+                if self.scrollbar_drag.take().is_some() {
+                    return EventResult::Consumed(None);
+                }
+
                 if !config.middle_click_paste {
                     return EventResult::Ignored(None);
                 }
@@ -1245,12 +2037,18 @@ fn handle_mouse_event(
             }
 
             MouseEventKind::Up(MouseButton::Right) => {
+                if let Some(doc_id) = bufferline_tab {
+                    cxt.editor.switch(doc_id, Action::Replace);
+                    commands::MappableCommand::buffer_menu.execute(cxt);
+                    return EventResult::Consumed(None);
+                }
+
                 if let Some((coords, view_id)) = gutter_coords_and_view(cxt.editor, row, column) {
                     cxt.editor.focus(view_id);
 
                     let (view, doc) = current!(cxt.editor);
-                    let line = coords.row + view.offset.row;
-                    if let Ok(pos) = doc.text().try_line_to_char(line) {
+                    let line = view.line_at_row(doc, coords.row);
+                    if let Some(pos) = line.map(|line| doc.text().line_to_char(line)) {
                         doc.set_selection(view_id, Selection::point(pos));
                         if modifiers == KeyModifiers::ALT {
                             commands::MappableCommand::dap_edit_log.execute(cxt);
@@ -1267,6 +2065,19 @@ fn handle_mouse_event(
 
             MouseEventKind::Up(MouseButton::Middle) => {
                 let editor = &mut cxt.editor;
+
+                if let Some(index) = tabline_tab {
+                    if editor.tree.tabs().len() > 1 {
+                        editor.close_tab(index);
+                    }
+                    return EventResult::Consumed(None);
+                }
+
+                if let Some(doc_id) = bufferline_tab {
+                    commands::close_buffer_impl(editor, doc_id);
+                    return EventResult::Consumed(None);
+                }
+
                 if !config.middle_click_paste {
                     return EventResult::Ignored(None);
                 }
@@ -1301,11 +2112,21 @@ fn handle_event(
         event: &Event,
         context: &mut crate::compositor::Context,
     ) -> EventResult {
+        if let Event::Mouse(event) = event {
+            if let Some(result) = self.handle_explorer_mouse_binding(event, context) {
+                return result;
+            }
+        }
         if let Some(explore) = self.explorer.as_mut() {
             if let EventResult::Consumed(callback) = explore.handle_event(event, context) {
                 return EventResult::Consumed(callback);
             }
         }
+        if let Some(terminal) = self.terminal.as_mut() {
+            if let EventResult::Consumed(callback) = terminal.handle_event(event, context) {
+                return EventResult::Consumed(callback);
+            }
+        }
         let mut cx = commands::Context {
             editor: context.editor,
             count: None,
@@ -1435,12 +2256,20 @@ fn handle_event(
                 EventResult::Consumed(callback)
             }
 
-            Event::Mouse(event) => self.handle_mouse_event(event, &mut cx),
+            Event::Mouse(event) => {
+                let result = self.handle_mouse_event(event, &mut cx);
+                // the menus opened by a click wait for a key like the ones opened by a key
+                if let Some(on_next_key) = cx.on_next_key_callback.take() {
+                    self.on_next_key = Some(on_next_key);
+                }
+                result
+            }
             Event::IdleTimeout => self.handle_idle_timeout(&mut cx),
+            Event::MouseHover(event) => self.handle_mouse_hover(event, &mut cx),
             Event::FocusGained => EventResult::Ignored(None),
             Event::FocusLost => {
-                if context.editor.config().auto_save {
-                    if let Err(e) = commands::typed::write_all_impl(context, false, false) {
+                if context.editor.config().auto_save.focus_lost {
+                    if let Err(e) = commands::typed::auto_save_impl(context, None) {
                         context.editor.set_error(format!("{}", e));
                     }
                 }
@@ -1454,13 +2283,22 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         surface.set_style(area, cx.editor.theme.get("ui.background"));
         let config = cx.editor.config();
 
+        if let Some(session) = self.restored_explorer.take() {
+            self.open_restored_explorer(session, cx);
+        }
+        for command in std::mem::take(&mut self.explorer_commands) {
+            self.run_explorer_command(command, cx);
+        }
+
         // check if bufferline should be rendered
         use helix_view::editor::BufferLine;
         let use_bufferline = match config.bufferline {
+            _ if cx.editor.zen_mode => false,
             BufferLine::Always => true,
             BufferLine::Multiple if cx.editor.documents.len() > 1 => true,
             _ => false,
         };
+        let use_tabline = cx.editor.tree.tabs().len() > 1 && !cx.editor.zen_mode;
 
         // -1 for commandline and -1 for bufferline
         let mut editor_area = area.clip_bottom(1);
@@ -1468,6 +2306,20 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         if self.explorer.is_some() && (config.explorer.is_embed()) {
             editor_area = editor_area.clip_left(config.explorer.column_width as u16 + 2);
         }
+
+        if matches!(&self.terminal, Some(terminal) if terminal.has_exited()) {
+            self.terminal = None;
+        }
+        // the terminal takes the bottom third of the space left for the views
+        let terminal_height = if self.terminal.is_some() {
+            editor_area.height / 3
+        } else {
+            0
+        };
+        let terminal_area = editor_area
+            .clip_top(editor_area.height - terminal_height)
+            .with_height(terminal_height);
+        editor_area = editor_area.clip_bottom(terminal_height);
         cx.editor.resize(editor_area); // -1 from bottom for commandline
 
         if let Some(explore) = self.explorer.as_mut() {
@@ -1476,6 +2328,9 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             }
         }
 
+        if use_tabline {
+            editor_area = editor_area.clip_top(1);
+        }
         if use_bufferline {
             editor_area = editor_area.clip_top(1);
         }
@@ -1483,13 +2338,53 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
         // if the terminal size suddenly changed, we need to trigger a resize
         cx.editor.resize(editor_area);
 
+        let mut top_area = area;
+        if use_tabline {
+            self.tabline_tabs = Self::render_tabline(cx.editor, top_area.with_height(1), surface);
+            top_area = top_area.clip_top(1);
+        } else {
+            self.tabline_tabs.clear();
+        }
         if use_bufferline {
-            Self::render_bufferline(cx.editor, area.with_height(1), surface);
+            self.bufferline_tabs =
+                Self::render_bufferline(cx.editor, top_area.with_height(1), surface);
+        } else {
+            self.bufferline_tabs.clear();
         }
 
+        // draw smoothly scrolling views at their intermediate position for this frame,
+        // their actual offsets are restored right after rendering
+        let now = Instant::now();
+        let scroll_duration = config.smooth_scroll.animation_duration();
+        let mut scroll_targets = Vec::new();
+        for (view, _) in cx.editor.tree.views_mut() {
+            let row = view.animated_scroll_row(scroll_duration, now);
+            if row != view.offset.row {
+                scroll_targets.push((view.id, view.offset.row));
+                view.offset.row = row;
+            }
+        }
+
+        self.scrollbar_marks.update(cx.editor);
+        let mut statusline_elements = Vec::new();
         for (view, is_focused) in cx.editor.tree.views() {
             let doc = cx.editor.document(view.doc).unwrap();
-            self.render_view(cx.editor, doc, view, area, surface, is_focused);
+            let elements = self.render_view(cx.editor, doc, view, area, surface, is_focused);
+            statusline_elements.extend(
+                elements
+                    .into_iter()
+                    .map(|(area, element)| (area, view.id, element)),
+            );
+        }
+        self.statusline_elements = statusline_elements;
+
+        for (view_id, row) in scroll_targets {
+            cx.editor.tree.get_mut(view_id).offset.row = row;
+        }
+        self.request_scroll_frame(cx, now);
+
+        if let Some(terminal) = self.terminal.as_mut() {
+            terminal.render(terminal_area, surface, cx);
         }
 
         if config.auto_info {
@@ -1585,6 +2480,15 @@ fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind)
                 }
             }
         }
+        if let Some(terminal) = &self.terminal {
+            if terminal.is_focused() {
+                return terminal.cursor(_area, editor);
+            }
+        }
+        // the cursor is placed with the target offset of the view, not where it is drawn
+        if self.scroll_frame.is_some() {
+            return (None, CursorKind::Hidden);
+        }
         match editor.cursor() {
             // All block cursors are drawn manually
             (pos, CursorKind::Block) => (pos, CursorKind::Hidden),
@@ -1602,3 +2506,17 @@ fn canonicalize_key(key: &mut KeyEvent) {
         key.modifiers.remove(KeyModifiers::SHIFT)
     }
 }
+
+/// Returns the char index at which the trailing whitespace of `line` starts,
+/// or the end of the line (before the line ending) if there is none.
+fn trailing_whitespace_start(text: RopeSlice, line: usize) -> usize {
+    let line_start = text.line_to_char(line);
+    let line_end = line_end_char_index(&text, line);
+    let trailing = text
+        .slice(line_start..line_end)
+        .chars_at(line_end - line_start)
+        .reversed()
+        .take_while(|&ch| char_is_whitespace(ch))
+        .count();
+    line_end - trailing
+}
@@ -0,0 +1,78 @@
+use helix_core::{
+    diagnostic::Severity, line_ending::line_end_char_index, unicode::width::UnicodeWidthStr,
+    visual_coords_at_pos,
+};
+use helix_view::{
+    editor::Config,
+    graphics::{Modifier, Style},
+    Document, Theme, View,
+};
+use tui::buffer::Buffer as Surface;
+
+use super::color_swatch;
+
+/// Renders the first diagnostic of every visible line, dimmed, after the end of that line.
+pub fn render(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme, config: &Config) {
+    let min_severity = config.end_of_line_diagnostics.min_severity;
+    let text = doc.text().slice(..);
+    let inner = view.inner_area(doc);
+    let first_line = view.offset.row;
+    let last_line = view.last_line(doc);
+
+    // diagnostics are sorted by their position
+    let diagnostics = doc
+        .diagnostics()
+        .iter()
+        .skip_while(|diagnostic| diagnostic.line < first_line)
+        .take_while(|diagnostic| diagnostic.line <= last_line)
+        .filter(|diagnostic| diagnostic.severity.unwrap_or(Severity::Warning) >= min_severity);
+
+    let mut last_rendered_line = None;
+    for diagnostic in diagnostics {
+        // only show the first diagnostic of each line
+        if last_rendered_line == Some(diagnostic.line) {
+            continue;
+        }
+        last_rendered_line = Some(diagnostic.line);
+
+        let line_end = line_end_char_index(&text, diagnostic.line);
+        let pos = match view.screen_coords_at_pos(doc, text, line_end) {
+            Some(pos) => pos,
+            None => continue,
+        };
+        // the end of the line is scrolled out of view
+        if visual_coords_at_pos(text, line_end, doc.tab_width()).col < view.offset.col {
+            continue;
+        }
+
+        // leave a space after the line and the color swatches drawn behind it
+        let mut x = inner.x as usize + pos.col + 1;
+        if config.color_swatches {
+            x += color_swatch::width(&text.line(diagnostic.line).to_string());
+        }
+        let y = inner.y + pos.row as u16;
+        let width = (inner.right() as usize).saturating_sub(x);
+        let message = diagnostic.message.lines().next().unwrap_or_default();
+        if width < 2 || message.is_empty() {
+            continue;
+        }
+
+        let style = severity_style(diagnostic.severity, theme).add_modifier(Modifier::DIM);
+        if message.width() <= width {
+            surface.set_stringn(x as u16, y, message, width, style);
+        } else {
+            let (end, _) = surface.set_stringn(x as u16, y, message, width - 1, style);
+            surface.set_string(end, y, "…", style);
+        }
+    }
+}
+
+fn severity_style(severity: Option<Severity>, theme: &Theme) -> Style {
+    let scope = match severity {
+        Some(Severity::Error) => "error",
+        Some(Severity::Warning) | None => "warning",
+        Some(Severity::Info) => "info",
+        Some(Severity::Hint) => "hint",
+    };
+    theme.get(scope)
+}
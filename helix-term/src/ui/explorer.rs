@@ -1,19 +1,45 @@
-use super::{Prompt, Tree, TreeItem, TreeOp};
+use super::{icons, image::human_size, ImagePreview, Prompt, Tree, TreeItem, TreeOp};
 use crate::{
+    commands,
     compositor::{Component, Compositor, Context, EventResult},
     ctrl, key, shift, ui,
 };
 use anyhow::{bail, ensure, Result};
-use helix_core::Position;
+use content_inspector::ContentType;
+use helix_core::{Position, Selection};
+use helix_vcs::{DiffProviderRegistry, FileStatus, Status};
 use helix_view::{
-    editor::Action,
-    graphics::{CursorKind, Modifier, Rect},
-    input::{Event, KeyEvent},
-    Editor,
+    align_view,
+    clipboard::ClipboardType,
+    editor::{Action, ConfigEvent, ExplorerConfig, ExplorerOpen, ExplorerSort, SortComparator},
+    graphics::{CursorKind, Modifier, Rect, Style},
+    hooks::HookEvent,
+    image::ImageFormat,
+    info::Info,
+    input::{Event, KeyEvent, MouseButton, MouseEvent, MouseEventKind},
+    keyboard::KeyModifiers,
+    recent,
+    session::ExplorerSession,
+    Align, Editor, Theme,
 };
+use ignore::{
+    gitignore::{Gitignore, GitignoreBuilder},
+    DirEntry, Match, WalkBuilder, WalkState,
+};
+use md5::Md5;
+use sha2::{Digest, Sha256};
 use std::borrow::Cow;
 use std::cmp::Ordering;
+use std::collections::{BTreeMap, BTreeSet, HashMap, HashSet};
+use std::fs::File;
+use std::io::{Read, Write};
 use std::path::{Path, PathBuf};
+use std::sync::{
+    mpsc::{self, Receiver, TryRecvError},
+    Arc, Mutex,
+};
+use std::time::{Duration, Instant, SystemTime};
+use tokio::sync::Notify;
 use tui::{
     buffer::Buffer as Surface,
     text::{Span, Spans},
@@ -26,7 +52,7 @@ macro_rules! get_theme {
     };
 }
 
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
 enum FileType {
     File,
     Dir,
@@ -34,32 +60,131 @@ enum FileType {
     Placeholder,
     Parent,
     Root,
+    /// The header of the files with unresolved merge conflicts.
+    Conflicts,
+    /// A file with unresolved merge conflicts, listed under the header.
+    Conflicted,
+    /// The header of the files of a workspace search pinned to the top.
+    Pinned,
+    /// A file matched by the pinned search, listed under the header.
+    PinnedFile,
+    /// The header of the files of the working directory opened the most and the most
+    /// lately.
+    Recent,
+    /// A file opened often or lately, listed under the header.
+    RecentFile,
 }
 
 #[derive(Debug, Clone)]
 struct FileInfo {
     file_type: FileType,
     path: PathBuf,
+    /// The order of the item among the entries of its directory.
+    sort: ExplorerSort,
+    /// How the name of the item is compared to the ones of the entries around it.
+    comparator: SortComparator,
+    /// When the item was last modified, as of its listing.
+    modified: Option<SystemTime>,
+    /// Whether the item is a symbolic link to nothing, as of its listing.
+    broken_link: bool,
+    /// The kind of the item when it is neither a regular file nor a directory.
+    special: Option<Special>,
+    /// The position of a recent file in its section, the highest score first.
+    rank: usize,
 }
 
 impl FileInfo {
     fn new(path: PathBuf, file_type: FileType) -> Self {
-        Self { path, file_type }
-    }
-
-    fn root(path: PathBuf) -> Self {
         Self {
-            file_type: FileType::Root,
             path,
+            file_type,
+            sort: ExplorerSort::default(),
+            comparator: SortComparator::default(),
+            modified: None,
+            broken_link: false,
+            special: None,
+            rank: 0,
         }
     }
 
+    fn root(path: PathBuf) -> Self {
+        Self::new(path, FileType::Root)
+    }
+
     fn parent(path: &Path) -> Self {
         let p = path.parent().unwrap_or_else(|| Path::new(""));
-        Self {
-            file_type: FileType::Parent,
-            path: p.to_path_buf(),
+        Self::new(p.to_path_buf(), FileType::Parent)
+    }
+
+    /// Whether the item is a file or directory which can be marked.
+    fn is_markable(&self) -> bool {
+        matches!(
+            self.file_type,
+            FileType::File
+                | FileType::Exe
+                | FileType::Dir
+                | FileType::Conflicted
+                | FileType::PinnedFile
+                | FileType::RecentFile
+        )
+    }
+
+    /// The entries of the directory in the order `sort`, their names compared with
+    /// `comparator`, listed as `config` says. The ones matching the `ignore` globs are
+    /// left out, and so are the ones ignored by the ignore files with `git-ignore`, the
+    /// files out of the filter `preset` and the entries `only` leaves out.
+    fn children(
+        &self,
+        config: &ExplorerConfig,
+        sort: ExplorerSort,
+        comparator: SortComparator,
+        preset: Option<&str>,
+        only: &OnlyFilter,
+        ignores: &mut IgnoreCache,
+    ) -> Result<Vec<Self>> {
+        match self.file_type {
+            FileType::Root | FileType::Dir => {}
+            _ => return Ok(vec![]),
+        };
+        let globs = listing_globs(config, preset);
+        let mut matchers: Vec<_> = ignores.globs(&globs).into_iter().collect();
+        matchers.extend(ignores.matchers(&self.path, &IgnoreRules::new(config)));
+        let mut ret: Vec<_> = std::fs::read_dir(&self.path)?
+            .filter_map(|entry| entry.ok())
+            .filter(|entry| {
+                config.show_hidden || !entry.file_name().to_string_lossy().starts_with('.')
+            })
+            .filter_map(|entry| {
+                entry.metadata().ok().map(|meta| {
+                    let is_exe = is_executable(&meta);
+                    let file_type = match (meta.is_dir(), is_exe) {
+                        (true, _) => FileType::Dir,
+                        (_, false) => FileType::File,
+                        (_, true) => FileType::Exe,
+                    };
+                    let path = self.path.join(entry.file_name());
+                    Self {
+                        file_type,
+                        broken_link: is_broken_link(&path, &meta),
+                        special: Special::of(&meta),
+                        path,
+                        sort,
+                        comparator,
+                        modified: meta.modified().ok(),
+                        rank: 0,
+                    }
+                })
+            })
+            .filter(|item| !is_ignored(&matchers, &item.path, item.file_type == FileType::Dir))
+            .filter(|item| {
+                let is_dir = item.file_type == FileType::Dir;
+                only.keeps(&item.path, is_dir, item.broken_link)
+            })
+            .collect();
+        if ret.is_empty() {
+            ret.push(Self::new(self.path.clone(), FileType::Placeholder))
         }
+        Ok(ret)
     }
 
     fn get_text(&self) -> Cow<'static, str> {
@@ -67,6 +192,15 @@ fn get_text(&self) -> Cow<'static, str> {
             FileType::Parent => "..".into(),
             FileType::Placeholder => "---".into(),
             FileType::Root => return format!("{}", self.path.display()).into(),
+            FileType::Conflicts => "Conflicts".into(),
+            FileType::Pinned => "Search results".into(),
+            FileType::Recent => "Recent".into(),
+            FileType::Conflicted | FileType::PinnedFile | FileType::RecentFile => {
+                helix_core::path::get_relative_path(&self.path)
+                    .to_string_lossy()
+                    .into_owned()
+                    .into()
+            }
             FileType::Dir => self
                 .path
                 .file_name()
@@ -84,30 +218,112 @@ impl TreeItem for FileInfo {
     fn text(&self, cx: &mut Context, selected: bool, state: &mut State) -> Spans {
         let text = self.get_text();
         let theme = &cx.editor.theme;
+        let styles = &mut state.styles;
+        if styles.theme_changes != cx.editor.theme_changes {
+            *styles = RowStyles {
+                theme_changes: cx.editor.theme_changes,
+                ..RowStyles::default()
+            };
+        }
 
-        let style = match self.file_type {
-            FileType::Parent | FileType::Dir | FileType::Root => "ui.explorer.dir",
-            FileType::File | FileType::Exe | FileType::Placeholder => "ui.explorer.file",
+        let key = StyleKey::Row {
+            file_type: self.file_type,
+            special: self.special,
+            broken: self.broken_link,
+            marked: self.is_markable() && state.marked.contains(&self.path),
+            selected: selected.then(|| state.focus),
         };
-        let mut style = theme.try_get(style).unwrap_or_else(|| theme.get("ui.text"));
-        if selected {
-            let patch = match state.focus {
-                true => "ui.explorer.focus",
-                false => "ui.explorer.unfocus",
+        let style = styles.get(theme, key);
+        let mut spans = Vec::new();
+        if cx.editor.config().explorer.icons {
+            let icon = match self.file_type {
+                FileType::File
+                | FileType::Exe
+                | FileType::Conflicted
+                | FileType::PinnedFile
+                | FileType::RecentFile => Some(icons::file_icon(Some(&self.path))),
+                FileType::Dir => Some(icons::DIRECTORY),
+                _ => None,
             };
-            if let Some(patch) = theme.try_get(patch) {
-                style = style.patch(patch);
-            } else {
-                style = style.add_modifier(Modifier::REVERSED);
+            if let Some(icon) = icon {
+                // the selected row keeps a single style
+                let icon_style = match selected {
+                    true => style,
+                    false => style.fg(icon.color),
+                };
+                let glyph = match self.file_type {
+                    FileType::Dir => format!(" {}", icon.glyph),
+                    _ => format!("{} ", icon.glyph),
+                };
+                spans.push(Span::styled(glyph, icon_style));
             }
         }
-        Spans::from(Span::styled(text, style))
+        spans.push(Span::styled(text, style));
+        // told by the colors otherwise
+        if cx.editor.config().explorer.screen_reader {
+            if let StyleKey::Row { marked: true, .. } = key {
+                spans.push(Span::styled(" [marked]", style));
+            }
+            if self.broken_link {
+                spans.push(Span::styled(" [broken]", style));
+            }
+        }
+        if cx.editor.config().explorer.classify {
+            let marker = match (self.file_type, self.special) {
+                (_, Some(special)) => special.marker(),
+                (FileType::Exe, _) => Some("*"),
+                _ => None,
+            };
+            if let Some(marker) = marker {
+                spans.push(Span::styled(marker, style));
+            }
+        }
+        // files with unsaved changes in their buffer, cleared once the buffer is written
+        if let FileType::File | FileType::Exe = self.file_type {
+            let modified = cx
+                .editor
+                .document_by_path(&self.path)
+                .map_or(false, |doc| doc.is_modified());
+            if modified {
+                let style = styles.get(theme, StyleKey::Modified);
+                spans.push(Span::styled(" [+]", style));
+            }
+        }
+        if cx.editor.config().explorer.last_commit {
+            if let FileType::File | FileType::Exe | FileType::Dir = self.file_type {
+                if let Some(commit) = state.commits.get(&self.path) {
+                    let style = styles.get(theme, StyleKey::Commit);
+                    spans.push(Span::styled(format!("  {}", commit), style));
+                }
+            }
+        }
+        if self.file_type == FileType::Root && cx.editor.explorer_locked {
+            spans.push(Span::styled("  [locked]", theme.get("warning")));
+        }
+        // the root is the header of the tree
+        if let (FileType::Root, Some(info)) = (self.file_type, &cx.editor.repo_status.info) {
+            if self.path.starts_with(&info.work_dir) {
+                let style = styles.get(theme, StyleKey::Vcs);
+                spans.push(Span::styled(format!("  {}", info), style));
+            }
+        }
+        Spans::from(spans)
     }
 
     fn is_child(&self, other: &Self) -> bool {
         if let FileType::Parent = other.file_type {
             return false;
         }
+        // the sections only hold their files
+        match (self.file_type, other.file_type) {
+            (FileType::Conflicted, other) => return other == FileType::Conflicts,
+            (FileType::PinnedFile, other) => return other == FileType::Pinned,
+            (FileType::RecentFile, other) => return other == FileType::Recent,
+            (_, FileType::Conflicts | FileType::Conflicted) => return false,
+            (_, FileType::Pinned | FileType::PinnedFile) => return false,
+            (_, FileType::Recent | FileType::RecentFile) => return false,
+            _ => {}
+        }
         if let FileType::Placeholder = self.file_type {
             self.path == other.path
         } else {
@@ -120,6 +336,21 @@ fn cmp(&self, other: &Self) -> Ordering {
         match (self.file_type, other.file_type) {
             (Parent, _) => return Ordering::Less,
             (_, Parent) => return Ordering::Greater,
+            (Pinned, _) => return Ordering::Less,
+            (_, Pinned) => return Ordering::Greater,
+            (PinnedFile, PinnedFile) => return self.path.cmp(&other.path),
+            (PinnedFile, _) => return Ordering::Less,
+            (_, PinnedFile) => return Ordering::Greater,
+            (Conflicts, _) => return Ordering::Less,
+            (_, Conflicts) => return Ordering::Greater,
+            (Conflicted, Conflicted) => return self.path.cmp(&other.path),
+            (Conflicted, _) => return Ordering::Less,
+            (_, Conflicted) => return Ordering::Greater,
+            (Recent, _) => return Ordering::Less,
+            (_, Recent) => return Ordering::Greater,
+            (RecentFile, RecentFile) => return self.rank.cmp(&other.rank),
+            (RecentFile, _) => return Ordering::Less,
+            (_, RecentFile) => return Ordering::Greater,
             (Root, _) => return Ordering::Less,
             (_, Root) => return Ordering::Greater,
             _ => {}
@@ -135,45 +366,33 @@ fn cmp(&self, other: &Self) -> Ordering {
 
         if let (Some(p1), Some(p2)) = (self.path.parent(), other.path.parent()) {
             if p1 == p2 {
-                match (self.file_type, other.file_type) {
-                    (Dir, File | Exe) => return Ordering::Less,
-                    (File | Exe, Dir) => return Ordering::Greater,
+                match (self.sort, self.file_type, other.file_type) {
+                    (ExplorerSort::DirsFirst, Dir, File | Exe) => return Ordering::Less,
+                    (ExplorerSort::DirsFirst, File | Exe, Dir) => return Ordering::Greater,
+                    (ExplorerSort::Modified, ..) if self.modified != other.modified => {
+                        return other.modified.cmp(&self.modified)
+                    }
                     _ => {}
                 };
             }
         }
-        self.path.cmp(&other.path)
+        self.comparator.compare_paths(&self.path, &other.path)
+    }
+
+    fn is_expandable(&self, cx: &Context) -> bool {
+        // the directories replace the listing in the list style
+        self.file_type == FileType::Dir && cx.editor.config().explorer.is_tree()
     }
 
     fn get_childs(&self) -> Result<Vec<Self>> {
-        match self.file_type {
-            FileType::Root | FileType::Dir => {}
-            _ => return Ok(vec![]),
-        };
-        let mut ret: Vec<_> = std::fs::read_dir(&self.path)?
-            .filter_map(|entry| entry.ok())
-            .filter_map(|entry| {
-                entry.metadata().ok().map(|meta| {
-                    let is_exe = false;
-                    let file_type = match (meta.is_dir(), is_exe) {
-                        (true, _) => FileType::Dir,
-                        (_, false) => FileType::File,
-                        (_, true) => FileType::Exe,
-                    };
-                    Self {
-                        file_type,
-                        path: self.path.join(entry.file_name()),
-                    }
-                })
-            })
-            .collect();
-        if ret.is_empty() {
-            ret.push(Self {
-                path: self.path.clone(),
-                file_type: FileType::Placeholder,
-            })
-        }
-        Ok(ret)
+        self.children(
+            &ExplorerConfig::default(),
+            ExplorerSort::default(),
+            SortComparator::default(),
+            None,
+            &OnlyFilter::default(),
+            &mut IgnoreCache::default(),
+        )
     }
 
     fn filter(&self, _cx: &mut Context, s: &str, _params: &mut Self::Params) -> bool {
@@ -226,12 +445,664 @@ enum PromptAction {
     RemoveDir,
     RemoveFile,
     Filter,
+    Retarget,
+}
+
+/// The least time between two additions of the entries of a [`Scan`] to the tree.
+const SCAN_MERGE_INTERVAL: Duration = Duration::from_millis(250);
+
+/// The files and directories under a directory, read in the background by parallel
+/// workers and added to the tree as they arrive.
+struct Scan {
+    entries: Receiver<FileInfo>,
+    /// The entries received but not added to the tree yet.
+    pending: Vec<FileInfo>,
+    /// When entries were last added to the tree.
+    merged: Instant,
+    /// Whether a redraw is on its way to add the pending entries.
+    wakeup: bool,
+    redraw: Arc<Notify>,
+    /// The directories received, and the ones holding entries, to tell the empty ones.
+    dirs: Vec<PathBuf>,
+    parents: HashSet<PathBuf>,
+}
+
+impl Scan {
+    /// Starts reading everything under `dir`, but the files ignored by the ignore files
+    /// with `git-ignore`, the hidden ones without `show-hidden` and the ones out of the
+    /// filter `preset` and the entries `only` leaves out, asking for a redraw after each
+    /// entry. The entries of the
+    /// directories in `sorts` are ordered by their own sort, and their names compared
+    /// with `comparator`.
+    fn start(
+        dir: PathBuf,
+        config: &ExplorerConfig,
+        preset: Option<&str>,
+        only: &OnlyFilter,
+        sorts: &BTreeMap<PathBuf, ExplorerSort>,
+        comparator: SortComparator,
+        redraw: Arc<Notify>,
+    ) -> Self {
+        let (show_hidden, sort) = (config.show_hidden, config.sort);
+        let globs = globs_matcher(&listing_globs(config, preset));
+        let rules = IgnoreRules::new(config);
+        let only = Arc::new(only.clone());
+        let sorts = Arc::new(sorts.clone());
+        let (sender, entries) = mpsc::channel();
+        let scan = Self {
+            entries,
+            pending: Vec::new(),
+            merged: Instant::now(),
+            wakeup: false,
+            redraw: redraw.clone(),
+            dirs: vec![dir.clone()],
+            parents: HashSet::new(),
+        };
+        tokio::task::spawn_blocking(move || {
+            let mut walk = WalkBuilder::new(&dir);
+            walk.standard_filters(false);
+            rules.configure(&mut walk);
+            walk.hidden(!show_hidden)
+                .filter_entry(move |entry: &DirEntry| {
+                    let is_dir = entry.file_type().map_or(false, |ft| ft.is_dir());
+                    let listed = only.keeps(entry.path(), is_dir, is_broken_entry(entry));
+                    listed && !is_ignored(&globs, entry.path(), is_dir)
+                })
+                .build_parallel()
+                .run(|| {
+                    let sender = sender.clone();
+                    let redraw = redraw.clone();
+                    let sorts = sorts.clone();
+                    Box::new(move |entry: Result<DirEntry, ignore::Error>| {
+                        let entry = match entry {
+                            // the directory itself is in the tree already
+                            Ok(entry) if entry.depth() > 0 => entry,
+                            _ => return WalkState::Continue,
+                        };
+                        // symbolic links are not followed, like when expanding
+                        let meta = entry.metadata().ok();
+                        let file_type = match (entry.file_type(), &meta) {
+                            (Some(file_type), _) if file_type.is_dir() => FileType::Dir,
+                            (_, Some(meta)) if is_executable(meta) => FileType::Exe,
+                            _ => FileType::File,
+                        };
+                        let dir = entry.path().parent();
+                        let item = FileInfo {
+                            sort: dir.and_then(|dir| sorts.get(dir)).copied().unwrap_or(sort),
+                            comparator,
+                            broken_link: is_broken_entry(&entry),
+                            special: meta.as_ref().and_then(Special::of),
+                            modified: meta.and_then(|meta| meta.modified().ok()),
+                            ..FileInfo::new(entry.into_path(), file_type)
+                        };
+                        if sender.send(item).is_err() {
+                            // the explorer was closed
+                            return WalkState::Quit;
+                        }
+                        redraw.notify_one();
+                        WalkState::Continue
+                    })
+                });
+            // for the explorer to notice the end
+            drop(sender);
+            redraw.notify_one();
+        });
+        scan
+    }
+
+    fn add(&mut self, item: FileInfo) {
+        if item.file_type == FileType::Dir {
+            self.dirs.push(item.path.clone());
+        }
+        if let Some(parent) = item.path.parent() {
+            if !self.parents.contains(parent) {
+                self.parents.insert(parent.to_path_buf());
+            }
+        }
+        self.pending.push(item);
+    }
+}
+
+/// The ignore files read in each directory before the `ignore-files` of the config, the
+/// later ones taking precedence like with ripgrep and fd.
+const IGNORE_FILES: [&str; 3] = [".gitignore", ".ignore", ".fdignore"];
+
+/// Which ignore files hide the entries, the same for the directories listed one by one
+/// and for the tree walked by a scan.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct IgnoreRules {
+    /// Whether the ignore files are read, with `git-ignore`. The `.git/info/exclude`
+    /// file of the repository and the global ignore file of git are read too then.
+    enabled: bool,
+    /// The names of the ignore files read in each directory, the later ones taking
+    /// precedence.
+    names: Vec<String>,
+}
+
+impl IgnoreRules {
+    fn new(config: &ExplorerConfig) -> Self {
+        let names = IGNORE_FILES.iter().map(|name| name.to_string());
+        Self {
+            enabled: config.git_ignore,
+            names: names.chain(config.ignore_files.iter().cloned()).collect(),
+        }
+    }
+
+    /// Has `walk` read the ignore files like [IgnoreCache::matchers] does.
+    fn configure(&self, walk: &mut WalkBuilder) {
+        if self.enabled {
+            // the walker reads the `.gitignore` and `.ignore` files by itself
+            for name in self.names.iter().skip(2) {
+                walk.add_custom_ignore_filename(name);
+            }
+        }
+        walk.git_ignore(self.enabled)
+            .ignore(self.enabled)
+            .parents(self.enabled)
+            .git_exclude(self.enabled)
+            .git_global(self.enabled)
+            // like the directories listed one by one
+            .require_git(false);
+    }
+
+    /// Whether `path` is one of the ignore files.
+    fn is_ignore_file(&self, path: &Path) -> bool {
+        let name = path.file_name().and_then(|name| name.to_str());
+        self.enabled && name.map_or(false, |name| self.names.iter().any(|n| n == name))
+    }
+}
+
+/// A directory whose ignore files were read.
+#[derive(Debug, Clone)]
+struct IgnoreDir {
+    /// Whether it holds a repository, whose parents' ignore files do not apply to it.
+    repo: bool,
+    matcher: Option<Arc<Gitignore>>,
+}
+
+/// The compiled ignore files of the directories listed so far. They are only read once,
+/// until [IgnoreCache::clear] once the editor writes them or notices they changed.
+#[derive(Debug, Clone, Default)]
+struct IgnoreCache {
+    dirs: HashMap<PathBuf, IgnoreDir>,
+    /// The global ignore file of git, once read.
+    global: Option<Option<Arc<Gitignore>>>,
+    /// The `ignore` globs of the config, compiled.
+    globs: (Vec<String>, Option<Arc<Gitignore>>),
+    /// The rules the directories were read with.
+    rules: IgnoreRules,
+}
+
+impl IgnoreCache {
+    /// The matcher of the `ignore` globs, compiled again once they change.
+    fn globs(&mut self, globs: &[String]) -> Option<Arc<Gitignore>> {
+        if self.globs.0 != globs {
+            let matcher = globs_matcher(globs).pop();
+            self.globs = (globs.to_vec(), matcher);
+        }
+        self.globs.1.clone()
+    }
+
+    /// Forgets the ignore files read, to read them again.
+    fn clear(&mut self) {
+        self.dirs.clear();
+        self.global = None;
+    }
+
+    /// The matchers applying to the entries of `dir` with `rules`: the ones of `dir` and
+    /// of its parents up to the root of its repository, the closest first, then the
+    /// global ignore file of git.
+    fn matchers(&mut self, dir: &Path, rules: &IgnoreRules) -> Vec<Arc<Gitignore>> {
+        if self.rules != *rules {
+            self.clear();
+            self.rules = rules.clone();
+        }
+        if !rules.enabled {
+            return Vec::new();
+        }
+        let mut matchers = Vec::new();
+        for dir in dir.ancestors() {
+            let dir = self.dir(dir);
+            matchers.extend(dir.matcher.clone());
+            // the ignore files outside of a repository do not apply to it
+            if dir.repo {
+                break;
+            }
+        }
+        let global = self.global.get_or_insert_with(|| {
+            let (matcher, err) = Gitignore::global();
+            if let Some(err) = err {
+                log::warn!("failed to read the global git ignore file: {}", err);
+            }
+            (!matcher.is_empty()).then(|| Arc::new(matcher))
+        });
+        matchers.extend(global.clone());
+        matchers
+    }
+
+    fn dir(&mut self, dir: &Path) -> &IgnoreDir {
+        let names = &self.rules.names;
+        self.dirs.entry(dir.to_path_buf()).or_insert_with(|| {
+            let repo = dir.join(".git").exists();
+            let mut builder = GitignoreBuilder::new(dir);
+            // `.git/info/exclude` takes precedence over nothing but the global file
+            let exclude = repo.then(|| dir.join(".git/info/exclude"));
+            let files = exclude
+                .into_iter()
+                .chain(names.iter().map(|name| dir.join(name)));
+            let mut read = false;
+            for file in files.filter(|file| file.is_file()) {
+                read = true;
+                if let Some(err) = builder.add(&file) {
+                    log::warn!("failed to read '{}': {}", file.display(), err);
+                }
+            }
+            let matcher = match read {
+                true => builder.build().ok().map(Arc::new),
+                false => None,
+            };
+            IgnoreDir { repo, matcher }
+        })
+    }
+}
+
+/// The matcher of `globs`, written like the lines of a `.gitignore` file in the working
+/// directory, empty without globs.
+fn globs_matcher(globs: &[String]) -> Vec<Arc<Gitignore>> {
+    if globs.is_empty() {
+        return Vec::new();
+    }
+    let mut builder = GitignoreBuilder::new(std::env::current_dir().unwrap_or_default());
+    for glob in globs {
+        if let Err(err) = builder.add_line(None, glob) {
+            log::warn!("invalid explorer ignore glob '{}': {}", glob, err);
+        }
+    }
+    match builder.build() {
+        Ok(matcher) => vec![Arc::new(matcher)],
+        Err(err) => {
+            log::warn!("failed to build the explorer ignore globs: {}", err);
+            Vec::new()
+        }
+    }
+}
+
+/// The `ignore` globs of `config`, after the ones hiding the files matching none of the
+/// globs of the filter `preset` when it is one of the config.
+fn listing_globs(config: &ExplorerConfig, preset: Option<&str>) -> Vec<String> {
+    let preset = match preset.and_then(|name| config.filter_presets.get(name)) {
+        Some(preset) => preset,
+        None => return config.ignore.clone(),
+    };
+    // the last matching glob wins, the directories stay listed unless ignored
+    let mut globs = vec!["*".to_string(), "!*/".to_string()];
+    globs.extend(preset.iter().map(|glob| format!("!{}", glob)));
+    globs.extend(config.ignore.iter().cloned());
+    globs
+}
+
+/// Whether the entry at `path`, with the metadata `meta` of the entry itself, is a symbolic
+/// link to nothing.
+fn is_broken_link(path: &Path, meta: &std::fs::Metadata) -> bool {
+    meta.file_type().is_symlink() && std::fs::metadata(path).is_err()
+}
+
+fn is_broken_entry(entry: &DirEntry) -> bool {
+    entry.path_is_symlink() && std::fs::metadata(entry.path()).is_err()
+}
+
+/// A kind of entries neither regular files nor directories, told apart by their style.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum Special {
+    Socket,
+    Fifo,
+    /// A block or character device.
+    Device,
+}
+
+impl Special {
+    #[cfg(unix)]
+    fn of(meta: &std::fs::Metadata) -> Option<Self> {
+        use std::os::unix::fs::FileTypeExt;
+        let file_type = meta.file_type();
+        if file_type.is_socket() {
+            Some(Self::Socket)
+        } else if file_type.is_fifo() {
+            Some(Self::Fifo)
+        } else if file_type.is_block_device() || file_type.is_char_device() {
+            Some(Self::Device)
+        } else {
+            None
+        }
+    }
+
+    #[cfg(not(unix))]
+    fn of(_meta: &std::fs::Metadata) -> Option<Self> {
+        None
+    }
+
+    fn scope(self) -> &'static str {
+        match self {
+            Self::Socket => "ui.explorer.socket",
+            Self::Fifo => "ui.explorer.fifo",
+            Self::Device => "ui.explorer.device",
+        }
+    }
+
+    /// The marker after the name with `classify`, like `ls -F` writes.
+    fn marker(self) -> Option<&'static str> {
+        match self {
+            Self::Socket => Some("="),
+            Self::Fifo => Some("|"),
+            Self::Device => None,
+        }
+    }
+}
+
+/// Whether the entry with the metadata `meta` is a file any of its users may execute.
+#[cfg(unix)]
+fn is_executable(meta: &std::fs::Metadata) -> bool {
+    use std::os::unix::fs::PermissionsExt;
+    meta.is_file() && meta.permissions().mode() & 0o111 != 0
+}
+
+#[cfg(not(unix))]
+fn is_executable(_meta: &std::fs::Metadata) -> bool {
+    false
+}
+
+/// A kind of files the listing can be restricted to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Only {
+    BrokenLinks,
+    /// The files the repository does not track.
+    Untracked,
+    /// The tracked files with uncommitted changes.
+    Modified,
+}
+
+impl Only {
+    fn name(self) -> &'static str {
+        match self {
+            Self::BrokenLinks => "broken links",
+            Self::Untracked => "untracked files",
+            Self::Modified => "modified files",
+        }
+    }
+}
+
+/// The files of a kind the entries listed are restricted to, with the directories holding
+/// them, or all the entries for `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct OnlyFilter {
+    only: Option<Only>,
+    /// The files of the repository status of the kind.
+    files: HashSet<PathBuf>,
+    /// The directories holding them.
+    dirs: HashSet<PathBuf>,
+}
+
+impl OnlyFilter {
+    fn new(only: Option<Only>, status: &Status) -> Self {
+        let kept = |status: FileStatus| match only {
+            Some(Only::Untracked) => status == FileStatus::Untracked,
+            Some(Only::Modified) => status != FileStatus::Untracked,
+            _ => false,
+        };
+        let files: HashSet<_> = status
+            .files
+            .iter()
+            .filter(|(_, status)| kept(**status))
+            .map(|(path, _)| path.clone())
+            .collect();
+        let dirs = files
+            .iter()
+            .flat_map(|file| file.ancestors().skip(1))
+            .map(Path::to_path_buf)
+            .collect();
+        Self { only, files, dirs }
+    }
+
+    /// Whether the entry at `path` is listed, `broken_link` telling whether it is a
+    /// symbolic link to nothing.
+    fn keeps(&self, path: &Path, is_dir: bool, broken_link: bool) -> bool {
+        match self.only {
+            None => true,
+            Some(Only::BrokenLinks) => is_dir || broken_link,
+            // the entries of an untracked directory are untracked too
+            Some(Only::Untracked | Only::Modified) => {
+                self.dirs.contains(path) || path.ancestors().any(|dir| self.files.contains(dir))
+            }
+        }
+    }
+}
+
+/// Whether the file or directory at `path` is ignored by `matchers`, the closest to it
+/// first.
+fn is_ignored(matchers: &[Arc<Gitignore>], path: &Path, is_dir: bool) -> bool {
+    for matcher in matchers {
+        match matcher.matched(path, is_dir) {
+            Match::Ignore(_) => return true,
+            Match::Whitelist(_) => return false,
+            Match::None => {}
+        }
+    }
+    false
+}
+
+/// What the style of a part of a row depends on.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+enum StyleKey {
+    Row {
+        file_type: FileType,
+        special: Option<Special>,
+        /// Whether the row is a symbolic link to nothing.
+        broken: bool,
+        marked: bool,
+        /// Whether the explorer is focused, for the selected row.
+        selected: Option<bool>,
+    },
+    /// The marker of the files with unsaved changes.
+    Modified,
+    /// The state of the repository next to the root.
+    Vcs,
+    /// The last commit of an entry.
+    Commit,
+}
+
+impl StyleKey {
+    fn resolve(self, theme: &Theme) -> Style {
+        let (file_type, special, broken, marked, selected) = match self {
+            Self::Modified => return get_theme!(theme, "ui.explorer.modified", "ui.text.inactive"),
+            Self::Vcs => return get_theme!(theme, "ui.explorer.vcs", "ui.text.inactive"),
+            Self::Commit => return get_theme!(theme, "ui.explorer.commit", "ui.text.inactive"),
+            Self::Row {
+                file_type,
+                special,
+                broken,
+                marked,
+                selected,
+            } => (file_type, special, broken, marked, selected),
+        };
+        let file = || get_theme!(theme, "ui.explorer.file", "ui.text");
+        let mut style = match file_type {
+            _ if broken => get_theme!(theme, "ui.explorer.broken-link", "error"),
+            _ if special.is_some() => special
+                .and_then(|special| theme.try_get(special.scope()))
+                .unwrap_or_else(file),
+            FileType::Exe => theme.try_get("ui.explorer.exe").unwrap_or_else(file),
+            FileType::Parent | FileType::Dir | FileType::Root => {
+                get_theme!(theme, "ui.explorer.dir", "ui.text")
+            }
+            FileType::File | FileType::Placeholder => file(),
+            FileType::Conflicts | FileType::Conflicted => {
+                get_theme!(theme, "ui.explorer.conflict", "warning")
+            }
+            FileType::Pinned | FileType::PinnedFile => {
+                get_theme!(theme, "ui.explorer.pinned", "ui.text")
+            }
+            FileType::Recent | FileType::RecentFile => {
+                get_theme!(theme, "ui.explorer.recent", "ui.text")
+            }
+        };
+        if marked {
+            style = style.patch(get_theme!(theme, "ui.explorer.marked", "ui.selection"));
+        }
+        if let Some(focus) = selected {
+            let patch = match focus {
+                true => "ui.explorer.focus",
+                false => "ui.explorer.unfocus",
+            };
+            if let Some(patch) = theme.try_get(patch) {
+                style = style.patch(patch);
+            } else {
+                style = style.add_modifier(Modifier::REVERSED);
+            }
+        }
+        style
+    }
+}
+
+/// The styles of the rows resolved so far, kept across frames instead of looking them
+/// up in the theme for each row.
+#[derive(Debug, Clone, Default)]
+struct RowStyles {
+    /// [`Editor::theme_changes`] as of the styles.
+    theme_changes: usize,
+    styles: HashMap<StyleKey, Style>,
+}
+
+impl RowStyles {
+    fn get(&mut self, theme: &Theme, key: StyleKey) -> Style {
+        *self.styles.entry(key).or_insert_with(|| key.resolve(theme))
+    }
+}
+
+/// The last commits of the entries shown with `last-commit`, as the subject and age
+/// shown after their name. They are read in the background as the entries are shown.
+#[derive(Debug, Clone, Default)]
+struct LastCommits {
+    /// The commits read so far, shared with the readings going on. Entries without one,
+    /// out of a repository or not committed yet, are kept as `None`.
+    read: Arc<Mutex<HashMap<PathBuf, Option<String>>>>,
+    /// The entries asked for, and the ones of them to be read yet.
+    requested: HashSet<PathBuf>,
+    pending: Vec<PathBuf>,
+}
+
+impl LastCommits {
+    /// The last commit of `path`, which is read by the next [`Self::read_pending`] unless
+    /// it was asked for already.
+    fn get(&mut self, path: &Path) -> Option<String> {
+        let read = self.read.lock().unwrap().get(path).cloned();
+        if read.is_none() && self.requested.insert(path.to_path_buf()) {
+            self.pending.push(path.to_path_buf());
+        }
+        read.flatten()
+    }
+
+    /// Reads the commits of the entries asked for since the last call, asking for a redraw
+    /// after each.
+    fn read_pending(&mut self, providers: DiffProviderRegistry, redraw: Arc<Notify>) {
+        if self.pending.is_empty() {
+            return;
+        }
+        let paths = std::mem::take(&mut self.pending);
+        let read = self.read.clone();
+        tokio::task::spawn_blocking(move || {
+            for path in paths {
+                let commit = providers.last_commit(&path).ok().flatten();
+                let commit = commit.map(|commit| format!("{} · {}", commit.message, commit.date));
+                read.lock().unwrap().insert(path, commit);
+                redraw.notify_one();
+            }
+        });
+    }
+
+    /// Forgets the commits read, for them to be read again. The readings going on finish
+    /// into the old ones.
+    fn clear(&mut self) {
+        *self = Self::default();
+    }
+}
+
+/// Images previewed at once at most, the others being read again when selected.
+const MAX_IMAGE_PREVIEWS: usize = 16;
+
+enum ImageState {
+    Loading,
+    /// The preview, `None` if the image could not be read, and the modification time of
+    /// the file when it was read.
+    Loaded(Option<ImagePreview>, Option<SystemTime>),
+}
+
+/// The previews of the images selected, read in the background, and again once their file
+/// changes.
+#[derive(Default)]
+struct ImagePreviews {
+    previews: Arc<Mutex<HashMap<PathBuf, ImageState>>>,
+}
+
+impl ImagePreviews {
+    /// Renders the preview of the image at `path`, reading it first. Returns `false` if
+    /// it could not be read, nothing being rendered until it is.
+    fn render(&self, path: &Path, area: Rect, surface: &mut Surface, editor: &Editor) -> bool {
+        let mtime = std::fs::metadata(path)
+            .and_then(|meta| meta.modified())
+            .ok();
+        let mut previews = self.previews.lock().unwrap();
+        match previews.get(path) {
+            Some(ImageState::Loading) => return true,
+            Some(ImageState::Loaded(preview, read)) if *read == mtime => {
+                if let Some(preview) = preview {
+                    preview.render_preview(area, surface, &editor.theme);
+                }
+                return preview.is_some();
+            }
+            _ => (),
+        }
+        if previews.len() >= MAX_IMAGE_PREVIEWS {
+            let loaded = previews
+                .iter()
+                .find(|(_, state)| matches!(state, ImageState::Loaded(..)))
+                .map(|(path, _)| path.clone());
+            if let Some(loaded) = loaded {
+                previews.remove(&loaded);
+            }
+        }
+        previews.insert(path.to_path_buf(), ImageState::Loading);
+
+        let previews = self.previews.clone();
+        let path = path.to_path_buf();
+        let protocol = editor.image_protocol;
+        let redraw = editor.redraw_handle.0.clone();
+        tokio::task::spawn_blocking(move || {
+            let preview = ImagePreview::new(path.clone(), protocol).ok();
+            let loaded = ImageState::Loaded(preview, mtime);
+            previews.lock().unwrap().insert(path, loaded);
+            redraw.notify_one();
+        });
+        true
+    }
 }
 
 #[derive(Clone, Debug)]
 struct State {
     focus: bool,
     current_root: PathBuf,
+    /// The files and directories marked to act on them together.
+    marked: BTreeSet<PathBuf>,
+    styles: RowStyles,
+    ignores: IgnoreCache,
+    /// The name of the active filter preset of the config.
+    preset: Option<String>,
+    /// The files listed, all of them unless they are restricted to some kind.
+    only: OnlyFilter,
+    /// The directories ordered otherwise than the `sort` of the config, with their order.
+    sorts: BTreeMap<PathBuf, ExplorerSort>,
+    /// The files of the workspace search pinned to the top, until they are cleared.
+    pinned: Vec<PathBuf>,
+    commits: LastCommits,
 }
 
 impl State {
@@ -239,8 +1110,76 @@ fn new(focus: bool, current_root: PathBuf) -> Self {
         Self {
             focus,
             current_root,
+            marked: BTreeSet::new(),
+            styles: RowStyles::default(),
+            ignores: IgnoreCache::default(),
+            preset: None,
+            only: OnlyFilter::default(),
+            sorts: BTreeMap::new(),
+            pinned: Vec::new(),
+            commits: LastCommits::default(),
         }
     }
+
+    /// The order of the entries of `dir`.
+    fn sort(&self, dir: &Path, config: &ExplorerConfig) -> ExplorerSort {
+        self.sorts.get(dir).copied().unwrap_or(config.sort)
+    }
+}
+
+/// A subcommand of `:explorer`, run by the editor view on its explorer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ExplorerCommand {
+    /// Opens the explorer unless it is open.
+    Open,
+    Close,
+    /// Closes the explorer if it is open, opens it otherwise.
+    Toggle,
+    /// Selects a file, the one of the focused buffer by default.
+    Reveal(Option<PathBuf>),
+    /// Shows a directory.
+    Root(PathBuf),
+    Refresh,
+    /// Focuses the explorer, opening it if it is closed.
+    Focus,
+    /// Lists a file created by saving a buffer, not run by `:explorer`.
+    Created(PathBuf),
+    /// Notes files written by the editor or changed on disk, not run by `:explorer`.
+    Changed(Vec<PathBuf>),
+}
+
+impl ExplorerCommand {
+    pub const NAMES: &'static [&'static str] = &[
+        "open", "close", "toggle", "reveal", "root", "refresh", "focus",
+    ];
+
+    /// Parses the arguments of `:explorer`, the paths being made absolute.
+    pub fn parse(args: &[Cow<str>]) -> Result<Self> {
+        let path = |path: &str| -> Result<PathBuf> {
+            let path = helix_core::path::expand_tilde(Path::new(path));
+            Ok(helix_core::path::get_canonicalized_path(&path)?)
+        };
+        let args: Vec<&str> = args.iter().map(|arg| arg.as_ref()).collect();
+        let command = match args.as_slice() {
+            ["open"] => Self::Open,
+            ["close"] => Self::Close,
+            ["toggle"] => Self::Toggle,
+            ["reveal"] => Self::Reveal(None),
+            ["reveal", file] => Self::Reveal(Some(path(file)?)),
+            ["root", dir] => {
+                let dir = path(dir)?;
+                ensure!(dir.is_dir(), "'{}' is not a directory", dir.display());
+                Self::Root(dir)
+            }
+            ["refresh"] => Self::Refresh,
+            ["focus"] => Self::Focus,
+            _ => bail!(
+                "Bad arguments. Usage: `:explorer {}`",
+                "open|close|toggle|reveal [path]|root <path>|refresh|focus"
+            ),
+        };
+        Ok(command)
+    }
 }
 
 pub struct Explorer {
@@ -251,63 +1190,1063 @@ pub struct Explorer {
     on_next_key: Option<Box<dyn FnMut(&mut Context, &mut Self, KeyEvent) -> EventResult>>,
     #[allow(clippy::type_complexity)]
     repeat_motion: Option<Box<dyn FnMut(&mut Self, PromptAction, &mut Context) + 'static>>,
+    image_previews: ImagePreviews,
+    /// The preview of the text of the last selected file.
+    text_preview: Option<TextPreview>,
+    /// Where the text preview was last rendered, for scrolling it with the mouse.
+    preview_area: Rect,
+    /// Where the prompt was last rendered, for the clicks pasting into it.
+    prompt_area: Rect,
+    /// The id of the tab page whose directory is shown.
+    tab: usize,
+    /// [`Editor::repo_refreshes`] as of the last check of the conflicted files.
+    repo_refreshes: usize,
+    /// The reading of the whole directory tree, until it is done.
+    scan: Option<Scan>,
+    /// The options as of the last render, to list the directories again once the ones
+    /// changing the listing are set.
+    config: ExplorerConfig,
+    comparator: SortComparator,
+    /// The file of the focused buffer last selected with `follow`.
+    followed: Option<PathBuf>,
+    /// The actions menu, until the key of an action is pressed.
+    menu: Option<Info>,
+    /// The directory the file or directory of the create prompt goes to.
+    new_dir: PathBuf,
+    /// The item last told in the statusline with `screen-reader`, and whether it was
+    /// expanded and marked then.
+    announced: Option<(PathBuf, FileType, bool, bool)>,
 }
 
 impl Explorer {
     pub fn new(cx: &mut Context) -> Result<Self> {
-        let current_root = std::env::current_dir().unwrap_or_else(|_| "./".into());
-        let items = Self::get_items(current_root.clone(), cx)?;
+        let current_root = Self::tab_root(cx.editor);
+        let mut state = State::new(true, current_root.clone());
+        let items = Self::get_items(current_root, cx, &mut state)?;
         Ok(Self {
             tree: Tree::build_tree(items).with_enter_fn(Self::toggle_current),
-            state: State::new(true, current_root),
+            state,
             repeat_motion: None,
             prompt: None,
             on_next_key: None,
+            image_previews: ImagePreviews::default(),
+            text_preview: None,
+            preview_area: Rect::default(),
+            prompt_area: Rect::default(),
+            tab: cx.editor.tree.tab().id,
+            repo_refreshes: cx.editor.repo_refreshes,
+            scan: None,
+            config: cx.editor.config().explorer.clone(),
+            comparator: cx.editor.config().sort_comparator,
+            followed: None,
+            menu: None,
+            new_dir: PathBuf::new(),
+            announced: None,
         })
     }
 
-    pub fn new_explorer_recursion() -> Result<Self> {
-        let current_root = std::env::current_dir().unwrap_or_else(|_| "./".into());
+    /// An explorer with all the directories under the root expanded. They are read in
+    /// the background and shown as they are read.
+    pub fn new_explorer_recursion(editor: &Editor) -> Result<Self> {
+        let current_root = Self::tab_root(editor);
+        // fail early for a root which can not be read
+        std::fs::read_dir(&current_root)?;
         let parent = FileInfo::parent(&current_root);
         let root = FileInfo::root(current_root.clone());
-        let mut tree =
-            Tree::build_from_root(root, usize::MAX / 2)?.with_enter_fn(Self::toggle_current);
-        tree.insert_current_level(parent);
+        let tree = Tree::build_tree(vec![parent, root]).with_enter_fn(Self::toggle_current);
+        let config = editor.config().explorer.clone();
+        let scan = Scan::start(
+            current_root.clone(),
+            &config,
+            None,
+            &OnlyFilter::default(),
+            &BTreeMap::new(),
+            editor.config().sort_comparator,
+            editor.redraw_handle.0.clone(),
+        );
         Ok(Self {
             tree,
             state: State::new(true, current_root),
             repeat_motion: None,
             prompt: None,
             on_next_key: None,
+            image_previews: ImagePreviews::default(),
+            text_preview: None,
+            preview_area: Rect::default(),
+            prompt_area: Rect::default(),
+            tab: editor.tree.tab().id,
+            repo_refreshes: editor.repo_refreshes,
+            scan: Some(scan),
+            config,
+            comparator: editor.config().sort_comparator,
+            followed: None,
+            menu: None,
+            new_dir: PathBuf::new(),
+            announced: None,
         })
         // let mut root = vec![, FileInfo::root(p)];
     }
 
-    // pub fn new_with_uri(uri: String) -> Result<Self> {
-    //     // support remote file?
-
-    //     let p = Path::new(&uri);
-    //     ensure!(p.exists(), "path: {uri} is not exist");
-    //     ensure!(p.is_dir(), "path: {uri} is not dir");
-    //     Ok(Self::default().with_list(get_sub(p, None)?))
-    // }
-
-    pub fn focus(&mut self) {
-        self.state.focus = true
+    // pub fn new_with_uri(uri: String) -> Result<Self> {
+    //     // support remote file?
+
+    //     let p = Path::new(&uri);
+    //     ensure!(p.exists(), "path: {uri} is not exist");
+    //     ensure!(p.is_dir(), "path: {uri} is not dir");
+    //     Ok(Self::default().with_list(get_sub(p, None)?))
+    // }
+
+    /// The directory of the active tab page, the `root` of the config or the working
+    /// directory unless it has one.
+    fn tab_root(editor: &Editor) -> PathBuf {
+        let cwd = std::env::current_dir().unwrap_or_else(|_| "./".into());
+        let root = editor.tree.tab().explorer_root.clone();
+        root.or_else(|| {
+            let config = editor.config();
+            config.explorer.root.as_ref().map(|root| cwd.join(root))
+        })
+        .unwrap_or(cwd)
+    }
+
+    /// Shows the directory of the active tab page after switching tab pages.
+    fn sync_tab(&mut self, cx: &mut Context) {
+        self.tab = cx.editor.tree.tab().id;
+        let root = Self::tab_root(cx.editor);
+        if root == self.state.current_root {
+            return;
+        }
+        match Self::get_items(root.clone(), cx, &mut self.state) {
+            Ok(items) => {
+                self.state.current_root = root;
+                self.tree = Tree::build_tree(items).with_enter_fn(Self::toggle_current);
+            }
+            Err(e) => cx.editor.set_error(format!("{e}")),
+        }
+    }
+
+    /// Lists the conflicted files again after the repository changed, dropping the ones
+    /// resolved since.
+    fn refresh_conflicts(&mut self, cx: &mut Context) {
+        self.repo_refreshes = cx.editor.repo_refreshes;
+        let mut listed: Vec<_> = self
+            .tree
+            .items()
+            .filter(|item| item.file_type == FileType::Conflicted)
+            .map(|item| item.path.clone())
+            .collect();
+        let mut conflicted = cx.editor.conflicted_files(&self.state.current_root);
+        listed.sort();
+        conflicted.sort();
+        if listed == conflicted {
+            return;
+        }
+
+        // only the rows of the conflicts change, the directories are not read again
+        self.tree.remove_where(|item| {
+            item.file_type == FileType::Conflicted && conflicted.binary_search(&item.path).is_err()
+        });
+        if conflicted.is_empty() {
+            self.tree
+                .remove_where(|item| item.file_type == FileType::Conflicts);
+            return;
+        }
+        if listed.is_empty() {
+            let header = FileInfo::new(self.state.current_root.clone(), FileType::Conflicts);
+            self.tree.insert(header);
+        }
+        for path in conflicted {
+            if listed.binary_search(&path).is_err() {
+                self.tree.insert(FileInfo::new(path, FileType::Conflicted));
+            }
+        }
+    }
+
+    /// Pins the files matched by the last workspace search to the top, in place of the
+    /// ones pinned before.
+    pub fn pin_search_results(&mut self, cx: &mut Context) {
+        if cx.editor.search_results.is_empty() {
+            cx.editor.set_status("No workspace search results to pin");
+            return;
+        }
+        self.clear_pinned();
+        self.state.pinned = cx.editor.search_results.clone();
+        let header = FileInfo::new(self.state.current_root.clone(), FileType::Pinned);
+        self.tree.insert(header);
+        for path in &self.state.pinned {
+            self.tree
+                .insert(FileInfo::new(path.clone(), FileType::PinnedFile));
+        }
+        self.tree
+            .select(|item| item.file_type == FileType::PinnedFile);
+    }
+
+    /// Removes the section of the pinned search results.
+    pub fn clear_pinned(&mut self) {
+        self.state.pinned.clear();
+        self.tree
+            .remove_where(|item| matches!(item.file_type, FileType::Pinned | FileType::PinnedFile));
+    }
+
+    /// Applies the options changed since the last render, set with `:set` or by reloading
+    /// the config: the directories are listed again once the options listing them change,
+    /// and a new `root` is shown unless the tab page has its own directory.
+    fn sync_config(&mut self, cx: &mut Context) {
+        let comparator = cx.editor.config().sort_comparator;
+        let old = {
+            let config = &cx.editor.config().explorer;
+            if self.config == *config && self.comparator == comparator {
+                return;
+            }
+            std::mem::replace(&mut self.config, config.clone())
+        };
+        let old_comparator = std::mem::replace(&mut self.comparator, comparator);
+        if !old.last_commit {
+            // read again, as the repository may have changed while they weren't shown
+            self.state.commits.clear();
+        }
+        let new_root =
+            old.root != self.config.root && Self::tab_root(cx.editor) != self.state.current_root;
+        if new_root {
+            // listed with the other new options along
+            self.sync_tab(cx);
+        } else if lists_differently(&old, &self.config) || old_comparator != comparator {
+            self.refresh(cx);
+        }
+    }
+
+    /// Lists the root and the expanded directories again, keeping the selected item. The
+    /// ignore files are read again.
+    pub fn refresh(&mut self, cx: &mut Context) {
+        self.state.ignores.clear();
+        let session = self.session();
+        let selected = {
+            let item = self.tree.current_item();
+            (item.path.clone(), item.file_type)
+        };
+        let items = match Self::get_items(session.root.clone(), cx, &mut self.state) {
+            Ok(items) => items,
+            Err(e) => {
+                cx.editor.set_error(format!("{e}"));
+                return;
+            }
+        };
+        self.tree = Tree::build_tree(items).with_enter_fn(Self::toggle_current);
+        self.state.commits.clear();
+        if self.scan.is_some() {
+            // the entries read so far may be listed differently
+            self.scan = Some(Scan::start(
+                session.root,
+                &self.config,
+                self.state.preset.as_deref(),
+                &self.state.only,
+                &self.state.sorts,
+                self.comparator,
+                cx.editor.redraw_handle.0.clone(),
+            ));
+        } else {
+            self.expand(&session.expanded, cx);
+        }
+        self.tree
+            .select(|item| item.path == selected.0 && item.file_type == selected.1);
+    }
+
+    /// Selects the file at `path`, expanding the directories between the root and it in
+    /// the tree style. The selection is kept for the files which are not listed.
+    fn reveal(&mut self, path: &Path, cx: &mut Context) {
+        let relative = match path.strip_prefix(&self.state.current_root) {
+            Ok(relative) => relative.to_path_buf(),
+            Err(_) => return,
+        };
+        let selected = {
+            let item = self.tree.current_item();
+            (item.path.clone(), item.file_type)
+        };
+        if self.config.is_tree() {
+            let mut dir = self.state.current_root.clone();
+            for component in relative.parent().into_iter().flat_map(Path::components) {
+                dir.push(component);
+                if self
+                    .tree
+                    .select(|item| item.file_type == FileType::Dir && item.path == dir)
+                {
+                    self.tree.expand_current(cx, &mut self.state);
+                }
+            }
+        }
+        let found = self.tree.select(|item| {
+            matches!(
+                item.file_type,
+                FileType::File | FileType::Exe | FileType::Dir
+            ) && item.path == path
+        });
+        if !found {
+            self.tree
+                .select(|item| item.path == selected.0 && item.file_type == selected.1);
+        }
+    }
+
+    /// Lists the entries again once one of `paths`, written by the editor or changed on
+    /// disk, is an ignore file.
+    pub fn files_changed(&mut self, paths: &[PathBuf], cx: &mut Context) {
+        let rules = IgnoreRules::new(&self.config);
+        if paths.iter().any(|path| rules.is_ignore_file(path)) {
+            self.refresh(cx);
+        }
+    }
+
+    /// Adds the file created at `path` outside of the explorer to the listed entries of its
+    /// directory, unless the filters hide it, and selects it with `follow`.
+    pub fn add_created(&mut self, path: &Path, cx: &mut Context) {
+        let dir = match path.parent() {
+            Some(dir) => dir,
+            None => return,
+        };
+        let listed = self.tree.lists_children(|item| {
+            matches!(item.file_type, FileType::Dir | FileType::Root) && item.path == dir
+        });
+        if listed && !self.tree.items().any(|item| item.path == path) {
+            let config = &cx.editor.config().explorer;
+            let sort = self.state.sort(dir, config);
+            let comparator = cx.editor.config().sort_comparator;
+            let preset = self.state.preset.as_deref();
+            let (only, ignores) = (&self.state.only, &mut self.state.ignores);
+            let parent = FileInfo::new(dir.to_path_buf(), FileType::Dir);
+            let entry = parent
+                .children(config, sort, comparator, preset, only, ignores)
+                .ok()
+                .and_then(|entries| entries.into_iter().find(|entry| entry.path == path));
+            if let Some(entry) = entry {
+                self.tree.remove_where(|item| {
+                    item.file_type == FileType::Placeholder && item.path == dir
+                });
+                self.tree.insert(entry);
+            }
+        }
+        if self.config.follow {
+            self.reveal(path, cx);
+        }
+    }
+
+    /// Selects the file at `path`, showing its directory first when it is not under the
+    /// root.
+    pub fn reveal_file(&mut self, path: &Path, cx: &mut Context) {
+        if !path.starts_with(&self.state.current_root) {
+            if let Some(dir) = path.parent() {
+                self.set_root(dir.to_path_buf(), cx);
+            }
+        }
+        self.reveal(path, cx);
+    }
+
+    /// Selects the first of the `manifests` of the config in the closest directory holding
+    /// one, from the selected directory or the one of the selected file up. Starting from
+    /// a manifest looks for the ones of the enclosing projects.
+    pub fn locate_manifest(&mut self, cx: &mut Context) {
+        let item = self.tree.current_item();
+        let manifests = &self.config.manifests;
+        let name = item.path.file_name().and_then(|name| name.to_str());
+        let is_manifest = name.map_or(false, |name| manifests.iter().any(|m| m == name));
+        let start = match item.file_type {
+            FileType::Dir | FileType::Root if !is_manifest => Some(item.path.as_path()),
+            _ => item.path.parent(),
+        };
+        let found = start.into_iter().flat_map(Path::ancestors).find_map(|dir| {
+            manifests
+                .iter()
+                .map(|name| dir.join(name))
+                .find(|path| path.exists())
+        });
+        let path = match found {
+            Some(path) => path,
+            None => return cx.editor.set_error("No project manifest found"),
+        };
+        self.reveal_file(&path, cx);
+        let shown = helix_core::path::get_relative_path(&path);
+        if self.tree.current_item().path == path {
+            cx.editor.set_status(format!("{}", shown.display()));
+        } else {
+            cx.editor
+                .set_status(format!("{} is hidden in the explorer", shown.display()));
+        }
+    }
+
+    /// Shows the parent of the root.
+    pub fn go_to_parent(&mut self, cx: &mut Context) {
+        if let Some(parent) = self.state.current_root.parent() {
+            self.set_root(parent.to_path_buf(), cx);
+        }
+    }
+
+    /// Shows the directory at `root`, as the root of the tab page.
+    pub fn set_root(&mut self, root: PathBuf, cx: &mut Context) {
+        match Self::get_items(root.clone(), cx, &mut self.state) {
+            Ok(items) => {
+                self.state.current_root = root.clone();
+                cx.editor.tree.tab_mut().explorer_root = Some(root);
+                self.tree = Tree::build_tree(items).with_enter_fn(Self::toggle_current);
+            }
+            Err(e) => cx.editor.set_error(format!("{e}")),
+        }
+    }
+
+    /// Locks the explorer against changing the files, or unlocks it, until the editor exits.
+    pub fn toggle_lock(editor: &mut Editor) {
+        editor.explorer_locked = !editor.explorer_locked;
+        if editor.explorer_locked {
+            editor.set_status("Explorer locked");
+        } else {
+            editor.set_status("Explorer unlocked");
+        }
+    }
+
+    /// Shows or hides the hidden files, changing `show-hidden` like `:set` does.
+    pub fn toggle_hidden(editor: &mut Editor) {
+        let mut config = (*editor.config()).clone();
+        config.explorer.show_hidden = !config.explorer.show_hidden;
+        if let Err(err) = editor
+            .config_events
+            .0
+            .send(ConfigEvent::Update(Box::new(config)))
+        {
+            editor.set_error(err.to_string());
+        }
+    }
+
+    /// Activates the filter preset of the config after the active one by name, or none
+    /// after the last one, listing the directories again.
+    pub fn cycle_filter_preset(&mut self, cx: &mut Context) {
+        let next = {
+            let presets = &cx.editor.config().explorer.filter_presets;
+            if presets.is_empty() {
+                let status = "No explorer filter presets in the config";
+                cx.editor.set_status(status);
+                return;
+            }
+            let mut names = presets.keys();
+            let next = match &self.state.preset {
+                Some(active) => names.skip_while(|name| *name != active).nth(1),
+                None => names.next(),
+            };
+            next.cloned()
+        };
+        match &next {
+            Some(name) => cx.editor.set_status(format!("Filter preset: {}", name)),
+            None => cx.editor.set_status("Filter preset off"),
+        }
+        self.state.preset = next;
+        self.refresh(cx);
+    }
+
+    /// Expands the selected directory and its subdirectories `depth` levels deep, as `zE`
+    /// after a count does.
+    pub fn expand_to_depth(&mut self, depth: usize, cx: &mut Context) {
+        self.tree.expand_to_depth(depth, cx, &mut self.state);
+    }
+
+    /// Lists only the broken symbolic links, with all the directories, or all the files
+    /// again.
+    pub fn toggle_broken_links(&mut self, cx: &mut Context) {
+        self.toggle_only(Only::BrokenLinks, cx);
+    }
+
+    /// Lists only the files the repository does not track, with the directories holding
+    /// them, or all the files again.
+    pub fn toggle_untracked(&mut self, cx: &mut Context) {
+        self.toggle_only(Only::Untracked, cx);
+    }
+
+    /// Lists only the files with uncommitted changes, with the directories holding them,
+    /// or all the files again.
+    pub fn toggle_modified(&mut self, cx: &mut Context) {
+        self.toggle_only(Only::Modified, cx);
+    }
+
+    fn toggle_only(&mut self, only: Only, cx: &mut Context) {
+        let status = &cx.editor.repo_status;
+        if only != Only::BrokenLinks && status.info.is_none() {
+            return cx.editor.set_error("Not in a repository");
+        }
+        let only = (self.state.only.only != Some(only)).then(|| only);
+        self.state.only = OnlyFilter::new(only, status);
+        match only {
+            Some(only) => cx
+                .editor
+                .set_status(format!("Listing the {} only", only.name())),
+            None => cx.editor.set_status("Listing all the files"),
+        }
+        self.refresh(cx);
+        // the changes are looked through at once
+        let root = &self.state.current_root;
+        let mut dirs: Vec<_> = self
+            .state
+            .only
+            .dirs
+            .iter()
+            .filter(|dir| dir.starts_with(root) && *dir != root)
+            .cloned()
+            .collect();
+        dirs.sort();
+        self.expand(&dirs, cx);
+    }
+
+    /// Shows the `actions` of the config and runs the one whose key is pressed next on
+    /// the selected item.
+    pub fn open_actions_menu(&mut self, cx: &mut Context) {
+        if is_locked(cx.editor) {
+            return;
+        }
+        let actions = cx.editor.config().explorer.actions.clone();
+        if actions.is_empty() {
+            cx.editor.set_status("No explorer actions in the config");
+            return;
+        }
+        let body: Vec<_> = actions.iter().collect();
+        self.menu = Some(Info::new("Actions", &body));
+        self.on_next_key = Some(Box::new(move |cx, explorer, event| {
+            explorer.menu = None;
+            let action = actions
+                .iter()
+                .find(|(key, _)| key.parse::<KeyEvent>().map_or(false, |key| key == event));
+            match action {
+                Some((_, template)) => explorer.run_action(template, cx),
+                None => return EventResult::Ignored(None),
+            }
+            EventResult::Consumed(None)
+        }));
+    }
+
+    /// Runs the shell command of an action in the background, listing the directories
+    /// again once it is done.
+    fn run_action(&self, template: &str, cx: &mut Context) {
+        let item = self.tree.current_item();
+        if !matches!(
+            item.file_type,
+            FileType::File
+                | FileType::Exe
+                | FileType::Dir
+                | FileType::Conflicted
+                | FileType::PinnedFile
+                | FileType::RecentFile
+        ) {
+            return;
+        }
+        let command = expand_action(template, &item.path, item.file_type == FileType::Dir);
+        let shell = cx.editor.config().shell.clone();
+        cx.jobs.callback(async move {
+            let result = commands::shell_impl_async(&shell, &command, None).await;
+            let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+                match result {
+                    Ok((output, _)) => match output.trim().lines().last() {
+                        Some(line) => editor.set_status(line.to_string()),
+                        None => editor.set_status(format!("`{}` succeeded", command)),
+                    },
+                    Err(err) => editor.set_error(err.to_string()),
+                }
+                if let Some(editor_view) = compositor.find::<ui::EditorView>() {
+                    editor_view.explorer_command(ui::ExplorerCommand::Refresh);
+                }
+            };
+            Ok(crate::job::Callback::EditorCompositor(Box::new(call)))
+        });
+    }
+
+    /// Selects the file of the focused buffer once another buffer is focused.
+    fn follow(&mut self, cx: &mut Context) {
+        let path = doc!(cx.editor).path().cloned();
+        if path == self.followed {
+            return;
+        }
+        self.followed = path.clone();
+        if let Some(path) = path {
+            self.reveal(&path, cx);
+        }
+    }
+
+    /// Adds the entries read by the scan since the last call to the tree.
+    /// Adds the entries read by the scan to the tree, at most once per
+    /// [`SCAN_MERGE_INTERVAL`] as adding them rebuilds the tree.
+    fn poll_scan(&mut self) {
+        let scan = match &mut self.scan {
+            Some(scan) => scan,
+            None => return,
+        };
+        let done = loop {
+            match scan.entries.try_recv() {
+                Ok(item) => scan.add(item),
+                Err(TryRecvError::Empty) => break false,
+                Err(TryRecvError::Disconnected) => break true,
+            }
+        };
+        let elapsed = scan.merged.elapsed();
+        if !done && elapsed < SCAN_MERGE_INTERVAL {
+            // the entries arriving until then are added along
+            if !scan.pending.is_empty() && !scan.wakeup {
+                scan.wakeup = true;
+                let redraw = scan.redraw.clone();
+                tokio::spawn(async move {
+                    tokio::time::sleep(SCAN_MERGE_INTERVAL - elapsed).await;
+                    redraw.notify_one();
+                });
+            }
+            return;
+        }
+
+        let mut items = std::mem::take(&mut scan.pending);
+        scan.merged = Instant::now();
+        scan.wakeup = false;
+        if done {
+            // the empty directories hold a placeholder, like when they are expanded
+            items.extend(
+                scan.dirs
+                    .iter()
+                    .filter(|dir| !scan.parents.contains(*dir))
+                    .map(|dir| FileInfo::new(dir.clone(), FileType::Placeholder)),
+            );
+            self.scan = None;
+        }
+        if !items.is_empty() {
+            self.tree.extend(items);
+        }
+    }
+
+    /// The root and the expanded directories, to be restored with [Self::expand].
+    pub fn session(&self) -> ExplorerSession {
+        ExplorerSession {
+            root: self.state.current_root.clone(),
+            expanded: self
+                .tree
+                .expanded_items()
+                .filter(|item| item.file_type == FileType::Dir)
+                .map(|item| item.path.clone())
+                .collect(),
+            sorts: self.state.sorts.clone(),
+        }
+    }
+
+    /// Orders the directories in `sorts` by their own sort, listing them again.
+    pub fn set_sorts(&mut self, sorts: BTreeMap<PathBuf, ExplorerSort>, cx: &mut Context) {
+        self.state.sorts = sorts;
+        self.refresh(cx);
+    }
+
+    /// Orders the directory of the selected item by the sort after its current one, the
+    /// root for the root itself, listing it again.
+    pub fn cycle_sort(&mut self, cx: &mut Context) {
+        let item = self.tree.current_item();
+        let dir = match item.file_type {
+            FileType::Root => item.path.clone(),
+            FileType::File | FileType::Exe | FileType::Dir | FileType::Placeholder => {
+                match item.path.parent() {
+                    Some(dir) => dir.to_path_buf(),
+                    None => return,
+                }
+            }
+            _ => return,
+        };
+        let sort = match self.state.sort(&dir, &self.config) {
+            ExplorerSort::DirsFirst => ExplorerSort::Name,
+            ExplorerSort::Name => ExplorerSort::Modified,
+            ExplorerSort::Modified => ExplorerSort::DirsFirst,
+        };
+        let order = match sort {
+            ExplorerSort::DirsFirst => "directories first",
+            ExplorerSort::Name => "by name",
+            ExplorerSort::Modified => "by modification time",
+        };
+        let name = dir
+            .file_name()
+            .map_or_else(|| dir.to_string_lossy(), |name| name.to_string_lossy());
+        cx.editor.set_status(format!("Sorted {}: {}", name, order));
+        if sort == self.config.sort {
+            self.state.sorts.remove(&dir);
+        } else {
+            self.state.sorts.insert(dir, sort);
+        }
+        self.refresh(cx);
+    }
+
+    /// Expands the directories at `paths`, given parents first.
+    pub fn expand(&mut self, paths: &[PathBuf], cx: &mut Context) {
+        // opening a directory replaces the list
+        if cx.editor.config().explorer.is_list() {
+            return;
+        }
+        for path in paths {
+            if self
+                .tree
+                .select(|item| item.file_type == FileType::Dir && item.path == *path)
+            {
+                self.tree.expand_current(cx, &mut self.state);
+            }
+        }
+        self.tree.select(|_| true);
+    }
+
+    pub fn focus(&mut self) {
+        self.state.focus = true
+    }
+
+    pub fn unfocus(&mut self) {
+        self.state.focus = false;
+    }
+
+    pub fn is_focus(&self) -> bool {
+        self.state.focus
+    }
+
+    /// Tells the selected item and its state in the statusline for the screen readers, when
+    /// either changed since they were last told.
+    fn announce(&mut self, cx: &mut Context) {
+        let item = self.tree.current_item();
+        let expanded = self.tree.is_current_expanded();
+        let marked = item.is_markable() && self.state.marked.contains(&item.path);
+        let key = (item.path.clone(), item.file_type, expanded, marked);
+        if self.announced.as_ref() != Some(&key) {
+            let announcement = announcement(item, expanded, marked, cx.editor);
+            cx.editor.set_status(announcement);
+            self.announced = Some(key);
+        }
+    }
+
+    /// Marks the item at `index`, or unmarks it if it is marked.
+    fn toggle_mark(&mut self, index: usize) {
+        let item = match self.tree.items().nth(index) {
+            Some(item) if item.is_markable() => item,
+            _ => return,
+        };
+        if !self.state.marked.remove(&item.path) {
+            self.state.marked.insert(item.path.clone());
+        }
+    }
+
+    /// Marks the items between the selected one and the one at `index`, both included.
+    fn mark_range(&mut self, index: usize) {
+        let selected = self.tree.selected_index();
+        let (start, end) = (selected.min(index), selected.max(index));
+        let paths = self
+            .tree
+            .items()
+            .skip(start)
+            .take(end - start + 1)
+            .filter(|item| item.is_markable())
+            .map(|item| item.path.clone());
+        self.state.marked.extend(paths);
+    }
+
+    /// Opens the marked files into buffers, the first one in the focused view, or into a
+    /// grid of splits with `grid`.
+    pub fn open_marked(&mut self, cx: &mut Context, grid: bool) {
+        let paths: Vec<_> = self
+            .state
+            .marked
+            .iter()
+            .filter(|path| path.is_file())
+            .cloned()
+            .collect();
+        if paths.is_empty() {
+            return cx.editor.set_error("No marked files");
+        }
+        let opened = if grid {
+            open_grid(cx.editor, &paths)
+        } else {
+            paths[1..]
+                .iter()
+                .try_for_each(|path| cx.editor.open(path, Action::Load).map(|_| ()))
+                .and_then(|_| cx.editor.open(&paths[0], Action::Replace).map(|_| ()))
+        };
+        match opened {
+            Ok(_) => self.unfocus(),
+            Err(e) => cx.editor.set_error(format!("{e}")),
+        }
+    }
+
+    /// Selects the row under the pointer, focusing the explorer. Returns whether there
+    /// is a row there.
+    pub fn select_at(&mut self, event: &MouseEvent, editor: &Editor) -> bool {
+        let config = editor.config();
+        if !config.mouse.explorer || (!self.is_focus() && !config.explorer.is_embed()) {
+            return false;
+        }
+        match self.tree.index_at(event.row, event.column) {
+            Some(index) => {
+                self.focus();
+                self.tree.select_index(index);
+                true
+            }
+            None => false,
+        }
+    }
+
+    /// Inserts `text` in the open prompt, updating the search or filter as typing would.
+    fn paste(&mut self, text: &str, cx: &mut Context) {
+        let (action, mut prompt) = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return,
+        };
+        prompt.insert_str(text, cx.editor);
+        match action {
+            PromptAction::Search(true) => self.tree.search_next(cx, prompt.line(), &mut self.state),
+            PromptAction::Search(false) => self.tree.search_pre(cx, prompt.line(), &mut self.state),
+            PromptAction::Filter => self.tree.filter(prompt.line(), cx, &mut self.state),
+            _ => {}
+        }
+        self.prompt = Some((action, prompt));
+    }
+
+    /// Middle-clicking the prompt pastes the primary selection into it.
+    /// Clicking a row selects it, opening it if it was already selected; with shift it
+    /// marks the rows up to the selected one, and with ctrl it toggles the mark of the row.
+    fn handle_mouse_event(&mut self, event: &MouseEvent, cx: &mut Context) -> EventResult {
+        let editor_config = cx.editor.config();
+        let paste = editor_config.middle_click_paste
+            && editor_config.mouse.prompt
+            && self.prompt.is_some()
+            && (self.prompt_area.top()..self.prompt_area.bottom()).contains(&event.row)
+            && (self.prompt_area.left()..self.prompt_area.right()).contains(&event.column);
+        let config = &editor_config.explorer;
+        match event.kind {
+            MouseEventKind::Up(MouseButton::Middle) if paste => {
+                let contents = cx
+                    .editor
+                    .clipboard_provider
+                    .get_contents(ClipboardType::Selection);
+                match contents {
+                    Ok(contents) => self.paste(&contents, cx),
+                    Err(err) => cx.editor.set_error(format!("{}", err)),
+                }
+            }
+            // a floating explorer still covers the views with its mouse support turned off
+            _ if !editor_config.mouse.explorer && config.is_embed() => {
+                return EventResult::Ignored(None)
+            }
+            _ if !editor_config.mouse.explorer => (),
+            MouseEventKind::Down(MouseButton::Left) => {
+                let index = match self.tree.index_at(event.row, event.column) {
+                    Some(index) => index,
+                    None if config.is_embed() => {
+                        // clicking the editor leaves the explorer to it
+                        self.unfocus();
+                        return EventResult::Ignored(None);
+                    }
+                    None => return EventResult::Consumed(None),
+                };
+                self.focus();
+                if event.modifiers.contains(KeyModifiers::SHIFT) {
+                    self.mark_range(index);
+                } else if event.modifiers.contains(KeyModifiers::CONTROL) {
+                    self.toggle_mark(index);
+                } else if index == self.tree.selected_index() {
+                    self.tree.on_enter(cx, &mut self.state);
+                    return EventResult::Consumed(None);
+                }
+                self.tree.select_index(index);
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
+                if (self.preview_area.top()..self.preview_area.bottom()).contains(&event.row)
+                    && (self.preview_area.left()..self.preview_area.right())
+                        .contains(&event.column) =>
+            {
+                let rows = if event.kind == MouseEventKind::ScrollDown {
+                    3
+                } else {
+                    -3
+                };
+                self.scroll_preview(rows);
+            }
+            MouseEventKind::ScrollDown | MouseEventKind::ScrollUp
+                if self.tree.index_at(event.row, event.column).is_some() =>
+            {
+                if event.kind == MouseEventKind::ScrollDown {
+                    self.tree.move_down(3);
+                } else {
+                    self.tree.move_up(3);
+                }
+            }
+            _ if config.is_embed() => return EventResult::Ignored(None),
+            _ => (),
+        }
+        EventResult::Consumed(None)
+    }
+
+    /// Shows the two marked files side by side with their differences highlighted.
+    pub fn diff_marked(&mut self, cx: &mut Context) {
+        let paths: Vec<_> = self
+            .state
+            .marked
+            .iter()
+            .filter(|path| path.is_file())
+            .collect();
+        let (before, after) = match paths[..] {
+            [before, after] => (before, after),
+            _ => return cx.editor.set_error("Mark two files to diff them"),
+        };
+        let sources = commands::typed::DiffSource::new(cx.editor, before).and_then(|before| {
+            let after = commands::typed::DiffSource::new(cx.editor, after)?;
+            Ok((before, after))
+        });
+        match sources {
+            Ok((before, after)) => commands::typed::open_diff(cx, Some(before), after),
+            Err(e) => cx.editor.set_error(format!("{e}")),
+        }
+    }
+
+    /// Computes the checksums of the selected file in the background, then shows them with
+    /// its size, type and timestamps.
+    pub fn inspect_current(&mut self, cx: &mut Context) {
+        let item = self.tree.current_item();
+        if !matches!(
+            item.file_type,
+            FileType::File
+                | FileType::Exe
+                | FileType::Conflicted
+                | FileType::PinnedFile
+                | FileType::RecentFile
+        ) {
+            return cx.editor.set_error("Select a file to inspect it");
+        }
+        let path = item.path.clone();
+        let name = helix_core::path::get_relative_path(&path);
+        let name = name.to_string_lossy().into_owned();
+        let language = cx
+            .editor
+            .syn_loader
+            .language_config_for_file_name(&path)
+            .map(|config| config.language_id.clone());
+        cx.editor
+            .set_status(format!("Computing the checksums of {name}"));
+        cx.jobs.callback(async move {
+            let details =
+                tokio::task::spawn_blocking(move || file_details(&path, language)).await?;
+            let call = move |editor: &mut Editor, compositor: &mut Compositor| {
+                let details = match details {
+                    Ok(details) => details,
+                    Err(e) => return editor.set_error(format!("{e}")),
+                };
+                editor.set_status(format!("Checksums of {}", name));
+                let explorer = compositor
+                    .find::<ui::EditorView>()
+                    .and_then(|editor_view| editor_view.explorer.as_mut());
+                if let Some(explorer) = explorer {
+                    explorer.content.show_details(&name, details);
+                }
+            };
+            Ok(crate::job::Callback::EditorCompositor(Box::new(call)))
+        });
+    }
+
+    /// Shows the metadata of the selected file or directory, the ones of the link itself
+    /// for a symbolic link.
+    pub fn show_metadata(&mut self, cx: &mut Context) {
+        let item = self.tree.current_item();
+        if matches!(
+            item.file_type,
+            FileType::Placeholder
+                | FileType::Parent
+                | FileType::Conflicts
+                | FileType::Pinned
+                | FileType::Recent
+        ) {
+            return cx.editor.set_error("Select a file or directory");
+        }
+        match node_metadata(&item.path, &cx.editor.repo_status) {
+            Ok(rows) => {
+                let name = helix_core::path::get_relative_path(&item.path);
+                self.menu = Some(Info::new(&name.to_string_lossy(), &rows));
+                // any key closes it
+                self.on_next_key = Some(Box::new(|_, explorer, _| {
+                    explorer.menu = None;
+                    EventResult::Consumed(None)
+                }));
+            }
+            Err(e) => cx.editor.set_error(format!("{e}")),
+        }
     }
 
-    pub fn unfocus(&mut self) {
-        self.state.focus = false;
+    /// Shows the details of a file, `y` copying its SHA-256 checksum and `Y` its MD5 one.
+    fn show_details(&mut self, name: &str, details: FileDetails) {
+        let mut body = details.rows;
+        body.push(("y".into(), "copy the SHA-256".into()));
+        body.push(("Y".into(), "copy the MD5".into()));
+        self.menu = Some(Info::new(name, &body));
+        let (sha256, md5) = (details.sha256, details.md5);
+        self.on_next_key = Some(Box::new(move |cx, explorer, event| {
+            explorer.menu = None;
+            let (kind, digest) = match event.into() {
+                key!('y') => ("SHA-256", &sha256),
+                shift!('Y') => ("MD5", &md5),
+                _ => return EventResult::Ignored(None),
+            };
+            let copied = cx
+                .editor
+                .clipboard_provider
+                .set_contents(digest.clone(), ClipboardType::Clipboard);
+            match copied {
+                Ok(_) => cx.editor.set_status(format!("Copied the {kind} checksum")),
+                Err(e) => cx.editor.set_error(format!("{e}")),
+            }
+            EventResult::Consumed(None)
+        }));
     }
 
-    pub fn is_focus(&self) -> bool {
-        self.state.focus
+    /// Lists the commits that changed the selected file.
+    fn file_log(&mut self, target: ui::LogTarget) -> EventResult {
+        let item = self.tree.current_item();
+        if !matches!(
+            item.file_type,
+            FileType::File
+                | FileType::Exe
+                | FileType::Conflicted
+                | FileType::PinnedFile
+                | FileType::RecentFile
+        ) {
+            return EventResult::Consumed(None);
+        }
+        let path = item.path.clone();
+        EventResult::Consumed(Some(Box::new(
+            move |compositor: &mut Compositor, cx: &mut Context| {
+                if let Some(picker) = ui::file_log_picker(cx.editor, path, target) {
+                    compositor.push(Box::new(ui::overlay::overlayed(picker)));
+                }
+            },
+        )))
     }
 
-    fn get_items(p: PathBuf, cx: &mut Context) -> Result<Vec<FileInfo>> {
+    fn get_items(p: PathBuf, cx: &mut Context, state: &mut State) -> Result<Vec<FileInfo>> {
         let mut items = vec![FileInfo::parent(p.as_path())];
+        if !state.pinned.is_empty() {
+            items.push(FileInfo::new(p.clone(), FileType::Pinned));
+            items.extend(
+                state
+                    .pinned
+                    .iter()
+                    .map(|path| FileInfo::new(path.clone(), FileType::PinnedFile)),
+            );
+        }
+        // the conflicts are only listed while a merge is in progress
+        let conflicted = cx.editor.conflicted_files(&p);
+        if !conflicted.is_empty() {
+            items.push(FileInfo::new(p.clone(), FileType::Conflicts));
+            items.extend(
+                conflicted
+                    .into_iter()
+                    .map(|path| FileInfo::new(path, FileType::Conflicted)),
+            );
+        }
+        let recent = recent_files(cx.editor, &p);
+        if !recent.is_empty() {
+            items.push(FileInfo::new(p.clone(), FileType::Recent));
+            items.extend(recent.into_iter().enumerate().map(|(rank, path)| FileInfo {
+                rank,
+                ..FileInfo::new(path, FileType::RecentFile)
+            }));
+        }
         let root = FileInfo::root(p);
-        let childs = root.get_childs()?;
+        let config = &cx.editor.config().explorer;
+        let sort = state.sort(&root.path, config);
+        let comparator = cx.editor.config().sort_comparator;
+        let preset = state.preset.as_deref();
+        let only = &state.only;
+        let childs = root.children(config, sort, comparator, preset, only, &mut state.ignores)?;
         if cx.editor.config().explorer.is_tree() {
             items.push(root)
         }
@@ -334,24 +2273,65 @@ fn render_preview(&mut self, area: Rect, surface: &mut Surface, editor: &Editor)
         );
 
         let body_area = area.clip_top(2);
+        let is_image = ImageFormat::from_path(&item.path).is_some() && item.path.is_file();
+        if is_image
+            && self
+                .image_previews
+                .render(&item.path, body_area, surface, editor)
+        {
+            return;
+        }
+        let height = body_area.height as usize;
+        let lines = if item.path.is_dir() {
+            match dir_preview(&item.path, height) {
+                Ok(lines) => Cow::Owned(lines),
+                Err(_) => return,
+            }
+        } else if item.path.is_file() {
+            let cached = matches!(&self.text_preview,
+                Some(preview) if preview.path == item.path && !preview.is_outdated());
+            if !cached {
+                self.text_preview = TextPreview::open(item.path.clone()).ok();
+            }
+            match &mut self.text_preview {
+                Some(preview) if preview.binary => Cow::Owned(vec!["<Binary file>".to_string()]),
+                Some(preview) => {
+                    self.preview_area = body_area;
+                    Cow::Borrowed(preview.lines(height))
+                }
+                None => return,
+            }
+        } else {
+            // opening pipes and devices could block
+            return;
+        };
         let style = editor.theme.get("ui.text");
-        if let Ok(preview_content) = get_preview(&item.path, body_area.height as usize) {
-            preview_content
-                .into_iter()
-                .enumerate()
-                .for_each(|(row, line)| {
-                    surface.set_stringn(
-                        body_area.x,
-                        body_area.y + row as u16,
-                        line,
-                        body_area.width as usize,
-                        style,
-                    );
-                })
+        for (row, line) in lines.iter().enumerate() {
+            surface.set_stringn(
+                body_area.x,
+                body_area.y + row as u16,
+                line,
+                body_area.width as usize,
+                style,
+            );
+        }
+    }
+
+    /// Scrolls the preview of the selected file, if it is shown.
+    fn scroll_preview(&mut self, rows: isize) {
+        let item = self.tree.current_item();
+        if let Some(preview) = &mut self.text_preview {
+            if preview.path == item.path {
+                if rows > 0 {
+                    preview.scroll_down(rows as usize);
+                } else {
+                    preview.scroll_up(rows.unsigned_abs());
+                }
+            }
         }
     }
 
-    fn new_search_prompt(&mut self, search_next: bool) {
+    pub fn new_search_prompt(&mut self, search_next: bool) {
         self.tree.save_view();
         self.prompt = Some((
             PromptAction::Search(search_next),
@@ -359,7 +2339,7 @@ fn new_search_prompt(&mut self, search_next: bool) {
         ))
     }
 
-    fn new_filter_prompt(&mut self) {
+    pub fn new_filter_prompt(&mut self) {
         self.tree.save_view();
         self.prompt = Some((
             PromptAction::Filter,
@@ -367,26 +2347,118 @@ fn new_filter_prompt(&mut self) {
         ))
     }
 
-    fn new_mkdir_prompt(&mut self) {
-        self.prompt = Some((
-            PromptAction::Mkdir,
-            Prompt::new("mkdir: ".into(), None, ui::completers::none, |_, _, _| {}),
-        ));
+    /// The directory to create in: the selected directory, or the one of the selected file.
+    /// With `other`, a directory gets a sibling instead of a child.
+    fn target_dir(&self, other: bool) -> Result<PathBuf> {
+        let item = self.tree.current_item();
+        let dir = match item.file_type {
+            FileType::Placeholder | FileType::Root => Some(item.path.as_path()),
+            FileType::Dir if !other => Some(item.path.as_path()),
+            FileType::File | FileType::Exe if other => bail!("A file can not hold new entries"),
+            FileType::Dir | FileType::File | FileType::Exe => item.path.parent(),
+            _ => bail!("Select a file or directory of the tree"),
+        };
+        dir.map(Path::to_path_buf)
+            .ok_or_else(|| anyhow::anyhow!("can not get parent dir"))
     }
 
-    fn new_create_file_prompt(&mut self) {
+    pub fn new_mkdir_prompt(&mut self, cx: &mut Context, other: bool) {
+        self.new_create_prompt(cx, PromptAction::Mkdir, other)
+    }
+
+    pub fn new_create_file_prompt(&mut self, cx: &mut Context, other: bool) {
+        self.new_create_prompt(cx, PromptAction::CreateFile, other)
+    }
+
+    fn new_create_prompt(&mut self, cx: &mut Context, action: PromptAction, other: bool) {
+        if is_locked(cx.editor) {
+            return;
+        }
+        let dir = match self.target_dir(other) {
+            Ok(dir) => dir,
+            Err(e) => return cx.editor.set_error(format!("{e}")),
+        };
+        let shown = helix_core::path::get_relative_path(&dir);
+        let shown = if shown == Path::new("") {
+            "./".to_string()
+        } else {
+            format!("{}/", shown.display())
+        };
+        let p = match action {
+            PromptAction::Mkdir => format!("mkdir in {shown}: "),
+            _ => format!("create file in {shown}: "),
+        };
+        self.new_dir = dir;
         self.prompt = Some((
-            PromptAction::CreateFile,
-            Prompt::new(
-                "create file: ".into(),
-                None,
-                ui::completers::none,
-                |_, _, _| {},
-            ),
+            action,
+            Prompt::new(p.into(), None, ui::completers::none, |_, _, _| {}),
         ));
     }
 
+    /// Asks for the new target of the selected symbolic link, the current one first.
+    pub fn new_retarget_prompt(&mut self, cx: &mut Context) {
+        if is_locked(cx.editor) {
+            return;
+        }
+        let path = &self.tree.current_item().path;
+        let target = match std::fs::symlink_metadata(path) {
+            Ok(meta) if meta.file_type().is_symlink() => std::fs::read_link(path),
+            _ => return cx.editor.set_error("Select a symbolic link"),
+        };
+        let target = match target {
+            Ok(target) => target,
+            Err(e) => return cx.editor.set_error(format!("{e}")),
+        };
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let p = format!("retarget {name} to: ");
+        let prompt = Prompt::new(p.into(), None, ui::completers::filename, |_, _, _| {})
+            .with_line(target.to_string_lossy().into_owned(), cx.editor);
+        self.prompt = Some((PromptAction::Retarget, prompt));
+    }
+
+    /// Points the selected symbolic link to `target`, replacing it at once. On Windows the
+    /// old link is removed first, since it can't be renamed over.
+    fn retarget_current(&mut self, target: &str, cx: &mut Context) -> Result<()> {
+        ensure!(!target.is_empty(), "The target is empty");
+        let item = self.tree.current_item();
+        let path = item.path.clone();
+        let name = path.file_name().unwrap_or_default().to_string_lossy();
+        let tmp = path.with_file_name(format!(".{name}.retarget"));
+        #[cfg(unix)]
+        std::os::unix::fs::symlink(target, &tmp)?;
+        #[cfg(windows)]
+        match std::fs::metadata(&path) {
+            Ok(meta) if meta.is_dir() => std::os::windows::fs::symlink_dir(target, &tmp)?,
+            _ => std::os::windows::fs::symlink_file(target, &tmp)?,
+        }
+        // the links to directories are directories themselves on Windows
+        #[cfg(windows)]
+        let replaced = std::fs::remove_file(&path)
+            .or_else(|_| std::fs::remove_dir(&path))
+            .and_then(|()| std::fs::rename(&tmp, &path));
+        #[cfg(not(windows))]
+        let replaced = std::fs::rename(&tmp, &path);
+        if let Err(e) = replaced {
+            let _ = std::fs::remove_file(&tmp).or_else(|_| std::fs::remove_dir(&tmp));
+            return Err(e.into());
+        }
+        let meta = std::fs::symlink_metadata(&path)?;
+        let item = FileInfo {
+            broken_link: is_broken_link(&path, &meta),
+            ..item.clone()
+        };
+        cx.editor.set_status(match item.broken_link {
+            true => format!("{name} points to {target}, which does not exist"),
+            false => format!("{name} points to {target}"),
+        });
+        self.tree.replace_current(item);
+        Ok(())
+    }
+
     fn new_remove_file_prompt(&mut self, cx: &mut Context) {
+        if is_locked(cx.editor) {
+            return;
+        }
         let item = self.tree.current_item();
         let check = || {
             ensure!(item.file_type != FileType::Placeholder, "The path is empty");
@@ -411,6 +2483,9 @@ fn new_remove_file_prompt(&mut self, cx: &mut Context) {
     }
 
     fn new_remove_dir_prompt(&mut self, cx: &mut Context) {
+        if is_locked(cx.editor) {
+            return;
+        }
         let item = self.tree.current_item();
         let check = || {
             ensure!(item.file_type != FileType::Placeholder, "The path is empty");
@@ -446,6 +2521,9 @@ fn toggle_current(
         if item.file_type == FileType::Placeholder {
             return TreeOp::Noop;
         }
+        if let FileType::Conflicts | FileType::Pinned | FileType::Recent = item.file_type {
+            return TreeOp::Restore;
+        }
         if item.path == Path::new("") {
             return TreeOp::Noop;
         }
@@ -457,24 +2535,61 @@ fn toggle_current(
             }
         };
         if meta.is_file() {
-            if let Err(e) = cx.editor.open(&item.path, Action::Replace) {
+            // the conflicts are always resolved in a buffer
+            let open = match item.file_type {
+                FileType::Conflicted => ExplorerOpen::Edit,
+                _ => cx.editor.config().explorer.open_with(&item.path),
+            };
+            let opened = match open {
+                ExplorerOpen::Edit => cx.editor.open(&item.path, Action::Replace).map(|_| {
+                    if item.file_type == FileType::Conflicted {
+                        goto_first_conflict(cx.editor);
+                    }
+                    cx.editor
+                        .emit(HookEvent::ExplorerNodeOpened(item.path.clone()));
+                }),
+                ExplorerOpen::PreviewOnly => preview_only(&item.path, cx),
+                ExplorerOpen::External => open_external(&item.path),
+                ExplorerOpen::Hexdump => open_hexdump(&item.path, cx.editor),
+            };
+            if let Err(e) = opened {
                 cx.editor.set_error(format!("{e}"));
             }
-            state.focus = false;
+            if matches!(open, ExplorerOpen::Edit | ExplorerOpen::Hexdump) {
+                state.focus = false;
+            }
             return TreeOp::Noop;
         }
 
         if item.path.is_dir() {
             if cx.editor.config().explorer.is_list() || item.file_type == FileType::Parent {
-                match Self::get_items(item.path.clone(), cx) {
+                match Self::get_items(item.path.clone(), cx, state) {
                     Ok(items) => {
                         state.current_root = item.path.clone();
+                        cx.editor.tree.tab_mut().explorer_root = Some(item.path.clone());
+                        cx.editor
+                            .emit(HookEvent::ExplorerNodeOpened(item.path.clone()));
                         return TreeOp::ReplaceTree(items);
                     }
                     Err(e) => cx.editor.set_error(format!("{e}")),
                 }
             } else {
-                return TreeOp::GetChildsAndInsert;
+                let config = &cx.editor.config().explorer;
+                let sort = state.sort(&item.path, config);
+                let comparator = cx.editor.config().sort_comparator;
+                let preset = state.preset.as_deref();
+                let only = &state.only;
+                match item.children(config, sort, comparator, preset, only, &mut state.ignores) {
+                    Ok(items) => {
+                        cx.editor
+                            .emit(HookEvent::ExplorerNodeOpened(item.path.clone()));
+                        return TreeOp::InsertChild(items);
+                    }
+                    Err(e) => {
+                        cx.editor.set_error(format!("{e}"));
+                        return TreeOp::Noop;
+                    }
+                }
             }
         }
         cx.editor.set_error("unkonw file type");
@@ -493,6 +2608,7 @@ fn render_float(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
             let promp_area =
                 render_block(preview_area.clip_top(area.height), surface, Borders::TOP);
             prompt.render(promp_area, surface, cx);
+            self.prompt_area = promp_area;
             preview_area = area;
         }
         self.render_preview(preview_area, surface, cx.editor);
@@ -552,7 +2668,8 @@ fn render_embed(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context)
         }
 
         if let Some((_, prompt)) = self.prompt.as_mut() {
-            prompt.render_prompt(prompt_area, surface, cx)
+            prompt.render_prompt(prompt_area, surface, cx);
+            self.prompt_area = prompt_area;
         }
     }
 
@@ -645,14 +2762,21 @@ fn handle_prompt_event(&mut self, event: KeyEvent, cx: &mut Context) -> EventRes
         };
         let line = prompt.line();
         match (action, event.into()) {
-            (PromptAction::Mkdir, key!(Enter)) => {
-                if let Err(e) = self.new_path(line, true) {
-                    cx.editor.set_error(format!("{e}"))
+            (PromptAction::Mkdir, key!(Enter)) => match self.new_path(line, true, cx) {
+                Ok(path) => cx.editor.emit(HookEvent::FileCreated(path)),
+                Err(e) => cx.editor.set_error(format!("{e}")),
+            },
+            (PromptAction::CreateFile, key!(Enter)) => match self.new_path(line, false, cx) {
+                Ok(path) => {
+                    // the new file is untracked
+                    cx.editor.vcs_status.changed(vec![path.clone()]);
+                    cx.editor.emit(HookEvent::FileCreated(path));
                 }
-            }
-            (PromptAction::CreateFile, key!(Enter)) => {
-                if let Err(e) = self.new_path(line, false) {
-                    cx.editor.set_error(format!("{e}"))
+                Err(e) => cx.editor.set_error(format!("{e}")),
+            },
+            (PromptAction::Retarget, key!(Enter)) => {
+                if let Err(e) = self.retarget_current(line, cx) {
+                    cx.editor.set_error(format!("{e}"));
                 }
             }
             (PromptAction::RemoveDir, key!(Enter)) => {
@@ -660,6 +2784,8 @@ fn handle_prompt_event(&mut self, event: KeyEvent, cx: &mut Context) -> EventRes
                 if let Err(e) = std::fs::remove_dir_all(&item.path) {
                     cx.editor.set_error(format!("{e}"));
                 } else {
+                    cx.editor.vcs_status.changed(vec![item.path.clone()]);
+                    cx.editor.emit(HookEvent::FileDeleted(item.path.clone()));
                     self.tree.fold_current_child();
                     self.tree.remove_current();
                 }
@@ -670,6 +2796,8 @@ fn handle_prompt_event(&mut self, event: KeyEvent, cx: &mut Context) -> EventRes
                     if let Err(e) = std::fs::remove_file(&item.path) {
                         cx.editor.set_error(format!("{e}"));
                     } else {
+                        cx.editor.vcs_status.changed(vec![item.path.clone()]);
+                        cx.editor.emit(HookEvent::FileDeleted(item.path.clone()));
                         self.tree.remove_current();
                     }
                 }
@@ -683,36 +2811,51 @@ fn handle_prompt_event(&mut self, event: KeyEvent, cx: &mut Context) -> EventRes
         EventResult::Consumed(None)
     }
 
-    fn new_path(&mut self, file_name: &str, is_dir: bool) -> Result<()> {
-        let current = self.tree.current_item();
-        let current_parent = if current.file_type == FileType::Placeholder {
-            &current.path
-        } else {
-            current
-                .path
-                .parent()
-                .ok_or_else(|| anyhow::anyhow!("can not get parent dir"))?
-        };
-        let p = helix_core::path::get_normalized_path(&current_parent.join(file_name));
+    /// Creates a file or directory in the directory of the prompt, returning its path.
+    fn new_path(&mut self, file_name: &str, is_dir: bool, cx: &mut Context) -> Result<PathBuf> {
+        let dir = self.new_dir.clone();
+        let p = helix_core::path::get_normalized_path(&dir.join(file_name));
         match p.parent() {
-            Some(p) if p == current_parent => {}
+            Some(p) if p == dir => {}
             _ => bail!("The file name is not illegal"),
         };
 
-        let f = if is_dir {
+        let file_type = if is_dir {
             std::fs::create_dir(&p)?;
-            FileInfo::new(p, FileType::Dir)
+            FileType::Dir
         } else {
             let mut fd = std::fs::OpenOptions::new();
-            fd.create_new(true).write(true).open(&p)?;
-            FileInfo::new(p, FileType::File)
+            let mut file = fd.create_new(true).write(true).open(&p)?;
+            let dir = self.config.templates.as_deref();
+            if let Some(text) = dir.and_then(|dir| file_template(dir, &p)) {
+                file.write_all(text.as_bytes())?;
+            }
+            FileType::File
         };
+        // ordered like the entries around it
+        let f = FileInfo {
+            sort: self.state.sort(&dir, &self.config),
+            comparator: self.comparator,
+            modified: Some(SystemTime::now()),
+            ..FileInfo::new(p.clone(), file_type)
+        };
+        let current = self.tree.current_item();
         if current.file_type == FileType::Placeholder {
             self.tree.replace_current(f);
-        } else {
+        } else if current.path != dir {
             self.tree.insert_current_level(f);
+        } else {
+            // a directory read for the first time lists the new entry already
+            self.tree.expand_current(cx, &mut self.state);
+            if !self.tree.select(|item| item.path == p) {
+                self.tree.remove_where(|item| {
+                    item.file_type == FileType::Placeholder && item.path == dir
+                });
+                self.tree.insert(f);
+                self.tree.select(|item| item.path == p);
+            }
         }
-        Ok(())
+        Ok(p)
     }
 }
 
@@ -722,6 +2865,14 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
         let key_event = match event {
             Event::Key(event) => event,
             Event::Resize(..) => return EventResult::Consumed(None),
+            Event::Paste(text) if self.is_focus() && self.prompt.is_some() => {
+                self.paste(text, cx);
+                return EventResult::Consumed(None);
+            }
+            // an embedded explorer takes the clicks on its rows even when unfocused
+            Event::Mouse(event) if self.is_focus() || cx.editor.config().explorer.is_embed() => {
+                return self.handle_mouse_event(event, cx);
+            }
             _ => return EventResult::Ignored(None),
         };
         if !self.is_focus() {
@@ -771,30 +2922,79 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
                     self.repeat_motion = Some(repeat_motion);
                 }
             }
-            key!('b') => {
-                if let Some(p) = self.state.current_root.parent() {
-                    match Self::get_items(p.to_path_buf(), cx) {
-                        Ok(items) => {
-                            self.state.current_root = p.to_path_buf();
-                            self.tree = Tree::build_tree(items).with_enter_fn(Self::toggle_current);
-                        }
-                        Err(e) => cx.editor.set_error(format!("{e}")),
-                    }
+            key!('b') => self.go_to_parent(cx),
+            shift!('R') => self.refresh(cx),
+            key!('.') => Self::toggle_hidden(cx.editor),
+            key!('a') => self.open_actions_menu(cx),
+            key!('f') => self.new_filter_prompt(),
+            shift!('F') => self.cycle_filter_preset(cx),
+            key!('s') => self.cycle_sort(cx),
+            key!('p') => self.pin_search_results(cx),
+            shift!('P') => self.clear_pinned(),
+            key!('t') => {
+                // open the terminal in the selected directory, or the one of the selected file
+                let item = self.tree.current_item();
+                let dir = match item.file_type {
+                    FileType::File
+                    | FileType::Exe
+                    | FileType::Conflicted
+                    | FileType::PinnedFile
+                    | FileType::RecentFile => item.path.parent().map(Path::to_path_buf),
+                    _ => Some(item.path.clone()),
+                };
+                if let Some(dir) = dir {
+                    self.unfocus();
+                    return EventResult::Consumed(Some(Box::new(
+                        move |compositor: &mut Compositor, cx: &mut Context| {
+                            if let Some(editor) = compositor.find::<ui::EditorView>() {
+                                editor.open_terminal(&dir, cx.editor);
+                            }
+                        },
+                    )));
                 }
             }
-            key!('f') => self.new_filter_prompt(),
+            ctrl!('e') => self.scroll_preview(1),
+            ctrl!('y') => self.scroll_preview(-1),
+            key!('x') => self.toggle_mark(self.tree.selected_index()),
+            shift!('X') => self.state.marked.clear(),
+            key!('o') => self.open_marked(cx, false),
+            key!('i') => self.inspect_current(cx),
+            shift!('K') => self.show_metadata(cx),
+            shift!('L') => Self::toggle_lock(cx.editor),
+            shift!('B') => self.toggle_broken_links(cx),
+            shift!('M') => self.locate_manifest(cx),
+            key!('u') => self.toggle_untracked(cx),
+            shift!('C') => self.toggle_modified(cx),
+            shift!('O') => self.open_marked(cx, true),
             key!('/') => self.new_search_prompt(true),
             key!('?') => self.new_search_prompt(false),
             key!('m') => {
-                self.on_next_key = Some(Box::new(|_, explorer, event| {
+                self.on_next_key = Some(Box::new(|cx, explorer, event| {
                     match event.into() {
-                        key!('d') => explorer.new_mkdir_prompt(),
-                        key!('f') => explorer.new_create_file_prompt(),
+                        key!('d') => explorer.new_mkdir_prompt(cx, false),
+                        key!('f') => explorer.new_create_file_prompt(cx, false),
+                        shift!('D') => explorer.new_mkdir_prompt(cx, true),
+                        shift!('F') => explorer.new_create_file_prompt(cx, true),
+                        key!('l') => explorer.new_retarget_prompt(cx),
                         _ => return EventResult::Ignored(None),
                     };
                     EventResult::Consumed(None)
                 }));
             }
+            key!('v') => {
+                self.on_next_key = Some(Box::new(|cx, explorer, event| {
+                    let target = match event.into() {
+                        key!('l') => ui::LogTarget::File,
+                        key!('d') => ui::LogTarget::Diff,
+                        key!('m') => {
+                            explorer.diff_marked(cx);
+                            return EventResult::Consumed(None);
+                        }
+                        _ => return EventResult::Ignored(None),
+                    };
+                    explorer.file_log(target)
+                }));
+            }
             key!('r') => {
                 self.on_next_key = Some(Box::new(|cx, explorer, event| {
                     match event.into() {
@@ -819,17 +3019,59 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
             cx.editor.set_error("explorer render area is too small");
             return;
         }
+        if self.tab != cx.editor.tree.tab().id {
+            self.sync_tab(cx);
+        }
+        if self.repo_refreshes != cx.editor.repo_refreshes {
+            // commits may have been made or checked out along
+            self.state.commits.clear();
+            self.refresh_conflicts(cx);
+            if let Some(Only::Untracked | Only::Modified) = self.state.only.only {
+                let only = OnlyFilter::new(self.state.only.only, &cx.editor.repo_status);
+                if only != self.state.only {
+                    self.state.only = only;
+                    self.refresh(cx);
+                }
+            }
+        }
+        self.sync_config(cx);
+        if self.config.follow && !self.is_focus() {
+            self.follow(cx);
+        }
+        // the filter works on the items shown when it was opened
+        if !matches!(self.prompt, Some((PromptAction::Filter, _))) {
+            self.poll_scan();
+        }
         let config = &cx.editor.config().explorer;
+        if config.screen_reader && self.is_focus() && self.prompt.is_none() {
+            self.announce(cx);
+        } else {
+            // told again once back in the tree
+            self.announced = None;
+        }
+        let guides = config.rainbow_guides.then(|| "ui.explorer.guides");
+        self.tree.set_guides_scope(guides);
         if config.is_embed() {
             self.render_embed(area, surface, cx);
         } else {
             self.render_float(area, surface, cx);
         }
+        if let Some(menu) = self.menu.as_mut() {
+            menu.render(area, surface, cx);
+        }
+        // the ones of the rows just shown
+        let redraw = cx.editor.redraw_handle.0.clone();
+        let providers = cx.editor.diff_providers.clone();
+        self.state.commits.read_pending(providers, redraw);
     }
 
     fn cursor(&self, area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
         let prompt = match self.prompt.as_ref() {
             Some((_, prompt)) => prompt,
+            // the screen readers follow the cursor rather than the highlighted row
+            None if editor.config().explorer.screen_reader && self.menu.is_none() => {
+                return (self.tree.selected_position(), CursorKind::Block)
+            }
             None => return (None, CursorKind::Hidden),
         };
         let config = &editor.config().explorer;
@@ -847,37 +3089,532 @@ fn cursor(&self, area: Rect, editor: &Editor) -> (Option<Position>, CursorKind)
     }
 }
 
-fn get_preview(p: impl AsRef<Path>, max_line: usize) -> Result<Vec<String>> {
-    let p = p.as_ref();
-    if p.is_dir() {
-        return Ok(p
-            .read_dir()?
-            .filter_map(|entry| entry.ok())
-            .take(max_line)
-            .map(|entry| {
-                if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
-                    format!("{}/", entry.file_name().to_string_lossy())
-                } else {
-                    format!("{}", entry.file_name().to_string_lossy())
-                }
-            })
-            .collect());
+/// Whether the explorer is locked, which the error then tells.
+fn is_locked(editor: &mut Editor) -> bool {
+    if editor.explorer_locked {
+        editor.set_error("The explorer is locked, `L` unlocks it");
+    }
+    editor.explorer_locked
+}
+
+/// The checksums and metadata of a file, shown by `inspect_current`.
+struct FileDetails {
+    sha256: String,
+    md5: String,
+    /// The rows of the details shown, the checksums first.
+    rows: Vec<(String, String)>,
+}
+
+/// Reads the file at `path` through to compute its checksums, its type being the `language`
+/// detected for it or else whether its contents are binary.
+fn file_details(path: &Path, language: Option<String>) -> std::io::Result<FileDetails> {
+    let meta = std::fs::metadata(path)?;
+    let mut file = File::open(path)?;
+    let (mut sha256, mut md5) = (Sha256::new(), Md5::new());
+    let mut buf = vec![0; 64 * 1024];
+    let mut binary = None;
+    loop {
+        let n = file.read(&mut buf)?;
+        if n == 0 {
+            break;
+        }
+        binary.get_or_insert_with(|| content_inspector::inspect(&buf[..n]) == ContentType::BINARY);
+        sha256.update(&buf[..n]);
+        md5.update(&buf[..n]);
+    }
+    let sha256 = to_hex(&sha256.finalize());
+    let md5 = to_hex(&md5.finalize());
+    let time = format_time;
+    let kind = match language {
+        Some(language) => language,
+        None if binary.unwrap_or(false) => "binary".to_string(),
+        None => "text".to_string(),
+    };
+    let rows = vec![
+        ("SHA-256".to_string(), sha256.clone()),
+        ("MD5".to_string(), md5.clone()),
+        ("Size".to_string(), human_size(meta.len())),
+        ("Type".to_string(), kind),
+        ("Modified".to_string(), time(meta.modified())),
+        ("Accessed".to_string(), time(meta.accessed())),
+        ("Created".to_string(), time(meta.created())),
+    ];
+    Ok(FileDetails { sha256, md5, rows })
+}
+
+fn to_hex(digest: &[u8]) -> String {
+    digest.iter().map(|byte| format!("{byte:02x}")).collect()
+}
+
+/// `time` in the local time zone, as it is unknown on the platforms not recording it.
+fn format_time(time: std::io::Result<SystemTime>) -> String {
+    match time {
+        Ok(time) => {
+            let time = chrono::DateTime::<chrono::Local>::from(time);
+            time.format("%Y-%m-%d %H:%M:%S").to_string()
+        }
+        Err(_) => "unknown".to_string(),
+    }
+}
+
+/// The rows of the metadata of the file or directory at `path` shown by `show_metadata`,
+/// with its state in the repository `status`.
+fn node_metadata(path: &Path, status: &Status) -> std::io::Result<Vec<(String, String)>> {
+    let meta = std::fs::symlink_metadata(path)?;
+    let mut rows = vec![("Path".to_string(), path.display().to_string())];
+    if meta.file_type().is_symlink() {
+        let target = std::fs::read_link(path)?;
+        let mut target = target.display().to_string();
+        if std::fs::metadata(path).is_err() {
+            target.push_str(" (broken)");
+        }
+        rows.push(("Target".to_string(), target));
+    }
+    let size = match meta.is_dir() {
+        true => match std::fs::read_dir(path) {
+            Ok(entries) => format!("{} entries", entries.count()),
+            Err(_) => "unknown".to_string(),
+        },
+        false => human_size(meta.len()),
+    };
+    rows.push(("Size".to_string(), size));
+    rows.push(("Modified".to_string(), format_time(meta.modified())));
+    rows.push(("Accessed".to_string(), format_time(meta.accessed())));
+    rows.push(("Created".to_string(), format_time(meta.created())));
+    rows.push(("Permissions".to_string(), permissions(&meta)));
+    #[cfg(unix)]
+    rows.push(("Owner".to_string(), owner(&meta)));
+    rows.push(("Git".to_string(), vcs_status(path, meta.is_dir(), status)));
+    Ok(rows)
+}
+
+#[cfg(unix)]
+fn permissions(meta: &std::fs::Metadata) -> String {
+    use std::os::unix::fs::PermissionsExt;
+    let mode = meta.permissions().mode();
+    let kind = match meta.file_type() {
+        kind if kind.is_symlink() => 'l',
+        kind if kind.is_dir() => 'd',
+        _ => '-',
+    };
+    let bits: String = (0..9)
+        .map(|i| match mode & (0o400 >> i) {
+            0 => '-',
+            _ => ['r', 'w', 'x'][i % 3],
+        })
+        .collect();
+    format!("{kind}{bits} ({:o})", mode & 0o7777)
+}
+
+#[cfg(not(unix))]
+fn permissions(meta: &std::fs::Metadata) -> String {
+    match meta.permissions().readonly() {
+        true => "read-only".to_string(),
+        false => "read-write".to_string(),
+    }
+}
+
+/// The names of the user and group owning a file, or their ids when they have none.
+#[cfg(unix)]
+fn owner(meta: &std::fs::Metadata) -> String {
+    use std::ffi::CStr;
+    use std::os::unix::fs::MetadataExt;
+    let (uid, gid) = (meta.uid(), meta.gid());
+    let mut buf = vec![0 as libc::c_char; 4096];
+    let mut user = uid.to_string();
+    let mut group = gid.to_string();
+    // SAFETY: the entries point into `buf`, which outlives them
+    unsafe {
+        let mut passwd: libc::passwd = std::mem::zeroed();
+        let mut found = std::ptr::null_mut();
+        let len = buf.len();
+        libc::getpwuid_r(uid, &mut passwd, buf.as_mut_ptr(), len, &mut found);
+        if !found.is_null() {
+            user = CStr::from_ptr(passwd.pw_name)
+                .to_string_lossy()
+                .into_owned();
+        }
+        let mut entry: libc::group = std::mem::zeroed();
+        let mut found = std::ptr::null_mut();
+        libc::getgrgid_r(gid, &mut entry, buf.as_mut_ptr(), len, &mut found);
+        if !found.is_null() {
+            group = CStr::from_ptr(entry.gr_name).to_string_lossy().into_owned();
+        }
+    }
+    format!("{user}:{group}")
+}
+
+/// How the file at `path` differs from the last commit of the repository of `status`, or
+/// how many of the files of the directory do.
+fn vcs_status(path: &Path, is_dir: bool, status: &Status) -> String {
+    match &status.info {
+        Some(info) if path.starts_with(&info.work_dir) => {}
+        _ => return "not in the repository".to_string(),
+    }
+    if is_dir {
+        let changed = status.files.keys().filter(|file| file.starts_with(path));
+        return match changed.count() {
+            0 => "unchanged".to_string(),
+            1 => "1 changed file".to_string(),
+            n => format!("{n} changed files"),
+        };
+    }
+    match status.files.get(path) {
+        Some(status) => format!("{status:?}").to_lowercase(),
+        None => "unchanged".to_string(),
+    }
+}
+
+/// The plain text description of `item` told in the statusline with `screen-reader`, like
+/// `src/, directory, collapsed, 14 children`.
+fn announcement(item: &FileInfo, expanded: bool, marked: bool, editor: &Editor) -> String {
+    let mut name = item.get_text().trim().to_string();
+    if item.file_type == FileType::Dir {
+        name.push('/');
+    }
+    let kind = match (item.file_type, item.special) {
+        _ if item.broken_link => "broken link",
+        (FileType::Dir, _) => "directory",
+        (FileType::Root, _) => "root directory",
+        (FileType::Parent, _) => "parent directory",
+        (FileType::Conflicts | FileType::Pinned | FileType::Recent, _) => "section",
+        (FileType::Placeholder, _) => "empty directory",
+        (_, Some(Special::Socket)) => "socket",
+        (_, Some(Special::Fifo)) => "fifo",
+        (_, Some(Special::Device)) => "device",
+        (FileType::Exe, _) => "executable file",
+        (
+            FileType::File | FileType::Conflicted | FileType::PinnedFile | FileType::RecentFile,
+            _,
+        ) => "file",
+    };
+    let mut parts = vec![name, kind.to_string()];
+    if item.file_type == FileType::Dir {
+        parts.push(if expanded { "expanded" } else { "collapsed" }.to_string());
+        if let Ok(entries) = std::fs::read_dir(&item.path) {
+            parts.push(match entries.count() {
+                1 => "1 child".to_string(),
+                n => format!("{n} children"),
+            });
+        }
+    }
+    if marked {
+        parts.push("marked".to_string());
+    }
+    if let Some(status) = editor.repo_status.files.get(&item.path) {
+        parts.push(format!("{status:?}").to_lowercase());
+    }
+    let modified = editor
+        .document_by_path(&item.path)
+        .map_or(false, |doc| doc.is_modified());
+    if modified {
+        parts.push("unsaved changes".to_string());
+    }
+    parts.join(", ")
+}
+
+/// Opens `paths` into a grid of splits, in columns of about as many rows as there are
+/// columns, the first one in the focused view.
+fn open_grid(editor: &mut Editor, paths: &[PathBuf]) -> Result<()> {
+    let columns = (paths.len() as f64).sqrt().ceil() as usize;
+    let rows = (paths.len() + columns - 1) / columns;
+    let mut heads = Vec::new();
+    for (index, column) in paths.chunks(rows).enumerate() {
+        let action = if index == 0 {
+            Action::Replace
+        } else {
+            Action::VerticalSplit
+        };
+        editor.open(&column[0], action)?;
+        heads.push(view!(editor).id);
+    }
+    for (&head, column) in heads.iter().zip(paths.chunks(rows)) {
+        editor.focus(head);
+        for path in &column[1..] {
+            editor.open(path, Action::HorizontalSplit)?;
+        }
     }
+    editor.focus(heads[0]);
+    Ok(())
+}
+
+/// The shell command of an action run on the file or directory at `path`.
+fn expand_action(template: &str, path: &Path, is_dir: bool) -> String {
+    let quote = |path: &Path| helix_core::shellwords::escape(path.to_string_lossy()).into_owned();
+    let dir = if is_dir { Some(path) } else { path.parent() };
+    let name = path.file_name().map(Path::new);
+    template
+        .replace("%path", &quote(path))
+        .replace("%dir", &dir.map(quote).unwrap_or_default())
+        .replace("%name", &name.map(quote).unwrap_or_default())
+}
+
+/// The text a new file at `path` starts with: the template in `dir` named like the file,
+/// or else like its extension, with `%name`, `%stem`, `%path`, `%module` and `%date`
+/// replaced.
+fn file_template(dir: &Path, path: &Path) -> Option<String> {
+    let dir = helix_loader::config_dir().join(helix_core::path::expand_tilde(dir));
+    let name = path.file_name()?.to_string_lossy();
+    let ext = path.extension().map(|ext| ext.to_string_lossy());
+    let text = std::iter::once(name.clone())
+        .chain(ext)
+        .find_map(|template| std::fs::read_to_string(dir.join(&*template)).ok())?;
+    let relative = helix_core::path::get_relative_path(path);
+    let stem = path.file_stem().unwrap_or_default().to_string_lossy();
+    let module = relative.with_extension("");
+    let date = chrono::Local::now().format("%Y-%m-%d").to_string();
+    let text = text
+        .replace("%name", &name)
+        .replace("%stem", &stem)
+        .replace("%path", &relative.to_string_lossy())
+        .replace("%module", &module.to_string_lossy())
+        .replace("%date", &date);
+    Some(text)
+}
+
+/// Whether the directories are listed differently with the options of `new`.
+fn lists_differently(old: &ExplorerConfig, new: &ExplorerConfig) -> bool {
+    (old.style, old.git_ignore, old.show_hidden, old.sort)
+        != (new.style, new.git_ignore, new.show_hidden, new.sort)
+        || (&old.ignore, &old.filter_presets) != (&new.ignore, &new.filter_presets)
+        || old.ignore_files != new.ignore_files
+}
 
-    ensure!(p.is_file(), "path: {} is not file or dir", p.display());
-    use std::fs::OpenOptions;
-    use std::io::BufRead;
-    let mut fd = OpenOptions::new();
-    fd.read(true);
-    let fd = fd.open(p)?;
-    Ok(std::io::BufReader::new(fd)
-        .lines()
+/// The first `max_line` entries of the directory at `p`.
+fn dir_preview(p: &Path, max_line: usize) -> Result<Vec<String>> {
+    Ok(p.read_dir()?
+        .filter_map(|entry| entry.ok())
         .take(max_line)
-        .filter_map(|line| line.ok())
-        .map(|line| line.replace('\t', "    "))
+        .map(|entry| {
+            if entry.file_type().map(|ft| ft.is_dir()).unwrap_or(false) {
+                format!("{}/", entry.file_name().to_string_lossy())
+            } else {
+                format!("{}", entry.file_name().to_string_lossy())
+            }
+        })
         .collect())
 }
 
+/// The bytes of a file read for its preview at once: first, and then each time the
+/// preview is scrolled past what was read. Longer lines are split.
+const PREVIEW_CHUNK: usize = 64 * 1024;
+
+/// Bytes of a file shown by `hexdump`, the rest is left out.
+const MAX_HEXDUMP: u64 = 1024 * 1024;
+
+/// The files under `root` opened the most and the most lately in the working directory,
+/// which still exist, `explorer.recent` of them at most.
+fn recent_files(editor: &Editor, root: &Path) -> Vec<PathBuf> {
+    let count = editor.config().explorer.recent;
+    let cwd = match std::env::current_dir() {
+        Ok(cwd) => cwd,
+        Err(_) => return Vec::new(),
+    };
+    editor
+        .recent
+        .ranked(&cwd, recent::now())
+        .into_iter()
+        .filter(|path| path.starts_with(root) && path.is_file())
+        .take(count)
+        .map(Path::to_path_buf)
+        .collect()
+}
+
+/// Shows an image in a popup like `:preview-image` does, the other files being
+/// previewed by the explorer itself.
+fn preview_only(path: &Path, cx: &mut Context) -> Result<()> {
+    if ImageFormat::from_path(path).is_none() {
+        let name = helix_core::path::get_relative_path(path);
+        cx.editor
+            .set_status(format!("{} is only previewed", name.display()));
+        return Ok(());
+    }
+    let (path, protocol) = (path.to_path_buf(), cx.editor.image_protocol);
+    cx.jobs.callback(async move {
+        let preview =
+            tokio::task::spawn_blocking(move || ImagePreview::new(path, protocol)).await?;
+        let call = move |editor: &mut Editor, compositor: &mut Compositor| match preview {
+            Ok(preview) => {
+                compositor.replace_or_push(ImagePreview::ID, ui::overlay::overlayed(preview))
+            }
+            Err(e) => editor.set_error(format!("{e}")),
+        };
+        Ok(crate::job::Callback::EditorCompositor(Box::new(call)))
+    });
+    Ok(())
+}
+
+/// Opens the file at `path` with the default application of the system, without waiting
+/// for it to exit.
+fn open_external(path: &Path) -> Result<()> {
+    let mut command = if cfg!(target_os = "macos") {
+        tokio::process::Command::new("open")
+    } else if cfg!(windows) {
+        let mut command = tokio::process::Command::new("cmd");
+        command.args(["/C", "start", ""]);
+        command
+    } else {
+        tokio::process::Command::new("xdg-open")
+    };
+    command
+        .arg(path)
+        .stdin(std::process::Stdio::null())
+        .stdout(std::process::Stdio::null())
+        .stderr(std::process::Stdio::null())
+        .spawn()?;
+    Ok(())
+}
+
+/// Opens the bytes of the file at `path` in a read-only buffer, in lines of 16 with their
+/// offset and the printable ones as text.
+fn open_hexdump(path: &Path, editor: &mut Editor) -> Result<()> {
+    let mut data = Vec::new();
+    File::open(path)?.take(MAX_HEXDUMP).read_to_end(&mut data)?;
+    let mut text = String::new();
+    for (index, line) in data.chunks(16).enumerate() {
+        let mut hex = String::new();
+        for (n, byte) in line.iter().enumerate() {
+            let gap = if n == 8 { "  " } else { " " };
+            hex.push_str(&format!("{gap}{byte:02x}"));
+        }
+        let ascii: String = line
+            .iter()
+            .map(|&byte| match byte {
+                0x20..=0x7e => byte as char,
+                _ => '.',
+            })
+            .collect();
+        text.push_str(&format!("{:08x} {:<49}  |{}|\n", index * 16, hex, ascii));
+    }
+    let mut doc = helix_view::Document::from(helix_core::Rope::from(text), None);
+    doc.set_readonly(true);
+    editor.new_file_from_document(Action::Replace, doc);
+    let name = helix_core::path::get_relative_path(path);
+    match std::fs::metadata(path) {
+        Ok(meta) if meta.len() > MAX_HEXDUMP => editor.set_status(format!(
+            "{}: the first {} shown",
+            name.display(),
+            human_size(MAX_HEXDUMP)
+        )),
+        _ => editor.set_status(name.display().to_string()),
+    }
+    Ok(())
+}
+
+/// The lines at the beginning of the last file previewed. Files are only read as far
+/// as the preview is scrolled, so that selecting a huge file reads a single chunk.
+struct TextPreview {
+    path: PathBuf,
+    /// The modification time of the file when it was opened, to read it again after
+    /// it changed.
+    mtime: Option<SystemTime>,
+    /// `None` once the whole file is read.
+    file: Option<File>,
+    /// Whether the first chunk looks binary, leaving the rest of the file unread.
+    binary: bool,
+    lines: Vec<String>,
+    /// The bytes after the last line break read.
+    partial: Vec<u8>,
+    /// The first line shown.
+    scroll: usize,
+}
+
+impl TextPreview {
+    fn open(path: PathBuf) -> std::io::Result<Self> {
+        let file = File::open(&path)?;
+        let mtime = file.metadata().and_then(|meta| meta.modified()).ok();
+        let mut preview = Self {
+            path,
+            mtime,
+            file: Some(file),
+            binary: false,
+            lines: Vec::new(),
+            partial: Vec::new(),
+            scroll: 0,
+        };
+        preview.read_chunk();
+        Ok(preview)
+    }
+
+    fn is_outdated(&self) -> bool {
+        let mtime = std::fs::metadata(&self.path).and_then(|meta| meta.modified());
+        mtime.ok() != self.mtime
+    }
+
+    fn read_chunk(&mut self) {
+        let file = match &mut self.file {
+            Some(file) => file,
+            None => return,
+        };
+        let mut chunk = Vec::with_capacity(PREVIEW_CHUNK);
+        let read = file.take(PREVIEW_CHUNK as u64).read_to_end(&mut chunk);
+        if self.lines.is_empty() && self.partial.is_empty() {
+            self.binary = content_inspector::inspect(&chunk) == ContentType::BINARY;
+        }
+        if self.binary || !matches!(read, Ok(n) if n > 0) {
+            self.file = None;
+            let rest = std::mem::take(&mut self.partial);
+            if !rest.is_empty() && !self.binary {
+                self.push_line(&rest);
+            }
+            return;
+        }
+
+        self.partial.extend_from_slice(&chunk);
+        let mut start = 0;
+        for (index, _) in chunk.iter().enumerate().filter(|(_, b)| **b == b'\n') {
+            let end = self.partial.len() - chunk.len() + index;
+            let line = self.partial[start..end].to_vec();
+            self.push_line(&line);
+            start = end + 1;
+        }
+        self.partial.drain(..start);
+        if self.partial.len() >= PREVIEW_CHUNK {
+            let line = std::mem::take(&mut self.partial);
+            self.push_line(&line);
+        }
+    }
+
+    fn push_line(&mut self, line: &[u8]) {
+        let line = String::from_utf8_lossy(line);
+        let line = line.strip_suffix('\r').unwrap_or(&line);
+        self.lines.push(line.replace('\t', "    "));
+    }
+
+    /// The lines shown in a preview of `height` rows, reading more of the file if they
+    /// were not read yet.
+    fn lines(&mut self, height: usize) -> &[String] {
+        while self.lines.len() < self.scroll + height && self.file.is_some() {
+            self.read_chunk();
+        }
+        let start = self.scroll.min(self.lines.len());
+        let end = (self.scroll + height).min(self.lines.len());
+        &self.lines[start..end]
+    }
+
+    fn scroll_down(&mut self, rows: usize) {
+        self.scroll += rows;
+        // reading up to the new position is left to the next render
+        if self.file.is_none() {
+            self.scroll = self.scroll.min(self.lines.len().saturating_sub(1));
+        }
+    }
+
+    fn scroll_up(&mut self, rows: usize) {
+        self.scroll = self.scroll.saturating_sub(rows);
+    }
+}
+
+/// Moves the cursor of the current view to the first conflict of its buffer.
+fn goto_first_conflict(editor: &mut Editor) {
+    let (view, doc) = current!(editor);
+    if let Some(conflict) = doc.conflicts().first() {
+        let pos = doc.text().line_to_char(conflict.start);
+        doc.set_selection(view.id, Selection::point(pos));
+        align_view(doc, view, Align::Center);
+    }
+}
+
 fn render_block(area: Rect, surface: &mut Surface, borders: Borders) -> Rect {
     let block = Block::default().borders(borders);
     let inner = block.inner(area);
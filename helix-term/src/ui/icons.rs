@@ -0,0 +1,87 @@
+use std::path::Path;
+
+use helix_view::graphics::{Color, Style};
+use tui::text::Span;
+
+/// A file type glyph, these are Nerd Font code points and need a patched font to display.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Icon {
+    pub glyph: &'static str,
+    pub color: Color,
+}
+
+/// Shorthand for the tables below, `rgb` is a `0xrrggbb` color.
+const fn icon(glyph: &'static str, rgb: u32) -> Icon {
+    Icon {
+        glyph,
+        color: Color::Rgb((rgb >> 16) as u8, (rgb >> 8) as u8, rgb as u8),
+    }
+}
+
+const DEFAULT_FILE: Icon = Icon {
+    glyph: "\u{f15b}",
+    color: Color::Gray,
+};
+
+/// The icon of directories, in the explorer.
+pub const DIRECTORY: Icon = icon("\u{f07b}", 0x519aba);
+
+/// Icons for files matched by their full name, checked before the extension.
+const FILE_NAMES: &[(&str, Icon)] = &[
+    ("Cargo.lock", icon("\u{e7a8}", 0xdea584)),
+    ("Dockerfile", icon("\u{f308}", 0x384d54)),
+    ("Makefile", icon("\u{e779}", 0x6d8086)),
+    (".gitignore", icon("\u{e702}", 0xf1502f)),
+    (".gitmodules", icon("\u{e702}", 0xf1502f)),
+];
+
+const EXTENSIONS: &[(&str, Icon)] = &[
+    ("rs", icon("\u{e7a8}", 0xdea584)),
+    ("toml", icon("\u{e615}", 0x6d8086)),
+    ("md", icon("\u{e609}", 0x519aba)),
+    ("json", icon("\u{e60b}", 0xcbcb41)),
+    ("yaml", icon("\u{e615}", 0x6d8086)),
+    ("yml", icon("\u{e615}", 0x6d8086)),
+    ("py", icon("\u{e606}", 0xffbc03)),
+    ("js", icon("\u{e74e}", 0xcbcb41)),
+    ("ts", icon("\u{e628}", 0x519aba)),
+    ("html", icon("\u{e736}", 0xe44d26)),
+    ("css", icon("\u{e749}", 0x42a5f5)),
+    ("go", icon("\u{e627}", 0x519aba)),
+    ("c", icon("\u{e61e}", 0x599eff)),
+    ("h", icon("\u{f0fd}", 0xa074c4)),
+    ("cpp", icon("\u{e61d}", 0xf34b7d)),
+    ("java", icon("\u{e738}", 0xcc3e44)),
+    ("lua", icon("\u{e620}", 0x51a0cf)),
+    ("nix", icon("\u{f313}", 0x7ebae4)),
+    ("sh", icon("\u{f489}", 0x4d5a5e)),
+    ("lock", icon("\u{f023}", 0xbbbbbb)),
+    ("txt", icon("\u{f15c}", 0x89e051)),
+];
+
+/// Returns the icon for a file, `None` is a buffer without a path.
+pub fn file_icon(path: Option<&Path>) -> Icon {
+    let path = match path {
+        Some(path) => path,
+        None => return DEFAULT_FILE,
+    };
+    let file_name = path.file_name().and_then(|name| name.to_str());
+    if let Some((_, icon)) = FILE_NAMES.iter().find(|(name, _)| Some(*name) == file_name) {
+        return *icon;
+    }
+    path.extension()
+        .and_then(|ext| ext.to_str())
+        .and_then(|ext| EXTENSIONS.iter().find(|(e, _)| *e == ext))
+        .map_or(DEFAULT_FILE, |(_, icon)| *icon)
+}
+
+/// The icon of a path shown in a listing, before its name, like in the pickers and in the
+/// completions of the prompts. Paths ending with a separator are directories.
+pub fn path_span(path: &Path) -> Span<'static> {
+    let icon = if path.to_string_lossy().ends_with(std::path::is_separator) {
+        DIRECTORY
+    } else {
+        file_icon(Some(path))
+    };
+    Span::styled(format!("{} ", icon.glyph), Style::default().fg(icon.color))
+}
@@ -0,0 +1,133 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    widgets::{Block, Borders, Widget},
+};
+
+use std::{
+    fs::File,
+    io::{self, Read},
+    path::{Path, PathBuf},
+    sync::Arc,
+};
+
+use helix_view::{
+    graphics::Rect,
+    image::{Image, ImageFormat, ImageProtocol, MAX_IMAGE_SIZE},
+    Theme,
+};
+
+/// A preview of an image file: the image itself when the terminal can draw it,
+/// its format, dimensions and size otherwise.
+pub struct ImagePreview {
+    path: PathBuf,
+    format: ImageFormat,
+    dimensions: Option<(u32, u32)>,
+    /// Size of the file in bytes.
+    len: u64,
+    image: Option<Arc<Image>>,
+}
+
+impl ImagePreview {
+    pub const ID: &'static str = "preview-image";
+
+    /// Reads the image at `path`, or only its header when it cannot be drawn with `protocol`.
+    pub fn new(path: PathBuf, protocol: Option<ImageProtocol>) -> io::Result<Self> {
+        let format = ImageFormat::from_path(&path).ok_or_else(|| {
+            io::Error::new(io::ErrorKind::InvalidInput, "not a supported image format")
+        })?;
+        let mut file = File::open(&path)?;
+        let len = file.metadata()?.len();
+        let protocol =
+            protocol.filter(|protocol| protocol.supports(format) && len <= MAX_IMAGE_SIZE);
+        let mut data = Vec::new();
+        match protocol {
+            Some(_) => file.read_to_end(&mut data)?,
+            // enough for the dimensions
+            None => file.take(32).read_to_end(&mut data)?,
+        };
+        let dimensions = format.dimensions(&data);
+        let image = protocol.map(|protocol| Arc::new(Image::new(protocol, format, data)));
+        Ok(Self {
+            path,
+            format,
+            dimensions,
+            len,
+            image,
+        })
+    }
+
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Draws the image in `area`, or lists its metadata.
+    pub fn render_preview(&self, area: Rect, surface: &mut Surface, theme: &Theme) {
+        if let Some(image) = &self.image {
+            surface.clear(area);
+            surface.set_image(area, image.clone());
+            return;
+        }
+
+        let dimensions = match self.dimensions {
+            Some((width, height)) => format!("{} x {} pixels", width, height),
+            None => "unknown".to_string(),
+        };
+        let lines = [
+            format!("Format: {}", self.format.name()),
+            format!("Dimensions: {}", dimensions),
+            format!("Size: {}", human_size(self.len)),
+        ];
+        let style = theme.get("ui.text");
+        for (y, line) in (area.top()..area.bottom()).zip(lines) {
+            surface.set_stringn(area.x, y, line, area.width as usize, style);
+        }
+    }
+}
+
+/// Formats a number of bytes with a binary unit.
+pub(crate) fn human_size(len: u64) -> String {
+    const UNITS: [&str; 4] = ["KiB", "MiB", "GiB", "TiB"];
+    if len < 1024 {
+        return format!("{} B", len);
+    }
+    let mut size = len as f64 / 1024.0;
+    let mut unit = 0;
+    while size >= 1024.0 && unit + 1 < UNITS.len() {
+        size /= 1024.0;
+        unit += 1;
+    }
+    format!("{:.1} {}", size, UNITS[unit])
+}
+
+impl Component for ImagePreview {
+    fn handle_event(&mut self, event: &Event, _cx: &mut Context) -> EventResult {
+        match event {
+            Event::Key(key!('q') | key!(Esc)) => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                EventResult::Consumed(Some(callback))
+            }
+            Event::Key(_) | Event::Paste(_) => EventResult::Consumed(None),
+            _ => EventResult::Ignored(None),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        surface.clear_with(area, theme.get("ui.popup"));
+        let title = helix_core::path::get_relative_path(&self.path);
+        let block = Block::default()
+            .borders(Borders::ALL)
+            .title(title.to_string_lossy().into_owned());
+        let inner = block.inner(area);
+        block.render(area, surface);
+        self.render_preview(inner, surface, theme);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
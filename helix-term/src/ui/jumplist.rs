@@ -0,0 +1,251 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::cmp::Ordering;
+
+use helix_core::Selection;
+use helix_view::{editor::Action, graphics::Rect, DocumentId, Editor};
+
+use super::{sidebar, Tree, TreeItem};
+
+/// Lines shown above and below the line of the selected jump in the preview.
+const PREVIEW_CONTEXT: usize = 2;
+
+/// A row of the panel: a file of the jumplist by its index in the files, or a jump to it
+/// by its index in the jumplist of the view.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum JumpItem {
+    File(usize),
+    Jump(usize, usize),
+}
+
+impl JumpItem {
+    fn key(&self) -> (usize, Option<usize>) {
+        match *self {
+            Self::File(file) => (file, None),
+            Self::Jump(file, index) => (file, Some(index)),
+        }
+    }
+}
+
+struct Jump {
+    doc_id: DocumentId,
+    selection: Selection,
+    /// 0 indexed line of the primary cursor.
+    line: usize,
+    text: String,
+}
+
+struct Jumps {
+    /// The documents jumped to, in the order of their first jump, with their names.
+    files: Vec<(DocumentId, String)>,
+    /// The jumps of the view, oldest first.
+    jumps: Vec<Option<Jump>>,
+}
+
+impl TreeItem for JumpItem {
+    type Params = Jumps;
+
+    fn text(&self, cx: &mut Context, selected: bool, jumps: &mut Jumps) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        let jump = match *self {
+            Self::File(file) => {
+                let name = jumps.files[file].1.clone();
+                return Spans::from(Span::styled(name, style("ui.text.focus")));
+            }
+            Self::Jump(_, index) => match &jumps.jumps[index] {
+                Some(jump) => jump,
+                None => return Spans::default(),
+            },
+        };
+        Spans::from(vec![
+            Span::styled(format!("{}: ", jump.line + 1), style("ui.linenr")),
+            Span::styled(jump.text.trim().to_string(), style("ui.text")),
+        ])
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::Jump(file, _), Self::File(parent)) if file == parent)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The jumplist of the focused view as a sidebar, the jumps grouped by file with the lines
+/// around the selected one below them, to jump back to any of them or prune it.
+pub struct JumpListPanel {
+    tree: Tree<JumpItem>,
+    jumps: Jumps,
+}
+
+impl JumpListPanel {
+    pub const ID: &'static str = "jumplist";
+
+    pub fn new(editor: &Editor) -> Self {
+        let mut panel = Self {
+            tree: Tree::build_tree(Vec::new()),
+            jumps: Jumps {
+                files: Vec::new(),
+                jumps: Vec::new(),
+            },
+        };
+        panel.refresh(editor);
+        panel
+    }
+
+    /// Lists the jumps of the focused view again, keeping the selected row in place.
+    fn refresh(&mut self, editor: &Editor) {
+        let selected = self.tree.selected_index();
+        let (view, _) = current_ref!(editor);
+        self.jumps.files.clear();
+        self.jumps.jumps.clear();
+        let mut items = Vec::new();
+        for (index, (doc_id, selection)) in view.jumps.iter().enumerate() {
+            // the jumps to a document are removed with it
+            let doc = match editor.documents.get(doc_id) {
+                Some(doc) => doc,
+                None => {
+                    self.jumps.jumps.push(None);
+                    continue;
+                }
+            };
+            let file = match self.jumps.files.iter().position(|(id, _)| id == doc_id) {
+                Some(file) => file,
+                None => {
+                    let name = doc.display_name().into_owned();
+                    self.jumps.files.push((*doc_id, name));
+                    items.push(JumpItem::File(self.jumps.files.len() - 1));
+                    self.jumps.files.len() - 1
+                }
+            };
+            let text = doc.text().slice(..);
+            let line = selection.primary().cursor_line(text);
+            self.jumps.jumps.push(Some(Jump {
+                doc_id: *doc_id,
+                selection: selection.clone(),
+                line,
+                text: text.line(line).to_string(),
+            }));
+            items.push(JumpItem::Jump(file, index));
+        }
+        self.tree.replace_with_new_items(items);
+        if !self.jumps.files.is_empty() {
+            let last = self.tree.items().count() - 1;
+            self.tree.select_index(selected.min(last));
+        }
+    }
+
+    fn current_jump(&self) -> Option<&Jump> {
+        match *self.tree.current_item() {
+            JumpItem::Jump(_, index) => self.jumps.jumps[index].as_ref(),
+            JumpItem::File(_) => None,
+        }
+    }
+
+    /// Moves the focused view to the current jump.
+    fn jump(&self, editor: &mut Editor) -> bool {
+        let jump = match self.current_jump() {
+            Some(jump) => jump,
+            None => return false,
+        };
+        editor.switch(jump.doc_id, Action::Replace);
+        let config = editor.config();
+        let (view, doc) = current!(editor);
+        doc.set_selection(view.id, jump.selection.clone());
+        view.ensure_cursor_in_view_center(doc, config.scrolloff);
+        true
+    }
+
+    /// Removes the current jump from the jumplist, or all the jumps to the current file.
+    fn delete_current(&mut self, editor: &mut Editor) {
+        let view = view_mut!(editor);
+        match *self.tree.current_item() {
+            JumpItem::Jump(_, index) => view.jumps.remove_at(index),
+            JumpItem::File(file) => view.jumps.remove(&self.jumps.files[file].0),
+        }
+        self.refresh(editor);
+    }
+
+    fn render_preview(&self, area: Rect, surface: &mut Surface, editor: &Editor) {
+        let theme = &editor.theme;
+        let jump = match self.current_jump() {
+            Some(jump) => jump,
+            None => return,
+        };
+        let text = match editor.documents.get(&jump.doc_id) {
+            Some(doc) => doc.text().slice(..),
+            None => return,
+        };
+        let first = jump.line.saturating_sub(PREVIEW_CONTEXT);
+        let last = (jump.line + PREVIEW_CONTEXT).min(text.len_lines().saturating_sub(1));
+        for (line, y) in (first..=last).zip(area.top()..area.bottom()) {
+            let (number_style, text_style) = if line == jump.line {
+                (theme.get("ui.linenr.selected"), theme.get("ui.text.focus"))
+            } else {
+                (theme.get("ui.linenr"), theme.get("ui.text"))
+            };
+            let content = text.line(line).to_string();
+            let spans = Spans::from(vec![
+                Span::styled(format!("{:>4} ", line + 1), number_style),
+                Span::styled(content.trim_end().replace('\t', " "), text_style),
+            ]);
+            surface.set_spans(area.x, y, &spans, area.width);
+        }
+    }
+}
+
+impl Component for JumpListPanel {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        let close: Callback = Box::new(|compositor, _| {
+            compositor.remove(Self::ID);
+        });
+        match key {
+            key!('q') | key!(Esc) => return EventResult::Consumed(Some(close)),
+            _ if self.jumps.files.is_empty() => {}
+            key!(Enter) if self.jump(cx.editor) => return EventResult::Consumed(Some(close)),
+            key!('d') => self.delete_current(cx.editor),
+            _ => return self.tree.handle_event(Event::Key(key), cx, &mut self.jumps),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let area = sidebar::area(area);
+        let help = "enter: jump  d: delete  q: close";
+        let inner = sidebar::render_frame(area, surface, &cx.editor.theme, "Jumplist", help, true);
+        if self.jumps.files.is_empty() {
+            let style = cx.editor.theme.get("ui.text.inactive");
+            surface.set_stringn(inner.x, inner.y, "No jumps", inner.width as usize, style);
+            return;
+        }
+        let preview_height = (2 * PREVIEW_CONTEXT as u16 + 2).min(inner.height / 2);
+        let tree_area = inner.clip_bottom(preview_height);
+        let preview_area = inner.clip_top(inner.height - preview_height);
+        self.tree.render(tree_area, surface, cx, &mut self.jumps);
+        // a blank row between the jumps and the preview
+        let preview_area = preview_area.clip_top(1);
+        self.render_preview(preview_area, surface, cx.editor);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
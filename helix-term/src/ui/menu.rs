@@ -3,8 +3,13 @@
 use crate::{
     compositor::{Callback, Component, Compositor, Context, Event, EventResult},
     ctrl, key, shift,
+    ui::icons,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+    widgets::Table,
 };
-use tui::{buffer::Buffer as Surface, text::Spans, widgets::Table};
 
 pub use tui::widgets::{Cell, Row};
 
@@ -36,14 +41,25 @@ fn row(&self, data: &Self::Data) -> Row {
 }
 
 impl Item for PathBuf {
-    /// Root prefix to strip.
-    type Data = PathBuf;
+    /// Root prefix to strip, and whether to show the file type icons.
+    type Data = (PathBuf, bool);
+
+    fn label(&self, data: &Self::Data) -> Spans {
+        let path = self.filter_text(data).into_owned();
+        match data.1 {
+            true => Spans::from(vec![icons::path_span(self), Span::raw(path)]),
+            false => path.into(),
+        }
+    }
+
+    fn sort_text(&self, data: &Self::Data) -> Cow<str> {
+        self.filter_text(data)
+    }
 
-    fn label(&self, root_path: &Self::Data) -> Spans {
+    fn filter_text(&self, (root_path, _): &Self::Data) -> Cow<str> {
         self.strip_prefix(root_path)
             .unwrap_or(self)
             .to_string_lossy()
-            .into()
     }
 }
 
@@ -60,6 +76,8 @@ pub struct Menu<T: Item> {
     widths: Vec<Constraint>,
 
     callback_fn: Box<dyn Fn(&mut Editor, Option<&T>, MenuEvent)>,
+    /// Builds a callback run once the menu is closed by choosing an option.
+    validate_fn: Option<Box<dyn Fn(&T) -> Callback>>,
 
     scroll: usize,
     size: (u16, u16),
@@ -86,6 +104,7 @@ pub fn new(
             cursor: None,
             widths: Vec::new(),
             callback_fn: Box::new(callback_fn),
+            validate_fn: None,
             scroll: 0,
             size: (0, 0),
             viewport: (0, 0),
@@ -93,6 +112,13 @@ pub fn new(
         }
     }
 
+    /// Runs the callback built by `f` from the chosen option once the menu is closed, for
+    /// choices which need the compositor.
+    pub fn with_validate_fn(mut self, f: impl Fn(&T) -> Callback + 'static) -> Self {
+        self.validate_fn = Some(Box::new(f));
+        self
+    }
+
     pub fn score(&mut self, pattern: &str) {
         // reuse the matches allocation
         self.matches.clear();
@@ -270,6 +296,14 @@ fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
             key!(Enter) => {
                 if let Some(selection) = self.selection() {
                     (self.callback_fn)(cx.editor, Some(selection), MenuEvent::Validate);
+                    if let Some(validate_fn) = &self.validate_fn {
+                        let validate = validate_fn(selection);
+                        let callback: Callback = Box::new(|compositor, cx| {
+                            compositor.pop();
+                            validate(compositor, cx);
+                        });
+                        return EventResult::Consumed(Some(callback));
+                    }
                     return EventResult::Consumed(close_fn);
                 } else {
                     return EventResult::Ignored(close_fn);
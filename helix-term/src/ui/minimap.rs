@@ -0,0 +1,161 @@
+use helix_core::{diagnostic::Severity, RopeSlice};
+use helix_view::{
+    theme::Style,
+    view::{MINIMAP_COLUMNS_PER_CELL, MINIMAP_LINES_PER_ROW},
+    Document, Theme, View,
+};
+use tui::buffer::Buffer as Surface;
+
+/// Offset of the braille patterns block, every dot of a pattern is a bit on top of it.
+const BRAILLE_BASE: u32 = 0x2800;
+/// Bits of the left and right column of dots in a braille pattern, from top to bottom.
+const BRAILLE_DOTS: [[u32; MINIMAP_LINES_PER_ROW]; 2] =
+    [[0x01, 0x02, 0x04, 0x40], [0x08, 0x10, 0x20, 0x80]];
+
+/// Marks mirrored from the gutter onto the minimap, ordered by priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Mark {
+    DiffModified,
+    DiffRemoved,
+    DiffAdded,
+    Diagnostic(Severity),
+}
+
+/// Renders a compressed overview of `doc` along the right edge of `view`.
+/// Every cell is a braille character with one dot for each two columns of
+/// one line of text.
+pub fn render(doc: &Document, view: &View, surface: &mut Surface, theme: &Theme) {
+    let area = match view.minimap_area() {
+        Some(area) if area.width > 0 && area.height > 0 => area,
+        _ => return,
+    };
+
+    let text = doc.text().slice(..);
+    let first_line = view.minimap_first_line(doc);
+    let last_line = (first_line + area.height as usize * MINIMAP_LINES_PER_ROW)
+        .min(text.len_lines())
+        .saturating_sub(1);
+    let marks = line_marks(doc, first_line, last_line);
+
+    let base_style = theme.get("ui.minimap");
+    let viewport_style = base_style.patch(
+        theme
+            .try_get("ui.minimap.viewport")
+            .unwrap_or_else(|| theme.get("ui.selection")),
+    );
+    surface.clear_with(area, base_style);
+
+    let view_start = view.offset.row;
+    let view_end = view.last_line(doc);
+    let dot_width = MINIMAP_COLUMNS_PER_CELL / 2;
+    let mut dots = vec![0u32; area.width as usize];
+
+    for row in 0..area.height {
+        let row_start = first_line + row as usize * MINIMAP_LINES_PER_ROW;
+        if row_start > last_line {
+            break;
+        }
+        let row_end = (row_start + MINIMAP_LINES_PER_ROW - 1).min(last_line);
+
+        dots.fill(0);
+        for (dot_row, line) in (row_start..=row_end).enumerate() {
+            for column in occupied_columns(text.line(line), doc.tab_width()) {
+                let cell = column / MINIMAP_COLUMNS_PER_CELL;
+                if cell >= dots.len() {
+                    break;
+                }
+                let dot_col = (column % MINIMAP_COLUMNS_PER_CELL) / dot_width;
+                dots[cell] |= BRAILLE_DOTS[dot_col][dot_row];
+            }
+        }
+
+        let mut style = if row_start <= view_end && row_end >= view_start {
+            viewport_style
+        } else {
+            base_style
+        };
+        if let Some(mark) = marks[row_start - first_line..=row_end - first_line]
+            .iter()
+            .flatten()
+            .max()
+        {
+            style = style.patch(mark_style(*mark, theme));
+        }
+
+        let y = area.y + row;
+        for (i, cell) in dots.iter().enumerate() {
+            let symbol = char::from_u32(BRAILLE_BASE + cell).unwrap_or(' ');
+            surface[(area.x + i as u16, y)]
+                .set_char(symbol)
+                .set_style(style);
+        }
+    }
+}
+
+/// Returns the visual columns of `line` which contain non-whitespace characters.
+fn occupied_columns(line: RopeSlice, tab_width: usize) -> impl Iterator<Item = usize> + '_ {
+    let mut column = 0;
+    line.chars().filter_map(move |ch| {
+        let current = column;
+        column += if ch == '\t' {
+            tab_width - (column % tab_width)
+        } else {
+            1
+        };
+        if ch.is_whitespace() {
+            None
+        } else {
+            Some(current)
+        }
+    })
+}
+
+/// Collects the diagnostics and diff marks for the lines from `start` to `end` (inclusive).
+fn line_marks(doc: &Document, start: usize, end: usize) -> Vec<Option<Mark>> {
+    let mut marks = vec![None; end + 1 - start];
+    let mut mark_line = |line: usize, mark: Mark| {
+        if (start..=end).contains(&line) {
+            let slot = &mut marks[line - start];
+            *slot = (*slot).max(Some(mark));
+        }
+    };
+
+    if let Some(diff_handle) = doc.diff_handle() {
+        let hunks = diff_handle.hunks();
+        for i in 0..hunks.len() {
+            let hunk = hunks.nth_hunk(i);
+            if hunk.is_pure_removal() {
+                mark_line(hunk.after.start as usize, Mark::DiffRemoved);
+                continue;
+            }
+            let mark = if hunk.is_pure_insertion() {
+                Mark::DiffAdded
+            } else {
+                Mark::DiffModified
+            };
+            for line in hunk.after.start..hunk.after.end {
+                mark_line(line as usize, mark);
+            }
+        }
+    }
+
+    for diagnostic in doc.diagnostics() {
+        let severity = diagnostic.severity.unwrap_or(Severity::Hint);
+        mark_line(diagnostic.line, Mark::Diagnostic(severity));
+    }
+
+    marks
+}
+
+fn mark_style(mark: Mark, theme: &Theme) -> Style {
+    let scope = match mark {
+        Mark::DiffAdded => "diff.plus",
+        Mark::DiffRemoved => "diff.minus",
+        Mark::DiffModified => "diff.delta",
+        Mark::Diagnostic(Severity::Error) => "error",
+        Mark::Diagnostic(Severity::Warning) => "warning",
+        Mark::Diagnostic(Severity::Info) => "info",
+        Mark::Diagnostic(Severity::Hint) => "hint",
+    };
+    theme.get(scope)
+}
@@ -1,37 +1,84 @@
+mod blame;
+pub mod breadcrumbs;
+mod buffer_tree;
+mod call_hierarchy;
+mod color_swatch;
 mod completion;
+mod conflict;
+mod dap_variables;
+mod dashboard;
+mod diff;
 pub(crate) mod editor;
+mod eol_diagnostics;
 mod explorer;
 mod fuzzy_match;
+pub(crate) mod icons;
+mod image;
 mod info;
+mod jumplist;
 pub mod lsp;
 mod markdown;
 pub mod menu;
+mod minimap;
+mod outline;
 pub mod overlay;
 mod picker;
 pub mod popup;
 mod prompt;
+mod registers;
+mod scrollbar;
+mod search_replace;
+mod sidebar;
 mod spinner;
 mod statusline;
+mod terminal;
 mod text;
 mod tree;
+mod undo_tree;
+mod workspace_edit;
+mod workspace_symbols;
 
 use crate::compositor::{Component, Compositor};
 use crate::job::{self, Callback};
+pub use blame::{file_log_picker, BlameInfo, LogTarget};
+pub use buffer_tree::BufferTree;
+pub use call_hierarchy::CallHierarchy;
 pub use completion::Completion;
-pub use editor::EditorView;
-pub use explorer::Explorer;
+pub use dap_variables::DebugVariables;
+pub use dashboard::Dashboard;
+pub use diff::{DiffSide, DiffView};
+pub use editor::{EditorView, MOUSE_HOVER_ID};
+pub use explorer::{Explorer, ExplorerCommand};
+pub use image::ImagePreview;
+pub use jumplist::JumpListPanel;
 pub use markdown::Markdown;
+pub use outline::DocumentOutline;
 pub use menu::Menu;
 pub use picker::{DynamicPicker, FileLocation, FilePicker, Picker};
 pub use popup::Popup;
 pub use prompt::{Prompt, PromptEvent};
+pub use registers::RegisterPanel;
+pub use search_replace::SearchReplace;
+pub use sidebar::TreePanel;
 pub use spinner::{ProgressSpinners, Spinner};
-pub use text::Text;
+pub use terminal::Terminal;
+pub use text::{one_line, Text};
 pub use tree::{Tree,TreeItem, TreeOp};
+pub use undo_tree::UndoTree;
+pub use workspace_edit::WorkspaceEditPreview;
+pub use workspace_symbols::WorkspaceSymbols;
 
 use helix_core::regex::Regex;
 use helix_core::regex::RegexBuilder;
-use helix_view::Editor;
+use helix_core::Rope;
+use helix_view::{
+    apply_transaction,
+    document::SCRATCH_BUFFER_NAME,
+    editor::Action,
+    recent,
+    swap::{self, Snapshot},
+    DocumentId, Editor,
+};
 
 use std::path::PathBuf;
 
@@ -160,11 +207,156 @@ pub fn regex_prompt(
     cx.push_layer(Box::new(prompt));
 }
 
-pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePicker<PathBuf> {
+/// Asks what to do with the buffer `doc_id`, whose file was changed outside of the
+/// editor while it has unsaved changes: reload it from the file, keep it as it is or
+/// compare both.
+pub fn file_changed_prompt(editor: &Editor, doc_id: DocumentId) -> Prompt {
+    let name = editor.documents[&doc_id]
+        .relative_path()
+        .map(|path| path.to_string_lossy().into_owned())
+        .unwrap_or_default();
+    let mut prompt = Prompt::new(
+        format!("'{}' changed on disk, reload/keep/diff: ", name).into(),
+        None,
+        |_editor: &Editor, input: &str| {
+            ["reload", "keep", "diff"]
+                .iter()
+                .filter(|choice| choice.starts_with(input))
+                .map(|choice| (0.., (*choice).into()))
+                .collect()
+        },
+        move |cx: &mut crate::compositor::Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate {
+                return;
+            }
+            let result = match input.trim() {
+                "r" | "reload" => cx.editor.reload_document(doc_id),
+                "d" | "diff" => disk_diff(cx, doc_id),
+                // the buffer overwrites the file when it is written
+                _ => Ok(()),
+            };
+            if let Err(err) = result {
+                cx.editor.set_error(format!("{}", err));
+            }
+        },
+    );
+    prompt.recalculate_completion(editor);
+    prompt
+}
+
+/// Shows the changes between the buffer `doc_id` and its file on disk.
+fn disk_diff(cx: &mut crate::compositor::Context, doc_id: DocumentId) -> anyhow::Result<()> {
+    let doc = cx
+        .editor
+        .document(doc_id)
+        .ok_or_else(|| anyhow::anyhow!("The buffer was closed"))?;
+    let path = doc
+        .path()
+        .ok_or_else(|| anyhow::anyhow!("The buffer has no file"))?;
+    let mut file = std::fs::File::open(path)?;
+    let (disk_text, _) = helix_view::document::from_reader(&mut file, Some(doc.encoding()))?;
+
+    let name = helix_core::path::get_relative_path(path);
+    let before = DiffSide {
+        title: format!("{} (buffer)", name.display()),
+        text: doc.text().clone(),
+        tab_width: doc.tab_width(),
+    };
+    let after = DiffSide {
+        title: format!("{} (disk)", name.display()),
+        text: disk_text,
+        tab_width: doc.tab_width(),
+    };
+    let diff = DiffView::new(before, after);
+    if !diff.has_changes() {
+        cx.editor.set_status("No differences");
+        return Ok(());
+    }
+    let callback = async move {
+        let call: job::Callback = Callback::EditorCompositor(Box::new(
+            move |_editor: &mut Editor, compositor: &mut Compositor| {
+                compositor.replace_or_push(DiffView::ID, diff);
+            },
+        ));
+        Ok(call)
+    };
+    cx.jobs.callback(callback);
+    Ok(())
+}
+
+/// Asks what to do with a snapshot of a buffer left by an editor which did not exit:
+/// recover its changes into the buffer or discard them. Cancelling keeps the snapshot
+/// for the next start.
+pub fn swap_recovery_prompt(editor: &Editor, swap_file: PathBuf, snapshot: Snapshot) -> Prompt {
+    let name = match &snapshot.path {
+        Some(path) => helix_core::path::get_relative_path(path)
+            .to_string_lossy()
+            .into_owned(),
+        None => SCRATCH_BUFFER_NAME.to_string(),
+    };
+    let age = snapshot.time.elapsed().map_or(0, |age| age.as_secs() / 60);
+    let mut prompt = Prompt::new(
+        format!(
+            "'{}' has unsaved changes from {} minutes ago, recover/discard: ",
+            name, age
+        )
+        .into(),
+        None,
+        |_editor: &Editor, input: &str| {
+            ["recover", "discard"]
+                .iter()
+                .filter(|choice| choice.starts_with(input))
+                .map(|choice| (0.., (*choice).into()))
+                .collect()
+        },
+        move |cx: &mut crate::compositor::Context, input: &str, event: PromptEvent| {
+            if event != PromptEvent::Validate {
+                return;
+            }
+            let result = match input.trim() {
+                "r" | "recover" => recover_snapshot(cx.editor, &snapshot)
+                    .and_then(|()| swap::remove_snapshot(&swap_file))
+                    .map(|()| {
+                        cx.editor
+                            .set_status(format!("Recovered the unsaved changes of '{}'", name))
+                    }),
+                "d" | "discard" => swap::remove_snapshot(&swap_file),
+                _ => return,
+            };
+            if let Err(err) = result {
+                cx.editor.set_error(format!("{}", err));
+            }
+        },
+    );
+    prompt.recalculate_completion(editor);
+    prompt
+}
+
+/// Opens the buffer of `snapshot` and replaces its text with the one of the snapshot, as
+/// a change which can be undone.
+fn recover_snapshot(editor: &mut Editor, snapshot: &Snapshot) -> anyhow::Result<()> {
+    match &snapshot.path {
+        Some(path) => {
+            editor.open(path, Action::Replace)?;
+        }
+        None => {
+            editor.new_file(Action::Replace);
+        }
+    }
+    let (view, doc) = current!(editor);
+    let text = Rope::from(snapshot.text.as_str());
+    let transaction = helix_core::diff::compare_ropes(doc.text(), &text);
+    apply_transaction(&transaction, doc, view);
+    doc.append_changes_to_history(view);
+    Ok(())
+}
+
+pub fn file_picker(root: PathBuf, editor: &Editor) -> FilePicker<PathBuf> {
     use ignore::{types::TypesBuilder, WalkBuilder};
     use std::time::Instant;
 
     let now = Instant::now();
+    let config = editor.config();
 
     let mut walk_builder = WalkBuilder::new(&root);
     walk_builder
@@ -219,12 +411,24 @@ pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePi
         files.take(MAX).collect()
     };
     files.sort();
+    if config.file_picker.frecency {
+        // the files opened the most and the most lately first, until a query is typed
+        if let Ok(cwd) = std::env::current_dir() {
+            let scores = editor.recent.scores(&cwd, recent::now());
+            if !scores.is_empty() {
+                files.sort_by_cached_key(|path| {
+                    let path = helix_core::path::get_normalized_path(&cwd.join(path));
+                    std::cmp::Reverse(scores.get(path.as_path()).copied().unwrap_or(0))
+                });
+            }
+        }
+    }
 
     log::debug!("file_picker init {:?}", Instant::now().duration_since(now));
 
     FilePicker::new(
         files,
-        root,
+        (root, config.icons),
         move |cx, path: &PathBuf, action| {
             if let Err(e) = cx.editor.open(path, action) {
                 let err = if let Some(err) = e.source() {
@@ -241,10 +445,12 @@ pub fn file_picker(root: PathBuf, config: &helix_view::editor::Config) -> FilePi
 
 pub mod completers {
     use crate::ui::prompt::Completion;
+    use crate::ui::ExplorerCommand;
     use fuzzy_matcher::skim::SkimMatcherV2 as Matcher;
     use fuzzy_matcher::FuzzyMatcher;
     use helix_view::document::SCRATCH_BUFFER_NAME;
     use helix_view::theme;
+    use helix_view::view::ViewOptions;
     use helix_view::{editor::Config, Editor};
     use once_cell::sync::Lazy;
     use std::borrow::Cow;
@@ -252,6 +458,14 @@ pub mod completers {
 
     pub type Completer = fn(&Editor, &str) -> Vec<Completion>;
 
+    /// What the arguments completed by a typable command are, the paths being shown with
+    /// their file type icons.
+    #[derive(Debug, Clone, Copy, PartialEq, Eq)]
+    pub enum CompletionKind {
+        Path,
+        Other,
+    }
+
     pub fn none(_editor: &Editor, _input: &str) -> Vec<Completion> {
         Vec::new()
     }
@@ -354,6 +568,94 @@ pub fn setting(_editor: &Editor, input: &str) -> Vec<Completion> {
             .collect()
     }
 
+    pub fn view_option(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let matcher = Matcher::default();
+
+        let mut matches: Vec<_> = ViewOptions::KEYS
+            .iter()
+            .filter_map(|name| matcher.fuzzy_match(name, input).map(|score| (name, score)))
+            .collect();
+
+        matches.sort_unstable_by_key(|(_name, score)| Reverse(*score));
+        matches
+            .into_iter()
+            .map(|(name, _)| ((0..), Cow::from(*name)))
+            .collect()
+    }
+
+    /// The subcommands of `:explorer` starting with `input`, or the files for its path
+    /// arguments.
+    pub fn explorer(editor: &Editor, input: &str) -> Vec<Completion> {
+        let names: Vec<_> = ExplorerCommand::NAMES
+            .iter()
+            .filter(|name| name.starts_with(input))
+            .map(|name| ((0..), Cow::from(*name)))
+            .collect();
+        if names.is_empty() {
+            filename(editor, input)
+        } else {
+            names
+        }
+    }
+
+    /// The names of the encodings of `https://encoding.spec.whatwg.org`, but the
+    /// `replacement` and `x-user-defined` ones.
+    const ENCODINGS: &[&str] = &[
+        "UTF-8",
+        "UTF-16LE",
+        "UTF-16BE",
+        "IBM866",
+        "ISO-8859-2",
+        "ISO-8859-3",
+        "ISO-8859-4",
+        "ISO-8859-5",
+        "ISO-8859-6",
+        "ISO-8859-7",
+        "ISO-8859-8",
+        "ISO-8859-8-I",
+        "ISO-8859-10",
+        "ISO-8859-13",
+        "ISO-8859-14",
+        "ISO-8859-15",
+        "ISO-8859-16",
+        "KOI8-R",
+        "KOI8-U",
+        "macintosh",
+        "windows-874",
+        "windows-1250",
+        "windows-1251",
+        "windows-1252",
+        "windows-1253",
+        "windows-1254",
+        "windows-1255",
+        "windows-1256",
+        "windows-1257",
+        "windows-1258",
+        "x-mac-cyrillic",
+        "GBK",
+        "gb18030",
+        "Big5",
+        "EUC-JP",
+        "ISO-2022-JP",
+        "Shift_JIS",
+        "EUC-KR",
+    ];
+
+    pub fn encoding(_editor: &Editor, input: &str) -> Vec<Completion> {
+        let matcher = Matcher::default();
+
+        let mut matches: Vec<_> = ENCODINGS
+            .iter()
+            .filter_map(|name| matcher.fuzzy_match(name, input).map(|score| (name, score)))
+            .collect();
+
+        matches.sort_unstable_by_key(|(_name, score)| Reverse(*score));
+        matches
+            .into_iter()
+            .map(|(name, _)| ((0..), Cow::from(*name)))
+            .collect()
+    }
+
     pub fn filename(editor: &Editor, input: &str) -> Vec<Completion> {
         filename_impl(editor, input, |entry| {
             let is_dir = entry.file_type().map_or(false, |entry| entry.is_dir());
@@ -458,7 +760,7 @@ enum FileMatch {
     }
 
     // TODO: we could return an iter/lazy thing so it can fetch as many as it needs.
-    fn filename_impl<F>(_editor: &Editor, input: &str, filter_fn: F) -> Vec<Completion>
+    fn filename_impl<F>(editor: &Editor, input: &str, filter_fn: F) -> Vec<Completion>
     where
         F: Fn(&ignore::DirEntry) -> FileMatch,
     {
@@ -552,8 +854,10 @@ fn filename_impl<F>(_editor: &Editor, input: &str, filter_fn: F) -> Vec<Completi
 
             let range = (input.len().saturating_sub(file_name.len()))..;
 
+            let comparator = editor.config().sort_comparator;
             matches.sort_unstable_by(|(file1, score1), (file2, score2)| {
-                (Reverse(*score1), file1).cmp(&(Reverse(*score2), file2))
+                let ordering = Reverse(*score1).cmp(&Reverse(*score2));
+                ordering.then_with(|| comparator.compare(file1, file2))
             });
 
             files = matches
@@ -563,7 +867,8 @@ fn filename_impl<F>(_editor: &Editor, input: &str, filter_fn: F) -> Vec<Completi
 
             // TODO: complete to longest common match
         } else {
-            files.sort_unstable_by(|(_, path1), (_, path2)| path1.cmp(path2));
+            let comparator = editor.config().sort_comparator;
+            files.sort_unstable_by(|(_, path1), (_, path2)| comparator.compare(path1, path2));
         }
 
         files
@@ -0,0 +1,275 @@
+use crate::commands;
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::cmp::Ordering;
+
+use helix_core::Selection;
+use helix_view::{
+    align_view,
+    document::DocumentSymbol,
+    graphics::{Modifier, Rect},
+    Align, DocumentId, Editor,
+};
+
+use super::{
+    breadcrumbs::symbol_kind_label,
+    sidebar::{self, TreePanel},
+    Tree, TreeItem,
+};
+
+/// A row of the outline: a symbol by its index in the symbols of the document, which are
+/// sorted by position, and the index of the symbol it is nested in.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct OutlineItem {
+    index: usize,
+    parent: Option<usize>,
+}
+
+struct Outline {
+    symbols: Vec<DocumentSymbol>,
+    /// The innermost symbol enclosing the cursor.
+    current: Option<usize>,
+}
+
+impl TreeItem for OutlineItem {
+    type Params = Outline;
+
+    fn text(&self, cx: &mut Context, selected: bool, outline: &mut Outline) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let mut style = theme.get(scope);
+            if outline.current == Some(self.index) {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if selected {
+                style = style.patch(theme.get("ui.menu.selected"));
+            }
+            style
+        };
+        let symbol = &outline.symbols[self.index];
+        let mut spans = Vec::new();
+        if let Some(kind) = symbol_kind_label(symbol.kind) {
+            spans.push(Span::styled(format!("{} ", kind), style("keyword")));
+        }
+        spans.push(Span::styled(symbol.name.clone(), style("ui.text")));
+        Spans::from(spans)
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        self.parent == Some(other.index)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.index.cmp(&other.index)
+    }
+}
+
+/// The items of `symbols`, each of them nested in the innermost symbol containing it.
+fn items(symbols: &[DocumentSymbol]) -> Vec<OutlineItem> {
+    let mut parents: Vec<usize> = Vec::new();
+    symbols
+        .iter()
+        .enumerate()
+        .map(|(index, symbol)| {
+            while let Some(&parent) = parents.last() {
+                if symbols[parent].end > symbol.start {
+                    break;
+                }
+                parents.pop();
+            }
+            let item = OutlineItem {
+                index,
+                parent: parents.last().copied(),
+            };
+            parents.push(index);
+            item
+        })
+        .collect()
+}
+
+/// The symbols of the focused document as a tree next to the views, following the cursor
+/// while the panel isn't focused. The keys go to the views then, running
+/// `document_outline` focuses it again.
+pub struct DocumentOutline {
+    tree: Tree<OutlineItem>,
+    outline: Outline,
+    /// The document of the symbols and the document version they were requested at.
+    shown: Option<(DocumentId, Option<i32>)>,
+    focused: bool,
+    area: Rect,
+}
+
+impl Default for DocumentOutline {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl DocumentOutline {
+    pub const ID: &'static str = "document-outline";
+
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::build_tree(Vec::new()),
+            outline: Outline {
+                symbols: Vec::new(),
+                current: None,
+            },
+            shown: None,
+            focused: true,
+            area: Rect::default(),
+        }
+    }
+
+    /// Shows the symbols of the focused document if they changed, and finds the one
+    /// enclosing the cursor.
+    fn update(&mut self, editor: &Editor) {
+        let (view, doc) = current_ref!(editor);
+        let shown = Some((doc.id(), doc.symbols_version()));
+        if self.shown != shown {
+            self.shown = shown;
+            self.outline.symbols = doc.symbols().to_vec();
+            self.tree.replace_with_new_items(items(doc.symbols()));
+        }
+        let text = doc.text().slice(..);
+        let cursor = doc.selection(view.id).primary().cursor(text);
+        // the symbols change position with the edits until they are requested again
+        let current = doc
+            .symbols()
+            .iter()
+            .enumerate()
+            .filter(|(_, symbol)| symbol.start <= cursor && cursor < symbol.end)
+            .map(|(index, _)| index)
+            .last();
+        if current != self.outline.current {
+            self.outline.current = current;
+            if let (Some(current), false) = (current, self.focused) {
+                self.tree.select(|item| item.index == current);
+            }
+        }
+    }
+
+    /// Moves the cursor to the current symbol and gives the keys back to the views.
+    fn jump(&mut self, editor: &mut Editor) {
+        let index = self.tree.current_item().index;
+        let (view, doc) = current!(editor);
+        let pos = match doc.symbols().get(index) {
+            Some(symbol) => symbol.start.min(doc.text().len_chars()),
+            None => return,
+        };
+        let jump = (doc.id(), doc.selection(view.id).clone());
+        view.jumps.push(jump);
+        doc.set_selection(view.id, Selection::point(pos));
+        align_view(doc, view, Align::Center);
+        self.focused = false;
+    }
+}
+
+impl TreePanel for DocumentOutline {
+    type Item = OutlineItem;
+
+    fn parts(&mut self) -> (&mut Tree<OutlineItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for DocumentOutline {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Mouse(event) => return self.handle_mouse_event(event),
+            Event::IdleTimeout => {
+                // the symbols are only kept up to date for the breadcrumbs otherwise
+                let mut cx = commands::Context {
+                    register: None,
+                    count: None,
+                    editor: cx.editor,
+                    callback: None,
+                    on_next_key_callback: None,
+                    jobs: cx.jobs,
+                };
+                commands::refresh_document_symbols(&mut cx);
+                return EventResult::Ignored(None);
+            }
+            Event::Key(key) if self.focused => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            _ if self.outline.symbols.is_empty() => {}
+            key!(Enter) => self.jump(cx.editor),
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.outline)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.update(cx.editor);
+        self.area = sidebar::area(area);
+        let help = "enter: jump  l: toggle  q: close";
+        let tree_area = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            "Outline",
+            help,
+            self.focused,
+        );
+        if self.outline.symbols.is_empty() {
+            let style = cx.editor.theme.get("ui.text.inactive");
+            surface.set_stringn(
+                tree_area.x,
+                tree_area.y,
+                "No symbols",
+                tree_area.width as usize,
+                style,
+            );
+            return;
+        }
+        self.tree.render(tree_area, surface, cx, &mut self.outline);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helix_lsp::lsp::SymbolKind;
+
+    #[test]
+    fn nesting() {
+        let symbol = |start, end| DocumentSymbol {
+            name: String::new(),
+            kind: SymbolKind::FUNCTION,
+            start,
+            end,
+        };
+        // mod { fn {} struct { field } } fn {}
+        let symbols = [
+            symbol(0, 50),
+            symbol(5, 10),
+            symbol(10, 40),
+            symbol(12, 20),
+            symbol(60, 70),
+        ];
+        let parents: Vec<_> = items(&symbols).iter().map(|item| item.parent).collect();
+        assert_eq!(parents, [None, Some(0), Some(0), Some(2), None]);
+    }
+}
@@ -300,6 +300,9 @@ fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
                 &cx.editor.theme,
                 highlights,
                 &cx.editor.config(),
+                None,
+                None,
+                &[],
             );
 
             // highlight the line
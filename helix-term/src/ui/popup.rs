@@ -6,7 +6,10 @@
 use tui::buffer::Buffer as Surface;
 
 use helix_core::Position;
-use helix_view::graphics::{Margin, Rect};
+use helix_view::{
+    floating::{Anchor, Bias, FloatingWindow, Size},
+    graphics::{Margin, Rect},
+};
 
 // TODO: share logic with Menu, it's essentially Popup(render_fn), but render fn needs to return
 // a width/height hint. maybe Popup(Box<Component>)
@@ -93,38 +96,18 @@ pub fn get_rel_position(&mut self, viewport: Rect, cx: &Context) -> (u16, u16) {
             .position
             .get_or_insert_with(|| cx.editor.cursor().0.unwrap_or_default());
 
-        let (width, height) = self.size;
-
-        // if there's a orientation preference, use that
-        // if we're on the top part of the screen, do below
-        // if we're on the bottom part, do above
-
-        // -- make sure frame doesn't stick out of bounds
-        let mut rel_x = position.col as u16;
-        let mut rel_y = position.row as u16;
-        if viewport.width <= rel_x + width {
-            rel_x = rel_x.saturating_sub((rel_x + width).saturating_sub(viewport.width));
-        }
-
-        let can_put_below = viewport.height > rel_y + height;
-        let can_put_above = rel_y.checked_sub(height).is_some();
-        let final_pos = match self.position_bias {
-            Open::Below => match can_put_below {
-                true => Open::Below,
-                false => Open::Above,
-            },
-            Open::Above => match can_put_above {
-                true => Open::Above,
-                false => Open::Below,
-            },
-        };
-
-        rel_y = match final_pos {
-            Open::Above => rel_y.saturating_sub(height),
-            Open::Below => rel_y + 1,
+        let bias = match self.position_bias {
+            Open::Below => Bias::Below,
+            Open::Above => Bias::Above,
         };
+        let (width, height) = self.size;
+        let window = FloatingWindow::new(
+            Anchor::Position(*position, bias),
+            Size::Fixed(width, height),
+        );
+        let area = window.area(viewport);
 
-        (rel_x, rel_y)
+        (area.x, area.y)
     }
 
     pub fn get_size(&self) -> (u16, u16) {
@@ -2,7 +2,7 @@
 use crate::{alt, ctrl, key, shift, ui};
 use helix_view::input::KeyEvent;
 use helix_view::keyboard::KeyCode;
-use std::{borrow::Cow, ops::RangeFrom};
+use std::{borrow::Cow, ops::RangeFrom, path::Path};
 use tui::buffer::Buffer as Surface;
 use tui::widgets::{Block, Borders, Widget};
 
@@ -28,6 +28,8 @@ pub struct Prompt {
     completion_fn: Box<dyn FnMut(&Editor, &str) -> Vec<Completion>>,
     callback_fn: Box<dyn FnMut(&mut Context, &str, PromptEvent)>,
     pub doc_fn: Box<dyn Fn(&str) -> Option<Cow<str>>>,
+    /// Whether the completions of the input are paths, shown with their file type icons.
+    pub icons_fn: Box<dyn Fn(&str) -> bool>,
     next_char_handler: Option<PromptCharHandler>,
 }
 
@@ -79,6 +81,7 @@ pub fn new(
             completion_fn: Box::new(completion_fn),
             callback_fn: Box::new(callback_fn),
             doc_fn: Box::new(|_| None),
+            icons_fn: Box::new(|_| false),
             next_char_handler: None,
         }
     }
@@ -355,10 +358,13 @@ pub fn render_prompt(&self, area: Rect, surface: &mut Surface, cx: &mut Context)
         let suggestion_color = theme.get("ui.text.inactive");
         // completion
 
+        let show_icons = cx.editor.config().icons && (self.icons_fn)(&self.line);
+        // the glyph and a space
+        let icon_width = if show_icons { 2 } else { 0 };
         let max_len = self
             .completion
             .iter()
-            .map(|(_, completion)| completion.len() as u16)
+            .map(|(_, completion)| completion.len() as u16 + icon_width)
             .max()
             .unwrap_or(BASE_WIDTH)
             .max(BASE_WIDTH);
@@ -401,11 +407,17 @@ pub fn render_prompt(&self, area: Rect, surface: &mut Surface, cx: &mut Context)
                 } else {
                     completion_color
                 };
+                let x = area.x + col * (1 + col_width);
+                if show_icons {
+                    let icon = ui::icons::path_span(Path::new(completion.as_ref()));
+                    let style = color.patch(icon.style);
+                    surface.set_stringn(x, area.y + row, &icon.content, 2, style);
+                }
                 surface.set_stringn(
-                    area.x + col * (1 + col_width),
+                    x + icon_width,
                     area.y + row,
                     completion,
-                    col_width.saturating_sub(1) as usize,
+                    col_width.saturating_sub(1 + icon_width) as usize,
                     color,
                 );
                 row += 1;
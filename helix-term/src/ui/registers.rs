@@ -0,0 +1,269 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::{ctrl, key};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{cmp::Ordering, collections::BTreeMap};
+
+use helix_core::Position;
+use helix_view::{
+    graphics::{CursorKind, Rect},
+    Editor,
+};
+
+use super::{completers, one_line, sidebar, Prompt, Tree, TreeItem};
+
+/// Characters of a value shown, longer ones are cut.
+const MAX_VALUE_LEN: usize = 80;
+
+/// A row of the panel: a register, or one of its values when it holds several, one for
+/// each selection they were yanked from.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum RegisterItem {
+    Register(char),
+    Value(char, usize),
+}
+
+impl RegisterItem {
+    fn key(&self) -> (char, Option<usize>) {
+        match *self {
+            Self::Register(name) => (name, None),
+            Self::Value(name, index) => (name, Some(index)),
+        }
+    }
+}
+
+type Contents = BTreeMap<char, Vec<String>>;
+
+impl TreeItem for RegisterItem {
+    type Params = Contents;
+
+    fn text(&self, cx: &mut Context, selected: bool, contents: &mut Contents) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        match *self {
+            Self::Register(name) => {
+                let values = &contents[&name];
+                let mut spans = vec![Span::styled(format!("{}  ", name), style("ui.text.focus"))];
+                if let Some(value) = values.first() {
+                    spans.push(Span::styled(
+                        one_line(value, MAX_VALUE_LEN),
+                        style("ui.text"),
+                    ));
+                }
+                if values.len() > 1 {
+                    spans.push(Span::styled(
+                        format!("  {} values", values.len()),
+                        style("ui.text.inactive"),
+                    ));
+                }
+                Spans::from(spans)
+            }
+            Self::Value(name, index) => Spans::from(vec![
+                Span::styled(format!("{}: ", index + 1), style("ui.linenr")),
+                Span::styled(
+                    one_line(&contents[&name][index], MAX_VALUE_LEN),
+                    style("ui.text"),
+                ),
+            ]),
+        }
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::Value(name, _), Self::Register(parent)) if name == parent)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The registers and their values as a sidebar, to look into them before using one, edit
+/// or delete them.
+pub struct RegisterPanel {
+    tree: Tree<RegisterItem>,
+    contents: Contents,
+    /// Editing the current value.
+    prompt: Option<Prompt>,
+    prompt_area: Rect,
+}
+
+impl RegisterPanel {
+    pub const ID: &'static str = "registers";
+
+    pub fn new(editor: &Editor) -> Self {
+        let mut panel = Self {
+            tree: Tree::build_tree(Vec::new()),
+            contents: Contents::new(),
+            prompt: None,
+            prompt_area: Rect::default(),
+        };
+        panel.refresh(editor);
+        panel
+    }
+
+    /// Lists the registers again, keeping the current one selected.
+    fn refresh(&mut self, editor: &Editor) {
+        let current = (!self.contents.is_empty()).then(|| self.tree.current_item().key());
+        self.contents = editor
+            .registers
+            .inner()
+            .iter()
+            .filter(|(_, register)| !register.read().is_empty())
+            .map(|(&name, register)| (name, register.read().to_vec()))
+            .collect();
+        let mut items = Vec::new();
+        for (&name, values) in &self.contents {
+            items.push(RegisterItem::Register(name));
+            if values.len() > 1 {
+                items.extend((0..values.len()).map(|index| RegisterItem::Value(name, index)));
+            }
+        }
+        self.tree.replace_with_new_items(items);
+        if let Some((name, index)) = current {
+            if !self.tree.select(|item| item.key() == (name, index)) {
+                self.tree.select(|item| item.key() >= (name, None));
+            }
+        }
+    }
+
+    /// The register and the index of the value of the current item, when it is a single one.
+    fn current_value(&self) -> Option<(char, usize)> {
+        match *self.tree.current_item() {
+            RegisterItem::Register(name) if self.contents[&name].len() == 1 => Some((name, 0)),
+            RegisterItem::Register(_) => None,
+            RegisterItem::Value(name, index) => Some((name, index)),
+        }
+    }
+
+    fn edit_current(&mut self, editor: &mut Editor) {
+        let (name, index) = match self.current_value() {
+            Some(value) => value,
+            None => return editor.set_error("Select one of the values of the register"),
+        };
+        let value = &self.contents[&name][index];
+        if value.contains('\n') {
+            return editor.set_error("Values spanning several lines cannot be edited here");
+        }
+        let prompt = Prompt::new(
+            format!("{} = ", name).into(),
+            None,
+            completers::none,
+            |_, _, _| {},
+        )
+        .with_line(value.clone(), editor);
+        self.prompt = Some(prompt);
+    }
+
+    fn set_current(&mut self, value: String, editor: &mut Editor) {
+        if let Some((name, index)) = self.current_value() {
+            let mut values = self.contents[&name].clone();
+            values[index] = value;
+            editor.registers.write(name, values);
+            self.refresh(editor);
+        }
+    }
+
+    /// Deletes the current register, or the current value of it.
+    fn delete_current(&mut self, editor: &mut Editor) {
+        match *self.tree.current_item() {
+            RegisterItem::Value(name, index) => {
+                let mut values = self.contents[&name].clone();
+                values.remove(index);
+                editor.registers.write(name, values);
+            }
+            RegisterItem::Register(name) => {
+                editor.registers.remove(name);
+            }
+        }
+        self.refresh(editor);
+    }
+
+    fn handle_prompt_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let mut prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return EventResult::Ignored(None),
+        };
+        match event {
+            Event::Key(key!(Enter)) => self.set_current(prompt.line().clone(), cx.editor),
+            Event::Key(key!(Esc) | ctrl!('c')) => {}
+            _ => {
+                prompt.handle_event(event, cx);
+                self.prompt = Some(prompt);
+            }
+        }
+        EventResult::Consumed(None)
+    }
+}
+
+impl Component for RegisterPanel {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        if self.prompt.is_some() {
+            return self.handle_prompt_event(event, cx);
+        }
+        let key = match event {
+            Event::Key(key) => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        let close: Callback = Box::new(|compositor, _| {
+            compositor.remove(Self::ID);
+        });
+        match key {
+            key!('q') | key!(Esc) => return EventResult::Consumed(Some(close)),
+            _ if self.contents.is_empty() => {}
+            key!(Enter) => {
+                // used by the next command, like after `"`
+                let (name, _) = self.tree.current_item().key();
+                cx.editor.selected_register = Some(name);
+                return EventResult::Consumed(Some(close));
+            }
+            key!('e') => self.edit_current(cx.editor),
+            key!('d') => self.delete_current(cx.editor),
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.contents)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let area = sidebar::area(area);
+        let help = "enter: select  e: edit  d: delete";
+        let mut tree_area =
+            sidebar::render_frame(area, surface, &cx.editor.theme, "Registers", help, true);
+        if let Some(prompt) = &self.prompt {
+            self.prompt_area = tree_area.clip_top(tree_area.height.saturating_sub(1));
+            tree_area = tree_area.clip_bottom(1);
+            prompt.render_prompt(self.prompt_area, surface, cx);
+        }
+        if self.contents.is_empty() {
+            let style = cx.editor.theme.get("ui.text.inactive");
+            let width = tree_area.width as usize;
+            surface.set_stringn(tree_area.x, tree_area.y, "No registers", width, style);
+            return;
+        }
+        self.tree.render(tree_area, surface, cx, &mut self.contents);
+    }
+
+    fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        match &self.prompt {
+            Some(prompt) => prompt.cursor(self.prompt_area, editor),
+            None => (None, CursorKind::Hidden),
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
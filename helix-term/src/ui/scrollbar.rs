@@ -0,0 +1,219 @@
+use std::{
+    borrow::Cow,
+    collections::HashMap,
+    sync::{Arc, Mutex},
+};
+
+use helix_core::{
+    diagnostic::Severity,
+    regex::{Regex, RegexBuilder},
+};
+use helix_view::{
+    graphics::{Color, Style},
+    Document, DocumentId, Editor, Theme, View,
+};
+use tokio::task::JoinHandle;
+use tui::buffer::Buffer as Surface;
+
+/// Marks drawn along the scrollbar, ordered by priority.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+enum Mark {
+    Search,
+    Diagnostic(Severity),
+}
+
+/// The most lines marked in a document, the search stops at the ones after.
+const MAX_MARKED_LINES: usize = 10_000;
+
+/// The lines with matches of the last search in the documents shown with a scrollbar,
+/// searched again only when the query or the document changes. The documents are
+/// searched in blocking tasks, one at a time for each of them, and the scrollbars drawn
+/// again once the lines are found. The text is searched line by line, so a match
+/// spanning lines is not marked.
+#[derive(Debug, Default)]
+pub struct SearchMarks {
+    query: String,
+    case_insensitive: bool,
+    /// The query compiled, `None` when it isn't a valid regex.
+    regex: Option<Regex>,
+    /// Counts the queries, the lines found for an earlier one are dropped.
+    generation: usize,
+    /// The version of each document searched last, and the task searching it.
+    searched: HashMap<DocumentId, (i32, JoinHandle<()>)>,
+    found: Arc<Mutex<Found>>,
+}
+
+/// The lines found by the tasks, for the query of `generation`.
+#[derive(Debug, Default)]
+struct Found {
+    generation: usize,
+    lines: HashMap<DocumentId, Arc<[usize]>>,
+}
+
+impl SearchMarks {
+    /// Starts searching the documents changed since their last search. It has to be
+    /// called within the tokio runtime.
+    pub fn update(&mut self, editor: &Editor) {
+        let query = match editor.registers.read('/').and_then(|query| query.last()) {
+            Some(query) if !query.is_empty() => query,
+            _ => {
+                if !self.query.is_empty() {
+                    self.query.clear();
+                    self.regex = None;
+                    self.clear();
+                }
+                return;
+            }
+        };
+        let search_config = &editor.config().search;
+        let case_insensitive = search_config.smart_case && !query.chars().any(char::is_uppercase);
+        if *query != self.query || case_insensitive != self.case_insensitive {
+            self.query = query.clone();
+            self.case_insensitive = case_insensitive;
+            self.regex = RegexBuilder::new(query)
+                .case_insensitive(case_insensitive)
+                .multi_line(true)
+                .build()
+                .ok();
+            self.clear();
+        }
+        let regex = match &self.regex {
+            Some(regex) => regex,
+            None => return,
+        };
+        self.searched
+            .retain(|doc_id, _| editor.documents.contains_key(doc_id));
+        self.found
+            .lock()
+            .unwrap()
+            .lines
+            .retain(|doc_id, _| editor.documents.contains_key(doc_id));
+
+        for (view, _) in editor.tree.views() {
+            if view.scrollbar_area().is_none() {
+                continue;
+            }
+            let doc = &editor.documents[&view.doc];
+            if doc.is_large_file() {
+                continue;
+            }
+            match self.searched.get(&view.doc) {
+                Some((version, _)) if *version == doc.version() => continue,
+                // searched again once the last search is done
+                Some((_, task)) if !task.is_finished() => continue,
+                _ => {}
+            }
+            let doc_id = view.doc;
+            let generation = self.generation;
+            let (text, regex) = (doc.text().clone(), regex.clone());
+            let (found, redraw) = (self.found.clone(), editor.redraw_handle.0.clone());
+            let task = tokio::task::spawn_blocking(move || {
+                // the lines within a chunk of the rope are searched without copying them
+                let lines = text
+                    .lines()
+                    .enumerate()
+                    .filter(|(_, line)| regex.is_match(&Cow::from(*line)))
+                    .map(|(line, _)| line)
+                    .take(MAX_MARKED_LINES)
+                    .collect();
+                let mut found = found.lock().unwrap();
+                if found.generation == generation {
+                    found.lines.insert(doc_id, lines);
+                    redraw.notify_one();
+                }
+            });
+            self.searched.insert(doc_id, (doc.version(), task));
+        }
+    }
+
+    /// Forgets the lines found, for another query.
+    fn clear(&mut self) {
+        self.generation += 1;
+        self.searched.clear();
+        let mut found = self.found.lock().unwrap();
+        found.generation = self.generation;
+        found.lines.clear();
+    }
+
+    /// The lines with matches found in the document last, sorted.
+    pub fn lines(&self, doc_id: DocumentId) -> Arc<[usize]> {
+        let found = self.found.lock().unwrap();
+        let lines = found.lines.get(&doc_id).cloned();
+        lines.unwrap_or_else(|| Vec::new().into())
+    }
+}
+
+/// Renders the scrollbar of `view`: its thumb stands for the visible lines, the
+/// diagnostics and `search_lines` are marked at their position in the document.
+pub fn render(
+    doc: &Document,
+    view: &View,
+    surface: &mut Surface,
+    theme: &Theme,
+    search_lines: &[usize],
+) {
+    let area = match view.scrollbar_area() {
+        Some(area) if area.width > 0 && area.height > 0 => area,
+        _ => return,
+    };
+    let style = theme
+        .try_get("ui.scrollbar")
+        .unwrap_or_else(|| theme.get("ui.menu.scroll"));
+    // the foreground is the thumb, the background the track
+    let track = Style::default().bg(style.bg.unwrap_or(Color::Reset));
+    let thumb = Style::default().bg(style.fg.unwrap_or(Color::Reset));
+
+    let (thumb_start, thumb_len) = view.scrollbar_thumb(doc);
+    for row in 0..area.height {
+        let style = if (thumb_start..thumb_start + thumb_len).contains(&row) {
+            thumb
+        } else {
+            track
+        };
+        for x in area.left()..area.right() {
+            surface[(x, area.y + row)].set_symbol(" ").set_style(style);
+        }
+    }
+
+    let total = doc.text().len_lines().max(1);
+    let height = area.height as usize;
+    let mut marks = vec![None; height];
+    let mut mark_line = |line: usize, mark: Mark| {
+        let slot = &mut marks[(line * height / total).min(height - 1)];
+        *slot = (*slot).max(Some(mark));
+    };
+    for &line in search_lines {
+        mark_line(line, Mark::Search);
+    }
+    for diagnostic in doc.diagnostics() {
+        let severity = diagnostic.severity.unwrap_or(Severity::Hint);
+        mark_line(diagnostic.line, Mark::Diagnostic(severity));
+    }
+
+    for (row, mark) in marks.into_iter().enumerate() {
+        let mark = match mark {
+            Some(mark) => mark,
+            None => continue,
+        };
+        let fg = mark_style(mark, theme).fg;
+        for x in area.left()..area.right() {
+            let cell = &mut surface[(x, area.y + row as u16)];
+            cell.set_symbol("━");
+            if let Some(fg) = fg {
+                cell.set_fg(fg);
+            }
+        }
+    }
+}
+
+fn mark_style(mark: Mark, theme: &Theme) -> Style {
+    match mark {
+        Mark::Search => theme
+            .try_get("ui.scrollbar.search")
+            .unwrap_or_else(|| theme.get("special")),
+        Mark::Diagnostic(Severity::Error) => theme.get("error"),
+        Mark::Diagnostic(Severity::Warning) => theme.get("warning"),
+        Mark::Diagnostic(Severity::Info) => theme.get("info"),
+        Mark::Diagnostic(Severity::Hint) => theme.get("hint"),
+    }
+}
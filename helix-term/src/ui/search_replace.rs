@@ -0,0 +1,694 @@
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::job;
+use crate::{ctrl, key};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{
+    cmp::Ordering,
+    fs,
+    ops::Range,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{anyhow, bail, Result};
+use helix_core::{
+    path::get_relative_path,
+    regex::{Regex, RegexBuilder},
+    Transaction,
+};
+use helix_view::{
+    apply_transaction, editor::Action, editor::FilePickerConfig, graphics::Rect, input::KeyEvent,
+    DocumentId, Editor,
+};
+use ignore::WalkBuilder;
+
+use super::{Prompt, Tree, TreeItem};
+
+/// Bytes of a line kept to show a match, longer lines are cut.
+const MAX_LINE_LEN: usize = 256;
+
+/// Where the matches of a file were searched.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Source {
+    /// An open buffer, at its version.
+    Buffer(DocumentId, i32),
+    /// The file on disk, with its modification time.
+    Disk(Option<SystemTime>),
+}
+
+#[derive(Debug, Clone)]
+struct Match {
+    /// 0 indexed line of the start of the match.
+    line: usize,
+    /// The line, without its indentation.
+    text: String,
+    /// Bytes of `text` matched, up to the end of the line.
+    range: Range<usize>,
+    accepted: bool,
+}
+
+#[derive(Debug, Clone)]
+struct FileMatches {
+    path: PathBuf,
+    source: Source,
+    matches: Vec<Match>,
+}
+
+/// The last search, shared with the items of the tree to render them.
+struct SearchState {
+    regex: Option<Regex>,
+    replacement: String,
+    files: Vec<FileMatches>,
+}
+
+/// A row of the results: a file, or one of its matches.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ResultItem {
+    File(usize),
+    Match(usize, usize),
+}
+
+impl ResultItem {
+    fn key(&self) -> (usize, usize) {
+        match *self {
+            Self::File(file) => (file, 0),
+            Self::Match(file, index) => (file, index + 1),
+        }
+    }
+}
+
+impl TreeItem for ResultItem {
+    type Params = SearchState;
+
+    fn text(&self, cx: &mut Context, selected: bool, state: &mut SearchState) -> Spans {
+        let theme = &cx.editor.theme;
+        let mut text_style = theme.get("ui.text");
+        if selected {
+            text_style = text_style.patch(theme.get("ui.menu.selected"));
+        }
+        match *self {
+            Self::File(file) => {
+                let file = &state.files[file];
+                let accepted = file.matches.iter().filter(|mat| mat.accepted).count();
+                let path = get_relative_path(&file.path);
+                Spans::from(Span::styled(
+                    format!(
+                        "{} ({}/{})",
+                        path.to_string_lossy(),
+                        accepted,
+                        file.matches.len()
+                    ),
+                    text_style,
+                ))
+            }
+            Self::Match(file, index) => {
+                let mat = &state.files[file].matches[index];
+                let style = |scope: &str| {
+                    let mut style = theme.get(scope);
+                    if !mat.accepted {
+                        style = theme.get("ui.text.inactive");
+                    }
+                    if selected {
+                        style = style.patch(theme.get("ui.menu.selected"));
+                    }
+                    style
+                };
+                let matched = &mat.text[mat.range.clone()];
+                let replacement = state
+                    .regex
+                    .as_ref()
+                    .map(|regex| replace_preview(regex, matched, &state.replacement))
+                    .unwrap_or_default();
+                let check = if mat.accepted { "[x]" } else { "[ ]" };
+                Spans::from(vec![
+                    Span::styled(format!("{} {}: ", check, mat.line + 1), style("ui.linenr")),
+                    Span::styled(mat.text[..mat.range.start].to_string(), style("ui.text")),
+                    Span::styled(matched.to_string(), style("diff.minus")),
+                    Span::styled(replacement, style("diff.plus")),
+                    Span::styled(mat.text[mat.range.end..].to_string(), style("ui.text")),
+                ])
+            }
+        }
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::Match(file, _), Self::File(parent)) if file == parent)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The replacement of `matched` shown next to it. Only the matched text is replaced,
+/// anchors and word boundaries may differ from the replacement in the whole file.
+fn replace_preview(regex: &Regex, matched: &str, replacement: &str) -> String {
+    if regex.is_match(matched) {
+        regex.replacen(matched, 1, replacement).into_owned()
+    } else {
+        replacement.to_string()
+    }
+}
+
+/// The non-empty matches of `regex` in `text`.
+fn find_matches(regex: &Regex, text: &str) -> Vec<Match> {
+    let mut line = 0;
+    let mut counted = 0;
+    regex
+        .find_iter(text)
+        .filter(|mat| !mat.as_str().is_empty())
+        .map(|mat| {
+            line += text[counted..mat.start()].matches('\n').count();
+            counted = mat.start();
+            let line_start = text[..mat.start()].rfind('\n').map_or(0, |i| i + 1);
+            let line_end = text[mat.start()..]
+                .find('\n')
+                .map_or(text.len(), |i| mat.start() + i);
+            let line_text = text[line_start..line_end].trim_end_matches('\r');
+            let indent = line_text.len() - line_text.trim_start().len();
+            let start = mat.start() - line_start;
+            let end = (mat.end() - line_start).min(line_text.len());
+            let offset = indent.min(start);
+            let mut cut = (offset + MAX_LINE_LEN).max(end).min(line_text.len());
+            while !line_text.is_char_boundary(cut) {
+                cut += 1;
+            }
+            Match {
+                line,
+                text: line_text[offset..cut].to_string(),
+                range: start - offset..end - offset,
+                accepted: true,
+            }
+        })
+        .collect()
+}
+
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    fs::metadata(path).and_then(|meta| meta.modified()).ok()
+}
+
+/// Searches the files under `root` on disk, honouring the ignore files like the file picker.
+fn search_files(regex: &Regex, root: &Path, config: &FilePickerConfig) -> Vec<FileMatches> {
+    let walk = WalkBuilder::new(root)
+        .hidden(config.hidden)
+        .parents(config.parents)
+        .ignore(config.ignore)
+        .follow_links(config.follow_symlinks)
+        .git_ignore(config.git_ignore)
+        .git_global(config.git_global)
+        .git_exclude(config.git_exclude)
+        .max_depth(config.max_depth)
+        .filter_entry(|entry| entry.file_name() != ".git")
+        .build();
+    let mut files = Vec::new();
+    for entry in walk.flatten() {
+        if !entry.file_type().map_or(false, |ty| ty.is_file()) {
+            continue;
+        }
+        let path = entry.path();
+        let mtime = file_mtime(path);
+        // binary files and files which are not UTF-8 are skipped
+        let text = match fs::read_to_string(path) {
+            Ok(text) if !text.contains('\0') => text,
+            _ => continue,
+        };
+        let matches = find_matches(regex, &text);
+        if !matches.is_empty() {
+            files.push(FileMatches {
+                path: helix_core::path::get_normalized_path(path),
+                source: Source::Disk(mtime),
+                matches,
+            });
+        }
+    }
+    files
+}
+
+/// The edits of the accepted matches of `file` in `text`, as byte ranges and their
+/// replacements. Fails when the matches in `text` are not the ones found by the search.
+fn replacements(
+    regex: &Regex,
+    replacement: &str,
+    text: &str,
+    file: &FileMatches,
+) -> Result<Vec<(Range<usize>, String)>> {
+    let captures: Vec<_> = regex
+        .captures_iter(text)
+        .filter(|caps| !caps[0].is_empty())
+        .collect();
+    if captures.len() != file.matches.len() {
+        bail!(
+            "'{}' changed since the search",
+            get_relative_path(&file.path).display()
+        );
+    }
+    Ok(captures
+        .iter()
+        .zip(&file.matches)
+        .filter(|(_, mat)| mat.accepted)
+        .map(|(caps, _)| {
+            let mut dst = String::new();
+            caps.expand(replacement, &mut dst);
+            (caps.get(0).unwrap().range(), dst)
+        })
+        .collect())
+}
+
+enum PromptAction {
+    Search,
+    /// The replacement follows the input, the one before it is kept to go back to.
+    Replace(String),
+}
+
+/// Searching the workspace for a regex and replacing the matches: the files with
+/// matches and their matches are listed in a tree, every match can be left out
+/// before the replacement is applied to the buffers and files.
+pub struct SearchReplace {
+    tree: Tree<ResultItem>,
+    state: SearchState,
+    query: String,
+    prompt: Option<(PromptAction, Prompt)>,
+    /// Whether a search is running.
+    searching: bool,
+}
+
+impl SearchReplace {
+    pub const ID: &'static str = "search-replace";
+
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::build_tree(Vec::new()),
+            state: SearchState {
+                regex: None,
+                replacement: String::new(),
+                files: Vec::new(),
+            },
+            query: String::new(),
+            prompt: None,
+            searching: false,
+        }
+    }
+
+    /// A panel waiting for the results of [`Self::search`] for `query`.
+    pub fn with_query(query: String) -> Self {
+        Self {
+            query,
+            searching: true,
+            ..Self::new()
+        }
+    }
+
+    /// Opens the prompt to edit the search.
+    pub fn edit_search(&mut self, editor: &Editor) {
+        self.open_prompt(PromptAction::Search, editor);
+    }
+
+    fn open_prompt(&mut self, action: PromptAction, editor: &Editor) {
+        let (label, line) = match &action {
+            PromptAction::Search => ("search: ", self.query.clone()),
+            PromptAction::Replace(replacement) => ("replace: ", replacement.clone()),
+        };
+        let prompt = Prompt::new(label.into(), None, super::completers::none, |_, _, _| {})
+            .with_line(line, editor);
+        self.prompt = Some((action, prompt));
+    }
+
+    /// Searches the working directory for `query` in the background. The results go to
+    /// the panel if it is still waiting for them, the buffers are searched instead of
+    /// their files.
+    pub fn search(query: &str, editor: &Editor, jobs: &mut job::Jobs) -> Result<()> {
+        let smart_case = editor.config().search.smart_case;
+        let case_insensitive = smart_case && !query.chars().any(char::is_uppercase);
+        let regex = RegexBuilder::new(query)
+            .case_insensitive(case_insensitive)
+            .multi_line(true)
+            .build()
+            .map_err(|err| anyhow!("Invalid regex: {}", err))?;
+
+        let config = editor.config().file_picker.clone();
+        let root = std::env::current_dir().unwrap_or_else(|_| PathBuf::from("."));
+        let callback = async move {
+            let files = tokio::task::spawn_blocking(move || {
+                let files = search_files(&regex, &root, &config);
+                (regex, files)
+            })
+            .await?;
+            let call: job::Callback = job::Callback::EditorCompositor(Box::new(
+                move |editor: &mut Editor, compositor: &mut Compositor| {
+                    let (regex, files) = files;
+                    match compositor.find_id::<Self>(Self::ID) {
+                        Some(panel) if panel.query == regex.as_str() => {
+                            panel.set_results(regex, files, editor)
+                        }
+                        _ => (),
+                    }
+                },
+            ));
+            Ok(call)
+        };
+        jobs.callback(callback);
+        Ok(())
+    }
+
+    fn search_again(&mut self, query: String, cx: &mut Context) {
+        match Self::search(&query, cx.editor, cx.jobs) {
+            Ok(()) => {
+                self.query = query;
+                self.searching = true;
+            }
+            Err(err) => cx.editor.set_error(format!("{}", err)),
+        }
+    }
+
+    fn set_results(&mut self, regex: Regex, mut files: Vec<FileMatches>, editor: &Editor) {
+        // the buffers may differ from their files
+        let root = std::env::current_dir().unwrap_or_default();
+        for doc in editor.documents() {
+            let path = match doc.path() {
+                Some(path) if path.starts_with(&root) => path,
+                _ => continue,
+            };
+            files.retain(|file| &file.path != path);
+            let matches = find_matches(&regex, &doc.text().to_string());
+            if !matches.is_empty() {
+                files.push(FileMatches {
+                    path: path.clone(),
+                    source: Source::Buffer(doc.id(), doc.version()),
+                    matches,
+                });
+            }
+        }
+        files.sort_by(|a, b| a.path.cmp(&b.path));
+
+        let items = files
+            .iter()
+            .enumerate()
+            .flat_map(|(file, matches)| {
+                std::iter::once(ResultItem::File(file)).chain(
+                    (0..matches.matches.len()).map(move |index| ResultItem::Match(file, index)),
+                )
+            })
+            .collect();
+        self.tree.replace_with_new_items(items);
+        self.state.regex = Some(regex);
+        self.state.files = files;
+        self.searching = false;
+    }
+
+    /// Accepts or leaves out the current match, or all matches of the current file.
+    fn toggle_current(&mut self) {
+        if self.state.files.is_empty() {
+            return;
+        }
+        match *self.tree.current_item() {
+            ResultItem::File(file) => {
+                let matches = &mut self.state.files[file].matches;
+                let accept = !matches.iter().all(|mat| mat.accepted);
+                for mat in matches {
+                    mat.accepted = accept;
+                }
+            }
+            ResultItem::Match(file, index) => {
+                let mat = &mut self.state.files[file].matches[index];
+                mat.accepted = !mat.accepted;
+            }
+        }
+    }
+
+    /// Opens the file of the current item at its match.
+    fn goto_current(&self, editor: &mut Editor) -> Result<()> {
+        if self.state.files.is_empty() {
+            return Ok(());
+        }
+        let (file, line) = match *self.tree.current_item() {
+            ResultItem::File(file) => (file, self.state.files[file].matches[0].line),
+            ResultItem::Match(file, index) => (file, self.state.files[file].matches[index].line),
+        };
+        editor.open(&self.state.files[file].path, Action::Replace)?;
+        let (view, doc) = current!(editor);
+        let text = doc.text();
+        let pos = text.line_to_char(line.min(text.len_lines().saturating_sub(1)));
+        doc.set_selection(view.id, helix_core::Selection::point(pos));
+        helix_view::align_view(doc, view, helix_view::Align::Center);
+        Ok(())
+    }
+
+    /// Replaces the accepted matches in the buffers and files. Nothing is replaced if
+    /// any of them changed since the search, the written files are restored if writing
+    /// one of them fails. Returns the number of matches and of files replaced in.
+    fn apply(&self, editor: &mut Editor) -> Result<(usize, usize)> {
+        let regex = match &self.state.regex {
+            Some(regex) => regex,
+            None => return Ok((0, 0)),
+        };
+        let replacement = &self.state.replacement;
+        let mut buffer_edits = Vec::new();
+        let mut file_edits = Vec::new();
+        let mut count = 0;
+
+        for file in &self.state.files {
+            if !file.matches.iter().any(|mat| mat.accepted) {
+                continue;
+            }
+            let changed = || anyhow!("'{}' changed since the search", file.path.display());
+            match file.source {
+                Source::Buffer(doc_id, version) => {
+                    let doc = editor
+                        .document(doc_id)
+                        .filter(|doc| doc.version() == version)
+                        .ok_or_else(changed)?;
+                    let text = doc.text();
+                    let edits = replacements(regex, replacement, &text.to_string(), file)?;
+                    count += edits.len();
+                    let changes = edits.into_iter().map(|(range, replacement)| {
+                        let start = text.byte_to_char(range.start);
+                        let end = text.byte_to_char(range.end);
+                        (start, end, Some(replacement.into()))
+                    });
+                    buffer_edits.push((doc_id, Transaction::change(text, changes)));
+                }
+                Source::Disk(mtime) => {
+                    if editor.document_by_path(&file.path).is_some()
+                        || file_mtime(&file.path) != mtime
+                    {
+                        return Err(changed());
+                    }
+                    let old = fs::read_to_string(&file.path)?;
+                    let edits = replacements(regex, replacement, &old, file)?;
+                    count += edits.len();
+                    let mut new = String::with_capacity(old.len());
+                    let mut last = 0;
+                    for (range, replacement) in edits {
+                        new.push_str(&old[last..range.start]);
+                        new.push_str(&replacement);
+                        last = range.end;
+                    }
+                    new.push_str(&old[last..]);
+                    file_edits.push((&file.path, old, new));
+                }
+            }
+        }
+
+        let mut written = Vec::new();
+        for (path, old, new) in &file_edits {
+            if let Err(err) = fs::write(path, new) {
+                for (path, old) in written {
+                    if let Err(err) = fs::write(path, old) {
+                        log::error!("failed to restore '{}': {}", path.display(), err);
+                    }
+                }
+                bail!("failed to write '{}': {}", path.display(), err);
+            }
+            written.push((*path, old));
+        }
+
+        let focus = editor.tree.focus;
+        for (doc_id, transaction) in &buffer_edits {
+            let doc = doc_mut!(editor, doc_id);
+            let view_id = doc.selections().keys().next().copied().unwrap_or(focus);
+            doc.ensure_view_init(view_id);
+            let view = view_mut!(editor, view_id);
+            apply_transaction(transaction, doc, view);
+            doc.append_changes_to_history(view);
+        }
+
+        Ok((count, buffer_edits.len() + file_edits.len()))
+    }
+
+    fn handle_prompt_event(&mut self, event: KeyEvent, cx: &mut Context) -> EventResult {
+        let (action, mut prompt) = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return EventResult::Ignored(None),
+        };
+        match (action, event) {
+            (PromptAction::Search, key!(Enter)) => {
+                let query = prompt.line().clone();
+                if !query.is_empty() {
+                    self.search_again(query, cx);
+                }
+            }
+            (PromptAction::Replace(_), key!(Enter)) => {}
+            (PromptAction::Replace(replacement), key!(Esc) | ctrl!('c')) => {
+                self.state.replacement = replacement;
+            }
+            (_, key!(Esc) | ctrl!('c')) => {}
+            (action, _) => {
+                prompt.handle_event(&Event::Key(event), cx);
+                if let PromptAction::Replace(_) = action {
+                    self.state.replacement = prompt.line().clone();
+                }
+                self.prompt = Some((action, prompt));
+            }
+        }
+        EventResult::Consumed(None)
+    }
+}
+
+impl Default for SearchReplace {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl Component for SearchReplace {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            // keep the paste from reaching the buffers behind the panel
+            Event::Paste(_) => return EventResult::Consumed(None),
+            _ => return EventResult::Ignored(None),
+        };
+        if self.prompt.is_some() {
+            return self.handle_prompt_event(key, cx);
+        }
+
+        match key {
+            key!('q') | key!(Esc) => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!('/') | key!('s') => self.edit_search(cx.editor),
+            key!('r') => {
+                let replacement = self.state.replacement.clone();
+                self.open_prompt(PromptAction::Replace(replacement), cx.editor)
+            }
+            key!(' ') => self.toggle_current(),
+            key!('o') => {
+                if let Err(err) = self.goto_current(cx.editor) {
+                    cx.editor.set_error(format!("{}", err));
+                    return EventResult::Consumed(None);
+                }
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!('a') => match self.apply(cx.editor) {
+                Ok((count, files)) => {
+                    cx.editor.set_status(format!(
+                        "Replaced {} match{} in {} file{}",
+                        count,
+                        if count == 1 { "" } else { "es" },
+                        files,
+                        if files == 1 { "" } else { "s" },
+                    ));
+                    // the results are out of date now
+                    let query = self.query.clone();
+                    self.search_again(query, cx);
+                }
+                Err(err) => cx.editor.set_error(format!("{}", err)),
+            },
+            _ => {
+                return self.tree.handle_event(Event::Key(key), cx, &mut self.state);
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        // keep the last row free for the status messages
+        let area = area.clip_bottom(1);
+        surface.clear_with(area, theme.get("ui.background"));
+        let label_style = theme.get("ui.text.inactive");
+        let text_style = theme.get("ui.text");
+
+        let header = [
+            ("search:  ", self.query.as_str()),
+            ("replace: ", self.state.replacement.as_str()),
+        ];
+        for (y, (label, value)) in (area.top()..area.bottom()).zip(header) {
+            surface.set_stringn(area.x, y, label, area.width as usize, label_style);
+            let x = area.x + label.len() as u16;
+            let width = area.width.saturating_sub(label.len() as u16) as usize;
+            surface.set_stringn(x, y, value, width, text_style);
+        }
+
+        let help = "space: toggle  r: replacement  /: search  a: apply  o: open  q: close";
+        let status = if self.searching {
+            "searching...".to_string()
+        } else {
+            let matches: usize = self.state.files.iter().map(|file| file.matches.len()).sum();
+            format!("{} matches in {} files", matches, self.state.files.len())
+        };
+        let status_y = area.y + 2;
+        if status_y < area.bottom() {
+            surface.set_stringn(area.x, status_y, &status, area.width as usize, label_style);
+            let x = area.right().saturating_sub(help.len() as u16);
+            if x > area.x + status.len() as u16 + 1 {
+                surface.set_stringn(x, status_y, help, help.len(), label_style);
+            }
+        }
+
+        let mut list_area = area.clip_top(4);
+        if let Some((_, prompt)) = &self.prompt {
+            let prompt_area = list_area.clip_top(list_area.height.saturating_sub(1));
+            list_area = list_area.clip_bottom(1);
+            surface.clear_with(prompt_area, theme.get("ui.background"));
+            prompt.render_prompt(prompt_area, surface, cx);
+        }
+        if !self.state.files.is_empty() {
+            self.tree.render(list_area, surface, cx, &mut self.state);
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_find_and_replace() {
+        let regex = Regex::new(r"(\w+)_id").unwrap();
+        let text = "let doc_id = 1;\n    view_id\r\nnone\n";
+        let matches = find_matches(&regex, text);
+        let found: Vec<_> = matches
+            .iter()
+            .map(|mat| (mat.line, &mat.text[mat.range.clone()], mat.text.as_str()))
+            .collect();
+        assert_eq!(
+            found,
+            [(0, "doc_id", "let doc_id = 1;"), (1, "view_id", "view_id")]
+        );
+
+        let mut file = FileMatches {
+            path: PathBuf::from("file"),
+            source: Source::Disk(None),
+            matches,
+        };
+        file.matches[1].accepted = false;
+        let edits = replacements(&regex, "${1}Id", text, &file).unwrap();
+        assert_eq!(edits, [(4..10, "docId".to_string())]);
+        assert!(replacements(&regex, "", "doc_id", &file).is_err());
+    }
+}
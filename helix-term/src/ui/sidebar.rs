@@ -0,0 +1,90 @@
+//! Panels shown on the right of the views, like the variables of the debugger or the
+//! symbols of the workspace. They are layers of the compositor drawing over the right
+//! columns of the views.
+
+use crate::compositor::EventResult;
+use helix_view::{
+    graphics::Rect,
+    input::{MouseButton, MouseEvent, MouseEventKind},
+    Theme,
+};
+use tui::{
+    buffer::Buffer as Surface,
+    widgets::{Block, Borders, Widget},
+};
+
+use super::{Tree, TreeItem};
+
+/// Columns of a panel, including its border.
+const WIDTH: u16 = 48;
+
+/// The area of a panel within the area of the editor. The last row is left to the status
+/// messages.
+pub fn area(area: Rect) -> Rect {
+    let area = area.clip_bottom(1);
+    area.clip_left(area.width - WIDTH.min(area.width))
+}
+
+/// Whether `event` is a click of the left button within `area`, focusing the panel.
+pub fn clicked(area: Rect, event: &MouseEvent) -> bool {
+    let MouseEvent {
+        kind, row, column, ..
+    } = *event;
+    kind == MouseEventKind::Down(MouseButton::Left)
+        && (area.top()..area.bottom()).contains(&row)
+        && (area.left()..area.right()).contains(&column)
+}
+
+/// A panel listing its contents as a tree, focused by its command or by a click.
+pub trait TreePanel {
+    type Item: TreeItem;
+
+    /// The tree of the panel, the area it was last drawn in and whether it has the keys.
+    fn parts(&mut self) -> (&mut Tree<Self::Item>, Rect, &mut bool);
+
+    fn focus(&mut self) {
+        *self.parts().2 = true;
+    }
+
+    /// Focuses the panel on a click within it, selecting the row clicked.
+    fn handle_mouse_event(&mut self, event: &MouseEvent) -> EventResult {
+        let (tree, area, focused) = self.parts();
+        if !clicked(area, event) {
+            return EventResult::Ignored(None);
+        }
+        *focused = true;
+        if let Some(index) = tree.index_at(event.row, event.column) {
+            tree.select_index(index);
+        }
+        EventResult::Consumed(None)
+    }
+}
+
+/// Draws the border of a panel and its header, the `title` and the keys of the panel,
+/// and returns the area left to the contents.
+pub fn render_frame(
+    area: Rect,
+    surface: &mut Surface,
+    theme: &Theme,
+    title: &str,
+    help: &str,
+    focused: bool,
+) -> Rect {
+    surface.clear_with(area, theme.get("ui.background"));
+    let block = Block::default().borders(Borders::LEFT);
+    let inner = block.inner(area);
+    block.render(area, surface);
+
+    let label_style = theme.get("ui.text.inactive");
+    let title_style = if focused {
+        theme.get("ui.text.focus")
+    } else {
+        label_style
+    };
+    surface.set_stringn(inner.x, inner.y, title, inner.width as usize, title_style);
+    let x = inner.right().saturating_sub(help.len() as u16);
+    if x > inner.x + title.len() as u16 + 1 {
+        surface.set_stringn(x, inner.y, help, help.len(), label_style);
+    }
+    inner.clip_top(2)
+}
@@ -7,7 +7,7 @@
     Document, Editor, View,
 };
 
-use crate::ui::ProgressSpinners;
+use crate::{commands::MappableCommand, ui::ProgressSpinners};
 
 use helix_view::editor::StatusLineElement as StatusLineElementID;
 use tui::buffer::Buffer as Surface;
@@ -19,6 +19,8 @@ pub struct RenderContext<'a> {
     pub view: &'a View,
     pub focused: bool,
     pub spinners: &'a ProgressSpinners,
+    /// State of the file explorer: `None` if it is closed, otherwise whether it is focused.
+    pub explorer: Option<bool>,
     pub parts: RenderBuffer<'a>,
 }
 
@@ -29,6 +31,7 @@ pub fn new(
         view: &'a View,
         focused: bool,
         spinners: &'a ProgressSpinners,
+        explorer: Option<bool>,
     ) -> Self {
         RenderContext {
             editor,
@@ -36,6 +39,7 @@ pub fn new(
             view,
             focused,
             spinners,
+            explorer,
             parts: RenderBuffer::default(),
         }
     }
@@ -48,7 +52,12 @@ pub struct RenderBuffer<'a> {
     pub right: Spans<'a>,
 }
 
-pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface) {
+/// Renders the statusline, returns the area each element was rendered at.
+pub fn render(
+    context: &mut RenderContext,
+    viewport: Rect,
+    surface: &mut Surface,
+) -> Vec<(Rect, StatusLineElementID)> {
     let base_style = if context.focused {
         context.editor.theme.get("ui.statusline")
     } else {
@@ -71,11 +80,12 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
 
     let config = context.editor.config();
 
-    let element_ids = &config.statusline.left;
-    element_ids
-        .iter()
-        .map(|element_id| get_render_function(*element_id))
-        .for_each(|render| render(context, write_left));
+    let mut left = Vec::new();
+    for element_id in &config.statusline.left {
+        let start = context.parts.left.width();
+        get_render_function(*element_id)(context, write_left);
+        left.push((start, context.parts.left.width(), *element_id));
+    }
 
     surface.set_spans(
         viewport.x,
@@ -86,17 +96,19 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
 
     // Right side of the status line.
 
-    let element_ids = &config.statusline.right;
-    element_ids
-        .iter()
-        .map(|element_id| get_render_function(*element_id))
-        .for_each(|render| render(context, write_right));
+    let mut right = Vec::new();
+    for element_id in &config.statusline.right {
+        let start = context.parts.right.width();
+        get_render_function(*element_id)(context, write_right);
+        right.push((start, context.parts.right.width(), *element_id));
+    }
 
+    let right_x = viewport.x
+        + viewport
+            .width
+            .saturating_sub(context.parts.right.width() as u16);
     surface.set_spans(
-        viewport.x
-            + viewport
-                .width
-                .saturating_sub(context.parts.right.width() as u16),
+        right_x,
         viewport.y,
         &context.parts.right,
         context.parts.right.width() as u16,
@@ -104,11 +116,12 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
 
     // Center of the status line.
 
-    let element_ids = &config.statusline.center;
-    element_ids
-        .iter()
-        .map(|element_id| get_render_function(*element_id))
-        .for_each(|render| render(context, write_center));
+    let mut center = Vec::new();
+    for element_id in &config.statusline.center {
+        let start = context.parts.center.width();
+        get_render_function(*element_id)(context, write_center);
+        center.push((start, context.parts.center.width(), *element_id));
+    }
 
     // Width of the empty space between the left and center area and between the center and right area.
     let spacing = 1u16;
@@ -116,13 +129,54 @@ pub fn render(context: &mut RenderContext, viewport: Rect, surface: &mut Surface
     let edge_width = context.parts.left.width().max(context.parts.right.width()) as u16;
     let center_max_width = viewport.width.saturating_sub(2 * edge_width + 2 * spacing);
     let center_width = center_max_width.min(context.parts.center.width() as u16);
+    let center_x = viewport.x + viewport.width / 2 - center_width / 2;
 
-    surface.set_spans(
-        viewport.x + viewport.width / 2 - center_width / 2,
-        viewport.y,
-        &context.parts.center,
-        center_width,
-    );
+    surface.set_spans(center_x, viewport.y, &context.parts.center, center_width);
+
+    let left_width = context.parts.left.width() as u16;
+    let right_width = context.parts.right.width() as u16;
+    let row = viewport.with_height(1);
+    let mut areas = element_areas(row.with_width(left_width.min(row.width)), &left);
+    areas.extend(element_areas(
+        Rect::new(center_x, row.y, center_width, 1),
+        &center,
+    ));
+    areas.extend(element_areas(
+        Rect::new(right_x, row.y, right_width, 1),
+        &right,
+    ));
+    areas
+}
+
+/// The areas of the elements rendered into `area`, given with the columns they span
+/// from its start. The parts of the elements outside of `area` were cut.
+fn element_areas(
+    area: Rect,
+    elements: &[(usize, usize, StatusLineElementID)],
+) -> Vec<(Rect, StatusLineElementID)> {
+    elements
+        .iter()
+        .filter_map(|&(start, end, element_id)| {
+            let x = area.x.saturating_add(start as u16);
+            let right = area.right().min(area.x.saturating_add(end as u16));
+            (x < right).then(|| (Rect::new(x, area.y, right - x, 1), element_id))
+        })
+        .collect()
+}
+
+/// The command clicking an element of the statusline runs, if any.
+pub fn click_command(element_id: StatusLineElementID) -> Option<MappableCommand> {
+    match element_id {
+        StatusLineElementID::FileName | StatusLineElementID::FileBaseName => {
+            Some(MappableCommand::buffer_picker)
+        }
+        StatusLineElementID::Diagnostics => Some(MappableCommand::diagnostics_picker),
+        StatusLineElementID::WorkspaceDiagnostics => {
+            Some(MappableCommand::workspace_diagnostics_picker)
+        }
+        StatusLineElementID::ExplorerIndicator => Some(MappableCommand::toggle_or_focus_explorer),
+        _ => None,
+    }
 }
 
 fn append(buffer: &mut Spans, text: String, base_style: &Style, style: Option<Style>) {
@@ -155,6 +209,9 @@ fn get_render_function<F>(element_id: StatusLineElementID) -> impl Fn(&mut Rende
         helix_view::editor::StatusLineElement::TotalLineNumbers => render_total_line_numbers,
         helix_view::editor::StatusLineElement::Separator => render_separator,
         helix_view::editor::StatusLineElement::Spacer => render_spacer,
+        helix_view::editor::StatusLineElement::GitBranch => render_git_branch,
+        helix_view::editor::StatusLineElement::RepoState => render_repo_state,
+        helix_view::editor::StatusLineElement::ExplorerIndicator => render_explorer_indicator,
     }
 }
 
@@ -417,11 +474,14 @@ fn render_file_name<F>(context: &mut RenderContext, write: F)
             .as_ref()
             .map(|p| p.to_string_lossy())
             .unwrap_or_else(|| SCRATCH_BUFFER_NAME.into());
-        format!(
-            " {}{} ",
-            path,
-            if context.doc.is_modified() { "[+]" } else { "" }
-        )
+        let flag = if context.doc.is_modified() {
+            "[+]"
+        } else if context.doc.is_readonly() {
+            "[RO]"
+        } else {
+            ""
+        };
+        format!(" {}{} ", path, flag)
     };
 
     write(context, title, None);
@@ -437,11 +497,14 @@ fn render_file_base_name<F>(context: &mut RenderContext, write: F)
             .as_ref()
             .and_then(|p| p.as_path().file_name().map(|s| s.to_string_lossy()))
             .unwrap_or_else(|| SCRATCH_BUFFER_NAME.into());
-        format!(
-            " {}{} ",
-            path,
-            if context.doc.is_modified() { "[+]" } else { "" }
-        )
+        let flag = if context.doc.is_modified() {
+            "[+]"
+        } else if context.doc.is_readonly() {
+            "[RO]"
+        } else {
+            ""
+        };
+        format!(" {}{} ", path, flag)
     };
 
     write(context, title, None);
@@ -466,3 +529,39 @@ fn render_spacer<F>(context: &mut RenderContext, write: F)
 {
     write(context, String::from(" "), None);
 }
+
+fn render_git_branch<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    if let Some(head) = context.doc.version_control_head() {
+        write(context, format!(" {} ", head), None);
+    }
+}
+
+fn render_repo_state<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let info = match &context.editor.repo_status.info {
+        Some(info) => format!(" {} ", info),
+        None => return,
+    };
+    write(context, info, None);
+}
+
+fn render_explorer_indicator<F>(context: &mut RenderContext, write: F)
+where
+    F: Fn(&mut RenderContext, String, Option<Style>) + Copy,
+{
+    let focused = match context.explorer {
+        Some(focused) => focused,
+        None => return,
+    };
+    let style = if focused {
+        Some(context.editor.theme.get("ui.statusline.explorer"))
+    } else {
+        None
+    };
+    write(context, " EXP ".to_string(), style);
+}
@@ -0,0 +1,819 @@
+//! A shell in a pseudo terminal, drawn in a panel below the editor. The screen only
+//! follows the control sequences shells and line-oriented programs use: the cursor
+//! moves, erasing, scrolling and the colors. Full screen programs, which switch to the
+//! alternate screen, are meant to be run in a terminal of their own. This keeps the
+//! panel to a few hundred lines without the dependencies of the `vt100` and
+//! `portable-pty` crates; the pseudo terminal is only opened on unix, where `libc` is
+//! a dependency already.
+
+use std::{
+    fs::File,
+    io::{self, Read, Write},
+    path::Path,
+    process::Child,
+    sync::{
+        atomic::{AtomicBool, Ordering},
+        mpsc::{self, Sender},
+        Arc, Mutex,
+    },
+};
+
+use helix_core::{unicode::width::UnicodeWidthChar, Position};
+use helix_view::{
+    graphics::{Color, CursorKind, Modifier, Rect, Style},
+    input::{Event, KeyEvent},
+    keyboard::{KeyCode, KeyModifiers},
+    Editor,
+};
+use tokio::sync::Notify;
+use tui::buffer::Buffer as Surface;
+
+use crate::{
+    compositor::{Component, Context, EventResult},
+    ctrl,
+};
+
+/// Key that moves the focus from the terminal back to the editor.
+pub const UNFOCUS_KEY: KeyEvent = ctrl!(' ');
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+struct Cell {
+    ch: char,
+    style: Style,
+}
+
+impl Default for Cell {
+    fn default() -> Self {
+        Self {
+            ch: ' ',
+            style: Style::default(),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+enum ParserState {
+    Ground,
+    Escape,
+    /// `ESC (` and friends, the next byte selects a character set which we ignore.
+    Charset,
+    /// Control sequence, holds the parameter and intermediate bytes read so far.
+    Csi(String),
+    /// Operating system command, e.g. setting the window title, which we ignore.
+    Osc {
+        escape: bool,
+    },
+}
+
+/// The grid of a minimal VT100/xterm compatible terminal: text, colors, cursor movement
+/// and erasing are supported, anything else is ignored.
+#[derive(Debug)]
+struct Screen {
+    rows: Vec<Vec<Cell>>,
+    width: usize,
+    cursor: Position,
+    /// Set after printing in the last column, the next character goes to the next line.
+    wrap_pending: bool,
+    cursor_visible: bool,
+    style: Style,
+    state: ParserState,
+    /// The end of the last read if it was cut in the middle of a UTF-8 sequence.
+    partial: Vec<u8>,
+}
+
+impl Screen {
+    fn new(width: u16, height: u16) -> Self {
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+        Self {
+            rows: vec![vec![Cell::default(); width]; height],
+            width,
+            cursor: Position::default(),
+            wrap_pending: false,
+            cursor_visible: true,
+            style: Style::default(),
+            state: ParserState::Ground,
+            partial: Vec::new(),
+        }
+    }
+
+    fn height(&self) -> usize {
+        self.rows.len()
+    }
+
+    fn resize(&mut self, width: u16, height: u16) {
+        let (width, height) = (width.max(1) as usize, height.max(1) as usize);
+        // keep the bottom of the screen where the prompt usually is
+        if height < self.height() {
+            let removed = self.height() - height;
+            let removed = removed.min(self.cursor.row);
+            self.rows.drain(..removed);
+            self.cursor.row -= removed;
+        }
+        self.rows.resize(height, vec![Cell::default(); width]);
+        for row in &mut self.rows {
+            row.resize(width, Cell::default());
+        }
+        self.width = width;
+        self.cursor.row = self.cursor.row.min(height - 1);
+        self.cursor.col = self.cursor.col.min(width - 1);
+    }
+
+    fn process(&mut self, bytes: &[u8]) {
+        let mut bytes = std::mem::take(&mut self.partial)
+            .into_iter()
+            .chain(bytes.iter().copied())
+            .collect::<Vec<_>>();
+        let text = match std::str::from_utf8(&bytes) {
+            Ok(text) => text.to_string(),
+            Err(err) => {
+                // keep an incomplete sequence at the end for the next read
+                if err.error_len().is_none() {
+                    self.partial = bytes.split_off(err.valid_up_to());
+                }
+                String::from_utf8_lossy(&bytes).into_owned()
+            }
+        };
+        for ch in text.chars() {
+            self.process_char(ch);
+        }
+    }
+
+    fn process_char(&mut self, ch: char) {
+        match std::mem::replace(&mut self.state, ParserState::Ground) {
+            ParserState::Ground => self.process_ground(ch),
+            ParserState::Escape => match ch {
+                '[' => self.state = ParserState::Csi(String::new()),
+                ']' => self.state = ParserState::Osc { escape: false },
+                '(' | ')' | '*' | '+' => self.state = ParserState::Charset,
+                'M' => {
+                    self.wrap_pending = false;
+                    self.reverse_line_feed();
+                }
+                'D' => {
+                    self.wrap_pending = false;
+                    self.line_feed();
+                }
+                'E' => {
+                    self.wrap_pending = false;
+                    self.cursor.col = 0;
+                    self.line_feed();
+                }
+                'c' => *self = Self::new(self.width as u16, self.height() as u16),
+                _ => {}
+            },
+            ParserState::Charset => {}
+            ParserState::Csi(mut params) => match ch {
+                '\x40'..='\x7e' => self.process_csi(&params, ch),
+                // a control sequence can't contain another one, start over
+                '\x1b' => self.state = ParserState::Escape,
+                _ => {
+                    params.push(ch);
+                    self.state = ParserState::Csi(params);
+                }
+            },
+            ParserState::Osc { escape } => match ch {
+                '\x07' => {}
+                '\\' if escape => {}
+                _ => {
+                    self.state = ParserState::Osc {
+                        escape: ch == '\x1b',
+                    }
+                }
+            },
+        }
+    }
+
+    fn process_ground(&mut self, ch: char) {
+        // moving the cursor cancels a pending wrap, printing handles it
+        if matches!(ch, '\r' | '\n' | '\x0b' | '\x0c' | '\x08' | '\t') {
+            self.wrap_pending = false;
+        }
+        match ch {
+            '\x1b' => self.state = ParserState::Escape,
+            '\r' => self.cursor.col = 0,
+            '\n' | '\x0b' | '\x0c' => self.line_feed(),
+            '\x08' => self.cursor.col = self.cursor.col.saturating_sub(1),
+            '\t' => self.cursor.col = ((self.cursor.col / 8 + 1) * 8).min(self.width - 1),
+            ch if ch.is_control() => {}
+            ch => self.print(ch),
+        }
+    }
+
+    fn print(&mut self, ch: char) {
+        let width = match ch.width() {
+            Some(width) if width > 0 => width,
+            // combining characters and the like
+            _ => return,
+        };
+        if self.wrap_pending || self.cursor.col + width > self.width {
+            self.cursor.col = 0;
+            self.line_feed();
+            self.wrap_pending = false;
+        }
+        let Position { row, col } = self.cursor;
+        self.rows[row][col] = Cell {
+            ch,
+            style: self.style,
+        };
+        // the cells covered by a wide character are left empty
+        for col in col + 1..(col + width).min(self.width) {
+            self.rows[row][col] = Cell {
+                ch: '\0',
+                style: self.style,
+            };
+        }
+        self.cursor.col += width;
+        // stay at the end of the line until the next character wraps it
+        if self.cursor.col >= self.width {
+            self.cursor.col = self.width - 1;
+            self.wrap_pending = true;
+        }
+    }
+
+    fn line_feed(&mut self) {
+        if self.cursor.row + 1 < self.height() {
+            self.cursor.row += 1;
+        } else {
+            self.rows.remove(0);
+            self.rows.push(vec![Cell::default(); self.width]);
+        }
+    }
+
+    fn reverse_line_feed(&mut self) {
+        if self.cursor.row > 0 {
+            self.cursor.row -= 1;
+        } else {
+            self.rows.pop();
+            self.rows.insert(0, vec![Cell::default(); self.width]);
+        }
+    }
+
+    fn blank(&self) -> Cell {
+        // erased cells keep the background color
+        Cell {
+            ch: ' ',
+            style: Style {
+                bg: self.style.bg,
+                ..Style::default()
+            },
+        }
+    }
+
+    fn process_csi(&mut self, params: &str, action: char) {
+        // colors don't move the cursor, everything else cancels a pending wrap
+        if action != 'm' {
+            self.wrap_pending = false;
+        }
+        // private sequences like `ESC [ ? 25 h`
+        if let Some(params) = params.strip_prefix('?') {
+            if params == "25" {
+                match action {
+                    'h' => self.cursor_visible = true,
+                    'l' => self.cursor_visible = false,
+                    _ => {}
+                }
+            }
+            return;
+        }
+        let args: Vec<usize> = params
+            .split(';')
+            .map(|arg| arg.parse().unwrap_or(0))
+            .collect();
+        // missing or zero arguments default to 1 for movements
+        let count = args.first().copied().unwrap_or(0).max(1);
+        let (height, width) = (self.height(), self.width);
+        let blank = self.blank();
+        let Position { row, col } = self.cursor;
+
+        match action {
+            'A' => self.cursor.row = row.saturating_sub(count),
+            'B' | 'e' => self.cursor.row = (row + count).min(height - 1),
+            'C' | 'a' => self.cursor.col = (col + count).min(width - 1),
+            'D' => self.cursor.col = col.saturating_sub(count),
+            'E' => self.cursor = Position::new((row + count).min(height - 1), 0),
+            'F' => self.cursor = Position::new(row.saturating_sub(count), 0),
+            'G' | '`' => self.cursor.col = (count - 1).min(width - 1),
+            'd' => self.cursor.row = (count - 1).min(height - 1),
+            'H' | 'f' => {
+                let row = args.first().copied().unwrap_or(1).max(1) - 1;
+                let col = args.get(1).copied().unwrap_or(1).max(1) - 1;
+                self.cursor = Position::new(row.min(height - 1), col.min(width - 1));
+            }
+            'J' => {
+                let (start, end) = match args[0] {
+                    0 => ((row, col), (height, 0)),
+                    1 => ((0, 0), (row, col + 1)),
+                    _ => ((0, 0), (height, 0)),
+                };
+                for (r, line) in self.rows.iter_mut().enumerate() {
+                    for (c, cell) in line.iter_mut().enumerate() {
+                        if (r, c) >= start && (r, c) < end {
+                            *cell = blank;
+                        }
+                    }
+                }
+            }
+            'K' => {
+                let range = match args[0] {
+                    0 => col..width,
+                    1 => 0..col + 1,
+                    _ => 0..width,
+                };
+                for cell in &mut self.rows[row][range] {
+                    *cell = blank;
+                }
+            }
+            'L' => {
+                for _ in 0..count.min(height - row) {
+                    self.rows.pop();
+                    self.rows.insert(row, vec![blank; width]);
+                }
+            }
+            'M' => {
+                for _ in 0..count.min(height - row) {
+                    self.rows.remove(row);
+                    self.rows.push(vec![blank; width]);
+                }
+            }
+            'P' => {
+                let line = &mut self.rows[row];
+                for _ in 0..count.min(width - col) {
+                    line.remove(col);
+                    line.push(blank);
+                }
+            }
+            '@' => {
+                let line = &mut self.rows[row];
+                for _ in 0..count.min(width - col) {
+                    line.pop();
+                    line.insert(col, blank);
+                }
+            }
+            'X' => {
+                for cell in &mut self.rows[row][col..(col + count).min(width)] {
+                    *cell = blank;
+                }
+            }
+            'm' => self.select_graphic_rendition(&args),
+            _ => {}
+        }
+    }
+
+    fn select_graphic_rendition(&mut self, args: &[usize]) {
+        let mut args = args.iter().copied();
+        while let Some(arg) = args.next() {
+            let style = self.style;
+            self.style = match arg {
+                0 => Style::default(),
+                1 => style.add_modifier(Modifier::BOLD),
+                2 => style.add_modifier(Modifier::DIM),
+                3 => style.add_modifier(Modifier::ITALIC),
+                4 => style.add_modifier(Modifier::UNDERLINED),
+                5 => style.add_modifier(Modifier::SLOW_BLINK),
+                7 => style.add_modifier(Modifier::REVERSED),
+                8 => style.add_modifier(Modifier::HIDDEN),
+                9 => style.add_modifier(Modifier::CROSSED_OUT),
+                22 => style.remove_modifier(Modifier::BOLD | Modifier::DIM),
+                23 => style.remove_modifier(Modifier::ITALIC),
+                24 => style.remove_modifier(Modifier::UNDERLINED),
+                25 => style.remove_modifier(Modifier::SLOW_BLINK),
+                27 => style.remove_modifier(Modifier::REVERSED),
+                28 => style.remove_modifier(Modifier::HIDDEN),
+                29 => style.remove_modifier(Modifier::CROSSED_OUT),
+                30..=37 => style.fg(ansi_color(arg - 30)),
+                38 => match extended_color(&mut args) {
+                    Some(color) => style.fg(color),
+                    None => style,
+                },
+                39 => Style { fg: None, ..style },
+                40..=47 => style.bg(ansi_color(arg - 40)),
+                48 => match extended_color(&mut args) {
+                    Some(color) => style.bg(color),
+                    None => style,
+                },
+                49 => Style { bg: None, ..style },
+                90..=97 => style.fg(ansi_color(arg - 90 + 8)),
+                100..=107 => style.bg(ansi_color(arg - 100 + 8)),
+                _ => style,
+            };
+        }
+    }
+}
+
+fn ansi_color(index: usize) -> Color {
+    match index {
+        0 => Color::Black,
+        1 => Color::Red,
+        2 => Color::Green,
+        3 => Color::Yellow,
+        4 => Color::Blue,
+        5 => Color::Magenta,
+        6 => Color::Cyan,
+        7 => Color::Gray,
+        8 => Color::LightGray,
+        9 => Color::LightRed,
+        10 => Color::LightGreen,
+        11 => Color::LightYellow,
+        12 => Color::LightBlue,
+        13 => Color::LightMagenta,
+        14 => Color::LightCyan,
+        _ => Color::White,
+    }
+}
+
+/// Parses the rest of a `38;5;n` or `38;2;r;g;b` color.
+fn extended_color(args: &mut impl Iterator<Item = usize>) -> Option<Color> {
+    match args.next()? {
+        5 => Some(Color::Indexed(args.next()? as u8)),
+        2 => Some(Color::Rgb(
+            args.next()? as u8,
+            args.next()? as u8,
+            args.next()? as u8,
+        )),
+        _ => None,
+    }
+}
+
+/// The bytes a terminal sends for a key press.
+fn key_sequence(key: KeyEvent) -> Option<Vec<u8>> {
+    let mut bytes = Vec::new();
+    if key.modifiers.contains(KeyModifiers::ALT) {
+        bytes.push(0x1b);
+    }
+    let sequence: &[u8] = match key.code {
+        KeyCode::Char(ch) if key.modifiers.contains(KeyModifiers::CONTROL) => {
+            let byte = match ch.to_ascii_lowercase() {
+                ch @ 'a'..='z' => ch as u8 - b'a' + 1,
+                '@' | ' ' => 0,
+                '[' => 0x1b,
+                '\\' => 0x1c,
+                ']' => 0x1d,
+                '^' => 0x1e,
+                '_' => 0x1f,
+                _ => return None,
+            };
+            bytes.push(byte);
+            return Some(bytes);
+        }
+        KeyCode::Char(ch) => {
+            let mut buf = [0; 4];
+            bytes.extend_from_slice(ch.encode_utf8(&mut buf).as_bytes());
+            return Some(bytes);
+        }
+        KeyCode::Enter => b"\r",
+        KeyCode::Backspace => b"\x7f",
+        KeyCode::Tab if key.modifiers.contains(KeyModifiers::SHIFT) => b"\x1b[Z",
+        KeyCode::Tab => b"\t",
+        KeyCode::Esc => b"\x1b",
+        KeyCode::Up => b"\x1b[A",
+        KeyCode::Down => b"\x1b[B",
+        KeyCode::Right => b"\x1b[C",
+        KeyCode::Left => b"\x1b[D",
+        KeyCode::Home => b"\x1b[H",
+        KeyCode::End => b"\x1b[F",
+        KeyCode::Insert => b"\x1b[2~",
+        KeyCode::Delete => b"\x1b[3~",
+        KeyCode::PageUp => b"\x1b[5~",
+        KeyCode::PageDown => b"\x1b[6~",
+        KeyCode::F(1) => b"\x1bOP",
+        KeyCode::F(2) => b"\x1bOQ",
+        KeyCode::F(3) => b"\x1bOR",
+        KeyCode::F(4) => b"\x1bOS",
+        KeyCode::F(n @ 5..=12) => {
+            let code = [15, 17, 18, 19, 20, 21, 23, 24][n as usize - 5];
+            bytes.extend_from_slice(format!("\x1b[{}~", code).as_bytes());
+            return Some(bytes);
+        }
+        _ => return None,
+    };
+    bytes.extend_from_slice(sequence);
+    Some(bytes)
+}
+
+/// A shell running in a pseudo terminal, drawn in a panel below the editor.
+pub struct Terminal {
+    screen: Arc<Mutex<Screen>>,
+    /// The controlling side of the pseudo terminal.
+    pty: File,
+    /// The input for the shell, written to the pseudo terminal by a thread of its own
+    /// since the shell may not read it right away.
+    input: Sender<Vec<u8>>,
+    child: Child,
+    exited: Arc<AtomicBool>,
+    focused: bool,
+    /// The area the screen was last drawn in.
+    area: Rect,
+}
+
+impl Terminal {
+    /// Starts the user's shell in `cwd`, `redraw` is notified whenever there is new output.
+    pub fn new(shell: &str, cwd: &Path, redraw: Arc<Notify>) -> io::Result<Self> {
+        let (width, height) = (80, 24);
+        let (pty, child) = pty::spawn(shell, cwd, width, height)?;
+        let screen = Arc::new(Mutex::new(Screen::new(width, height)));
+        let exited = Arc::new(AtomicBool::new(false));
+
+        let mut reader = pty.try_clone()?;
+        let (output, finished) = (screen.clone(), exited.clone());
+        std::thread::spawn(move || {
+            let mut buf = [0; 4096];
+            // reading fails with EIO once the shell exited
+            while let Ok(n @ 1..) = reader.read(&mut buf) {
+                output.lock().unwrap().process(&buf[..n]);
+                redraw.notify_one();
+            }
+            finished.store(true, Ordering::Relaxed);
+            redraw.notify_one();
+        });
+
+        let mut writer = pty.try_clone()?;
+        let (input, keys) = mpsc::channel::<Vec<u8>>();
+        // ends once the terminal is dropped, or the write fails and the keys are dropped
+        std::thread::spawn(move || {
+            for bytes in keys {
+                if let Err(err) = writer.write_all(&bytes) {
+                    log::error!("Failed to write to the terminal: {}", err);
+                    break;
+                }
+            }
+        });
+
+        Ok(Self {
+            screen,
+            pty,
+            input,
+            child,
+            exited,
+            focused: true,
+            area: Rect::new(0, 0, width, height),
+        })
+    }
+
+    /// Whether the shell exited, the terminal should be closed then.
+    pub fn has_exited(&self) -> bool {
+        self.exited.load(Ordering::Relaxed)
+    }
+
+    pub fn is_focused(&self) -> bool {
+        self.focused
+    }
+
+    pub fn focus(&mut self) {
+        self.focused = true;
+    }
+
+    pub fn unfocus(&mut self) {
+        self.focused = false;
+    }
+
+    fn write(&mut self, bytes: &[u8], cx: &mut Context) {
+        if self.input.send(bytes.to_vec()).is_err() {
+            cx.editor
+                .set_error("The terminal does not take input anymore");
+        }
+    }
+}
+
+impl Drop for Terminal {
+    fn drop(&mut self) {
+        // closing the pty hangs up the shell, kill it in case it ignores that
+        let _ = self.child.kill();
+        let _ = self.child.wait();
+    }
+}
+
+impl Component for Terminal {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        if !self.focused {
+            return EventResult::Ignored(None);
+        }
+        match event {
+            Event::Key(key) if *key == UNFOCUS_KEY => self.unfocus(),
+            Event::Key(key) => {
+                if let Some(bytes) = key_sequence(*key) {
+                    self.write(&bytes, cx);
+                }
+            }
+            Event::Paste(contents) => {
+                let contents = format!("\x1b[200~{}\x1b[201~", contents);
+                self.write(contents.as_bytes(), cx);
+            }
+            _ => return EventResult::Ignored(None),
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        let title_style = if self.focused {
+            theme.get("ui.statusline")
+        } else {
+            theme.get("ui.statusline.inactive")
+        };
+        let title_area = area.with_height(1.min(area.height));
+        surface.clear_with(title_area, title_style);
+        surface.set_stringn(
+            area.x + 1,
+            area.y,
+            "Terminal",
+            area.width.saturating_sub(1) as usize,
+            title_style,
+        );
+
+        let area = area.clip_top(1);
+        if area.width == 0 || area.height == 0 {
+            return;
+        }
+        let background = theme
+            .try_get("ui.terminal")
+            .unwrap_or_else(|| theme.get("ui.background"));
+        surface.clear_with(area, background);
+
+        let mut screen = self.screen.lock().unwrap();
+        if (self.area.width, self.area.height) != (area.width, area.height) {
+            screen.resize(area.width, area.height);
+            if let Err(err) = pty::resize(&self.pty, area.width, area.height) {
+                log::warn!("failed to resize the terminal: {}", err);
+            }
+        }
+        self.area = area;
+
+        for (y, row) in screen.rows.iter().enumerate() {
+            for (x, cell) in row.iter().enumerate() {
+                // skip the cells covered by wide characters
+                if cell.ch == '\0' {
+                    continue;
+                }
+                let (x, y) = (area.x + x as u16, area.y + y as u16);
+                surface[(x, y)]
+                    .set_char(cell.ch)
+                    .set_style(background.patch(cell.style));
+            }
+        }
+    }
+
+    fn cursor(&self, _area: Rect, _editor: &Editor) -> (Option<Position>, CursorKind) {
+        let screen = self.screen.lock().unwrap();
+        if !self.focused || !screen.cursor_visible {
+            return (None, CursorKind::Hidden);
+        }
+        let position = Position::new(
+            self.area.y as usize + screen.cursor.row,
+            self.area.x as usize + screen.cursor.col,
+        );
+        (Some(position), CursorKind::Block)
+    }
+}
+
+#[cfg(unix)]
+mod pty {
+    use std::{
+        fs::File,
+        io,
+        os::unix::{
+            io::{AsRawFd, FromRawFd},
+            process::CommandExt,
+        },
+        path::Path,
+        process::{Child, Command},
+        ptr,
+    };
+
+    fn size(width: u16, height: u16) -> libc::winsize {
+        libc::winsize {
+            ws_row: height,
+            ws_col: width,
+            ws_xpixel: 0,
+            ws_ypixel: 0,
+        }
+    }
+
+    fn check(result: libc::c_int) -> io::Result<libc::c_int> {
+        if result == -1 {
+            Err(io::Error::last_os_error())
+        } else {
+            Ok(result)
+        }
+    }
+
+    /// Starts `shell` with a new pseudo terminal as its controlling terminal and returns
+    /// the controlling side of the pseudo terminal.
+    pub fn spawn(shell: &str, cwd: &Path, width: u16, height: u16) -> io::Result<(File, Child)> {
+        let (mut master, mut slave) = (0, 0);
+        let mut size = size(width, height);
+        // SAFETY: the pointers are valid for the duration of the call
+        check(unsafe {
+            libc::openpty(
+                &mut master,
+                &mut slave,
+                ptr::null_mut(),
+                ptr::null_mut(),
+                &mut size,
+            )
+        })?;
+        // SAFETY: openpty returned two open file descriptors that nothing else owns
+        let (master, slave) = unsafe { (File::from_raw_fd(master), File::from_raw_fd(slave)) };
+        // SAFETY: the file descriptor is open
+        check(unsafe { libc::fcntl(master.as_raw_fd(), libc::F_SETFD, libc::FD_CLOEXEC) })?;
+
+        let mut command = Command::new(shell);
+        command
+            .current_dir(cwd)
+            .env("TERM", "xterm-256color")
+            .stdin(slave.try_clone()?)
+            .stdout(slave.try_clone()?)
+            .stderr(slave);
+        // SAFETY: only async-signal-safe functions are called between fork and exec
+        unsafe {
+            command.pre_exec(|| {
+                // start a new session and make the pseudo terminal, which is stdin by now,
+                // its controlling terminal
+                check(libc::setsid())?;
+                check(libc::ioctl(0, libc::TIOCSCTTY as _, 0))?;
+                Ok(())
+            });
+        }
+        let child = command.spawn()?;
+        Ok((master, child))
+    }
+
+    pub fn resize(pty: &File, width: u16, height: u16) -> io::Result<()> {
+        let size = size(width, height);
+        // SAFETY: the file descriptor is open and the size is valid for the duration of the call
+        check(unsafe { libc::ioctl(pty.as_raw_fd(), libc::TIOCSWINSZ, &size as *const _) })?;
+        Ok(())
+    }
+}
+
+#[cfg(not(unix))]
+mod pty {
+    use std::{fs::File, io, path::Path, process::Child};
+
+    pub fn spawn(
+        _shell: &str,
+        _cwd: &Path,
+        _width: u16,
+        _height: u16,
+    ) -> io::Result<(File, Child)> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "the terminal is only supported on unix",
+        ))
+    }
+
+    pub fn resize(_pty: &File, _width: u16, _height: u16) -> io::Result<()> {
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn text(screen: &Screen) -> Vec<String> {
+        screen
+            .rows
+            .iter()
+            .map(|row| row.iter().map(|cell| cell.ch).collect::<String>())
+            .map(|row| row.trim_end().to_string())
+            .collect()
+    }
+
+    #[test]
+    fn test_screen() {
+        let mut screen = Screen::new(10, 3);
+        screen.process(b"hello\r\nworld");
+        assert_eq!(text(&screen), ["hello", "world", ""]);
+        assert_eq!(screen.cursor, Position::new(1, 5));
+
+        // cursor movements and erasing
+        screen.process(b"\x1b[1;2H\x1b[K\x1b[2Bx");
+        assert_eq!(text(&screen), ["h", "world", " x"]);
+
+        // lines past the bottom scroll the screen
+        screen.process(b"\r\nnext");
+        assert_eq!(text(&screen), ["world", " x", "next"]);
+
+        // long lines wrap
+        screen.process(b"\x1b[2J\x1b[H0123456789ab");
+        assert_eq!(text(&screen), ["0123456789", "ab", ""]);
+    }
+
+    #[test]
+    fn test_graphic_rendition() {
+        let mut screen = Screen::new(10, 1);
+        // sequences split across reads and multibyte characters are kept for the next one
+        screen.process(b"\x1b[1;3");
+        screen.process(b"1m\xc3");
+        screen.process(b"\xa9\x1b[0m.");
+        let styled = Style::default().fg(Color::Red).add_modifier(Modifier::BOLD);
+        assert_eq!(
+            screen.rows[0][0],
+            Cell {
+                ch: 'é',
+                style: styled
+            }
+        );
+        assert_eq!(screen.rows[0][1].style, Style::default());
+    }
+}
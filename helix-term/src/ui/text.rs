@@ -3,6 +3,15 @@
 
 use helix_view::graphics::Rect;
 
+/// `text` on a single line, cut to `max_len` characters.
+pub fn one_line(text: &str, max_len: usize) -> String {
+    let text = text.replace('\n', "⏎");
+    match text.char_indices().nth(max_len) {
+        Some((end, _)) => format!("{}…", &text[..end]),
+        None => text,
+    }
+}
+
 pub struct Text {
     pub(crate) contents: tui::text::Text<'static>,
     size: (u16, u16),
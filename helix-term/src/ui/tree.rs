@@ -7,7 +7,7 @@
     compositor::{Context, EventResult},
     ctrl, key, shift,
 };
-use helix_core::unicode::width::UnicodeWidthStr;
+use helix_core::{unicode::width::UnicodeWidthStr, Position};
 use helix_view::{
     graphics::Rect,
     input::{Event, KeyEvent},
@@ -34,6 +34,12 @@ fn filter(&self, cx: &mut Context, s: &str, params: &mut Self::Params) -> bool {
     fn get_childs(&self) -> Result<Vec<Self>> {
         Ok(vec![])
     }
+
+    /// Whether expanding the tree to a depth opens the item, besides the ones with folded
+    /// children.
+    fn is_expandable(&self, _cx: &Context) -> bool {
+        false
+    }
 }
 
 fn tree_item_cmp<T: TreeItem>(item1: &T, item2: &T) -> Ordering {
@@ -100,6 +106,108 @@ fn get_elems_recursion<T: TreeItem>(t: &mut Elem<T>, depth: usize) -> Result<usi
     Ok(total)
 }
 
+/// Whether `item` is shown before `bound`, or there is no bound.
+fn precedes<T: TreeItem>(item: &T, bound: Option<&T>) -> bool {
+    bound.map_or(true, |bound| tree_item_cmp(item, bound) == Ordering::Less)
+}
+
+/// Adds the sorted `items` to `elems`, at `level` and below, each one under its parent,
+/// folded or not. The descendants of `parent` before `until` are taken, or all the items
+/// for the top level without a parent. The items already in `elems` are only kept once.
+/// Returns the new index of `elems[selected]`.
+fn merge_elems<T, I>(
+    elems: &mut Vec<Elem<T>>,
+    items: &mut Peekable<I>,
+    parent: Option<&T>,
+    until: Option<&T>,
+    level: usize,
+    selected: Option<usize>,
+) -> Option<usize>
+where
+    T: TreeItem,
+    I: Iterator<Item = T>,
+{
+    let mut moved = None;
+    let mut taking = true;
+    // the indices of the ancestors of the last elem in `elems`
+    let mut ancestors: Vec<usize> = Vec::new();
+    let mut old = std::mem::take(elems).into_iter().enumerate().peekable();
+    loop {
+        let bound = old.peek().map(|(_, elem)| &elem.item).or(until);
+        if taking && items.peek().map_or(false, |item| precedes(item, bound)) {
+            let item = items.peek().unwrap();
+            let item_level = loop {
+                match ancestors.last().copied() {
+                    Some(index) if item.is_child(&elems[index].item) => {
+                        break Some(elems[index].level + 1)
+                    }
+                    Some(_) => ancestors.pop(),
+                    None => match parent {
+                        Some(parent) if !item.is_child(parent) => break None,
+                        _ => break Some(level),
+                    },
+                };
+            };
+            match item_level {
+                Some(item_level) => {
+                    ancestors.push(elems.len());
+                    elems.push(Elem::new(items.next().unwrap(), item_level));
+                }
+                // the items left are shown after the parent
+                None => taking = false,
+            }
+            continue;
+        }
+
+        let (index, mut elem) = match old.next() {
+            Some(next) => next,
+            None => break,
+        };
+        while items.peek().map_or(false, |item| {
+            tree_item_cmp(item, &elem.item) == Ordering::Equal
+        }) {
+            items.next();
+        }
+        if taking && !elem.folded.is_empty() {
+            let until = old.peek().map(|(_, elem)| &elem.item).or(until);
+            let mut folded = std::mem::take(&mut elem.folded);
+            merge_elems(
+                &mut folded,
+                items,
+                Some(&elem.item),
+                until,
+                elem.level + 1,
+                None,
+            );
+            elem.folded = folded;
+        }
+        while ancestors
+            .last()
+            .map_or(false, |&ancestor| elems[ancestor].level >= elem.level)
+        {
+            ancestors.pop();
+        }
+        ancestors.push(elems.len());
+        if Some(index) == selected {
+            moved = Some(elems.len());
+        }
+        elems.push(elem);
+    }
+    moved
+}
+
+/// Where `item` goes among the items at `level` of `elems` from `start`, which ends at
+/// the first item of a lower level.
+fn insert_pos<T: TreeItem>(elems: &[Elem<T>], start: usize, level: usize, item: &T) -> usize {
+    elems[start..]
+        .iter()
+        .position(|elem| {
+            elem.level < level
+                || (elem.level == level && tree_item_cmp(&elem.item, item) == Ordering::Greater)
+        })
+        .map_or(elems.len(), |pos| pos + start)
+}
+
 fn expand_elems<T: TreeItem>(dist: &mut Vec<Elem<T>>, mut t: Elem<T>) {
     let childs = std::mem::take(&mut t.folded);
     dist.push(t);
@@ -152,10 +260,16 @@ pub struct Tree<T: TreeItem> {
     selected: usize,           // select item index
     save_view: (usize, usize), // (selected, row)
     winline: usize,            // view row
+    rendered: (Rect, usize),   // area and first item of the last render
     col: usize,
     max_len: usize,
     count: usize,
+    /// The count of a `z` waiting for an `E` to expand the tree as many levels deep.
+    pending_depth: Option<usize>,
     tree_symbol_style: String,
+    /// The prefix of the scopes the indent guides of each depth are drawn with, numbered
+    /// from 1 and cycled through as far as the theme has them.
+    guides_scope: Option<&'static str>,
     #[allow(clippy::type_complexity)]
     pre_render: Option<Box<dyn Fn(&mut Self, Rect) + 'static>>,
     #[allow(clippy::type_complexity)]
@@ -175,10 +289,13 @@ pub fn new(items: Vec<Elem<T>>) -> Self {
             selected: 0,
             save_view: (0, 0),
             winline: 0,
+            rendered: (Rect::default(), 0),
             col: 0,
             max_len: 0,
             count: 0,
+            pending_depth: None,
             tree_symbol_style: "ui.text".into(),
+            guides_scope: None,
             pre_render: None,
             on_opened_fn: None,
             on_folded_fn: None,
@@ -191,6 +308,7 @@ pub fn replace_with_new_items(&mut self, items: Vec<T>) {
         self.on_opened_fn = old.on_opened_fn;
         self.on_folded_fn = old.on_folded_fn;
         self.tree_symbol_style = old.tree_symbol_style;
+        self.guides_scope = old.guides_scope;
     }
 
     pub fn build_tree(items: Vec<T>) -> Self {
@@ -226,6 +344,12 @@ pub fn tree_symbol_style(mut self, style: String) -> Self {
         self
     }
 
+    /// Draws the indent guides of each depth with the next of the scopes `scope.1`,
+    /// `scope.2`... of the theme, or all of them with the tree symbol style for `None`.
+    pub fn set_guides_scope(&mut self, scope: Option<&'static str>) {
+        self.guides_scope = scope;
+    }
+
     fn next_item(&self) -> Option<&Elem<T>> {
         self.items.get(self.selected + 1)
     }
@@ -236,6 +360,12 @@ fn next_not_descendant_pos(&self, index: usize) -> usize {
             .unwrap_or(self.items.len())
     }
 
+    fn is_expanded(&self, index: usize) -> bool {
+        self.items
+            .get(index + 1)
+            .map_or(false, |next| next.level > self.items[index].level)
+    }
+
     fn find_parent(&self, index: usize) -> Option<usize> {
         let item = &self.items[index];
         self.find(index, true, |p| p.level < item.level)
@@ -312,6 +442,148 @@ pub fn on_enter(&mut self, cx: &mut Context, params: &mut T::Params) {
         }
     }
 
+    /// Shows the children of the current item, unless they are shown already.
+    pub fn expand_current(&mut self, cx: &mut Context, params: &mut T::Params) {
+        if !self.items.is_empty() && !self.is_expanded(self.selected) {
+            self.on_enter(cx, params);
+        }
+    }
+
+    /// Shows the descendants of the current item down to `depth` levels below it.
+    pub fn expand_to_depth(&mut self, depth: usize, cx: &mut Context, params: &mut T::Params) {
+        if self.items.is_empty() {
+            return;
+        }
+        let selected = self.selected;
+        let level = self.items[selected].level;
+        let mut index = selected;
+        // the children are inserted right after their parent, to be expanded in turn
+        while index < self.items.len() {
+            let elem = &self.items[index];
+            if index > selected && elem.level <= level {
+                break;
+            }
+            let expandable = !elem.folded.is_empty() || elem.item.is_expandable(cx);
+            if elem.level < level + depth && expandable && !self.is_expanded(index) {
+                self.selected = index;
+                self.on_enter(cx, params);
+            }
+            index += 1;
+        }
+        self.selected = selected;
+    }
+
+    /// The items whose children are shown.
+    pub fn expanded_items(&self) -> impl Iterator<Item = &T> {
+        (0..self.items.len())
+            .filter(|&index| self.is_expanded(index))
+            .map(|index| &self.items[index].item)
+    }
+
+    /// Whether the children of the first shown item matching `f` were added to the tree,
+    /// shown or folded.
+    pub fn lists_children<F>(&self, mut f: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        match self.items.iter().position(|elem| f(&elem.item)) {
+            Some(index) => self.is_expanded(index) || !self.items[index].folded.is_empty(),
+            None => false,
+        }
+    }
+
+    /// Adds many `items` to the tree at once, each one under its parent, keeping the
+    /// selected item and the folded items folded. Only the new items are sorted, the
+    /// tree is walked once to merge them. The items already in the tree are only kept
+    /// once.
+    pub fn extend(&mut self, mut items: Vec<T>) {
+        items.sort_by(tree_item_cmp);
+        items.dedup_by(|a, b| tree_item_cmp(a, b) == Ordering::Equal);
+        let mut items = items.into_iter().peekable();
+        let selected = Some(self.selected);
+        let selected = merge_elems(&mut self.items, &mut items, None, None, 0, selected);
+        self.selected = selected.unwrap_or(0);
+    }
+
+    /// Adds `item` among the children of its parent, shown or folded, or at the top
+    /// level without one. Returns `false` when its parent is itself folded in another
+    /// item. Cheaper than [Self::extend] for a few items.
+    pub fn insert(&mut self, item: T) -> bool {
+        let parent = self.items.iter().position(|elem| item.is_child(&elem.item));
+        let (index, level) = match parent {
+            Some(parent) => (parent, self.items[parent].level + 1),
+            None if self
+                .items
+                .iter()
+                .any(|elem| elem.folded.iter().any(|e| item.is_child(&e.item))) =>
+            {
+                return false
+            }
+            None => {
+                let pos = insert_pos(&self.items, 0, 0, &item);
+                if pos <= self.selected && !self.items.is_empty() {
+                    self.selected += 1;
+                }
+                self.items.insert(pos, Elem::new(item, 0));
+                return true;
+            }
+        };
+        if !self.items[index].folded.is_empty() {
+            let folded = &mut self.items[index].folded;
+            let pos = insert_pos(folded, 0, level, &item);
+            folded.insert(pos, Elem::new(item, level));
+        } else {
+            let pos = insert_pos(&self.items, index + 1, level, &item);
+            if pos <= self.selected {
+                self.selected += 1;
+            }
+            self.items.insert(pos, Elem::new(item, level));
+        }
+        true
+    }
+
+    /// Removes the items matching `f` with their children, shown or folded. The
+    /// selection moves up when the selected item is removed.
+    pub fn remove_where<F>(&mut self, mut f: F)
+    where
+        F: FnMut(&T) -> bool,
+    {
+        let mut index = 0;
+        while index < self.items.len() {
+            if !f(&self.items[index].item) {
+                self.items[index].folded.retain(|elem| !f(&elem.item));
+                index += 1;
+                continue;
+            }
+            let end = self.next_not_descendant_pos(index);
+            self.items.drain(index..end);
+            if self.selected >= end {
+                self.selected -= end - index;
+            } else if self.selected >= index {
+                self.selected = index.saturating_sub(1);
+            }
+        }
+    }
+
+    /// All the items, in the order they are shown.
+    pub fn items(&self) -> impl Iterator<Item = &T> {
+        self.items.iter().map(|elem| &elem.item)
+    }
+
+    /// Selects the first item matching `f`, returns whether there was one.
+    pub fn select<F>(&mut self, mut f: F) -> bool
+    where
+        F: FnMut(&T) -> bool,
+    {
+        match self.items.iter().position(|elem| f(&elem.item)) {
+            Some(index) => {
+                self.selected = index;
+                true
+            }
+            None => false,
+        }
+    }
+
     pub fn fold_current_level(&mut self) {
         let start = match self.find_parent(self.selected) {
             Some(start) => start,
@@ -420,6 +692,39 @@ pub fn row(&self) -> usize {
         self.winline
     }
 
+    pub fn selected_index(&self) -> usize {
+        self.selected
+    }
+
+    /// Whether the children of the selected item are shown.
+    pub fn is_current_expanded(&self) -> bool {
+        self.is_expanded(self.selected)
+    }
+
+    /// The screen position of the start of the selected row, as of the last render.
+    pub fn selected_position(&self) -> Option<Position> {
+        let (area, skip) = self.rendered;
+        let row = self.selected.checked_sub(skip)?;
+        (row < area.height as usize).then(|| Position::new(area.y as usize + row, area.x as usize))
+    }
+
+    /// Selects the item at `index`, keeping the other items where they are shown.
+    pub fn select_index(&mut self, index: usize) {
+        if index < self.items.len() {
+            self.selected = index;
+            self.winline = index.saturating_sub(self.rendered.1);
+        }
+    }
+
+    /// The index of the item shown at the screen position, as of the last render.
+    pub fn index_at(&self, row: u16, column: u16) -> Option<usize> {
+        let (area, skip) = self.rendered;
+        let within = (area.top()..area.bottom()).contains(&row)
+            && (area.left()..area.right()).contains(&column);
+        let index = skip + (row.checked_sub(area.y)? as usize);
+        (within && index < self.items.len()).then(|| index)
+    }
+
     pub fn remove_current(&mut self) -> T {
         let elem = self.items.remove(self.selected);
         self.selected = self.selected.saturating_sub(1);
@@ -471,8 +776,16 @@ pub fn render(
         self.max_len = 0;
         self.winline = std::cmp::min(self.winline, area.height.saturating_sub(1) as usize);
         let style = cx.editor.theme.get(&self.tree_symbol_style);
+        let palette: Vec<_> = match self.guides_scope {
+            Some(scope) => (1..)
+                .map_while(|depth| cx.editor.theme.try_get(&format!("{scope}.{depth}")))
+                .map(|guide| style.patch(guide))
+                .collect(),
+            None => Vec::new(),
+        };
         let last_item_index = self.items.len().saturating_sub(1);
         let skip = self.selected.saturating_sub(self.winline);
+        self.rendered = (area, skip);
         let iter = self
             .items
             .iter()
@@ -482,27 +795,38 @@ pub fn render(
         for (index, elem) in iter {
             let row = index as u16;
             let mut area = Rect::new(area.x, area.y + row, area.width, 1);
-            let indent = if elem.level > 0 {
-                if index + skip != last_item_index {
-                    format!("{}├─", "│ ".repeat(elem.level - 1))
-                } else {
-                    format!("└─{}", "┴─".repeat(elem.level - 1))
-                }
+            // the guide of each depth, the last one leading to the item
+            let guides = if elem.level == 0 {
+                Vec::new()
+            } else if index + skip != last_item_index {
+                let mut guides = vec!["│ "; elem.level - 1];
+                guides.push("├─");
+                guides
             } else {
-                "".to_string()
+                let mut guides = vec!["└─"];
+                guides.extend(vec!["┴─"; elem.level - 1]);
+                guides
             };
 
-            let indent_len = indent.chars().count();
-            if indent_len > self.col {
-                let indent: String = indent.chars().skip(self.col).collect();
-                if !indent.is_empty() {
-                    surface.set_stringn(area.x, area.y, &indent, area.width as usize, style);
-                    area = area.clip_left(indent.width() as u16);
+            let mut skipped = self.col;
+            for (depth, guide) in guides.iter().enumerate() {
+                let guide: String = guide.chars().skip(skipped).collect();
+                skipped = skipped.saturating_sub(2);
+                if guide.is_empty() {
+                    continue;
                 }
-            };
+                let style = match palette.len() {
+                    0 => style,
+                    n => palette[depth % n],
+                };
+                surface.set_stringn(area.x, area.y, &guide, area.width as usize, style);
+                area = area.clip_left(guide.width() as u16);
+            }
+            let indent_len = guides.len() * 2;
             let mut start_index = self.col.saturating_sub(indent_len);
             let mut text = elem.item.text(cx, skip + index == self.selected, params);
-            self.max_len = self.max_len.max(text.width() + indent.len());
+            let indent_bytes: usize = guides.iter().map(|guide| guide.len()).sum();
+            self.max_len = self.max_len.max(text.width() + indent_bytes);
             for span in text.0.iter_mut() {
                 if area.width == 0 {
                     return;
@@ -559,10 +883,18 @@ pub fn handle_event(
             return EventResult::Consumed(None);
         }
         let count = std::mem::replace(&mut self.count, 0);
+        if let Some(depth) = self.pending_depth.take() {
+            match key_event.into() {
+                shift!('E') => self.expand_to_depth(depth, cx, params),
+                _ => self.fold_current_level(),
+            }
+            return EventResult::Consumed(None);
+        }
         match key_event.into() {
             key!(i @ '0'..='9') => self.count = i.to_digit(10).unwrap() as usize + count * 10,
             key!('k') | shift!(Tab) | key!(Up) | ctrl!('k') => self.move_up(1.max(count)),
             key!('j') | key!(Tab) | key!(Down) | ctrl!('j') => self.move_down(1.max(count)),
+            key!('z') if count > 0 => self.pending_depth = Some(count),
             key!('z') => self.fold_current_level(),
             ctrl!('h') => self.move_left(1.max(count)),
             ctrl!('l') => self.move_right(1.max(count)),
@@ -680,3 +1012,66 @@ pub fn restore_recycle(&mut self) {
         }
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[derive(Clone, Copy, Debug, PartialEq)]
+    struct Path(&'static str);
+
+    impl TreeItem for Path {
+        type Params = ();
+
+        fn text(&self, _cx: &mut Context, _selected: bool, _params: &mut ()) -> Spans {
+            Spans::from(self.0)
+        }
+
+        fn is_child(&self, other: &Self) -> bool {
+            self.0
+                .rsplit_once('/')
+                .map_or(false, |(parent, _)| parent == other.0)
+        }
+
+        fn cmp(&self, other: &Self) -> Ordering {
+            self.0.cmp(other.0)
+        }
+    }
+
+    fn paths<'a>(elems: impl IntoIterator<Item = &'a Elem<Path>>) -> Vec<(&'static str, usize)> {
+        elems
+            .into_iter()
+            .map(|elem| (elem.item.0, elem.level))
+            .collect()
+    }
+
+    #[test]
+    fn extend_under_parents() {
+        let items = ["a", "a/x", "b", "b/y", "c"].map(Path).to_vec();
+        let mut tree = Tree::build_tree(items);
+        tree.fold_current_child();
+        assert!(tree.select(|item| item.0 == "c"));
+
+        let items = ["d", "c/q", "b/w", "a/z", "b/y", "a/x/r"]
+            .map(Path)
+            .to_vec();
+        tree.extend(items);
+        assert_eq!(
+            paths(&tree.items),
+            [
+                ("a", 0),
+                ("b", 0),
+                ("b/w", 1),
+                ("b/y", 1),
+                ("c", 0),
+                ("c/q", 1),
+                ("d", 0)
+            ]
+        );
+        assert_eq!(
+            paths(&tree.items[0].folded),
+            [("a/x", 1), ("a/x/r", 2), ("a/z", 1)]
+        );
+        assert_eq!(tree.current_item().0, "c");
+    }
+}
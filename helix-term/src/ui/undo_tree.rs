@@ -0,0 +1,347 @@
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{
+    cmp::Ordering,
+    time::{Duration, Instant},
+};
+
+use helix_core::{history::History, Rope};
+use helix_view::{
+    graphics::{Modifier, Rect},
+    DocumentId, Editor, Theme,
+};
+
+use super::{
+    one_line,
+    sidebar::{self, TreePanel},
+    Tree, TreeItem,
+};
+
+/// Characters of the old and new text of a change shown in the preview, longer ones are cut.
+const MAX_HUNK_LEN: usize = 80;
+
+/// A row of the panel: a revision of the history, the last of its key. The first edit made
+/// on top of a revision continues its line, the later ones start branches nested in it.
+#[derive(Debug, Clone, PartialEq, Eq)]
+struct RevisionItem {
+    key: Vec<usize>,
+}
+
+impl RevisionItem {
+    fn revision(&self) -> usize {
+        self.key[self.key.len() - 1]
+    }
+}
+
+struct Revisions {
+    /// When each revision was committed.
+    timestamps: Vec<Instant>,
+    current: usize,
+}
+
+impl TreeItem for RevisionItem {
+    type Params = Revisions;
+
+    fn text(&self, cx: &mut Context, selected: bool, revisions: &mut Revisions) -> Spans {
+        let theme = &cx.editor.theme;
+        let revision = self.revision();
+        let current = revisions.current == revision;
+        let style = |scope: &str| {
+            let mut style = theme.get(scope);
+            if current {
+                style = style.add_modifier(Modifier::BOLD);
+            }
+            if selected {
+                style = style.patch(theme.get("ui.menu.selected"));
+            }
+            style
+        };
+        let label = if revision == 0 {
+            "original".to_string()
+        } else {
+            format!("#{}", revision)
+        };
+        let marker = if current { "● " } else { "  " };
+        let age = format_age(revisions.timestamps[revision].elapsed());
+        Spans::from(vec![
+            Span::styled(marker, style("ui.text.focus")),
+            Span::styled(label, style("ui.text")),
+            Span::styled(format!("  {} ago", age), style("ui.text.inactive")),
+        ])
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        self.key.len() == other.key.len() + 1 && self.key.starts_with(&other.key)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key.cmp(&other.key)
+    }
+}
+
+fn format_age(age: Duration) -> String {
+    match age.as_secs() {
+        secs if secs < 60 => format!("{}s", secs),
+        secs if secs < 60 * 60 => format!("{}m", secs / 60),
+        secs if secs < 24 * 60 * 60 => format!("{}h", secs / (60 * 60)),
+        secs => format!("{}d", secs / (24 * 60 * 60)),
+    }
+}
+
+/// The items of the revisions of `history`, parents being committed before their children.
+fn items(history: &History) -> Vec<RevisionItem> {
+    let count = history.revision_count();
+    let mut items: Vec<RevisionItem> = Vec::with_capacity(count);
+    let mut continued = vec![false; count];
+    for revision in 0..count {
+        let key = match history.parent(revision) {
+            Some(parent) => {
+                let mut key = items[parent].key.clone();
+                if !std::mem::replace(&mut continued[parent], true) {
+                    key.pop();
+                }
+                key.push(revision);
+                key
+            }
+            None => vec![revision],
+        };
+        items.push(RevisionItem { key });
+    }
+    items
+}
+
+/// A change of a revision to the text of its parent, as shown in the preview.
+struct Hunk {
+    /// 0 indexed line the change starts at.
+    line: usize,
+    old: String,
+    new: String,
+}
+
+/// The changes `revision` made to the text of its parent, `text` being the one of the
+/// current revision.
+fn hunks(history: &History, text: &Rope, revision: usize) -> Vec<Hunk> {
+    let parent = match history.parent(revision) {
+        Some(parent) => parent,
+        None => return Vec::new(),
+    };
+    // the text has changes yet to be committed while editing, which the revisions don't
+    // apply to
+    let mut parent_text = text.clone();
+    for transaction in history.path_to(parent) {
+        if !transaction.apply(&mut parent_text) {
+            return Vec::new();
+        }
+    }
+    let transaction = history.transaction(revision);
+    if !transaction.apply(&mut parent_text.clone()) {
+        return Vec::new();
+    }
+    transaction
+        .changes_iter()
+        .map(|(from, to, fragment)| Hunk {
+            line: parent_text.char_to_line(from),
+            old: one_line(&parent_text.slice(from..to).to_string(), MAX_HUNK_LEN),
+            new: fragment
+                .as_deref()
+                .map(|text| one_line(text, MAX_HUNK_LEN))
+                .unwrap_or_default(),
+        })
+        .collect()
+}
+
+/// The undo history of the focused document as a tree next to the views, with the changes
+/// of the selected revision below it. The keys go to the views while the panel isn't
+/// focused, running `undo_tree` focuses it again.
+pub struct UndoTree {
+    tree: Tree<RevisionItem>,
+    revisions: Revisions,
+    /// The document of the history and the number of its revisions.
+    shown: Option<(DocumentId, usize)>,
+    /// The changes of the selected revision, by the revision and the current one.
+    preview: Option<((usize, usize), Vec<Hunk>)>,
+    focused: bool,
+    area: Rect,
+}
+
+impl Default for UndoTree {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl UndoTree {
+    pub const ID: &'static str = "undo-tree";
+
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::build_tree(Vec::new()),
+            revisions: Revisions {
+                timestamps: Vec::new(),
+                current: 0,
+            },
+            shown: None,
+            preview: None,
+            focused: true,
+            area: Rect::default(),
+        }
+    }
+
+    /// Shows the history of the focused document if it changed, and the changes of the
+    /// selected revision.
+    fn update(&mut self, editor: &Editor) {
+        let doc = doc!(editor);
+        let history = doc.history.take();
+        let current = history.current_revision();
+        let shown = Some((doc.id(), history.revision_count()));
+        if self.shown != shown {
+            self.shown = shown;
+            self.revisions.timestamps = (0..history.revision_count())
+                .map(|revision| history.timestamp(revision))
+                .collect();
+            self.tree.replace_with_new_items(items(&history));
+            self.tree.select(|item| item.revision() == current);
+            self.preview = None;
+        } else if current != self.revisions.current && !self.focused {
+            self.tree.select(|item| item.revision() == current);
+        }
+        self.revisions.current = current;
+        let key = (self.tree.current_item().revision(), current);
+        if !matches!(&self.preview, Some((shown, _)) if *shown == key) {
+            self.preview = Some((key, hunks(&history, doc.text(), key.0)));
+        }
+        doc.history.set(history);
+    }
+
+    /// Moves the focused document to the selected revision.
+    fn checkout(&mut self, editor: &mut Editor) {
+        let revision = self.tree.current_item().revision();
+        let (view, doc) = current!(editor);
+        doc.append_changes_to_history(view);
+        if revision != self.revisions.current && !doc.jump_to_revision(view, revision) {
+            editor.set_error("Failed to check out the revision");
+        }
+    }
+
+    fn render_preview(&self, area: Rect, surface: &mut Surface, theme: &Theme) {
+        let hunks = match &self.preview {
+            Some((_, hunks)) => hunks,
+            None => return,
+        };
+        if hunks.is_empty() {
+            let style = theme.get("ui.text.inactive");
+            surface.set_stringn(area.x, area.y, "No changes", area.width as usize, style);
+            return;
+        }
+        for (hunk, y) in hunks.iter().zip(area.top()..area.bottom()) {
+            let spans = Spans::from(vec![
+                Span::styled(format!("{}: ", hunk.line + 1), theme.get("ui.linenr")),
+                Span::styled(hunk.old.as_str(), theme.get("diff.minus")),
+                Span::styled(" → ", theme.get("ui.text")),
+                Span::styled(hunk.new.as_str(), theme.get("diff.plus")),
+            ]);
+            surface.set_spans(area.x, y, &spans, area.width);
+        }
+    }
+}
+
+impl TreePanel for UndoTree {
+    type Item = RevisionItem;
+
+    fn parts(&mut self) -> (&mut Tree<RevisionItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for UndoTree {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Mouse(event) => return self.handle_mouse_event(event),
+            Event::Key(key) if self.focused => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            _ if self.shown.is_none() => {}
+            key!(Enter) => self.checkout(cx.editor),
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.revisions)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.update(cx.editor);
+        self.area = sidebar::area(area);
+        let help = "enter: check out  l: toggle  q: close";
+        let inner = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            "Undo tree",
+            help,
+            self.focused,
+        );
+        let preview_height = inner.height / 3;
+        let tree_area = inner.clip_bottom(preview_height);
+        let preview_area = inner.clip_top(inner.height - preview_height);
+        self.tree
+            .render(tree_area, surface, cx, &mut self.revisions);
+        if preview_area.height > 1 {
+            let style = cx.editor.theme.get("ui.text.inactive");
+            let width = preview_area.width as usize;
+            surface.set_stringn(preview_area.x, preview_area.y, "Changes", width, style);
+            self.render_preview(preview_area.clip_top(1), surface, &cx.editor.theme);
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use helix_core::{history::State, Selection, Transaction};
+
+    #[test]
+    fn branches() {
+        let mut history = History::default();
+        let mut state = State {
+            doc: Rope::from("a"),
+            selection: Selection::point(0),
+        };
+        let commit = |history: &mut History, state: &mut State, text: &str| {
+            let len = state.doc.len_chars();
+            let transaction =
+                Transaction::change(&state.doc, vec![(len, len, Some(text.into()))].into_iter());
+            history.commit_revision(&transaction, state);
+            transaction.apply(&mut state.doc);
+        };
+        commit(&mut history, &mut state, "b");
+        commit(&mut history, &mut state, "c");
+        if let Some(transaction) = history.undo() {
+            transaction.apply(&mut state.doc);
+        }
+        // a branch off the first edit, growing a line of its own
+        commit(&mut history, &mut state, "d");
+        commit(&mut history, &mut state, "e");
+        let keys: Vec<_> = items(&history).into_iter().map(|item| item.key).collect();
+        assert_eq!(keys, [vec![0], vec![1], vec![2], vec![1, 3], vec![1, 4]]);
+    }
+}
@@ -0,0 +1,347 @@
+use crate::commands::{apply_workspace_edit, execute_lsp_command};
+use crate::compositor::{Callback, Component, Context, Event, EventResult};
+use crate::key;
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::{cmp::Ordering, fs::File, io::BufReader};
+
+use helix_core::{path::get_relative_path, Rope};
+use helix_lsp::{
+    lsp::{self, DocumentChangeOperation, DocumentChanges, OneOf, ResourceOp},
+    util::lsp_pos_to_pos,
+    OffsetEncoding,
+};
+use helix_view::{graphics::Rect, Editor};
+
+use super::{one_line, Tree, TreeItem};
+
+/// Characters of the old and new text of an edit shown, longer ones are cut.
+const MAX_HUNK_LEN: usize = 120;
+
+/// An edit of the text of a file, as shown in the preview.
+struct Hunk {
+    /// 0 indexed line the edit starts at.
+    line: usize,
+    old: String,
+    new: String,
+}
+
+/// What a change of the workspace edit does to its file.
+enum ChangeKind {
+    Edit(lsp::TextDocumentEdit),
+    /// Creating, renaming or deleting the file.
+    Op(ResourceOp),
+}
+
+struct Change {
+    label: String,
+    kind: ChangeKind,
+    hunks: Vec<Hunk>,
+    accepted: bool,
+}
+
+/// A row of the preview: a changed file, or one of its edits.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum ChangeItem {
+    File(usize),
+    Hunk(usize, usize),
+}
+
+impl ChangeItem {
+    fn key(&self) -> (usize, usize) {
+        match *self {
+            Self::File(change) => (change, 0),
+            Self::Hunk(change, index) => (change, index + 1),
+        }
+    }
+}
+
+impl TreeItem for ChangeItem {
+    type Params = Vec<Change>;
+
+    fn text(&self, cx: &mut Context, selected: bool, changes: &mut Vec<Change>) -> Spans {
+        let theme = &cx.editor.theme;
+        let (change, hunk) = match *self {
+            Self::File(change) => (&changes[change], None),
+            Self::Hunk(change, index) => (&changes[change], Some(index)),
+        };
+        let style = |scope: &str| {
+            let mut style = if change.accepted {
+                theme.get(scope)
+            } else {
+                theme.get("ui.text.inactive")
+            };
+            if selected {
+                style = style.patch(theme.get("ui.menu.selected"));
+            }
+            style
+        };
+        match hunk {
+            None => {
+                let check = if change.accepted { "[x]" } else { "[ ]" };
+                Spans::from(Span::styled(
+                    format!("{} {}", check, change.label),
+                    style("ui.text"),
+                ))
+            }
+            Some(index) => {
+                let hunk = &change.hunks[index];
+                Spans::from(vec![
+                    Span::styled(format!("{}: ", hunk.line + 1), style("ui.linenr")),
+                    Span::styled(hunk.old.clone(), style("diff.minus")),
+                    Span::styled(" → ", style("ui.text")),
+                    Span::styled(hunk.new.clone(), style("diff.plus")),
+                ])
+            }
+        }
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        matches!((self, other), (Self::Hunk(change, _), Self::File(parent)) if change == parent)
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+}
+
+/// The text of the file at `uri`: its buffer if it is open, the file on disk otherwise.
+fn file_text(editor: &Editor, uri: &lsp::Url) -> Option<Rope> {
+    let path = uri.to_file_path().ok()?;
+    if let Some(doc) = editor.document_by_path(&path) {
+        return Some(doc.text().clone());
+    }
+    let file = File::open(&path).ok()?;
+    Rope::from_reader(BufReader::new(file)).ok()
+}
+
+fn uri_label(uri: &lsp::Url) -> String {
+    match uri.to_file_path() {
+        Ok(path) => get_relative_path(&path).to_string_lossy().into_owned(),
+        Err(_) => uri.to_string(),
+    }
+}
+
+fn hunks(
+    editor: &Editor,
+    offset_encoding: OffsetEncoding,
+    edit: &lsp::TextDocumentEdit,
+) -> Vec<Hunk> {
+    let text = file_text(editor, &edit.text_document.uri).unwrap_or_default();
+    edit.edits
+        .iter()
+        .map(|edit| {
+            let edit = match edit {
+                OneOf::Left(text_edit) => text_edit,
+                OneOf::Right(annotated_text_edit) => &annotated_text_edit.text_edit,
+            };
+            let start = lsp_pos_to_pos(&text, edit.range.start, offset_encoding);
+            let end = lsp_pos_to_pos(&text, edit.range.end, offset_encoding);
+            let old = match (start, end) {
+                (Some(start), Some(end)) if start <= end => text.slice(start..end).to_string(),
+                _ => String::new(),
+            };
+            Hunk {
+                line: text.char_to_line(start.unwrap_or(0).min(text.len_chars())),
+                old: one_line(&old, MAX_HUNK_LEN),
+                new: one_line(&edit.new_text, MAX_HUNK_LEN),
+            }
+        })
+        .collect()
+}
+
+/// The changes of `workspace_edit` in the order they are applied.
+fn changes(
+    editor: &Editor,
+    offset_encoding: OffsetEncoding,
+    workspace_edit: &lsp::WorkspaceEdit,
+) -> Vec<Change> {
+    let edit_change = |edit: lsp::TextDocumentEdit| Change {
+        label: uri_label(&edit.text_document.uri),
+        hunks: hunks(editor, offset_encoding, &edit),
+        kind: ChangeKind::Edit(edit),
+        accepted: true,
+    };
+    let mut changes = Vec::new();
+    if let Some(edits) = &workspace_edit.changes {
+        let mut edits: Vec<_> = edits.iter().collect();
+        edits.sort_by(|(a, _), (b, _)| a.as_str().cmp(b.as_str()));
+        for (uri, edits) in edits {
+            changes.push(edit_change(lsp::TextDocumentEdit {
+                text_document: lsp::OptionalVersionedTextDocumentIdentifier {
+                    uri: uri.clone(),
+                    version: None,
+                },
+                edits: edits.iter().cloned().map(OneOf::Left).collect(),
+            }));
+        }
+    }
+    match &workspace_edit.document_changes {
+        Some(DocumentChanges::Edits(edits)) => {
+            changes.extend(edits.iter().cloned().map(edit_change));
+        }
+        Some(DocumentChanges::Operations(operations)) => {
+            for operation in operations {
+                changes.push(match operation {
+                    DocumentChangeOperation::Edit(edit) => edit_change(edit.clone()),
+                    DocumentChangeOperation::Op(op) => Change {
+                        label: op_label(op),
+                        kind: ChangeKind::Op(op.clone()),
+                        hunks: Vec::new(),
+                        accepted: true,
+                    },
+                });
+            }
+        }
+        None => (),
+    }
+    changes
+}
+
+fn op_label(op: &ResourceOp) -> String {
+    match op {
+        ResourceOp::Create(op) => format!("create {}", uri_label(&op.uri)),
+        ResourceOp::Rename(op) => format!(
+            "rename {} to {}",
+            uri_label(&op.old_uri),
+            uri_label(&op.new_uri)
+        ),
+        ResourceOp::Delete(op) => format!("delete {}", uri_label(&op.uri)),
+    }
+}
+
+/// The changes of a workspace edit from a language server, listed to be accepted or
+/// rejected file by file before any of them is applied.
+pub struct WorkspaceEditPreview {
+    tree: Tree<ChangeItem>,
+    changes: Vec<Change>,
+    offset_encoding: OffsetEncoding,
+    /// The command of a code action, executed once its edit is applied.
+    command: Option<lsp::Command>,
+}
+
+impl WorkspaceEditPreview {
+    pub const ID: &'static str = "workspace-edit";
+
+    pub fn new(
+        editor: &Editor,
+        offset_encoding: OffsetEncoding,
+        workspace_edit: &lsp::WorkspaceEdit,
+        command: Option<lsp::Command>,
+    ) -> Self {
+        let changes = changes(editor, offset_encoding, workspace_edit);
+        let items = changes
+            .iter()
+            .enumerate()
+            .flat_map(|(index, change)| {
+                std::iter::once(ChangeItem::File(index))
+                    .chain((0..change.hunks.len()).map(move |hunk| ChangeItem::Hunk(index, hunk)))
+            })
+            .collect();
+        Self {
+            tree: Tree::build_tree(items),
+            changes,
+            offset_encoding,
+            command,
+        }
+    }
+
+    /// The number of changes, each of them to a single file.
+    pub fn len(&self) -> usize {
+        self.changes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.changes.is_empty()
+    }
+
+    fn toggle_current(&mut self) {
+        if self.changes.is_empty() {
+            return;
+        }
+        let change = match *self.tree.current_item() {
+            ChangeItem::File(change) | ChangeItem::Hunk(change, _) => change,
+        };
+        let change = &mut self.changes[change];
+        change.accepted = !change.accepted;
+    }
+
+    /// Applies the accepted changes and then the command, if any.
+    pub fn apply(&mut self, editor: &mut Editor) {
+        let operations = std::mem::take(&mut self.changes)
+            .into_iter()
+            .filter(|change| change.accepted)
+            .map(|change| match change.kind {
+                ChangeKind::Edit(edit) => DocumentChangeOperation::Edit(edit),
+                ChangeKind::Op(op) => DocumentChangeOperation::Op(op),
+            })
+            .collect();
+        let workspace_edit = lsp::WorkspaceEdit {
+            document_changes: Some(DocumentChanges::Operations(operations)),
+            ..Default::default()
+        };
+        apply_workspace_edit(editor, self.offset_encoding, &workspace_edit);
+        if let Some(command) = self.command.take() {
+            execute_lsp_command(editor, command);
+        }
+    }
+}
+
+impl Component for WorkspaceEditPreview {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let key = match event {
+            Event::Key(key) => *key,
+            Event::Paste(_) => return EventResult::Consumed(None),
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') | key!(Esc) => {
+                cx.editor.set_status("Workspace edit cancelled");
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                EventResult::Consumed(Some(callback))
+            }
+            key!(' ') => {
+                self.toggle_current();
+                EventResult::Consumed(None)
+            }
+            key!('a') => {
+                self.apply(cx.editor);
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                EventResult::Consumed(Some(callback))
+            }
+            _ => self
+                .tree
+                .handle_event(Event::Key(key), cx, &mut self.changes),
+        }
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        let theme = &cx.editor.theme;
+        // keep the last row free for the status messages
+        let area = area.clip_bottom(1);
+        surface.clear_with(area, theme.get("ui.background"));
+        let label_style = theme.get("ui.text.inactive");
+
+        let accepted = self.changes.iter().filter(|change| change.accepted).count();
+        let status = format!("{} of {} files accepted", accepted, self.changes.len());
+        let help = "space: accept/reject  a: apply  q: cancel";
+        surface.set_stringn(area.x, area.y, &status, area.width as usize, label_style);
+        let x = area.right().saturating_sub(help.len() as u16);
+        if x > area.x + status.len() as u16 + 1 {
+            surface.set_stringn(x, area.y, help, help.len(), label_style);
+        }
+        self.tree
+            .render(area.clip_top(2), surface, cx, &mut self.changes);
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
@@ -0,0 +1,319 @@
+use crate::commands::jump_to_location;
+use crate::compositor::{Callback, Component, Compositor, Context, Event, EventResult};
+use crate::job;
+use crate::{ctrl, key};
+use tui::{
+    buffer::Buffer as Surface,
+    text::{Span, Spans},
+};
+
+use std::cmp::Ordering;
+
+use helix_core::{path::get_relative_path, Position};
+use helix_lsp::{lsp, OffsetEncoding};
+use helix_view::{
+    editor::Action,
+    graphics::{CursorKind, Rect},
+    Editor,
+};
+
+use super::{
+    breadcrumbs::symbol_kind_label,
+    completers,
+    sidebar::{self, TreePanel},
+    Prompt, Tree, TreeItem,
+};
+
+/// A row of the panel: the container of symbols, like a module or a class, or a symbol
+/// by its index in the symbols of the panel, with its container.
+#[derive(Debug, Clone, PartialEq, Eq)]
+enum SymbolItem {
+    Container(String),
+    Symbol(usize, Option<String>),
+}
+
+impl SymbolItem {
+    fn key(&self) -> (Option<&str>, Option<usize>) {
+        match self {
+            Self::Container(name) => (Some(name), None),
+            Self::Symbol(index, container) => (container.as_deref(), Some(*index)),
+        }
+    }
+}
+
+impl TreeItem for SymbolItem {
+    type Params = Vec<lsp::SymbolInformation>;
+
+    fn text(&self, cx: &mut Context, selected: bool, symbols: &mut Self::Params) -> Spans {
+        let theme = &cx.editor.theme;
+        let style = |scope: &str| {
+            let style = theme.get(scope);
+            if selected {
+                style.patch(theme.get("ui.menu.selected"))
+            } else {
+                style
+            }
+        };
+        let symbol = match self {
+            Self::Container(name) => {
+                return Spans::from(Span::styled(name.clone(), style("ui.text.focus")))
+            }
+            Self::Symbol(index, _) => &symbols[*index],
+        };
+        let mut spans = Vec::new();
+        if let Some(kind) = symbol_kind_label(symbol.kind) {
+            spans.push(Span::styled(format!("{} ", kind), style("keyword")));
+        }
+        spans.push(Span::styled(symbol.name.clone(), style("ui.text")));
+        if let Ok(path) = symbol.location.uri.to_file_path() {
+            let path = get_relative_path(&path);
+            spans.push(Span::styled(
+                format!("  {}", path.display()),
+                style("ui.text.inactive"),
+            ));
+        }
+        Spans::from(spans)
+    }
+
+    fn is_child(&self, other: &Self) -> bool {
+        match (self, other) {
+            (Self::Symbol(_, Some(container)), Self::Container(parent)) => container == parent,
+            _ => false,
+        }
+    }
+
+    fn cmp(&self, other: &Self) -> Ordering {
+        self.key().cmp(&other.key())
+    }
+
+    fn filter(&self, _cx: &mut Context, s: &str, symbols: &mut Self::Params) -> bool {
+        let name = match self {
+            Self::Container(name) => name,
+            Self::Symbol(index, _) => &symbols[*index].name,
+        };
+        name.to_lowercase().contains(&s.to_lowercase())
+    }
+}
+
+/// The symbols of the workspace grouped by their container, kept open to jump from symbol
+/// to symbol unlike the symbol picker. The keys go to the views while the panel isn't
+/// focused, running `workspace_symbol_tree` focuses it again.
+pub struct WorkspaceSymbols {
+    tree: Tree<SymbolItem>,
+    symbols: Vec<lsp::SymbolInformation>,
+    offset_encoding: OffsetEncoding,
+    focused: bool,
+    /// Filtering the symbols as the query is typed.
+    prompt: Option<Prompt>,
+    /// The last query the symbols were filtered by, sent to the language server on refresh.
+    query: String,
+    area: Rect,
+    prompt_area: Rect,
+}
+
+impl Default for WorkspaceSymbols {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl WorkspaceSymbols {
+    pub const ID: &'static str = "workspace-symbols";
+
+    pub fn new() -> Self {
+        Self {
+            tree: Tree::build_tree(Vec::new()),
+            symbols: Vec::new(),
+            // the default of the protocol, until the symbols come with the one of the server
+            offset_encoding: OffsetEncoding::Utf16,
+            focused: true,
+            prompt: None,
+            query: String::new(),
+            area: Rect::default(),
+            prompt_area: Rect::default(),
+        }
+    }
+
+    /// Asks the language server of the current buffer for the symbols matching `query`,
+    /// which replace the ones of the panel once received. Returns whether they were asked
+    /// for.
+    pub fn request(editor: &mut Editor, jobs: &mut job::Jobs, query: String) -> bool {
+        let doc = doc!(editor);
+        let language_server = match doc.language_server() {
+            Some(language_server) => language_server,
+            None => {
+                editor.set_error("Language server not active for current buffer");
+                return false;
+            }
+        };
+        let offset_encoding = language_server.offset_encoding();
+        let future = match language_server.workspace_symbols(query) {
+            Some(future) => future,
+            None => {
+                editor.set_error("Language server does not support workspace symbols");
+                return false;
+            }
+        };
+        jobs.callback(async move {
+            let json = future.await?;
+            let response: Option<Vec<lsp::SymbolInformation>> = serde_json::from_value(json)?;
+            let call = job::Callback::EditorCompositor(Box::new(
+                move |_editor: &mut Editor, compositor: &mut Compositor| {
+                    if let Some(panel) = compositor.find_id::<Self>(Self::ID) {
+                        panel.set_symbols(response.unwrap_or_default(), offset_encoding);
+                    }
+                },
+            ));
+            Ok(call)
+        });
+        true
+    }
+
+    fn set_symbols(
+        &mut self,
+        mut symbols: Vec<lsp::SymbolInformation>,
+        offset_encoding: OffsetEncoding,
+    ) {
+        symbols.sort_by(|a, b| (&a.container_name, &a.name).cmp(&(&b.container_name, &b.name)));
+        let mut items: Vec<_> = symbols
+            .iter()
+            .filter_map(|symbol| symbol.container_name.clone())
+            .map(SymbolItem::Container)
+            .collect();
+        items.dedup();
+        items.extend(
+            symbols
+                .iter()
+                .enumerate()
+                .map(|(index, symbol)| SymbolItem::Symbol(index, symbol.container_name.clone())),
+        );
+        self.tree.replace_with_new_items(items);
+        self.symbols = symbols;
+        self.offset_encoding = offset_encoding;
+    }
+
+    /// Jumps to the current symbol and gives the keys back to the views.
+    fn jump(&mut self, editor: &mut Editor) {
+        let symbol = match self.tree.current_item() {
+            SymbolItem::Symbol(index, _) => &self.symbols[*index],
+            SymbolItem::Container(_) => return,
+        };
+        jump_to_location(
+            editor,
+            &symbol.location,
+            self.offset_encoding,
+            Action::Replace,
+        );
+        self.focused = false;
+    }
+
+    fn handle_filter_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        let mut prompt = match self.prompt.take() {
+            Some(prompt) => prompt,
+            None => return EventResult::Ignored(None),
+        };
+        match event {
+            Event::Key(key!(Enter)) => {
+                self.query = prompt.line().clone();
+                self.tree.clean_recycle();
+            }
+            Event::Key(key!(Esc) | ctrl!('c')) => {
+                self.query.clear();
+                self.tree.restore_recycle();
+            }
+            _ => {
+                if let EventResult::Consumed(_) = prompt.handle_event(event, cx) {
+                    self.tree.filter(prompt.line(), cx, &mut self.symbols);
+                }
+                self.prompt = Some(prompt);
+            }
+        }
+        EventResult::Consumed(None)
+    }
+}
+
+impl TreePanel for WorkspaceSymbols {
+    type Item = SymbolItem;
+
+    fn parts(&mut self) -> (&mut Tree<SymbolItem>, Rect, &mut bool) {
+        (&mut self.tree, self.area, &mut self.focused)
+    }
+}
+
+impl Component for WorkspaceSymbols {
+    fn handle_event(&mut self, event: &Event, cx: &mut Context) -> EventResult {
+        if let Event::Mouse(event) = event {
+            return self.handle_mouse_event(event);
+        }
+        if !self.focused {
+            return EventResult::Ignored(None);
+        }
+        if self.prompt.is_some() {
+            return self.handle_filter_event(event, cx);
+        }
+        let key = match event {
+            Event::Key(key) => *key,
+            _ => return EventResult::Ignored(None),
+        };
+        match key {
+            key!('q') => {
+                let callback: Callback = Box::new(|compositor, _| {
+                    compositor.remove(Self::ID);
+                });
+                return EventResult::Consumed(Some(callback));
+            }
+            key!(Esc) => self.focused = false,
+            key!('r') => {
+                Self::request(cx.editor, cx.jobs, self.query.clone());
+            }
+            _ if self.symbols.is_empty() => {}
+            key!('/') => {
+                self.tree.save_view();
+                let prompt = Prompt::new("filter: ".into(), None, completers::none, |_, _, _| {})
+                    .with_line(self.query.clone(), cx.editor);
+                self.prompt = Some(prompt);
+            }
+            key!(Enter) if matches!(self.tree.current_item(), SymbolItem::Symbol(..)) => {
+                self.jump(cx.editor)
+            }
+            _ => {
+                return self
+                    .tree
+                    .handle_event(Event::Key(key), cx, &mut self.symbols)
+            }
+        }
+        EventResult::Consumed(None)
+    }
+
+    fn render(&mut self, area: Rect, surface: &mut Surface, cx: &mut Context) {
+        self.area = sidebar::area(area);
+        let help = "/: filter  r: refresh  q: close";
+        let mut tree_area = sidebar::render_frame(
+            self.area,
+            surface,
+            &cx.editor.theme,
+            "Workspace symbols",
+            help,
+            self.focused,
+        );
+        if let Some(prompt) = &self.prompt {
+            self.prompt_area = tree_area.clip_top(tree_area.height.saturating_sub(1));
+            tree_area = tree_area.clip_bottom(1);
+            prompt.render_prompt(self.prompt_area, surface, cx);
+        }
+        if !self.symbols.is_empty() {
+            self.tree.render(tree_area, surface, cx, &mut self.symbols);
+        }
+    }
+
+    fn cursor(&self, _area: Rect, editor: &Editor) -> (Option<Position>, CursorKind) {
+        match &self.prompt {
+            Some(prompt) if self.focused => prompt.cursor(self.prompt_area, editor),
+            _ => (None, CursorKind::Hidden),
+        }
+    }
+
+    fn id(&self) -> Option<&'static str> {
+        Some(Self::ID)
+    }
+}
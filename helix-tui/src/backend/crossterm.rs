@@ -9,7 +9,10 @@
     terminal::{self, Clear, ClearType},
     Command,
 };
-use helix_view::graphics::{Color, CursorKind, Modifier, Rect, UnderlineStyle};
+use helix_view::{
+    graphics::{Color, CursorKind, Modifier, Rect, UnderlineStyle},
+    image::Image,
+};
 use std::{
     fmt,
     io::{self, Write},
@@ -52,6 +55,8 @@ pub fn from_env_or_default() -> Self {
 pub struct CrosstermBackend<W: Write> {
     buffer: W,
     capabilities: Capabilities,
+    /// The sequence removing the images drawn since they were last cleared.
+    clear_images: Option<&'static str>,
 }
 
 impl<W> CrosstermBackend<W>
@@ -62,6 +67,7 @@ pub fn new(buffer: W) -> CrosstermBackend<W> {
         CrosstermBackend {
             buffer,
             capabilities: Capabilities::from_env_or_default(),
+            clear_images: None,
         }
     }
 }
@@ -187,6 +193,25 @@ fn size(&self) -> io::Result<Rect> {
     fn flush(&mut self) -> io::Result<()> {
         self.buffer.flush()
     }
+
+    fn draw_image(&mut self, area: Rect, image: &Image) -> io::Result<()> {
+        map_error(queue!(
+            self.buffer,
+            MoveTo(area.x, area.y),
+            Print(image.escape_sequence(area))
+        ))?;
+        if let Some(sequence) = Image::clear_sequence(image.protocol) {
+            self.clear_images = Some(sequence);
+        }
+        Ok(())
+    }
+
+    fn clear_images(&mut self) -> io::Result<()> {
+        match self.clear_images.take() {
+            Some(sequence) => map_error(queue!(self.buffer, Print(sequence))),
+            None => Ok(()),
+        }
+    }
 }
 
 fn map_error(error: crossterm::Result<()>) -> io::Result<()> {
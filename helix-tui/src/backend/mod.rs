@@ -2,7 +2,10 @@
 
 use crate::buffer::Cell;
 
-use helix_view::graphics::{CursorKind, Rect};
+use helix_view::{
+    graphics::{CursorKind, Rect},
+    image::Image,
+};
 
 #[cfg(feature = "crossterm")]
 mod crossterm;
@@ -23,4 +26,12 @@ fn draw<'a, I>(&mut self, content: I) -> Result<(), io::Error>
     fn clear(&mut self) -> Result<(), io::Error>;
     fn size(&self) -> Result<Rect, io::Error>;
     fn flush(&mut self) -> Result<(), io::Error>;
+    /// Draws `image` over the cells of `area`. Backends without images draw nothing.
+    fn draw_image(&mut self, _area: Rect, _image: &Image) -> Result<(), io::Error> {
+        Ok(())
+    }
+    /// Removes the images that are not removed by drawing cells over them.
+    fn clear_images(&mut self) -> Result<(), io::Error> {
+        Ok(())
+    }
 }
@@ -1,9 +1,13 @@
 use crate::text::{Span, Spans};
 use helix_core::unicode::width::UnicodeWidthStr;
 use std::cmp::min;
+use std::sync::Arc;
 use unicode_segmentation::UnicodeSegmentation;
 
-use helix_view::graphics::{Color, Modifier, Rect, Style, UnderlineStyle};
+use helix_view::{
+    graphics::{Color, Modifier, Rect, Style, UnderlineStyle},
+    image::Image,
+};
 
 /// A buffer cell
 #[derive(Debug, Clone, PartialEq, Eq)]
@@ -126,6 +130,8 @@ pub struct Buffer {
     /// The content of the buffer. The length of this Vec should always be equal to area.width *
     /// area.height
     pub content: Vec<Cell>,
+    /// Images drawn over the cells, after the cells are drawn.
+    pub images: Vec<(Rect, Arc<Image>)>,
 }
 
 impl Buffer {
@@ -142,7 +148,11 @@ pub fn filled(area: Rect, cell: &Cell) -> Buffer {
         for _ in 0..size {
             content.push(cell.clone());
         }
-        Buffer { area, content }
+        Buffer {
+            area,
+            content,
+            images: Vec::new(),
+        }
     }
 
     /// Returns a Buffer containing the given lines
@@ -495,6 +505,34 @@ pub fn reset(&mut self) {
         for c in &mut self.content {
             c.reset();
         }
+        self.images.clear();
+    }
+
+    /// Draws `image` over `area`. The cells below the image should be blank.
+    pub fn set_image(&mut self, area: Rect, image: Arc<Image>) {
+        let area = self.area.intersection(area);
+        if area.width > 0 && area.height > 0 {
+            self.images.push((area, image));
+        }
+    }
+
+    /// Whether the same images are drawn at the same places as in `other`.
+    pub fn same_images(&self, other: &Buffer) -> bool {
+        self.images.len() == other.images.len()
+            && self.images.iter().zip(&other.images).all(
+                |((area, image), (other_area, other_image))| {
+                    area == other_area && Arc::ptr_eq(image, other_image)
+                },
+            )
+    }
+
+    /// Forces the cells of `area` to be drawn again by the next diff.
+    pub fn invalidate(&mut self, area: Rect) {
+        for x in area.left()..area.right() {
+            for y in area.top()..area.bottom() {
+                self[(x, y)].symbol.clear();
+            }
+        }
     }
 
     /// Clear an area in the buffer
@@ -121,10 +121,26 @@ pub fn backend_mut(&mut self) -> &mut B {
     /// Obtains a difference between the previous and the current buffer and passes it to the
     /// current backend for drawing.
     pub fn flush(&mut self) -> io::Result<()> {
+        let images_changed =
+            !self.buffers[self.current].same_images(&self.buffers[1 - self.current]);
+        if images_changed {
+            // draw the cells below the previous images again to remove them
+            self.backend.clear_images()?;
+            let previous_buffer = &mut self.buffers[1 - self.current];
+            for (area, _) in std::mem::take(&mut previous_buffer.images) {
+                previous_buffer.invalidate(area);
+            }
+        }
         let previous_buffer = &self.buffers[1 - self.current];
         let current_buffer = &self.buffers[self.current];
         let updates = previous_buffer.diff(current_buffer);
-        self.backend.draw(updates.into_iter())
+        self.backend.draw(updates.into_iter())?;
+        if images_changed {
+            for (area, image) in &current_buffer.images {
+                self.backend.draw_image(*area, image)?;
+            }
+        }
+        Ok(())
     }
 
     /// Updates the Terminal so that internal buffers match the requested size. Requested size will
@@ -1,7 +1,8 @@
 use std::ops::Range;
 use std::sync::Arc;
 
-use helix_core::Rope;
+use helix_core::line_ending::get_line_ending;
+use helix_core::{Rope, RopeSlice};
 use imara_diff::Algorithm;
 use parking_lot::{Mutex, MutexGuard};
 use tokio::sync::mpsc::{unbounded_channel, UnboundedSender};
@@ -11,6 +12,7 @@
 
 use crate::diff::worker::DiffWorker;
 
+pub mod compare;
 mod line_cache;
 mod worker;
 
@@ -33,6 +35,7 @@ pub struct DiffHandle {
     channel: UnboundedSender<Event>,
     render_lock: Arc<RwLock<()>>,
     hunks: Arc<Mutex<Vec<Hunk>>>,
+    diff_base: Arc<Mutex<Rope>>,
     inverted: bool,
 }
 
@@ -55,13 +58,14 @@ fn new_with_handle(
             redraw_notify: redraw_handle.0,
             diff_finished_notify: Arc::default(),
         };
-        let handle = tokio::spawn(worker.run(diff_base, doc));
         let differ = DiffHandle {
             channel: sender,
             hunks,
+            diff_base: Arc::new(Mutex::new(diff_base.clone())),
             inverted: false,
             render_lock: redraw_handle.1,
         };
+        let handle = tokio::spawn(worker.run(diff_base, doc));
         (differ, handle)
     }
 
@@ -93,7 +97,13 @@ pub fn update_document(&self, doc: Rope, block: bool) -> bool {
         self.update_document_impl(doc, self.inverted, Some(RenderLock { lock, timeout }))
     }
 
+    /// The text the document is diffed against.
+    pub fn diff_base(&self) -> Rope {
+        self.diff_base.lock().clone()
+    }
+
     pub fn update_diff_base(&self, diff_base: Rope) -> bool {
+        *self.diff_base.lock() = diff_base.clone();
         self.update_document_impl(diff_base, !self.inverted, None)
     }
 
@@ -163,6 +173,41 @@ pub fn is_pure_insertion(&self) -> bool {
     pub fn is_pure_removal(&self) -> bool {
         self.after.is_empty()
     }
+
+    /// Formats this hunk as a unified diff hunk without any context lines (as
+    /// accepted by `git apply --unidiff-zero`). `diff_base` and `doc` must be the
+    /// texts this hunk was computed between.
+    pub fn to_unified_diff(&self, diff_base: RopeSlice, doc: RopeSlice) -> String {
+        let header_range = |range: &Range<u32>| {
+            let len = range.end - range.start;
+            // empty ranges refer to the line before the change
+            let start = if len == 0 {
+                range.start
+            } else {
+                range.start + 1
+            };
+            format!("{},{}", start, len)
+        };
+
+        let mut patch = format!(
+            "@@ -{} +{} @@\n",
+            header_range(&self.before),
+            header_range(&self.after)
+        );
+        let mut push_lines = |prefix: char, text: RopeSlice, range: &Range<u32>| {
+            for line in range.start..range.end {
+                let line = text.line(line as usize);
+                patch.push(prefix);
+                patch.extend(line.chunks());
+                if get_line_ending(&line).is_none() {
+                    patch.push_str("\n\\ No newline at end of file\n");
+                }
+            }
+        };
+        push_lines('-', diff_base, &self.before);
+        push_lines('+', doc, &self.after);
+        patch
+    }
 }
 
 /// A list of changes in a file sorted in ascending
@@ -0,0 +1,146 @@
+//! One-off synchronous diffs between two texts, used to show files side by side.
+
+use std::ops::Range;
+
+use helix_core::chars::char_is_word;
+use helix_core::Rope;
+use imara_diff::intern::{InternedInput, Interner};
+
+use super::line_cache::InternedRopeLines;
+use super::{Hunk, ALGORITHM};
+
+#[cfg(test)]
+mod test;
+
+/// Computes the changed lines between `before` and `after` with the same
+/// algorithm used for the diff gutter. Texts that are too large to diff
+/// are reported as changed entirely.
+pub fn compare_ropes(before: &Rope, after: &Rope) -> Vec<Hunk> {
+    let lines = InternedRopeLines::new(before.clone(), after.clone());
+    let input = match lines.interned_lines() {
+        Some(input) => input,
+        None => {
+            return vec![Hunk {
+                before: 0..before.len_lines() as u32,
+                after: 0..after.len_lines() as u32,
+            }]
+        }
+    };
+    let mut hunks = Vec::new();
+    imara_diff::diff(ALGORITHM, input, |before: Range<u32>, after: Range<u32>| {
+        hunks.push(Hunk { before, after })
+    });
+    hunks
+}
+
+/// A row of a side by side diff. Either side has no line when the other side
+/// has more lines in that part of the diff, a filler row is drawn there instead.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffRow {
+    pub before: Option<usize>,
+    pub after: Option<usize>,
+    /// Whether the lines of this row are part of a hunk.
+    pub changed: bool,
+}
+
+/// Aligns the lines of two texts with `before_lines` and `after_lines` lines that
+/// differ by `hunks`. Changed lines are paired up in order, the remaining lines of
+/// the longer side of a hunk are aligned with filler rows.
+pub fn side_by_side(hunks: &[Hunk], before_lines: usize, after_lines: usize) -> Vec<DiffRow> {
+    let mut rows = Vec::with_capacity(before_lines.max(after_lines));
+    let (mut before, mut after) = (0, 0);
+    let unchanged = |rows: &mut Vec<DiffRow>, before: &mut usize, after: &mut usize, end| {
+        while *before < end {
+            rows.push(DiffRow {
+                before: Some(*before),
+                after: Some(*after),
+                changed: false,
+            });
+            *before += 1;
+            *after += 1;
+        }
+    };
+
+    for hunk in hunks {
+        unchanged(
+            &mut rows,
+            &mut before,
+            &mut after,
+            hunk.before.start as usize,
+        );
+        let removed = hunk.before.end as usize - before;
+        let added = hunk.after.end as usize - after;
+        for i in 0..removed.max(added) {
+            rows.push(DiffRow {
+                before: (i < removed).then(|| before + i),
+                after: (i < added).then(|| after + i),
+                changed: true,
+            });
+        }
+        before += removed;
+        after += added;
+    }
+    unchanged(&mut rows, &mut before, &mut after, before_lines);
+    debug_assert_eq!(after, after_lines);
+    rows
+}
+
+/// Splits a line into words, runs of whitespace and single punctuation characters.
+fn words(line: &str) -> impl Iterator<Item = &str> {
+    let mut rest = line;
+    std::iter::from_fn(move || {
+        let first = rest.chars().next()?;
+        let len = if char_is_word(first) || first.is_whitespace() {
+            let class = |ch: char| (char_is_word(ch), ch.is_whitespace());
+            rest.find(|ch| class(ch) != class(first))
+                .unwrap_or(rest.len())
+        } else {
+            first.len_utf8()
+        };
+        let (word, tail) = rest.split_at(len);
+        rest = tail;
+        Some(word)
+    })
+}
+
+/// Computes the char ranges of the words that changed between two versions of a
+/// line, for `before` and `after` respectively.
+pub fn changed_words(before: &str, after: &str) -> (Vec<Range<usize>>, Vec<Range<usize>>) {
+    let before_words: Vec<_> = words(before).collect();
+    let after_words: Vec<_> = words(after).collect();
+    let mut input = InternedInput {
+        before: Vec::with_capacity(before_words.len()),
+        after: Vec::with_capacity(after_words.len()),
+        interner: Interner::new(before_words.len() + after_words.len()),
+    };
+    input.update_before(before_words.iter().copied());
+    input.update_after(after_words.iter().copied());
+
+    // offsets of the words in chars, with the end of the line last
+    let offsets = |words: &[&str]| {
+        let mut offsets = Vec::with_capacity(words.len() + 1);
+        offsets.push(0);
+        for word in words {
+            offsets.push(offsets[offsets.len() - 1] + word.chars().count());
+        }
+        offsets
+    };
+    let (before_offsets, after_offsets) = (offsets(&before_words), offsets(&after_words));
+
+    let (mut removed, mut added) = (Vec::new(), Vec::new());
+    imara_diff::diff(
+        ALGORITHM,
+        &input,
+        |before: Range<u32>, after: Range<u32>| {
+            if !before.is_empty() {
+                removed.push(
+                    before_offsets[before.start as usize]..before_offsets[before.end as usize],
+                );
+            }
+            if !after.is_empty() {
+                added.push(after_offsets[after.start as usize]..after_offsets[after.end as usize]);
+            }
+        },
+    );
+    (removed, added)
+}
@@ -0,0 +1,55 @@
+use helix_core::Rope;
+
+use crate::diff::compare::{changed_words, compare_ropes, side_by_side, DiffRow};
+
+fn row(before: Option<usize>, after: Option<usize>, changed: bool) -> DiffRow {
+    DiffRow {
+        before,
+        after,
+        changed,
+    }
+}
+
+#[test]
+fn side_by_side_filler() {
+    let before = Rope::from_str("a\nb\nc\nd\n");
+    let after = Rope::from_str("a\nB\nB2\nB3\nd\ne\n");
+    let hunks = compare_ropes(&before, &after);
+    let rows = side_by_side(&hunks, before.len_lines(), after.len_lines());
+    assert_eq!(
+        rows,
+        vec![
+            row(Some(0), Some(0), false),
+            row(Some(1), Some(1), true),
+            row(Some(2), Some(2), true),
+            row(None, Some(3), true),
+            row(Some(3), Some(4), false),
+            row(None, Some(5), true),
+            row(Some(4), Some(6), false),
+        ]
+    );
+}
+
+#[test]
+fn side_by_side_removal() {
+    let before = Rope::from_str("a\nb\nc\n");
+    let after = Rope::from_str("a\nc\n");
+    let hunks = compare_ropes(&before, &after);
+    let rows = side_by_side(&hunks, before.len_lines(), after.len_lines());
+    assert_eq!(
+        rows,
+        vec![
+            row(Some(0), Some(0), false),
+            row(Some(1), None, true),
+            row(Some(2), Some(1), false),
+            row(Some(3), Some(2), false),
+        ]
+    );
+}
+
+#[test]
+fn changed_words_of_line() {
+    let (removed, added) = changed_words("let foo = bar(1);", "let foo = baz(1, 2);");
+    assert_eq!(removed, vec![10..13]);
+    assert_eq!(added, vec![10..13, 15..18]);
+}
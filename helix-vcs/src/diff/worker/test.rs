@@ -147,3 +147,27 @@ async fn update_base() {
         ]
     )
 }
+
+#[test]
+fn unified_diff() {
+    let diff_base = Rope::from_str("foo\nbar\nbaz");
+    let doc = Rope::from_str("foo\nqux\nbaz\nend");
+
+    let hunk = Hunk {
+        before: 1..2,
+        after: 1..2,
+    };
+    assert_eq!(
+        hunk.to_unified_diff(diff_base.slice(..), doc.slice(..)),
+        "@@ -2,1 +2,1 @@\n-bar\n+qux\n"
+    );
+
+    let hunk = Hunk {
+        before: 3..3,
+        after: 3..4,
+    };
+    assert_eq!(
+        hunk.to_unified_diff(diff_base.slice(..), doc.slice(..)),
+        "@@ -3,0 +4,1 @@\n+end\n\\ No newline at end of file\n"
+    );
+}
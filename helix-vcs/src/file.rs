@@ -0,0 +1,28 @@
+//! Plain files, for the workspaces without a repository. The diff base of a file is the
+//! file at the same path in the `.helix/diff-base` directory of the workspace, like a
+//! copy of the sources before they were changed.
+
+use std::fs;
+use std::path::{Path, PathBuf};
+
+use crate::{find_root, DiffProvider};
+
+#[cfg(test)]
+mod test;
+
+/// The directory holding the diff bases, relative to the root of the workspace.
+const BASE_DIR: &str = ".helix/diff-base";
+
+pub struct FileBase;
+
+impl DiffProvider for FileBase {
+    fn root(&self, path: &Path) -> Option<PathBuf> {
+        find_root(path, BASE_DIR)
+    }
+
+    fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        let root = self.root(file)?;
+        let rel_path = file.strip_prefix(&root).ok()?;
+        fs::read(root.join(BASE_DIR).join(rel_path)).ok()
+    }
+}
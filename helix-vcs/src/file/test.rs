@@ -0,0 +1,20 @@
+use std::fs;
+
+use crate::{DiffProvider, FileBase};
+
+#[test]
+fn diff_base() {
+    let tmp = tempfile::tempdir().unwrap();
+    let base_dir = tmp.path().join(".helix/diff-base/src");
+    fs::create_dir_all(&base_dir).unwrap();
+    fs::write(base_dir.join("file.txt"), b"foo").unwrap();
+    fs::create_dir(tmp.path().join("src")).unwrap();
+    let file = tmp.path().join("src/file.txt");
+    fs::write(&file, b"bar").unwrap();
+    let new_file = tmp.path().join("src/new.txt");
+    fs::write(&new_file, b"baz").unwrap();
+
+    assert_eq!(FileBase.root(&file).as_deref(), Some(tmp.path()));
+    assert_eq!(FileBase.get_diff_base(&file), Some(b"foo".to_vec()));
+    assert_eq!(FileBase.get_diff_base(&new_file), None);
+}
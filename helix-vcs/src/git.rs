@@ -1,11 +1,13 @@
-use std::path::Path;
+use std::io::{self, Write};
+use std::path::{Path, PathBuf};
+use std::process::{Command, Stdio};
 
 use git::objs::tree::EntryMode;
 use git::sec::trust::DefaultForLevel;
 use git::{Commit, ObjectId, Repository, ThreadSafeRepository};
 use git_repository as git;
 
-use crate::DiffProvider;
+use crate::{CommitInfo, DiffProvider, FileStatus, RepoInfo};
 
 #[cfg(test)]
 mod test;
@@ -54,14 +56,25 @@ fn open_repo(path: &Path, ceiling_dir: Option<&Path>) -> Option<ThreadSafeReposi
 }
 
 impl DiffProvider for Git {
+    fn root(&self, path: &Path) -> Option<PathBuf> {
+        crate::find_root(path, ".git")
+    }
+
     fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
         debug_assert!(!file.exists() || file.is_file());
         debug_assert!(file.is_absolute());
 
         // TODO cache repository lookup
         let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
-        let head = repo.head_commit().ok()?;
-        let file_oid = find_file_in_commit(&repo, &head, file)?;
+        // the staged contents, so that staged hunks leave the gutter like `git diff` shows
+        let file_oid = match find_file_in_index(&repo, file) {
+            Ok(file_oid) => file_oid?,
+            // without a git binary to read the index, the last commit is compared with
+            Err(_) => {
+                let head = repo.head_commit().ok()?;
+                find_file_in_commit(&repo, &head, file)?
+            }
+        };
 
         let file_object = repo.find_object(file_oid).ok()?;
         let mut data = file_object.detach().data;
@@ -88,9 +101,424 @@ fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
         }
         Some(data)
     }
+
+    fn get_current_head_name(&self, file: &Path) -> Option<String> {
+        debug_assert!(!file.exists() || file.is_file());
+        debug_assert!(file.is_absolute());
+
+        let repo = Git::open_repo(file.parent()?, None)?.to_thread_local();
+        let head_ref = repo.head_ref().ok()?;
+        let head_commit = repo.head_commit().ok()?;
+
+        let name = match head_ref {
+            Some(reference) => reference.name().shorten().to_string(),
+            // detached HEAD
+            None => head_commit.id.to_hex_with_len(8).to_string(),
+        };
+        Some(name)
+    }
+
+    fn stage_hunk(&self, file: &Path, hunk: &[u8]) -> io::Result<()> {
+        let (work_dir, rel_path) = work_tree_path(file)?;
+
+        let mut patch =
+            format!("diff --git a/{rel_path} b/{rel_path}\n--- a/{rel_path}\n+++ b/{rel_path}\n")
+                .into_bytes();
+        patch.extend_from_slice(hunk);
+
+        // gitoxide can't write to the index yet, let git apply the patch instead
+        run_git(
+            &work_dir,
+            &["apply", "--cached", "--unidiff-zero", "-"],
+            Some(&patch),
+        )?;
+        Ok(())
+    }
+
+    fn blame_line(
+        &self,
+        file: &Path,
+        contents: &[u8],
+        line: usize,
+    ) -> io::Result<Option<CommitInfo>> {
+        let (work_dir, rel_path) = work_tree_path(file)?;
+
+        // gitoxide doesn't implement blame yet. The contents are given on stdin, for the
+        // lines to be the ones of the buffer rather than the file on disk.
+        let range = format!("{},{}", line + 1, line + 1);
+        let blame = run_git(
+            &work_dir,
+            &[
+                "blame",
+                "--porcelain",
+                "--contents",
+                "-",
+                "-L",
+                &range,
+                "--",
+                &rel_path,
+            ],
+            Some(contents),
+        )?;
+        let hash = match blame.split(|&b| b == b' ').next() {
+            Some(hash) => String::from_utf8_lossy(hash).into_owned(),
+            None => return Ok(None),
+        };
+        // lines that are not committed yet are attributed to the null commit
+        if hash.is_empty() || hash.bytes().all(|b| b == b'0') {
+            return Ok(None);
+        }
+
+        let show = run_git(
+            &work_dir,
+            &[
+                "show",
+                "--no-patch",
+                "--date=iso",
+                "--format=%H%x00%an <%ae>%x00%ad%x00%B",
+                &hash,
+            ],
+            None,
+        )?;
+        let show = String::from_utf8_lossy(&show);
+        let mut fields = show.splitn(4, '\0');
+        let mut next = || fields.next().unwrap_or_default().trim().to_owned();
+        Ok(Some(CommitInfo {
+            hash: next(),
+            author: next(),
+            date: next(),
+            message: next(),
+        }))
+    }
+
+    fn commit_diff(&self, file: &Path, commit: &str) -> io::Result<String> {
+        let (work_dir, _) = work_tree_path(file)?;
+        let diff = run_git(
+            &work_dir,
+            &["show", "--no-color", "--format=fuller", commit],
+            None,
+        )?;
+        Ok(String::from_utf8_lossy(&diff).into_owned())
+    }
+
+    fn file_log(&self, file: &Path) -> io::Result<Vec<CommitInfo>> {
+        let (work_dir, rel_path) = work_tree_path(file)?;
+        // one commit per line, the subject can't contain a line break
+        let log = run_git(
+            &work_dir,
+            &[
+                "log",
+                "--follow",
+                "--date=short",
+                "--format=%H%x00%an <%ae>%x00%ad%x00%s",
+                "--",
+                &rel_path,
+            ],
+            None,
+        )?;
+        Ok(String::from_utf8_lossy(&log)
+            .lines()
+            .filter_map(|line| {
+                let mut fields = line.splitn(4, '\0');
+                Some(CommitInfo {
+                    hash: fields.next()?.to_owned(),
+                    author: fields.next()?.to_owned(),
+                    date: fields.next()?.to_owned(),
+                    message: fields.next()?.to_owned(),
+                })
+            })
+            .collect())
+    }
+
+    fn last_commit(&self, path: &Path) -> io::Result<Option<CommitInfo>> {
+        let (work_dir, rel_path) = work_tree_path(path)?;
+        let pathspec = format!(":(literal){rel_path}");
+        let log = run_git(
+            &work_dir,
+            &[
+                "log",
+                "-1",
+                "--date=relative",
+                "--format=%H%x00%an <%ae>%x00%ad%x00%s",
+                "--",
+                &pathspec,
+            ],
+            None,
+        )?;
+        let log = String::from_utf8_lossy(&log);
+        // nothing is logged for the paths which were never committed
+        if log.trim().is_empty() {
+            return Ok(None);
+        }
+        let mut fields = log.splitn(4, '\0');
+        let mut next = || fields.next().unwrap_or_default().trim().to_owned();
+        Ok(Some(CommitInfo {
+            hash: next(),
+            author: next(),
+            date: next(),
+            message: next(),
+        }))
+    }
+
+    fn file_at_commit(&self, file: &Path, commit: &str) -> io::Result<Vec<u8>> {
+        let (work_dir, rel_path) = work_tree_path(file)?;
+        let object = format!("{commit}:{rel_path}");
+        run_git(&work_dir, &["show", &object], None)
+    }
+
+    fn conflicted_files(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let work_dir = work_tree(dir)?;
+        // unmerged paths are relative to the root of the work tree
+        let output = run_git(
+            &work_dir,
+            &["diff", "--name-only", "--diff-filter=U", "-z"],
+            None,
+        )?;
+        Ok(output
+            .split(|&b| b == 0)
+            .filter(|path| !path.is_empty())
+            .map(|path| work_dir.join(&*String::from_utf8_lossy(path)))
+            .collect())
+    }
+
+    fn repo_info(&self, dir: &Path) -> io::Result<RepoInfo> {
+        let work_dir = work_tree(dir)?;
+        let git_dir = run_git(&work_dir, &["rev-parse", "--absolute-git-dir"], None)?;
+        let git_dir = PathBuf::from(String::from_utf8_lossy(&git_dir).trim());
+        // without refreshing the index, which would be taken for a change of the repository
+        let status = run_git(
+            &work_dir,
+            &[
+                "--no-optional-locks",
+                "status",
+                "--porcelain=v2",
+                "--branch",
+                "-z",
+            ],
+            None,
+        )?;
+        let status = String::from_utf8_lossy(&status);
+
+        let mut info = RepoInfo {
+            work_dir,
+            head: String::new(),
+            ahead: 0,
+            behind: 0,
+            dirty: false,
+            operation: None,
+            watch: Vec::new(),
+        };
+        let mut oid = "";
+        // renames are followed by their original path, which is skipped with them
+        let mut entries = status.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            match entry.strip_prefix("# ") {
+                Some(header) => {
+                    let (key, value) = header.split_once(' ').unwrap_or((header, ""));
+                    match key {
+                        "branch.oid" => oid = value,
+                        "branch.head" if value != "(detached)" => info.head = value.to_owned(),
+                        "branch.ab" => {
+                            // `+<ahead> -<behind>`
+                            let mut counts = value.split(' ').map(|count| {
+                                count.get(1..).and_then(|n| n.parse().ok()).unwrap_or(0)
+                            });
+                            info.ahead = counts.next().unwrap_or(0);
+                            info.behind = counts.next().unwrap_or(0);
+                        }
+                        _ => (),
+                    }
+                }
+                None => {
+                    info.dirty = true;
+                    if entry.starts_with("2 ") {
+                        entries.next();
+                    }
+                }
+            }
+        }
+        if info.head.is_empty() {
+            info.head = oid.chars().take(8).collect();
+        }
+
+        info.operation = [
+            ("rebase-merge", "rebasing"),
+            ("rebase-apply", "rebasing"),
+            ("MERGE_HEAD", "merging"),
+            ("CHERRY_PICK_HEAD", "cherry-picking"),
+            ("REVERT_HEAD", "reverting"),
+            ("BISECT_LOG", "bisecting"),
+        ]
+        .iter()
+        .find(|(file, _)| git_dir.join(file).exists())
+        .map(|(_, operation)| *operation);
+        // commits, checkouts, staging and fetches
+        info.watch = ["HEAD", "index", "logs/HEAD", "FETCH_HEAD", "MERGE_HEAD"]
+            .iter()
+            .map(|file| git_dir.join(file))
+            .collect();
+        Ok(info)
+    }
+
+    fn status(&self, dir: &Path, paths: &[PathBuf]) -> io::Result<Vec<(PathBuf, FileStatus)>> {
+        let work_dir = work_tree(dir)?;
+        let pathspecs: Vec<_> = paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(&work_dir).ok())
+            .map(|path| match path.to_string_lossy().replace('\\', "/") {
+                path if path.is_empty() => ":(literal).".to_owned(),
+                path => format!(":(literal){path}"),
+            })
+            .collect();
+        if pathspecs.is_empty() && !paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        let mut args = vec![
+            "--no-optional-locks",
+            "status",
+            "--porcelain=v2",
+            "--untracked-files=all",
+            "-z",
+            "--",
+        ];
+        args.extend(pathspecs.iter().map(String::as_str));
+        let output = run_git(&work_dir, &args, None)?;
+        let output = String::from_utf8_lossy(&output);
+
+        let mut files = Vec::new();
+        let mut entries = output.split('\0').filter(|entry| !entry.is_empty());
+        while let Some(entry) = entries.next() {
+            let (kind, rest) = entry.split_once(' ').unwrap_or((entry, ""));
+            let (status, path) = match kind {
+                "?" => (FileStatus::Untracked, rest),
+                "1" | "2" | "u" => {
+                    // the status, the modes and the object names come before the path
+                    let count = match kind {
+                        "1" => 8,
+                        "2" => 9,
+                        _ => 10,
+                    };
+                    let fields: Vec<_> = rest.splitn(count, ' ').collect();
+                    if fields.len() < count {
+                        continue;
+                    }
+                    let xy = fields[0];
+                    let status = match kind {
+                        "u" => FileStatus::Conflicted,
+                        // renames are followed by their original path
+                        "2" => {
+                            entries.next();
+                            FileStatus::Renamed
+                        }
+                        _ if xy.starts_with('A') => FileStatus::Added,
+                        _ if xy.contains('D') => FileStatus::Deleted,
+                        _ => FileStatus::Modified,
+                    };
+                    (status, fields[count - 1])
+                }
+                // ignored files and headers
+                _ => continue,
+            };
+            files.push((work_dir.join(path), status));
+        }
+        Ok(files)
+    }
+}
+
+fn not_tracked() -> io::Error {
+    io::Error::new(
+        io::ErrorKind::Unsupported,
+        "file is not part of a git work tree",
+    )
+}
+
+/// Returns the root of the work tree containing `dir`.
+fn work_tree(dir: &Path) -> io::Result<PathBuf> {
+    let repo = Git::open_repo(dir, None)
+        .ok_or_else(not_tracked)?
+        .to_thread_local();
+    let work_dir = repo.work_dir().ok_or_else(not_tracked)?;
+    Ok(work_dir.to_owned())
+}
+
+/// Returns the work tree containing `file` and the path of `file` relative to it,
+/// using forward slashes like git does.
+fn work_tree_path(file: &Path) -> io::Result<(PathBuf, String)> {
+    let work_dir = work_tree(file.parent().ok_or_else(not_tracked)?)?;
+    let rel_path = file.strip_prefix(&work_dir).map_err(|_| not_tracked())?;
+    let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+    Ok((work_dir, rel_path))
+}
+
+/// Runs the git binary in `work_dir` and returns its stdout.
+/// The contents of stderr are used as the error message if git fails.
+fn run_git(work_dir: &Path, args: &[&str], stdin: Option<&[u8]>) -> io::Result<Vec<u8>> {
+    let mut child = Command::new("git")
+        .arg("-C")
+        .arg(work_dir)
+        .args(args)
+        .stdin(if stdin.is_some() {
+            Stdio::piped()
+        } else {
+            Stdio::null()
+        })
+        .stdout(Stdio::piped())
+        .stderr(Stdio::piped())
+        .spawn()?;
+    if let Some(input) = stdin {
+        // unwrap is safe because stdin was piped, dropping it closes the pipe
+        child.stdin.take().unwrap().write_all(input)?;
+    }
+    let output = child.wait_with_output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
+    }
 }
 
 /// Finds the object that contains the contents of a file at a specific commit.
+/// Returns the blob staged for `file`, or `Ok(None)` if it is not staged as a regular file
+/// or has conflicts. gitoxide can't read the index yet, the git binary lists its entry.
+fn find_file_in_index(repo: &Repository, file: &Path) -> io::Result<Option<ObjectId>> {
+    let work_dir = repo.work_dir().ok_or_else(not_tracked)?;
+    let rel_path = file.strip_prefix(work_dir).map_err(|_| not_tracked())?;
+    let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+    // `<mode> <oid> <stage>\t<path>` for the entries under the path
+    let output = run_git(
+        work_dir,
+        &[
+            "--literal-pathspecs",
+            "ls-files",
+            "--stage",
+            "-z",
+            "--",
+            &rel_path,
+        ],
+        None,
+    )?;
+    let output = String::from_utf8_lossy(&output);
+    let entry = output.split('\0').find_map(|entry| {
+        let (info, path) = entry.split_once('\t')?;
+        if path == rel_path {
+            Some(info)
+        } else {
+            None
+        }
+    });
+    let mut fields = entry.unwrap_or_default().split(' ');
+    Ok(match (fields.next(), fields.next(), fields.next()) {
+        // directories and symlinks have no contents to compare with, nor conflicts
+        (Some("100644" | "100755"), Some(oid), Some("0")) => {
+            ObjectId::from_hex(oid.as_bytes()).ok()
+        }
+        _ => None,
+    })
+}
+
 fn find_file_in_commit(repo: &Repository, commit: &Commit, file: &Path) -> Option<ObjectId> {
     let repo_dir = repo.work_dir()?;
     let rel_path = file.strip_prefix(repo_dir).ok()?;
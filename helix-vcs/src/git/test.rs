@@ -1,8 +1,13 @@
-use std::{fs::File, io::Write, path::Path, process::Command};
+use std::{
+    fs::File,
+    io::Write,
+    path::{Path, PathBuf},
+    process::Command,
+};
 
 use tempfile::TempDir;
 
-use crate::{DiffProvider, Git};
+use crate::{DiffProvider, FileStatus, Git};
 
 fn exec_git_cmd(args: &str, git_dir: &Path) {
     let res = Command::new("git")
@@ -119,3 +124,154 @@ fn symlink() {
     assert_eq!(Git.get_diff_base(&file_link), None);
     assert_eq!(Git.get_diff_base(&file), Some(Vec::from(contents)));
 }
+
+#[test]
+fn blame_line() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo\n").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file)
+        .unwrap()
+        .write_all(b"foo\nbar\n")
+        .unwrap();
+
+    let info = Git.blame_line(&file, b"foo\nbar\n", 0).unwrap().unwrap();
+    assert_eq!(info.hash.len(), 40);
+    assert_eq!(info.author, "author <author@example.com>");
+    assert_eq!(info.date, "2000-01-01 00:00:00 +0000");
+    assert_eq!(info.message, "message");
+    // the second line is not committed yet
+    assert_eq!(Git.blame_line(&file, b"foo\nbar\n", 1).unwrap(), None);
+    // the lines of the unsaved contents are blamed, not the ones of the file
+    assert_eq!(Git.blame_line(&file, b"baz\nfoo\n", 0).unwrap(), None);
+    let moved = Git.blame_line(&file, b"baz\nfoo\n", 1).unwrap().unwrap();
+    assert_eq!(moved.hash, info.hash);
+
+    let diff = Git.commit_diff(&file, &info.hash).unwrap();
+    assert!(diff.contains("+foo"));
+}
+
+#[test]
+fn file_log() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo\n").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"bar\n").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let log = Git.file_log(&file).unwrap();
+    assert_eq!(log.len(), 2);
+    assert_eq!(log[0].author, "author <author@example.com>");
+    assert_eq!(log[0].date, "2000-01-01");
+    assert_eq!(log[0].message, "message");
+    assert_eq!(Git.file_at_commit(&file, &log[0].hash).unwrap(), b"bar\n");
+    assert_eq!(Git.file_at_commit(&file, &log[1].hash).unwrap(), b"foo\n");
+}
+
+#[test]
+fn last_commit() {
+    let temp_git = empty_git_repo();
+    let dir = temp_git.path().join("dir");
+    std::fs::create_dir(&dir).unwrap();
+    let file = dir.join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo\n").unwrap();
+    create_commit(temp_git.path(), true);
+
+    for path in [&file, &dir] {
+        let info = Git.last_commit(path).unwrap().unwrap();
+        assert_eq!(info.hash.len(), 40);
+        assert!(info.date.ends_with(" ago"));
+        assert_eq!(info.message, "message");
+    }
+    let new = dir.join("new.txt");
+    File::create(&new).unwrap().write_all(b"bar\n").unwrap();
+    assert_eq!(Git.last_commit(&new).unwrap(), None);
+}
+
+#[test]
+fn current_head_name() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+    assert_eq!(Git.get_current_head_name(&file).as_deref(), Some("main"));
+
+    exec_git_cmd("checkout --detach", temp_git.path());
+    let head = Git.get_current_head_name(&file).unwrap();
+    assert_eq!(head.len(), 8);
+}
+
+#[test]
+fn repo_info() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+
+    let info = Git.repo_info(temp_git.path()).unwrap();
+    assert_eq!(info.head, "main");
+    assert_eq!((info.ahead, info.behind), (0, 0));
+    assert!(!info.dirty);
+    assert_eq!(info.operation, None);
+    assert_eq!(info.to_string(), "main");
+
+    File::create(&file).unwrap().write_all(b"bar").unwrap();
+    exec_git_cmd("checkout --detach", temp_git.path());
+    let info = Git.repo_info(temp_git.path()).unwrap();
+    assert_eq!(info.head.len(), 8);
+    assert!(info.dirty);
+}
+
+#[test]
+fn status() {
+    let temp_git = empty_git_repo();
+    let write = |name: &str, contents: &[u8]| {
+        let file = temp_git.path().join(name);
+        File::create(&file).unwrap().write_all(contents).unwrap();
+        file
+    };
+    let modified = write("modified.txt", b"foo");
+    write("removed.txt", b"foo");
+    create_commit(temp_git.path(), true);
+    write("modified.txt", b"bar");
+    let untracked = write("untracked.txt", b"foo");
+    std::fs::remove_file(temp_git.path().join("removed.txt")).unwrap();
+
+    let names = |files: Vec<(PathBuf, FileStatus)>| {
+        let mut names: Vec<_> = files
+            .into_iter()
+            .map(|(path, status)| {
+                let name = path.file_name().unwrap().to_string_lossy().into_owned();
+                (name, status)
+            })
+            .collect();
+        names.sort_by(|(a, _), (b, _)| a.cmp(b));
+        names
+    };
+    let status = Git.status(temp_git.path(), &[]).unwrap();
+    assert_eq!(
+        names(status),
+        [
+            ("modified.txt".to_owned(), FileStatus::Modified),
+            ("removed.txt".to_owned(), FileStatus::Deleted),
+            ("untracked.txt".to_owned(), FileStatus::Untracked),
+        ]
+    );
+    let status = Git.status(temp_git.path(), &[modified, untracked]).unwrap();
+    assert_eq!(status.len(), 2);
+}
+
+#[test]
+fn staged_file() {
+    let temp_git = empty_git_repo();
+    let file = temp_git.path().join("file.txt");
+    File::create(&file).unwrap().write_all(b"foo").unwrap();
+    create_commit(temp_git.path(), true);
+    File::create(&file).unwrap().write_all(b"bar").unwrap();
+    exec_git_cmd("add file.txt", temp_git.path());
+    File::create(&file).unwrap().write_all(b"baz").unwrap();
+
+    assert_eq!(Git.get_diff_base(&file), Some(b"bar".to_vec()));
+}
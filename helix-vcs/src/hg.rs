@@ -0,0 +1,133 @@
+//! Mercurial, through the `hg` binary.
+
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{find_root, not_a_repository, run, DiffProvider, FileStatus, RepoInfo};
+
+#[cfg(test)]
+mod test;
+
+pub struct Mercurial;
+
+impl Mercurial {
+    /// Returns the root of the repository containing `file` and the path of `file`
+    /// relative to it.
+    fn repo_path(&self, file: &Path) -> io::Result<(PathBuf, String)> {
+        let root = self.root(file).ok_or_else(not_a_repository)?;
+        let rel_path = file.strip_prefix(&root).map_err(|_| not_a_repository())?;
+        let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+        Ok((root, rel_path))
+    }
+}
+
+fn hg(root: &Path) -> Command {
+    let mut command = Command::new("hg");
+    // plain output, without the configuration of the user changing it
+    command.arg("--cwd").arg(root).env("HGPLAIN", "1");
+    command
+}
+
+impl DiffProvider for Mercurial {
+    fn root(&self, path: &Path) -> Option<PathBuf> {
+        find_root(path, ".hg")
+    }
+
+    fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        let (root, rel_path) = self.repo_path(file).ok()?;
+        run(hg(&root)
+            .args(["cat", "--rev", "."])
+            .arg(format!("path:{rel_path}")))
+        .ok()
+    }
+
+    fn get_current_head_name(&self, file: &Path) -> Option<String> {
+        let root = self.root(file)?;
+        let dot_hg = root.join(".hg");
+        let read = |file: &str| {
+            let name = fs::read_to_string(dot_hg.join(file)).ok()?;
+            let name = name.trim();
+            (!name.is_empty()).then(|| name.to_owned())
+        };
+        // the active bookmark, then the named branch, which is `default` when unset
+        read("bookmarks.current")
+            .or_else(|| read("branch"))
+            .or_else(|| Some("default".to_owned()))
+    }
+
+    fn conflicted_files(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        let root = self.root(dir).ok_or_else(not_a_repository)?;
+        // `U <path>` for the unresolved files, relative to the root
+        let output = run(hg(&root).args(["resolve", "--list"]))?;
+        Ok(String::from_utf8_lossy(&output)
+            .lines()
+            .filter_map(|line| line.strip_prefix("U "))
+            .map(|path| root.join(path))
+            .collect())
+    }
+
+    fn repo_info(&self, dir: &Path) -> io::Result<RepoInfo> {
+        let root = self.root(dir).ok_or_else(not_a_repository)?;
+        let dot_hg = root.join(".hg");
+        let status = run(hg(&root).arg("status"))?;
+        let head = self.get_current_head_name(dir).unwrap_or_default();
+        let operation = [
+            ("rebasestate", "rebasing"),
+            ("histedit-state", "histediting"),
+            ("graftstate", "grafting"),
+            ("merge/state", "merging"),
+        ]
+        .iter()
+        .find(|(file, _)| dot_hg.join(file).exists())
+        .map(|(_, operation)| *operation);
+        Ok(RepoInfo {
+            head,
+            // there is no upstream to compare with
+            ahead: 0,
+            behind: 0,
+            dirty: !status.is_empty(),
+            operation,
+            watch: ["dirstate", "branch", "bookmarks.current"]
+                .iter()
+                .map(|file| dot_hg.join(file))
+                .collect(),
+            work_dir: root,
+        })
+    }
+
+    fn status(&self, dir: &Path, paths: &[PathBuf]) -> io::Result<Vec<(PathBuf, FileStatus)>> {
+        let root = self.root(dir).ok_or_else(not_a_repository)?;
+        let mut patterns: Vec<_> = paths
+            .iter()
+            .filter_map(|path| path.strip_prefix(&root).ok())
+            .map(|path| format!("path:{}", path.to_string_lossy().replace('\\', "/")))
+            .collect();
+        if patterns.is_empty() && !paths.is_empty() {
+            return Ok(Vec::new());
+        }
+        // the root itself stands for all the files
+        if patterns.iter().any(|pattern| pattern == "path:") {
+            patterns.clear();
+        }
+        let conflicted = self.conflicted_files(&root)?;
+        // `<code> <path>`, relative to the root
+        let output = run(hg(&root).args(["status", "--print0"]).args(&patterns))?;
+        Ok(String::from_utf8_lossy(&output)
+            .split('\0')
+            .filter_map(|entry| {
+                let (code, path) = entry.split_once(' ')?;
+                let path = root.join(path);
+                let status = match code {
+                    _ if conflicted.contains(&path) => FileStatus::Conflicted,
+                    "A" => FileStatus::Added,
+                    "R" | "!" => FileStatus::Deleted,
+                    "?" => FileStatus::Untracked,
+                    _ => FileStatus::Modified,
+                };
+                Some((path, status))
+            })
+            .collect())
+    }
+}
@@ -0,0 +1,46 @@
+use std::{fs, path::Path, process::Command};
+
+use tempfile::TempDir;
+
+use crate::{DiffProvider, Mercurial};
+
+fn exec_hg_cmd(args: &str, repo: &Path) {
+    let res = Command::new("hg")
+        .arg("--cwd")
+        .arg(repo)
+        .args(args.split_whitespace())
+        .env("HGPLAIN", "1")
+        .env("HGUSER", "author <author@example.com>")
+        .env("HGRCPATH", "")
+        .output()
+        .unwrap_or_else(|_| panic!("`hg {args}` failed"));
+    if !res.status.success() {
+        println!("{}", String::from_utf8_lossy(&res.stdout));
+        eprintln!("{}", String::from_utf8_lossy(&res.stderr));
+        panic!("`hg {args}` failed (see output above)")
+    }
+}
+
+/// An empty repository, or `None` without Mercurial installed.
+fn empty_hg_repo() -> Option<TempDir> {
+    Command::new("hg").arg("version").output().ok()?;
+    let tmp = tempfile::tempdir().expect("create temp dir for hg testing");
+    exec_hg_cmd("init", tmp.path());
+    Some(tmp)
+}
+
+#[test]
+fn diff_base() {
+    let temp_hg = match empty_hg_repo() {
+        Some(temp_hg) => temp_hg,
+        None => return,
+    };
+    let file = temp_hg.path().join("file.txt");
+    fs::write(&file, b"foo").unwrap();
+    assert_eq!(Mercurial.get_diff_base(&file), None);
+
+    exec_hg_cmd("commit --addremove -m message", temp_hg.path());
+    fs::write(&file, b"bar").unwrap();
+    assert_eq!(Mercurial.root(&file).as_deref(), Some(temp_hg.path()));
+    assert_eq!(Mercurial.get_diff_base(&file), Some(b"foo".to_vec()));
+}
@@ -0,0 +1,80 @@
+//! Jujutsu, through the `jj` binary. The diff base of a file is its content in the parent
+//! of the working copy commit, since the working copy is a commit itself.
+
+use std::io;
+use std::path::{Path, PathBuf};
+use std::process::Command;
+
+use crate::{find_root, not_a_repository, run, DiffProvider, RepoInfo};
+
+#[cfg(test)]
+mod test;
+
+pub struct Jujutsu;
+
+impl Jujutsu {
+    /// Returns the root of the workspace containing `file` and the path of `file`
+    /// relative to it.
+    fn workspace_path(&self, file: &Path) -> io::Result<(PathBuf, String)> {
+        let root = self.root(file).ok_or_else(not_a_repository)?;
+        let rel_path = file.strip_prefix(&root).map_err(|_| not_a_repository())?;
+        let rel_path = rel_path.to_string_lossy().replace('\\', "/");
+        Ok((root, rel_path))
+    }
+}
+
+fn jj(root: &Path) -> Command {
+    let mut command = Command::new("jj");
+    // snapshotting the working copy would create an operation on every call
+    command
+        .current_dir(root)
+        .args(["--ignore-working-copy", "--color=never", "--no-pager"]);
+    command
+}
+
+/// Evaluates `template` for the working copy commit.
+fn log_template(root: &Path, template: &str) -> io::Result<String> {
+    let output = run(jj(root).args(["log", "--no-graph", "-r", "@", "-T", template]))?;
+    Ok(String::from_utf8_lossy(&output).trim().to_owned())
+}
+
+impl DiffProvider for Jujutsu {
+    fn root(&self, path: &Path) -> Option<PathBuf> {
+        find_root(path, ".jj")
+    }
+
+    fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
+        let (root, rel_path) = self.workspace_path(file).ok()?;
+        run(jj(&root)
+            .args(["file", "show", "-r", "@-", "--"])
+            .arg(format!("file:\"{}\"", rel_path.replace('"', "\\\""))))
+        .ok()
+    }
+
+    fn get_current_head_name(&self, file: &Path) -> Option<String> {
+        let root = self.root(file)?;
+        log_template(&root, "change_id.shortest(8)").ok()
+    }
+
+    fn repo_info(&self, dir: &Path) -> io::Result<RepoInfo> {
+        let root = self.root(dir).ok_or_else(not_a_repository)?;
+        let head = log_template(&root, "change_id.shortest(8)")?;
+        let empty = log_template(&root, "empty")?;
+        let operation = (log_template(&root, "conflict")? == "true").then(|| "conflicted");
+        let dot_jj = root.join(".jj");
+        Ok(RepoInfo {
+            head,
+            ahead: 0,
+            behind: 0,
+            // the working copy commit holds the uncommitted changes
+            dirty: empty != "true",
+            operation,
+            // new operations, and the working copy moving to another commit
+            watch: vec![
+                dot_jj.join("repo").join("op_heads").join("heads"),
+                dot_jj.join("working_copy").join("checkout"),
+            ],
+            work_dir: root,
+        })
+    }
+}
@@ -0,0 +1,46 @@
+use std::{fs, path::Path, process::Command};
+
+use tempfile::TempDir;
+
+use crate::{DiffProvider, Jujutsu};
+
+fn exec_jj_cmd(args: &str, repo: &Path) {
+    let res = Command::new("jj")
+        .current_dir(repo)
+        .args(args.split_whitespace())
+        .env("JJ_USER", "author")
+        .env("JJ_EMAIL", "author@example.com")
+        .output()
+        .unwrap_or_else(|_| panic!("`jj {args}` failed"));
+    if !res.status.success() {
+        println!("{}", String::from_utf8_lossy(&res.stdout));
+        eprintln!("{}", String::from_utf8_lossy(&res.stderr));
+        panic!("`jj {args}` failed (see output above)")
+    }
+}
+
+/// An empty repository, or `None` without Jujutsu installed.
+fn empty_jj_repo() -> Option<TempDir> {
+    Command::new("jj").arg("version").output().ok()?;
+    let tmp = tempfile::tempdir().expect("create temp dir for jj testing");
+    exec_jj_cmd("git init", tmp.path());
+    Some(tmp)
+}
+
+#[test]
+fn diff_base() {
+    let temp_jj = match empty_jj_repo() {
+        Some(temp_jj) => temp_jj,
+        None => return,
+    };
+    let file = temp_jj.path().join("file.txt");
+    fs::write(&file, b"foo").unwrap();
+    // the file is in the working copy commit, not yet in its parent
+    exec_jj_cmd("status", temp_jj.path());
+    assert_eq!(Jujutsu.get_diff_base(&file), None);
+
+    exec_jj_cmd("commit -m message", temp_jj.path());
+    fs::write(&file, b"bar").unwrap();
+    assert_eq!(Jujutsu.root(&file).as_deref(), Some(temp_jj.path()));
+    assert_eq!(Jujutsu.get_diff_base(&file), Some(b"foo".to_vec()));
+}
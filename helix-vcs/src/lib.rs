@@ -1,4 +1,10 @@
-use std::path::Path;
+use std::{
+    cmp::Reverse,
+    io,
+    path::{Path, PathBuf},
+    process::Command,
+    sync::Arc,
+};
 
 #[cfg(feature = "git")]
 pub use git::Git;
@@ -9,15 +15,174 @@
 mod git;
 
 mod diff;
+mod file;
+mod hg;
+mod jj;
+mod status;
 
+pub use file::FileBase;
+pub use hg::Mercurial;
+pub use jj::Jujutsu;
+pub use status::{FileStatus, Status, StatusEvent, StatusHandle};
+
+pub use diff::compare::{changed_words, compare_ropes, side_by_side, DiffRow};
 pub use diff::{DiffHandle, Hunk};
 
-pub trait DiffProvider {
+/// Information about the commit that last changed a line.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CommitInfo {
+    /// Full commit hash.
+    pub hash: String,
+    /// Author in the `name <email>` format.
+    pub author: String,
+    /// Author date, formatted by the provider.
+    pub date: String,
+    /// Full commit message.
+    pub message: String,
+}
+
+/// The state of the repository containing the working directory.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct RepoInfo {
+    /// The root of the work tree.
+    pub work_dir: PathBuf,
+    /// The current branch, or an abbreviated commit hash if no branch is checked out.
+    pub head: String,
+    /// Commits of the branch not in its upstream yet, and the other way around.
+    pub ahead: usize,
+    pub behind: usize,
+    /// Whether there are uncommitted changes, including untracked files.
+    pub dirty: bool,
+    /// An operation in progress, like `merging` or `rebasing`.
+    pub operation: Option<&'static str>,
+    /// Files changed by the provider whenever this information changes, watched to
+    /// refresh it.
+    pub watch: Vec<PathBuf>,
+}
+
+impl std::fmt::Display for RepoInfo {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        write!(f, "{}", self.head)?;
+        if self.ahead > 0 {
+            write!(f, " ↑{}", self.ahead)?;
+        }
+        if self.behind > 0 {
+            write!(f, " ↓{}", self.behind)?;
+        }
+        if self.dirty {
+            write!(f, " *")?;
+        }
+        if let Some(operation) = self.operation {
+            write!(f, " ({})", operation)?;
+        }
+        Ok(())
+    }
+}
+
+pub trait DiffProvider: Send + Sync {
+    /// Returns the root of the repository of this provider containing `path`. Of the
+    /// providers with a repository containing a file, the one with the closest root
+    /// is asked first.
+    fn root(&self, _path: &Path) -> Option<PathBuf> {
+        None
+    }
+
     /// Returns the data that a diff should be computed against
     /// if this provider is used.
     /// The data is returned as raw byte without any decoding or encoding performed
     /// to ensure all file encodings are handled correctly.
     fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>>;
+
+    /// Returns the name of the current branch of the repository containing `file`,
+    /// or an abbreviated commit hash if no branch is checked out.
+    fn get_current_head_name(&self, _file: &Path) -> Option<String> {
+        None
+    }
+
+    /// Stages a single hunk of changes to `file`. The hunk is given in the unified
+    /// diff format without context lines, see [`Hunk::to_unified_diff`].
+    fn stage_hunk(&self, _file: &Path, _hunk: &[u8]) -> io::Result<()> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "staging is not supported",
+        ))
+    }
+
+    /// Returns the commit that last changed the zero-based `line` of `file`, whose current
+    /// `contents` may differ from the file on disk. `Ok(None)` is returned if the line has
+    /// not been committed yet.
+    fn blame_line(
+        &self,
+        _file: &Path,
+        _contents: &[u8],
+        _line: usize,
+    ) -> io::Result<Option<CommitInfo>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "blame is not supported",
+        ))
+    }
+
+    /// Returns the full diff introduced by `commit` in the repository containing `file`.
+    fn commit_diff(&self, _file: &Path, _commit: &str) -> io::Result<String> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "showing commits is not supported",
+        ))
+    }
+
+    /// Returns the commits that changed `file`, the most recent first. The message of
+    /// each commit is only its first line.
+    fn file_log(&self, _file: &Path) -> io::Result<Vec<CommitInfo>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "file history is not supported",
+        ))
+    }
+
+    /// Returns the last commit that changed `path`, a file or a directory, with the date
+    /// relative to now and only the first line of the message. `Ok(None)` is returned if
+    /// nothing under `path` has been committed yet.
+    fn last_commit(&self, _path: &Path) -> io::Result<Option<CommitInfo>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "file history is not supported",
+        ))
+    }
+
+    /// Returns the contents of `file` as of `commit`, as raw bytes.
+    fn file_at_commit(&self, _file: &Path, _commit: &str) -> io::Result<Vec<u8>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "showing old versions of files is not supported",
+        ))
+    }
+
+    /// Returns the files with unresolved merge conflicts in the repository containing `dir`.
+    fn conflicted_files(&self, _dir: &Path) -> io::Result<Vec<PathBuf>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "listing conflicts is not supported",
+        ))
+    }
+
+    /// Returns the branch and state of the repository containing `dir`.
+    fn repo_info(&self, _dir: &Path) -> io::Result<RepoInfo> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "repository information is not supported",
+        ))
+    }
+
+    /// Returns the files of the repository containing `dir` which differ from the last
+    /// commit, with their status. Only the files under `paths` are listed, unless it is
+    /// empty.
+    fn status(&self, _dir: &Path, _paths: &[PathBuf]) -> io::Result<Vec<(PathBuf, FileStatus)>> {
+        Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            "listing changed files is not supported",
+        ))
+    }
 }
 
 #[doc(hidden)]
@@ -28,24 +193,171 @@ fn get_diff_base(&self, _file: &Path) -> Option<Vec<u8>> {
     }
 }
 
+/// The providers asked about the files, cheap to clone into the background tasks.
+#[derive(Clone)]
 pub struct DiffProviderRegistry {
-    providers: Vec<Box<dyn DiffProvider>>,
+    providers: Arc<[Box<dyn DiffProvider>]>,
 }
 
 impl DiffProviderRegistry {
     pub fn get_diff_base(&self, file: &Path) -> Option<Vec<u8>> {
-        self.providers
-            .iter()
+        self.providers_for(file)
+            .into_iter()
             .find_map(|provider| provider.get_diff_base(file))
     }
+
+    pub fn get_current_head_name(&self, file: &Path) -> Option<String> {
+        self.providers_for(file)
+            .into_iter()
+            .find_map(|provider| provider.get_current_head_name(file))
+    }
+
+    /// Stages `hunk` with the first provider that supports staging `file`.
+    pub fn stage_hunk(&self, file: &Path, hunk: &[u8]) -> io::Result<()> {
+        self.first_supported(file, "staging this file", |provider| {
+            provider.stage_hunk(file, hunk)
+        })
+    }
+
+    /// Blames `line` of `file`, as in its current `contents`, with the first provider that
+    /// supports it.
+    pub fn blame_line(
+        &self,
+        file: &Path,
+        contents: &[u8],
+        line: usize,
+    ) -> io::Result<Option<CommitInfo>> {
+        self.first_supported(file, "blaming this file", |provider| {
+            provider.blame_line(file, contents, line)
+        })
+    }
+
+    /// Returns the diff of `commit` from the first provider that supports `file`.
+    pub fn commit_diff(&self, file: &Path, commit: &str) -> io::Result<String> {
+        self.first_supported(file, "showing commits of this file", |provider| {
+            provider.commit_diff(file, commit)
+        })
+    }
+
+    /// Returns the history of `file` from the first provider that supports it.
+    pub fn file_log(&self, file: &Path) -> io::Result<Vec<CommitInfo>> {
+        self.first_supported(file, "the history of this file", |provider| {
+            provider.file_log(file)
+        })
+    }
+
+    /// Returns the last commit of `path` from the first provider that supports it.
+    pub fn last_commit(&self, path: &Path) -> io::Result<Option<CommitInfo>> {
+        self.first_supported(path, "the history of this path", |provider| {
+            provider.last_commit(path)
+        })
+    }
+
+    /// Returns `file` as of `commit` from the first provider that supports it.
+    pub fn file_at_commit(&self, file: &Path, commit: &str) -> io::Result<Vec<u8>> {
+        self.first_supported(file, "old versions of this file", |provider| {
+            provider.file_at_commit(file, commit)
+        })
+    }
+
+    /// Returns the conflicted files from the first provider that supports `dir`.
+    pub fn conflicted_files(&self, dir: &Path) -> io::Result<Vec<PathBuf>> {
+        self.first_supported(dir, "listing conflicts in this directory", |provider| {
+            provider.conflicted_files(dir)
+        })
+    }
+
+    /// Returns the state of the repository containing `dir` from the first provider
+    /// that supports it.
+    pub fn repo_info(&self, dir: &Path) -> io::Result<RepoInfo> {
+        self.first_supported(dir, "this directory", |provider| provider.repo_info(dir))
+    }
+
+    /// Returns the changed files under `paths` from the first provider that supports `dir`.
+    pub fn status(&self, dir: &Path, paths: &[PathBuf]) -> io::Result<Vec<(PathBuf, FileStatus)>> {
+        self.first_supported(dir, "listing changed files", |provider| {
+            provider.status(dir, paths)
+        })
+    }
+
+    /// The providers in the order they are asked about `path`: the ones with the closest
+    /// repository containing it first, then the ones which do not tell their root.
+    fn providers_for(&self, path: &Path) -> Vec<&dyn DiffProvider> {
+        let mut providers: Vec<_> = self
+            .providers
+            .iter()
+            .map(|provider| {
+                let depth = provider.root(path).map(|root| root.components().count());
+                (Reverse(depth), provider.as_ref())
+            })
+            .collect();
+        // the sort is stable, providers with the same root keep their order
+        providers.sort_by_key(|(depth, _)| *depth);
+        providers
+            .into_iter()
+            .map(|(_, provider)| provider)
+            .collect()
+    }
+
+    /// Calls `f` for each provider of `path` until one does not return
+    /// [`io::ErrorKind::Unsupported`].
+    fn first_supported<T>(
+        &self,
+        path: &Path,
+        what: &str,
+        mut f: impl FnMut(&dyn DiffProvider) -> io::Result<T>,
+    ) -> io::Result<T> {
+        let mut res = Err(io::Error::new(
+            io::ErrorKind::Unsupported,
+            format!("no diff provider supports {what}"),
+        ));
+        for provider in self.providers_for(path) {
+            res = f(provider);
+            if !matches!(&res, Err(err) if err.kind() == io::ErrorKind::Unsupported) {
+                break;
+            }
+        }
+        res
+    }
 }
 
 impl Default for DiffProviderRegistry {
     fn default() -> Self {
-        // currently only git is supported
-        // TODO make this configurable when more providers are added
-        let git: Box<dyn DiffProvider> = Box::new(Git);
-        let providers = vec![git];
-        DiffProviderRegistry { providers }
+        // Jujutsu comes first for the repositories colocated with git
+        let providers: Vec<Box<dyn DiffProvider>> = vec![
+            Box::new(Jujutsu),
+            Box::new(Git),
+            Box::new(Mercurial),
+            Box::new(FileBase),
+        ];
+        DiffProviderRegistry {
+            providers: providers.into(),
+        }
+    }
+}
+
+/// Returns the closest ancestor of `path` containing `marker`, like `.git`.
+fn find_root(path: &Path, marker: &str) -> Option<PathBuf> {
+    path.ancestors()
+        .find(|dir| dir.join(marker).exists())
+        .map(Path::to_path_buf)
+}
+
+/// Runs `command`, returning its output. It fails with the error output of the
+/// command if it exits with an error.
+fn run(command: &mut Command) -> io::Result<Vec<u8>> {
+    let output = command.stdin(std::process::Stdio::null()).output()?;
+    if output.status.success() {
+        Ok(output.stdout)
+    } else {
+        Err(io::Error::new(
+            io::ErrorKind::Other,
+            String::from_utf8_lossy(&output.stderr).trim().to_owned(),
+        ))
     }
 }
+
+/// The error of the providers asked about a path outside of their repositories.
+fn not_a_repository() -> io::Error {
+    io::Error::new(io::ErrorKind::Unsupported, "not in a repository")
+}
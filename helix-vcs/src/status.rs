@@ -0,0 +1,376 @@
+//! The status of the files of the repository containing the working directory, kept by
+//! a long-lived background task. The whole work tree is asked about when the repository
+//! itself changes, like after a commit, a checkout or staging files, and when the editor
+//! notices files changed by other programs, see [`StatusHandle::refresh`]; the files
+//! written in between are asked about alone. The subscribers receive the differences
+//! once, instead of each of them running the version control system.
+
+use std::{
+    collections::HashMap,
+    mem,
+    path::{Path, PathBuf},
+    time::{Duration, SystemTime},
+};
+
+use tokio::sync::mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender};
+
+use crate::{DiffProviderRegistry, RepoInfo};
+
+/// How a file differs from the last commit, in the index or in the work tree.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum FileStatus {
+    Modified,
+    Added,
+    Deleted,
+    Renamed,
+    Untracked,
+    /// With unresolved merge conflicts.
+    Conflicted,
+}
+
+/// The state of the repository containing the working directory and the files differing
+/// from its last commit, by absolute path.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Status {
+    /// `None` outside of a repository.
+    pub info: Option<RepoInfo>,
+    pub files: HashMap<PathBuf, FileStatus>,
+}
+
+/// A change of the [`Status`] sent to the subscribers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum StatusEvent {
+    /// The status of another repository, replacing the previous one. It is also the
+    /// first event of each subscriber.
+    Reset(Status),
+    /// The files whose status changed, `None` for the ones no longer differing from the
+    /// last commit, and the new state of the repository if it changed.
+    Delta {
+        files: Vec<(PathBuf, Option<FileStatus>)>,
+        info: Option<RepoInfo>,
+    },
+}
+
+impl Status {
+    pub fn apply(&mut self, event: StatusEvent) {
+        match event {
+            StatusEvent::Reset(status) => *self = status,
+            StatusEvent::Delta { files, info } => {
+                for (path, status) in files {
+                    match status {
+                        Some(status) => self.files.insert(path, status),
+                        None => self.files.remove(&path),
+                    };
+                }
+                if info.is_some() {
+                    self.info = info;
+                }
+            }
+        }
+    }
+
+    /// The files with unresolved merge conflicts.
+    pub fn conflicted(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, status)| **status == FileStatus::Conflicted)
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// The event turning `self` into `new`, `None` if they are the same.
+    fn delta(&self, new: &Status) -> Option<StatusEvent> {
+        let work_dir = |status: &Status| status.info.as_ref().map(|info| info.work_dir.clone());
+        if work_dir(self) != work_dir(new) {
+            return Some(StatusEvent::Reset(new.clone()));
+        }
+        let mut files: Vec<_> = new
+            .files
+            .iter()
+            .filter(|(path, status)| self.files.get(*path) != Some(status))
+            .map(|(path, status)| (path.clone(), Some(*status)))
+            .collect();
+        files.extend(
+            self.files
+                .keys()
+                .filter(|path| !new.files.contains_key(*path))
+                .map(|path| (path.clone(), None)),
+        );
+        let info = (self.info != new.info).then(|| new.info.clone()).flatten();
+        (!files.is_empty() || info.is_some()).then(|| StatusEvent::Delta { files, info })
+    }
+}
+
+enum Request {
+    Subscribe(UnboundedSender<StatusEvent>),
+    Changed(Vec<PathBuf>),
+    Refresh,
+}
+
+/// A handle to the status task, which stops once all the handles are dropped.
+#[derive(Clone)]
+pub struct StatusHandle {
+    requests: UnboundedSender<Request>,
+}
+
+impl StatusHandle {
+    /// Starts the task, which checks for changes every `interval` with `providers`. It has
+    /// to be called within the tokio runtime.
+    pub fn spawn(interval: Duration, providers: DiffProviderRegistry) -> Self {
+        let (sender, receiver) = unbounded_channel();
+        let service = StatusService {
+            providers,
+            requests: receiver,
+            subscribers: Vec::new(),
+            status: Status::default(),
+            stamp: None,
+            refresh: false,
+            changed: Vec::new(),
+        };
+        tokio::spawn(service.run(interval));
+        Self { requests: sender }
+    }
+
+    /// Receives the current status and then its changes.
+    pub fn subscribe(&self) -> UnboundedReceiver<StatusEvent> {
+        let (sender, receiver) = unbounded_channel();
+        let _ = self.requests.send(Request::Subscribe(sender));
+        receiver
+    }
+
+    /// Asks about `paths` again at the next check, after files or directories were
+    /// written, created or removed.
+    pub fn changed(&self, paths: Vec<PathBuf>) {
+        let _ = self.requests.send(Request::Changed(paths));
+    }
+
+    /// Asks about the whole work tree again at the next check, once files may have been
+    /// changed by other programs.
+    pub fn refresh(&self) {
+        let _ = self.requests.send(Request::Refresh);
+    }
+}
+
+struct StatusService {
+    providers: DiffProviderRegistry,
+    requests: UnboundedReceiver<Request>,
+    subscribers: Vec<UnboundedSender<StatusEvent>>,
+    status: Status,
+    /// The working directory and the modification times of the files watched for the
+    /// repository as of the last full refresh, `None` before the first one.
+    stamp: Option<(PathBuf, Vec<Option<SystemTime>>)>,
+    /// Whether the whole work tree is asked about at the next check.
+    refresh: bool,
+    /// The paths reported changed since the last check.
+    changed: Vec<PathBuf>,
+}
+
+impl StatusService {
+    async fn run(mut self, interval: Duration) {
+        let mut interval = tokio::time::interval(interval);
+        loop {
+            tokio::select! {
+                request = self.requests.recv() => match request {
+                    Some(Request::Subscribe(subscriber)) => {
+                        if subscriber.send(StatusEvent::Reset(self.status.clone())).is_ok() {
+                            self.subscribers.push(subscriber);
+                        }
+                    }
+                    Some(Request::Changed(paths)) => self.changed.extend(paths),
+                    Some(Request::Refresh) => self.refresh = true,
+                    None => break,
+                },
+                _ = interval.tick() => self.check().await,
+            }
+        }
+    }
+
+    fn stamp(&self) -> (PathBuf, Vec<Option<SystemTime>>) {
+        let cwd = std::env::current_dir().unwrap_or_default();
+        let watch = self
+            .status
+            .info
+            .as_ref()
+            .map_or(&[][..], |info| &info.watch);
+        let mtimes = watch
+            .iter()
+            .map(|path| {
+                std::fs::metadata(path)
+                    .and_then(|meta| meta.modified())
+                    .ok()
+            })
+            .collect();
+        (cwd, mtimes)
+    }
+
+    async fn check(&mut self) {
+        let (cwd, mtimes) = self.stamp();
+        let changed = mem::take(&mut self.changed);
+        let refresh = mem::take(&mut self.refresh);
+        let event = if refresh || self.stamp.as_ref() != Some(&(cwd.clone(), mtimes)) {
+            let providers = self.providers.clone();
+            let status = tokio::task::spawn_blocking(move || full_status(&providers, &cwd));
+            let status = match status.await {
+                Ok(status) => status,
+                Err(err) => {
+                    log::error!("failed to refresh the status: {}", err);
+                    return;
+                }
+            };
+            let event = self.status.delta(&status);
+            self.status = status;
+            // the files watched may have changed with the repository
+            self.stamp = Some(self.stamp());
+            event
+        } else if !changed.is_empty() {
+            self.refresh_paths(changed).await
+        } else {
+            None
+        };
+
+        if let Some(event) = event {
+            self.subscribers
+                .retain(|subscriber| subscriber.send(event.clone()).is_ok());
+        }
+    }
+
+    /// Asks about the changed `paths` alone, and updates the status with the answer.
+    async fn refresh_paths(&mut self, paths: Vec<PathBuf>) -> Option<StatusEvent> {
+        let info = self.status.info.clone()?;
+        let work_dir = info.work_dir.clone();
+        let paths: Vec<_> = paths
+            .into_iter()
+            .filter(|path| path.starts_with(&work_dir))
+            .collect();
+        if paths.is_empty() {
+            return None;
+        }
+        let providers = self.providers.clone();
+        let (listed, paths) = tokio::task::spawn_blocking(move || {
+            let listed = providers.status(&work_dir, &paths);
+            (listed, paths)
+        })
+        .await
+        .ok()?;
+        let listed: HashMap<_, _> = match listed {
+            Ok(listed) => listed.into_iter().collect(),
+            Err(err) => {
+                log::warn!("failed to refresh the status of changed files: {}", err);
+                return None;
+            }
+        };
+
+        // the files under the paths which are no longer listed are unchanged now
+        let mut files: Vec<_> = self
+            .status
+            .files
+            .keys()
+            .filter(|file| {
+                !listed.contains_key(*file) && paths.iter().any(|path| file.starts_with(path))
+            })
+            .map(|file| (file.clone(), None))
+            .collect();
+        files.extend(
+            listed
+                .into_iter()
+                .filter(|(file, status)| self.status.files.get(file) != Some(status))
+                .map(|(file, status)| (file, Some(status))),
+        );
+        if files.is_empty() {
+            return None;
+        }
+        let mut event = StatusEvent::Delta { files, info: None };
+        self.status.apply(event.clone());
+
+        let dirty = !self.status.files.is_empty();
+        if dirty != info.dirty {
+            let info = RepoInfo { dirty, ..info };
+            self.status.info = Some(info.clone());
+            if let StatusEvent::Delta {
+                info: event_info, ..
+            } = &mut event
+            {
+                *event_info = Some(info);
+            }
+        }
+        Some(event)
+    }
+}
+
+/// The status of the whole repository containing `dir`.
+fn full_status(providers: &DiffProviderRegistry, dir: &Path) -> Status {
+    let info = match providers.repo_info(dir) {
+        Ok(info) => info,
+        Err(_) => return Status::default(),
+    };
+    let files = match providers.status(&info.work_dir, &[]) {
+        Ok(files) => files.into_iter().collect(),
+        Err(err) => {
+            log::debug!("failed to list the status of the files: {}", err);
+            HashMap::new()
+        }
+    };
+    Status {
+        info: Some(info),
+        files,
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn info(dirty: bool) -> RepoInfo {
+        RepoInfo {
+            work_dir: PathBuf::from("/repo"),
+            head: "main".to_owned(),
+            ahead: 0,
+            behind: 0,
+            dirty,
+            operation: None,
+            watch: Vec::new(),
+        }
+    }
+
+    #[test]
+    fn delta() {
+        let file = |name: &str| PathBuf::from("/repo").join(name);
+        let old = Status {
+            info: Some(info(true)),
+            files: [
+                (file("a"), FileStatus::Modified),
+                (file("b"), FileStatus::Untracked),
+            ]
+            .into_iter()
+            .collect(),
+        };
+        let new = Status {
+            info: Some(info(true)),
+            files: [
+                (file("a"), FileStatus::Conflicted),
+                (file("c"), FileStatus::Added),
+            ]
+            .into_iter()
+            .collect(),
+        };
+
+        let event = old.delta(&new).unwrap();
+        match &event {
+            StatusEvent::Delta { files, info } => {
+                assert_eq!(files.len(), 3);
+                assert!(files.contains(&(file("b"), None)));
+                assert!(info.is_none());
+            }
+            StatusEvent::Reset(_) => panic!("the repository is the same"),
+        }
+        let mut applied = old.clone();
+        applied.apply(event);
+        assert_eq!(applied, new);
+        assert_eq!(applied.conflicted().collect::<Vec<_>>(), [file("a")]);
+
+        assert_eq!(new.delta(&new), None);
+        assert!(matches!(
+            new.delta(&Status::default()),
+            Some(StatusEvent::Reset(_))
+        ));
+    }
+}
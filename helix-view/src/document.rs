@@ -2,8 +2,10 @@
 use futures_util::future::BoxFuture;
 use futures_util::FutureExt;
 use helix_core::auto_pairs::AutoPairs;
+use helix_core::conflict::{self, Conflict};
 use helix_core::Range;
 use helix_vcs::{DiffHandle, DiffProviderRegistry};
+use once_cell::unsync::OnceCell;
 
 use serde::de::{self, Deserialize, Deserializer};
 use serde::Serialize;
@@ -15,6 +17,7 @@
 use std::path::{Path, PathBuf};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::time::SystemTime;
 
 use helix_core::{
     encoding,
@@ -27,6 +30,7 @@
 };
 
 use crate::editor::RedrawHandle;
+use crate::editorconfig::EditorConfig;
 use crate::{apply_transaction, DocumentId, Editor, View, ViewId};
 
 /// 8kB of buffer space for encoding and decoding `Rope`s.
@@ -86,6 +90,37 @@ fn serialize<S>(&self, serializer: S) -> Result<S::Ok, S::Error>
     }
 }
 
+/// The result of the last request for code actions on a line of a document.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct CodeActionHint {
+    pub line: usize,
+    /// Document version the request was made at.
+    pub version: i32,
+    /// Whether the language server reported any code actions. `false` while the
+    /// request is still pending.
+    pub available: bool,
+}
+
+/// An inlay hint from the language server, drawn as virtual text in front of the
+/// char at `char_idx`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct InlayHint {
+    pub char_idx: usize,
+    /// Text of the hint, including the padding requested by the server.
+    pub label: String,
+    pub kind: Option<helix_lsp::lsp::InlayHintKind>,
+}
+
+/// A symbol of the document as reported by the language server, spanning the chars
+/// `start..end`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DocumentSymbol {
+    pub name: String,
+    pub kind: helix_lsp::lsp::SymbolKind,
+    pub start: usize,
+    pub end: usize,
+}
+
 /// A snapshot of the text of a document that we want to write out to disk
 #[derive(Debug, Clone)]
 pub struct DocumentSavedEvent {
@@ -93,6 +128,10 @@ pub struct DocumentSavedEvent {
     pub doc_id: DocumentId,
     pub path: PathBuf,
     pub text: Rope,
+    /// Modification time of the written file.
+    pub mtime: Option<SystemTime>,
+    /// Whether the file did not exist before.
+    pub created: bool,
 }
 
 pub type DocumentSavedEventResult = Result<DocumentSavedEvent, anyhow::Error>;
@@ -138,6 +177,32 @@ pub struct Document {
     language_server: Option<Arc<helix_lsp::Client>>,
 
     diff_handle: Option<DiffHandle>,
+    version_control_head: Option<String>,
+    code_action_hint: Option<CodeActionHint>,
+    /// Inlay hints sorted by position, and the version of the document they were requested at.
+    inlay_hints: Vec<InlayHint>,
+    inlay_hints_version: Option<i32>,
+    /// Document symbols sorted by position, and the version of the document they were requested at.
+    symbols: Vec<DocumentSymbol>,
+    symbols_version: Option<i32>,
+    /// Conflicts left by a merge, found when first asked for after each change of the text.
+    conflicts: OnceCell<Vec<Conflict>>,
+    /// Modification time of the file as of the last time it was read or written, to
+    /// notice changes made outside of the editor.
+    disk_mtime: Option<SystemTime>,
+    /// The properties of the `.editorconfig` files applying to the file.
+    editor_config: EditorConfig,
+    /// Whether the file is opened in large-file mode, without syntax highlighting,
+    /// language server or diff.
+    large_file: bool,
+    /// Whether the text of a large file is still being read, the document is empty and
+    /// cannot be changed or written until then.
+    loading: bool,
+    /// Whether the text cannot be changed or written, like for old versions of files.
+    readonly: bool,
+    /// Whether the buffer is listed first in the bufferline and kept open by
+    /// `:buffer-close-others`.
+    pinned: bool,
 }
 
 use std::{fmt, mem};
@@ -164,6 +229,13 @@ fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
     }
 }
 
+/// Modification time of the file at `path`, `None` if it cannot be read.
+fn file_mtime(path: &Path) -> Option<SystemTime> {
+    std::fs::metadata(path)
+        .and_then(|meta| meta.modified())
+        .ok()
+}
+
 // The documentation and implementation of this function should be up-to-date with
 // its sibling function, `to_writer()`.
 //
@@ -183,19 +255,21 @@ pub fn from_reader<R: std::io::Read + ?Sized>(
     let mut buf_out = [0u8; BUF_SIZE];
     let mut builder = RopeBuilder::new();
 
-    // By default, the encoding of the text is auto-detected via the
-    // `chardetng` crate which requires sample data from the reader.
+    // By default, the encoding of the text is auto-detected from its byte order mark,
+    // or else via the `chardetng` crate which requires sample data from the reader.
     // As a manual override to this auto-detection is possible, the
     // same data is read into `buf` to ensure symmetry in the upcoming
     // loop.
     let (encoding, mut decoder, mut slice, mut is_empty) = {
         let read = reader.read(&mut buf)?;
         let is_empty = read == 0;
-        let encoding = encoding.unwrap_or_else(|| {
-            let mut encoding_detector = chardetng::EncodingDetector::new();
-            encoding_detector.feed(&buf, is_empty);
-            encoding_detector.guess(None, true)
-        });
+        let encoding = encoding
+            .or_else(|| encoding::Encoding::for_bom(&buf[..read]).map(|(encoding, _)| encoding))
+            .unwrap_or_else(|| {
+                let mut encoding_detector = chardetng::EncodingDetector::new();
+                encoding_detector.feed(&buf[..read], is_empty);
+                encoding_detector.guess(None, true)
+            });
         let decoder = encoding.new_decoder();
 
         // If the amount of bytes read from the reader is less than
@@ -283,6 +357,10 @@ pub async fn to_writer<'a, W: tokio::io::AsyncWriteExt + Unpin + ?Sized>(
     encoding: &'static encoding::Encoding,
     rope: &'a Rope,
 ) -> Result<(), Error> {
+    if encoding == encoding::UTF_16LE || encoding == encoding::UTF_16BE {
+        return to_writer_utf16(writer, encoding == encoding::UTF_16BE, rope).await;
+    }
+
     // Text inside a `Rope` is stored as non-contiguous blocks of data called
     // chunks. The absolute size of each chunk is unknown, thus it is impossible
     // to predict the end of the chunk iterator ahead of time. Instead, it is
@@ -339,6 +417,35 @@ pub async fn to_writer<'a, W: tokio::io::AsyncWriteExt + Unpin + ?Sized>(
     Ok(())
 }
 
+/// Writes `rope` as UTF-16, which the encoders of `encoding_rs` do not support. A byte
+/// order mark is written first, without it the encoding is not detected when the file is
+/// read again.
+async fn to_writer_utf16<'a, W: tokio::io::AsyncWriteExt + Unpin + ?Sized>(
+    writer: &'a mut W,
+    big_endian: bool,
+    rope: &'a Rope,
+) -> Result<(), Error> {
+    let mut buf = Vec::with_capacity(BUF_SIZE + 4);
+    buf.extend_from_slice(if big_endian { b"\xFE\xFF" } else { b"\xFF\xFE" });
+    for chunk in rope.chunks() {
+        for unit in chunk.encode_utf16() {
+            let bytes = if big_endian {
+                unit.to_be_bytes()
+            } else {
+                unit.to_le_bytes()
+            };
+            buf.extend_from_slice(&bytes);
+            if buf.len() >= BUF_SIZE {
+                writer.write_all(&buf).await?;
+                buf.clear();
+            }
+        }
+    }
+    writer.write_all(&buf).await?;
+    writer.flush().await?;
+    Ok(())
+}
+
 fn take_with<T, F>(mut_ref: &mut T, f: F)
 where
     T: Default,
@@ -377,6 +484,19 @@ pub fn from(text: Rope, encoding: Option<&'static encoding::Encoding>) -> Self {
             modified_since_accessed: false,
             language_server: None,
             diff_handle: None,
+            version_control_head: None,
+            code_action_hint: None,
+            inlay_hints: Vec::new(),
+            inlay_hints_version: None,
+            symbols: Vec::new(),
+            symbols_version: None,
+            conflicts: OnceCell::new(),
+            disk_mtime: None,
+            editor_config: EditorConfig::default(),
+            large_file: false,
+            loading: false,
+            readonly: false,
+            pinned: false,
         }
     }
 
@@ -402,6 +522,7 @@ pub fn open(
 
         // set the path and try detecting the language
         doc.set_path(Some(path))?;
+        doc.disk_mtime = file_mtime(path);
         if let Some(loader) = config_loader {
             doc.detect_language(loader);
         }
@@ -411,6 +532,55 @@ pub fn open(
         Ok(doc)
     }
 
+    /// Creates an empty document for the large file at `path`, in large-file mode. Its
+    /// text is read separately and set with [`Document::finish_loading`].
+    pub fn open_large(path: &Path, encoding: Option<&'static encoding::Encoding>) -> Self {
+        let mut doc = Self::from(Rope::new(), encoding);
+        doc.path = Some(path.to_path_buf());
+        doc.disk_mtime = file_mtime(path);
+        doc.large_file = true;
+        doc.loading = true;
+        doc
+    }
+
+    /// Sets the text read from the file of a document opened with
+    /// [`Document::open_large`]. The cursors are moved to the start of the text.
+    pub fn finish_loading(&mut self, text: Rope, encoding: &'static encoding::Encoding) {
+        self.changes = ChangeSet::new(&text);
+        self.text = text;
+        self.encoding = encoding;
+        self.loading = false;
+        for selection in self.selections.values_mut() {
+            *selection = Selection::point(0);
+        }
+        self.version += 1;
+        self.detect_indent_and_line_ending();
+    }
+
+    pub fn is_large_file(&self) -> bool {
+        self.large_file
+    }
+
+    pub fn is_loading(&self) -> bool {
+        self.loading
+    }
+
+    pub fn set_readonly(&mut self, readonly: bool) {
+        self.readonly = readonly;
+    }
+
+    pub fn is_readonly(&self) -> bool {
+        self.readonly
+    }
+
+    pub fn set_pinned(&mut self, pinned: bool) {
+        self.pinned = pinned;
+    }
+
+    pub fn is_pinned(&self) -> bool {
+        self.pinned
+    }
+
     /// The same as [`format`], but only returns formatting changes if auto-formatting
     /// is configured.
     pub fn auto_format(&self) -> Option<BoxFuture<'static, Result<Transaction, FormatterError>>> {
@@ -538,6 +708,12 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
             "submitting save of doc '{:?}'",
             self.path().map(|path| path.to_string_lossy())
         );
+        if self.loading {
+            bail!("Can't save while the file is still loading");
+        }
+        if self.readonly {
+            bail!("Can't save a read-only buffer");
+        }
 
         // we clone and move text + path into the future so that we asynchronously save the current
         // state without blocking any further edits.
@@ -577,14 +753,18 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
                 }
             }
 
+            let created = !path.exists();
             let mut file = File::create(&path).await?;
             to_writer(&mut file, encoding, &text).await?;
+            let mtime = file.metadata().await?.modified().ok();
 
             let event = DocumentSavedEvent {
                 revision: current_rev,
                 doc_id,
                 path,
                 text: text.clone(),
+                mtime,
+                created,
             };
 
             if let Some(language_server) = language_server {
@@ -596,7 +776,14 @@ impl Future<Output = Result<DocumentSavedEvent, anyhow::Error>> + 'static + Send
                     if let Some(notification) =
                         language_server.text_document_did_save(identifier, &text)
                     {
-                        notification.await?;
+                        // the file is written either way, its modification time has to
+                        // reach the document not to be taken for a change on disk
+                        if let Err(err) = notification.await {
+                            log::error!(
+                                "failed to notify the language server of the save: {}",
+                                err
+                            );
+                        }
                     }
                 }
             }
@@ -621,11 +808,16 @@ pub fn detect_language(&mut self, config_loader: Arc<syntax::Loader>) {
     /// configured in `languages.toml`, with a fallback to tabs if it isn't specified. Line ending
     /// is likewise auto-detected, and will fallback to the default OS line ending.
     pub fn detect_indent_and_line_ending(&mut self) {
-        self.indent_style = auto_detect_indent_style(&self.text).unwrap_or_else(|| {
-            self.language_config()
-                .and_then(|config| config.indent.as_ref())
-                .map_or(DEFAULT_INDENT, |config| IndentStyle::from_str(&config.unit))
-        });
+        // the indentation of the `.editorconfig` files wins over the one of the text
+        self.indent_style = self
+            .editor_config
+            .indent_style
+            .or_else(|| auto_detect_indent_style(&self.text))
+            .unwrap_or_else(|| {
+                self.language_config()
+                    .and_then(|config| config.indent.as_ref())
+                    .map_or(DEFAULT_INDENT, |config| IndentStyle::from_str(&config.unit))
+            });
         self.line_ending = auto_detect_line_ending(&self.text).unwrap_or(DEFAULT_LINE_ENDING);
     }
 
@@ -645,6 +837,7 @@ pub fn reload(
 
         let mut file = std::fs::File::open(&path)?;
         let (rope, ..) = from_reader(&mut file, Some(encoding))?;
+        self.disk_mtime = file_mtime(&path);
 
         // Calculate the difference between the buffer and source text, and apply it.
         // This is not considered a modification of the contents of the file regardless
@@ -660,6 +853,7 @@ pub fn reload(
             Some(diff_base) => self.set_diff_base(diff_base, redraw_handle),
             None => self.diff_handle = None,
         }
+        self.version_control_head = provider_registry.get_current_head_name(&path);
 
         Ok(())
     }
@@ -806,6 +1000,7 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
             if let Some(diff_handle) = &self.diff_handle {
                 diff_handle.update_document(self.text.clone(), false);
             }
+            self.conflicts = OnceCell::new();
 
             // generate revert to savepoint
             if self.savepoint.is_some() {
@@ -834,6 +1029,18 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
             self.diagnostics
                 .sort_unstable_by_key(|diagnostic| diagnostic.range);
 
+            // keep inlay hints attached to the char following them until they are refreshed
+            for hint in &mut self.inlay_hints {
+                hint.char_idx = transaction
+                    .changes()
+                    .map_pos(hint.char_idx, helix_core::Assoc::After);
+            }
+            for symbol in &mut self.symbols {
+                let changes = transaction.changes();
+                symbol.start = changes.map_pos(symbol.start, helix_core::Assoc::After);
+                symbol.end = changes.map_pos(symbol.end, helix_core::Assoc::Before);
+            }
+
             // emit lsp notification
             if let Some(language_server) = self.language_server() {
                 let notify = language_server.text_document_did_change(
@@ -856,6 +1063,10 @@ fn apply_impl(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
     /// to ensure that the transaction is applied to the appropriate [`View`] as
     /// well.
     pub fn apply(&mut self, transaction: &Transaction, view_id: ViewId) -> bool {
+        // the changes of a loading document would be lost once its text is read
+        if self.loading || self.readonly {
+            return false;
+        }
         // store the state just before any changes are made. This allows us to undo to the
         // state just before a transaction was applied.
         if self.changes.is_empty() && !transaction.changes().is_empty() {
@@ -921,6 +1132,10 @@ fn earlier_later_impl(&mut self, view: &mut View, uk: UndoKind, earlier: bool) -
         } else {
             self.history.get_mut().later(uk)
         };
+        self.apply_history_jump(view, txns)
+    }
+
+    fn apply_history_jump(&mut self, view: &mut View, txns: Vec<Transaction>) -> bool {
         let mut success = false;
         for txn in txns {
             if self.apply_impl(&txn, view.id) {
@@ -946,6 +1161,12 @@ pub fn later(&mut self, view: &mut View, uk: UndoKind) -> bool {
         self.earlier_later_impl(view, uk, false)
     }
 
+    /// Moves to `revision` of the history, on its branch of the undo tree.
+    pub fn jump_to_revision(&mut self, view: &mut View, revision: usize) -> bool {
+        let txns = self.history.get_mut().jump_to(revision);
+        self.apply_history_jump(view, txns)
+    }
+
     /// Commit pending changes to history
     pub fn append_changes_to_history(&mut self, view: &mut View) {
         if self.changes.is_empty() {
@@ -996,6 +1217,41 @@ pub fn reset_modified(&mut self) {
         self.last_saved_revision = current_revision;
     }
 
+    pub fn editor_config(&self) -> &EditorConfig {
+        &self.editor_config
+    }
+
+    /// Applies the properties of the `.editorconfig` files of the file, detecting the
+    /// indentation again.
+    pub fn set_editor_config(&mut self, editor_config: EditorConfig) {
+        self.editor_config = editor_config;
+        self.detect_indent_and_line_ending();
+    }
+
+    /// Sets the modification time of the file as of writing it.
+    pub fn set_disk_mtime(&mut self, mtime: Option<SystemTime>) {
+        self.disk_mtime = mtime;
+    }
+
+    /// Whether the file was changed outside of the editor since it was last read or
+    /// written. The new modification time is remembered, every change is reported once.
+    pub fn check_changed_on_disk(&mut self) -> bool {
+        // the file is compared once it is read
+        if self.loading {
+            return false;
+        }
+        let mtime = match self.path.as_deref().and_then(file_mtime) {
+            Some(mtime) => mtime,
+            // a deleted file is left to the next write
+            None => return false,
+        };
+        if self.disk_mtime == Some(mtime) {
+            return false;
+        }
+        self.disk_mtime = Some(mtime);
+        true
+    }
+
     /// Set the document's latest saved revision to the given one.
     pub fn set_last_saved_revision(&mut self, rev: usize) {
         log::debug!(
@@ -1069,6 +1325,92 @@ pub fn diff_handle(&self) -> Option<&DiffHandle> {
         self.diff_handle.as_ref()
     }
 
+    /// Name of the branch (or abbreviated commit) checked out in the repository of this document.
+    pub fn version_control_head(&self) -> Option<&str> {
+        self.version_control_head.as_deref()
+    }
+
+    pub fn set_version_control_head(&mut self, head: Option<String>) {
+        self.version_control_head = head;
+    }
+
+    /// Result of the last code action request, see [`CodeActionHint`].
+    pub fn code_action_hint(&self) -> Option<CodeActionHint> {
+        self.code_action_hint
+    }
+
+    pub fn set_code_action_hint(&mut self, hint: Option<CodeActionHint>) {
+        self.code_action_hint = hint;
+    }
+
+    /// Whether inlay hints should be displayed for this document. The language
+    /// configuration may override the editor's `lsp.display-inlay-hints`.
+    pub fn display_inlay_hints(&self, config: &crate::editor::LspConfig) -> bool {
+        self.language_config()
+            .and_then(|config| config.inlay_hints)
+            .unwrap_or(config.display_inlay_hints)
+    }
+
+    pub fn inlay_hints(&self) -> &[InlayHint] {
+        &self.inlay_hints
+    }
+
+    /// The inlay hints in front of chars of `line`, including its line ending.
+    pub fn inlay_hints_on_line(&self, line: usize) -> &[InlayHint] {
+        let start = self.text.line_to_char(line);
+        // hints at the very end of the document belong to the last line
+        let end = if line + 1 < self.text.len_lines() {
+            self.text.line_to_char(line + 1)
+        } else {
+            self.text.len_chars() + 1
+        };
+        let from = self
+            .inlay_hints
+            .partition_point(|hint| hint.char_idx < start);
+        let to = self.inlay_hints.partition_point(|hint| hint.char_idx < end);
+        &self.inlay_hints[from..to]
+    }
+
+    /// The document version the inlay hints were last requested at.
+    pub fn inlay_hints_version(&self) -> Option<i32> {
+        self.inlay_hints_version
+    }
+
+    pub fn set_inlay_hints(&mut self, version: Option<i32>, mut hints: Vec<InlayHint>) {
+        hints.sort_by_key(|hint| hint.char_idx);
+        self.inlay_hints = hints;
+        self.inlay_hints_version = version;
+    }
+
+    pub fn symbols(&self) -> &[DocumentSymbol] {
+        &self.symbols
+    }
+
+    /// Conflicts left by a merge, sorted by position.
+    pub fn conflicts(&self) -> &[Conflict] {
+        self.conflicts
+            .get_or_init(|| conflict::find_conflicts(self.text.slice(..)))
+    }
+
+    /// The symbols enclosing `pos`, from the outermost to the innermost one.
+    pub fn symbols_at(&self, pos: usize) -> impl Iterator<Item = &DocumentSymbol> {
+        self.symbols
+            .iter()
+            .filter(move |symbol| symbol.start <= pos && pos < symbol.end)
+    }
+
+    /// The document version the symbols were last requested at.
+    pub fn symbols_version(&self) -> Option<i32> {
+        self.symbols_version
+    }
+
+    pub fn set_symbols(&mut self, version: Option<i32>, mut symbols: Vec<DocumentSymbol>) {
+        // outer symbols start before (or together with) the symbols nested in them
+        symbols.sort_by_key(|symbol| (symbol.start, std::cmp::Reverse(symbol.end)));
+        self.symbols = symbols;
+        self.symbols_version = version;
+    }
+
     /// Intialize/updates the differ for this document with a new base.
     pub fn set_diff_base(&mut self, diff_base: Vec<u8>, redraw_handle: RedrawHandle) {
         if let Ok((diff_base, _)) = from_reader(&mut diff_base.as_slice(), Some(self.encoding)) {
@@ -1241,6 +1583,21 @@ fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
 mod test {
     use super::*;
 
+    #[tokio::test]
+    async fn own_save_not_changed_on_disk() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("file.txt");
+        std::fs::write(&path, "old").unwrap();
+        let mut doc = Document::from(Rope::from("new"), None);
+        doc.set_path(Some(&path)).unwrap();
+        assert!(doc.check_changed_on_disk());
+
+        let event = doc.save::<PathBuf>(None, false).unwrap().await.unwrap();
+        doc.set_disk_mtime(event.mtime);
+        assert!(!doc.check_changed_on_disk());
+        assert_eq!(std::fs::read_to_string(&path).unwrap(), "new");
+    }
+
     #[test]
     fn changeset_to_changes_ignore_line_endings() {
         use helix_lsp::{lsp, Client, OffsetEncoding};
@@ -1459,4 +1816,17 @@ fn $name() {
     decode!(jis0212_decode, "jis0212", "EUC-JP");
     decode!(shift_jis_decode, "shift_jis");
     encode!(shift_jis_encode, "shift_jis");
+
+    #[test]
+    fn test_utf16_round_trip() {
+        let text = Rope::from("hello\nwörld €\n");
+        for encoding in [encoding::UTF_16LE, encoding::UTF_16BE] {
+            let mut buf: Vec<u8> = Vec::new();
+            helix_lsp::block_on(to_writer(&mut buf, encoding, &text)).unwrap();
+            // the encoding is detected from the byte order mark
+            let (decoded, detected) = from_reader(&mut buf.as_slice(), None).unwrap();
+            assert_eq!(detected, encoding);
+            assert_eq!(decoded, text);
+        }
+    }
 }
@@ -1,23 +1,30 @@
 use crate::{
-    align_view,
+    align_view, apply_transaction,
     clipboard::{get_clipboard_provider, ClipboardProvider},
     document::{DocumentSavedEventFuture, DocumentSavedEventResult, Mode},
+    editorconfig::EditorConfig,
     graphics::{CursorKind, Rect},
+    hooks::{Hook, HookEvent},
+    image::ImageProtocol,
     info::Info,
-    input::KeyEvent,
+    input::{KeyEvent, MouseEvent},
+    recent::{self, Recent},
+    swap::Swaps,
     theme::{self, Theme},
     tree::{self, Tree},
     Align, Document, DocumentId, View, ViewId,
 };
-use helix_vcs::DiffProviderRegistry;
+use helix_vcs::{DiffProviderRegistry, Status, StatusEvent, StatusHandle};
 
-use futures_util::stream::select_all::SelectAll;
+use futures_util::future::BoxFuture;
+use futures_util::stream::{select_all::SelectAll, FuturesUnordered};
 use futures_util::{future, StreamExt};
 use helix_lsp::Call;
 use tokio_stream::wrappers::UnboundedReceiverStream;
 
 use std::{
     borrow::Cow,
+    cmp::Ordering,
     collections::{BTreeMap, HashMap},
     io::stdin,
     num::NonZeroUsize,
@@ -31,18 +38,19 @@
         mpsc::{unbounded_channel, UnboundedReceiver, UnboundedSender},
         Notify, RwLock,
     },
-    time::{sleep, Duration, Instant, Sleep},
+    time::{sleep, sleep_until, Duration, Instant, Sleep},
 };
 
-use anyhow::{anyhow, bail, Error};
+use anyhow::{anyhow, bail, Context, Error};
 
 pub use helix_core::diagnostic::Severity;
 pub use helix_core::register::Registers;
 use helix_core::Position;
 use helix_core::{
     auto_pairs::AutoPairs,
+    encoding,
     syntax::{self, AutoPairConfig},
-    Change,
+    Change, Rope, Selection, Transaction,
 };
 use helix_dap as dap;
 use helix_lsp::lsp;
@@ -98,6 +106,9 @@ pub struct FilePickerConfig {
     /// WalkBuilder options
     /// Maximum Depth to recurse directories in file picker and global search. Defaults to `None`.
     pub max_depth: Option<usize>,
+    /// List the files opened the most and the most lately in the working directory first in
+    /// the file picker, until a query is typed. Defaults to true.
+    pub frecency: bool,
 }
 
 impl Default for FilePickerConfig {
@@ -111,6 +122,7 @@ fn default() -> Self {
             git_global: true,
             git_exclude: true,
             max_depth: None,
+            frecency: true,
         }
     }
 }
@@ -129,6 +141,131 @@ pub enum ExplorerPosition {
     Overlay,
 }
 
+/// How names are compared to order them, in the explorer and in path completion.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum SortComparator {
+    /// By their characters, the uppercase ones before the lowercase ones.
+    Lexical,
+    /// By their characters, ignoring their case.
+    CaseInsensitive,
+    /// Ignoring their case, with the numbers in them compared by value, `file2` coming
+    /// before `file10`.
+    Natural,
+}
+
+impl Default for SortComparator {
+    fn default() -> Self {
+        Self::Lexical
+    }
+}
+
+impl SortComparator {
+    /// Compares the names `a` and `b`. The ones only differing in what is ignored are
+    /// ordered lexically.
+    pub fn compare(self, a: &str, b: &str) -> Ordering {
+        let ordering = match self {
+            Self::Lexical => Ordering::Equal,
+            Self::CaseInsensitive => compare_case_insensitive(a, b),
+            Self::Natural => compare_natural(a, b),
+        };
+        ordering.then_with(|| a.cmp(b))
+    }
+
+    /// Compares `a` and `b` component by component, a path coming before the paths under it.
+    pub fn compare_paths(self, a: &Path, b: &Path) -> Ordering {
+        let mut b_components = b.components();
+        for a_component in a.components() {
+            let b_component = match b_components.next() {
+                Some(component) => component,
+                None => return Ordering::Greater,
+            };
+            let a_name = a_component.as_os_str().to_string_lossy();
+            let b_name = b_component.as_os_str().to_string_lossy();
+            let ordering = self.compare(&a_name, &b_name);
+            if ordering != Ordering::Equal {
+                return ordering;
+            }
+        }
+        match b_components.next() {
+            Some(_) => Ordering::Less,
+            None => Ordering::Equal,
+        }
+    }
+}
+
+fn compare_case_insensitive(a: &str, b: &str) -> Ordering {
+    let a = a.chars().flat_map(char::to_lowercase);
+    a.cmp(b.chars().flat_map(char::to_lowercase))
+}
+
+fn compare_natural(mut a: &str, mut b: &str) -> Ordering {
+    while !a.is_empty() || !b.is_empty() {
+        let (a_chunk, a_rest) = split_chunk(a);
+        let (b_chunk, b_rest) = split_chunk(b);
+        let is_number = |chunk: &str| chunk.starts_with(|c: char| c.is_ascii_digit());
+        let ordering = if is_number(a_chunk) && is_number(b_chunk) {
+            // by value, the longer of two numbers without leading zeros being the larger
+            let a_number = a_chunk.trim_start_matches('0');
+            let b_number = b_chunk.trim_start_matches('0');
+            (a_number.len(), a_number).cmp(&(b_number.len(), b_number))
+        } else {
+            compare_case_insensitive(a_chunk, b_chunk)
+        };
+        if ordering != Ordering::Equal {
+            return ordering;
+        }
+        a = a_rest;
+        b = b_rest;
+    }
+    Ordering::Equal
+}
+
+/// Splits the leading digits of `s` from the rest, or the characters up to the first digit.
+fn split_chunk(s: &str) -> (&str, &str) {
+    let digits = s.starts_with(|c: char| c.is_ascii_digit());
+    let end = s.find(|c: char| c.is_ascii_digit() != digits);
+    s.split_at(end.unwrap_or(s.len()))
+}
+
+/// The order of the entries of each directory in the explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExplorerSort {
+    /// The directories first, then the files, each by name.
+    DirsFirst,
+    /// By name, the directories among the files.
+    Name,
+    /// The most recently modified first, the directories among the files.
+    Modified,
+}
+
+impl Default for ExplorerSort {
+    fn default() -> Self {
+        Self::DirsFirst
+    }
+}
+
+/// How a file is opened from the explorer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum ExplorerOpen {
+    /// In a buffer.
+    Edit,
+    /// Only in the preview of the explorer, or a popup for images.
+    PreviewOnly,
+    /// With the default application of the system.
+    External,
+    /// As hexadecimal bytes in a read-only buffer.
+    Hexdump,
+}
+
+impl Default for ExplorerOpen {
+    fn default() -> Self {
+        Self::Edit
+    }
+}
+
 #[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
 #[serde(rename_all = "kebab-case", default, deny_unknown_fields)]
 pub struct ExplorerConfig {
@@ -136,6 +273,64 @@ pub struct ExplorerConfig {
     pub position: ExplorerPosition,
     /// explorer column width
     pub column_width: usize,
+    /// Whether to hide the files ignored by the `.gitignore`, `.ignore` and `.fdignore`
+    /// files of their directory and its parents, and by the `ignore-files`. Defaults to
+    /// false.
+    pub git_ignore: bool,
+    /// Names of more ignore files read in each directory with `git-ignore`, like
+    /// `.rgignore`, the later ones taking precedence. Defaults to none.
+    pub ignore_files: Vec<String>,
+    /// Whether to list the files and directories whose name starts with a dot. Defaults
+    /// to true.
+    pub show_hidden: bool,
+    /// Whether to select the file of the focused buffer as it changes, or the one created
+    /// by saving a buffer, expanding the directories holding it. Defaults to false.
+    pub follow: bool,
+    /// Show file type icons next to the names. Requires a Nerd Font. Defaults to false.
+    pub icons: bool,
+    /// Show the subject and age of the last commit changing each entry after its name.
+    /// Defaults to false.
+    pub last_commit: bool,
+    /// Whether to tell the selected item and its state in the statusline, put the cursor on
+    /// its row and write out what the colors of the rows tell, for the screen readers.
+    /// Defaults to false.
+    pub screen_reader: bool,
+    /// Whether to write `*` after the executables, `=` after the sockets and `|` after the
+    /// FIFOs, like `ls -F` does. Defaults to false.
+    pub classify: bool,
+    /// The names of the files and directories marking the root of a project, looked for in
+    /// this order. Defaults to `Cargo.toml`, `package.json`, `pyproject.toml` and `.git`.
+    pub manifests: Vec<String>,
+    /// Number of files listed in the Recent section at the top, the files of the working
+    /// directory opened the most and the most lately with `file-picker.frecency`. `0`
+    /// hides the section. Defaults to 5.
+    pub recent: usize,
+    /// Whether to color the indent guides of each depth of the tree with the next of the
+    /// `ui.explorer.guides.1`, `ui.explorer.guides.2`... scopes of the theme. Defaults to
+    /// false.
+    pub rainbow_guides: bool,
+    /// Defaults to `dirs-first`.
+    pub sort: ExplorerSort,
+    /// The directory shown when the explorer is opened instead of the working directory,
+    /// relative to the working directory, or to the workspace in its config. Defaults to
+    /// none.
+    pub root: Option<PathBuf>,
+    /// Globs of the files and directories to hide, written like the lines of a
+    /// `.gitignore` file in the working directory. Defaults to none.
+    pub ignore: Vec<String>,
+    /// Named lists of globs, like `ignore` ones, cycled through in the explorer. The files
+    /// matching none of the globs of the active one are hidden. Defaults to none.
+    pub filter_presets: BTreeMap<String, Vec<String>>,
+    /// Shell commands run on the selected item by key from the actions menu, with
+    /// `%path`, `%dir` and `%name` replaced by its path, directory and name. Defaults
+    /// to none.
+    pub actions: BTreeMap<String, String>,
+    /// The directory of the templates new files start from, named like the file or like
+    /// its extension, relative to the config directory. Defaults to none.
+    pub templates: Option<PathBuf>,
+    /// How the files are opened from the explorer by their extension, in lowercase.
+    /// Defaults to none, editing them all.
+    pub open: BTreeMap<String, ExplorerOpen>,
 }
 
 impl ExplorerConfig {
@@ -166,6 +361,15 @@ pub fn is_tree(&self) -> bool {
             ExplorerStyle::Tree => true,
         }
     }
+
+    /// How the file at `path` is opened, by its extension.
+    pub fn open_with(&self, path: &Path) -> ExplorerOpen {
+        let ext = path
+            .extension()
+            .map(|ext| ext.to_string_lossy().to_lowercase());
+        ext.and_then(|ext| self.open.get(&ext).copied())
+            .unwrap_or_default()
+    }
 }
 
 impl Default for ExplorerConfig {
@@ -174,6 +378,26 @@ fn default() -> Self {
             style: ExplorerStyle::Tree,
             position: ExplorerPosition::Embed,
             column_width: 30,
+            git_ignore: false,
+            ignore_files: Vec::new(),
+            show_hidden: true,
+            follow: false,
+            icons: false,
+            last_commit: false,
+            rainbow_guides: false,
+            classify: false,
+            screen_reader: false,
+            manifests: ["Cargo.toml", "package.json", "pyproject.toml", ".git"]
+                .map(String::from)
+                .to_vec(),
+            recent: 5,
+            sort: ExplorerSort::DirsFirst,
+            root: None,
+            ignore: Vec::new(),
+            filter_presets: BTreeMap::new(),
+            actions: BTreeMap::new(),
+            templates: None,
+            open: BTreeMap::new(),
         }
     }
 }
@@ -184,10 +408,18 @@ impl Eq for ExplorerConfig  {}
 pub struct Config {
     /// Padding to keep between the edge of the screen and the cursor when scrolling. Defaults to 5.
     pub scrolloff: usize,
+    /// Padding to keep between the left and right edges of the view and the cursor when
+    /// scrolling horizontally. Defaults to 5.
+    pub horizontal_scrolloff: usize,
+    /// Minimum number of columns to scroll horizontally at once, larger values scroll
+    /// long lines less often. Defaults to 1.
+    pub horizontal_scroll_chunk: usize,
     /// Number of lines to scroll at once. Defaults to 3
     pub scroll_lines: isize,
-    /// Mouse support. Defaults to true.
-    pub mouse: bool,
+    /// Mouse support of each part of the editor. `mouse = false` turns it off everywhere,
+    /// like setting all of them to false.
+    #[serde(deserialize_with = "deserialize_mouse")]
+    pub mouse: Mouse,
     /// Shell to use for shell commands. Defaults to ["cmd", "/C"] on Windows and ["sh", "-c"] otherwise.
     pub shell: Vec<String>,
     /// Line number mode.
@@ -198,6 +430,9 @@ pub struct Config {
     pub cursorcolumn: bool,
     /// Gutters. Default ["diagnostics", "line-numbers"]
     pub gutters: Vec<GutterType>,
+    /// Gutters of the views that aren't focused, like `gutters`. Defaults to `None`, showing
+    /// `gutters` in all the views.
+    pub unfocused_gutters: Option<Vec<GutterType>>,
     /// Middle click paste support. Defaults to true.
     pub middle_click_paste: bool,
     /// Automatic insertion of pairs to parentheses, brackets,
@@ -208,8 +443,13 @@ pub struct Config {
     pub auto_completion: bool,
     /// Automatic formatting on save. Defaults to true.
     pub auto_format: bool,
-    /// Automatic save on focus lost. Defaults to false.
-    pub auto_save: bool,
+    /// When to save modified buffers automatically. `auto-save = true` saves them on focus
+    /// lost, like `auto-save.focus-lost = true`.
+    #[serde(deserialize_with = "deserialize_auto_save")]
+    pub auto_save: AutoSave,
+    /// Save the session of the working directory when quitting and restore it when
+    /// starting without files. Defaults to false.
+    pub auto_session: bool,
     /// Time in milliseconds since last keypress before idle timers trigger.
     /// Used for autocompletion, set to 0 for instant. Defaults to 400ms.
     #[serde(
@@ -238,12 +478,58 @@ pub struct Config {
     pub whitespace: WhitespaceConfig,
     /// Persistently display open buffers along the top
     pub bufferline: BufferLine,
+    /// Show file type icons in the bufferline, the file and buffer pickers and the completions
+    /// of paths. Requires a Nerd Font. Defaults to false.
+    pub icons: bool,
     /// Vertical indent width guides.
     pub indent_guides: IndentGuidesConfig,
+    /// Soft wrapping of lines wider than the view.
+    pub soft_wrap: SoftWrap,
+    /// Symbols used to draw breakpoints in the diagnostics gutter.
+    pub breakpoint_symbols: BreakpointSymbols,
+    /// Compressed overview of the document along the right edge of each view.
+    pub minimap: Minimap,
+    /// Scrollbar along the right edge of each view.
+    pub scrollbar: Scrollbar,
+    /// The dashboard shown when helix is started without files.
+    pub dashboard: Dashboard,
+    /// Checking the files of the open buffers for changes made outside of helix.
+    pub file_watch: FileWatch,
+    /// Snapshots of the unsaved changes of the buffers, to recover them after a crash.
+    pub swap_files: SwapFiles,
+    /// Popups describing the text the mouse pointer rests over.
+    pub mouse_hover: MouseHover,
+    /// Enclosing scopes pinned to the top of each view.
+    pub sticky_context: StickyContext,
     /// Whether to color modes with different colors. Defaults to `false`.
     pub color_modes: bool,
+    /// Render a swatch after lines containing hex or `rgb()` color literals. Defaults to `false`.
+    pub color_swatches: bool,
+    /// Apply the indentation, final newline, charset and maximum line length of the
+    /// `.editorconfig` files of opened files. Defaults to `true`.
+    pub editorconfig: bool,
+    /// Listen on a local socket for the commands of `hx --remote`. Defaults to `false`.
+    pub remote_control: bool,
+    /// Size in bytes from which files are opened in large-file mode: read in the
+    /// background, without syntax highlighting, language server or diff. 0 disables it.
+    /// Defaults to 50MiB.
+    pub large_file_size: u64,
+    /// Bar above every view showing the path to the symbol under the cursor.
+    pub breadcrumbs: Breadcrumbs,
+    /// Animation of large jumps of the viewport.
+    pub smooth_scroll: SmoothScroll,
+    /// Diagnostic messages shown after the end of their line.
+    pub end_of_line_diagnostics: EndOfLineDiagnostics,
+    /// Layout of the distraction free mode toggled with `toggle_zen_mode`.
+    pub zen_mode: ZenMode,
+    /// How names are compared to order them in the explorer and in path completion.
+    /// Defaults to `lexical`.
+    pub sort_comparator: SortComparator,
     /// explore config
     pub explorer: ExplorerConfig,
+    /// Shell commands or commands run on the events of the files, the explorer and the
+    /// buffers. Defaults to none.
+    pub hooks: Vec<Hook>,
 }
 
 #[derive(Debug, Default, Clone, PartialEq, Eq, Serialize, Deserialize)]
@@ -308,6 +594,11 @@ pub struct LspConfig {
     pub auto_signature_help: bool,
     /// Display docs under signature help popup
     pub display_signature_help_docs: bool,
+    /// Display LSP inlay hints (type and parameter hints) inline. Defaults to false.
+    pub display_inlay_hints: bool,
+    /// Review the edits of renames and code actions changing several files before they
+    /// are applied. Defaults to true.
+    pub preview_workspace_edits: bool,
 }
 
 impl Default for LspConfig {
@@ -316,6 +607,8 @@ fn default() -> Self {
             display_messages: false,
             auto_signature_help: true,
             display_signature_help_docs: true,
+            display_inlay_hints: false,
+            preview_workspace_edits: true,
         }
     }
 }
@@ -421,6 +714,15 @@ pub enum StatusLineElement {
 
     /// A single space
     Spacer,
+
+    /// The current branch (or commit) of the file's repository
+    GitBranch,
+
+    /// The branch, ahead/behind counts and uncommitted changes of the working directory's repository
+    RepoState,
+
+    /// Shown while the file explorer is open, highlighted when it is focused
+    ExplorerIndicator,
 }
 
 // Cursor shape is read and used on every rendered frame and so needs
@@ -529,6 +831,8 @@ pub enum GutterType {
     Spacer,
     /// Highlight local changes
     Diff,
+    /// Show a marker on the cursor line if code actions are available
+    CodeActions,
 }
 
 impl std::str::FromStr for GutterType {
@@ -540,7 +844,10 @@ fn from_str(s: &str) -> Result<Self, Self::Err> {
             "spacer" => Ok(Self::Spacer),
             "line-numbers" => Ok(Self::LineNumbers),
             "diff" => Ok(Self::Diff),
-            _ => anyhow::bail!("Gutter type can only be `diagnostics` or `line-numbers`."),
+            "code-actions" => Ok(Self::CodeActions),
+            _ => anyhow::bail!(
+                "Gutter type can only be `diagnostics`, `spacer`, `line-numbers`, `diff` or `code-actions`."
+            ),
         }
     }
 }
@@ -578,8 +885,10 @@ pub enum WhitespaceRender {
 #[serde(rename_all = "kebab-case")]
 pub enum WhitespaceRenderValue {
     None,
-    // TODO
-    // Selection,
+    /// Only render whitespace inside of selections.
+    Selection,
+    /// Only render whitespace at the end of a line.
+    Trailing,
     All,
 }
 
@@ -609,11 +918,17 @@ pub fn tab(&self) -> WhitespaceRenderValue {
         }
     }
     pub fn newline(&self) -> WhitespaceRenderValue {
-        match *self {
+        let val = match *self {
             Self::Basic(val) => val,
             Self::Specific {
                 default, newline, ..
             } => newline.or(default).unwrap_or(WhitespaceRenderValue::None),
+        };
+        // line endings are never trailing whitespace themselves
+        if val == WhitespaceRenderValue::Trailing {
+            WhitespaceRenderValue::None
+        } else {
+            val
         }
     }
 }
@@ -658,12 +973,411 @@ fn default() -> Self {
     }
 }
 
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Minimap {
+    /// Whether to show the minimap. Defaults to false.
+    pub enable: bool,
+    /// Width of the minimap in cells. Defaults to 10.
+    pub width: u16,
+}
+
+impl Default for Minimap {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            width: 10,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Scrollbar {
+    /// Whether to show the scrollbar. Defaults to false.
+    pub enable: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Dashboard {
+    /// Whether to show the dashboard instead of an empty scratch buffer. Defaults to false.
+    pub enable: bool,
+    /// Number of recent workspaces and of recent files listed. Defaults to 5.
+    pub recent: usize,
+    /// Entries running a command, listed after opening the explorer.
+    pub shortcuts: Vec<DashboardShortcut>,
+}
+
+impl Default for Dashboard {
+    fn default() -> Self {
+        let shortcut = |key, label: &str, command: &str| DashboardShortcut {
+            key,
+            label: label.to_string(),
+            command: command.to_string(),
+        };
+        Self {
+            enable: false,
+            recent: 5,
+            shortcuts: vec![
+                shortcut('f', "Find file", "file_picker"),
+                shortcut('n', "New file", ":new"),
+                shortcut('s', "Restore session", ":session-load"),
+                shortcut('c', "Open config", ":config-open"),
+                shortcut('q', "Quit", ":quit"),
+            ],
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct FileWatch {
+    /// Whether to check the files for changes. Defaults to true.
+    pub enable: bool,
+    /// Time in milliseconds between two checks. Defaults to 1000ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub interval: Duration,
+    /// Whether to reload buffers without unsaved changes instead of asking. Defaults to true.
+    pub auto_reload: bool,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SwapFiles {
+    /// Whether to write snapshots of the buffers with unsaved changes. Defaults to true.
+    pub enable: bool,
+    /// Time in milliseconds between two snapshots of a changing buffer. Defaults to 4000ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub interval: Duration,
+}
+
+impl Default for SwapFiles {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            interval: Duration::from_millis(4000),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Mouse {
+    /// Clicks, scrolling and hovering in the views: their text, gutters, scrollbars and
+    /// statuslines. Defaults to true.
+    pub text: bool,
+    /// Clicks and scrolling on the rows of the explorer. Defaults to true.
+    pub explorer: bool,
+    /// Clicks on the bufferline and the tabline. Defaults to true.
+    pub bufferline: bool,
+    /// Pasting into the prompts with the middle button. Defaults to true.
+    pub prompt: bool,
+}
+
+impl Default for Mouse {
+    fn default() -> Self {
+        Self {
+            text: true,
+            explorer: true,
+            bufferline: true,
+            prompt: true,
+        }
+    }
+}
+
+impl Mouse {
+    /// Whether the mouse is captured from the terminal, which is needed by any of the parts.
+    pub fn is_captured(&self) -> bool {
+        self.text || self.explorer || self.bufferline || self.prompt
+    }
+}
+
+fn deserialize_mouse<'de, D>(deserializer: D) -> Result<Mouse, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum MouseToml {
+        Enable(bool),
+        Mouse(Mouse),
+    }
+
+    Ok(match MouseToml::deserialize(deserializer)? {
+        MouseToml::Enable(enable) => Mouse {
+            text: enable,
+            explorer: enable,
+            bufferline: enable,
+            prompt: enable,
+        },
+        MouseToml::Mouse(mouse) => mouse,
+    })
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct MouseHover {
+    /// Whether to show the diagnostics under the resting mouse pointer, and the language
+    /// server's hover information while holding ctrl. Defaults to true.
+    pub enable: bool,
+    /// Time in milliseconds the pointer has to rest before the popup shows. Defaults to
+    /// 500ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub delay: Duration,
+}
+
+impl Default for MouseHover {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            delay: Duration::from_millis(500),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct AutoSave {
+    /// Save when the terminal loses focus. Defaults to false.
+    pub focus_lost: bool,
+    /// Save the buffer left when switching to another one. Defaults to false.
+    pub buffer_switch: bool,
+    /// Save after `delay` without a keypress. Defaults to false.
+    pub after_delay: bool,
+    /// Time in milliseconds without a keypress before saving. Defaults to 3000ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub delay: Duration,
+}
+
+impl Default for AutoSave {
+    fn default() -> Self {
+        Self {
+            focus_lost: false,
+            buffer_switch: false,
+            after_delay: false,
+            delay: Duration::from_millis(3000),
+        }
+    }
+}
+
+fn deserialize_auto_save<'de, D>(deserializer: D) -> Result<AutoSave, D::Error>
+where
+    D: serde::Deserializer<'de>,
+{
+    #[derive(Deserialize)]
+    #[serde(untagged)]
+    enum AutoSaveToml {
+        FocusLost(bool),
+        AutoSave(AutoSave),
+    }
+
+    Ok(match AutoSaveToml::deserialize(deserializer)? {
+        AutoSaveToml::FocusLost(focus_lost) => AutoSave {
+            focus_lost,
+            ..AutoSave::default()
+        },
+        AutoSaveToml::AutoSave(auto_save) => auto_save,
+    })
+}
+
+impl Default for FileWatch {
+    fn default() -> Self {
+        Self {
+            enable: true,
+            interval: Duration::from_millis(1000),
+            auto_reload: true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct DashboardShortcut {
+    pub key: char,
+    pub label: String,
+    /// A command name, or a typable command with its arguments prefixed with `:`.
+    pub command: String,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct StickyContext {
+    /// Whether to pin the first lines of the enclosing functions and classes to the top
+    /// of the view when they are scrolled out of view. Defaults to false.
+    pub enable: bool,
+    /// Maximum number of enclosing scopes shown at once. Defaults to 3.
+    pub max_depth: usize,
+}
+
+impl Default for StickyContext {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            max_depth: 3,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Breadcrumbs {
+    /// Whether to show the breadcrumbs bar. Defaults to false.
+    pub enable: bool,
+    /// Text drawn between the segments of the bar. Defaults to ` ▸ `.
+    pub separator: String,
+}
+
+impl Default for Breadcrumbs {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            separator: String::from(" ▸ "),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct EndOfLineDiagnostics {
+    /// Whether to show the first diagnostic of each line after its end. Defaults to false.
+    pub enable: bool,
+    /// Diagnostics less severe than this are not shown. Defaults to `Hint`.
+    pub min_severity: Severity,
+}
+
+impl Default for EndOfLineDiagnostics {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            min_severity: Severity::Hint,
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SmoothScroll {
+    /// Whether to animate jumps of the viewport instead of redrawing it at the
+    /// new position at once. Defaults to false.
+    pub enable: bool,
+    /// Time in milliseconds an animation takes. Defaults to 150ms.
+    #[serde(
+        serialize_with = "serialize_duration_millis",
+        deserialize_with = "deserialize_duration_millis"
+    )]
+    pub duration: Duration,
+}
+
+impl SmoothScroll {
+    /// The duration of animations, `None` if smooth scrolling is disabled.
+    pub fn animation_duration(&self) -> Option<Duration> {
+        self.enable.then(|| self.duration)
+    }
+}
+
+impl Default for SmoothScroll {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            duration: Duration::from_millis(150),
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct ZenMode {
+    /// Width of the text column centered in every view. Defaults to 100.
+    pub width: u16,
+}
+
+impl Default for ZenMode {
+    fn default() -> Self {
+        Self { width: 100 }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct SoftWrap {
+    /// Whether to soft wrap lines wider than the view. Defaults to false.
+    pub enable: bool,
+    /// Text drawn at the start of every continuation row. Defaults to `↪ `.
+    pub wrap_indicator: String,
+    /// Text drawn in the line number gutter of every continuation row, aligned like the
+    /// numbers. Defaults to none, leaving the gutter blank.
+    pub continuation_marker: String,
+}
+
+impl Default for SoftWrap {
+    fn default() -> Self {
+        Self {
+            enable: false,
+            wrap_indicator: "↪ ".to_string(),
+            continuation_marker: String::new(),
+        }
+    }
+}
+
+impl SoftWrap {
+    /// Returns the width of the wrap indicator if `enable` is set, which views use
+    /// instead of `self.enable` when they override it.
+    pub fn indicator_width(&self, enable: bool) -> Option<usize> {
+        use helix_core::unicode::width::UnicodeWidthStr;
+
+        if enable {
+            Some(self.wrap_indicator.width())
+        } else {
+            None
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case", deny_unknown_fields)]
+pub struct BreakpointSymbols {
+    /// Breakpoint without a condition or log message. Defaults to `▲`.
+    pub plain: String,
+    /// Breakpoint with a condition. Defaults to `▲`.
+    pub conditional: String,
+    /// Breakpoint with a log message. Defaults to `▲`.
+    pub log_point: String,
+    /// Breakpoint the debugger hasn't verified yet. Defaults to `⊚`.
+    pub unverified: String,
+}
+
+impl Default for BreakpointSymbols {
+    fn default() -> Self {
+        Self {
+            plain: "▲".to_string(),
+            conditional: "▲".to_string(),
+            log_point: "▲".to_string(),
+            unverified: "⊚".to_string(),
+        }
+    }
+}
+
 impl Default for Config {
     fn default() -> Self {
         Self {
             scrolloff: 5,
+            horizontal_scrolloff: 5,
+            horizontal_scroll_chunk: 1,
             scroll_lines: 3,
-            mouse: true,
+            mouse: Mouse::default(),
             shell: if cfg!(windows) {
                 vec!["cmd".to_owned(), "/C".to_owned()]
             } else {
@@ -679,11 +1393,13 @@ fn default() -> Self {
                 GutterType::Spacer,
                 GutterType::Diff,
             ],
+            unfocused_gutters: None,
             middle_click_paste: true,
             auto_pairs: AutoPairConfig::default(),
             auto_completion: true,
             auto_format: true,
-            auto_save: false,
+            auto_save: AutoSave::default(),
+            auto_session: false,
             idle_timeout: Duration::from_millis(400),
             completion_trigger_len: 2,
             auto_info: true,
@@ -697,9 +1413,29 @@ fn default() -> Self {
             rulers: Vec::new(),
             whitespace: WhitespaceConfig::default(),
             bufferline: BufferLine::default(),
+            icons: false,
             indent_guides: IndentGuidesConfig::default(),
+            soft_wrap: SoftWrap::default(),
+            breakpoint_symbols: BreakpointSymbols::default(),
+            minimap: Minimap::default(),
+            scrollbar: Scrollbar::default(),
+            dashboard: Dashboard::default(),
+            file_watch: FileWatch::default(),
+            swap_files: SwapFiles::default(),
+            mouse_hover: MouseHover::default(),
+            sticky_context: StickyContext::default(),
             color_modes: false,
+            color_swatches: false,
+            editorconfig: true,
+            remote_control: false,
+            large_file_size: 50 * 1024 * 1024,
+            breadcrumbs: Breadcrumbs::default(),
+            smooth_scroll: SmoothScroll::default(),
+            end_of_line_diagnostics: EndOfLineDiagnostics::default(),
+            zen_mode: ZenMode::default(),
+            sort_comparator: SortComparator::default(),
             explorer: ExplorerConfig::default(),
+            hooks: Vec::new(),
         }
     }
 }
@@ -767,6 +1503,8 @@ pub struct Editor {
     pub breakpoints: HashMap<PathBuf, Vec<Breakpoint>>,
 
     pub clipboard_provider: Box<dyn ClipboardProvider>,
+    /// The protocol to draw images in the terminal, if any is supported.
+    pub image_protocol: Option<ImageProtocol>,
 
     pub syn_loader: Arc<syntax::Loader>,
     pub theme_loader: Arc<theme::Loader>,
@@ -776,6 +1514,9 @@ pub struct Editor {
     /// The currently applied editor theme. While previewing a theme, the previewed theme
     /// is set here.
     pub theme: Theme,
+    /// How many times `theme` was set, for the components caching its styles to notice
+    /// they are out of date.
+    pub theme_changes: usize,
     pub last_line_number: Option<usize>,
     pub status_msg: Option<(Cow<'static, str>, Severity)>,
     pub autoinfo: Option<Info>,
@@ -784,22 +1525,62 @@ pub struct Editor {
     pub auto_pairs: Option<AutoPairs>,
 
     pub idle_timer: Pin<Box<Sleep>>,
+    /// Fires when the files of the buffers are checked for changes next.
+    file_watch_timer: Pin<Box<Sleep>>,
+    /// Fires when the modified buffers are saved automatically next.
+    auto_save_timer: Pin<Box<Sleep>>,
+    /// Fires when the snapshots of the modified buffers are written next.
+    swap_timer: Pin<Box<Sleep>>,
+    /// Fires once the mouse pointer rested long enough for the hover popup.
+    mouse_hover_timer: Pin<Box<Sleep>>,
+    /// The last movement of the mouse pointer, until the hover popup is due.
+    mouse_hover: Option<MouseEvent>,
     pub last_motion: Option<Motion>,
 
     pub last_completion: Option<CompleteAction>,
+    /// The files matched by the last workspace search, for the explorer to pin them.
+    pub search_results: Vec<PathBuf>,
+    /// The recent workspaces and files and the visits of the files of each workspace,
+    /// loaded and saved by the application.
+    pub recent: Recent,
+    /// Whether the explorer refuses to create and remove files and to run its actions
+    /// until it is unlocked, for the rest of the session.
+    pub explorer_locked: bool,
 
     pub exit_code: i32,
 
     pub config_events: (UnboundedSender<ConfigEvent>, UnboundedReceiver<ConfigEvent>),
+    /// The events for the hooks of the config, see [`Editor::emit`].
+    pub hook_events: (UnboundedSender<HookEvent>, UnboundedReceiver<HookEvent>),
     /// Allows asynchronous tasks to control the rendering
     /// The `Notify` allows asynchronous tasks to request the editor to perform a redraw
     /// The `RwLock` blocks the editor from performing the render until an exclusive lock can be aquired
     pub redraw_handle: RedrawHandle,
     pub needs_redraw: bool,
+    /// Whether the gutters, statusline and bufferline are hidden and the text is centered.
+    pub zen_mode: bool,
+    /// The repository of the working directory and its changed files, as last received
+    /// from `vcs_status`.
+    pub repo_status: Status,
+    /// The background task keeping the status of the repository, to be told about the
+    /// files the editor writes.
+    pub vcs_status: StatusHandle,
+    status_events: UnboundedReceiver<StatusEvent>,
+    /// How many times `repo_status` changed, for the views listing the state of the
+    /// repository to notice it may have changed.
+    pub repo_refreshes: usize,
+    /// The snapshots of the buffers with unsaved changes written so far.
+    pub swaps: Swaps,
+    /// The large files being read, see [`Document::open_large`].
+    loading_docs: FuturesUnordered<LoadingDocument>,
 }
 
 pub type RedrawHandle = (Arc<Notify>, Arc<RwLock<()>>);
 
+/// The text and the encoding read from the file of a large document.
+type LoadResult = Result<(Rope, &'static encoding::Encoding), Error>;
+type LoadingDocument = BoxFuture<'static, (DocumentId, LoadResult)>;
+
 #[derive(Debug)]
 pub enum EditorEvent {
     DocumentSaved(DocumentSavedEventResult),
@@ -807,6 +1588,18 @@ pub enum EditorEvent {
     LanguageServerMessage((usize, Call)),
     DebuggerEvent(dap::Payload),
     IdleTimer,
+    /// The files of the buffers are due to be checked for changes.
+    FileWatchTimer,
+    /// The delay without a keypress before saving the modified buffers has passed.
+    AutoSaveTimer,
+    /// The snapshots of the modified buffers are due to be written.
+    SwapTimer,
+    /// The text of a large file was read.
+    DocumentLoaded,
+    /// The mouse pointer rested after the given movement.
+    MouseHover(MouseEvent),
+    /// An event to run the matching hooks of the config for.
+    Hook(HookEvent),
 }
 
 #[derive(Debug, Clone)]
@@ -856,6 +1649,8 @@ pub fn new(
 
         // HAXX: offset the render area height by 1 to account for prompt/commandline
         area.height -= 1;
+        let diff_providers = DiffProviderRegistry::default();
+        let vcs_status = StatusHandle::spawn(conf.file_watch.interval, diff_providers.clone());
 
         Self {
             mode: Mode::Normal,
@@ -870,9 +1665,10 @@ pub fn new(
             macro_recording: None,
             macro_replaying: Vec::new(),
             theme: theme_loader.default(),
+            theme_changes: 0,
             language_servers: helix_lsp::Registry::new(),
             diagnostics: BTreeMap::new(),
-            diff_providers: DiffProviderRegistry::default(),
+            diff_providers,
             debugger: None,
             debugger_events: SelectAll::new(),
             breakpoints: HashMap::new(),
@@ -882,15 +1678,32 @@ pub fn new(
             last_line_number: None,
             registers: Registers::default(),
             clipboard_provider: get_clipboard_provider(),
+            image_protocol: ImageProtocol::detect(),
             status_msg: None,
             autoinfo: None,
+            zen_mode: false,
+            repo_status: Status::default(),
+            status_events: vcs_status.subscribe(),
+            vcs_status,
+            swaps: Swaps::default(),
+            loading_docs: FuturesUnordered::new(),
+            repo_refreshes: 0,
             idle_timer: Box::pin(sleep(conf.idle_timeout)),
+            file_watch_timer: Box::pin(sleep(conf.file_watch.interval)),
+            auto_save_timer: Box::pin(sleep_until(far_future())),
+            swap_timer: Box::pin(sleep(conf.swap_files.interval)),
+            mouse_hover_timer: Box::pin(sleep_until(far_future())),
+            mouse_hover: None,
             last_motion: None,
             last_completion: None,
+            search_results: Vec::new(),
+            recent: Recent::default(),
+            explorer_locked: false,
             config,
             auto_pairs,
             exit_code: 0,
             config_events: unbounded_channel(),
+            hook_events: unbounded_channel(),
             redraw_handle: Default::default(),
             needs_redraw: false,
         }
@@ -910,14 +1723,18 @@ pub fn config(&self) -> DynGuard<Config> {
     pub fn refresh_config(&mut self) {
         let config = self.config();
         self.auto_pairs = (&config.auto_pairs).into();
+        for doc in self.documents.values_mut() {
+            if !doc.display_inlay_hints(&config.lsp) {
+                doc.set_inlay_hints(None, Vec::new());
+            }
+        }
         self.reset_idle_timer();
+        self.reset_auto_save_timer();
+        self._refresh();
     }
 
     pub fn clear_idle_timer(&mut self) {
-        // equivalent to internal Instant::far_future() (30 years)
-        self.idle_timer
-            .as_mut()
-            .reset(Instant::now() + Duration::from_secs(86400 * 365 * 30));
+        self.idle_timer.as_mut().reset(far_future());
     }
 
     pub fn reset_idle_timer(&mut self) {
@@ -927,10 +1744,47 @@ pub fn reset_idle_timer(&mut self) {
             .reset(Instant::now() + config.idle_timeout);
     }
 
+    /// Writes snapshots of the buffers changed since the last ones, and removes the
+    /// snapshots of the buffers written or closed since, in the background.
+    pub fn update_swaps(&mut self) {
+        self.swaps.update(self.documents.values())
+    }
+
+    /// Restarts the delay before the modified buffers are saved, if they are saved after
+    /// a delay.
+    pub fn reset_auto_save_timer(&mut self) {
+        let config = self.config();
+        let deadline = if config.auto_save.after_delay {
+            Instant::now() + config.auto_save.delay
+        } else {
+            far_future()
+        };
+        self.auto_save_timer.as_mut().reset(deadline);
+    }
+
+    /// Restarts the delay before the hover popup for the pointer moved with `event`.
+    /// `None` cancels the popup, after any other event.
+    pub fn reset_mouse_hover(&mut self, event: Option<MouseEvent>) {
+        let delay = self.config().mouse_hover.delay;
+        self.mouse_hover = event.filter(|_| self.config().mouse_hover.enable);
+        let deadline = match self.mouse_hover {
+            Some(_) => Instant::now() + delay,
+            None => far_future(),
+        };
+        self.mouse_hover_timer.as_mut().reset(deadline);
+    }
+
     pub fn clear_status(&mut self) {
         self.status_msg = None;
     }
 
+    /// Sends an event to the application, which runs the hooks of the config matching it.
+    pub fn emit(&self, event: HookEvent) {
+        if !self.config().hooks.is_empty() {
+            let _ = self.hook_events.0.send(event);
+        }
+    }
+
     #[inline]
     pub fn set_status<T: Into<Cow<'static, str>>>(&mut self, status: T) {
         let status = status.into();
@@ -995,6 +1849,7 @@ fn set_theme_impl(&mut self, theme: Theme, preview: ThemeAction) {
                 self.theme = theme;
             }
         }
+        self.theme_changes += 1;
 
         self._refresh();
     }
@@ -1048,9 +1903,29 @@ fn launch_language_server(ls: &mut helix_lsp::Registry, doc: &mut Document) -> O
 
     fn _refresh(&mut self) {
         let config = self.config();
-        for (view, _) in self.tree.views_mut() {
+        let minimap_width = if config.minimap.enable {
+            config.minimap.width
+        } else {
+            0
+        };
+        let winbar_height = config.breadcrumbs.enable as u16;
+        let zen_width = self.zen_mode.then(|| config.zen_mode.width);
+        for (view, focused) in self.tree.all_views_mut() {
             let doc = doc_mut!(self, &view.doc);
             view.sync_changes(doc);
+            view.unfocused_gutters = config.unfocused_gutters.clone();
+            view.focused = focused;
+            let soft_wrap = view.options.soft_wrap.unwrap_or(config.soft_wrap.enable);
+            view.soft_wrap = config.soft_wrap.indicator_width(soft_wrap);
+            view.minimap_width = minimap_width;
+            view.scrollbar_width = config.scrollbar.enable as u16;
+            view.winbar_height = winbar_height;
+            view.zen_width = zen_width;
+            view.horizontal_scrolloff = config.horizontal_scrolloff;
+            view.horizontal_scroll_chunk = config.horizontal_scroll_chunk;
+            if soft_wrap {
+                view.offset.col = 0;
+            }
             view.ensure_cursor_in_view(doc, config.scrolloff)
         }
     }
@@ -1088,11 +1963,11 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                     && doc.path().is_none()
                     // If the buffer we are changing to is not this buffer
                     && id != doc.id
-                    // Ensure the buffer is not displayed in any other splits.
+                    // Ensure the buffer is not displayed in any other splits or tab pages.
                     && !self
                         .tree
-                        .traverse()
-                        .any(|(_, v)| v.doc == doc.id && v.id != view.id);
+                        .all_views()
+                        .any(|(v, _)| v.doc == doc.id && v.id != view.id);
 
                 let (view, doc) = current!(self);
                 let view_id = view.id;
@@ -1107,7 +1982,7 @@ pub fn switch(&mut self, id: DocumentId, action: Action) {
                     self.documents.remove(&id);
 
                     // Remove the scratch buffer from any jumplists
-                    for (view, _) in self.tree.views_mut() {
+                    for (view, _) in self.tree.all_views_mut() {
                         view.remove_document(&id);
                     }
                 } else {
@@ -1178,7 +2053,7 @@ fn new_document(&mut self, mut doc: Document) -> DocumentId {
         id
     }
 
-    fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
+    pub fn new_file_from_document(&mut self, action: Action, doc: Document) -> DocumentId {
         let id = self.new_document(doc);
         self.switch(id, action);
         id
@@ -1201,19 +2076,92 @@ pub fn open(&mut self, path: &Path, action: Action) -> Result<DocumentId, Error>
         let id = if let Some(id) = id {
             id
         } else {
-            let mut doc = Document::open(&path, None, Some(self.syn_loader.clone()))?;
+            let editor_config = if self.config().editorconfig {
+                EditorConfig::for_path(&path)
+            } else {
+                EditorConfig::default()
+            };
+            let large_file_size = self.config().large_file_size;
+            let large_file = large_file_size > 0
+                && std::fs::metadata(&path).map_or(false, |meta| meta.len() >= large_file_size);
+            if large_file {
+                let charset = editor_config.charset;
+                let mut doc = Document::open_large(&path, charset);
+                doc.set_editor_config(editor_config);
+                let id = self.new_document(doc);
+                self.load_large_file(id, path.clone(), charset);
+                id
+            } else {
+                let mut doc =
+                    Document::open(&path, editor_config.charset, Some(self.syn_loader.clone()))?;
+                doc.set_editor_config(editor_config);
 
-            let _ = Self::launch_language_server(&mut self.language_servers, &mut doc);
-            if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
-                doc.set_diff_base(diff_base, self.redraw_handle.clone());
+                let _ = Self::launch_language_server(&mut self.language_servers, &mut doc);
+                if let Some(diff_base) = self.diff_providers.get_diff_base(&path) {
+                    doc.set_diff_base(diff_base, self.redraw_handle.clone());
+                }
+                doc.set_version_control_head(self.diff_providers.get_current_head_name(&path));
+                self.new_document(doc)
             }
-            self.new_document(doc)
         };
 
+        if self.config().file_picker.frecency {
+            if let Ok(cwd) = std::env::current_dir() {
+                self.recent.visit(&cwd, &path, recent::now());
+            }
+        }
         self.switch(id, action);
         Ok(id)
     }
 
+    /// Reads the text of the large file of `doc_id` in the background.
+    fn load_large_file(
+        &mut self,
+        doc_id: DocumentId,
+        path: PathBuf,
+        encoding: Option<&'static encoding::Encoding>,
+    ) {
+        self.set_status(format!(
+            "Opening '{}' in large-file mode: no syntax highlighting, language server or diff",
+            helix_core::path::get_relative_path(&path).display()
+        ));
+        let load = tokio::task::spawn_blocking(move || {
+            let mut file =
+                std::fs::File::open(&path).with_context(|| format!("unable to open {:?}", path))?;
+            crate::document::from_reader(&mut file, encoding)
+        });
+        self.loading_docs.push(Box::pin(async move {
+            let result = load.await.map_err(Error::from).and_then(|result| result);
+            (doc_id, result)
+        }));
+    }
+
+    /// Sets the text of a large file once it is read, or closes its document if it could
+    /// not be read.
+    fn finish_loading(&mut self, doc_id: DocumentId, result: LoadResult) {
+        if !self.documents.contains_key(&doc_id) {
+            return;
+        }
+        let (text, encoding) = match result {
+            Ok(loaded) => loaded,
+            Err(err) => {
+                // an empty buffer left behind would write over the file
+                let _ = self.close_document(doc_id, true);
+                self.set_error(format!("Failed to read the file: {}", err));
+                return;
+            }
+        };
+        let doc = doc_mut!(self, &doc_id);
+        doc.finish_loading(text, encoding);
+        // the views of the other tabs too
+        for (view, _) in self.tree.all_views_mut() {
+            if view.doc == doc_id {
+                view.offset = Position::default();
+            }
+        }
+        self.clear_status();
+    }
+
     pub fn close(&mut self, id: ViewId) {
         // Remove selections for the closed view on all documents.
         for doc in self.documents_mut() {
@@ -1223,6 +2171,48 @@ pub fn close(&mut self, id: ViewId) {
         self._refresh();
     }
 
+    /// Opens a tab page after the current one, showing `doc_id`.
+    /// Overrides an option of the configuration in a single view, see [crate::view::ViewOptions::set].
+    pub fn set_view_option(
+        &mut self,
+        view_id: ViewId,
+        key: &str,
+        value: Option<&str>,
+    ) -> anyhow::Result<()> {
+        self.tree.get_mut(view_id).options.set(key, value)?;
+        self._refresh();
+        Ok(())
+    }
+
+    pub fn new_tab(&mut self, doc_id: DocumentId) {
+        self.enter_normal_mode();
+        self.tree.new_tab();
+        let view = View::new(doc_id, self.config().gutters.clone());
+        let view_id = self.tree.insert(view);
+        let doc = doc_mut!(self, &doc_id);
+        doc.ensure_view_init(view_id);
+        self._refresh();
+    }
+
+    pub fn switch_tab(&mut self, index: usize) {
+        if index == self.tree.active_tab() || index >= self.tree.tabs().len() {
+            return;
+        }
+        self.enter_normal_mode();
+        self.tree.switch_tab(index);
+        self._refresh();
+    }
+
+    /// Closes the tab page at `index` and its views, unless it is the last tab page.
+    pub fn close_tab(&mut self, index: usize) {
+        for view_id in self.tree.close_tab(index) {
+            for doc in self.documents_mut() {
+                doc.remove_view(view_id);
+            }
+        }
+        self._refresh();
+    }
+
     pub fn close_document(&mut self, doc_id: DocumentId, force: bool) -> Result<(), CloseError> {
         let doc = match self.documents.get_mut(&doc_id) {
             Some(doc) => doc,
@@ -1247,7 +2237,7 @@ enum Action {
 
         let actions: Vec<Action> = self
             .tree
-            .views_mut()
+            .all_views_mut()
             .filter_map(|(view, _focus)| {
                 view.remove_document(&doc_id);
 
@@ -1309,7 +2299,31 @@ pub fn save<P: Into<PathBuf>>(
         // via stream.then() ? then push into main future
 
         let path = path.map(|path| path.into());
+        let focus = self.tree.focus;
         let doc = doc_mut!(self, &doc_id);
+        if doc.editor_config().insert_final_newline == Some(true) {
+            let text = doc.text();
+            let ends_with_newline = text.len_chars() == 0
+                || helix_core::line_ending::get_line_ending(&text.slice(..)).is_some();
+            if !ends_with_newline {
+                let end = text.len_chars();
+                let transaction = Transaction::insert(
+                    text,
+                    &Selection::point(end),
+                    doc.line_ending.as_str().into(),
+                );
+                // the change is recorded in a view showing the document, if any
+                let view_id = if doc.selections().contains_key(&focus) {
+                    focus
+                } else {
+                    doc.selections().keys().next().copied().unwrap_or(focus)
+                };
+                doc.ensure_view_init(view_id);
+                let view = self.tree.get_mut(view_id);
+                apply_transaction(&transaction, doc, view);
+                doc.append_changes_to_history(view);
+            }
+        }
         let future = doc.save(path, force)?;
 
         use futures_util::stream;
@@ -1325,6 +2339,12 @@ pub fn save<P: Into<PathBuf>>(
         Ok(())
     }
 
+    /// Toggles the distraction free layout of all views.
+    pub fn toggle_zen_mode(&mut self) {
+        self.zen_mode = !self.zen_mode;
+        self._refresh();
+    }
+
     pub fn resize(&mut self, area: Rect) {
         if self.tree.resize(area) {
             self._refresh();
@@ -1337,6 +2357,10 @@ pub fn focus(&mut self, view_id: ViewId) {
         // if leaving the view: mode should reset and the cursor should be
         // within view
         if prev_id != view_id {
+            // the gutters may change with the focus, and the width of the text with them
+            for (view, focused) in self.tree.all_views_mut() {
+                view.focused = focused;
+            }
             self.enter_normal_mode();
             self.ensure_cursor_in_view(view_id);
 
@@ -1367,6 +2391,34 @@ pub fn transpose_view(&mut self) {
         self.tree.transpose();
     }
 
+    pub fn rotate_splits(&mut self, forward: bool) {
+        self.tree.rotate_splits(forward);
+        self._refresh();
+    }
+
+    /// Grows the focused split by `amount` columns or rows, see [Tree::resize_split].
+    pub fn resize_split(&mut self, layout: tree::Layout, amount: i32) -> bool {
+        let resized = self.tree.resize_split(self.tree.focus, layout, amount);
+        if resized {
+            self._refresh();
+        }
+        resized
+    }
+
+    /// Sets the width or height of the focused split, see [Tree::set_split_size].
+    pub fn set_split_size(&mut self, layout: tree::Layout, size: u16) -> bool {
+        let resized = self.tree.set_split_size(self.tree.focus, layout, size);
+        if resized {
+            self._refresh();
+        }
+        resized
+    }
+
+    pub fn equalize_splits(&mut self) {
+        self.tree.equalize_splits();
+        self._refresh();
+    }
+
     pub fn should_close(&self) -> bool {
         self.tree.is_empty()
     }
@@ -1393,6 +2445,14 @@ pub fn documents(&self) -> impl Iterator<Item = &Document> {
         self.documents.values()
     }
 
+    /// The documents in the order of the bufferline, the pinned ones first.
+    pub fn buffers_in_order(&self) -> Vec<&Document> {
+        let (mut buffers, unpinned): (Vec<_>, Vec<_>) =
+            self.documents().partition(|doc| doc.is_pinned());
+        buffers.extend(unpinned);
+        buffers
+    }
+
     #[inline]
     pub fn documents_mut(&mut self) -> impl Iterator<Item = &mut Document> {
         self.documents.values_mut()
@@ -1460,6 +2520,9 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                 Some(config_event) = self.config_events.1.recv() => {
                     return EditorEvent::ConfigEvent(config_event)
                 }
+                Some(event) = self.hook_events.1.recv() => {
+                    return EditorEvent::Hook(event)
+                }
                 Some(message) = self.language_servers.incoming.next() => {
                     return EditorEvent::LanguageServerMessage(message)
                 }
@@ -1467,23 +2530,126 @@ pub async fn wait_event(&mut self) -> EditorEvent {
                     return EditorEvent::DebuggerEvent(event)
                 }
 
-                _ = self.redraw_handle.0.notified() => {
-                    if  !self.needs_redraw{
-                        self.needs_redraw = true;
-                        let timeout = Instant::now() + Duration::from_millis(96);
-                        if timeout < self.idle_timer.deadline(){
-                            self.idle_timer.as_mut().reset(timeout)
-                        }
-                    }
-                }
+                _ = self.redraw_handle.0.notified() => self.schedule_redraw(),
 
                 _ = &mut self.idle_timer  => {
                     return EditorEvent::IdleTimer
                 }
+
+                _ = &mut self.file_watch_timer => {
+                    let config = self.config();
+                    self.file_watch_timer
+                        .as_mut()
+                        .reset(Instant::now() + config.file_watch.interval);
+                    if config.file_watch.enable {
+                        return EditorEvent::FileWatchTimer
+                    }
+                }
+
+                Some(event) = self.status_events.recv() => {
+                    self.repo_status.apply(event);
+                    self.repo_refreshes += 1;
+                    // a burst of changes, like a build writing many files, is shown at once
+                    self.schedule_redraw();
+                }
+
+                Some((doc_id, result)) = self.loading_docs.next() => {
+                    self.finish_loading(doc_id, result);
+                    return EditorEvent::DocumentLoaded
+                }
+
+                _ = &mut self.swap_timer => {
+                    let config = self.config();
+                    self.swap_timer
+                        .as_mut()
+                        .reset(Instant::now() + config.swap_files.interval);
+                    if config.swap_files.enable {
+                        return EditorEvent::SwapTimer
+                    }
+                }
+
+                _ = &mut self.auto_save_timer => {
+                    self.auto_save_timer.as_mut().reset(far_future());
+                    return EditorEvent::AutoSaveTimer
+                }
+
+                _ = &mut self.mouse_hover_timer => {
+                    self.mouse_hover_timer.as_mut().reset(far_future());
+                    if let Some(event) = self.mouse_hover.take() {
+                        return EditorEvent::MouseHover(event)
+                    }
+                }
+            }
+        }
+    }
+
+    /// Returns the documents whose files were changed outside of the editor since the
+    /// last check. Documents being written are left to the next check.
+    pub fn documents_changed_on_disk(&mut self) -> Vec<DocumentId> {
+        if self.write_count > 0 {
+            return Vec::new();
+        }
+        self.documents
+            .values_mut()
+            .filter_map(|doc| doc.check_changed_on_disk().then(|| doc.id()))
+            .collect()
+    }
+
+    /// Redraws shortly, along with the other redraws asked for until then.
+    fn schedule_redraw(&mut self) {
+        if !self.needs_redraw {
+            self.needs_redraw = true;
+            let timeout = Instant::now() + Duration::from_millis(96);
+            if timeout < self.idle_timer.deadline() {
+                self.idle_timer.as_mut().reset(timeout)
             }
         }
     }
 
+    /// The files with unresolved merge conflicts in the repository containing `dir`, as
+    /// of the last run of the status job. The repository has to be the one of the working
+    /// directory, as the others are not watched.
+    pub fn conflicted_files(&self, dir: &Path) -> Vec<PathBuf> {
+        match &self.repo_status.info {
+            Some(info) if dir.starts_with(&info.work_dir) => self
+                .repo_status
+                .conflicted()
+                .map(Path::to_path_buf)
+                .collect(),
+            _ => Vec::new(),
+        }
+    }
+
+    /// Reloads the document from its file, keeping the cursors of the views showing it
+    /// in view.
+    pub fn reload_document(&mut self, doc_id: DocumentId) -> anyhow::Result<()> {
+        let scrolloff = self.config().scrolloff;
+        let focus = self.tree.focus;
+        let doc = self
+            .documents
+            .get_mut(&doc_id)
+            .ok_or_else(|| anyhow::anyhow!("no such document"))?;
+        let mut view_ids: Vec<_> = doc.selections().keys().cloned().collect();
+        if view_ids.is_empty() {
+            doc.ensure_view_init(focus);
+            view_ids.push(focus);
+        }
+
+        // Every doc is guaranteed to have at least 1 view at this point.
+        let view = self.tree.get_mut(view_ids[0]);
+        // Ensure that the view is synced with the document's history.
+        view.sync_changes(doc);
+        doc.reload(view, &self.diff_providers, self.redraw_handle.clone())?;
+
+        for view_id in view_ids {
+            let view = self.tree.get_mut(view_id);
+            if view.doc == doc_id {
+                view.ensure_cursor_in_view(doc, scrolloff);
+            }
+        }
+        Ok(())
+    }
+
     pub async fn flush_writes(&mut self) -> anyhow::Result<()> {
         while self.write_count > 0 {
             if let Some(save_event) = self.save_queue.next().await {
@@ -1499,6 +2665,7 @@ pub async fn flush_writes(&mut self) -> anyhow::Result<()> {
 
                 let doc = doc_mut!(self, &save_event.doc_id);
                 doc.set_last_saved_revision(save_event.revision);
+                doc.set_disk_mtime(save_event.mtime);
             }
         }
 
@@ -1534,10 +2701,14 @@ pub fn enter_normal_mode(&mut self) {
     }
 }
 
+/// A deadline timers never reach, equivalent to the internal `Instant::far_future()` of
+/// tokio (30 years).
+fn far_future() -> Instant {
+    Instant::now() + Duration::from_secs(86400 * 365 * 30)
+}
+
 fn try_restore_indent(doc: &mut Document, view: &mut View) {
-    use helix_core::{
-        chars::char_is_whitespace, line_ending::line_end_char_index, Operation, Transaction,
-    };
+    use helix_core::{chars::char_is_whitespace, line_ending::line_end_char_index, Operation};
 
     fn inserted_a_new_blank_line(changes: &[Operation], pos: usize, line_end_pos: usize) -> bool {
         if let [Operation::Retain(move_pos), Operation::Insert(ref inserted_str), Operation::Retain(_)] =
@@ -1568,3 +2739,36 @@ fn inserted_a_new_blank_line(changes: &[Operation], pos: usize, line_end_pos: us
         crate::apply_transaction(&transaction, doc, view);
     }
 }
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn sort_comparators() {
+        let sorted = |comparator: SortComparator| {
+            let mut names = vec!["file10", "File2", "file2", "file01", "file", "a"];
+            names.sort_by(|a, b| comparator.compare(a, b));
+            names
+        };
+        assert_eq!(
+            sorted(SortComparator::Lexical),
+            ["File2", "a", "file", "file01", "file10", "file2"]
+        );
+        assert_eq!(
+            sorted(SortComparator::CaseInsensitive),
+            ["a", "file", "file01", "file10", "File2", "file2"]
+        );
+        assert_eq!(
+            sorted(SortComparator::Natural),
+            ["a", "file", "file01", "File2", "file2", "file10"]
+        );
+
+        let comparator = SortComparator::Natural;
+        let cmp = |a: &str, b: &str| comparator.compare_paths(Path::new(a), Path::new(b));
+        assert_eq!(cmp("dir/file2", "dir/file10"), Ordering::Less);
+        assert_eq!(cmp("dir2/file", "dir10"), Ordering::Less);
+        assert_eq!(cmp("dir", "dir/file"), Ordering::Less);
+        assert_eq!(cmp("dir/file", "dir/file"), Ordering::Equal);
+    }
+}
@@ -0,0 +1,266 @@
+//! Support for [EditorConfig](https://editorconfig.org) files: the `.editorconfig` files of
+//! the directories containing a file, up to the one marked as the root, set how it is
+//! indented and written.
+
+use std::{
+    fs,
+    path::{Path, PathBuf},
+};
+
+use helix_core::{encoding, indent::IndentStyle, regex::Regex};
+
+/// The properties of the `.editorconfig` files which apply to a file. Properties the
+/// files leave out, or set to `unset`, are `None`.
+#[derive(Debug, Default, Clone, PartialEq, Eq)]
+pub struct EditorConfig {
+    pub indent_style: Option<IndentStyle>,
+    /// Whether the file ends with a line ending when written.
+    pub insert_final_newline: Option<bool>,
+    pub charset: Option<&'static encoding::Encoding>,
+    pub max_line_length: Option<usize>,
+}
+
+/// A section of an `.editorconfig` file: the glob matching the files it applies to, and
+/// its properties in order, with lowercase names.
+struct Section {
+    glob: Regex,
+    properties: Vec<(String, String)>,
+}
+
+impl EditorConfig {
+    /// Reads the `.editorconfig` files applying to the file at `path`.
+    pub fn for_path(path: &Path) -> Self {
+        let mut files = Vec::new();
+        for dir in path.ancestors().skip(1) {
+            let file = dir.join(".editorconfig");
+            let contents = match fs::read_to_string(&file) {
+                Ok(contents) => contents,
+                Err(_) => continue,
+            };
+            let (root, sections) = parse(&contents);
+            files.push((dir.to_path_buf(), sections));
+            if root {
+                break;
+            }
+        }
+        resolve(files, path)
+    }
+
+    fn from_properties(properties: &[(String, String)]) -> Self {
+        let get = |name: &str| {
+            properties
+                .iter()
+                .rev()
+                .find(|(key, _)| key == name)
+                .map(|(_, value)| value.to_lowercase())
+                .filter(|value| value != "unset")
+        };
+        let size = |name: &str| get(name).and_then(|value| value.parse::<u8>().ok());
+
+        let indent_style = match get("indent_style").as_deref() {
+            Some("tab") => Some(IndentStyle::Tabs),
+            Some("space") => {
+                // `indent_size = tab` uses the width of tabs
+                let width = size("indent_size").or_else(|| size("tab_width"));
+                Some(IndentStyle::Spaces(width.unwrap_or(4).clamp(1, 16)))
+            }
+            _ => None,
+        };
+        let charset = get("charset").and_then(|charset| match charset.as_str() {
+            // the byte order mark is kept by the decoder but not written
+            "utf-8-bom" => Some(encoding::UTF_8),
+            charset => encoding::Encoding::for_label(charset.as_bytes()),
+        });
+        Self {
+            indent_style,
+            insert_final_newline: get("insert_final_newline").and_then(|value| value.parse().ok()),
+            charset,
+            max_line_length: get("max_line_length").and_then(|value| value.parse().ok()),
+        }
+    }
+}
+
+/// The properties of the sections of `files` matching `path`. The files are given with
+/// their directory, the closest to `path` first.
+fn resolve(files: Vec<(PathBuf, Vec<Section>)>, path: &Path) -> EditorConfig {
+    // the properties of the files closer to `path` win, and so do the later sections
+    let mut properties: Vec<(String, String)> = Vec::new();
+    for (dir, sections) in files.into_iter().rev() {
+        let relative = match path.strip_prefix(&dir) {
+            Ok(relative) => relative.to_string_lossy().replace('\\', "/"),
+            Err(_) => continue,
+        };
+        for section in sections {
+            if section.glob.is_match(&relative) {
+                properties.extend(section.properties);
+            }
+        }
+    }
+    EditorConfig::from_properties(&properties)
+}
+
+/// Parses an `.editorconfig` file into whether it is the root and its sections.
+/// Invalid lines and sections are skipped.
+fn parse(contents: &str) -> (bool, Vec<Section>) {
+    let mut root = false;
+    let mut sections: Vec<Section> = Vec::new();
+    // properties after an invalid section header belong to no section
+    let mut in_section = false;
+    for line in contents.lines() {
+        let line = line.trim();
+        if line.is_empty() || line.starts_with('#') || line.starts_with(';') {
+            continue;
+        }
+        if let Some(header) = line
+            .strip_prefix('[')
+            .and_then(|line| line.strip_suffix(']'))
+        {
+            in_section = match glob_to_regex(header) {
+                Some(glob) => {
+                    sections.push(Section {
+                        glob,
+                        properties: Vec::new(),
+                    });
+                    true
+                }
+                None => false,
+            };
+            continue;
+        }
+        let (key, value) = match line.split_once('=') {
+            Some((key, value)) => (key.trim().to_lowercase(), value.trim().to_string()),
+            None => continue,
+        };
+        match sections.last_mut() {
+            Some(section) if in_section => section.properties.push((key, value)),
+            None if key == "root" => root = value.eq_ignore_ascii_case("true"),
+            _ => (),
+        }
+    }
+    (root, sections)
+}
+
+/// Converts the glob of a section to a regex matching the paths relative to the directory
+/// of the `.editorconfig` file, with forward slashes. Globs without a slash match file
+/// names in any directory.
+fn glob_to_regex(glob: &str) -> Option<Regex> {
+    let (anchored, glob) = match glob.strip_prefix('/') {
+        Some(glob) => (true, glob),
+        None => (glob.contains('/'), glob),
+    };
+    let mut regex = String::from(if anchored { "^" } else { "^(?:.*/)?" });
+    let chars: Vec<char> = glob.chars().collect();
+    let mut braces = 0;
+    let mut i = 0;
+    while i < chars.len() {
+        match chars[i] {
+            '\\' if i + 1 < chars.len() => {
+                i += 1;
+                regex.push_str(&helix_core::regex::escape(&chars[i].to_string()));
+            }
+            '*' if chars.get(i + 1) == Some(&'*') => {
+                i += 1;
+                regex.push_str(".*");
+            }
+            '*' => regex.push_str("[^/]*"),
+            '?' => regex.push_str("[^/]"),
+            '[' => {
+                let end = chars[i..].iter().position(|&ch| ch == ']')? + i;
+                let mut class: String = chars[i + 1..end].iter().collect();
+                if let Some(negated) = class.strip_prefix('!') {
+                    class = format!("^{}", negated);
+                }
+                regex.push_str(&format!("[{}]", class.replace('\\', "\\\\")));
+                i = end;
+            }
+            '{' => {
+                let end = chars[i..]
+                    .iter()
+                    .position(|&ch| ch == '}')
+                    .map(|end| end + i);
+                let range = end.and_then(|end| {
+                    let inner: String = chars[i + 1..end].iter().collect();
+                    let (start, stop) = inner.split_once("..")?;
+                    Some((start.parse::<i64>().ok()?, stop.parse::<i64>().ok()?, end))
+                });
+                match range {
+                    Some((start, stop, end)) => {
+                        let (low, high) = (start.min(stop), start.max(stop));
+                        // large ranges match any number
+                        if high - low > 1000 {
+                            regex.push_str(r"-?\d+");
+                        } else {
+                            let numbers: Vec<_> = (low..=high).map(|n| n.to_string()).collect();
+                            regex.push_str(&format!("(?:{})", numbers.join("|")));
+                        }
+                        i = end;
+                    }
+                    None => {
+                        braces += 1;
+                        regex.push_str("(?:");
+                    }
+                }
+            }
+            ',' if braces > 0 => regex.push('|'),
+            '}' if braces > 0 => {
+                braces -= 1;
+                regex.push(')');
+            }
+            ch => regex.push_str(&helix_core::regex::escape(&ch.to_string())),
+        }
+        i += 1;
+    }
+    if braces > 0 {
+        return None;
+    }
+    regex.push('$');
+    Regex::new(&regex).ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_glob() {
+        let matches = |glob: &str, path: &str| glob_to_regex(glob).unwrap().is_match(path);
+        assert!(matches("*", "src/main.rs"));
+        assert!(matches("*.rs", "src/main.rs"));
+        assert!(!matches("*.rs", "src/main.rc"));
+        assert!(matches("*.{js,ts}", "app.ts"));
+        assert!(matches("src/*.rs", "src/main.rs"));
+        assert!(!matches("src/*.rs", "src/bin/main.rs"));
+        assert!(matches("src/**.rs", "src/bin/main.rs"));
+        assert!(!matches("/main.rs", "src/main.rs"));
+        assert!(matches("file{1..3}.txt", "file2.txt"));
+        assert!(!matches("file{1..3}.txt", "file4.txt"));
+        assert!(matches("[!a]*.md", "README.md"));
+        assert!(!matches("[!a]*.md", "about.md"));
+        assert!(glob_to_regex("{a,b").is_none());
+    }
+
+    #[test]
+    fn test_resolve() {
+        const TOP: &str = "root = true\n\n[*]\nindent_style = space\nindent_size = 2\n\
+            insert_final_newline = true\n\n[*.go]\nindent_style = tab\n";
+        const SUB: &str = "[*.rs]\nindent_size = 4\nmax_line_length = 100\ncharset = latin1\n";
+        assert!(parse(TOP).0);
+        assert!(!parse(SUB).0);
+        let files = || {
+            vec![
+                (PathBuf::from("/project/sub"), parse(SUB).1),
+                (PathBuf::from("/project"), parse(TOP).1),
+            ]
+        };
+
+        let config = resolve(files(), Path::new("/project/sub/main.rs"));
+        assert_eq!(config.indent_style, Some(IndentStyle::Spaces(4)));
+        assert_eq!(config.insert_final_newline, Some(true));
+        assert_eq!(config.max_line_length, Some(100));
+        assert_eq!(config.charset, Some(encoding::WINDOWS_1252));
+
+        let config = resolve(files(), Path::new("/project/sub/main.go"));
+        assert_eq!(config.indent_style, Some(IndentStyle::Tabs));
+        assert_eq!(config.max_line_length, None);
+    }
+}
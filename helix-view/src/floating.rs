@@ -0,0 +1,146 @@
+//! Placement of windows floating in front of the editor, like popups, menus and overlays.
+
+use crate::graphics::Rect;
+use helix_core::Position;
+
+/// The side of its anchor a window prefers to open at.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Bias {
+    Above,
+    Below,
+}
+
+/// What a floating window is positioned relative to.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Anchor {
+    /// Next to a screen position, usually the cursor. The window opens on the side of the
+    /// bias unless there isn't enough space, then it opens on the other side.
+    Position(Position, Bias),
+    /// The center of the viewport.
+    Center,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Size {
+    /// A number of columns and rows.
+    Fixed(u16, u16),
+    /// A percentage of the width and the height of the viewport.
+    Relative(u8, u8),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct FloatingWindow {
+    pub anchor: Anchor,
+    pub size: Size,
+    /// Windows with a higher z-index are drawn in front of the ones with a lower z-index.
+    pub z_index: u16,
+    /// Whether the window takes input, events pass through to the windows behind it otherwise.
+    pub focusable: bool,
+}
+
+impl FloatingWindow {
+    pub fn new(anchor: Anchor, size: Size) -> Self {
+        Self {
+            anchor,
+            size,
+            z_index: 0,
+            focusable: true,
+        }
+    }
+
+    pub fn z_index(mut self, z_index: u16) -> Self {
+        self.z_index = z_index;
+        self
+    }
+
+    pub fn focusable(mut self, focusable: bool) -> Self {
+        self.focusable = focusable;
+        self
+    }
+
+    /// The width and height of the window in `viewport`.
+    pub fn dimensions(&self, viewport: Rect) -> (u16, u16) {
+        fn mul_and_cast(size: u16, factor: u8) -> u16 {
+            ((size as u32) * (factor as u32) / 100).try_into().unwrap()
+        }
+
+        match self.size {
+            Size::Fixed(width, height) => (width, height),
+            Size::Relative(width, height) => (
+                mul_and_cast(viewport.width, width),
+                mul_and_cast(viewport.height, height),
+            ),
+        }
+    }
+
+    /// The area the window is drawn at. A window next to a position is moved to the left
+    /// to stay within the viewport, and may still stick out of it if it's too large.
+    pub fn area(&self, viewport: Rect) -> Rect {
+        let (width, height) = self.dimensions(viewport);
+        match self.anchor {
+            Anchor::Position(position, bias) => {
+                let mut x = position.col as u16;
+                let y = position.row as u16;
+                if viewport.width <= x + width {
+                    x = x.saturating_sub((x + width).saturating_sub(viewport.width));
+                }
+
+                let can_put_below = viewport.height > y + height;
+                let can_put_above = y.checked_sub(height).is_some();
+                let bias = match bias {
+                    Bias::Below if !can_put_below => Bias::Above,
+                    Bias::Above if !can_put_above => Bias::Below,
+                    bias => bias,
+                };
+                let y = match bias {
+                    Bias::Above => y.saturating_sub(height),
+                    Bias::Below => y + 1,
+                };
+
+                Rect::new(x, y, width, height)
+            }
+            Anchor::Center => {
+                let offset_x = viewport.width.saturating_sub(width) / 2;
+                let offset_y = viewport.height.saturating_sub(height) / 2;
+                Rect::new(viewport.x + offset_x, viewport.y + offset_y, width, height)
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_position_anchor() {
+        let viewport = Rect::new(0, 0, 80, 24);
+        let window = |row, col, bias| {
+            FloatingWindow::new(
+                Anchor::Position(Position::new(row, col), bias),
+                Size::Fixed(20, 5),
+            )
+            .area(viewport)
+        };
+        assert_eq!(window(10, 10, Bias::Below), Rect::new(10, 11, 20, 5));
+        assert_eq!(window(10, 10, Bias::Above), Rect::new(10, 5, 20, 5));
+        // not enough space on the side of the bias
+        assert_eq!(window(20, 10, Bias::Below), Rect::new(10, 15, 20, 5));
+        assert_eq!(window(2, 10, Bias::Above), Rect::new(10, 3, 20, 5));
+        // moved left to stay within the viewport
+        assert_eq!(window(10, 70, Bias::Below), Rect::new(60, 11, 20, 5));
+    }
+
+    #[test]
+    fn test_center_anchor() {
+        let window = FloatingWindow::new(Anchor::Center, Size::Relative(90, 90));
+        assert_eq!(
+            window.area(Rect::new(0, 0, 100, 40)),
+            Rect::new(5, 2, 90, 36)
+        );
+        assert_eq!(
+            window.area(Rect::new(10, 1, 50, 20)),
+            Rect::new(12, 2, 45, 18)
+        );
+    }
+}
@@ -32,6 +32,7 @@ pub fn style<'doc>(
             GutterType::LineNumbers => line_numbers(editor, doc, view, theme, is_focused),
             GutterType::Spacer => padding(editor, doc, view, theme, is_focused),
             GutterType::Diff => diff(editor, doc, view, theme, is_focused),
+            GutterType::CodeActions => code_actions(editor, doc, view, theme, is_focused),
         }
     }
 
@@ -41,6 +42,7 @@ pub fn width(self, _view: &View, doc: &Document) -> usize {
             GutterType::LineNumbers => line_numbers_width(_view, doc),
             GutterType::Spacer => 1,
             GutterType::Diff => 1,
+            GutterType::CodeActions => 2,
         }
     }
 }
@@ -132,6 +134,27 @@ pub fn diff<'doc>(
     }
 }
 
+pub fn code_actions<'doc>(
+    _editor: &'doc Editor,
+    doc: &'doc Document,
+    _view: &View,
+    theme: &Theme,
+    _is_focused: bool,
+) -> GutterFn<'doc> {
+    let style = theme.get("ui.gutter.code-action");
+    let hint = doc
+        .code_action_hint()
+        .filter(|hint| hint.available && hint.version == doc.version());
+
+    Box::new(move |line: usize, _selected: bool, out: &mut String| {
+        if hint?.line != line {
+            return None;
+        }
+        write!(out, "💡").unwrap();
+        Some(style)
+    })
+}
+
 pub fn line_numbers<'doc>(
     editor: &'doc Editor,
     doc: &'doc Document,
@@ -154,7 +177,10 @@ pub fn line_numbers<'doc>(
         .text()
         .char_to_line(doc.selection(view.id).primary().cursor(text));
 
-    let line_number = editor.config().line_number;
+    let line_number = view
+        .options
+        .line_number
+        .unwrap_or(editor.config().line_number);
     let mode = editor.mode;
 
     Box::new(move |line: usize, selected: bool, out: &mut String| {
@@ -223,9 +249,19 @@ pub fn breakpoints<'doc>(
     theme: &Theme,
     _is_focused: bool,
 ) -> GutterFn<'doc> {
-    let warning = theme.get("warning");
-    let error = theme.get("error");
-    let info = theme.get("info");
+    // fall back to the diagnostic colors for themes without breakpoint styles
+    let plain = theme
+        .try_get("ui.breakpoint")
+        .unwrap_or_else(|| theme.get("warning"));
+    let conditional = theme
+        .try_get_exact("ui.breakpoint.conditional")
+        .unwrap_or_else(|| theme.get("error"));
+    let log_point = theme
+        .try_get_exact("ui.breakpoint.log-point")
+        .unwrap_or_else(|| theme.get("info"));
+    let unverified = theme
+        .try_get_exact("ui.breakpoint.unverified")
+        .unwrap_or_else(|| Style::default().fg(Color::Gray));
 
     let breakpoints = doc.path().and_then(|path| editor.breakpoints.get(path));
 
@@ -234,35 +270,34 @@ pub fn breakpoints<'doc>(
         None => return Box::new(move |_, _, _| None),
     };
 
+    let symbols = editor.config().breakpoint_symbols.clone();
+
     Box::new(move |line: usize, _selected: bool, out: &mut String| {
         let breakpoint = breakpoints
             .iter()
             .find(|breakpoint| breakpoint.line == line)?;
 
-        let mut style = if breakpoint.condition.is_some() && breakpoint.log_message.is_some() {
-            error.underline_style(UnderlineStyle::Line)
+        let (sym, mut style) = if breakpoint.condition.is_some() && breakpoint.log_message.is_some()
+        {
+            (
+                &symbols.conditional,
+                conditional.underline_style(UnderlineStyle::Line),
+            )
         } else if breakpoint.condition.is_some() {
-            error
+            (&symbols.conditional, conditional)
         } else if breakpoint.log_message.is_some() {
-            info
+            (&symbols.log_point, log_point)
         } else {
-            warning
+            (&symbols.plain, plain)
         };
 
-        if !breakpoint.verified {
-            // Faded colors
-            style = if let Some(Color::Rgb(r, g, b)) = style.fg {
-                style.fg(Color::Rgb(
-                    ((r as f32) * 0.4).floor() as u8,
-                    ((g as f32) * 0.4).floor() as u8,
-                    ((b as f32) * 0.4).floor() as u8,
-                ))
-            } else {
-                style.fg(Color::Gray)
-            }
+        let sym = if breakpoint.verified {
+            sym
+        } else {
+            style = style.patch(unverified);
+            &symbols.unverified
         };
 
-        let sym = if breakpoint.verified { "▲" } else { "⊚" };
         write!(out, "{}", sym).unwrap();
         Some(style)
     })
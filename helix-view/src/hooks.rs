@@ -0,0 +1,140 @@
+//! Events of the files, the explorer and the buffers, sent to the application which runs
+//! the `[[editor.hooks]]` of the config matching them: shell commands in the background
+//! or commands, like in keymaps.
+
+use std::{
+    ffi::OsStr,
+    path::{Path, PathBuf},
+};
+
+use serde::{Deserialize, Serialize};
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HookEvent {
+    /// A file or directory was created in the explorer or by a workspace edit.
+    FileCreated(PathBuf),
+    FileRenamed {
+        from: PathBuf,
+        to: PathBuf,
+    },
+    FileDeleted(PathBuf),
+    /// A file was opened or a directory expanded from the explorer.
+    ExplorerNodeOpened(PathBuf),
+    /// A buffer was written to its file.
+    DocumentSaved(PathBuf),
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum HookEventKind {
+    FileCreated,
+    FileRenamed,
+    FileDeleted,
+    ExplorerNodeOpened,
+    DocumentSaved,
+}
+
+impl HookEventKind {
+    pub fn name(&self) -> &'static str {
+        match self {
+            Self::FileCreated => "file-created",
+            Self::FileRenamed => "file-renamed",
+            Self::FileDeleted => "file-deleted",
+            Self::ExplorerNodeOpened => "explorer-node-opened",
+            Self::DocumentSaved => "document-saved",
+        }
+    }
+}
+
+impl HookEvent {
+    pub fn kind(&self) -> HookEventKind {
+        match self {
+            Self::FileCreated(_) => HookEventKind::FileCreated,
+            Self::FileRenamed { .. } => HookEventKind::FileRenamed,
+            Self::FileDeleted(_) => HookEventKind::FileDeleted,
+            Self::ExplorerNodeOpened(_) => HookEventKind::ExplorerNodeOpened,
+            Self::DocumentSaved(_) => HookEventKind::DocumentSaved,
+        }
+    }
+
+    /// The file or directory of the event, the new path for renames.
+    pub fn path(&self) -> &Path {
+        match self {
+            Self::FileCreated(path)
+            | Self::FileDeleted(path)
+            | Self::ExplorerNodeOpened(path)
+            | Self::DocumentSaved(path) => path,
+            Self::FileRenamed { to, .. } => to,
+        }
+    }
+
+    /// The environment of the shell commands of the hooks: `HELIX_EVENT`, `HELIX_PATH`
+    /// and, for renames, `HELIX_OLD_PATH`.
+    pub fn env(&self) -> Vec<(&'static str, &OsStr)> {
+        let mut env = vec![
+            ("HELIX_EVENT", OsStr::new(self.kind().name())),
+            ("HELIX_PATH", self.path().as_os_str()),
+        ];
+        if let Self::FileRenamed { from, .. } = self {
+            env.push(("HELIX_OLD_PATH", from.as_os_str()));
+        }
+        env
+    }
+}
+
+/// What to do on the events of a kind, from the `[[editor.hooks]]` of the config.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case", deny_unknown_fields)]
+pub struct Hook {
+    pub event: HookEventKind,
+    /// Only the events about the paths under this directory, relative to the working
+    /// directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dir: Option<PathBuf>,
+    /// A shell command run in the background, in the working directory.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub run: Option<String>,
+    /// A command, like `:format` or `reload`, run in the focused view.
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub command: Option<String>,
+}
+
+impl Hook {
+    pub fn matches(&self, event: &HookEvent, cwd: &Path) -> bool {
+        self.event == event.kind()
+            && self
+                .dir
+                .as_ref()
+                .map_or(true, |dir| event.path().starts_with(cwd.join(dir)))
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn matches() {
+        let hook = Hook {
+            event: HookEventKind::DocumentSaved,
+            dir: Some(PathBuf::from("web")),
+            run: Some("prettier --write \"$HELIX_PATH\"".to_owned()),
+            command: None,
+        };
+        let cwd = Path::new("/project");
+        let saved = |path: &str| HookEvent::DocumentSaved(PathBuf::from(path));
+        assert!(hook.matches(&saved("/project/web/index.js"), cwd));
+        assert!(!hook.matches(&saved("/project/src/main.rs"), cwd));
+        assert!(!hook.matches(
+            &HookEvent::FileCreated(PathBuf::from("/project/web/index.js")),
+            cwd
+        ));
+
+        let renamed = HookEvent::FileRenamed {
+            from: PathBuf::from("/project/src/a.js"),
+            to: PathBuf::from("/project/web/a.js"),
+        };
+        assert_eq!(renamed.path(), Path::new("/project/web/a.js"));
+        assert_eq!(renamed.env().len(), 3);
+    }
+}
@@ -0,0 +1,205 @@
+//! Inline images for terminals implementing the kitty graphics protocol or the
+//! iTerm2 inline image protocol.
+
+use std::fmt::Write;
+use std::path::Path;
+
+use crate::{base64, graphics::Rect};
+
+/// Bytes of base64 sent per kitty graphics command, the maximum the protocol allows.
+const KITTY_CHUNK_SIZE: usize = 4096;
+
+/// Images larger than this are not sent to the terminal.
+pub const MAX_IMAGE_SIZE: u64 = 16 * 1024 * 1024;
+
+/// A protocol to draw images in the terminal.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageProtocol {
+    /// <https://sw.kovidgoyal.net/kitty/graphics-protocol/>
+    Kitty,
+    /// <https://iterm2.com/documentation-images.html>
+    Iterm,
+}
+
+impl ImageProtocol {
+    /// Detects the protocol supported by the terminal from its environment variables.
+    pub fn detect() -> Option<Self> {
+        let var = |name: &str| std::env::var(name).unwrap_or_default();
+        // multiplexers only forward the escape sequences when they are wrapped
+        if ["TMUX", "STY", "ZELLIJ"]
+            .iter()
+            .any(|name| std::env::var_os(name).is_some())
+        {
+            return None;
+        }
+        if std::env::var_os("KITTY_WINDOW_ID").is_some() || var("TERM") == "xterm-kitty" {
+            return Some(Self::Kitty);
+        }
+        match var("TERM_PROGRAM").as_str() {
+            "ghostty" => Some(Self::Kitty),
+            "iTerm.app" | "WezTerm" => Some(Self::Iterm),
+            _ if var("LC_TERMINAL") == "iTerm2" => Some(Self::Iterm),
+            _ => None,
+        }
+    }
+
+    /// Whether images of `format` can be sent to the terminal as they are.
+    pub fn supports(self, format: ImageFormat) -> bool {
+        match self {
+            // other formats would need decoding to raw pixels first
+            Self::Kitty => format == ImageFormat::Png,
+            Self::Iterm => true,
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ImageFormat {
+    Png,
+    Jpeg,
+    Gif,
+    Bmp,
+    Webp,
+}
+
+impl ImageFormat {
+    /// Guesses the format of an image from the extension of its path.
+    pub fn from_path(path: &Path) -> Option<Self> {
+        let extension = path.extension()?.to_str()?.to_ascii_lowercase();
+        let format = match extension.as_str() {
+            "png" => Self::Png,
+            "jpg" | "jpeg" => Self::Jpeg,
+            "gif" => Self::Gif,
+            "bmp" => Self::Bmp,
+            "webp" => Self::Webp,
+            _ => return None,
+        };
+        Some(format)
+    }
+
+    pub fn name(self) -> &'static str {
+        match self {
+            Self::Png => "PNG",
+            Self::Jpeg => "JPEG",
+            Self::Gif => "GIF",
+            Self::Bmp => "BMP",
+            Self::Webp => "WebP",
+        }
+    }
+
+    /// Reads the width and height in pixels from the header of an image.
+    /// Only PNG and GIF headers are understood.
+    pub fn dimensions(self, data: &[u8]) -> Option<(u32, u32)> {
+        let be = |bytes: &[u8]| u32::from_be_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+        let le = |bytes: &[u8]| u16::from_le_bytes([bytes[0], bytes[1]]) as u32;
+        match self {
+            // the signature is followed by the IHDR chunk
+            Self::Png if data.len() >= 24 && data.starts_with(b"\x89PNG\r\n\x1a\n") => {
+                Some((be(&data[16..20]), be(&data[20..24])))
+            }
+            Self::Gif if data.len() >= 10 && data.starts_with(b"GIF8") => {
+                Some((le(&data[6..8]), le(&data[8..10])))
+            }
+            _ => None,
+        }
+    }
+}
+
+/// An image file to draw over an area of the terminal.
+#[derive(Debug, PartialEq, Eq)]
+pub struct Image {
+    pub protocol: ImageProtocol,
+    pub format: ImageFormat,
+    /// Width and height in pixels, when known.
+    pub dimensions: Option<(u32, u32)>,
+    /// The content of the image file.
+    pub data: Vec<u8>,
+}
+
+impl Image {
+    pub fn new(protocol: ImageProtocol, format: ImageFormat, data: Vec<u8>) -> Self {
+        Self {
+            protocol,
+            format,
+            dimensions: format.dimensions(&data),
+            data,
+        }
+    }
+
+    /// Size in cells of the image scaled to fit `area` while keeping its aspect
+    /// ratio, assuming cells are twice as high as they are wide.
+    fn fit(&self, area: Rect) -> (u16, u16) {
+        let (width, height) = match self.dimensions {
+            Some((width, height)) if width > 0 && height > 0 => (width as f64, height as f64),
+            _ => return (area.width, area.height),
+        };
+        let cols = (area.height as f64 * 2.0 * width / height).min(area.width as f64);
+        let rows = (cols * height / width / 2.0).min(area.height as f64);
+        ((cols as u16).max(1), (rows as u16).max(1))
+    }
+
+    /// The escape sequence drawing the image at the cursor, scaled to fit `area`.
+    pub fn escape_sequence(&self, area: Rect) -> String {
+        let (cols, rows) = self.fit(area);
+        let data = base64::encode(&self.data);
+        let mut sequence = String::with_capacity(data.len() + 64);
+        match self.protocol {
+            ImageProtocol::Kitty => {
+                let chunks = data.as_bytes().chunks(KITTY_CHUNK_SIZE);
+                let count = chunks.len();
+                for (i, chunk) in chunks.enumerate() {
+                    let more = (i + 1 < count) as u8;
+                    // the chunks are ascii, they can be split anywhere
+                    let chunk = std::str::from_utf8(chunk).unwrap();
+                    if i == 0 {
+                        // transmit and display a png, without responses and moving the cursor
+                        let _ = write!(
+                            sequence,
+                            "\x1b_Ga=T,f=100,c={},r={},q=2,C=1,m={};{}\x1b\\",
+                            cols, rows, more, chunk
+                        );
+                    } else {
+                        let _ = write!(sequence, "\x1b_Gm={};{}\x1b\\", more, chunk);
+                    }
+                }
+            }
+            ImageProtocol::Iterm => {
+                let _ = write!(
+                    sequence,
+                    "\x1b]1337;File=inline=1;size={};width={};height={};preserveAspectRatio=1:{}\x07",
+                    self.data.len(),
+                    cols,
+                    rows,
+                    data
+                );
+            }
+        }
+        sequence
+    }
+
+    /// The escape sequence removing the images drawn with `protocol`, if they are not
+    /// removed by drawing over them.
+    pub fn clear_sequence(protocol: ImageProtocol) -> Option<&'static str> {
+        match protocol {
+            ImageProtocol::Kitty => Some("\x1b_Ga=d,q=2\x1b\\"),
+            ImageProtocol::Iterm => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_fit() {
+        let mut png = b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR".to_vec();
+        png.extend_from_slice(&200u32.to_be_bytes());
+        png.extend_from_slice(&100u32.to_be_bytes());
+        let image = Image::new(ImageProtocol::Kitty, ImageFormat::Png, png);
+        assert_eq!(image.dimensions, Some((200, 100)));
+        // limited by the height, then by the width
+        assert_eq!(image.fit(Rect::new(0, 0, 80, 10)), (40, 10));
+        assert_eq!(image.fit(Rect::new(0, 0, 20, 10)), (20, 5));
+    }
+}
@@ -15,6 +15,8 @@ pub enum Event {
     Paste(String),
     Resize(u16, u16),
     IdleTimeout,
+    /// The mouse pointer rested after the movement, see [`crate::editor::MouseHover`].
+    MouseHover(MouseEvent),
 }
 
 #[derive(Debug, PartialOrd, PartialEq, Eq, Clone, Copy, Hash)]
@@ -4,17 +4,24 @@
 pub mod clipboard;
 pub mod document;
 pub mod editor;
+pub mod editorconfig;
 pub mod env;
+pub mod floating;
 pub mod graphics;
 pub mod gutter;
+pub mod hooks;
 pub mod handlers {
     pub mod dap;
     pub mod lsp;
 }
 pub mod base64;
+pub mod image;
 pub mod info;
 pub mod input;
 pub mod keyboard;
+pub mod recent;
+pub mod session;
+pub mod swap;
 pub mod theme;
 pub mod tree;
 pub mod view;
@@ -53,8 +60,6 @@ pub fn align_view(doc: &Document, view: &mut View, align: Align) {
         .selection(view.id)
         .primary()
         .cursor(doc.text().slice(..));
-    let line = doc.text().char_to_line(pos);
-
     let last_line_height = view.inner_height().saturating_sub(1);
 
     let relative = match align {
@@ -63,7 +68,7 @@ pub fn align_view(doc: &Document, view: &mut View, align: Align) {
         Align::Bottom => last_line_height,
     };
 
-    view.offset.row = line.saturating_sub(relative);
+    view.offset.row = view.offset_row_with_pos_at(doc, pos, relative);
 }
 
 /// Applies a [`helix_core::Transaction`] to the given [`Document`]
@@ -0,0 +1,239 @@
+//! The workspaces and files opened recently, remembered across restarts to list them
+//! on the dashboard, and how often and how lately the files of each workspace were opened
+//! to list the ones opened the most first in the file picker and the explorer.
+
+use std::{
+    collections::{BTreeMap, HashMap},
+    fs,
+    path::{Path, PathBuf},
+    time::{SystemTime, UNIX_EPOCH},
+};
+
+use anyhow::{Context, Error};
+use serde::{Deserialize, Serialize};
+
+use crate::Editor;
+
+/// Number of workspaces and of files remembered.
+const MAX_RECENT: usize = 50;
+
+/// Number of files whose visits are remembered for each workspace, the ones with the
+/// lowest scores are forgotten first.
+const MAX_VISITED: usize = 1000;
+
+#[derive(Debug, Default, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Visits {
+    pub count: u32,
+    /// When the file was last opened, in seconds since the Unix epoch.
+    pub last: u64,
+}
+
+impl Visits {
+    /// The number of visits weighted by the age of the last one at `now`, the files opened
+    /// in the last hour weighing the most.
+    pub fn score(&self, now: u64) -> u64 {
+        let weight = match now.saturating_sub(self.last) {
+            age if age < 60 * 60 => 16,
+            age if age < 24 * 60 * 60 => 8,
+            age if age < 7 * 24 * 60 * 60 => 2,
+            _ => 1,
+        };
+        self.count as u64 * weight
+    }
+}
+
+#[derive(Debug, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default, rename_all = "kebab-case")]
+pub struct Recent {
+    /// Working directories, the most recent first.
+    pub workspaces: Vec<PathBuf>,
+    /// Files, the most recent first.
+    pub files: Vec<PathBuf>,
+    /// The visits of the files opened in each working directory.
+    pub visits: BTreeMap<PathBuf, BTreeMap<PathBuf, Visits>>,
+    /// The workspaces, files and times of the visits made since loading, which are added
+    /// to the ones other editors saved meanwhile.
+    #[serde(skip)]
+    pending: Vec<(PathBuf, PathBuf, u64)>,
+}
+
+/// The time the visits are counted at, in seconds since the Unix epoch.
+pub fn now() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map_or(0, |time| time.as_secs())
+}
+
+fn recent_file() -> PathBuf {
+    helix_loader::cache_dir().join("recent.json")
+}
+
+/// Moves `path` to the front of `list`, dropping the oldest paths beyond the limit.
+fn push(list: &mut Vec<PathBuf>, path: &Path) {
+    list.retain(|other| other != path);
+    list.insert(0, path.to_path_buf());
+    list.truncate(MAX_RECENT);
+}
+
+impl Recent {
+    /// Loads the recent paths, none if they were never saved or cannot be read.
+    pub fn load() -> Self {
+        fs::read_to_string(recent_file())
+            .ok()
+            .and_then(|json| serde_json::from_str(&json).ok())
+            .unwrap_or_default()
+    }
+
+    pub fn save(&self) -> Result<(), Error> {
+        let path = recent_file();
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(&path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write '{}'", path.display()))
+    }
+
+    /// Adds the working directory and the files open in `editor`, the focused one first.
+    pub fn record(&mut self, editor: &Editor) {
+        if let Ok(cwd) = std::env::current_dir() {
+            push(&mut self.workspaces, &cwd);
+        }
+        let focused = editor.tree.try_get(editor.tree.focus).map(|view| view.doc);
+        let mut docs: Vec<_> = editor.documents().collect();
+        // documents opened later have greater ids and end up in front
+        docs.sort_by_key(|doc| (Some(doc.id()) == focused, doc.id()));
+        for doc in docs {
+            if let Some(path) = doc.path() {
+                push(&mut self.files, path);
+            }
+        }
+    }
+
+    /// The recent workspaces which still exist, at most `count` of them.
+    pub fn existing_workspaces(&self, count: usize) -> impl Iterator<Item = &PathBuf> {
+        self.workspaces
+            .iter()
+            .filter(|path| path.is_dir())
+            .take(count)
+    }
+
+    /// The recent files which still exist, at most `count` of them.
+    pub fn existing_files(&self, count: usize) -> impl Iterator<Item = &PathBuf> {
+        self.files.iter().filter(|path| path.is_file()).take(count)
+    }
+
+    /// Counts a visit of `path` in the working directory `workspace` at `time`.
+    pub fn visit(&mut self, workspace: &Path, path: &Path, time: u64) {
+        self.add_visit(workspace, path, time);
+        self.pending
+            .push((workspace.to_path_buf(), path.to_path_buf(), time));
+    }
+
+    /// Adds the visits `other` counted since it was loaded, to save them on top of the
+    /// ones saved since then.
+    pub fn add_visits(&mut self, other: &Recent) {
+        for (workspace, path, time) in &other.pending {
+            self.add_visit(workspace, path, *time);
+        }
+    }
+
+    fn add_visit(&mut self, workspace: &Path, path: &Path, time: u64) {
+        let files = self.visits.entry(workspace.to_path_buf()).or_default();
+        let visits = files.entry(path.to_path_buf()).or_default();
+        visits.count += 1;
+        visits.last = visits.last.max(time);
+        if files.len() > MAX_VISITED {
+            let lowest = files
+                .iter()
+                .filter(|(file, _)| *file != path)
+                .min_by_key(|(_, visits)| visits.score(time))
+                .map(|(file, _)| file.clone());
+            if let Some(lowest) = lowest {
+                files.remove(&lowest);
+            }
+        }
+    }
+
+    /// The scores of the files opened in the working directory `workspace`, at `now`.
+    pub fn scores(&self, workspace: &Path, now: u64) -> HashMap<&Path, u64> {
+        self.visits
+            .get(workspace)
+            .into_iter()
+            .flatten()
+            .map(|(path, visits)| (path.as_path(), visits.score(now)))
+            .collect()
+    }
+
+    /// The files opened in the working directory `workspace`, the highest scores at `now`
+    /// first.
+    pub fn ranked(&self, workspace: &Path, now: u64) -> Vec<&Path> {
+        let mut files: Vec<_> = self.scores(workspace, now).into_iter().collect();
+        files.sort_by(|(a, a_score), (b, b_score)| b_score.cmp(a_score).then(a.cmp(b)));
+        files.into_iter().map(|(path, _)| path).collect()
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    #[test]
+    fn test_push() {
+        let mut list = Vec::new();
+        push(&mut list, Path::new("a"));
+        push(&mut list, Path::new("b"));
+        push(&mut list, Path::new("a"));
+        assert_eq!(list, vec![PathBuf::from("a"), PathBuf::from("b")]);
+
+        for i in 0..MAX_RECENT {
+            push(&mut list, Path::new(&i.to_string()));
+        }
+        assert_eq!(list.len(), MAX_RECENT);
+        assert_eq!(list[0], PathBuf::from((MAX_RECENT - 1).to_string()));
+    }
+
+    #[test]
+    fn test_scores() {
+        let mut recent = Recent::default();
+        let workspace = Path::new("/work");
+        let day = 24 * 60 * 60;
+        // opened often a while ago, or once lately
+        for _ in 0..10 {
+            recent.visit(workspace, Path::new("/work/old"), 0);
+        }
+        recent.visit(workspace, Path::new("/work/new"), 30 * day);
+        recent.visit(Path::new("/other"), Path::new("/other/file"), 30 * day);
+
+        let scores = recent.scores(workspace, 30 * day + 60);
+        assert_eq!(scores.len(), 2);
+        assert_eq!(scores[Path::new("/work/old")], 10);
+        assert_eq!(scores[Path::new("/work/new")], 16);
+        assert_eq!(
+            recent.ranked(workspace, 30 * day + 60),
+            [Path::new("/work/new"), Path::new("/work/old")]
+        );
+        assert!(recent.scores(Path::new("/none"), 0).is_empty());
+
+        // the visits are saved on top of the ones saved meanwhile
+        let mut saved = Recent::default();
+        saved.visit(workspace, Path::new("/work/old"), 0);
+        saved.add_visits(&recent);
+        assert_eq!(saved.visits[workspace][Path::new("/work/old")].count, 11);
+        assert_eq!(saved.visits.len(), 2);
+    }
+
+    #[test]
+    fn test_max_visited() {
+        let mut recent = Recent::default();
+        let workspace = Path::new("/work");
+        recent.visit(workspace, Path::new("/work/kept"), 0);
+        recent.visit(workspace, Path::new("/work/kept"), 0);
+        for i in 0..MAX_VISITED {
+            recent.visit(workspace, Path::new(&format!("/work/{}", i)), 0);
+        }
+        let files = &recent.visits[workspace];
+        assert_eq!(files.len(), MAX_VISITED);
+        assert!(files.contains_key(Path::new("/work/kept")));
+    }
+}
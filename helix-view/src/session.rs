@@ -0,0 +1,477 @@
+//! Sessions: the buffers, window layouts, cursors and jumplists of an editor, saved
+//! to a file to return to them after restarting.
+
+use std::{
+    collections::BTreeMap,
+    fs,
+    path::{Path, PathBuf},
+};
+
+use anyhow::{bail, Context, Error};
+use helix_core::{Position, Range, RopeSlice, Selection};
+use serde::{Deserialize, Serialize};
+
+use crate::{
+    editor::{Action, ExplorerSort},
+    tree::Layout,
+    view::{JumpList, View},
+    Editor, ViewId,
+};
+
+#[derive(Debug, Default, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct Session {
+    pub tabs: Vec<TabSession>,
+    /// Index of the active tab page.
+    pub active_tab: usize,
+    /// Paths of all the buffers, including the ones not shown in any window.
+    pub buffers: Vec<PathBuf>,
+    /// Paths of the pinned buffers.
+    #[serde(default)]
+    pub pinned: Vec<PathBuf>,
+    /// The file explorer, if it was open. Filled in by the user interface, which owns it.
+    pub explorer: Option<ExplorerSession>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct TabSession {
+    pub layout: WindowLayout,
+    /// Index of the focused window, in the order the windows appear in the layout.
+    pub focus: usize,
+    pub explorer_root: Option<PathBuf>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub enum WindowLayout {
+    Window(WindowSession),
+    /// Windows split along `layout`, with the share of the space each of them takes.
+    Split {
+        layout: Layout,
+        children: Vec<(f64, WindowLayout)>,
+    },
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct WindowSession {
+    /// `None` for a scratch buffer, which is restored empty.
+    pub path: Option<PathBuf>,
+    pub selection: SelectionSession,
+    /// The first line and column on screen.
+    pub offset: (usize, usize),
+    pub jumps: Vec<(PathBuf, SelectionSession)>,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct SelectionSession {
+    /// The anchor and head of each range.
+    pub ranges: Vec<(usize, usize)>,
+    pub primary: usize,
+}
+
+#[derive(Debug, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
+pub struct ExplorerSession {
+    pub root: PathBuf,
+    /// The expanded directories, parents before their children.
+    pub expanded: Vec<PathBuf>,
+    /// The directories ordered otherwise than the `sort` of the config, with their order.
+    #[serde(default)]
+    pub sorts: BTreeMap<PathBuf, ExplorerSort>,
+}
+
+impl SelectionSession {
+    fn new(selection: &Selection) -> Self {
+        Self {
+            ranges: selection
+                .iter()
+                .map(|range| (range.anchor, range.head))
+                .collect(),
+            primary: selection.primary_index(),
+        }
+    }
+
+    /// The selection in `text`, which may have changed since the session was saved.
+    fn selection(&self, text: RopeSlice) -> Selection {
+        if self.ranges.is_empty() {
+            return Selection::point(0);
+        }
+        let len = text.len_chars();
+        let ranges = self
+            .ranges
+            .iter()
+            .map(|&(anchor, head)| Range::new(anchor.min(len), head.min(len)))
+            .collect();
+        Selection::new(ranges, self.primary.min(self.ranges.len() - 1)).ensure_invariants(text)
+    }
+}
+
+impl WindowLayout {
+    fn first_window(&self) -> &WindowSession {
+        match self {
+            Self::Window(window) => window,
+            Self::Split { children, .. } => children[0].1.first_window(),
+        }
+    }
+}
+
+/// The file the session of the working directory `dir` is saved to when no other is given.
+pub fn session_file(dir: &Path) -> PathBuf {
+    // like the undo files of vim, the path itself with its separators escaped
+    let name: String = dir
+        .to_string_lossy()
+        .chars()
+        .map(|ch| {
+            if std::path::is_separator(ch) || ch == ':' {
+                '%'
+            } else {
+                ch
+            }
+        })
+        .collect();
+    helix_loader::cache_dir()
+        .join("sessions")
+        .join(format!("{}.json", name))
+}
+
+impl Session {
+    /// Captures the session of `editor`, without the file explorer.
+    pub fn capture(editor: &Editor) -> Self {
+        let tree = &editor.tree;
+        let tabs = tree
+            .tabs()
+            .iter()
+            .enumerate()
+            .filter_map(|(index, tab)| {
+                let mut windows = Vec::new();
+                let layout = capture_layout(editor, tab.root(), &mut windows)?;
+                let focus = tree.tab_focus(index);
+                Some(TabSession {
+                    layout,
+                    focus: windows.iter().position(|&id| id == focus).unwrap_or(0),
+                    explorer_root: tab.explorer_root.clone(),
+                })
+            })
+            .collect();
+        Self {
+            tabs,
+            active_tab: tree.active_tab(),
+            buffers: editor
+                .documents()
+                .filter_map(|doc| doc.path().cloned())
+                .collect(),
+            pinned: editor
+                .documents()
+                .filter(|doc| doc.is_pinned())
+                .filter_map(|doc| doc.path().cloned())
+                .collect(),
+            explorer: None,
+        }
+    }
+
+    pub fn load(path: &Path) -> Result<Self, Error> {
+        let json = fs::read_to_string(path)
+            .with_context(|| format!("failed to read session '{}'", path.display()))?;
+        serde_json::from_str(&json)
+            .with_context(|| format!("failed to parse session '{}'", path.display()))
+    }
+
+    pub fn save(&self, path: &Path) -> Result<(), Error> {
+        if let Some(dir) = path.parent() {
+            fs::create_dir_all(dir)?;
+        }
+        fs::write(path, serde_json::to_string_pretty(self)?)
+            .with_context(|| format!("failed to write session '{}'", path.display()))
+    }
+
+    /// Replaces the tab pages of `editor` with the ones of the session and opens its
+    /// buffers. Files which no longer exist are replaced with scratch buffers.
+    pub fn restore(&self, editor: &mut Editor) -> Result<(), Error> {
+        if self.tabs.is_empty() {
+            bail!("the session has no windows");
+        }
+        let old_tabs: Vec<_> = editor.tree.tabs().iter().map(|tab| tab.id).collect();
+
+        let mut windows = Vec::new();
+        for tab in &self.tabs {
+            editor.tree.new_tab();
+            editor.tree.tab_mut().explorer_root = tab.explorer_root.clone();
+            let start = windows.len();
+            open_window(editor, tab.layout.first_window(), Action::VerticalSplit);
+            restore_layout(editor, &tab.layout, &mut windows);
+            if let Some(&(view_id, _)) = windows[start..].get(tab.focus) {
+                editor.tree.focus = view_id;
+            }
+            editor.tree.recalculate();
+        }
+
+        for path in &self.buffers {
+            if path.exists() && editor.document_by_path(path).is_none() {
+                if let Err(err) = editor.open(path, Action::Load) {
+                    log::warn!("failed to restore '{}': {}", path.display(), err);
+                }
+            }
+        }
+        for path in &self.pinned {
+            if let Some(doc) = editor.document_by_path_mut(path) {
+                doc.set_pinned(true);
+            }
+        }
+        // the jumps may lead to any of the buffers
+        for (view_id, window) in windows {
+            restore_window(editor, view_id, window);
+        }
+
+        for id in old_tabs {
+            if let Some(index) = editor.tree.tabs().iter().position(|tab| tab.id == id) {
+                editor.close_tab(index);
+            }
+        }
+        editor.switch_tab(self.active_tab.min(editor.tree.tabs().len() - 1));
+        Ok(())
+    }
+}
+
+/// Captures the layout under the node `id`, collecting its windows in order. Containers
+/// with a single child are left out, empty ones return `None`.
+fn capture_layout(editor: &Editor, id: ViewId, windows: &mut Vec<ViewId>) -> Option<WindowLayout> {
+    let tree = &editor.tree;
+    let (layout, children) = match tree.children(id) {
+        Some(container) => container,
+        None => {
+            windows.push(id);
+            return Some(WindowLayout::Window(capture_window(editor, tree.get(id))));
+        }
+    };
+    let mut children: Vec<_> = children
+        .iter()
+        .filter_map(|&child| {
+            let layout = capture_layout(editor, child, windows)?;
+            Some((tree.weight(child), layout))
+        })
+        .collect();
+    match children.len() {
+        0 => None,
+        1 => children.pop().map(|(_, layout)| layout),
+        _ => Some(WindowLayout::Split { layout, children }),
+    }
+}
+
+fn capture_window(editor: &Editor, view: &View) -> WindowSession {
+    let doc = &editor.documents[&view.doc];
+    let jumps = view
+        .jumps
+        .iter()
+        .filter_map(|(doc_id, selection)| {
+            let path = editor.documents.get(doc_id)?.path()?;
+            Some((path.clone(), SelectionSession::new(selection)))
+        })
+        .collect();
+    WindowSession {
+        path: doc.path().cloned(),
+        selection: SelectionSession::new(doc.selection(view.id)),
+        offset: (view.offset.row, view.offset.col),
+        jumps,
+    }
+}
+
+/// Opens the buffer of `window` in a new window created with `action`.
+fn open_window(editor: &mut Editor, window: &WindowSession, action: Action) {
+    if let Some(path) = window.path.as_ref().filter(|path| path.exists()) {
+        match editor.open(path, action) {
+            Ok(_) => return,
+            Err(err) => log::warn!("failed to restore '{}': {}", path.display(), err),
+        }
+    }
+    editor.new_file(action);
+}
+
+/// Splits the focused window, which shows the first window of `layout`, into the rest
+/// of the layout. The windows are collected in order, with their session.
+fn restore_layout<'a>(
+    editor: &mut Editor,
+    layout: &'a WindowLayout,
+    windows: &mut Vec<(ViewId, &'a WindowSession)>,
+) {
+    let (layout, children) = match layout {
+        WindowLayout::Window(window) => {
+            windows.push((editor.tree.focus, window));
+            return;
+        }
+        WindowLayout::Split { layout, children } => (layout, children),
+    };
+    let action = match layout {
+        Layout::Horizontal => Action::HorizontalSplit,
+        Layout::Vertical => Action::VerticalSplit,
+    };
+    // the siblings first, each split is inserted after the focused window
+    let mut ids = vec![editor.tree.focus];
+    for (_, child) in &children[1..] {
+        open_window(editor, child.first_window(), action);
+        ids.push(editor.tree.focus);
+    }
+    for ((weight, child), id) in children.iter().zip(ids) {
+        // splitting the window again passes its weight on to the new container
+        editor.tree.set_weight(id, *weight);
+        editor.tree.focus = id;
+        restore_layout(editor, child, windows);
+    }
+}
+
+fn restore_window(editor: &mut Editor, view_id: ViewId, window: &WindowSession) {
+    let jumps: Vec<_> = window
+        .jumps
+        .iter()
+        .filter_map(|(path, selection)| {
+            let doc = editor.document_by_path(path)?;
+            Some((doc.id(), selection.selection(doc.text().slice(..))))
+        })
+        .collect();
+
+    let view = editor.tree.get_mut(view_id);
+    let doc = editor.documents.get_mut(&view.doc).unwrap();
+    let text = doc.text().slice(..);
+    let selection = window.selection.selection(text);
+    let row = window.offset.0.min(text.len_lines().saturating_sub(1));
+    view.offset = Position::new(row, window.offset.1);
+
+    let mut jumps = jumps.into_iter();
+    if let Some(first) = jumps.next() {
+        view.jumps = JumpList::new(first);
+        for jump in jumps {
+            view.jumps.push(jump);
+        }
+    }
+    doc.set_selection(view_id, selection);
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+    use crate::{editor::Config, graphics::Rect, theme};
+    use arc_swap::ArcSwap;
+    use helix_core::{syntax, Rope};
+    use std::sync::Arc;
+
+    fn new_editor() -> Editor {
+        let syn_loader = syntax::Loader::new(syntax::Configuration {
+            language: Vec::new(),
+        });
+        Editor::new(
+            Rect::new(0, 0, 120, 40),
+            Arc::new(theme::Loader::new("", "")),
+            Arc::new(syn_loader),
+            Box::new(ArcSwap::from_pointee(Config::default())),
+        )
+    }
+
+    fn files(names: &[&str]) -> (tempfile::TempDir, Vec<PathBuf>) {
+        let dir = tempfile::tempdir().unwrap();
+        let paths = names
+            .iter()
+            .map(|name| {
+                let path = dir.path().canonicalize().unwrap().join(name);
+                fs::write(&path, "one\ntwo\nthree\n").unwrap();
+                path
+            })
+            .collect();
+        (dir, paths)
+    }
+
+    fn window(path: &Path, jumps: Vec<(PathBuf, SelectionSession)>) -> WindowSession {
+        WindowSession {
+            path: Some(path.to_path_buf()),
+            selection: SelectionSession {
+                ranges: vec![(0, 0)],
+                primary: 0,
+            },
+            offset: (0, 0),
+            jumps,
+        }
+    }
+
+    #[tokio::test]
+    async fn layout_round_trip() {
+        let (dir, paths) = files(&["a", "b", "c"]);
+        let mut editor = new_editor();
+        editor.open(&paths[0], Action::VerticalSplit).unwrap();
+        editor.open(&paths[1], Action::VerticalSplit).unwrap();
+        let (view, doc) = current!(editor);
+        doc.set_selection(view.id, Selection::single(2, 6));
+        editor.open(&paths[2], Action::HorizontalSplit).unwrap();
+
+        let file = dir.path().join("session.json");
+        let session = Session::capture(&editor);
+        session.save(&file).unwrap();
+        let mut restored = new_editor();
+        Session::load(&file)
+            .unwrap()
+            .restore(&mut restored)
+            .unwrap();
+
+        let value = |session: &Session| serde_json::to_value(session).unwrap();
+        assert!(matches!(
+            &session.tabs[0].layout,
+            WindowLayout::Split { children, .. } if children.len() == 2
+        ));
+        assert_eq!(value(&Session::capture(&restored)), value(&session));
+    }
+
+    #[test]
+    fn selection_clamped() {
+        let text = Rope::from("short\n");
+        let session = SelectionSession {
+            ranges: vec![(2, 40), (50, 60)],
+            primary: 5,
+        };
+        let selection = session.selection(text.slice(..));
+        assert!(selection.iter().all(|range| range.to() <= text.len_chars()));
+        assert_eq!(selection.primary_index(), selection.len() - 1);
+
+        let empty = SelectionSession {
+            ranges: Vec::new(),
+            primary: 0,
+        };
+        assert_eq!(empty.selection(text.slice(..)), Selection::point(0));
+    }
+
+    #[tokio::test]
+    async fn jumps_to_unrestored_files_dropped() {
+        let (_dir, paths) = files(&["a", "b"]);
+        let missing = paths[0].with_file_name("missing");
+        let jump = |path: &Path| {
+            let selection = SelectionSession {
+                ranges: vec![(4, 100)],
+                primary: 0,
+            };
+            (path.to_path_buf(), selection)
+        };
+        // `b` exists but is not one of the buffers
+        let jumps = vec![jump(&missing), jump(&paths[1]), jump(&paths[0])];
+        let mut window = window(&paths[0], jumps);
+        window.offset = (10, 0);
+        let session = Session {
+            tabs: vec![TabSession {
+                layout: WindowLayout::Window(window),
+                focus: 0,
+                explorer_root: None,
+            }],
+            buffers: vec![paths[0].clone()],
+            ..Session::default()
+        };
+        let mut editor = new_editor();
+        session.restore(&mut editor).unwrap();
+
+        let (view, doc) = current_ref!(editor);
+        assert_eq!(doc.path(), Some(&paths[0]));
+        assert_eq!(view.offset.row, doc.text().len_lines() - 1);
+        assert!(editor.document_by_path(&paths[1]).is_none());
+        let jumps: Vec<_> = view.jumps.iter().collect();
+        assert_eq!(jumps.len(), 1);
+        assert_eq!(jumps[0].0, doc.id());
+        assert_eq!(jumps[0].1.primary().to(), doc.text().len_chars());
+    }
+}
@@ -0,0 +1,264 @@
+//! Snapshots of the buffers with unsaved changes, written to the cache directory
+//! periodically and removed when the editor exits, so that the changes can be recovered
+//! after a crash or a lost connection.
+
+use std::{
+    collections::HashMap,
+    fs,
+    io::Write,
+    path::{Path, PathBuf},
+    time::SystemTime,
+};
+
+use anyhow::{Context, Error};
+use serde::{Deserialize, Serialize};
+use tokio::task::JoinHandle;
+
+use crate::{Document, DocumentId};
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Snapshot {
+    /// The file of the buffer, `None` for scratch buffers.
+    pub path: Option<PathBuf>,
+    pub text: String,
+    pub time: SystemTime,
+    /// The process of the editor which wrote the snapshot.
+    pub pid: u32,
+}
+
+fn swap_dir() -> PathBuf {
+    helix_loader::cache_dir().join("swap")
+}
+
+/// The name of the swap file of a buffer: the process of the editor and the path of the
+/// buffer with the separators replaced, or the id of the document for scratch buffers.
+fn swap_file_name(doc: &Document) -> String {
+    let name = match doc.path() {
+        Some(path) => path
+            .to_string_lossy()
+            .replace(|ch: char| matches!(ch, '/' | '\\' | ':'), "%"),
+        None => format!("scratch-{}", doc.id()),
+    };
+    format!("{}-{}.json", std::process::id(), name)
+}
+
+/// The swap files written by this editor, by document, with the version of the document
+/// they hold.
+#[derive(Debug, Default)]
+pub struct Swaps {
+    files: HashMap<DocumentId, (PathBuf, i32)>,
+    /// The files being written and removed in the background.
+    writing: Option<JoinHandle<()>>,
+}
+
+impl Swaps {
+    /// Writes snapshots of the modified buffers changed since the last update, and removes
+    /// the ones of the buffers written or closed since, in a blocking task. Nothing is
+    /// done while the files of the last update are still being written. It has to be
+    /// called within the tokio runtime.
+    pub fn update<'a>(&mut self, docs: impl Iterator<Item = &'a Document>) {
+        if matches!(&self.writing, Some(writing) if !writing.is_finished()) {
+            return;
+        }
+        let mut removed = Vec::new();
+        let mut modified = HashMap::new();
+        // large files would take too long to write again and again
+        for doc in docs.filter(|doc| doc.is_modified() && !doc.is_large_file()) {
+            modified.insert(doc.id(), doc);
+        }
+        self.files.retain(|doc_id, (file, _)| {
+            let keep = match modified.get(doc_id) {
+                // a buffer getting another path moves to another file
+                Some(doc) => file.file_name() == Some(swap_file_name(doc).as_ref()),
+                None => false,
+            };
+            if !keep {
+                removed.push(file.clone());
+            }
+            keep
+        });
+
+        let dir = swap_dir();
+        let mut written = Vec::new();
+        for (doc_id, doc) in modified {
+            if matches!(self.files.get(&doc_id), Some((_, version)) if *version == doc.version()) {
+                continue;
+            }
+            let file = dir.join(swap_file_name(doc));
+            // the rope is cheap to clone, turning it into a string is left to the task
+            written.push((file.clone(), doc.path().cloned(), doc.text().clone()));
+            self.files.insert(doc_id, (file, doc.version()));
+        }
+        if removed.is_empty() && written.is_empty() {
+            return;
+        }
+
+        self.writing = Some(tokio::task::spawn_blocking(move || {
+            for file in removed {
+                remove_file(&file);
+            }
+            if !written.is_empty() {
+                if let Err(err) = fs::create_dir_all(&dir) {
+                    log::error!("Error writing swap files: {}", err);
+                    return;
+                }
+            }
+            for (file, path, text) in written {
+                let snapshot = Snapshot {
+                    path,
+                    text: text.to_string(),
+                    time: SystemTime::now(),
+                    pid: std::process::id(),
+                };
+                if let Err(err) = write_snapshot(&file, &snapshot) {
+                    log::error!("Error writing swap files: {:#}", err);
+                }
+            }
+        }));
+    }
+
+    /// Removes the swap files, once the editor exits and the last ones are written.
+    pub async fn clear(&mut self) {
+        if let Some(writing) = self.writing.take() {
+            let _ = writing.await;
+        }
+        for (_, (file, _)) in self.files.drain() {
+            remove_file(&file);
+        }
+    }
+}
+
+/// Writes `snapshot` to `file`, which only the user may read since the buffer may hold
+/// secrets.
+fn write_snapshot(file: &Path, snapshot: &Snapshot) -> Result<(), Error> {
+    let mut options = fs::OpenOptions::new();
+    options.write(true).create(true).truncate(true);
+    #[cfg(unix)]
+    {
+        use std::os::unix::fs::OpenOptionsExt;
+        options.mode(0o600);
+    }
+    options
+        .open(file)
+        .and_then(|mut out| out.write_all(serde_json::to_string(snapshot)?.as_bytes()))
+        .with_context(|| format!("failed to write '{}'", file.display()))
+}
+
+fn remove_file(file: &Path) {
+    if let Err(err) = fs::remove_file(file) {
+        log::warn!("failed to remove swap file '{}': {}", file.display(), err);
+    }
+}
+
+/// The snapshots left by editors which did not exit, with their swap file, the most
+/// recent first. The ones of editors still running are left out.
+pub fn leftover_snapshots() -> Vec<(PathBuf, Snapshot)> {
+    snapshots_in(&swap_dir(), process_running)
+}
+
+/// The snapshots in `dir` of the editors not `running`, the most recent first.
+fn snapshots_in(dir: &Path, running: impl Fn(u32) -> bool) -> Vec<(PathBuf, Snapshot)> {
+    let entries = match fs::read_dir(dir) {
+        Ok(entries) => entries,
+        Err(_) => return Vec::new(),
+    };
+    let mut snapshots: Vec<_> = entries
+        .filter_map(|entry| {
+            let file = entry.ok()?.path();
+            let snapshot: Snapshot = serde_json::from_str(&fs::read_to_string(&file).ok()?).ok()?;
+            (!running(snapshot.pid)).then(|| (file, snapshot))
+        })
+        .collect();
+    snapshots.sort_by(|(_, a), (_, b)| b.time.cmp(&a.time));
+    snapshots
+}
+
+/// Removes a snapshot left by another editor, once it is recovered or discarded.
+pub fn remove_snapshot(file: &Path) -> Result<(), Error> {
+    fs::remove_file(file).with_context(|| format!("failed to remove '{}'", file.display()))
+}
+
+/// Whether the process `pid` exists. A signal of 0 only checks that it could be sent,
+/// which fails with `EPERM` for the processes of other users.
+#[cfg(unix)]
+fn process_running(pid: u32) -> bool {
+    // 0 and negative pids stand for process groups
+    let pid = match libc::pid_t::try_from(pid) {
+        Ok(pid) if pid > 0 => pid,
+        _ => return false,
+    };
+    pid as u32 == std::process::id()
+        || unsafe { libc::kill(pid, 0) } == 0
+        || std::io::Error::last_os_error().raw_os_error() == Some(libc::EPERM)
+}
+
+/// Whether the process `pid` exists and has not exited yet. The processes which can not
+/// be opened count as running.
+#[cfg(windows)]
+fn process_running(pid: u32) -> bool {
+    use windows_sys::Win32::{
+        Foundation::{CloseHandle, GetLastError, ERROR_ACCESS_DENIED, STILL_ACTIVE},
+        System::Threading::{GetExitCodeProcess, OpenProcess, PROCESS_QUERY_LIMITED_INFORMATION},
+    };
+
+    if pid == std::process::id() {
+        return true;
+    }
+    unsafe {
+        let handle = OpenProcess(PROCESS_QUERY_LIMITED_INFORMATION, 0, pid);
+        if handle == 0 {
+            return GetLastError() == ERROR_ACCESS_DENIED;
+        }
+        let mut code = 0;
+        let running = GetExitCodeProcess(handle, &mut code) == 0 || code == STILL_ACTIVE as u32;
+        CloseHandle(handle);
+        running
+    }
+}
+
+#[cfg(test)]
+mod test {
+    use super::*;
+
+    fn snapshot(pid: u32, secs: u64) -> Snapshot {
+        Snapshot {
+            path: Some(PathBuf::from(format!("/work/{pid}.rs"))),
+            text: format!("fn main() {{}} // {pid}\n"),
+            time: SystemTime::UNIX_EPOCH + std::time::Duration::from_secs(secs),
+            pid,
+        }
+    }
+
+    #[test]
+    fn snapshot_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let file = dir.path().join("1-%work%1.rs.json");
+        let written = Snapshot {
+            path: None,
+            ..snapshot(1, 10)
+        };
+        write_snapshot(&file, &written).unwrap();
+        assert_eq!(snapshots_in(dir.path(), |_| false), vec![(file, written)]);
+    }
+
+    #[test]
+    fn running_editors_left_out() {
+        let dir = tempfile::tempdir().unwrap();
+        for (pid, secs) in [(1, 10), (2, 30), (3, 20)] {
+            let file = dir.path().join(format!("{pid}-%work%{pid}.rs.json"));
+            write_snapshot(&file, &snapshot(pid, secs)).unwrap();
+        }
+        fs::write(dir.path().join("4-broken.json"), "{").unwrap();
+        let pids: Vec<_> = snapshots_in(dir.path(), |pid| pid == 2)
+            .into_iter()
+            .map(|(_, snapshot)| snapshot.pid)
+            .collect();
+        assert_eq!(pids, vec![3, 1]);
+    }
+
+    #[test]
+    fn own_process_running() {
+        assert!(process_running(std::process::id()));
+        assert!(!process_running(0));
+    }
+}
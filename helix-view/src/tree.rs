@@ -1,5 +1,7 @@
 use crate::{graphics::Rect, View, ViewId};
+use serde::{Deserialize, Serialize};
 use slotmap::HopSlotMap;
+use std::path::PathBuf;
 
 // the dimensions are recomputed on window resize/tree change.
 //
@@ -13,14 +15,45 @@ pub struct Tree {
 
     nodes: HopSlotMap<ViewId, Node>,
 
+    /// The tab pages, `root` and `focus` belong to the active one.
+    tabs: Vec<TabPage>,
+    active_tab: usize,
+    next_tab_id: usize,
+
     // used for traversals
     stack: Vec<(ViewId, Rect)>,
 }
 
+/// A window layout of its own. The views of all tab pages share the nodes of the tree,
+/// so their ids stay unique, but only the views of the active tab page are shown.
+#[derive(Debug)]
+pub struct TabPage {
+    /// A unique id, unlike the index which changes when tab pages are closed.
+    pub id: usize,
+    root: ViewId,
+    /// The focused view while the tab page isn't active.
+    focus: ViewId,
+    /// The directory shown by the file explorer in this tab page, the working
+    /// directory when unset.
+    pub explorer_root: Option<PathBuf>,
+}
+
+impl TabPage {
+    /// The container holding the window layout of the tab page.
+    pub fn root(&self) -> ViewId {
+        self.root
+    }
+}
+
+/// Smallest width or height a split can be resized to.
+const MIN_SPLIT_SIZE: u16 = 2;
+
 #[derive(Debug)]
 pub struct Node {
     parent: ViewId,
     content: Content,
+    /// Share of the space of the parent container, relative to the weights of its siblings.
+    weight: f64,
 }
 
 #[derive(Debug)]
@@ -34,6 +67,7 @@ pub fn container(layout: Layout) -> Self {
         Self {
             parent: ViewId::default(),
             content: Content::Container(Box::new(Container::new(layout))),
+            weight: 1.0,
         }
     }
 
@@ -41,11 +75,13 @@ pub fn view(view: View) -> Self {
         Self {
             parent: ViewId::default(),
             content: Content::View(Box::new(view)),
+            weight: 1.0,
         }
     }
 }
 
-#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "kebab-case")]
 pub enum Layout {
     Horizontal,
     Vertical,
@@ -99,15 +135,118 @@ pub fn new(area: Rect) -> Self {
             // fullscreen: false,
             area,
             nodes,
+            tabs: vec![TabPage {
+                id: 0,
+                root,
+                focus: root,
+                explorer_root: None,
+            }],
+            active_tab: 0,
+            next_tab_id: 1,
             stack: Vec::new(),
         }
     }
 
+    /// Returns the root container of the tab page containing the node `index`.
+    fn tab_root(&self, mut index: ViewId) -> ViewId {
+        // roots are their own parent
+        while self.nodes[index].parent != index {
+            index = self.nodes[index].parent;
+        }
+        index
+    }
+
+    pub fn tabs(&self) -> &[TabPage] {
+        &self.tabs
+    }
+
+    /// Index of the active tab page.
+    pub fn active_tab(&self) -> usize {
+        self.active_tab
+    }
+
+    pub fn tab(&self) -> &TabPage {
+        &self.tabs[self.active_tab]
+    }
+
+    pub fn tab_mut(&mut self) -> &mut TabPage {
+        &mut self.tabs[self.active_tab]
+    }
+
+    /// Returns the focused view of the tab page at `index`, or its root when it is empty.
+    pub fn tab_focus(&self, index: usize) -> ViewId {
+        if index == self.active_tab {
+            self.focus
+        } else {
+            self.tabs[index].focus
+        }
+    }
+
+    /// Opens an empty tab page after the active one and switches to it.
+    pub fn new_tab(&mut self) {
+        let root = self.nodes.insert(Node::container(Layout::Vertical));
+        self.nodes[root].parent = root;
+        let tab = TabPage {
+            id: self.next_tab_id,
+            root,
+            focus: root,
+            explorer_root: None,
+        };
+        self.next_tab_id += 1;
+        self.tabs.insert(self.active_tab + 1, tab);
+        self.switch_tab(self.active_tab + 1);
+    }
+
+    pub fn switch_tab(&mut self, index: usize) {
+        self.tabs[self.active_tab].focus = self.focus;
+        self.show_tab(index);
+    }
+
+    fn show_tab(&mut self, index: usize) {
+        self.active_tab = index;
+        self.root = self.tabs[index].root;
+        self.focus = self.tabs[index].focus;
+        if !self.contains(self.focus) || self.try_get(self.focus).is_none() {
+            self.focus = self.traverse().next().map_or(self.root, |(id, _)| id);
+        }
+        self.recalculate();
+    }
+
+    /// Closes the tab page at `index` with all of its views, unless it is the last one.
+    /// Returns the ids of the closed views.
+    pub fn close_tab(&mut self, index: usize) -> Vec<ViewId> {
+        if self.tabs.len() == 1 {
+            return Vec::new();
+        }
+        let root = self.tabs[index].root;
+        let nodes: Vec<_> = self
+            .nodes
+            .keys()
+            .filter(|&key| self.tab_root(key) == root)
+            .collect();
+        let views = nodes
+            .iter()
+            .copied()
+            .filter(|&key| self.try_get(key).is_some())
+            .collect();
+        for node in nodes {
+            self.nodes.remove(node);
+        }
+        self.tabs.remove(index);
+        if index == self.active_tab {
+            self.show_tab(index.min(self.tabs.len() - 1));
+        } else if index < self.active_tab {
+            self.active_tab -= 1;
+        }
+        views
+    }
+
     pub fn insert(&mut self, view: View) -> ViewId {
         let focus = self.focus;
         let parent = self.nodes[focus].parent;
         let mut node = Node::view(view);
         node.parent = parent;
+        node.weight = self.mean_child_weight(parent);
         let node = self.nodes.insert(node);
         self.get_mut(node).id = node;
 
@@ -145,7 +284,8 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
         let focus = self.focus;
         let parent = self.nodes[focus].parent;
 
-        let node = Node::view(view);
+        let mut node = Node::view(view);
+        node.weight = self.mean_child_weight(parent);
         let node = self.nodes.insert(node);
         self.get_mut(node).id = node;
 
@@ -173,6 +313,9 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
         } else {
             let mut split = Node::container(layout);
             split.parent = parent;
+            // the new container takes the place of the focused view
+            split.weight = std::mem::replace(&mut self.nodes[focus].weight, 1.0);
+            self.nodes[node].weight = 1.0;
             let split = self.nodes.insert(split);
 
             let container = match &mut self.nodes[split] {
@@ -216,11 +359,17 @@ pub fn split(&mut self, view: View, layout: Layout) -> ViewId {
 
     pub fn remove(&mut self, index: ViewId) {
         let mut stack = Vec::new();
+        let tab_root = self.tab_root(index);
 
         if self.focus == index {
             // focus on something else
             self.focus = self.prev();
         }
+        for tab in &mut self.tabs {
+            if tab.focus == index {
+                tab.focus = tab.root;
+            }
+        }
 
         stack.push(index);
 
@@ -234,7 +383,7 @@ pub fn remove(&mut self, index: ViewId) {
                 if let Some(pos) = container.children.iter().position(|&child| child == index) {
                     container.children.remove(pos);
                     // TODO: if container now only has one child, remove it and place child in parent
-                    if container.children.is_empty() && parent_id != self.root {
+                    if container.children.is_empty() && self.nodes[parent_id].parent != parent_id {
                         // if container now empty, remove it
                         stack.push(parent_id);
                     }
@@ -243,10 +392,36 @@ pub fn remove(&mut self, index: ViewId) {
             self.nodes.remove(index);
         }
 
+        // a tab page is closed with its last view, unless it is the last tab page
+        let is_empty = match &self.nodes[tab_root].content {
+            Content::Container(container) => container.children.is_empty(),
+            Content::View(_) => false,
+        };
+        if is_empty && self.tabs.len() > 1 {
+            if let Some(tab) = self.tabs.iter().position(|tab| tab.root == tab_root) {
+                self.close_tab(tab);
+                return;
+            }
+        }
+
         self.recalculate()
     }
 
+    /// The views of the active tab page.
     pub fn views(&self) -> impl Iterator<Item = (&View, bool)> {
+        self.all_views()
+            .filter(move |(view, _)| self.tab_root(view.id) == self.root)
+    }
+
+    /// The views of the active tab page.
+    pub fn views_mut(&mut self) -> impl Iterator<Item = (&mut View, bool)> {
+        let active: Vec<_> = self.views().map(|(view, _)| view.id).collect();
+        self.all_views_mut()
+            .filter(move |(view, _)| active.contains(&view.id))
+    }
+
+    /// The views of all tab pages.
+    pub fn all_views(&self) -> impl Iterator<Item = (&View, bool)> {
         let focus = self.focus;
         self.nodes.iter().filter_map(move |(key, node)| match node {
             Node {
@@ -257,7 +432,8 @@ pub fn views(&self) -> impl Iterator<Item = (&View, bool)> {
         })
     }
 
-    pub fn views_mut(&mut self) -> impl Iterator<Item = (&mut View, bool)> {
+    /// The views of all tab pages.
+    pub fn all_views_mut(&mut self) -> impl Iterator<Item = (&mut View, bool)> {
         let focus = self.focus;
         self.nodes
             .iter_mut()
@@ -306,6 +482,25 @@ pub fn get_mut(&mut self, index: ViewId) -> &mut View {
         }
     }
 
+    /// The layout and children of a container, `None` if the node is a [View].
+    pub fn children(&self, index: ViewId) -> Option<(Layout, &[ViewId])> {
+        match &self.nodes[index].content {
+            Content::Container(container) => Some((container.layout, &container.children)),
+            Content::View(_) => None,
+        }
+    }
+
+    /// Share of the space of the parent container taken by a node.
+    pub fn weight(&self, index: ViewId) -> f64 {
+        self.nodes[index].weight
+    }
+
+    /// Sets the share of the space of the parent container taken by a node. The areas of
+    /// the views are updated by the next [Self::recalculate].
+    pub fn set_weight(&mut self, index: ViewId, weight: f64) {
+        self.nodes[index].weight = weight;
+    }
+
     /// Check if tree contains a [Node] with a given index.
     pub fn contains(&self, index: ViewId) -> bool {
         self.nodes.contains_key(index)
@@ -346,6 +541,7 @@ pub fn recalculate(&mut self) {
         // b) node is container, calculate areas for each child and push them on the stack
 
         while let Some((key, area)) = self.stack.pop() {
+            let weights = self.child_weights(key);
             let node = &mut self.nodes[key];
 
             match &mut node.content {
@@ -361,11 +557,12 @@ pub fn recalculate(&mut self) {
                         Layout::Horizontal => {
                             let len = container.children.len();
 
-                            let height = area.height / len as u16;
+                            let heights = split_sizes(area.height, &weights);
 
                             let mut child_y = area.y;
 
                             for (i, child) in container.children.iter().enumerate() {
+                                let height = heights[i];
                                 let mut area = Rect::new(
                                     container.area.x,
                                     child_y,
@@ -386,7 +583,7 @@ pub fn recalculate(&mut self) {
                         Layout::Vertical => {
                             let len = container.children.len();
 
-                            let width = area.width / len as u16;
+                            let widths = split_sizes(area.width, &weights);
 
                             let inner_gap = 1u16;
                             // let total_gap = inner_gap * (len as u16 - 1);
@@ -394,6 +591,7 @@ pub fn recalculate(&mut self) {
                             let mut child_x = area.x;
 
                             for (i, child) in container.children.iter().enumerate() {
+                                let width = widths[i];
                                 let mut area = Rect::new(
                                     child_x,
                                     container.area.y,
@@ -417,6 +615,137 @@ pub fn recalculate(&mut self) {
         }
     }
 
+    /// The weights of the children of a container, empty for a view.
+    fn child_weights(&self, key: ViewId) -> Vec<f64> {
+        match &self.nodes[key].content {
+            Content::Container(container) => container
+                .children
+                .iter()
+                .map(|child| self.nodes[*child].weight)
+                .collect(),
+            Content::View(_) => Vec::new(),
+        }
+    }
+
+    /// The weight of a new child of a container, the mean weight of the existing children
+    /// so that a new split doesn't end up tiny or huge next to resized ones.
+    fn mean_child_weight(&self, key: ViewId) -> f64 {
+        let weights = self.child_weights(key);
+        if weights.is_empty() {
+            return 1.0;
+        }
+        weights.iter().sum::<f64>() / weights.len() as f64
+    }
+
+    /// Finds the closest container of `id` with the given layout that has more than one child,
+    /// returns it along with the position of the child containing `id`.
+    fn split_in_layout(&self, id: ViewId, layout: Layout) -> Option<(ViewId, usize)> {
+        let mut node = id;
+        loop {
+            let parent = self.nodes[node].parent;
+            // we got to the root of the tree
+            if parent == node {
+                return None;
+            }
+            if let Content::Container(container) = &self.nodes[parent].content {
+                if container.layout == layout && container.children.len() > 1 {
+                    let index = container.children.iter().position(|&child| child == node)?;
+                    return Some((parent, index));
+                }
+            }
+            node = parent;
+        }
+    }
+
+    /// Grows the split containing `id` by `amount` columns of a [Layout::Vertical] container
+    /// or rows of a [Layout::Horizontal] one, a negative `amount` shrinks it.
+    /// Returns false if `id` isn't in a split with that layout.
+    pub fn resize_split(&mut self, id: ViewId, layout: Layout, amount: i32) -> bool {
+        let (container, index) = match self.split_in_layout(id, layout) {
+            Some(split) => split,
+            None => return false,
+        };
+        let (_, sizes) = self.split_sizes_of(container);
+        let size = (sizes[index] as i32 + amount).clamp(0, u16::MAX as i32) as u16;
+        self.set_child_size(container, index, size);
+        true
+    }
+
+    /// Sets the width (for [Layout::Vertical]) or height (for [Layout::Horizontal]) of the
+    /// split containing `id` to `size`, taking the space from or giving it to the closest
+    /// splits next to it. Returns false if `id` isn't in a split with that layout.
+    pub fn set_split_size(&mut self, id: ViewId, layout: Layout, size: u16) -> bool {
+        let (container, index) = match self.split_in_layout(id, layout) {
+            Some(split) => split,
+            None => return false,
+        };
+        let (layout, sizes) = self.split_sizes_of(container);
+        // the borders between vertical splits are taken from the last one
+        let size = match layout {
+            Layout::Vertical if index == sizes.len() - 1 => size + (sizes.len() as u16 - 1),
+            _ => size,
+        };
+        self.set_child_size(container, index, size);
+        true
+    }
+
+    /// Gives all splits the same share of their container.
+    pub fn equalize_splits(&mut self) {
+        for (_, node) in self.nodes.iter_mut() {
+            node.weight = 1.0;
+        }
+        self.recalculate();
+    }
+
+    /// The layout of a container and the space each of its children takes along it.
+    fn split_sizes_of(&self, key: ViewId) -> (Layout, Vec<u16>) {
+        let container = match &self.nodes[key].content {
+            Content::Container(container) => container,
+            Content::View(_) => unreachable!(),
+        };
+        let extent = match container.layout {
+            Layout::Horizontal => container.area.height,
+            Layout::Vertical => container.area.width,
+        };
+        (
+            container.layout,
+            split_sizes(extent, &self.child_weights(key)),
+        )
+    }
+
+    fn set_child_size(&mut self, key: ViewId, index: usize, size: u16) {
+        let (_, mut sizes) = self.split_sizes_of(key);
+        let extent: u16 = sizes.iter().sum();
+        let max = extent.saturating_sub(MIN_SPLIT_SIZE * (sizes.len() as u16 - 1));
+        let size = size.min(max).max(MIN_SPLIT_SIZE);
+
+        // take the space from (or give it to) the closest siblings first, preferring the
+        // ones after the resized split
+        let mut siblings: Vec<_> = (0..sizes.len()).filter(|&i| i != index).collect();
+        siblings.sort_by_key(|&i| (i.abs_diff(index), i < index));
+        let mut missing = size as i32 - sizes[index] as i32;
+        for i in siblings {
+            if missing < 0 {
+                sizes[i] += (-missing) as u16;
+                missing = 0;
+            } else {
+                let taken = sizes[i].saturating_sub(MIN_SPLIT_SIZE).min(missing as u16);
+                sizes[i] -= taken;
+                missing -= taken as i32;
+            }
+        }
+        sizes[index] = (size as i32 - missing) as u16;
+
+        let children = match &self.nodes[key].content {
+            Content::Container(container) => container.children.clone(),
+            Content::View(_) => unreachable!(),
+        };
+        for (child, size) in children.into_iter().zip(sizes) {
+            self.nodes[child].weight = size as f64;
+        }
+        self.recalculate();
+    }
+
     pub fn traverse(&self) -> Traverse {
         Traverse::new(self)
     }
@@ -572,6 +901,31 @@ pub fn transpose(&mut self) {
         }
     }
 
+    /// Moves every split of the container of the focused view to the next position, the
+    /// last one becoming the first, or to the previous position if `forward` is false.
+    /// The sizes of the splits stay where they are.
+    pub fn rotate_splits(&mut self, forward: bool) {
+        let parent = self.nodes[self.focus].parent;
+        let weights = self.child_weights(parent);
+        let container = match &mut self.nodes[parent].content {
+            Content::Container(container) => container,
+            Content::View(_) => unreachable!(),
+        };
+        if container.children.len() < 2 {
+            return;
+        }
+        if forward {
+            container.children.rotate_right(1);
+        } else {
+            container.children.rotate_left(1);
+        }
+        let children = container.children.clone();
+        for (child, weight) in children.into_iter().zip(weights) {
+            self.nodes[child].weight = weight;
+        }
+        self.recalculate();
+    }
+
     pub fn swap_split_in_direction(&mut self, direction: Direction) -> Option<()> {
         let focus = self.focus;
         let target = self.find_split_in_direction(focus, direction)?;
@@ -597,6 +951,8 @@ pub fn swap_split_in_direction(&mut self, direction: Direction) -> Option<()> {
                     parent.children[target_pos] = focus_view.id;
                     // swap area so that views rendered at the correct location
                     std::mem::swap(&mut focus_view.area, &mut target_view.area);
+                    // and the weights so the sizes of the splits stay where they are
+                    std::mem::swap(&mut focus.weight, &mut target.weight);
 
                     Some(())
                 }
@@ -634,6 +990,8 @@ pub fn swap_split_in_direction(&mut self, direction: Direction) -> Option<()> {
                     std::mem::swap(&mut focus.parent, &mut target.parent);
                     // swap area so that views rendered at the correct location
                     std::mem::swap(&mut focus_view.area, &mut target_view.area);
+                    // and the weights so the sizes of the splits stay where they are
+                    std::mem::swap(&mut focus.weight, &mut target.weight);
 
                     Some(())
                 }
@@ -647,6 +1005,20 @@ pub fn area(&self) -> Rect {
     }
 }
 
+/// Splits `extent` between children with the given weights, the last child takes the
+/// remaining space because we can get uneven space from rounding.
+fn split_sizes(extent: u16, weights: &[f64]) -> Vec<u16> {
+    let total: f64 = weights.iter().sum();
+    let mut sizes: Vec<u16> = weights
+        .iter()
+        .map(|weight| (extent as f64 * weight / total) as u16)
+        .collect();
+    if let Some((last, rest)) = sizes.split_last_mut() {
+        *last = extent.saturating_sub(rest.iter().sum());
+    }
+    sizes
+}
+
 #[derive(Debug)]
 pub struct Traverse<'a> {
     tree: &'a Tree,
@@ -899,4 +1271,88 @@ fn doc_id(tree: &Tree, view_id: ViewId) -> Option<DocumentId> {
         assert_eq!(doc_id(&tree, l2), Some(doc_r0));
         assert_eq!(doc_id(&tree, r0), Some(doc_l0));
     }
+
+    #[test]
+    fn resize_split() {
+        let mut tree = Tree::new(Rect::new(0, 0, 180, 80));
+        let gutters = vec![GutterType::Diagnostics, GutterType::LineNumbers];
+        let left = tree.insert(View::new(DocumentId::default(), gutters.clone()));
+        let middle = tree.split(
+            View::new(DocumentId::default(), gutters.clone()),
+            Layout::Vertical,
+        );
+        let right = tree.split(View::new(DocumentId::default(), gutters), Layout::Vertical);
+        let widths = |tree: &Tree| [left, middle, right].map(|id| tree.get(id).area.width);
+        assert_eq!(widths(&tree), [60, 60, 58]);
+
+        // the space is taken from the closest split
+        assert!(tree.set_split_size(left, Layout::Vertical, 80));
+        assert_eq!(widths(&tree), [80, 40, 58]);
+        assert!(tree.resize_split(right, Layout::Vertical, 10));
+        assert_eq!(widths(&tree), [80, 30, 68]);
+        // the sizes are kept relative to each other when the area changes
+        tree.resize(Rect::new(0, 0, 90, 80));
+        assert_eq!(widths(&tree), [40, 15, 33]);
+
+        // there's no split in that direction
+        assert!(!tree.resize_split(left, Layout::Horizontal, 10));
+
+        tree.equalize_splits();
+        assert_eq!(widths(&tree), [30, 30, 28]);
+    }
+
+    #[test]
+    fn rotate_splits() {
+        let mut tree = Tree::new(Rect::new(0, 0, 180, 80));
+        let gutters = vec![GutterType::Diagnostics, GutterType::LineNumbers];
+        let left = tree.insert(View::new(DocumentId::default(), gutters.clone()));
+        let middle = tree.split(
+            View::new(DocumentId::default(), gutters.clone()),
+            Layout::Vertical,
+        );
+        let right = tree.split(View::new(DocumentId::default(), gutters), Layout::Vertical);
+        tree.set_split_size(left, Layout::Vertical, 80);
+        let order = |tree: &Tree| tree.traverse().map(|(id, _)| id).collect::<Vec<_>>();
+
+        tree.rotate_splits(true);
+        assert_eq!(order(&tree), vec![right, left, middle]);
+        // the focused view keeps the focus while the sizes stay in place
+        assert_eq!(tree.focus, right);
+        assert_eq!(tree.get(right).area, Rect::new(0, 0, 80, 80));
+
+        tree.rotate_splits(false);
+        tree.rotate_splits(false);
+        assert_eq!(order(&tree), vec![middle, right, left]);
+    }
+
+    #[test]
+    fn tab_pages() {
+        let mut tree = Tree::new(Rect::new(0, 0, 180, 80));
+        let gutters = vec![GutterType::Diagnostics, GutterType::LineNumbers];
+        let first = tree.insert(View::new(DocumentId::default(), gutters.clone()));
+        tree.split(
+            View::new(DocumentId::default(), gutters.clone()),
+            Layout::Vertical,
+        );
+
+        tree.new_tab();
+        assert!(tree.is_empty());
+        let second = tree.insert(View::new(DocumentId::default(), gutters));
+        assert_eq!(tree.views().count(), 1);
+        assert_eq!(tree.all_views().count(), 3);
+        // the view takes the whole area of its tab page
+        assert_eq!(tree.get(second).area, Rect::new(0, 0, 180, 80));
+
+        tree.switch_tab(0);
+        assert_eq!(tree.views().count(), 2);
+        tree.focus = first;
+        tree.switch_tab(1);
+        assert_eq!(tree.focus, second);
+
+        // closing the last view of a tab page closes the tab page
+        tree.remove(second);
+        assert_eq!(tree.tabs().len(), 1);
+        assert_eq!(tree.focus, first);
+        assert_eq!(tree.views().count(), 2);
+    }
 }
@@ -1,17 +1,45 @@
-use crate::{align_view, editor::GutterType, graphics::Rect, Align, Document, DocumentId, ViewId};
+use crate::{
+    align_view,
+    editor::{GutterType, LineNumber, WhitespaceRender},
+    graphics::Rect,
+    Align, Document, DocumentId, ViewId,
+};
 use helix_core::{
-    pos_at_visual_coords, visual_coords_at_pos, Position, RopeSlice, Selection, Transaction,
+    pos_at_visual_coords,
+    unicode::width::UnicodeWidthStr,
+    visual_coords_at_pos,
+    wrap::{soft_wrap_coords_at_pos, soft_wrap_line_height, soft_wrap_pos_at_coords},
+    Position, RopeSlice, Selection, Transaction,
 };
 
 use std::{
     collections::{HashMap, VecDeque},
     fmt,
+    time::{Duration, Instant},
 };
 
 const JUMP_LIST_CAPACITY: usize = 30;
 
+/// Number of document lines drawn in a single row of the minimap,
+/// one for each dot of a braille character.
+pub const MINIMAP_LINES_PER_ROW: usize = 4;
+/// Number of text columns drawn in a single cell of the minimap.
+/// A braille character is two dots wide and each dot covers two columns.
+pub const MINIMAP_COLUMNS_PER_CELL: usize = 4;
+
+/// Jumps of the viewport by at most this many lines are never animated.
+const SMOOTH_SCROLL_MIN_LINES: usize = 1;
+
 type Jump = (DocumentId, Selection);
 
+/// A running smooth scroll of the first visible line from `from` to `to`.
+#[derive(Debug, Clone, Copy)]
+struct ScrollAnimation {
+    from: usize,
+    to: usize,
+    start: Instant,
+}
+
 #[derive(Debug, Clone)]
 pub struct JumpList {
     jumps: VecDeque<Jump>,
@@ -66,6 +94,13 @@ pub fn remove(&mut self, doc_id: &DocumentId) {
         self.jumps.retain(|(other_id, _)| other_id != doc_id);
     }
 
+    /// Removes the jump at `index`, keeping the position in the list on the same jump.
+    pub fn remove_at(&mut self, index: usize) {
+        if self.jumps.remove(index).is_some() && index < self.current {
+            self.current -= 1;
+        }
+    }
+
     pub fn iter(&self) -> impl Iterator<Item = &Jump> {
         self.jumps.iter()
     }
@@ -87,6 +122,58 @@ fn apply(&mut self, transaction: &Transaction, doc: &Document) {
     }
 }
 
+/// Settings of a single view overriding the ones of the editor configuration, which
+/// apply where they are `None`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ViewOptions {
+    pub soft_wrap: Option<bool>,
+    pub line_number: Option<LineNumber>,
+    pub gutters: Option<Vec<GutterType>>,
+    pub whitespace: Option<WhitespaceRender>,
+}
+
+impl ViewOptions {
+    pub const KEYS: &'static [&'static str] =
+        &["soft-wrap", "line-number", "gutters", "whitespace"];
+
+    /// Overrides the option `key` with `value`, or follows the configuration again
+    /// without a value. Gutters are given as a comma separated list, or `none`.
+    pub fn set(&mut self, key: &str, value: Option<&str>) -> anyhow::Result<()> {
+        let value = match value {
+            Some(value) => value,
+            None => {
+                match key {
+                    "soft-wrap" => self.soft_wrap = None,
+                    "line-number" => self.line_number = None,
+                    "gutters" => self.gutters = None,
+                    "whitespace" => self.whitespace = None,
+                    _ => anyhow::bail!("Unknown view option `{}`", key),
+                }
+                return Ok(());
+            }
+        };
+        let field_error = || anyhow::anyhow!("Could not parse `{}` for `{}`", value, key);
+        match key {
+            "soft-wrap" => self.soft_wrap = Some(value.parse().map_err(|_| field_error())?),
+            "line-number" => self.line_number = Some(value.parse()?),
+            "gutters" if value == "none" => self.gutters = Some(Vec::new()),
+            "gutters" => {
+                let gutters = value
+                    .split(',')
+                    .map(|gutter| gutter.trim().parse())
+                    .collect::<anyhow::Result<_>>()?;
+                self.gutters = Some(gutters);
+            }
+            "whitespace" => {
+                let value = serde_json::Value::String(value.to_string());
+                self.whitespace = Some(serde_json::from_value(value).map_err(|_| field_error())?);
+            }
+            _ => anyhow::bail!("Unknown view option `{}`", key),
+        }
+        Ok(())
+    }
+}
+
 #[derive(Clone)]
 pub struct View {
     pub id: ViewId,
@@ -105,6 +192,32 @@ pub struct View {
     pub object_selections: Vec<Selection>,
     /// GutterTypes used to fetch Gutter (constructor) and width for rendering
     gutters: Vec<GutterType>,
+    /// GutterTypes used instead of `gutters` while the view isn't focused, `None` to keep them.
+    pub unfocused_gutters: Option<Vec<GutterType>>,
+    /// Whether this is the focused view, kept in sync by the editor.
+    pub focused: bool,
+    /// Width of the wrap indicator if long lines are soft wrapped, `None` otherwise.
+    /// While soft wrapping, `offset.col` is always 0 and `offset.row` is the first
+    /// visible document line.
+    pub soft_wrap: Option<usize>,
+    /// Width of the minimap drawn along the right edge of the view, 0 if it is hidden.
+    pub minimap_width: u16,
+    /// Width of the scrollbar drawn along the right edge of the view, 0 if it is hidden.
+    pub scrollbar_width: u16,
+    /// Height of the breadcrumbs bar drawn above the text, 0 if it is hidden.
+    pub winbar_height: u16,
+    /// Minimum number of columns to keep between the cursor and the left and right edges.
+    pub horizontal_scrolloff: usize,
+    /// Minimum number of columns to scroll horizontally at once.
+    pub horizontal_scroll_chunk: usize,
+    /// Width of the centered text column in zen mode, `None` outside of zen mode.
+    /// The gutters and the statusline are hidden in zen mode.
+    pub zen_width: Option<u16>,
+    /// Settings of this view overriding the editor configuration.
+    pub options: ViewOptions,
+    scroll_animation: Option<ScrollAnimation>,
+    /// The first line shown by the last rendered frame.
+    rendered_row: usize,
     /// A mapping between documents and the last history revision the view was updated at.
     /// Changes between documents and views are synced lazily when switching windows. This
     /// mapping keeps track of the last applied history revision so that only new changes
@@ -134,6 +247,18 @@ pub fn new(doc: DocumentId, gutter_types: Vec<crate::editor::GutterType>) -> Sel
             last_modified_docs: [None, None],
             object_selections: Vec::new(),
             gutters: gutter_types,
+            unfocused_gutters: None,
+            focused: true,
+            soft_wrap: None,
+            minimap_width: 0,
+            scrollbar_width: 0,
+            winbar_height: 0,
+            horizontal_scrolloff: 5,
+            horizontal_scroll_chunk: 1,
+            zen_width: None,
+            options: ViewOptions::default(),
+            scroll_animation: None,
+            rendered_row: 0,
             doc_revisions: HashMap::new(),
         }
     }
@@ -146,19 +271,197 @@ pub fn add_to_history(&mut self, id: DocumentId) {
     }
 
     pub fn inner_area(&self, doc: &Document) -> Rect {
-        self.area.clip_left(self.gutter_offset(doc)).clip_bottom(1) // -1 for statusline
+        self.text_rows_area()
+            .clip_left(self.gutter_offset(doc))
+            .clip_right(self.minimap_width + self.scrollbar_width)
+    }
+
+    /// The rows of the view between the breadcrumbs bar and the statusline, this is
+    /// the text area including the gutters and the minimap.
+    pub fn text_rows_area(&self) -> Rect {
+        let area = self.area.clip_top(self.winbar_height);
+        match self.zen_width {
+            Some(width) if width < area.width => {
+                let padding = (area.width - width) / 2;
+                Rect::new(area.x + padding, area.y, width, area.height)
+            }
+            Some(_) => area,
+            None => area.clip_bottom(1), // -1 for statusline
+        }
+    }
+
+    /// The area of the breadcrumbs bar, `None` if it is hidden.
+    pub fn winbar_area(&self) -> Option<Rect> {
+        if self.winbar_height == 0 {
+            return None;
+        }
+        let height = self.winbar_height.min(self.area.height);
+        Some(self.area.with_height(height))
+    }
+
+    /// The area of the minimap, `None` if the minimap is hidden. It is drawn left of the
+    /// scrollbar.
+    pub fn minimap_area(&self) -> Option<Rect> {
+        if self.minimap_width == 0 {
+            return None;
+        }
+        let area = self.text_rows_area().clip_right(self.scrollbar_width);
+        let width = self.minimap_width.min(area.width);
+        Some(Rect::new(area.right() - width, area.y, width, area.height))
+    }
+
+    /// The first document line shown in the minimap. The minimap scrolls along with
+    /// the view so that the visible part of the document stays centered in it.
+    pub fn minimap_first_line(&self, doc: &Document) -> usize {
+        let area = match self.minimap_area() {
+            Some(area) => area,
+            None => return 0,
+        };
+        let capacity = area.height as usize * MINIMAP_LINES_PER_ROW;
+        let total = doc.text().len_lines();
+        if total <= capacity {
+            return 0;
+        }
+        let margin = capacity.saturating_sub(self.inner_height()) / 2;
+        self.offset.row.saturating_sub(margin).min(total - capacity)
+    }
+
+    /// Translates screen coordinates into the document line shown at that position of
+    /// the minimap. Returns None if the coordinates are not on the minimap.
+    pub fn minimap_line_at_screen_coords(
+        &self,
+        doc: &Document,
+        row: u16,
+        column: u16,
+    ) -> Option<usize> {
+        let area = self.minimap_area()?;
+        if row < area.top()
+            || row >= area.bottom()
+            || column < area.left()
+            || column >= area.right()
+        {
+            return None;
+        }
+        let line =
+            self.minimap_first_line(doc) + (row - area.top()) as usize * MINIMAP_LINES_PER_ROW;
+        Some(line.min(doc.text().len_lines().saturating_sub(1)))
+    }
+
+    /// The area of the scrollbar, `None` if the scrollbar is hidden.
+    pub fn scrollbar_area(&self) -> Option<Rect> {
+        if self.scrollbar_width == 0 {
+            return None;
+        }
+        let area = self.text_rows_area();
+        let width = self.scrollbar_width.min(area.width);
+        Some(Rect::new(area.right() - width, area.y, width, area.height))
+    }
+
+    /// The first row and the number of rows of the scrollbar thumb, which stands for
+    /// the lines visible in the view.
+    pub fn scrollbar_thumb(&self, doc: &Document) -> (u16, u16) {
+        let height = match self.scrollbar_area() {
+            Some(area) => area.height as usize,
+            None => return (0, 0),
+        };
+        let total = doc.text().len_lines();
+        let visible = self.inner_height();
+        if total <= visible || height == 0 {
+            return (0, height as u16);
+        }
+        let len = ((height * visible + total - 1) / total).clamp(1, height);
+        let max_offset = total - visible;
+        let start = (height - len) * self.offset.row.min(max_offset) / max_offset;
+        (start as u16, len as u16)
+    }
+
+    /// The document line at a row of the scrollbar, rows outside of it are clamped.
+    pub fn scrollbar_line_at_row(&self, doc: &Document, row: u16) -> usize {
+        let area = match self.scrollbar_area() {
+            Some(area) if area.height > 0 => area,
+            _ => return 0,
+        };
+        let row = row.saturating_sub(area.top()).min(area.height - 1) as usize;
+        let total = doc.text().len_lines();
+        (row * total / area.height as usize).min(total.saturating_sub(1))
+    }
+
+    /// Whether the screen coordinates are on the scrollbar.
+    pub fn is_on_scrollbar(&self, row: u16, column: u16) -> bool {
+        self.scrollbar_area().map_or(false, |area| {
+            (area.top()..area.bottom()).contains(&row)
+                && (area.left()..area.right()).contains(&column)
+        })
+    }
+
+    /// Returns the first line to render in the current frame. With smooth scrolling
+    /// (`duration` is `Some`) jumps of `offset.row` are animated: the returned line
+    /// moves from the line shown in the last frame to `offset.row` within `duration`.
+    pub fn animated_scroll_row(&mut self, duration: Option<Duration>, now: Instant) -> usize {
+        let target = self.offset.row;
+        let duration = match duration {
+            Some(duration) if !duration.is_zero() => duration,
+            _ => {
+                self.scroll_animation = None;
+                self.rendered_row = target;
+                return target;
+            }
+        };
+
+        if self.scroll_animation.map_or(true, |anim| anim.to != target) {
+            // the viewport jumped since the last frame, continue from the currently shown line
+            self.scroll_animation = (self.rendered_row.abs_diff(target) > SMOOTH_SCROLL_MIN_LINES)
+                .then(|| ScrollAnimation {
+                    from: self.rendered_row,
+                    to: target,
+                    start: now,
+                });
+        }
+
+        let row = match self.scroll_animation {
+            Some(anim) => {
+                let t = now.saturating_duration_since(anim.start).as_secs_f64()
+                    / duration.as_secs_f64();
+                if t >= 1.0 {
+                    self.scroll_animation = None;
+                    target
+                } else {
+                    // ease out: move fast at first and slow down towards the target
+                    let progress = 1.0 - (1.0 - t) * (1.0 - t);
+                    let distance = anim.to as f64 - anim.from as f64;
+                    (anim.from as f64 + distance * progress).round() as usize
+                }
+            }
+            None => target,
+        };
+        self.rendered_row = row;
+        row
+    }
+
+    /// Whether a smooth scroll is still running and more frames need to be rendered.
+    pub fn is_scroll_animating(&self) -> bool {
+        self.scroll_animation.is_some()
     }
 
     pub fn inner_height(&self) -> usize {
-        self.area.clip_bottom(1).height.into() // -1 for statusline
+        self.text_rows_area().height.into()
     }
 
     pub fn gutters(&self) -> &[GutterType] {
-        &self.gutters
+        if self.zen_width.is_some() {
+            return &[];
+        }
+        if let Some(gutters) = &self.options.gutters {
+            return gutters;
+        }
+        match &self.unfocused_gutters {
+            Some(gutters) if !self.focused => gutters,
+            _ => &self.gutters,
+        }
     }
 
     pub fn gutter_offset(&self, doc: &Document) -> u16 {
-        self.gutters
+        self.gutters()
             .iter()
             .map(|gutter| gutter.width(self, doc) as u16)
             .sum()
@@ -191,11 +494,17 @@ pub fn offset_coords_to_in_view_center(
         let last_line = (self.offset.row + inner_area.height as usize).saturating_sub(1);
         let last_col = self.offset.col + inner_area.width.saturating_sub(1) as usize;
 
-        let new_offset = |scrolloff: usize| {
+        let new_offset = |scrolloff: usize, horizontal_scrolloff: usize| {
             // - 1 so we have at least one gap in the middle.
             // a height of 6 with padding of 3 on each side will keep shifting the view back and forth
             // as we type
             let scrolloff = scrolloff.min(inner_area.height.saturating_sub(1) as usize / 2);
+            let horizontal_scrolloff =
+                horizontal_scrolloff.min(inner_area.width.saturating_sub(1) as usize / 2);
+
+            if self.soft_wrap.is_some() {
+                return (self.soft_wrap_offset_row(doc, cursor, scrolloff), 0);
+            }
 
             let row = if line > last_line.saturating_sub(scrolloff) {
                 // scroll down
@@ -207,12 +516,22 @@ pub fn offset_coords_to_in_view_center(
                 self.offset.row
             };
 
-            let col = if col > last_col.saturating_sub(scrolloff) {
+            // scroll at least `horizontal_scroll_chunk` columns at once but never so far
+            // that the cursor ends up within the scrolloff of the opposite edge
+            let chunk = self.horizontal_scroll_chunk.max(1);
+            let col = if col > last_col.saturating_sub(horizontal_scrolloff) {
                 // scroll right
-                self.offset.col + col - (last_col.saturating_sub(scrolloff))
-            } else if col < self.offset.col + scrolloff {
+                let needed = col - (last_col.saturating_sub(horizontal_scrolloff));
+                (self.offset.col + needed.max(chunk)).min(col.saturating_sub(horizontal_scrolloff))
+            } else if col < self.offset.col + horizontal_scrolloff {
                 // scroll left
-                col.saturating_sub(scrolloff)
+                let needed = self.offset.col + horizontal_scrolloff - col;
+                let min_col =
+                    (col + horizontal_scrolloff + 1).saturating_sub(inner_area.width as usize);
+                self.offset
+                    .col
+                    .saturating_sub(needed.max(chunk))
+                    .max(min_col)
             } else {
                 self.offset.col
             };
@@ -221,17 +540,17 @@ pub fn offset_coords_to_in_view_center(
         let current_offset = (self.offset.row, self.offset.col);
         if centering {
             // return None if cursor is out of view
-            let offset = new_offset(0);
+            let offset = new_offset(0, 0);
             (offset == current_offset).then(|| {
-                if scrolloff == 0 {
+                if scrolloff == 0 && self.horizontal_scrolloff == 0 {
                     offset
                 } else {
-                    new_offset(scrolloff)
+                    new_offset(scrolloff, self.horizontal_scrolloff)
                 }
             })
         } else {
             // return None if cursor is in (view - scrolloff)
-            let offset = new_offset(scrolloff);
+            let offset = new_offset(scrolloff, self.horizontal_scrolloff);
             (offset != current_offset).then(|| offset) // TODO: use 'then_some' when 1.62 <= MSRV
         }
     }
@@ -256,14 +575,120 @@ pub fn is_cursor_in_view(&mut self, doc: &Document, scrolloff: usize) -> bool {
         self.offset_coords_to_in_view(doc, scrolloff).is_none()
     }
 
+    /// Calculates the first line to display so that the character at `pos` ends
+    /// up at most `rows` rows below the top of the view.
+    pub fn offset_row_with_pos_at(&self, doc: &Document, pos: usize, rows: usize) -> usize {
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(pos);
+        let indent = match self.soft_wrap {
+            Some(indent) => indent,
+            None => return line.saturating_sub(rows),
+        };
+
+        let width = self.inner_area(doc).width as usize;
+        let mut rows_above = soft_wrap_coords_at_pos(text, pos, doc.tab_width(), width, indent).row;
+        let mut first_line = line;
+        while first_line > 0 {
+            let height = self.line_height(doc, first_line - 1);
+            if rows_above + height > rows {
+                break;
+            }
+            rows_above += height;
+            first_line -= 1;
+        }
+        first_line
+    }
+
+    /// Calculates the new `offset.row` needed to keep the cursor at `cursor`
+    /// `scrolloff` rows away from the edges of a soft wrapped view.
+    fn soft_wrap_offset_row(&self, doc: &Document, cursor: usize, scrolloff: usize) -> usize {
+        let text = doc.text().slice(..);
+        let line = text.char_to_line(cursor);
+        let last_row = self.inner_height().saturating_sub(1 + scrolloff);
+        if line < self.offset.row {
+            return self.offset_row_with_pos_at(doc, cursor, scrolloff);
+        }
+
+        let width = self.inner_area(doc).width as usize;
+        let indent = self.soft_wrap.unwrap_or_default();
+        // Only count the rows above the cursor as far as the bottom of the view.
+        let mut row = soft_wrap_coords_at_pos(text, cursor, doc.tab_width(), width, indent).row;
+        for line in self.offset.row..line {
+            if row > last_row {
+                break;
+            }
+            row += self.line_height(doc, line);
+        }
+
+        if row < scrolloff {
+            self.offset_row_with_pos_at(doc, cursor, scrolloff)
+        } else if row > last_row {
+            self.offset_row_with_pos_at(doc, cursor, last_row)
+        } else {
+            self.offset.row
+        }
+    }
+
+    /// Returns the number of rows `line` takes up on screen.
+    pub fn line_height(&self, doc: &Document, line: usize) -> usize {
+        match self.soft_wrap {
+            Some(indent) => soft_wrap_line_height(
+                doc.text().line(line),
+                doc.tab_width(),
+                self.inner_area(doc).width as usize,
+                indent,
+            ),
+            None => 1,
+        }
+    }
+
+    /// Returns the lines visible on screen, along with the row each of them starts at
+    /// (relative to the top of the view) and the number of visible rows it takes up.
+    pub fn visible_lines<'a>(
+        &'a self,
+        doc: &'a Document,
+    ) -> impl Iterator<Item = (usize, usize, usize)> + 'a {
+        let height = self.inner_height();
+        (self.offset.row..=self.last_line(doc)).scan(0, move |row, line| {
+            let start = *row;
+            let rows = self
+                .line_height(doc, line)
+                .min(height.saturating_sub(start));
+            *row += rows;
+            Some((line, start, rows))
+        })
+    }
+
+    /// Returns the line drawn on `row` (relative to the top of the view), if any.
+    pub fn line_at_row(&self, doc: &Document, row: usize) -> Option<usize> {
+        self.visible_lines(doc)
+            .find(|&(_, start, rows)| row < start + rows)
+            .map(|(line, _, _)| line)
+    }
+
     /// Calculates the last visible line on screen
     #[inline]
     pub fn last_line(&self, doc: &Document) -> usize {
-        std::cmp::min(
-            // Saturating subs to make it inclusive zero indexing.
-            (self.offset.row + self.inner_height()).saturating_sub(1),
-            doc.text().len_lines().saturating_sub(1),
-        )
+        let last_doc_line = doc.text().len_lines().saturating_sub(1);
+        if self.soft_wrap.is_none() {
+            return std::cmp::min(
+                // Saturating subs to make it inclusive zero indexing.
+                (self.offset.row + self.inner_height()).saturating_sub(1),
+                last_doc_line,
+            );
+        }
+
+        let height = self.inner_height();
+        let mut rows = 0;
+        let mut line = self.offset.row;
+        while line < last_doc_line {
+            rows += self.line_height(doc, line);
+            if rows >= height {
+                break;
+            }
+            line += 1;
+        }
+        line.min(last_doc_line)
     }
 
     /// Translates a document position to an absolute position in the terminal.
@@ -283,8 +708,21 @@ pub fn screen_coords_at_pos(
         }
 
         let tab_width = doc.tab_width();
+
+        if let Some(indent) = self.soft_wrap {
+            let width = self.inner_area(doc).width as usize;
+            let rows_above: usize = (self.offset.row..line)
+                .map(|line| self.line_height(doc, line))
+                .sum();
+            let Position { row, col } =
+                soft_wrap_coords_at_pos(text, pos, tab_width, width, indent);
+            let row = rows_above + row;
+            return (row < self.inner_height()).then(|| Position::new(row, col));
+        }
+
         // TODO: visual_coords_at_pos also does char_to_line which we ignore, can we reuse the call?
         let Position { col, .. } = visual_coords_at_pos(text, pos, tab_width);
+        let col = col + self.inlay_hints_width(doc, line, pos);
 
         // It is possible for underflow to occur if the buffer length is larger than the terminal width.
         let row = line.saturating_sub(self.offset.row);
@@ -311,12 +749,53 @@ pub fn text_pos_at_screen_coords(
             return None;
         }
 
+        if let Some(indent) = self.soft_wrap {
+            let mut row = (row - inner.y) as usize;
+            let mut line = self.offset.row;
+            loop {
+                if line > text.len_lines() - 1 {
+                    return Some(text.len_chars());
+                }
+                let height = self.line_height(doc, line);
+                if row < height {
+                    break;
+                }
+                row -= height;
+                line += 1;
+            }
+
+            return Some(soft_wrap_pos_at_coords(
+                text,
+                line,
+                Position::new(row, (column - inner.x) as usize),
+                tab_width,
+                inner.width as usize,
+                indent,
+            ));
+        }
+
         let text_row = (row - inner.y) as usize + self.offset.row;
         if text_row > text.len_lines() - 1 {
             return Some(text.len_chars());
         }
 
-        let text_col = (column - inner.x) as usize + self.offset.col;
+        let mut text_col = (column - inner.x) as usize + self.offset.col;
+
+        // skip over the inlay hints drawn in front of the column
+        let mut hints_width = 0;
+        for hint in doc.inlay_hints_on_line(text_row) {
+            let start = visual_coords_at_pos(text, hint.char_idx, tab_width).col + hints_width;
+            if text_col < start {
+                break;
+            }
+            let width = hint.label.width();
+            if text_col < start + width {
+                // clicking a hint moves to the char following it
+                return Some(hint.char_idx.min(text.len_chars()));
+            }
+            hints_width += width;
+        }
+        text_col -= hints_width;
 
         Some(pos_at_visual_coords(
             text,
@@ -328,6 +807,16 @@ pub fn text_pos_at_screen_coords(
         ))
     }
 
+    /// Width of the inlay hints drawn in front of `pos` on `line`. Inlay hints are
+    /// not drawn while soft wrapping.
+    fn inlay_hints_width(&self, doc: &Document, line: usize, pos: usize) -> usize {
+        doc.inlay_hints_on_line(line)
+            .iter()
+            .take_while(|hint| hint.char_idx <= pos)
+            .map(|hint| hint.label.width())
+            .sum()
+    }
+
     /// Translates a screen position to position in the text document.
     /// Returns a usize typed position in bounds of the text if found in this view, None if out of view.
     pub fn pos_at_screen_coords(&self, doc: &Document, row: u16, column: u16) -> Option<usize> {
@@ -338,18 +827,18 @@ pub fn pos_at_screen_coords(&self, doc: &Document, row: u16, column: u16) -> Opt
     /// Returns a tuple of usize typed line and column numbers starting with 0.
     /// Returns None if coordinates are not on the gutter.
     pub fn gutter_coords_at_screen_coords(&self, row: u16, column: u16) -> Option<Position> {
-        // 1 for status
-        if row < self.area.top() || row >= self.area.bottom() {
+        let area = self.text_rows_area();
+        if row < area.top() || row >= area.bottom() {
             return None;
         }
 
-        if column < self.area.left() || column > self.area.right() {
+        if column < area.left() || column > area.right() {
             return None;
         }
 
         Some(Position::new(
-            (row - self.area.top()) as usize,
-            (column - self.area.left()) as usize,
+            (row - area.top()) as usize,
+            (column - area.left()) as usize,
         ))
     }
 
@@ -492,6 +981,43 @@ fn test_text_pos_at_screen_coords_without_any_gutters() {
         assert_eq!(view.text_pos_at_screen_coords(&doc, 41, 40 + 1, 4), Some(4));
     }
 
+    #[test]
+    fn test_text_pos_at_screen_coords_soft_wrap() {
+        let mut view = View::new(DocumentId::default(), vec![]);
+        view.area = Rect::new(40, 40, 10, 10);
+        view.soft_wrap = Some(2);
+        let rope = Rope::from_str("abcdefghijklmno\nxyz");
+        let doc = Document::from(rope, None);
+        let text = doc.text().slice(..);
+
+        assert_eq!(view.line_height(&doc, 0), 2);
+        assert_eq!(view.last_line(&doc), 1);
+
+        // continuation row, on and after the wrap indicator
+        assert_eq!(view.text_pos_at_screen_coords(&doc, 41, 40, 4), Some(10));
+        assert_eq!(
+            view.text_pos_at_screen_coords(&doc, 41, 40 + 2, 4),
+            Some(10)
+        );
+        assert_eq!(
+            view.text_pos_at_screen_coords(&doc, 41, 40 + 4, 4),
+            Some(12)
+        );
+        assert_eq!(
+            view.text_pos_at_screen_coords(&doc, 42, 40 + 1, 4),
+            Some(17)
+        );
+
+        assert_eq!(
+            view.screen_coords_at_pos(&doc, text, 12),
+            Some(Position::new(1, 4))
+        );
+        assert_eq!(
+            view.screen_coords_at_pos(&doc, text, 17),
+            Some(Position::new(2, 1))
+        );
+    }
+
     #[test]
     fn test_text_pos_at_screen_coords_cjk() {
         let mut view = View::new(
@@ -567,4 +1093,147 @@ fn test_text_pos_at_screen_coords_graphemes() {
             Some(7)
         );
     }
+
+    #[test]
+    fn test_animated_scroll_row() {
+        let mut view = View::new(DocumentId::default(), vec![]);
+        let start = Instant::now();
+        let ms = Duration::from_millis;
+        let duration = Some(ms(100));
+
+        assert_eq!(view.animated_scroll_row(duration, start), 0);
+        view.offset.row = 100;
+        assert_eq!(view.animated_scroll_row(duration, start), 0);
+        assert_eq!(view.animated_scroll_row(duration, start + ms(50)), 75);
+        assert!(view.is_scroll_animating());
+        assert_eq!(view.animated_scroll_row(duration, start + ms(100)), 100);
+        assert!(!view.is_scroll_animating());
+
+        // single line scrolls are not animated
+        view.offset.row = 101;
+        assert_eq!(view.animated_scroll_row(duration, start + ms(150)), 101);
+
+        // nothing is animated without a duration
+        view.offset.row = 0;
+        assert_eq!(view.animated_scroll_row(None, start + ms(200)), 0);
+    }
+
+    #[test]
+    fn test_horizontal_scroll_chunk() {
+        let mut view = View::new(DocumentId::default(), vec![]);
+        view.area = Rect::new(0, 0, 20, 10);
+        view.horizontal_scrolloff = 2;
+        view.horizontal_scroll_chunk = 10;
+        let rope = Rope::from_str(&"a".repeat(100));
+        let mut doc = Document::from(rope, None);
+
+        let mut scroll_to = |view: &mut View, col: usize| {
+            doc.set_selection(view.id, Selection::point(col));
+            view.ensure_cursor_in_view(&doc, 0);
+            view.offset.col
+        };
+        assert_eq!(scroll_to(&mut view, 30), 13);
+        // scrolls by the chunk although a single column would be enough
+        assert_eq!(scroll_to(&mut view, 32), 23);
+        assert_eq!(scroll_to(&mut view, 24), 13);
+        // but never moves the cursor out of view
+        assert_eq!(scroll_to(&mut view, 0), 0);
+    }
+
+    #[test]
+    fn test_zen_mode_inner_area() {
+        let mut view = View::new(
+            DocumentId::default(),
+            vec![GutterType::Diagnostics, GutterType::LineNumbers],
+        );
+        view.area = Rect::new(10, 0, 120, 40);
+        let doc = Document::from(Rope::from_str("abc"), None);
+        assert_eq!(
+            view.inner_area(&doc),
+            Rect::new(10 + OFFSET, 0, 120 - OFFSET, 39)
+        );
+
+        // the statusline and the gutters are hidden and the text is centered
+        view.zen_width = Some(100);
+        assert_eq!(view.inner_area(&doc), Rect::new(20, 0, 100, 40));
+        // the column takes the full width of narrower views
+        view.zen_width = Some(200);
+        assert_eq!(view.inner_area(&doc), Rect::new(10, 0, 120, 40));
+    }
+
+    #[test]
+    fn test_scrollbar() {
+        let mut view = View::new(DocumentId::default(), vec![]);
+        view.area = Rect::new(0, 0, 80, 11);
+        view.scrollbar_width = 1;
+        let doc = Document::from(Rope::from_str(&"a\n".repeat(99)), None);
+        assert_eq!(view.scrollbar_area(), Some(Rect::new(79, 0, 1, 10)));
+        assert_eq!(view.inner_area(&doc).width, 79);
+
+        // 10 of the 100 lines are visible
+        assert_eq!(view.scrollbar_thumb(&doc), (0, 1));
+        view.offset.row = 45;
+        assert_eq!(view.scrollbar_thumb(&doc), (4, 1));
+        view.offset.row = 90;
+        assert_eq!(view.scrollbar_thumb(&doc), (9, 1));
+
+        assert_eq!(view.scrollbar_line_at_row(&doc, 5), 50);
+        assert_eq!(view.scrollbar_line_at_row(&doc, 20), 90);
+        assert!(view.is_on_scrollbar(3, 79));
+        assert!(!view.is_on_scrollbar(3, 78));
+    }
+
+    #[test]
+    fn test_view_options() {
+        let mut view = View::new(
+            DocumentId::default(),
+            vec![GutterType::Diagnostics, GutterType::LineNumbers],
+        );
+        view.options.set("gutters", Some("none")).unwrap();
+        assert!(view.gutters().is_empty());
+        view.options.set("gutters", Some("diff, spacer")).unwrap();
+        assert_eq!(view.gutters(), [GutterType::Diff, GutterType::Spacer]);
+        view.options.set("gutters", None).unwrap();
+        assert_eq!(view.gutters().len(), 2);
+
+        // the gutters set for the view are kept while it isn't focused
+        view.unfocused_gutters = Some(vec![GutterType::LineNumbers]);
+        view.focused = false;
+        assert_eq!(view.gutters(), [GutterType::LineNumbers]);
+        view.options.set("gutters", Some("diff")).unwrap();
+        assert_eq!(view.gutters(), [GutterType::Diff]);
+        view.options.set("gutters", None).unwrap();
+        view.focused = true;
+        assert_eq!(view.gutters().len(), 2);
+
+        view.options.set("soft-wrap", Some("true")).unwrap();
+        assert_eq!(view.options.soft_wrap, Some(true));
+        view.options.set("line-number", Some("relative")).unwrap();
+        assert_eq!(view.options.line_number, Some(LineNumber::Relative));
+        view.options.set("whitespace", Some("all")).unwrap();
+        assert!(view.options.whitespace.is_some());
+        assert!(view.options.set("soft-wrap", Some("maybe")).is_err());
+        assert!(view.options.set("tab-width", Some("4")).is_err());
+    }
+
+    #[test]
+    fn test_jumplist_remove_at() {
+        let view_id = ViewId::default();
+        let mut doc = Document::from(Rope::from_str("abcd"), None);
+        doc.set_selection(view_id, Selection::point(3));
+        let doc_id = doc.id();
+        let jump = |pos| (doc_id, Selection::point(pos));
+        let mut jumps = JumpList::new(jump(0));
+        jumps.push(jump(1));
+        jumps.push(jump(2));
+        jumps.push(jump(3));
+        assert_eq!(jumps.backward(view_id, &mut doc, 2), Some(&jump(2)));
+
+        // removing a jump before the current one or after it doesn't move off it
+        jumps.remove_at(0);
+        jumps.remove_at(2);
+        assert_eq!(jumps.iter().count(), 2);
+        assert_eq!(jumps.backward(view_id, &mut doc, 1), Some(&jump(1)));
+        assert_eq!(jumps.forward(1), Some(&jump(2)));
+    }
 }